@@ -1,4 +1,4 @@
-use super::{Box, Lit, SubDenomination, Type};
+use super::{Box, Lit, NodeId, SubDenomination, Type};
 use either::Either;
 use solar_interface::{Ident, Span};
 use std::fmt;
@@ -11,6 +11,7 @@ pub type NamedArgList<'ast> = Box<'ast, [NamedArg<'ast>]>;
 /// Reference: <https://docs.soliditylang.org/en/latest/grammar.html#a4.SolidityParser.expression>
 #[derive(Debug)]
 pub struct Expr<'ast> {
+    pub id: NodeId,
     pub span: Span,
     pub kind: ExprKind<'ast>,
 }
@@ -22,14 +23,14 @@ impl AsRef<Self> for Expr<'_> {
 }
 
 impl<'ast> Expr<'ast> {
-    /// Creates a new expression from an identifier.
+    /// Creates a new expression from an identifier, with a [dummy](NodeId::DUMMY) ID.
     pub fn from_ident(ident: Ident) -> Self {
-        Self { span: ident.span, kind: ExprKind::Ident(ident) }
+        Self { id: NodeId::DUMMY, span: ident.span, kind: ExprKind::Ident(ident) }
     }
 
-    /// Creates a new expression from a type.
+    /// Creates a new expression from a type, with a [dummy](NodeId::DUMMY) ID.
     pub fn from_ty(ty: Type<'ast>) -> Self {
-        Self { span: ty.span, kind: ExprKind::Type(ty) }
+        Self { id: NodeId::DUMMY, span: ty.span, kind: ExprKind::Type(ty) }
     }
 }
 
@@ -1,9 +1,10 @@
 use super::{
-    AstPath, BinOpKind, Block, Box, CallArgs, DocComments, Expr, SemverReq, StrLit, Type, UnOpKind,
+    AstPath, BinOpKind, Block, Box, CallArgs, DocComments, Expr, NodeId, SemverReq, StrLit, Type,
+    UnOpKind,
 };
 use crate::token::Token;
 use either::Either;
-use solar_interface::{Ident, Span};
+use solar_interface::{Ident, Span, Symbol};
 use std::fmt;
 use strum::EnumIs;
 
@@ -13,6 +14,7 @@ pub type ParameterList<'ast> = Box<'ast, [VariableDefinition<'ast>]>;
 /// A top-level item in a Solidity source file.
 #[derive(Debug)]
 pub struct Item<'ast> {
+    pub id: NodeId,
     pub docs: DocComments<'ast>,
     pub span: Span,
     /// The item's kind.
@@ -215,6 +217,14 @@ impl IdentOrStrLit {
         }
     }
 
+    /// Returns the symbol of the identifier or the value of the literal.
+    pub fn symbol(&self) -> Symbol {
+        match self {
+            Self::Ident(ident) => ident.name,
+            Self::StrLit(str_lit) => str_lit.value,
+        }
+    }
+
     /// Returns the span of the identifier or literal.
     pub fn span(&self) -> Span {
         match self {
@@ -577,6 +587,7 @@ impl Visibility {
 /// Reference: <https://docs.soliditylang.org/en/latest/grammar.html#a4.SolidityParser.stateVariableDeclaration>
 #[derive(Debug)]
 pub struct VariableDefinition<'ast> {
+    pub id: NodeId,
     pub span: Span,
     pub ty: Type<'ast>,
     pub visibility: Option<Visibility>,
@@ -52,6 +52,23 @@ impl LitKind {
             Self::Err(_) => "<error>",
         }
     }
+
+    /// Multiplies a number literal by the value of the given sub-denomination.
+    ///
+    /// Rational numbers that become integers are converted to [`Number`](Self::Number). Other
+    /// kinds of literals are left unchanged.
+    pub fn apply_sub_denomination(&mut self, sub_denomination: SubDenomination) {
+        let value = num_bigint::BigInt::from(sub_denomination.value());
+        match self {
+            Self::Number(n) => *n *= value,
+            Self::Rational(r) => {
+                let r = &*r * num_rational::BigRational::from_integer(value);
+                *self =
+                    if r.is_integer() { Self::Number(r.to_integer()) } else { Self::Rational(r) };
+            }
+            _ => {}
+        }
+    }
 }
 
 /// A single UTF-8 string literal. Only used in import paths and statements, not expressions.
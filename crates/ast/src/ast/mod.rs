@@ -15,6 +15,12 @@ pub use item::*;
 mod lit;
 pub use lit::*;
 
+mod natspec;
+pub use natspec::*;
+
+mod parent_map;
+pub use parent_map::*;
+
 mod path;
 pub use path::*;
 
@@ -109,6 +115,22 @@ newtype_index! {
     pub struct ItemId;
 }
 
+newtype_index! {
+    /// An AST node ID.
+    ///
+    /// The parser assigns a unique ID to each [item](Item), [statement](Stmt),
+    /// [expression](Expr), [type](Type) and [variable definition](VariableDefinition) of a source
+    /// unit, in the order in which the nodes are parsed. IDs are not unique across source units.
+    ///
+    /// See [`ParentMap`] to look up nodes and their parents by ID.
+    pub struct NodeId;
+}
+
+impl NodeId {
+    /// The ID of nodes that were not created by the parser.
+    pub const DUMMY: Self = Self::MAX;
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
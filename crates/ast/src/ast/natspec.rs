@@ -0,0 +1,177 @@
+use super::{CommentKind, DocComment, Span, Symbol};
+
+/// A single NatSpec tag parsed from doc-comments, such as `@notice foo`.
+///
+/// Reference: <https://docs.soliditylang.org/en/latest/natspec-format.html>
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct NatSpecItem {
+    /// The tag kind.
+    pub kind: NatSpecKind,
+    /// The span of the doc-comment containing the tag.
+    pub span: Span,
+    /// The tag's contents, excluding the tag itself and, for `@param`, the parameter name.
+    pub content: Symbol,
+}
+
+/// A NatSpec tag kind.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum NatSpecKind {
+    /// `@title`
+    Title,
+    /// `@author`
+    Author,
+    /// `@notice`, or untagged text at the start of a doc-comment.
+    Notice,
+    /// `@dev`
+    Dev,
+    /// `@param <name>`
+    Param {
+        /// The parameter name.
+        name: Symbol,
+    },
+    /// `@return`
+    Return,
+    /// `@inheritdoc <contract>`
+    Inheritdoc {
+        /// The name of the base contract to inherit the documentation from.
+        contract: Symbol,
+    },
+    /// `@custom:<name>`
+    Custom {
+        /// The custom tag name, excluding the `custom:` prefix.
+        name: Symbol,
+    },
+    /// Any other tag, including the `@`.
+    Unknown(Symbol),
+}
+
+impl NatSpecKind {
+    /// Returns the tag name, excluding the `@` and any arguments.
+    pub fn tag(&self) -> &str {
+        match self {
+            Self::Title => "title",
+            Self::Author => "author",
+            Self::Notice => "notice",
+            Self::Dev => "dev",
+            Self::Param { .. } => "param",
+            Self::Return => "return",
+            Self::Inheritdoc { .. } => "inheritdoc",
+            Self::Custom { .. } => "custom",
+            Self::Unknown(tag) => &tag.as_str()[1..],
+        }
+    }
+}
+
+impl NatSpecItem {
+    /// Parses the NatSpec tags of the given doc-comments.
+    ///
+    /// Untagged text at the start is treated as `@notice`, and lines that don't start with a tag
+    /// are appended to the previous tag.
+    pub fn parse(docs: &[DocComment]) -> Vec<Self> {
+        let mut items = Vec::new();
+        let mut current: Option<(NatSpecKind, Span, String)> = None;
+        let mut finish = |current: &mut Option<(NatSpecKind, Span, String)>| {
+            if let Some((kind, span, content)) = current.take() {
+                let content = Symbol::intern(content.trim_end());
+                items.push(Self { kind, span, content });
+            }
+        };
+        for doc in docs {
+            for line in doc.symbol.as_str().lines() {
+                let mut line = line.trim_start();
+                if doc.kind == CommentKind::Block {
+                    line = line.strip_prefix('*').unwrap_or(line).trim_start();
+                }
+                let line = line.trim_end();
+
+                if let Some(rest) = line.strip_prefix('@') {
+                    finish(&mut current);
+                    let (tag, content) = match rest.split_once(char::is_whitespace) {
+                        Some((tag, content)) => (tag, content.trim_start()),
+                        None => (rest, ""),
+                    };
+                    let (kind, content) = parse_tag(tag, content);
+                    current = Some((kind, doc.span, content.to_string()));
+                } else if let Some((_, _, content)) = &mut current {
+                    content.push('\n');
+                    content.push_str(line);
+                } else if !line.is_empty() {
+                    current = Some((NatSpecKind::Notice, doc.span, line.to_string()));
+                }
+            }
+        }
+        finish(&mut current);
+        items
+    }
+}
+
+fn parse_tag<'a>(tag: &str, content: &'a str) -> (NatSpecKind, &'a str) {
+    let first_word = || match content.split_once(char::is_whitespace) {
+        Some((word, rest)) => (Symbol::intern(word), rest.trim_start()),
+        None => (Symbol::intern(content), ""),
+    };
+    match tag {
+        "title" => (NatSpecKind::Title, content),
+        "author" => (NatSpecKind::Author, content),
+        "notice" => (NatSpecKind::Notice, content),
+        "dev" => (NatSpecKind::Dev, content),
+        "param" => {
+            let (name, content) = first_word();
+            (NatSpecKind::Param { name }, content)
+        }
+        "return" => (NatSpecKind::Return, content),
+        "inheritdoc" => {
+            let (contract, content) = first_word();
+            (NatSpecKind::Inheritdoc { contract }, content)
+        }
+        _ => match tag.strip_prefix("custom:") {
+            Some(name) if !name.is_empty() => {
+                (NatSpecKind::Custom { name: Symbol::intern(name) }, content)
+            }
+            _ => (NatSpecKind::Unknown(Symbol::intern(&format!("@{tag}"))), content),
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(docs: &[(CommentKind, &str)]) -> Vec<(NatSpecKind, String)> {
+        let docs = docs
+            .iter()
+            .map(|&(kind, s)| DocComment { kind, span: Span::DUMMY, symbol: Symbol::intern(s) })
+            .collect::<Vec<_>>();
+        NatSpecItem::parse(&docs).into_iter().map(|i| (i.kind, i.content.to_string())).collect()
+    }
+
+    #[test]
+    fn natspec() {
+        solar_interface::enter(|| {
+            use CommentKind::*;
+            use NatSpecKind::*;
+
+            assert!(parse(&[]).is_empty());
+            assert_eq!(parse(&[(Line, " hello")]), [(Notice, "hello".into())]);
+            assert_eq!(
+                parse(&[(Line, " @notice a"), (Line, " b"), (Line, " @dev c")]),
+                [(Notice, "a\nb".into()), (Dev, "c".into())]
+            );
+            assert_eq!(
+                parse(&[(Block, "\n * @param x the x\n * @return y\n ")]),
+                [(Param { name: Symbol::intern("x") }, "the x".into()), (Return, "y".into())]
+            );
+            assert_eq!(
+                parse(&[(Line, " @inheritdoc Base"), (Line, " @custom:foo bar")]),
+                [
+                    (Inheritdoc { contract: Symbol::intern("Base") }, String::new()),
+                    (Custom { name: Symbol::intern("foo") }, "bar".into())
+                ]
+            );
+            assert_eq!(
+                parse(&[(Line, " @foo bar")]),
+                [(Unknown(Symbol::intern("@foo")), "bar".into())]
+            );
+        });
+    }
+}
@@ -0,0 +1,147 @@
+use super::{Expr, Item, NodeId, SourceUnit, Stmt, Type, VariableDefinition};
+use crate::visit::Visit;
+use solar_data_structures::{map::FxHashMap, Never};
+use solar_interface::Span;
+use std::ops::ControlFlow;
+
+/// A reference to an AST node that has a [`NodeId`].
+#[derive(Clone, Copy, Debug)]
+pub enum Node<'ast> {
+    /// An item.
+    Item(&'ast Item<'ast>),
+    /// A statement.
+    Stmt(&'ast Stmt<'ast>),
+    /// An expression.
+    Expr(&'ast Expr<'ast>),
+    /// A type.
+    Type(&'ast Type<'ast>),
+    /// A variable definition.
+    Var(&'ast VariableDefinition<'ast>),
+}
+
+impl Node<'_> {
+    /// Returns the ID of the node.
+    pub fn id(&self) -> NodeId {
+        match self {
+            Self::Item(item) => item.id,
+            Self::Stmt(stmt) => stmt.id,
+            Self::Expr(expr) => expr.id,
+            Self::Type(ty) => ty.id,
+            Self::Var(var) => var.id,
+        }
+    }
+
+    /// Returns the span of the node.
+    pub fn span(&self) -> Span {
+        match self {
+            Self::Item(item) => item.span,
+            Self::Stmt(stmt) => stmt.span,
+            Self::Expr(expr) => expr.span,
+            Self::Type(ty) => ty.span,
+            Self::Var(var) => var.span,
+        }
+    }
+}
+
+/// Maps the [`NodeId`]s of a source unit to their nodes and parents.
+///
+/// The parent of a node is the closest enclosing node with an ID. For example, the parent of a
+/// function parameter is the function item, and the parent of an expression statement's
+/// expression is the statement. Top-level items have no parent.
+///
+/// Nodes with a [dummy ID](NodeId::DUMMY) are not recorded; their children are recorded with the
+/// closest recorded ancestor as parent.
+#[derive(Debug, Default)]
+pub struct ParentMap<'ast> {
+    nodes: FxHashMap<NodeId, (Node<'ast>, Option<NodeId>)>,
+}
+
+impl<'ast> ParentMap<'ast> {
+    /// Builds the parent map of a source unit.
+    pub fn new(source_unit: &SourceUnit<'ast>) -> Self {
+        let mut collector = Collector { map: Self::default(), parent: None };
+        let _ = collector.visit_source_unit(source_unit);
+        collector.map
+    }
+
+    /// Returns the node with the given ID.
+    pub fn get(&self, id: NodeId) -> Option<Node<'ast>> {
+        self.nodes.get(&id).map(|&(node, _)| node)
+    }
+
+    /// Returns the ID of the parent of the given node.
+    pub fn parent(&self, id: NodeId) -> Option<NodeId> {
+        self.nodes.get(&id).and_then(|&(_, parent)| parent)
+    }
+
+    /// Returns the parent of the given node.
+    pub fn parent_node(&self, id: NodeId) -> Option<Node<'ast>> {
+        self.parent(id).and_then(|parent| self.get(parent))
+    }
+
+    /// Returns an iterator over the IDs of the ancestors of the given node, starting with its
+    /// parent and ending with a top-level item.
+    pub fn ancestors(&self, id: NodeId) -> impl Iterator<Item = NodeId> + '_ {
+        std::iter::successors(self.parent(id), |&id| self.parent(id))
+    }
+
+    /// Returns the number of nodes in the map.
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    /// Returns `true` if the map is empty.
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+}
+
+struct Collector<'ast> {
+    map: ParentMap<'ast>,
+    parent: Option<NodeId>,
+}
+
+impl<'ast> Collector<'ast> {
+    fn record(
+        &mut self,
+        node: Node<'ast>,
+        walk: impl FnOnce(&mut Self) -> ControlFlow<Never>,
+    ) -> ControlFlow<Never> {
+        let id = node.id();
+        if id == NodeId::DUMMY {
+            return walk(self);
+        }
+        self.map.nodes.insert(id, (node, self.parent));
+        let parent = self.parent.replace(id);
+        let r = walk(self);
+        self.parent = parent;
+        r
+    }
+}
+
+impl<'ast> Visit<'ast> for Collector<'ast> {
+    type BreakValue = Never;
+
+    fn visit_item(&mut self, item: &'ast Item<'ast>) -> ControlFlow<Self::BreakValue> {
+        self.record(Node::Item(item), |this| this.walk_item(item))
+    }
+
+    fn visit_stmt(&mut self, stmt: &'ast Stmt<'ast>) -> ControlFlow<Self::BreakValue> {
+        self.record(Node::Stmt(stmt), |this| this.walk_stmt(stmt))
+    }
+
+    fn visit_expr(&mut self, expr: &'ast Expr<'ast>) -> ControlFlow<Self::BreakValue> {
+        self.record(Node::Expr(expr), |this| this.walk_expr(expr))
+    }
+
+    fn visit_ty(&mut self, ty: &'ast Type<'ast>) -> ControlFlow<Self::BreakValue> {
+        self.record(Node::Type(ty), |this| this.walk_ty(ty))
+    }
+
+    fn visit_variable_definition(
+        &mut self,
+        var: &'ast VariableDefinition<'ast>,
+    ) -> ControlFlow<Self::BreakValue> {
+        self.record(Node::Var(var), |this| this.walk_variable_definition(var))
+    }
+}
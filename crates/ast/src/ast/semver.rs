@@ -171,6 +171,40 @@ impl From<SemverVersion> for semver::Version {
 }
 
 impl SemverVersion {
+    /// Creates a new version with all of its numbers specified.
+    #[inline]
+    pub const fn new(major: u32, minor: u32, patch: u32) -> Self {
+        Self {
+            span: Span::DUMMY,
+            major: SemverVersionNumber::Number(major),
+            minor: Some(SemverVersionNumber::Number(minor)),
+            patch: Some(SemverVersionNumber::Number(patch)),
+        }
+    }
+
+    /// Returns the lowest version that is equal to this version, in which missing and wildcard
+    /// numbers match any number.
+    fn lowest(&self) -> Self {
+        let n = |n: Option<SemverVersionNumber>| match n {
+            Some(SemverVersionNumber::Number(n)) => n,
+            Some(SemverVersionNumber::Wildcard) | None => 0,
+        };
+        Self::new(n(Some(self.major)), n(self.minor), n(self.patch))
+    }
+
+    /// Returns the lowest version that is greater than this version.
+    fn next(&self) -> Self {
+        use SemverVersionNumber::Number;
+        match (self.major, self.minor, self.patch) {
+            (Number(major), Some(Number(minor)), Some(Number(patch))) => {
+                Self::new(major, minor, patch.saturating_add(1))
+            }
+            (Number(major), Some(Number(minor)), _) => Self::new(major, minor.saturating_add(1), 0),
+            (Number(major), ..) => Self::new(major.saturating_add(1), 0, 0),
+            (SemverVersionNumber::Wildcard, ..) => Self::new(u32::MAX, 0, 0),
+        }
+    }
+
     /// Creates a new [::semver] version from this version.
     #[inline]
     pub fn into_semver(self) -> semver::Version {
@@ -207,6 +241,17 @@ impl SemverReq<'_> {
         self.dis.iter().any(|c| c.matches(version))
     }
 
+    /// Returns the lowest version that satisfies this requirement, if any.
+    pub fn min_version(&self) -> Option<SemverVersion> {
+        self.min_version_from(&SemverVersion::new(0, 0, 0))
+    }
+
+    /// Returns the lowest version that satisfies this requirement and is greater than or equal to
+    /// `bound`, if any.
+    pub fn min_version_from(&self, bound: &SemverVersion) -> Option<SemverVersion> {
+        self.dis.iter().filter_map(|con| con.min_version_from(bound)).min()
+    }
+
     /// Converts this requirement to a [::semver] version requirement.
     pub fn to_semver(&self) -> SemverVersionReqCompat {
         SemverVersionReqCompat { reqs: self.dis.iter().map(SemverReqCon::to_semver).collect() }
@@ -260,6 +305,21 @@ impl SemverReqCon<'_> {
     pub fn matches(&self, version: &SemverVersion) -> bool {
         self.components.iter().all(|c| c.matches(version))
     }
+
+    /// Returns the lowest version that satisfies all of the components, if any.
+    pub fn min_version(&self) -> Option<SemverVersion> {
+        self.min_version_from(&SemverVersion::new(0, 0, 0))
+    }
+
+    /// Returns the lowest version that satisfies all of the components and is greater than or
+    /// equal to `bound`, if any.
+    pub fn min_version_from(&self, bound: &SemverVersion) -> Option<SemverVersion> {
+        // Each component allows a single range of versions, so the intersection starts at the
+        // highest lower bound, if it is not empty.
+        let lower_bounds = self.components.iter().map(|c| c.kind.lower_bound());
+        let min = lower_bounds.chain(std::iter::once(bound.clone())).max()?;
+        self.matches(&min).then_some(min)
+    }
 }
 
 /// A single SemVer version requirement component.
@@ -340,6 +400,18 @@ impl SemverReqComponentKind {
         }
     }
 
+    /// Returns the lowest version that can satisfy this requirement component.
+    fn lower_bound(&self) -> SemverVersion {
+        match self {
+            Self::Op(op, version) => match op.unwrap_or(Op::Exact) {
+                Op::Exact | Op::GreaterEq | Op::Tilde | Op::Caret => version.lowest(),
+                Op::Greater => version.next(),
+                _ => SemverVersion::new(0, 0, 0),
+            },
+            Self::Range(start, _) => start.lowest(),
+        }
+    }
+
     /// Returns `true` if the given version satisfies this requirement component.
     pub fn matches(&self, version: &SemverVersion) -> bool {
         match self {
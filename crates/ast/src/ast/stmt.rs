@@ -1,17 +1,40 @@
 use super::{
-    yul, AstPath, Box, CallArgs, DocComments, Expr, ParameterList, PathSlice, StrLit,
+    yul, AstPath, Box, CallArgs, DocComments, Expr, NodeId, ParameterList, PathSlice, StrLit,
     VariableDefinition,
 };
 use solar_interface::{Ident, Span};
 
-/// A block of statements.
-pub type Block<'ast> = Box<'ast, [Stmt<'ast>]>;
+/// A block of statements: `{ ... }`.
+#[derive(Debug)]
+pub struct Block<'ast> {
+    /// The span of the block, including the braces.
+    pub span: Span,
+    /// The statements in the block.
+    pub stmts: Box<'ast, [Stmt<'ast>]>,
+}
+
+impl<'ast> std::ops::Deref for Block<'ast> {
+    type Target = [Stmt<'ast>];
+
+    #[inline]
+    fn deref(&self) -> &Self::Target {
+        &self.stmts
+    }
+}
+
+impl std::ops::DerefMut for Block<'_> {
+    #[inline]
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.stmts
+    }
+}
 
 /// A statement, usually ending in a semicolon.
 ///
 /// Reference: <https://docs.soliditylang.org/en/latest/grammar.html#a4.SolidityParser.statement>
 #[derive(Debug)]
 pub struct Stmt<'ast> {
+    pub id: NodeId,
     pub docs: DocComments<'ast>,
     pub span: Span,
     pub kind: StmtKind<'ast>,
@@ -96,6 +119,9 @@ pub struct StmtAssembly<'ast> {
 #[derive(Debug)]
 pub struct StmtTry<'ast> {
     pub expr: Box<'ast, Expr<'ast>>,
+    /// The span of the success clause: `returns (...) { ... }`, or just the try block if there are
+    /// no return parameters.
+    pub span: Span,
     pub returns: ParameterList<'ast>,
     /// The try block.
     pub block: Block<'ast>,
@@ -108,6 +134,7 @@ pub struct StmtTry<'ast> {
 /// Reference: <https://docs.soliditylang.org/en/latest/grammar.html#a4.SolidityParser.catchClause>
 #[derive(Debug)]
 pub struct CatchClause<'ast> {
+    pub span: Span,
     pub name: Option<Ident>,
     pub args: ParameterList<'ast>,
     pub block: Block<'ast>,
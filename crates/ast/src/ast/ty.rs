@@ -1,4 +1,4 @@
-use super::{AstPath, Box, Expr, ParameterList, StateMutability, Visibility};
+use super::{AstPath, Box, Expr, NodeId, ParameterList, StateMutability, Visibility};
 use solar_interface::{kw, Ident, Span, Symbol};
 use std::{borrow::Cow, fmt};
 
@@ -7,6 +7,7 @@ use std::{borrow::Cow, fmt};
 /// Reference: <https://docs.soliditylang.org/en/latest/grammar.html#a4.SolidityParser.typeName>
 #[derive(Debug)]
 pub struct Type<'ast> {
+    pub id: NodeId,
     pub span: Span,
     pub kind: TypeKind<'ast>,
 }
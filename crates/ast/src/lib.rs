@@ -12,5 +12,12 @@ pub use solar_interface as interface;
 mod ast;
 pub use ast::*;
 
+pub mod pretty;
 pub mod token;
+pub mod trivia;
 pub mod visit;
+
+#[cfg(feature = "json")]
+mod solc_json;
+#[cfg(feature = "json")]
+pub use solc_json::{from_solc_json, SolcJsonError, SolcJsonReader};
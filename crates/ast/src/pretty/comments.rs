@@ -0,0 +1,79 @@
+use crate::token::CommentKind;
+use solar_interface::{BytePos, Span};
+
+/// A comment in a source file.
+#[derive(Clone, Debug)]
+pub struct Comment {
+    /// The span of the comment, including its delimiters.
+    pub span: Span,
+    /// The comment kind.
+    pub kind: CommentKind,
+    /// Whether the comment is a doc-comment.
+    pub is_doc: bool,
+}
+
+/// The comments and the original text of a source file, used to preserve comments, blank lines and
+/// the spelling of literals when printing its AST.
+#[derive(Clone, Debug)]
+pub struct Comments<'src> {
+    src: &'src str,
+    start_pos: BytePos,
+    comments: Vec<Comment>,
+}
+
+impl<'src> Comments<'src> {
+    /// Creates a new comment table for the source text starting at `start_pos`.
+    ///
+    /// `comments` must be sorted by position.
+    pub fn new(src: &'src str, start_pos: BytePos, comments: Vec<Comment>) -> Self {
+        Self { src, start_pos, comments }
+    }
+
+    /// Returns the comments.
+    pub fn comments(&self) -> &[Comment] {
+        &self.comments
+    }
+
+    /// Returns the text of a comment, including its delimiters.
+    pub fn text(&self, comment: &Comment) -> &'src str {
+        self.snippet(comment.span.lo(), comment.span.hi())
+    }
+
+    /// Returns the source text between the given positions, or an empty string if they are out of
+    /// bounds, like dummy spans.
+    pub(crate) fn snippet(&self, lo: BytePos, hi: BytePos) -> &'src str {
+        let (Some(lo), Some(hi)) = (self.offset(lo), self.offset(hi)) else { return "" };
+        self.src.get(lo..hi).unwrap_or_default()
+    }
+
+    /// Returns the source text of a span, if it is not a dummy span.
+    pub(crate) fn span_snippet(&self, span: Span) -> Option<&'src str> {
+        let s = self.snippet(span.lo(), span.hi());
+        (!s.is_empty()).then_some(s)
+    }
+
+    /// Returns `true` if there is a line break between the given positions.
+    pub(crate) fn has_line_break(&self, lo: BytePos, hi: BytePos) -> bool {
+        self.snippet(lo, hi).contains('\n')
+    }
+
+    /// Returns `true` if there is an empty line between the given positions.
+    pub(crate) fn has_blank_line(&self, lo: BytePos, hi: BytePos) -> bool {
+        let s = self.snippet(lo, hi);
+        let mut lines = s.split('\n');
+        lines.next();
+        lines.next_back();
+        lines.any(|line| line.trim().is_empty())
+    }
+
+    /// Returns the source text from the start of the line of `pos` to `pos`.
+    pub(crate) fn line_prefix(&self, pos: BytePos) -> &'src str {
+        let Some(offset) = self.offset(pos) else { return "" };
+        self.src[..offset].rsplit('\n').next().unwrap_or_default()
+    }
+
+    fn offset(&self, pos: BytePos) -> Option<usize> {
+        let offset = pos.0.checked_sub(self.start_pos.0)? as usize;
+        (offset <= self.src.len()).then_some(offset)
+    }
+}
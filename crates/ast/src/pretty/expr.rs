@@ -0,0 +1,212 @@
+use super::{Doc, Printer};
+use crate::ast::*;
+use solar_interface::config::IntTypes;
+
+impl Printer<'_> {
+    pub(super) fn expr(&self, expr: &Expr<'_>) -> Doc {
+        match &expr.kind {
+            ExprKind::Array(exprs) => self.list("[", self.exprs(exprs), "]"),
+            ExprKind::Assign(lhs, op, rhs) => {
+                let op = op.map_or_else(|| "=".to_string(), |op| format!("{}=", op.kind.to_str()));
+                Doc::concat([self.expr(lhs), Doc::text(format!(" {op} ")), self.expr(rhs)])
+            }
+            ExprKind::Binary(lhs, op, rhs) => Doc::group(Doc::concat([
+                self.expr(lhs),
+                Doc::text(format!(" {}", op.kind.to_str())),
+                Doc::nest(Doc::concat([Doc::Line, self.expr(rhs)])),
+            ])),
+            ExprKind::Call(callee, args) => Doc::concat([self.expr(callee), self.call_args(args)]),
+            ExprKind::CallOptions(callee, options) => {
+                Doc::concat([self.expr(callee), self.brace_list(self.named_args(options))])
+            }
+            ExprKind::Delete(expr) => Doc::concat([Doc::text("delete "), self.expr(expr)]),
+            ExprKind::Ident(ident) => self.ident(*ident),
+            ExprKind::Index(expr, kind) => {
+                let index = match kind {
+                    IndexKind::Index(index) => self.opt_expr(index.as_deref()),
+                    IndexKind::Range(start, end) => Doc::concat([
+                        self.opt_expr(start.as_deref()),
+                        Doc::text(":"),
+                        self.opt_expr(end.as_deref()),
+                    ]),
+                };
+                Doc::concat([self.expr(expr), Doc::text("["), index, Doc::text("]")])
+            }
+            ExprKind::Lit(lit, sub) => match sub {
+                Some(sub) => Doc::concat([self.lit(lit), Doc::text(format!(" {sub}"))]),
+                None => self.lit(lit),
+            },
+            ExprKind::Member(expr, member) => {
+                Doc::concat([self.expr(expr), Doc::text("."), self.ident(*member)])
+            }
+            ExprKind::New(ty) => Doc::concat([Doc::text("new "), self.ty(ty)]),
+            ExprKind::Payable(args) => Doc::concat([Doc::text("payable"), self.call_args(args)]),
+            ExprKind::Ternary(cond, then, else_) => Doc::group(Doc::concat([
+                self.expr(cond),
+                Doc::nest(Doc::concat([
+                    Doc::Line,
+                    Doc::text("? "),
+                    self.expr(then),
+                    Doc::Line,
+                    Doc::text(": "),
+                    self.expr(else_),
+                ])),
+            ])),
+            ExprKind::Tuple(exprs) => {
+                let exprs = exprs.iter().map(|expr| self.opt_expr(expr.as_deref())).collect();
+                self.list("(", exprs, ")")
+            }
+            ExprKind::TypeCall(ty) => {
+                Doc::concat([Doc::text("type("), self.ty(ty), Doc::text(")")])
+            }
+            ExprKind::Type(ty) => self.ty(ty),
+            ExprKind::Unary(op, expr) => {
+                let op_str = op.kind.to_str();
+                if op.kind.is_postfix() {
+                    return Doc::concat([self.expr(expr), Doc::text(op_str)]);
+                }
+                // `- -x` must not be printed as `--x`.
+                let is_minus = |kind: UnOpKind| matches!(kind, UnOpKind::Neg | UnOpKind::PreDec);
+                let space = is_minus(op.kind)
+                    && matches!(&expr.kind, ExprKind::Unary(inner, _) if is_minus(inner.kind));
+                let op_str = if space { format!("{op_str} ") } else { op_str.to_string() };
+                Doc::concat([Doc::text(op_str), self.expr(expr)])
+            }
+        }
+    }
+
+    fn opt_expr(&self, expr: Option<&Expr<'_>>) -> Doc {
+        expr.map_or(Doc::NIL, |expr| self.expr(expr))
+    }
+
+    fn exprs(&self, exprs: &[Box<'_, Expr<'_>>]) -> Vec<Doc> {
+        exprs.iter().map(|expr| self.expr(expr)).collect()
+    }
+
+    fn named_args(&self, args: &[NamedArg<'_>]) -> Vec<Doc> {
+        let arg = |arg: &NamedArg<'_>| {
+            Doc::concat([self.ident(arg.name), Doc::text(": "), self.expr(&arg.value)])
+        };
+        args.iter().map(arg).collect()
+    }
+
+    pub(super) fn call_args(&self, args: &CallArgs<'_>) -> Doc {
+        match args {
+            CallArgs::Unnamed(exprs) => self.list("(", self.exprs(exprs), ")"),
+            CallArgs::Named(args) => Doc::concat([
+                Doc::text("("),
+                self.brace_list(self.named_args(args)),
+                Doc::text(")"),
+            ]),
+        }
+    }
+
+    pub(super) fn ty(&self, ty: &Type<'_>) -> Doc {
+        match &ty.kind {
+            TypeKind::Elementary(ty) => self.elementary_ty(*ty),
+            TypeKind::Array(array) => Doc::concat([
+                self.ty(&array.element),
+                Doc::text("["),
+                self.opt_expr(array.size.as_deref()),
+                Doc::text("]"),
+            ]),
+            TypeKind::Function(f) => {
+                let mut attributes = Vec::new();
+                if let Some(visibility) = f.visibility {
+                    attributes.push(Doc::text(visibility.to_str()));
+                }
+                if f.state_mutability != StateMutability::NonPayable {
+                    attributes.push(Doc::text(f.state_mutability.to_str()));
+                }
+                if !f.returns.is_empty() {
+                    attributes.push(Doc::concat([Doc::text("returns "), self.params(&f.returns)]));
+                }
+                Doc::group(Doc::concat([
+                    Doc::text("function"),
+                    self.params(&f.parameters),
+                    Doc::nest(Doc::concat(
+                        attributes.into_iter().map(|a| Doc::concat([Doc::Line, a])),
+                    )),
+                ]))
+            }
+            TypeKind::Mapping(mapping) => {
+                let name = |name: Option<Ident>| {
+                    name.map_or(Doc::NIL, |name| Doc::concat([Doc::text(" "), self.ident(name)]))
+                };
+                Doc::concat([
+                    Doc::text("mapping("),
+                    self.ty(&mapping.key),
+                    name(mapping.key_name),
+                    Doc::text(" => "),
+                    self.ty(&mapping.value),
+                    name(mapping.value_name),
+                    Doc::text(")"),
+                ])
+            }
+            TypeKind::Custom(path) => self.path(path),
+        }
+    }
+
+    fn elementary_ty(&self, ty: ElementaryType) -> Doc {
+        let int = |name: &str, size: TypeSize| match self.config.int_types {
+            IntTypes::Preserve if size.bytes_raw() == 0 => name.to_string(),
+            IntTypes::Short if size.bits() == 256 => name.to_string(),
+            _ => format!("{name}{}", size.bits()),
+        };
+        let fixed = |name: &str, m: TypeSize, n: TypeFixedSize| {
+            if m.bytes_raw() == 0 {
+                name.to_string()
+            } else {
+                format!("{name}{}x{}", m.bits(), n.get())
+            }
+        };
+        Doc::text(match ty {
+            ElementaryType::Int(size) => int("int", size),
+            ElementaryType::UInt(size) => int("uint", size),
+            ElementaryType::Fixed(m, n) => fixed("fixed", m, n),
+            ElementaryType::UFixed(m, n) => fixed("ufixed", m, n),
+            _ => ty.to_string(),
+        })
+    }
+
+    /// Prints a parameter list: `(uint a, uint b)`.
+    pub(super) fn params(&self, params: &[VariableDefinition<'_>]) -> Doc {
+        self.list("(", params.iter().map(|var| self.var(var)).collect(), ")")
+    }
+
+    /// Prints a variable definition, without the trailing semicolon.
+    pub(super) fn var(&self, var: &VariableDefinition<'_>) -> Doc {
+        let mut parts = vec![self.ty(&var.ty)];
+        if var.indexed {
+            parts.push(Doc::text("indexed"));
+        }
+        if let Some(data_location) = var.data_location {
+            parts.push(Doc::text(data_location.to_str()));
+        }
+        if let Some(visibility) = var.visibility {
+            parts.push(Doc::text(visibility.to_str()));
+        }
+        if let Some(mutability) = var.mutability {
+            parts.push(Doc::text(mutability.to_str()));
+        }
+        if let Some(override_) = &var.override_ {
+            parts.push(self.override_(override_));
+        }
+        if let Some(name) = var.name {
+            parts.push(self.ident(name));
+        }
+        let var_doc = Doc::join(parts, Doc::text(" "));
+        match &var.initializer {
+            Some(init) => Doc::concat([var_doc, Doc::text(" = "), self.expr(init)]),
+            None => var_doc,
+        }
+    }
+
+    pub(super) fn override_(&self, override_: &Override<'_>) -> Doc {
+        if override_.paths.is_empty() {
+            return Doc::text("override");
+        }
+        let paths = override_.paths.iter().map(|path| self.path(path)).collect();
+        Doc::concat([Doc::text("override"), self.list("(", paths, ")")])
+    }
+}
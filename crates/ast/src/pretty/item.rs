@@ -0,0 +1,254 @@
+use super::{Doc, Printer, EOF};
+use crate::{ast::*, token::Token};
+use solar_interface::BytePos;
+
+impl Printer<'_> {
+    pub(super) fn source_unit(&mut self, source_unit: &SourceUnit<'_>) -> Doc {
+        let items = &source_unit.items.raw;
+        let no_source = self.comments.is_none();
+        let blank = |a: &Item<'_>, b: &Item<'_>| {
+            let pragma = |item: &Item<'_>| matches!(item.kind, ItemKind::Pragma(_));
+            no_source && (has_body(a) || has_body(b) || (pragma(a) && !pragma(b)))
+        };
+        self.lines(items, Some(EOF), |item| item.span, blank, Self::item)
+    }
+
+    pub(super) fn item(&mut self, item: &Item<'_>) -> Doc {
+        let hi = Some(item.span.hi());
+        let doc = match &item.kind {
+            ItemKind::Pragma(pragma) => self.pragma(item.span, pragma),
+            ItemKind::Import(import) => self.import(import),
+            ItemKind::Using(using) => self.using(using),
+            ItemKind::Contract(contract) => self.contract(contract, hi),
+            ItemKind::Function(function) => self.function(function, hi),
+            ItemKind::Variable(var) => Doc::concat([self.var(var), Doc::text(";")]),
+            ItemKind::Struct(strukt) => self.strukt(strukt, hi),
+            ItemKind::Enum(enumm) => self.enumm(enumm, hi),
+            ItemKind::Udvt(udvt) => self.udvt(udvt),
+            ItemKind::Error(error) => self.error(error),
+            ItemKind::Event(event) => self.event(event),
+        };
+        Doc::concat([self.docs(&item.docs), doc])
+    }
+
+    /// Prints a pragma directive. Verbatim pragmas are printed as written in `span` if the source
+    /// is available.
+    pub(super) fn pragma(&self, span: Span, pragma: &PragmaDirective<'_>) -> Doc {
+        let tokens = match &pragma.tokens {
+            PragmaTokens::Version(name, req) => {
+                Doc::concat([self.ident(*name), Doc::text(format!(" {req}"))])
+            }
+            PragmaTokens::Custom(name, value) => {
+                let value = value
+                    .as_ref()
+                    .map_or(Doc::NIL, |v| Doc::concat([Doc::text(" "), self.ident_or_str(v)]));
+                Doc::concat([self.ident_or_str(name), value])
+            }
+            PragmaTokens::Verbatim(tokens) => {
+                if let Some(s) = self.comments.and_then(|c| c.span_snippet(span)) {
+                    return Doc::text(s.to_string());
+                }
+                Doc::text(tokens.iter().map(token_str).collect::<Vec<_>>().join(" "))
+            }
+        };
+        Doc::concat([Doc::text("pragma "), tokens, Doc::text(";")])
+    }
+
+    fn ident_or_str(&self, x: &IdentOrStrLit) -> Doc {
+        match x {
+            IdentOrStrLit::Ident(ident) => self.ident(*ident),
+            IdentOrStrLit::StrLit(lit) => self.str_lit(lit),
+        }
+    }
+
+    pub(super) fn import(&self, import: &ImportDirective<'_>) -> Doc {
+        let path = self.str_lit(&import.path);
+        let alias = |alias: Option<Ident>| {
+            alias.map_or(Doc::NIL, |alias| Doc::concat([Doc::text(" as "), self.ident(alias)]))
+        };
+        let doc = match &import.items {
+            ImportItems::Plain(name) => Doc::concat([path, alias(*name)]),
+            ImportItems::Aliases(aliases) => {
+                let aliases = aliases
+                    .iter()
+                    .map(|&(name, alias_name)| Doc::concat([self.ident(name), alias(alias_name)]))
+                    .collect();
+                Doc::concat([self.brace_list(aliases), Doc::text(" from "), path])
+            }
+            ImportItems::Glob(name) => {
+                Doc::concat([Doc::text("*"), alias(*name), Doc::text(" from "), path])
+            }
+        };
+        Doc::concat([Doc::text("import "), doc, Doc::text(";")])
+    }
+
+    pub(super) fn using(&self, using: &UsingDirective<'_>) -> Doc {
+        let list = match &using.list {
+            UsingList::Single(path) => self.path(path),
+            UsingList::Multiple(paths) => {
+                let paths = paths
+                    .iter()
+                    .map(|(path, op)| match op {
+                        Some(op) => {
+                            let op = op.to_op().either(|op| op.to_str(), |op| op.to_str());
+                            Doc::concat([self.path(path), Doc::text(format!(" as {op}"))])
+                        }
+                        None => self.path(path),
+                    })
+                    .collect();
+                self.brace_list(paths)
+            }
+        };
+        let ty = using.ty.as_ref().map_or(Doc::text("*"), |ty| self.ty(ty));
+        Doc::concat([
+            Doc::text("using "),
+            list,
+            Doc::text(" for "),
+            ty,
+            Doc::text(if using.global { " global;" } else { ";" }),
+        ])
+    }
+
+    /// Prints a contract. The comments before `close` are printed at the end of its body.
+    pub(super) fn contract(&mut self, contract: &ItemContract<'_>, close: Option<BytePos>) -> Doc {
+        let mut head = vec![Doc::text(format!("{} ", contract.kind)), self.ident(contract.name)];
+        if !contract.bases.is_empty() {
+            let bases = contract.bases.iter().map(|base| self.modifier(base));
+            head.push(Doc::text(" is "));
+            head.push(Doc::join(bases, Doc::text(", ")));
+        }
+        let no_source = self.comments.is_none();
+        let blank = |a: &Item<'_>, b: &Item<'_>| no_source && (has_body(a) || has_body(b));
+        let body = self.lines(&*contract.body, close, |item| item.span, blank, Self::item);
+        Doc::concat([Doc::concat(head), Doc::text(" "), self.braces(body)])
+    }
+
+    /// Prints a function. The comments before `close` are printed at the end of its body.
+    pub(super) fn function(&mut self, function: &ItemFunction<'_>, close: Option<BytePos>) -> Doc {
+        let header = &function.header;
+        let mut head = vec![Doc::text(function.kind.to_str())];
+        if let Some(name) = header.name {
+            head.push(Doc::text(" "));
+            head.push(self.ident(name));
+        }
+        head.push(self.params(&header.parameters));
+
+        let mut attributes = Vec::new();
+        if let Some(visibility) = header.visibility {
+            attributes.push(Doc::text(visibility.to_str()));
+        }
+        if header.state_mutability != StateMutability::NonPayable {
+            attributes.push(Doc::text(header.state_mutability.to_str()));
+        }
+        if header.virtual_ {
+            attributes.push(Doc::text("virtual"));
+        }
+        if let Some(override_) = &header.override_ {
+            attributes.push(self.override_(override_));
+        }
+        attributes.extend(header.modifiers.iter().map(|modifier| self.modifier(modifier)));
+        if !header.returns.is_empty() {
+            attributes.push(Doc::concat([Doc::text("returns "), self.params(&header.returns)]));
+        }
+        head.push(Doc::nest(Doc::concat(
+            attributes.into_iter().map(|attribute| Doc::concat([Doc::Line, attribute])),
+        )));
+
+        let body = match &function.body {
+            Some(body) => Doc::concat([Doc::text(" "), self.block(body, close)]),
+            None => Doc::text(";"),
+        };
+        Doc::concat([Doc::group(Doc::concat(head)), body])
+    }
+
+    /// Prints a struct. The comments before `close` are printed at the end of its fields.
+    pub(super) fn strukt(&mut self, strukt: &ItemStruct<'_>, close: Option<BytePos>) -> Doc {
+        let fields = self.lines(
+            &*strukt.fields,
+            close,
+            |field| field.span,
+            |_, _| false,
+            |this, field| Doc::concat([this.var(field), Doc::text(";")]),
+        );
+        Doc::concat([
+            Doc::text("struct "),
+            self.ident(strukt.name),
+            Doc::text(" "),
+            self.braces(fields),
+        ])
+    }
+
+    /// Prints an enum. The comments before `close` are printed at the end of its variants.
+    pub(super) fn enumm(&mut self, enumm: &ItemEnum<'_>, close: Option<BytePos>) -> Doc {
+        let variants = enumm.variants.iter().enumerate().collect::<Vec<_>>();
+        let last = variants.len().saturating_sub(1);
+        let variants = self.lines(
+            &variants,
+            close,
+            |(_, variant)| variant.span,
+            |_, _| false,
+            |this, &(i, variant)| {
+                let comma = if i == last { "" } else { "," };
+                Doc::concat([this.ident(*variant), Doc::text(comma)])
+            },
+        );
+        Doc::concat([
+            Doc::text("enum "),
+            self.ident(enumm.name),
+            Doc::text(" "),
+            self.braces(variants),
+        ])
+    }
+
+    pub(super) fn udvt(&self, udvt: &ItemUdvt<'_>) -> Doc {
+        Doc::concat([
+            Doc::text("type "),
+            self.ident(udvt.name),
+            Doc::text(" is "),
+            self.ty(&udvt.ty),
+            Doc::text(";"),
+        ])
+    }
+
+    pub(super) fn error(&self, error: &ItemError<'_>) -> Doc {
+        Doc::concat([
+            Doc::text("error "),
+            self.ident(error.name),
+            self.params(&error.parameters),
+            Doc::text(";"),
+        ])
+    }
+
+    pub(super) fn event(&self, event: &ItemEvent<'_>) -> Doc {
+        Doc::concat([
+            Doc::text("event "),
+            self.ident(event.name),
+            self.params(&event.parameters),
+            Doc::text(if event.anonymous { " anonymous;" } else { ";" }),
+        ])
+    }
+
+    /// Prints a modifier invocation or an inheritance specifier.
+    pub(super) fn modifier(&self, modifier: &Modifier<'_>) -> Doc {
+        if modifier.arguments.is_empty() {
+            return self.path(&modifier.name);
+        }
+        Doc::concat([self.path(&modifier.name), self.call_args(&modifier.arguments)])
+    }
+}
+
+/// Returns `true` if the item has a body, which is separated from other items by blank lines.
+fn has_body(item: &Item<'_>) -> bool {
+    match &item.kind {
+        ItemKind::Contract(_) => true,
+        ItemKind::Function(function) => function.body.is_some(),
+        _ => false,
+    }
+}
+
+fn token_str(token: &Token) -> String {
+    match token.lit() {
+        Some(lit) => lit.to_string(),
+        None => token.kind.to_string(),
+    }
+}
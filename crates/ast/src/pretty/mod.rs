@@ -0,0 +1,396 @@
+//! Solidity source code pretty-printing.
+//!
+//! The printer lays out the AST into lines of at most [`FormatConfig::line_width`] columns where
+//! possible. When given the [`Comments`] of the source, comments and single blank lines between
+//! items and statements are preserved, and literals are printed as written.
+//!
+//! The AST nodes that can be printed on their own also implement [`Display`](fmt::Display) with
+//! the default [`FormatConfig`], which regenerates valid Solidity source code from them without
+//! comments: source units, items and their kinds, statements, blocks, expressions, types,
+//! literals, and Yul statements and expressions. Parts of nodes, like `catch` clauses and import
+//! lists, are only printed as part of their node, and Yul objects are not supported.
+
+use crate::{ast::*, token::CommentKind};
+use solar_interface::{config::IntTypes, BytePos, Span};
+use std::fmt;
+
+mod comments;
+pub use comments::{Comment, Comments};
+
+mod pp;
+use pp::Doc;
+
+mod expr;
+mod item;
+mod stmt;
+mod yul;
+
+/// Formatting options.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FormatConfig {
+    /// The maximum line width. Defaults to 120.
+    pub line_width: usize,
+    /// The number of spaces per indentation level. Defaults to 4.
+    pub indent_width: usize,
+    /// Whether to indent with tabs instead of spaces.
+    pub use_tabs: bool,
+    /// Whether to print spaces inside of braces: `{ a, b }` instead of `{a, b}`.
+    pub bracket_spacing: bool,
+    /// How to print `int` and `uint` types.
+    pub int_types: IntTypes,
+}
+
+impl Default for FormatConfig {
+    fn default() -> Self {
+        Self {
+            line_width: 120,
+            indent_width: 4,
+            use_tabs: false,
+            bracket_spacing: false,
+            int_types: IntTypes::default(),
+        }
+    }
+}
+
+/// Prints a source unit.
+///
+/// If `comments` are given, they must be the comments of the source that `source_unit` was parsed
+/// from. Otherwise, only the doc-comments in the AST are printed.
+pub fn print_source_unit(
+    source_unit: &SourceUnit<'_>,
+    comments: Option<&Comments<'_>>,
+    config: &FormatConfig,
+) -> String {
+    let mut printer = Printer::new(config, comments);
+    let doc = printer.source_unit(source_unit);
+    pp::render(&doc, config)
+}
+
+macro_rules! impl_display {
+    ($($ty:ty => |$printer:ident, $node:ident| $print:expr),* $(,)?) => {$(
+        impl fmt::Display for $ty {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                let $node = self;
+                display(f, |$printer| $print)
+            }
+        }
+    )*};
+}
+
+impl_display! {
+    SourceUnit<'_> => |p, x| p.source_unit(x),
+    Item<'_> => |p, x| p.item(x),
+    PragmaDirective<'_> => |p, x| p.pragma(Span::DUMMY, x),
+    ImportDirective<'_> => |p, x| p.import(x),
+    UsingDirective<'_> => |p, x| p.using(x),
+    ItemContract<'_> => |p, x| p.contract(x, None),
+    ItemFunction<'_> => |p, x| p.function(x, None),
+    ItemStruct<'_> => |p, x| p.strukt(x, None),
+    ItemEnum<'_> => |p, x| p.enumm(x, None),
+    ItemUdvt<'_> => |p, x| p.udvt(x),
+    ItemError<'_> => |p, x| p.error(x),
+    ItemEvent<'_> => |p, x| p.event(x),
+    Modifier<'_> => |p, x| p.modifier(x),
+    Override<'_> => |p, x| p.override_(x),
+    Block<'_> => |p, x| p.block(x, None),
+    Stmt<'_> => |p, x| p.stmt(x),
+    StmtAssembly<'_> => |p, x| p.assembly(x, None),
+    StmtTry<'_> => |p, x| p.try_(x, None),
+    Expr<'_> => |p, x| p.expr(x),
+    CallArgs<'_> => |p, x| p.call_args(x),
+    Type<'_> => |p, x| p.ty(x),
+    VariableDefinition<'_> => |p, x| p.var(x),
+    Lit => |p, x| p.lit(x),
+    StrLit => |p, x| p.str_lit(x),
+    yul::Stmt<'_> => |p, x| p.yul_stmt(x),
+    yul::StmtSwitch<'_> => |p, x| p.yul_switch(x, None),
+    yul::Function<'_> => |p, x| p.yul_function(x, None),
+    yul::Expr<'_> => |p, x| p.yul_expr(x),
+}
+
+fn display(f: &mut fmt::Formatter<'_>, print: impl FnOnce(&mut Printer<'_>) -> Doc) -> fmt::Result {
+    let config = FormatConfig::default();
+    let doc = print(&mut Printer::new(&config, None));
+    f.write_str(pp::render(&doc, &config).trim_end_matches('\n'))
+}
+
+/// The end of the source, used to print all the remaining comments.
+const EOF: BytePos = BytePos(u32::MAX);
+
+/// Builds the [`Doc`] of an AST.
+struct Printer<'a> {
+    config: &'a FormatConfig,
+    comments: Option<&'a Comments<'a>>,
+    /// The index of the next comment to print.
+    next_comment: usize,
+}
+
+impl<'a> Printer<'a> {
+    fn new(config: &'a FormatConfig, comments: Option<&'a Comments<'a>>) -> Self {
+        Self { config, comments, next_comment: 0 }
+    }
+
+    /// Returns the next comment to print, if it starts before `pos`.
+    fn peek_comment(&self, pos: BytePos) -> Option<&'a Comment> {
+        let comment = self.comments?.comments().get(self.next_comment)?;
+        (comment.span.lo() < pos).then_some(comment)
+    }
+
+    /// Returns the start of the next comment if it starts before `pos`, or `pos` otherwise.
+    fn next_pos(&self, pos: BytePos) -> BytePos {
+        self.peek_comment(pos).map_or(pos, |comment| comment.span.lo())
+    }
+
+    /// Returns the end of the last printed comment, if it ends after `pos`, or `pos` otherwise.
+    fn last_pos(&self, pos: BytePos) -> BytePos {
+        let last = self.next_comment.checked_sub(1).and_then(|i| self.comments?.comments().get(i));
+        last.map_or(pos, |comment| comment.span.hi().max(pos))
+    }
+
+    fn has_blank_line(&self, lo: BytePos, hi: BytePos) -> bool {
+        self.comments.is_some_and(|comments| comments.has_blank_line(lo, hi))
+    }
+
+    /// Prints the comments that start before `pos` on their own lines.
+    ///
+    /// The comments are followed by a line break if `end_break` is `true`, or if the last comment
+    /// is a line comment.
+    fn leading_comments(&mut self, pos: BytePos, end_break: bool) -> Doc {
+        let Some(comments) = self.comments else { return Doc::NIL };
+        let mut docs = Vec::new();
+        let mut prev_hi = None;
+        while let Some(comment) = self.peek_comment(pos) {
+            self.next_comment += 1;
+            if prev_hi.is_some_and(|hi| comments.has_blank_line(hi, comment.span.lo())) {
+                docs.push(Doc::HardLine);
+            }
+            docs.push(self.comment(comment));
+            let next = self.next_pos(pos);
+            if comment.kind == CommentKind::Line || comments.has_line_break(comment.span.hi(), next)
+            {
+                docs.push(Doc::HardLine);
+                if next == pos && comments.has_blank_line(comment.span.hi(), pos) {
+                    docs.push(Doc::HardLine);
+                }
+            } else {
+                docs.push(Doc::text(" "));
+            }
+            prev_hi = Some(comment.span.hi());
+        }
+        if !end_break && matches!(docs.last(), Some(Doc::HardLine)) {
+            while let Some(Doc::HardLine) = docs.last() {
+                docs.pop();
+            }
+        }
+        Doc::concat(docs)
+    }
+
+    /// Prints the comments that start before `hi`, or on the same line after it, after an element
+    /// ending at `hi`. The element must be followed by a line break.
+    fn trailing_comments(&mut self, hi: BytePos) -> Doc {
+        let Some(comments) = self.comments else { return Doc::NIL };
+        let mut docs = Vec::new();
+        while let Some(comment) = self.peek_comment(EOF) {
+            let lo = comment.span.lo();
+            if lo >= hi && comments.has_line_break(hi, lo) {
+                break;
+            }
+            if !matches!(docs.last(), Some(Doc::HardLine)) {
+                docs.push(Doc::text(" "));
+            }
+            self.next_comment += 1;
+            docs.push(self.comment(comment));
+            if comment.kind == CommentKind::Line {
+                docs.push(Doc::HardLine);
+            }
+        }
+        // The caller breaks the line after the element.
+        if let Some(Doc::HardLine) = docs.last() {
+            docs.pop();
+        }
+        Doc::concat(docs)
+    }
+
+    /// Prints a comment. Block comments spanning multiple lines are re-indented.
+    fn comment(&self, comment: &Comment) -> Doc {
+        let comments = self.comments.unwrap();
+        let text = comments.text(comment);
+        if !text.contains('\n') {
+            return Doc::text(text.to_string());
+        }
+        let column = comments.line_prefix(comment.span.lo()).len();
+        let lines = text.split('\n').enumerate().map(|(i, line)| {
+            let indent = line.len() - line.trim_start_matches([' ', '\t']).len();
+            let line = if i == 0 { line } else { &line[indent.min(column)..] };
+            Doc::text(line.trim_end().to_string())
+        });
+        Doc::join(lines, Doc::HardLine)
+    }
+
+    /// Prints the doc-comments of an element if the source comments are not available.
+    fn docs(&self, docs: &[DocComment]) -> Doc {
+        if self.comments.is_some() {
+            return Doc::NIL;
+        }
+        Doc::concat(docs.iter().map(|doc| {
+            let text = match doc.kind {
+                CommentKind::Line => format!("///{}", doc.symbol),
+                CommentKind::Block => format!("/**{}*/", doc.symbol),
+            };
+            Doc::concat([Doc::text(text), Doc::HardLine])
+        }))
+    }
+
+    /// Prints elements on their own lines, with their comments.
+    ///
+    /// The comments before `close` are printed after the last element. Blank lines between the
+    /// elements are preserved, and added where `blank` returns `true`.
+    fn lines<T>(
+        &mut self,
+        items: &[T],
+        close: Option<BytePos>,
+        span: impl Fn(&T) -> Span,
+        blank: impl Fn(&T, &T) -> bool,
+        mut print: impl FnMut(&mut Self, &T) -> Doc,
+    ) -> Doc {
+        let mut docs = Vec::new();
+        let mut prev: Option<(&T, BytePos)> = None;
+        for item in items {
+            let span = span(item);
+            if let Some((prev, prev_hi)) = prev {
+                docs.push(Doc::HardLine);
+                if self.has_blank_line(prev_hi, self.next_pos(span.lo())) || blank(prev, item) {
+                    docs.push(Doc::HardLine);
+                }
+            }
+            docs.push(self.leading_comments(span.lo(), true));
+            docs.push(print(self, item));
+            docs.push(self.trailing_comments(span.hi()));
+            prev = Some((item, self.last_pos(span.hi())));
+        }
+        if let Some(close) = close {
+            if let Some(comment) = self.peek_comment(close) {
+                if let Some((_, prev_hi)) = prev {
+                    docs.push(Doc::HardLine);
+                    if self.has_blank_line(prev_hi, comment.span.lo()) {
+                        docs.push(Doc::HardLine);
+                    }
+                }
+                docs.push(self.leading_comments(close, false));
+            }
+        }
+        Doc::concat(docs)
+    }
+
+    /// Wraps lines in braces: `{ ... }`.
+    fn braces(&self, lines: Doc) -> Doc {
+        if lines.is_nil() {
+            return Doc::text("{}");
+        }
+        Doc::concat([
+            Doc::text("{"),
+            Doc::nest(Doc::concat([Doc::HardLine, lines])),
+            Doc::HardLine,
+            Doc::text("}"),
+        ])
+    }
+
+    /// Prints a comma-separated list in delimiters, which is broken one element per line if it
+    /// does not fit.
+    fn list(&self, open: &'static str, docs: Vec<Doc>, close: &'static str) -> Doc {
+        self.delimited(open, docs, close, Doc::SoftLine)
+    }
+
+    /// Prints a comma-separated list in braces, with spaces inside the braces depending on
+    /// [`FormatConfig::bracket_spacing`].
+    fn brace_list(&self, docs: Vec<Doc>) -> Doc {
+        let line = if self.config.bracket_spacing { Doc::Line } else { Doc::SoftLine };
+        self.delimited("{", docs, "}", line)
+    }
+
+    fn delimited(&self, open: &'static str, docs: Vec<Doc>, close: &'static str, line: Doc) -> Doc {
+        if docs.is_empty() {
+            return Doc::text(format!("{open}{close}"));
+        }
+        Doc::group(Doc::concat([
+            Doc::text(open),
+            Doc::nest(Doc::concat([
+                line.clone(),
+                Doc::join(docs, Doc::concat([Doc::text(","), Doc::Line])),
+            ])),
+            line,
+            Doc::text(close),
+        ]))
+    }
+
+    fn ident(&self, ident: Ident) -> Doc {
+        Doc::text(ident.to_string())
+    }
+
+    fn path(&self, path: &PathSlice) -> Doc {
+        Doc::text(path.to_string())
+    }
+
+    /// Prints a string literal, as written if the source is available.
+    fn str_lit(&self, lit: &StrLit) -> Doc {
+        match self.comments.and_then(|comments| comments.span_snippet(lit.span)) {
+            Some(snippet) => Doc::text(snippet.to_string()),
+            None => Doc::text(format!("\"{}\"", lit.value)),
+        }
+    }
+
+    /// Prints a literal, as written if the source is available.
+    fn lit(&self, lit: &Lit) -> Doc {
+        if let Some(snippet) = self.comments.and_then(|comments| comments.span_snippet(lit.span)) {
+            return Doc::text(snippet.to_string());
+        }
+        let LitKind::Str(kind, value) = &lit.kind else { return Doc::text(lit.symbol.to_string()) };
+        let prefix = match kind {
+            StrKind::Str => "",
+            StrKind::Unicode => "unicode",
+            StrKind::Hex => "hex",
+        };
+        // The symbol is only the first literal of a concatenation, like `"a" "b"`.
+        let symbol = lit.symbol.as_str();
+        let single = lit.span.hi().0 - lit.span.lo().0 == (prefix.len() + symbol.len() + 2) as u32;
+        let s = if single && !symbol.contains('"') {
+            format!("{prefix}\"{symbol}\"")
+        } else if single {
+            format!("{prefix}'{symbol}'")
+        } else if *kind == StrKind::Hex {
+            format!("hex\"{}\"", alloy_primitives::hex::encode(value))
+        } else {
+            format!("{prefix}\"{}\"", escape_str(value, *kind == StrKind::Unicode))
+        };
+        Doc::text(s)
+    }
+}
+
+/// Escapes the contents of a string literal. Non-ASCII characters are only allowed in unicode
+/// string literals.
+fn escape_str(bytes: &[u8], unicode: bool) -> String {
+    let mut s = String::new();
+    for chunk in bytes.utf8_chunks() {
+        for c in chunk.valid().chars() {
+            match c {
+                '"' => s.push_str("\\\""),
+                '\\' => s.push_str("\\\\"),
+                '\n' => s.push_str("\\n"),
+                '\r' => s.push_str("\\r"),
+                '\t' => s.push_str("\\t"),
+                c if c.is_ascii_control() => s.push_str(&format!("\\x{:02x}", c as u32)),
+                c if !c.is_ascii() && !unicode => {
+                    let mut buf = [0; 4];
+                    for b in c.encode_utf8(&mut buf).bytes() {
+                        s.push_str(&format!("\\x{b:02x}"));
+                    }
+                }
+                c => s.push(c),
+            }
+        }
+        for b in chunk.invalid() {
+            s.push_str(&format!("\\x{b:02x}"));
+        }
+    }
+    s
+}
@@ -0,0 +1,238 @@
+//! A width-aware document layout engine, based on Philip Wadler's
+//! ["A prettier printer"](https://homepages.inf.ed.ac.uk/wadler/papers/prettier/prettier.pdf).
+//!
+//! A [`Doc`] describes text with optional line breaks. [`Group`](Doc::Group)s are laid out on a
+//! single line if they fit in the remaining width, otherwise all of their direct line breaks are
+//! taken.
+
+use super::FormatConfig;
+use std::borrow::Cow;
+
+/// A document to lay out.
+#[derive(Clone, Debug)]
+pub(crate) enum Doc {
+    /// Text without line breaks, unless it comes from the source verbatim, like block comments.
+    Text(Cow<'static, str>),
+    /// A space, or a line break.
+    Line,
+    /// Nothing, or a line break.
+    SoftLine,
+    /// A line break, which breaks all the enclosing groups.
+    HardLine,
+    /// A sequence of documents.
+    Concat(Vec<Doc>),
+    /// A group of line breaks that are either all taken or none.
+    Group { doc: Box<Doc>, breaks: bool },
+    /// A document whose line breaks are indented by one more level.
+    Nest(Box<Doc>),
+    /// The first document if the enclosing group is broken, the second one otherwise.
+    IfBreak(Box<Doc>, Box<Doc>),
+}
+
+impl Doc {
+    /// The empty document.
+    pub(crate) const NIL: Self = Self::Concat(Vec::new());
+
+    /// Creates a text document.
+    pub(crate) fn text(s: impl Into<Cow<'static, str>>) -> Self {
+        Self::Text(s.into())
+    }
+
+    /// Creates a sequence of documents.
+    pub(crate) fn concat(docs: impl IntoIterator<Item = Self>) -> Self {
+        Self::Concat(docs.into_iter().collect())
+    }
+
+    /// Creates a group.
+    pub(crate) fn group(doc: Self) -> Self {
+        let breaks = doc.breaks();
+        Self::Group { doc: Box::new(doc), breaks }
+    }
+
+    /// Creates an indented document.
+    pub(crate) fn nest(doc: Self) -> Self {
+        Self::Nest(Box::new(doc))
+    }
+
+    /// Creates a document that is only printed if the enclosing group is broken.
+    pub(crate) fn if_break(doc: Self) -> Self {
+        Self::IfBreak(Box::new(doc), Box::new(Self::NIL))
+    }
+
+    /// Joins documents with a separator.
+    pub(crate) fn join(docs: impl IntoIterator<Item = Self>, sep: Self) -> Self {
+        let mut v = Vec::new();
+        for (i, doc) in docs.into_iter().enumerate() {
+            if i != 0 {
+                v.push(sep.clone());
+            }
+            v.push(doc);
+        }
+        Self::Concat(v)
+    }
+
+    /// Returns `true` if the document is empty.
+    pub(crate) fn is_nil(&self) -> bool {
+        match self {
+            Self::Text(s) => s.is_empty(),
+            Self::Concat(docs) => docs.iter().all(Self::is_nil),
+            Self::Group { doc, .. } | Self::Nest(doc) => doc.is_nil(),
+            _ => false,
+        }
+    }
+
+    /// Returns `true` if the document always breaks the enclosing group.
+    fn breaks(&self) -> bool {
+        match self {
+            Self::Text(s) => s.contains('\n'),
+            Self::Line | Self::SoftLine => false,
+            Self::HardLine => true,
+            Self::Concat(docs) => docs.iter().any(Self::breaks),
+            Self::Group { breaks, .. } => *breaks,
+            Self::Nest(doc) => doc.breaks(),
+            Self::IfBreak(_, flat) => flat.breaks(),
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Mode {
+    Flat,
+    Break,
+}
+
+/// Lays out the document.
+pub(crate) fn render(doc: &Doc, config: &FormatConfig) -> String {
+    let mut out = String::new();
+    let mut column = 0;
+    // The indentation level to write before the next text, after a line break.
+    let mut pending_indent = None;
+    let mut stack = vec![(0, Mode::Break, doc)];
+    while let Some((indent, mode, doc)) = stack.pop() {
+        match doc {
+            Doc::Text(s) => {
+                if s.is_empty() {
+                    continue;
+                }
+                if let Some(level) = pending_indent.take() {
+                    column = write_indent(&mut out, level, config);
+                }
+                out.push_str(s);
+                match s.rfind('\n') {
+                    Some(i) => column = width(&s[i + 1..]),
+                    None => column += width(s),
+                }
+            }
+            Doc::Line if mode == Mode::Flat => {
+                if pending_indent.is_none() {
+                    out.push(' ');
+                    column += 1;
+                }
+            }
+            Doc::SoftLine if mode == Mode::Flat => {}
+            Doc::Line | Doc::SoftLine | Doc::HardLine => {
+                trim_end(&mut out);
+                out.push('\n');
+                column = 0;
+                pending_indent = Some(indent);
+            }
+            Doc::Concat(docs) => stack.extend(docs.iter().rev().map(|doc| (indent, mode, doc))),
+            Doc::Group { doc, breaks } => {
+                let current = pending_indent.map_or(column, |level| level * config.indent_width);
+                let remaining = config.line_width as isize - current as isize;
+                let flat = !*breaks && (mode == Mode::Flat || fits(doc, &stack, remaining));
+                stack.push((indent, if flat { Mode::Flat } else { Mode::Break }, doc));
+            }
+            Doc::Nest(doc) => stack.push((indent + 1, mode, doc)),
+            Doc::IfBreak(broken, flat) => {
+                stack.push((indent, mode, if mode == Mode::Break { broken } else { flat }))
+            }
+        }
+    }
+    out.truncate(out.trim_end().len());
+    if !out.is_empty() {
+        out.push('\n');
+    }
+    out
+}
+
+/// Returns `true` if the document fits in `remaining` columns when laid out flat, including the
+/// rest of the current line.
+fn fits(doc: &Doc, rest: &[(usize, Mode, &Doc)], mut remaining: isize) -> bool {
+    let mut rest = rest.iter().rev();
+    let mut stack = vec![(Mode::Flat, doc)];
+    while remaining >= 0 {
+        let Some((mode, doc)) = stack.pop().or_else(|| rest.next().map(|&(_, m, d)| (m, d))) else {
+            return true;
+        };
+        match doc {
+            Doc::Text(s) => match s.find('\n') {
+                Some(i) => return remaining >= width(&s[..i]) as isize,
+                None => remaining -= width(s) as isize,
+            },
+            Doc::Line | Doc::SoftLine if mode == Mode::Flat => {
+                remaining -= matches!(doc, Doc::Line) as isize;
+            }
+            Doc::Line | Doc::SoftLine | Doc::HardLine => return true,
+            Doc::Concat(docs) => stack.extend(docs.iter().rev().map(|doc| (mode, doc))),
+            Doc::Group { doc, breaks } => {
+                stack.push((if *breaks { Mode::Break } else { mode }, doc));
+            }
+            Doc::Nest(doc) => stack.push((mode, doc)),
+            Doc::IfBreak(broken, flat) => {
+                stack.push((mode, if mode == Mode::Break { broken } else { flat }));
+            }
+        }
+    }
+    false
+}
+
+fn write_indent(out: &mut String, level: usize, config: &FormatConfig) -> usize {
+    if config.use_tabs {
+        out.extend(std::iter::repeat_n('\t', level));
+    } else {
+        out.extend(std::iter::repeat_n(' ', level * config.indent_width));
+    }
+    level * config.indent_width
+}
+
+fn trim_end(out: &mut String) {
+    out.truncate(out.trim_end_matches([' ', '\t']).len());
+}
+
+fn width(s: &str) -> usize {
+    s.chars().count()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn list(items: &[&'static str]) -> Doc {
+        let items = items.iter().map(|&s| Doc::text(s));
+        Doc::group(Doc::concat([
+            Doc::text("f("),
+            Doc::nest(Doc::concat([
+                Doc::SoftLine,
+                Doc::join(items, Doc::concat([Doc::text(","), Doc::Line])),
+                Doc::if_break(Doc::text(",")),
+            ])),
+            Doc::SoftLine,
+            Doc::text(");"),
+        ]))
+    }
+
+    #[test]
+    fn groups() {
+        let config = FormatConfig { line_width: 10, ..Default::default() };
+        assert_eq!(render(&list(&["a", "b"]), &config), "f(a, b);\n");
+        assert_eq!(render(&list(&["aaa", "bbb"]), &config), "f(\n    aaa,\n    bbb,\n);\n");
+
+        let config = FormatConfig { line_width: 10, use_tabs: true, ..Default::default() };
+        assert_eq!(render(&list(&["aaa", "bbb"]), &config), "f(\n\taaa,\n\tbbb,\n);\n");
+
+        let doc = Doc::concat([Doc::text("a"), Doc::Line, Doc::text("b"), Doc::HardLine]);
+        let doc = Doc::group(Doc::concat([doc, Doc::HardLine, Doc::text("c")]));
+        assert_eq!(render(&doc, &config), "a\nb\n\nc\n");
+    }
+}
@@ -0,0 +1,160 @@
+use super::{Doc, Printer};
+use crate::ast::*;
+use solar_interface::BytePos;
+
+impl Printer<'_> {
+    /// Prints a block. The comments before `close` are printed at the end of the block.
+    pub(super) fn block(&mut self, block: &[Stmt<'_>], close: Option<BytePos>) -> Doc {
+        let stmts = self.lines(block, close, |stmt| stmt.span, |_, _| false, Self::stmt);
+        self.braces(stmts)
+    }
+
+    pub(super) fn stmt(&mut self, stmt: &Stmt<'_>) -> Doc {
+        let hi = Some(stmt.span.hi());
+        let doc = match &stmt.kind {
+            StmtKind::Assembly(assembly) => self.assembly(assembly, hi),
+            StmtKind::DeclSingle(var) => Doc::concat([self.var(var), Doc::text(";")]),
+            StmtKind::DeclMulti(vars, expr) => {
+                let vars =
+                    vars.iter().map(|var| var.as_ref().map_or(Doc::NIL, |v| self.var(v))).collect();
+                Doc::concat([
+                    self.list("(", vars, ")"),
+                    Doc::text(" = "),
+                    self.expr(expr),
+                    Doc::text(";"),
+                ])
+            }
+            StmtKind::Block(block) => self.block(block, hi),
+            StmtKind::Break => Doc::text("break;"),
+            StmtKind::Continue => Doc::text("continue;"),
+            StmtKind::DoWhile(body, cond) => {
+                let separator = if matches!(body.kind, StmtKind::Block(_)) {
+                    Doc::text(" ")
+                } else {
+                    Doc::HardLine
+                };
+                Doc::concat([
+                    Doc::text("do"),
+                    self.body(body),
+                    separator,
+                    Doc::text("while ("),
+                    self.expr(cond),
+                    Doc::text(");"),
+                ])
+            }
+            StmtKind::Emit(path, args) => Doc::concat([
+                Doc::text("emit "),
+                self.path(path),
+                self.call_args(args),
+                Doc::text(";"),
+            ]),
+            StmtKind::Expr(expr) => Doc::concat([self.expr(expr), Doc::text(";")]),
+            StmtKind::For { init, cond, next, body } => {
+                let init = init.as_ref().map_or(Doc::text(";"), |init| self.stmt(init));
+                let opt = |expr: &Option<Box<'_, Expr<'_>>>| {
+                    expr.as_deref()
+                        .map_or(Doc::NIL, |e| Doc::concat([Doc::text(" "), self.expr(e)]))
+                };
+                Doc::concat([
+                    Doc::text("for ("),
+                    init,
+                    opt(cond),
+                    Doc::text(";"),
+                    opt(next),
+                    Doc::text(")"),
+                    self.body(body),
+                ])
+            }
+            StmtKind::If(cond, then, else_) => {
+                let mut doc = vec![Doc::text("if ("), self.expr(cond), Doc::text(")")];
+                doc.push(self.body(then));
+                if let Some(else_) = else_ {
+                    doc.push(if matches!(then.kind, StmtKind::Block(_)) {
+                        Doc::text(" ")
+                    } else {
+                        Doc::HardLine
+                    });
+                    doc.push(Doc::text("else"));
+                    if let StmtKind::If(..) = else_.kind {
+                        doc.push(Doc::text(" "));
+                        doc.push(self.stmt(else_));
+                    } else {
+                        doc.push(self.body(else_));
+                    }
+                }
+                Doc::concat(doc)
+            }
+            StmtKind::Return(expr) => match expr {
+                Some(expr) => Doc::concat([Doc::text("return "), self.expr(expr), Doc::text(";")]),
+                None => Doc::text("return;"),
+            },
+            StmtKind::Revert(path, args) => Doc::concat([
+                Doc::text("revert "),
+                self.path(path),
+                self.call_args(args),
+                Doc::text(";"),
+            ]),
+            StmtKind::Try(try_) => self.try_(try_, hi),
+            StmtKind::UncheckedBlock(block) => {
+                Doc::concat([Doc::text("unchecked "), self.block(block, hi)])
+            }
+            StmtKind::While(cond, body) => Doc::concat([
+                Doc::text("while ("),
+                self.expr(cond),
+                Doc::text(")"),
+                self.body(body),
+            ]),
+            StmtKind::Placeholder => Doc::text("_;"),
+        };
+        Doc::concat([self.docs(&stmt.docs), doc])
+    }
+
+    /// Prints an assembly block. The comments before `close` are printed at the end of the block.
+    pub(super) fn assembly(&mut self, assembly: &StmtAssembly<'_>, close: Option<BytePos>) -> Doc {
+        let mut doc = vec![Doc::text("assembly ")];
+        if let Some(dialect) = &assembly.dialect {
+            doc.push(self.str_lit(dialect));
+            doc.push(Doc::text(" "));
+        }
+        if !assembly.flags.is_empty() {
+            let flags = assembly.flags.iter().map(|flag| self.str_lit(flag)).collect();
+            doc.push(self.list("(", flags, ")"));
+            doc.push(Doc::text(" "));
+        }
+        doc.push(self.yul_block(&assembly.block, close));
+        Doc::concat(doc)
+    }
+
+    /// Prints a `try` statement. The comments before `close` are printed at the end of the last
+    /// `catch` clause.
+    pub(super) fn try_(&mut self, try_: &StmtTry<'_>, close: Option<BytePos>) -> Doc {
+        let mut doc = vec![Doc::text("try "), self.expr(&try_.expr)];
+        if !try_.returns.is_empty() {
+            doc.push(Doc::text(" returns "));
+            doc.push(self.params(&try_.returns));
+        }
+        doc.push(Doc::text(" "));
+        doc.push(self.block(&try_.block, None));
+        for (i, catch) in try_.catch.iter().enumerate() {
+            doc.push(Doc::text(" catch "));
+            if let Some(name) = catch.name {
+                doc.push(self.ident(name));
+            }
+            if catch.name.is_some() || !catch.args.is_empty() {
+                doc.push(self.params(&catch.args));
+                doc.push(Doc::text(" "));
+            }
+            let close = if i == try_.catch.len() - 1 { close } else { None };
+            doc.push(self.block(&catch.block, close));
+        }
+        Doc::concat(doc)
+    }
+
+    /// Prints the body of a control flow statement, including the leading space or line break.
+    fn body(&mut self, body: &Stmt<'_>) -> Doc {
+        if matches!(body.kind, StmtKind::Block(_)) {
+            return Doc::concat([Doc::text(" "), self.stmt(body)]);
+        }
+        Doc::group(Doc::nest(Doc::concat([Doc::Line, self.stmt(body)])))
+    }
+}
@@ -0,0 +1,127 @@
+use super::{Doc, Printer};
+use crate::ast::yul::*;
+use solar_interface::{BytePos, Ident};
+
+impl Printer<'_> {
+    /// Prints a Yul block. The comments before `close` are printed at the end of the block.
+    pub(super) fn yul_block(&mut self, block: &[Stmt<'_>], close: Option<BytePos>) -> Doc {
+        let stmts = self.lines(block, close, |stmt| stmt.span, |_, _| false, Self::yul_stmt);
+        self.braces(stmts)
+    }
+
+    /// Prints a Yul block on a single line if it contains a single statement that fits, like the
+    /// initialization and post-iteration blocks of `for` loops.
+    fn yul_inline_block(&mut self, block: &[Stmt<'_>]) -> Doc {
+        match block {
+            [stmt] if self.peek_comment(stmt.span.hi()).is_none() => Doc::group(Doc::concat([
+                Doc::text("{"),
+                Doc::nest(Doc::concat([Doc::Line, self.yul_stmt(stmt)])),
+                Doc::Line,
+                Doc::text("}"),
+            ])),
+            _ => self.yul_block(block, None),
+        }
+    }
+
+    pub(super) fn yul_stmt(&mut self, stmt: &Stmt<'_>) -> Doc {
+        let hi = Some(stmt.span.hi());
+        let doc = match &stmt.kind {
+            StmtKind::Block(block) => self.yul_block(block, hi),
+            StmtKind::AssignSingle(path, expr) => {
+                Doc::concat([self.path(path), Doc::text(" := "), self.yul_expr(expr)])
+            }
+            StmtKind::AssignMulti(paths, call) => Doc::concat([
+                Doc::join(paths.iter().map(|path| self.path(path)), Doc::text(", ")),
+                Doc::text(" := "),
+                self.yul_call(call),
+            ]),
+            StmtKind::Expr(call) => self.yul_call(call),
+            StmtKind::If(cond, block) => Doc::concat([
+                Doc::text("if "),
+                self.yul_expr(cond),
+                Doc::text(" "),
+                self.yul_block(block, hi),
+            ]),
+            StmtKind::For { init, cond, step, body } => Doc::concat([
+                Doc::text("for "),
+                self.yul_inline_block(init),
+                Doc::text(" "),
+                self.yul_expr(cond),
+                Doc::text(" "),
+                self.yul_inline_block(step),
+                Doc::text(" "),
+                self.yul_block(body, hi),
+            ]),
+            StmtKind::Switch(switch) => self.yul_switch(switch, hi),
+            StmtKind::Leave => Doc::text("leave"),
+            StmtKind::Break => Doc::text("break"),
+            StmtKind::Continue => Doc::text("continue"),
+            StmtKind::FunctionDef(function) => self.yul_function(function, hi),
+            StmtKind::VarDecl(idents, expr) => {
+                let idents = idents.iter().map(|&ident| self.ident(ident));
+                let mut doc = vec![Doc::text("let "), Doc::join(idents, Doc::text(", "))];
+                if let Some(expr) = expr {
+                    doc.push(Doc::text(" := "));
+                    doc.push(self.yul_expr(expr));
+                }
+                Doc::concat(doc)
+            }
+        };
+        Doc::concat([self.docs(&stmt.docs), doc])
+    }
+
+    /// Prints a `switch` statement. The comments before `close` are printed at the end of the last
+    /// case.
+    pub(super) fn yul_switch(&mut self, switch: &StmtSwitch<'_>, close: Option<BytePos>) -> Doc {
+        let mut doc = vec![Doc::text("switch "), self.yul_expr(&switch.selector)];
+        for (i, case) in switch.branches.iter().enumerate() {
+            let last = i == switch.branches.len() - 1 && switch.default_case.is_none();
+            doc.push(Doc::HardLine);
+            doc.push(Doc::text("case "));
+            doc.push(self.lit(&case.constant));
+            doc.push(Doc::text(" "));
+            doc.push(self.yul_block(&case.body, if last { close } else { None }));
+        }
+        if let Some(default) = &switch.default_case {
+            doc.push(Doc::HardLine);
+            doc.push(Doc::text("default "));
+            doc.push(self.yul_block(default, close));
+        }
+        Doc::concat(doc)
+    }
+
+    /// Prints a function definition. The comments before `close` are printed at the end of its
+    /// body.
+    pub(super) fn yul_function(&mut self, function: &Function<'_>, close: Option<BytePos>) -> Doc {
+        let idents = |idents: &[Ident]| {
+            Doc::join(idents.iter().map(|&ident| self.ident(ident)), Doc::text(", "))
+        };
+        let mut doc = vec![
+            Doc::text("function "),
+            self.ident(function.name),
+            Doc::text("("),
+            idents(&function.parameters[..]),
+            Doc::text(")"),
+        ];
+        if !function.returns.is_empty() {
+            doc.push(Doc::text(" -> "));
+            doc.push(idents(&function.returns[..]));
+        }
+        doc.push(Doc::text(" "));
+        doc.push(self.yul_block(&function.body, close));
+        Doc::concat(doc)
+    }
+
+    pub(super) fn yul_expr(&self, expr: &Expr<'_>) -> Doc {
+        match &expr.kind {
+            ExprKind::Path(path) => self.path(path),
+            ExprKind::Call(call) => self.yul_call(call),
+            ExprKind::Lit(lit) => self.lit(lit),
+        }
+    }
+
+    fn yul_call(&self, call: &ExprCall<'_>) -> Doc {
+        let arguments = call.arguments.iter().map(|arg| self.yul_expr(arg)).collect();
+        Doc::concat([self.ident(call.name), self.list("(", arguments, ")")])
+    }
+}
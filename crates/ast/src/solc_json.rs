@@ -0,0 +1,1199 @@
+//! Conversion from solc's AST JSON into the solar AST.
+//!
+//! Reference: <https://docs.soliditylang.org/en/latest/using-the-compiler.html#output-description>
+
+use crate::ast::*;
+use alloy_primitives::Address;
+use serde_json::{Map, Value};
+use solar_data_structures::BumpExt;
+use solar_interface::BytePos;
+use std::{cell::Cell, fmt};
+
+type Node = Map<String, Value>;
+type Result<T, E = SolcJsonError> = std::result::Result<T, E>;
+
+macro_rules! bail {
+    ($($arg:tt)*) => {
+        return Err(SolcJsonError { msg: format!($($arg)*) })
+    };
+}
+
+/// An error that occurred while converting solc AST JSON. See [`from_solc_json`].
+#[derive(Clone, Debug)]
+pub struct SolcJsonError {
+    msg: String,
+}
+
+impl fmt::Display for SolcJsonError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid solc AST JSON: {}", self.msg)
+    }
+}
+
+impl std::error::Error for SolcJsonError {}
+
+/// Converts a solc `SourceUnit` AST JSON node into a [`SourceUnit`].
+///
+/// Source locations are interpreted as offsets from [`BytePos(0)`](BytePos). Use
+/// [`SolcJsonReader::with_base_pos`] to offset them into a source file loaded in the source map.
+///
+/// Must be called inside of a session, since identifiers need to be interned.
+pub fn from_solc_json<'ast>(arena: &'ast Arena, json: &Value) -> Result<SourceUnit<'ast>> {
+    SolcJsonReader::new(arena).source_unit(json)
+}
+
+/// Converts solc AST JSON nodes into AST nodes allocated on an [`Arena`].
+///
+/// Information that is only present after semantic analysis, such as referenced declarations
+/// and type descriptions, is ignored.
+pub struct SolcJsonReader<'ast> {
+    arena: &'ast Arena,
+    base: BytePos,
+    next_id: Cell<NodeId>,
+}
+
+impl<'ast> SolcJsonReader<'ast> {
+    /// Creates a new reader that allocates on the given arena.
+    pub fn new(arena: &'ast Arena) -> Self {
+        Self { arena, base: BytePos(0), next_id: Cell::new(NodeId::new(0)) }
+    }
+
+    /// Sets the position that `src` offsets are relative to.
+    ///
+    /// This is usually the `start_pos` of the source file that the AST was produced from.
+    pub fn with_base_pos(mut self, base: BytePos) -> Self {
+        self.base = base;
+        self
+    }
+
+    /// Converts a `SourceUnit` node.
+    pub fn source_unit(&self, json: &Value) -> Result<SourceUnit<'ast>> {
+        let node = expect_node(json, "SourceUnit")?;
+        let items = self.list(node, "nodes", |v| self.item(v))?;
+        Ok(SourceUnit::new(items))
+    }
+
+    fn list<T>(
+        &self,
+        node: &Node,
+        key: &str,
+        f: impl FnMut(&Value) -> Result<T>,
+    ) -> Result<Box<'ast, [T]>> {
+        self.vec(node, key, f).map(|values| self.arena.alloc_vec(values))
+    }
+
+    fn vec<T>(&self, node: &Node, key: &str, f: impl FnMut(&Value) -> Result<T>) -> Result<Vec<T>> {
+        match opt_field(node, key) {
+            Some(Value::Array(values)) => values.iter().map(f).collect(),
+            Some(value) => bail!("expected `{key}` to be an array, got {value}"),
+            None => Ok(Vec::new()),
+        }
+    }
+
+    /// Returns a new node ID. IDs are assigned in conversion order, like in the parser.
+    fn next_id(&self) -> NodeId {
+        let id = self.next_id.get();
+        self.next_id.set(NodeId::new(id.get() + 1));
+        id
+    }
+
+    fn alloc<T>(&self, value: T) -> Box<'ast, T> {
+        self.arena.alloc(value)
+    }
+
+    fn span(&self, node: &Node) -> Result<Span> {
+        self.parse_src(str_field(node, "src")?)
+    }
+
+    fn parse_src(&self, src: &str) -> Result<Span> {
+        let mut parts = src.split(':');
+        let (Some(start), Some(len)) = (parts.next(), parts.next()) else {
+            bail!("invalid source location {src:?}");
+        };
+        if start == "-1" || len == "-1" {
+            return Ok(Span::DUMMY);
+        }
+        let (Ok(start), Ok(len)) = (start.parse::<u32>(), len.parse::<u32>()) else {
+            bail!("invalid source location {src:?}");
+        };
+        let Some(lo) = self.base.0.checked_add(start) else {
+            bail!("source location {src:?} is out of bounds");
+        };
+        let Some(hi) = lo.checked_add(len) else {
+            bail!("source location {src:?} is out of bounds");
+        };
+        Ok(Span::new(BytePos(lo), BytePos(hi)))
+    }
+
+    /// Returns the span of `key` if present, otherwise the span of the node itself.
+    fn location(&self, node: &Node, key: &str) -> Result<Span> {
+        match opt_field(node, key).and_then(Value::as_str) {
+            Some(src) => self.parse_src(src),
+            None => self.span(node),
+        }
+    }
+
+    fn ident(&self, name: &str, span: Span) -> Ident {
+        Ident::new(Symbol::intern(name), span)
+    }
+
+    /// The `name` of a declaration, located at `nameLocation`.
+    fn name(&self, node: &Node) -> Result<Ident> {
+        Ok(self.ident(str_field(node, "name")?, self.location(node, "nameLocation")?))
+    }
+
+    fn opt_name(&self, node: &Node) -> Result<Option<Ident>> {
+        match opt_field(node, "name").and_then(Value::as_str) {
+            Some("") | None => Ok(None),
+            Some(_) => self.name(node).map(Some),
+        }
+    }
+
+    fn docs(&self, node: &Node) -> Result<DocComments<'ast>> {
+        let (text, span) = match opt_field(node, "documentation") {
+            Some(Value::String(text)) => (text.as_str(), self.span(node)?.shrink_to_lo()),
+            Some(doc @ Value::Object(_)) => {
+                let doc = expect_node(doc, "StructuredDocumentation")?;
+                (str_field(doc, "text")?, self.span(doc)?)
+            }
+            Some(value) => bail!("invalid documentation {value}"),
+            None => return Ok(self.arena.alloc_vec(Vec::new())),
+        };
+        let kind = if text.contains('\n') { CommentKind::Block } else { CommentKind::Line };
+        Ok(self.arena.alloc_as_slice(DocComment { kind, span, symbol: Symbol::intern(text) }))
+    }
+
+    fn item(&self, json: &Value) -> Result<Item<'ast>> {
+        let node = as_node(json)?;
+        let span = self.span(node)?;
+        let docs = self.docs(node)?;
+        let kind = match node_type(node)? {
+            "PragmaDirective" => {
+                let literals =
+                    self.vec(node, "literals", |v| as_str(v).map(ToString::to_string))?;
+                let Some((name, rest)) = literals.split_first() else {
+                    bail!("empty pragma directive");
+                };
+                let name = IdentOrStrLit::Ident(self.ident(name, span));
+                let value = (!rest.is_empty()).then(|| {
+                    IdentOrStrLit::StrLit(StrLit { span, value: Symbol::intern(&rest.concat()) })
+                });
+                ItemKind::Pragma(PragmaDirective { tokens: PragmaTokens::Custom(name, value) })
+            }
+            "ImportDirective" => {
+                let path = StrLit { span, value: Symbol::intern(str_field(node, "file")?) };
+                let aliases = self.list(node, "symbolAliases", |v| {
+                    let alias = as_node(v)?;
+                    let foreign = field(alias, "foreign").and_then(as_node)?;
+                    let name = self.name(foreign)?;
+                    let local = match opt_field(alias, "local").and_then(Value::as_str) {
+                        Some(local) => {
+                            Some(self.ident(local, self.location(alias, "nameLocation")?))
+                        }
+                        None => None,
+                    };
+                    Ok((name, local))
+                })?;
+                let items = if !aliases.is_empty() {
+                    ImportItems::Aliases(aliases)
+                } else {
+                    match opt_field(node, "unitAlias").and_then(Value::as_str) {
+                        Some("") | None => ImportItems::Plain(None),
+                        Some(alias) => {
+                            let span = self.location(node, "nameLocation")?;
+                            ImportItems::Plain(Some(self.ident(alias, span)))
+                        }
+                    }
+                };
+                ItemKind::Import(ImportDirective { path, items })
+            }
+            "UsingForDirective" => ItemKind::Using(self.using_directive(node)?),
+            "ContractDefinition" => {
+                let kind = match str_field(node, "contractKind")? {
+                    "contract" if bool_field(node, "abstract") => ContractKind::AbstractContract,
+                    "contract" => ContractKind::Contract,
+                    "interface" => ContractKind::Interface,
+                    "library" => ContractKind::Library,
+                    kind => bail!("unknown contract kind {kind:?}"),
+                };
+                let bases = self.list(node, "baseContracts", |v| {
+                    let base = expect_node(v, "InheritanceSpecifier")?;
+                    let name = self.path(field(base, "baseName").and_then(as_node)?)?;
+                    let arguments = self.call_args(base, "arguments", None)?;
+                    Ok(Modifier { name, arguments })
+                })?;
+                let body = self.list(node, "nodes", |v| self.item(v))?;
+                ItemKind::Contract(ItemContract { kind, name: self.name(node)?, bases, body })
+            }
+            "FunctionDefinition" | "ModifierDefinition" => ItemKind::Function(self.function(node)?),
+            "VariableDeclaration" => ItemKind::Variable(self.variable_definition(node)?),
+            "StructDefinition" => ItemKind::Struct(ItemStruct {
+                name: self.name(node)?,
+                fields: self.list(node, "members", |v| {
+                    self.variable_definition(expect_node(v, "VariableDeclaration")?)
+                })?,
+            }),
+            "EnumDefinition" => ItemKind::Enum(ItemEnum {
+                name: self.name(node)?,
+                variants: self
+                    .list(node, "members", |v| self.name(expect_node(v, "EnumValue")?))?,
+            }),
+            "UserDefinedValueTypeDefinition" => ItemKind::Udvt(ItemUdvt {
+                name: self.name(node)?,
+                ty: self.ty(field(node, "underlyingType")?)?,
+            }),
+            "ErrorDefinition" => ItemKind::Error(ItemError {
+                name: self.name(node)?,
+                parameters: self.parameter_list(field(node, "parameters")?)?,
+            }),
+            "EventDefinition" => ItemKind::Event(ItemEvent {
+                name: self.name(node)?,
+                parameters: self.parameter_list(field(node, "parameters")?)?,
+                anonymous: bool_field(node, "anonymous"),
+            }),
+            ty => bail!("unknown item node type {ty:?}"),
+        };
+        Ok(Item { id: self.next_id(), docs, span, kind })
+    }
+
+    fn using_directive(&self, node: &Node) -> Result<UsingDirective<'ast>> {
+        let list = if let Some(library) = opt_field(node, "libraryName") {
+            UsingList::Single(self.path(as_node(library)?)?)
+        } else {
+            UsingList::Multiple(self.list(node, "functionList", |v| {
+                let entry = as_node(v)?;
+                if let Some(function) = opt_field(entry, "function") {
+                    return Ok((self.path(as_node(function)?)?, None));
+                }
+                let definition = self.path(field(entry, "definition").and_then(as_node)?)?;
+                let op = user_definable_operator(str_field(entry, "operator")?)?;
+                Ok((definition, Some(op)))
+            })?)
+        };
+        let ty = opt_field(node, "typeName").map(|ty| self.ty(ty)).transpose()?;
+        Ok(UsingDirective { list, ty, global: bool_field(node, "global") })
+    }
+
+    fn function(&self, node: &Node) -> Result<ItemFunction<'ast>> {
+        let is_modifier = node_type(node)? == "ModifierDefinition";
+        let kind = if is_modifier {
+            FunctionKind::Modifier
+        } else {
+            match opt_field(node, "kind").and_then(Value::as_str) {
+                Some("function" | "freeFunction") | None => FunctionKind::Function,
+                Some("constructor") => FunctionKind::Constructor,
+                Some("fallback") => FunctionKind::Fallback,
+                Some("receive") => FunctionKind::Receive,
+                Some(kind) => bail!("unknown function kind {kind:?}"),
+            }
+        };
+        // solc always fills in the visibility, even where it can't be written in the source.
+        let is_free = opt_field(node, "kind").and_then(Value::as_str) == Some("freeFunction");
+        let visibility = if is_modifier || is_free || kind.is_constructor() {
+            None
+        } else {
+            opt_field(node, "visibility").and_then(Value::as_str).map(visibility).transpose()?
+        };
+        let state_mutability = match opt_field(node, "stateMutability").and_then(Value::as_str) {
+            Some(s) => state_mutability(s)?,
+            None => StateMutability::NonPayable,
+        };
+        let modifiers = self.list(node, "modifiers", |v| {
+            let modifier = expect_node(v, "ModifierInvocation")?;
+            let name = self.path(field(modifier, "modifierName").and_then(as_node)?)?;
+            let arguments = self.call_args(modifier, "arguments", None)?;
+            Ok(Modifier { name, arguments })
+        })?;
+        let returns = match opt_field(node, "returnParameters") {
+            Some(returns) => self.parameter_list(returns)?,
+            None => self.arena.alloc_vec(Vec::new()),
+        };
+        let header = FunctionHeader {
+            name: if kind.is_ordinary() || kind.is_modifier() {
+                self.opt_name(node)?
+            } else {
+                None
+            },
+            parameters: self.parameter_list(field(node, "parameters")?)?,
+            visibility,
+            state_mutability,
+            modifiers,
+            virtual_: bool_field(node, "virtual"),
+            override_: self.override_(node)?,
+            returns,
+        };
+        let body = opt_field(node, "body").map(|body| self.block(body)).transpose()?;
+        Ok(ItemFunction { kind, header, body })
+    }
+
+    fn override_(&self, node: &Node) -> Result<Option<Override<'ast>>> {
+        let Some(overrides) = opt_field(node, "overrides") else { return Ok(None) };
+        let overrides = expect_node(overrides, "OverrideSpecifier")?;
+        let paths = self.list(overrides, "overrides", |v| self.path(as_node(v)?))?;
+        Ok(Some(Override { span: self.span(overrides)?, paths }))
+    }
+
+    fn parameter_list(&self, json: &Value) -> Result<ParameterList<'ast>> {
+        let node = expect_node(json, "ParameterList")?;
+        self.list(node, "parameters", |v| {
+            self.variable_definition(expect_node(v, "VariableDeclaration")?)
+        })
+    }
+
+    fn variable_definition(&self, node: &Node) -> Result<VariableDefinition<'ast>> {
+        let state_variable = bool_field(node, "stateVariable");
+        let mutability = match opt_field(node, "mutability").and_then(Value::as_str) {
+            Some("mutable") => None,
+            Some("immutable") => Some(VarMut::Immutable),
+            Some("constant") => Some(VarMut::Constant),
+            Some(m) => bail!("unknown variable mutability {m:?}"),
+            None => bool_field(node, "constant").then_some(VarMut::Constant),
+        };
+        let visibility = if state_variable {
+            opt_field(node, "visibility").and_then(Value::as_str).map(visibility).transpose()?
+        } else {
+            None
+        };
+        let data_location = match opt_field(node, "storageLocation").and_then(Value::as_str) {
+            Some("default") | None => None,
+            Some("storage") => Some(DataLocation::Storage),
+            Some("transient") => Some(DataLocation::Transient),
+            Some("memory") => Some(DataLocation::Memory),
+            Some("calldata") => Some(DataLocation::Calldata),
+            Some(loc) => bail!("unknown data location {loc:?}"),
+        };
+        let Some(ty) = opt_field(node, "typeName") else {
+            bail!("variable declarations without a type name are not supported");
+        };
+        Ok(VariableDefinition {
+            id: self.next_id(),
+            span: self.span(node)?,
+            ty: self.ty(ty)?,
+            visibility,
+            mutability,
+            data_location,
+            override_: self.override_(node)?,
+            indexed: bool_field(node, "indexed"),
+            name: self.opt_name(node)?,
+            initializer: opt_field(node, "value").map(|v| self.expr_box(v)).transpose()?,
+        })
+    }
+
+    /// An `IdentifierPath`, or an `Identifier` in older solc versions.
+    fn path(&self, node: &Node) -> Result<AstPath<'ast>> {
+        let name = str_field(node, "name")?;
+        let span = self.span(node)?;
+        let locations = match opt_field(node, "nameLocations") {
+            Some(Value::Array(locations)) => Some(locations),
+            _ => None,
+        };
+        let segments = name
+            .split('.')
+            .enumerate()
+            .map(|(i, segment)| {
+                let span = match locations.and_then(|l| l.get(i)) {
+                    Some(src) => self.parse_src(as_str(src)?)?,
+                    None => span,
+                };
+                Ok(self.ident(segment, span))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        Ok(PathSlice::from_mut_slice(self.arena.alloc_vec(segments)))
+    }
+
+    /// Converts an `Identifier` or `MemberAccess` expression chain into a path.
+    fn expr_path(&self, node: &Node) -> Result<AstPath<'ast>> {
+        let mut segments = Vec::new();
+        let mut current = node;
+        loop {
+            match node_type(current)? {
+                "Identifier" => {
+                    segments.push(self.ident(str_field(current, "name")?, self.span(current)?));
+                    break;
+                }
+                "MemberAccess" => {
+                    let span = self.location(current, "memberLocation")?;
+                    segments.push(self.ident(str_field(current, "memberName")?, span));
+                    current = field(current, "expression").and_then(as_node)?;
+                }
+                ty => bail!("expected a path expression, got {ty:?}"),
+            }
+        }
+        segments.reverse();
+        Ok(PathSlice::from_mut_slice(self.arena.alloc_vec(segments)))
+    }
+
+    fn ty(&self, json: &Value) -> Result<Type<'ast>> {
+        let node = as_node(json)?;
+        let span = self.span(node)?;
+        let kind = match node_type(node)? {
+            "ElementaryTypeName" => {
+                let name = str_field(node, "name")?;
+                let Some(mut ty) = elementary_type(name) else {
+                    bail!("unknown elementary type {name:?}");
+                };
+                if let ElementaryType::Address(payable) = &mut ty {
+                    if opt_field(node, "stateMutability").and_then(Value::as_str) == Some("payable")
+                    {
+                        *payable = true;
+                    }
+                }
+                TypeKind::Elementary(ty)
+            }
+            "ArrayTypeName" => TypeKind::Array(self.alloc(TypeArray {
+                element: self.ty(field(node, "baseType")?)?,
+                size: opt_field(node, "length").map(|v| self.expr_box(v)).transpose()?,
+            })),
+            "FunctionTypeName" => {
+                let visibility = match str_field(node, "visibility")? {
+                    "external" => Some(Visibility::External),
+                    _ => None,
+                };
+                TypeKind::Function(self.alloc(TypeFunction {
+                    parameters: self.parameter_list(field(node, "parameterTypes")?)?,
+                    visibility,
+                    state_mutability: state_mutability(str_field(node, "stateMutability")?)?,
+                    returns: self.parameter_list(field(node, "returnParameterTypes")?)?,
+                }))
+            }
+            "Mapping" => {
+                let opt_name = |name: &str, location: &str| -> Result<Option<Ident>> {
+                    match opt_field(node, name).and_then(Value::as_str) {
+                        Some("") | None => Ok(None),
+                        Some(n) => Ok(Some(self.ident(n, self.location(node, location)?))),
+                    }
+                };
+                TypeKind::Mapping(self.alloc(TypeMapping {
+                    key: self.ty(field(node, "keyType")?)?,
+                    key_name: opt_name("keyName", "keyNameLocation")?,
+                    value: self.ty(field(node, "valueType")?)?,
+                    value_name: opt_name("valueName", "valueNameLocation")?,
+                }))
+            }
+            "UserDefinedTypeName" => match opt_field(node, "pathNode") {
+                Some(path) => TypeKind::Custom(self.path(as_node(path)?)?),
+                None => TypeKind::Custom(self.path(node)?),
+            },
+            ty => bail!("unknown type node type {ty:?}"),
+        };
+        Ok(Type { id: self.next_id(), span, kind })
+    }
+
+    fn block(&self, json: &Value) -> Result<Block<'ast>> {
+        let node = as_node(json)?;
+        match node_type(node)? {
+            "Block" | "UncheckedBlock" => Ok(Block {
+                span: self.span(node)?,
+                stmts: self.list(node, "statements", |v| self.stmt(v))?,
+            }),
+            ty => bail!("expected a block, got {ty:?}"),
+        }
+    }
+
+    fn stmt(&self, json: &Value) -> Result<Stmt<'ast>> {
+        let node = as_node(json)?;
+        let span = self.span(node)?;
+        let docs = self.docs(node)?;
+        let kind = match node_type(node)? {
+            "Block" => StmtKind::Block(self.block(json)?),
+            "UncheckedBlock" => StmtKind::UncheckedBlock(self.block(json)?),
+            "InlineAssembly" => {
+                let flags = self.list(node, "flags", |v| {
+                    Ok(StrLit { span, value: Symbol::intern(as_str(v)?) })
+                })?;
+                let block = self.yul_block(field(node, "AST")?)?;
+                StmtKind::Assembly(StmtAssembly { dialect: None, flags, block })
+            }
+            "VariableDeclarationStatement" => {
+                let mut vars = self.vec(node, "declarations", |v| match v {
+                    Value::Null => Ok(None),
+                    v => self.variable_definition(as_node(v)?).map(Some),
+                })?;
+                let init = opt_field(node, "initialValue").map(|v| self.expr_box(v)).transpose()?;
+                if let [Some(_)] = &vars[..] {
+                    let mut var = vars.pop().unwrap().unwrap();
+                    var.initializer = init;
+                    StmtKind::DeclSingle(self.alloc(var))
+                } else if let Some(init) = init {
+                    StmtKind::DeclMulti(self.arena.alloc_vec(vars), init)
+                } else {
+                    bail!("multi-variable declarations must have an initial value");
+                }
+            }
+            "Break" => StmtKind::Break,
+            "Continue" => StmtKind::Continue,
+            "PlaceholderStatement" => StmtKind::Placeholder,
+            "DoWhileStatement" => StmtKind::DoWhile(
+                self.stmt_box(field(node, "body")?)?,
+                self.expr_box(field(node, "condition")?)?,
+            ),
+            "WhileStatement" => StmtKind::While(
+                self.expr_box(field(node, "condition")?)?,
+                self.stmt_box(field(node, "body")?)?,
+            ),
+            "EmitStatement" => {
+                let call = field(node, "eventCall").and_then(|v| expect_node(v, "FunctionCall"))?;
+                let path = self.expr_path(field(call, "expression").and_then(as_node)?)?;
+                StmtKind::Emit(path, self.call_args(call, "arguments", Some("names"))?)
+            }
+            "RevertStatement" => {
+                let call = field(node, "errorCall").and_then(|v| expect_node(v, "FunctionCall"))?;
+                let path = self.expr_path(field(call, "expression").and_then(as_node)?)?;
+                StmtKind::Revert(path, self.call_args(call, "arguments", Some("names"))?)
+            }
+            "ExpressionStatement" => StmtKind::Expr(self.expr_box(field(node, "expression")?)?),
+            "ForStatement" => StmtKind::For {
+                init: opt_field(node, "initializationExpression")
+                    .map(|v| self.stmt_box(v))
+                    .transpose()?,
+                cond: opt_field(node, "condition").map(|v| self.expr_box(v)).transpose()?,
+                next: opt_field(node, "loopExpression")
+                    .map(|v| {
+                        let stmt = expect_node(v, "ExpressionStatement")?;
+                        self.expr_box(field(stmt, "expression")?)
+                    })
+                    .transpose()?,
+                body: self.stmt_box(field(node, "body")?)?,
+            },
+            "IfStatement" => StmtKind::If(
+                self.expr_box(field(node, "condition")?)?,
+                self.stmt_box(field(node, "trueBody")?)?,
+                opt_field(node, "falseBody").map(|v| self.stmt_box(v)).transpose()?,
+            ),
+            "Return" => StmtKind::Return(
+                opt_field(node, "expression").map(|v| self.expr_box(v)).transpose()?,
+            ),
+            "TryStatement" => {
+                let expr = self.expr_box(field(node, "externalCall")?)?;
+                let clauses = match field(node, "clauses")? {
+                    Value::Array(clauses) => clauses,
+                    value => bail!("expected `clauses` to be an array, got {value}"),
+                };
+                let Some((success, catches)) = clauses.split_first() else {
+                    bail!("try statement without clauses");
+                };
+                let success = expect_node(success, "TryCatchClause")?;
+                let params = |clause: &Node| match opt_field(clause, "parameters") {
+                    Some(params) => self.parameter_list(params),
+                    None => Ok(self.arena.alloc_vec(Vec::new())),
+                };
+                let catch = catches
+                    .iter()
+                    .map(|v| {
+                        let clause = expect_node(v, "TryCatchClause")?;
+                        let name = match opt_field(clause, "errorName").and_then(Value::as_str) {
+                            Some("") | None => None,
+                            Some(name) => Some(self.ident(name, self.span(clause)?)),
+                        };
+                        let args = params(clause)?;
+                        let block = self.block(field(clause, "block")?)?;
+                        Ok(CatchClause { span: self.span(clause)?, name, args, block })
+                    })
+                    .collect::<Result<Vec<_>>>()?;
+                StmtKind::Try(self.alloc(StmtTry {
+                    expr,
+                    span: self.span(success)?,
+                    returns: params(success)?,
+                    block: self.block(field(success, "block")?)?,
+                    catch: self.arena.alloc_vec(catch),
+                }))
+            }
+            ty => bail!("unknown statement node type {ty:?}"),
+        };
+        Ok(Stmt { id: self.next_id(), docs, span, kind })
+    }
+
+    fn stmt_box(&self, json: &Value) -> Result<Box<'ast, Stmt<'ast>>> {
+        self.stmt(json).map(|stmt| self.alloc(stmt))
+    }
+
+    /// Reads the arguments of a call. If `names_key` is set and the names are not empty, the
+    /// arguments are named.
+    fn call_args(
+        &self,
+        node: &Node,
+        args_key: &str,
+        names_key: Option<&str>,
+    ) -> Result<CallArgs<'ast>> {
+        let names = match names_key.and_then(|key| opt_field(node, key)) {
+            Some(Value::Array(names)) if !names.is_empty() => names,
+            _ => return Ok(CallArgs::Unnamed(self.list(node, args_key, |v| self.expr_box(v))?)),
+        };
+        Ok(CallArgs::Named(self.named_args(node, args_key, names)?))
+    }
+
+    fn named_args(
+        &self,
+        node: &Node,
+        args_key: &str,
+        names: &[Value],
+    ) -> Result<NamedArgList<'ast>> {
+        let locations = match opt_field(node, "nameLocations") {
+            Some(Value::Array(locations)) => Some(locations),
+            _ => None,
+        };
+        let mut i = 0;
+        self.list(node, args_key, |v| {
+            let Some(name) = names.get(i) else { bail!("missing argument name") };
+            let value = self.expr_box(v)?;
+            let span = match locations.and_then(|l| l.get(i)) {
+                Some(src) => self.parse_src(as_str(src)?)?,
+                None => value.span,
+            };
+            i += 1;
+            Ok(NamedArg { name: self.ident(as_str(name)?, span), value })
+        })
+    }
+
+    fn expr(&self, json: &Value) -> Result<Expr<'ast>> {
+        let node = as_node(json)?;
+        let span = self.span(node)?;
+        let kind = match node_type(node)? {
+            "Assignment" => {
+                let op = match str_field(node, "operator")? {
+                    "=" => None,
+                    op => {
+                        let Some(kind) = op.strip_suffix('=').and_then(bin_op_kind) else {
+                            bail!("unknown assignment operator {op:?}");
+                        };
+                        Some(BinOp { span, kind })
+                    }
+                };
+                ExprKind::Assign(
+                    self.expr_box(field(node, "leftHandSide")?)?,
+                    op,
+                    self.expr_box(field(node, "rightHandSide")?)?,
+                )
+            }
+            "BinaryOperation" => {
+                let op = str_field(node, "operator")?;
+                let Some(kind) = bin_op_kind(op) else { bail!("unknown binary operator {op:?}") };
+                ExprKind::Binary(
+                    self.expr_box(field(node, "leftExpression")?)?,
+                    BinOp { span, kind },
+                    self.expr_box(field(node, "rightExpression")?)?,
+                )
+            }
+            "UnaryOperation" => {
+                let operand = self.expr_box(field(node, "subExpression")?)?;
+                let prefix = bool_field(node, "prefix");
+                let kind = match str_field(node, "operator")? {
+                    "delete" => {
+                        return Ok(Expr {
+                            id: self.next_id(),
+                            span,
+                            kind: ExprKind::Delete(operand),
+                        })
+                    }
+                    "++" if prefix => UnOpKind::PreInc,
+                    "--" if prefix => UnOpKind::PreDec,
+                    "++" => UnOpKind::PostInc,
+                    "--" => UnOpKind::PostDec,
+                    "!" => UnOpKind::Not,
+                    "-" => UnOpKind::Neg,
+                    "~" => UnOpKind::BitNot,
+                    op => bail!("unknown unary operator {op:?}"),
+                };
+                ExprKind::Unary(UnOp { span, kind }, operand)
+            }
+            "FunctionCall" => {
+                let callee = field(node, "expression").and_then(as_node)?;
+                let args = self.call_args(node, "arguments", Some("names"))?;
+                match node_type(callee)? {
+                    "ElementaryTypeNameExpression" if is_payable_conversion(callee) => {
+                        ExprKind::Payable(args)
+                    }
+                    "Identifier"
+                        if str_field(callee, "name")? == "type"
+                            && matches!(&args, CallArgs::Unnamed(args) if args.len() == 1) =>
+                    {
+                        let arg = field(node, "arguments").and_then(|v| match v {
+                            Value::Array(args) => Ok(&args[0]),
+                            _ => bail!("expected `arguments` to be an array"),
+                        })?;
+                        ExprKind::TypeCall(self.expr_ty(as_node(arg)?)?)
+                    }
+                    _ => ExprKind::Call(self.expr_box(field(node, "expression")?)?, args),
+                }
+            }
+            "FunctionCallOptions" => {
+                let names = match field(node, "names")? {
+                    Value::Array(names) => names,
+                    value => bail!("expected `names` to be an array, got {value}"),
+                };
+                ExprKind::CallOptions(
+                    self.expr_box(field(node, "expression")?)?,
+                    self.named_args(node, "options", names)?,
+                )
+            }
+            "Identifier" => ExprKind::Ident(self.ident(str_field(node, "name")?, span)),
+            "IndexAccess" => ExprKind::Index(
+                self.expr_box(field(node, "baseExpression")?)?,
+                IndexKind::Index(
+                    opt_field(node, "indexExpression").map(|v| self.expr_box(v)).transpose()?,
+                ),
+            ),
+            "IndexRangeAccess" => ExprKind::Index(
+                self.expr_box(field(node, "baseExpression")?)?,
+                IndexKind::Range(
+                    opt_field(node, "startExpression").map(|v| self.expr_box(v)).transpose()?,
+                    opt_field(node, "endExpression").map(|v| self.expr_box(v)).transpose()?,
+                ),
+            ),
+            "Literal" => {
+                let sub = match opt_field(node, "subdenomination").and_then(Value::as_str) {
+                    Some(sub) => Some(subdenomination(sub)?),
+                    None => None,
+                };
+                ExprKind::Lit(self.lit(node)?, sub)
+            }
+            "MemberAccess" => ExprKind::Member(
+                self.expr_box(field(node, "expression")?)?,
+                self.ident(str_field(node, "memberName")?, self.location(node, "memberLocation")?),
+            ),
+            "NewExpression" => ExprKind::New(self.ty(field(node, "typeName")?)?),
+            "Conditional" => ExprKind::Ternary(
+                self.expr_box(field(node, "condition")?)?,
+                self.expr_box(field(node, "trueExpression")?)?,
+                self.expr_box(field(node, "falseExpression")?)?,
+            ),
+            "TupleExpression" => {
+                if bool_field(node, "isInlineArray") {
+                    ExprKind::Array(self.list(node, "components", |v| self.expr_box(v))?)
+                } else {
+                    ExprKind::Tuple(self.list(node, "components", |v| match v {
+                        Value::Null => Ok(None),
+                        v => self.expr_box(v).map(Some),
+                    })?)
+                }
+            }
+            "ElementaryTypeNameExpression" => ExprKind::Type(self.expr_ty(node)?),
+            ty => bail!("unknown expression node type {ty:?}"),
+        };
+        Ok(Expr { id: self.next_id(), span, kind })
+    }
+
+    fn expr_box(&self, json: &Value) -> Result<Box<'ast, Expr<'ast>>> {
+        self.expr(json).map(|expr| self.alloc(expr))
+    }
+
+    /// Converts an expression that is used as a type, such as the argument to `type(...)`.
+    fn expr_ty(&self, node: &Node) -> Result<Type<'ast>> {
+        let span = self.span(node)?;
+        match node_type(node)? {
+            "ElementaryTypeNameExpression" => match field(node, "typeName")? {
+                // Older solc versions emit the type name as a string.
+                Value::String(name) => match elementary_type(name) {
+                    Some(ty) => {
+                        Ok(Type { id: self.next_id(), span, kind: TypeKind::Elementary(ty) })
+                    }
+                    None => bail!("unknown elementary type {name:?}"),
+                },
+                ty => self.ty(ty),
+            },
+            "Identifier" | "MemberAccess" => {
+                Ok(Type { id: self.next_id(), span, kind: TypeKind::Custom(self.expr_path(node)?) })
+            }
+            ty => bail!("expected a type expression, got {ty:?}"),
+        }
+    }
+
+    fn lit(&self, node: &Node) -> Result<&'ast mut Lit> {
+        let span = self.span(node)?;
+        let value = opt_field(node, "value").and_then(Value::as_str);
+        let hex_value = opt_field(node, "hexValue").and_then(Value::as_str);
+        let (symbol, kind) = match str_field(node, "kind")? {
+            "bool" => {
+                let value = value.unwrap_or_default();
+                (value, LitKind::Bool(value == "true"))
+            }
+            kind @ ("string" | "unicodeString" | "hexString") => {
+                let str_kind = match kind {
+                    "string" => StrKind::Str,
+                    "unicodeString" => StrKind::Unicode,
+                    _ => StrKind::Hex,
+                };
+                let bytes = match (hex_value, value) {
+                    (Some(hex), _) => match alloy_primitives::hex::decode(hex) {
+                        Ok(bytes) => bytes,
+                        Err(e) => bail!("invalid literal hex value {hex:?}: {e}"),
+                    },
+                    (None, Some(value)) => value.as_bytes().to_vec(),
+                    (None, None) => bail!("string literal without a value"),
+                };
+                let symbol = match str_kind {
+                    StrKind::Hex => hex_value.unwrap_or_default(),
+                    _ => value.unwrap_or_default(),
+                };
+                (symbol, LitKind::Str(str_kind, bytes.into()))
+            }
+            "number" => {
+                let Some(value) = value else { bail!("number literal without a value") };
+                (value, number_lit(value)?)
+            }
+            kind => bail!("unknown literal kind {kind:?}"),
+        };
+        Ok(self.arena.literals.alloc(Lit { span, symbol: Symbol::intern(symbol), kind }))
+    }
+
+    fn yul_block(&self, json: &Value) -> Result<yul::Block<'ast>> {
+        let node = expect_node(json, "YulBlock")?;
+        self.list(node, "statements", |v| self.yul_stmt(v))
+    }
+
+    fn yul_stmt(&self, json: &Value) -> Result<yul::Stmt<'ast>> {
+        let node = as_node(json)?;
+        let span = self.span(node)?;
+        let docs = self.arena.alloc_vec(Vec::new());
+        let kind = match node_type(node)? {
+            "YulBlock" => yul::StmtKind::Block(self.yul_block(json)?),
+            "YulAssignment" => {
+                let mut paths = self
+                    .vec(node, "variableNames", |v| self.path(expect_node(v, "YulIdentifier")?))?;
+                if paths.len() == 1 {
+                    yul::StmtKind::AssignSingle(
+                        paths.pop().unwrap(),
+                        self.yul_expr(field(node, "value")?)?,
+                    )
+                } else {
+                    yul::StmtKind::AssignMulti(
+                        self.arena.alloc_vec(paths),
+                        self.yul_call(field(node, "value").and_then(as_node)?)?,
+                    )
+                }
+            }
+            "YulExpressionStatement" => {
+                yul::StmtKind::Expr(self.yul_call(field(node, "expression").and_then(as_node)?)?)
+            }
+            "YulIf" => yul::StmtKind::If(
+                self.yul_expr(field(node, "condition")?)?,
+                self.yul_block(field(node, "body")?)?,
+            ),
+            "YulForLoop" => yul::StmtKind::For {
+                init: self.yul_block(field(node, "pre")?)?,
+                cond: self.yul_expr(field(node, "condition")?)?,
+                step: self.yul_block(field(node, "post")?)?,
+                body: self.yul_block(field(node, "body")?)?,
+            },
+            "YulSwitch" => {
+                let mut branches = Vec::new();
+                let mut default_case = None;
+                let cases = match field(node, "cases")? {
+                    Value::Array(cases) => cases,
+                    value => bail!("expected `cases` to be an array, got {value}"),
+                };
+                for case in cases {
+                    let case = expect_node(case, "YulCase")?;
+                    let body = self.yul_block(field(case, "body")?)?;
+                    match field(case, "value")? {
+                        Value::String(s) if s == "default" => default_case = Some(body),
+                        value => {
+                            let constant = self.yul_lit(expect_node(value, "YulLiteral")?)?;
+                            branches.push(yul::StmtSwitchCase { constant, body });
+                        }
+                    }
+                }
+                yul::StmtKind::Switch(yul::StmtSwitch {
+                    selector: self.yul_expr(field(node, "expression")?)?,
+                    branches: self.arena.alloc_vec(branches),
+                    default_case,
+                })
+            }
+            "YulLeave" => yul::StmtKind::Leave,
+            "YulBreak" => yul::StmtKind::Break,
+            "YulContinue" => yul::StmtKind::Continue,
+            "YulFunctionDefinition" => yul::StmtKind::FunctionDef(yul::Function {
+                name: self.name(node)?,
+                parameters: self.list(node, "parameters", |v| self.yul_typed_name(v))?,
+                returns: self.list(node, "returnVariables", |v| self.yul_typed_name(v))?,
+                body: self.yul_block(field(node, "body")?)?,
+            }),
+            "YulVariableDeclaration" => yul::StmtKind::VarDecl(
+                self.list(node, "variables", |v| self.yul_typed_name(v))?,
+                opt_field(node, "value").map(|v| self.yul_expr(v)).transpose()?,
+            ),
+            ty => bail!("unknown Yul statement node type {ty:?}"),
+        };
+        Ok(yul::Stmt { docs, span, kind })
+    }
+
+    fn yul_typed_name(&self, json: &Value) -> Result<Ident> {
+        self.name(expect_node(json, "YulTypedName")?)
+    }
+
+    fn yul_expr(&self, json: &Value) -> Result<yul::Expr<'ast>> {
+        let node = as_node(json)?;
+        let span = self.span(node)?;
+        let kind = match node_type(node)? {
+            "YulIdentifier" => yul::ExprKind::Path(self.path(node)?),
+            "YulFunctionCall" => yul::ExprKind::Call(self.yul_call(node)?),
+            "YulLiteral" => yul::ExprKind::Lit(self.yul_lit(node)?),
+            ty => bail!("unknown Yul expression node type {ty:?}"),
+        };
+        Ok(yul::Expr { span, kind })
+    }
+
+    fn yul_call(&self, node: &Node) -> Result<yul::ExprCall<'ast>> {
+        if node_type(node)? != "YulFunctionCall" {
+            bail!("expected a Yul function call, got {:?}", node_type(node)?);
+        }
+        let name = self.name(field(node, "functionName").and_then(as_node)?)?;
+        let arguments = self.list(node, "arguments", |v| self.yul_expr(v))?;
+        Ok(yul::ExprCall { name, arguments })
+    }
+
+    fn yul_lit(&self, node: &Node) -> Result<&'ast mut Lit> {
+        let mut node = node.clone();
+        // Yul string literals don't distinguish between kinds of strings.
+        if opt_field(&node, "hexValue").is_none() {
+            if let Some(Value::String(value)) = node.get("value") {
+                let hex = alloy_primitives::hex::encode(value);
+                node.insert("hexValue".into(), hex.into());
+            }
+        }
+        self.lit(&node)
+    }
+}
+
+fn as_node(value: &Value) -> Result<&Node> {
+    match value {
+        Value::Object(node) => Ok(node),
+        value => bail!("expected an object, got {value}"),
+    }
+}
+
+fn expect_node<'a>(value: &'a Value, expected: &str) -> Result<&'a Node> {
+    let node = as_node(value)?;
+    let ty = node_type(node)?;
+    if ty != expected {
+        bail!("expected node type {expected:?}, got {ty:?}");
+    }
+    Ok(node)
+}
+
+fn node_type(node: &Node) -> Result<&str> {
+    str_field(node, "nodeType")
+}
+
+fn field<'a>(node: &'a Node, key: &str) -> Result<&'a Value> {
+    match opt_field(node, key) {
+        Some(value) => Ok(value),
+        None => bail!("missing field `{key}`"),
+    }
+}
+
+/// Returns `None` if the field is missing or `null`.
+fn opt_field<'a>(node: &'a Node, key: &str) -> Option<&'a Value> {
+    node.get(key).filter(|v| !v.is_null())
+}
+
+fn str_field<'a>(node: &'a Node, key: &str) -> Result<&'a str> {
+    field(node, key).and_then(as_str)
+}
+
+fn bool_field(node: &Node, key: &str) -> bool {
+    opt_field(node, key).and_then(Value::as_bool).unwrap_or(false)
+}
+
+fn as_str(value: &Value) -> Result<&str> {
+    match value {
+        Value::String(s) => Ok(s),
+        value => bail!("expected a string, got {value}"),
+    }
+}
+
+fn is_payable_conversion(node: &Node) -> bool {
+    let Some(Value::Object(ty)) = opt_field(node, "typeName") else { return false };
+    opt_field(ty, "stateMutability").and_then(Value::as_str) == Some("payable")
+}
+
+fn elementary_type(name: &str) -> Option<ElementaryType> {
+    let int_size = |bits: &str| TypeSize::try_new_int_bits(bits.parse().ok()?);
+    Some(match name {
+        "address" => ElementaryType::Address(false),
+        "address payable" => ElementaryType::Address(true),
+        "bool" => ElementaryType::Bool,
+        "string" => ElementaryType::String,
+        "bytes" => ElementaryType::Bytes,
+        "byte" => ElementaryType::FixedBytes(TypeSize::new_fb_bytes(1)),
+        "int" => ElementaryType::Int(TypeSize::ZERO),
+        "uint" => ElementaryType::UInt(TypeSize::ZERO),
+        "fixed" => ElementaryType::Fixed(TypeSize::ZERO, TypeFixedSize::ZERO),
+        "ufixed" => ElementaryType::UFixed(TypeSize::ZERO, TypeFixedSize::ZERO),
+        _ => {
+            if let Some(bits) = name.strip_prefix("uint") {
+                ElementaryType::UInt(int_size(bits)?)
+            } else if let Some(bits) = name.strip_prefix("int") {
+                ElementaryType::Int(int_size(bits)?)
+            } else if let Some(bytes) = name.strip_prefix("bytes") {
+                ElementaryType::FixedBytes(TypeSize::try_new_fb_bytes(bytes.parse().ok()?)?)
+            } else if let Some(size) = name.strip_prefix("ufixed") {
+                let (m, n) = size.split_once('x')?;
+                ElementaryType::UFixed(int_size(m)?, TypeFixedSize::new(n.parse().ok()?)?)
+            } else if let Some(size) = name.strip_prefix("fixed") {
+                let (m, n) = size.split_once('x')?;
+                ElementaryType::Fixed(int_size(m)?, TypeFixedSize::new(n.parse().ok()?)?)
+            } else {
+                return None;
+            }
+        }
+    })
+}
+
+fn number_lit(value: &str) -> Result<LitKind> {
+    let s = value.replace('_', "");
+    if let Some(hex) = s.strip_prefix("0x") {
+        if hex.len() == 40 {
+            if let Ok(address) = Address::parse_checksummed(&s, None) {
+                return Ok(LitKind::Address(address));
+            }
+        }
+        return match num_bigint::BigInt::parse_bytes(hex.as_bytes(), 16) {
+            Some(n) => Ok(LitKind::Number(n)),
+            None => bail!("invalid number literal {value:?}"),
+        };
+    }
+
+    // Exponents and fractional parts are bounded like in the parser, so that huge literals can't
+    // exhaust memory.
+    let (mantissa, exp) = match s.split_once(['e', 'E']) {
+        Some((mantissa, exp)) => match exp.parse::<i16>() {
+            Ok(exp) => (mantissa, exp),
+            Err(_) => bail!("invalid exponent in number literal {value:?}"),
+        },
+        None => (&s[..], 0),
+    };
+    let (int, frac) = mantissa.split_once('.').unwrap_or((mantissa, ""));
+    let Ok(frac_len) = u16::try_from(frac.len()) else {
+        bail!("fractional part too large in number literal {value:?}");
+    };
+    let digits = format!("{int}{frac}");
+    let Some(numer) = num_bigint::BigInt::parse_bytes(digits.as_bytes(), 10) else {
+        bail!("invalid number literal {value:?}");
+    };
+    let exp = i32::from(exp) - i32::from(frac_len);
+    let ten = num_bigint::BigInt::from(10);
+    let n = if exp >= 0 {
+        num_rational::BigRational::from_integer(numer * ten.pow(exp as u32))
+    } else {
+        num_rational::BigRational::new(numer, ten.pow(exp.unsigned_abs()))
+    };
+    Ok(if n.is_integer() { LitKind::Number(n.to_integer()) } else { LitKind::Rational(n) })
+}
+
+fn bin_op_kind(s: &str) -> Option<BinOpKind> {
+    use BinOpKind::*;
+    [
+        Lt, Le, Gt, Ge, Eq, Ne, Or, And, Shr, Shl, Sar, BitAnd, BitOr, BitXor, Add, Sub, Pow, Mul,
+        Div, Rem,
+    ]
+    .into_iter()
+    .find(|op| op.to_str() == s)
+}
+
+fn user_definable_operator(s: &str) -> Result<UserDefinableOperator> {
+    use UserDefinableOperator::*;
+    let op = [BitAnd, BitNot, BitOr, BitXor, Add, Div, Rem, Mul, Sub, Eq, Ge, Gt, Le, Lt, Ne]
+        .into_iter()
+        .find(|op| match op.to_op() {
+            either::Either::Left(op) => op.to_str() == s,
+            either::Either::Right(op) => op.to_str() == s,
+        });
+    match op {
+        Some(op) => Ok(op),
+        None => bail!("unknown user-definable operator {s:?}"),
+    }
+}
+
+fn visibility(s: &str) -> Result<Visibility> {
+    Ok(match s {
+        "private" => Visibility::Private,
+        "internal" => Visibility::Internal,
+        "public" => Visibility::Public,
+        "external" => Visibility::External,
+        s => bail!("unknown visibility {s:?}"),
+    })
+}
+
+fn state_mutability(s: &str) -> Result<StateMutability> {
+    Ok(match s {
+        "pure" => StateMutability::Pure,
+        "view" | "constant" => StateMutability::View,
+        "payable" => StateMutability::Payable,
+        "nonpayable" => StateMutability::NonPayable,
+        s => bail!("unknown state mutability {s:?}"),
+    })
+}
+
+fn subdenomination(s: &str) -> Result<SubDenomination> {
+    Ok(match s {
+        "wei" => SubDenomination::Ether(EtherSubDenomination::Wei),
+        "gwei" => SubDenomination::Ether(EtherSubDenomination::Gwei),
+        "ether" => SubDenomination::Ether(EtherSubDenomination::Ether),
+        "seconds" => SubDenomination::Time(TimeSubDenomination::Seconds),
+        "minutes" => SubDenomination::Time(TimeSubDenomination::Minutes),
+        "hours" => SubDenomination::Time(TimeSubDenomination::Hours),
+        "days" => SubDenomination::Time(TimeSubDenomination::Days),
+        "weeks" => SubDenomination::Time(TimeSubDenomination::Weeks),
+        "years" => SubDenomination::Time(TimeSubDenomination::Years),
+        s => bail!("unknown subdenomination {s:?}"),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn elementary_types() {
+        let cases = [
+            ("address", Some(ElementaryType::Address(false))),
+            ("address payable", Some(ElementaryType::Address(true))),
+            ("uint", Some(ElementaryType::UInt(TypeSize::ZERO))),
+            ("uint8", Some(ElementaryType::UInt(TypeSize::new_int_bits(8)))),
+            ("int256", Some(ElementaryType::Int(TypeSize::new_int_bits(256)))),
+            ("bytes", Some(ElementaryType::Bytes)),
+            ("bytes32", Some(ElementaryType::FixedBytes(TypeSize::new_fb_bytes(32)))),
+            ("bytes0", None),
+            ("uint7", None),
+            ("foo", None),
+        ];
+        for (name, expected) in cases {
+            assert_eq!(elementary_type(name), expected, "{name:?}");
+        }
+    }
+
+    #[test]
+    fn number_lits() {
+        let number = |n: i64| LitKind::Number(n.into());
+        let cases = [
+            ("1", number(1)),
+            ("1_000", number(1000)),
+            ("0x10", number(16)),
+            ("1e3", number(1000)),
+            ("1.5e1", number(15)),
+        ];
+        for (value, expected) in cases {
+            let actual = number_lit(value).unwrap();
+            assert_eq!(format!("{actual:?}"), format!("{expected:?}"), "{value:?}");
+        }
+
+        let LitKind::Rational(r) = number_lit("2.5").unwrap() else { panic!() };
+        assert_eq!(r, num_rational::BigRational::new(5.into(), 2.into()));
+        assert!(number_lit("0xzz").is_err());
+        assert!(number_lit("1e4294967295").is_err());
+        assert!(number_lit("1e-99999").is_err());
+    }
+
+    #[test]
+    fn source_locations() {
+        let arena = Arena::new();
+        let reader = SolcJsonReader::new(&arena).with_base_pos(BytePos(10));
+        let span = reader.parse_src("1:2:0").unwrap();
+        assert_eq!((span.lo(), span.hi()), (BytePos(11), BytePos(13)));
+        assert!(reader.parse_src("-1:-1:-1").unwrap().is_dummy());
+        assert!(reader.parse_src("1").is_err());
+        assert!(reader.parse_src(&format!("{}:0:0", u32::MAX)).is_err());
+        assert!(reader.parse_src(&format!("0:{}:0", u32::MAX)).is_err());
+    }
+}
@@ -0,0 +1,129 @@
+//! Trivia: the comments and whitespace between tokens.
+//!
+//! Trivia is not part of the AST. The lexer can collect it in a side table, so that tools like
+//! formatters and codemods can find the comments and line breaks around AST nodes through their
+//! spans, and regenerate the source without losing them.
+
+use crate::token::CommentKind;
+use solar_interface::{BytePos, Span};
+
+/// The comments and whitespace of a source file, sorted by position.
+#[derive(Clone, Debug, Default)]
+pub struct Trivia {
+    start_pos: BytePos,
+    pieces: Vec<TriviaPiece>,
+}
+
+/// A single piece of trivia.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TriviaPiece {
+    /// The span of the piece. Comments include their delimiters.
+    pub span: Span,
+    /// The kind of the piece.
+    pub kind: TriviaKind,
+}
+
+/// A kind of [`TriviaPiece`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TriviaKind {
+    /// A run of whitespace between two tokens or comments.
+    Whitespace {
+        /// The number of line breaks in the whitespace. More than one means that there are blank
+        /// lines.
+        newlines: u32,
+    },
+    /// A comment.
+    ///
+    /// Doc-comments are also part of the token stream, and are attached to the AST as
+    /// [`DocComments`](crate::DocComments).
+    Comment {
+        /// The comment kind.
+        kind: CommentKind,
+        /// Whether the comment is a doc-comment.
+        is_doc: bool,
+    },
+}
+
+impl TriviaPiece {
+    /// Returns `true` if the piece is a comment.
+    pub fn is_comment(&self) -> bool {
+        matches!(self.kind, TriviaKind::Comment { .. })
+    }
+
+    /// Returns `true` if the piece is whitespace containing a line break.
+    pub fn is_line_break(&self) -> bool {
+        matches!(self.kind, TriviaKind::Whitespace { newlines } if newlines > 0)
+    }
+}
+
+impl Trivia {
+    /// Creates a new trivia table for the source file starting at `start_pos`.
+    ///
+    /// `pieces` must be sorted by position and must not overlap.
+    pub fn new(start_pos: BytePos, pieces: Vec<TriviaPiece>) -> Self {
+        debug_assert!(
+            pieces.windows(2).all(|w| w[0].span.hi() <= w[1].span.lo()),
+            "trivia must be sorted"
+        );
+        Self { start_pos, pieces }
+    }
+
+    /// Returns all the pieces of trivia.
+    pub fn pieces(&self) -> &[TriviaPiece] {
+        &self.pieces
+    }
+
+    /// Returns an iterator over all the comments.
+    pub fn comments(&self) -> impl Iterator<Item = &TriviaPiece> + '_ {
+        self.pieces.iter().filter(|piece| piece.is_comment())
+    }
+
+    /// Returns the trivia contained between the given positions.
+    pub fn between(&self, lo: BytePos, hi: BytePos) -> &[TriviaPiece] {
+        let start = self.pieces.partition_point(|piece| piece.span.lo() < lo);
+        let end = self.pieces.partition_point(|piece| piece.span.hi() <= hi).max(start);
+        &self.pieces[start..end]
+    }
+
+    /// Returns the trivia inside of a span, for example the comments inside of a block.
+    pub fn inner(&self, span: Span) -> &[TriviaPiece] {
+        self.between(span.lo(), span.hi())
+    }
+
+    /// Returns the trivia that belongs to the start of a span: the comments and whitespace
+    /// directly before it, excluding the trailing trivia of the previous token.
+    pub fn leading(&self, span: Span) -> &[TriviaPiece] {
+        let end = self.pieces.partition_point(|piece| piece.span.hi() <= span.lo());
+        let mut start = end;
+        let mut pos = span.lo();
+        while start > 0 && self.pieces[start - 1].span.hi() == pos {
+            start -= 1;
+            pos = self.pieces[start].span.lo();
+        }
+        let run = &self.pieces[start..end];
+        if pos == self.start_pos {
+            return run;
+        }
+        // The run follows a token: its first line is the trailing trivia of that token.
+        match run.iter().position(TriviaPiece::is_line_break) {
+            Some(i) => &run[i + 1..],
+            None => &[],
+        }
+    }
+
+    /// Returns the trivia that belongs to the end of a span: the comments and whitespace directly
+    /// after it, up to and including the first line break.
+    pub fn trailing(&self, span: Span) -> &[TriviaPiece] {
+        let start = self.pieces.partition_point(|piece| piece.span.lo() < span.hi());
+        let mut end = start;
+        let mut pos = span.hi();
+        while let Some(piece) = self.pieces.get(end).filter(|piece| piece.span.lo() == pos) {
+            end += 1;
+            pos = piece.span.hi();
+            if piece.is_line_break() {
+                break;
+            }
+        }
+        &self.pieces[start..end]
+    }
+}
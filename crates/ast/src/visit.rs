@@ -1,6 +1,29 @@
 //! Constant and mutable AST visitor trait definitions.
+//!
+//! Visitor methods return a [`ControlFlow`], which allows stopping the traversal early, for
+//! example once a node has been found:
+//!
+//! ```
+//! use solar_ast::{visit::Visit, Expr, ExprKind, Ident};
+//! use std::ops::ControlFlow;
+//!
+//! /// Finds the first identifier expression.
+//! struct FindIdent;
+//!
+//! impl<'ast> Visit<'ast> for FindIdent {
+//!     type BreakValue = Ident;
+//!
+//!     fn visit_expr(&mut self, expr: &'ast Expr<'ast>) -> ControlFlow<Self::BreakValue> {
+//!         if let ExprKind::Ident(ident) = expr.kind {
+//!             return ControlFlow::Break(ident);
+//!         }
+//!         self.walk_expr(expr)
+//!     }
+//! }
+//! ```
 
 use crate::ast::*;
+use rayon::prelude::*;
 use solar_data_structures::trustme;
 use solar_interface::{Ident, Span};
 use solar_macros::declare_visitors;
@@ -26,7 +49,7 @@ declare_visitors! {
         }
 
         fn visit_item(&mut self, item: &'ast #mut Item<'ast>) -> ControlFlow<Self::BreakValue> {
-            let Item { docs, span, kind } = item;
+            let Item { id: _, docs, span, kind } = item;
             self.visit_span #_mut(span)?;
             self.visit_doc_comments #_mut(docs)?;
             match kind {
@@ -157,6 +180,7 @@ declare_visitors! {
 
         fn visit_variable_definition(&mut self, var: &'ast #mut VariableDefinition<'ast>) -> ControlFlow<Self::BreakValue> {
             let VariableDefinition {
+                id: _,
                 span,
                 ty,
                 visibility: _,
@@ -179,7 +203,7 @@ declare_visitors! {
         }
 
         fn visit_ty(&mut self, ty: &'ast #mut Type<'ast>) -> ControlFlow<Self::BreakValue> {
-            let Type { span, kind } = ty;
+            let Type { id: _, span, kind } = ty;
             self.visit_span #_mut(span)?;
             match kind {
                 TypeKind::Elementary(_) => {}
@@ -262,7 +286,7 @@ declare_visitors! {
         }
 
         fn visit_stmt(&mut self, stmt: &'ast #mut Stmt<'ast>) -> ControlFlow<Self::BreakValue> {
-            let Stmt { docs, span, kind } = stmt;
+            let Stmt { id: _, docs, span, kind } = stmt;
             self.visit_doc_comments #_mut(docs)?;
             self.visit_span #_mut(span)?;
             match kind {
@@ -346,8 +370,9 @@ declare_visitors! {
         }
 
         fn visit_stmt_try(&mut self, try_: &'ast #mut StmtTry<'ast>) -> ControlFlow<Self::BreakValue> {
-            let StmtTry { expr, returns, block, catch } = try_;
+            let StmtTry { expr, span, returns, block, catch } = try_;
             self.visit_expr #_mut(expr)?;
+            self.visit_span #_mut(span)?;
             self.visit_parameter_list #_mut(returns)?;
             self.visit_block #_mut(block)?;
             for catch in catch.iter #_mut() {
@@ -357,7 +382,8 @@ declare_visitors! {
         }
 
         fn visit_catch_clause(&mut self, catch: &'ast #mut CatchClause<'ast>) -> ControlFlow<Self::BreakValue> {
-            let CatchClause { name, args, block } = catch;
+            let CatchClause { span, name, args, block } = catch;
+            self.visit_span #_mut(span)?;
             if let Some(name) = name {
                 self.visit_ident #_mut(name)?;
             }
@@ -367,14 +393,16 @@ declare_visitors! {
         }
 
         fn visit_block(&mut self, block: &'ast #mut Block<'ast>) -> ControlFlow<Self::BreakValue> {
-            for stmt in block.iter #_mut() {
+            let Block { span, stmts } = block;
+            self.visit_span #_mut(span)?;
+            for stmt in stmts.iter #_mut() {
                 self.visit_stmt #_mut(stmt)?;
             }
             ControlFlow::Continue(())
         }
 
         fn visit_expr(&mut self, expr: &'ast #mut Expr<'ast>) -> ControlFlow<Self::BreakValue> {
-            let Expr { span, kind } = expr;
+            let Expr { id: _, span, kind } = expr;
             self.visit_span #_mut(span)?;
             match kind {
                 ExprKind::Array(exprs) => {
@@ -623,3 +651,109 @@ declare_visitors! {
         }
     }
 }
+
+/// Parallel AST traversal, for read-only analyses over many items.
+///
+/// [`par_visit_source_unit`](Self::par_visit_source_unit) visits the top-level items of a source
+/// unit in parallel, each with its own visitor created with [`fork`](Self::fork). The visitors are
+/// then [merged](Self::merge) back in source order, so the result does not depend on scheduling.
+///
+/// This must be called inside of a session so that the items are visited in its thread pool.
+pub trait ParVisit<'ast>: Visit<'ast> + Send + Sized {
+    /// Creates a new visitor for a single top-level item.
+    fn fork(&self) -> Self;
+
+    /// Merges the state of a visitor created with [`fork`](Self::fork) into `self`.
+    fn merge(&mut self, other: Self);
+
+    /// Visits the items of a source unit in parallel.
+    ///
+    /// If the traversal of an item breaks, the visitors of the previous items and of the breaking
+    /// item are merged, and the first break value in source order is returned.
+    fn par_visit_source_unit(
+        &mut self,
+        source_unit: &SourceUnit<'ast>,
+    ) -> ControlFlow<Self::BreakValue>
+    where
+        Self::BreakValue: Send,
+    {
+        // SAFETY: Same as in `visit_source_unit`.
+        let source_unit = unsafe { trustme::decouple_lt(source_unit) };
+        let items = &source_unit.items.raw;
+        let forks = items.iter().map(|_| self.fork()).collect::<Vec<_>>();
+        let results = forks
+            .into_par_iter()
+            .zip(items.par_iter())
+            .map(|(mut visitor, item)| {
+                let r = visitor.visit_item(item);
+                (visitor, r)
+            })
+            .collect::<Vec<_>>();
+        for (visitor, r) in results {
+            self.merge(visitor);
+            r?;
+        }
+        ControlFlow::Continue(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use solar_data_structures::Never;
+
+    /// Collects the names of the identifier expressions, delegating to the default traversal with
+    /// the free `walk_` functions.
+    #[derive(Default)]
+    struct Idents(Vec<String>);
+
+    impl<'ast> Visit<'ast> for Idents {
+        type BreakValue = Never;
+
+        fn visit_expr(&mut self, expr: &'ast Expr<'ast>) -> ControlFlow<Self::BreakValue> {
+            if let ExprKind::Ident(ident) = expr.kind {
+                self.0.push(ident.to_string());
+            }
+            walk_expr(self, expr)
+        }
+    }
+
+    impl<'ast> VisitMut<'ast> for Idents {
+        type BreakValue = Never;
+
+        fn visit_expr_mut(&mut self, expr: &'ast mut Expr<'ast>) -> ControlFlow<Self::BreakValue> {
+            if let ExprKind::Ident(ident) = expr.kind {
+                self.0.push(ident.to_string());
+            }
+            walk_expr_mut(self, expr)
+        }
+    }
+
+    #[test]
+    fn free_walk_fns() {
+        solar_interface::enter(|| {
+            let ident = |name: &str| Expr::from_ident(Ident::from_str(name));
+            let add = BinOp { span: Span::DUMMY, kind: BinOpKind::Add };
+
+            let (mut a, mut b) = (ident("a"), ident("b"));
+            let expr = Expr {
+                id: NodeId::DUMMY,
+                span: Span::DUMMY,
+                kind: ExprKind::Binary(&mut a, add, &mut b),
+            };
+            let mut idents = Idents::default();
+            let _ = idents.visit_expr(&expr);
+            assert_eq!(idents.0, ["a", "b"]);
+
+            let (mut a, mut b) = (ident("a"), ident("b"));
+            let mut expr = Expr {
+                id: NodeId::DUMMY,
+                span: Span::DUMMY,
+                kind: ExprKind::Binary(&mut a, add, &mut b),
+            };
+            let mut idents = Idents::default();
+            let _ = idents.visit_expr_mut(&mut expr);
+            assert_eq!(idents.0, ["a", "b"]);
+        });
+    }
+}
@@ -1,19 +1,26 @@
 //! Solar CLI arguments.
 
 use clap::{ColorChoice, Parser, ValueHint};
-use solar_config::{CompilerOutput, CompilerStage, Dump, EvmVersion, Language};
+use solar_config::{
+    BytecodeHash, CompilerOutput, CompilerStage, Dump, EvmVersion, IntTypes, Language, Library,
+    LintLevel, Unpretty,
+};
 use std::path::PathBuf;
 
 /// Blazingly fast Solidity compiler.
-#[derive(Parser)]
+#[derive(Clone, Parser)]
 #[command(
     name = "solar",
     version = crate::version::SHORT_VERSION,
     long_version = crate::version::LONG_VERSION,
     arg_required_else_help = true,
+    args_conflicts_with_subcommands = true,
 )]
 #[non_exhaustive]
 pub struct Args {
+    #[command(subcommand)]
+    pub command: Option<Command>,
+
     /// Files to compile or import remappings.
     #[arg(value_hint = ValueHint::FilePath)]
     pub input: Vec<PathBuf>,
@@ -23,9 +30,26 @@ pub struct Args {
     /// Map to search for files. Can also be provided as a positional argument.
     #[arg(help_heading = "Input options", long, short = 'm', value_name = "MAP=PATH")]
     pub import_map: Vec<ImportMap>,
+    /// The name of the source read from stdin, with the `-` input.
+    ///
+    /// It is displayed in diagnostics and relative imports are resolved from it.
+    #[arg(help_heading = "Input options", long, value_name = "PATH")]
+    pub stdin_name: Option<PathBuf>,
+    /// Do not load the import remappings of `remappings.txt` and `lib/*/remappings.txt` in the
+    /// project directory.
+    ///
+    /// The project directory is the one of the configuration file, or the current directory.
+    #[arg(help_heading = "Input options", long)]
+    pub no_auto_remappings: bool,
     /// Source code language. Only Solidity is currently implemented.
     #[arg(help_heading = "Input options", long, value_enum, default_value_t, hide = true)]
     pub language: Language,
+    /// Accept syntax that was removed before Solidity 0.8, to analyze legacy code.
+    ///
+    /// This accepts the `now` global, `throw` statements, `var` declarations, constructors named
+    /// after their contract, and `constant` functions. Code generation outputs cannot be emitted.
+    #[arg(help_heading = "Input options", long)]
+    pub legacy_syntax: bool,
 
     /// Number of threads to use. Zero specifies the number of logical cores.
     #[arg(long, short = 'j', visible_alias = "jobs", default_value = "8")]
@@ -36,13 +60,98 @@ pub struct Args {
     /// Stop execution after the given compiler stage.
     #[arg(long, value_enum)]
     pub stop_after: Option<CompilerStage>,
+    /// Watch the source files and compile them again whenever they change.
+    ///
+    /// Combine with `--cache-dir` to reuse the outputs of unchanged contracts.
+    #[arg(long)]
+    pub watch: bool,
 
     /// Directory to write output files.
     #[arg(long, value_hint = ValueHint::DirPath)]
     pub out_dir: Option<PathBuf>,
+    /// Directory in which to cache code generation outputs across runs.
+    ///
+    /// Outputs of contracts whose source files and imports have not changed are reused.
+    #[arg(long, value_hint = ValueHint::DirPath)]
+    pub cache_dir: Option<PathBuf>,
     /// Comma separated list of types of output for the compiler to emit.
     #[arg(long, value_delimiter = ',')]
     pub emit: Vec<CompilerOutput>,
+    /// The metadata hash to append to the bytecode.
+    #[arg(long, value_enum, default_value_t)]
+    pub metadata_hash: BytecodeHash,
+    /// Comma separated list of library addresses to link, as `[<path>:]<name>=<address>`.
+    #[arg(long, value_delimiter = ',', value_name = "LIBRARIES")]
+    pub libraries: Vec<Library>,
+    /// Generate bytecode by assembling the Yul IR, as emitted with `--emit ir`.
+    #[arg(long)]
+    pub via_ir: bool,
+
+    /// Enable the optimizer.
+    ///
+    /// The optimizer works on the Yul IR, so this implies `--via-ir`.
+    #[arg(help_heading = "Optimizer options", long)]
+    pub optimize: bool,
+    /// The number of times each opcode of the deployed code is expected to be executed.
+    ///
+    /// Lower values optimize for deployment cost, higher values for runtime cost.
+    #[arg(help_heading = "Optimizer options", long, value_name = "RUNS", default_value = "200")]
+    pub optimizer_runs: u64,
+
+    /// Set the level of the given lints or warning codes to `allow`, disabling them.
+    ///
+    /// `warnings` disables all warnings.
+    #[arg(
+        help_heading = "Lint options",
+        long,
+        short = 'A',
+        value_name = "LINT",
+        value_delimiter = ','
+    )]
+    pub allow: Vec<String>,
+    /// Set the level of the given lints or warning codes to `warn`.
+    #[arg(
+        help_heading = "Lint options",
+        long,
+        short = 'W',
+        value_name = "LINT",
+        value_delimiter = ','
+    )]
+    pub warn: Vec<String>,
+    /// Set the level of the given lints or warning codes to `deny`, emitting them as errors.
+    ///
+    /// `warnings` denies all warnings, like `--deny-warnings`.
+    #[arg(
+        help_heading = "Lint options",
+        long,
+        short = 'D',
+        value_name = "LINT",
+        value_delimiter = ','
+    )]
+    pub deny: Vec<String>,
+    /// Emit all warnings as errors, except those allowed or warned by code.
+    #[arg(help_heading = "Lint options", long)]
+    pub deny_warnings: bool,
+    /// JSON file mapping lint names to levels, like `{ "unused-imports": "allow" }`.
+    ///
+    /// Levels set with `--allow`, `--warn` and `--deny` take precedence, in this order.
+    #[arg(help_heading = "Lint options", long, value_hint = ValueHint::FilePath)]
+    pub lint_config: Option<PathBuf>,
+
+    /// Project configuration file. Defaults to the closest `solar.toml` from the current directory.
+    ///
+    /// `foundry.toml` files are also supported, to compile Foundry projects without flags.
+    ///
+    /// Flags given on the command line take precedence over the values of the file.
+    #[arg(long, value_hint = ValueHint::FilePath, conflicts_with = "no_config")]
+    pub config: Option<PathBuf>,
+    /// Do not load a project configuration file.
+    #[arg(long)]
+    pub no_config: bool,
+
+    /// Print the extended explanation of an error code, like `E2904`, and exit.
+    #[arg(long, value_name = "CODE")]
+    pub explain: Option<String>,
 
     /// Coloring.
     #[arg(help_heading = "Display options", long, value_enum, default_value = "auto")]
@@ -50,6 +159,12 @@ pub struct Args {
     /// Use verbose output.
     #[arg(help_heading = "Display options", long, short)]
     pub verbose: bool,
+    /// Enable debug logging, filtered with the given directives.
+    ///
+    /// This uses the same syntax as the `RUST_LOG` environment variable, which it overrides, e.g.
+    /// `solar_sema::ast_lowering=debug`.
+    #[arg(help_heading = "Display options", long, value_name = "FILTER")]
+    pub log: Option<String>,
     /// Pretty-print JSON output.
     ///
     /// Does not include errors. See `--pretty-json-err`.
@@ -61,6 +176,23 @@ pub struct Args {
     /// How errors and other messages are produced.
     #[arg(help_heading = "Display options", long, value_enum, default_value_t)]
     pub error_format: ErrorFormat,
+    /// Write each JSON diagnostic on its own line as soon as it is produced.
+    ///
+    /// Overrides `--pretty-json-err`, and makes `--error-format solc-json` emit one error object
+    /// per line instead of a single document at the end.
+    #[arg(help_heading = "Display options", long)]
+    pub json_lines: bool,
+    /// Only display errors. Equivalent to `--message-level error`.
+    #[arg(help_heading = "Display options", long, short, conflicts_with = "message_level")]
+    pub quiet: bool,
+    /// Do not display diagnostics less severe than this level.
+    ///
+    /// Hidden diagnostics are still counted in the summary.
+    #[arg(help_heading = "Display options", long, value_enum, default_value_t)]
+    pub message_level: MessageLevel,
+    /// Abort compilation after emitting this many errors. Zero means no limit.
+    #[arg(help_heading = "Display options", long, value_name = "N", default_value = "100")]
+    pub error_limit: usize,
 
     /// Unstable flags. WARNING: these are completely unstable, and may change at any time.
     ///
@@ -72,6 +204,97 @@ pub struct Args {
     /// Parsed unstable flags.
     #[arg(skip)]
     pub unstable: UnstableFeatures,
+
+    /// The levels of the `[lints]` table of the project configuration file.
+    #[arg(skip)]
+    pub config_lints: Vec<(String, LintLevel)>,
+    /// The directory of the project configuration file, if any.
+    #[arg(skip)]
+    pub project_root: Option<PathBuf>,
+}
+
+/// Subcommands.
+#[derive(Clone, Debug, clap::Subcommand)]
+pub enum Command {
+    /// Run the language server, communicating over standard input and output.
+    Lsp,
+    /// Format Solidity source files in place.
+    Fmt(FmtArgs),
+    /// Flatten a Solidity source file and its imports into a single source file.
+    Flatten(FlattenArgs),
+    /// Apply the fixes suggested by the compiler's diagnostics to the source files in place.
+    Fix(FixArgs),
+}
+
+/// Arguments of the `fmt` subcommand.
+#[derive(Clone, Debug, clap::Args)]
+#[non_exhaustive]
+pub struct FmtArgs {
+    /// Files to format. `-` formats the standard input and prints the result.
+    #[arg(required = true, value_hint = ValueHint::FilePath)]
+    pub paths: Vec<PathBuf>,
+    /// Do not write the files, and fail if any of them is not formatted.
+    #[arg(long)]
+    pub check: bool,
+    /// Maximum line width.
+    #[arg(long, default_value = "120")]
+    pub line_width: usize,
+    /// Number of spaces per indentation level.
+    #[arg(long, default_value = "4")]
+    pub indent_width: usize,
+    /// Indent with tabs instead of spaces.
+    #[arg(long)]
+    pub use_tabs: bool,
+    /// Print spaces inside of braces: `{ a, b }` instead of `{a, b}`.
+    #[arg(long)]
+    pub bracket_spacing: bool,
+    /// How to print `int` and `uint` types.
+    #[arg(long, value_enum, default_value_t)]
+    pub int_types: IntTypes,
+}
+
+/// Arguments of the `flatten` subcommand.
+#[derive(Clone, Debug, clap::Args)]
+#[non_exhaustive]
+pub struct FlattenArgs {
+    /// The file to flatten.
+    #[arg(value_hint = ValueHint::FilePath)]
+    pub path: PathBuf,
+    /// Write the flattened source to this file instead of the standard output.
+    #[arg(long, short, value_hint = ValueHint::FilePath)]
+    pub output: Option<PathBuf>,
+    /// Directory to search for files.
+    #[arg(long, short = 'I', value_hint = ValueHint::DirPath)]
+    pub import_path: Vec<PathBuf>,
+    /// Map to search for files.
+    #[arg(long, short = 'm', value_name = "MAP=PATH")]
+    pub import_map: Vec<ImportMap>,
+    /// Do not load the import remappings of `remappings.txt` and `lib/*/remappings.txt` in the
+    /// current directory.
+    #[arg(long)]
+    pub no_auto_remappings: bool,
+}
+
+/// Arguments of the `fix` subcommand.
+#[derive(Clone, Debug, clap::Args)]
+#[non_exhaustive]
+pub struct FixArgs {
+    /// Files to compile and fix.
+    #[arg(required = true, value_hint = ValueHint::FilePath)]
+    pub paths: Vec<PathBuf>,
+    /// Do not write the files, and print the fixes as a diff instead.
+    #[arg(long)]
+    pub dry_run: bool,
+    /// Directory to search for files.
+    #[arg(long, short = 'I', value_hint = ValueHint::DirPath)]
+    pub import_path: Vec<PathBuf>,
+    /// Map to search for files.
+    #[arg(long, short = 'm', value_name = "MAP=PATH")]
+    pub import_map: Vec<ImportMap>,
+    /// Do not load the import remappings of `remappings.txt` and `lib/*/remappings.txt` in the
+    /// current directory.
+    #[arg(long)]
+    pub no_auto_remappings: bool,
 }
 
 impl Args {
@@ -128,6 +351,37 @@ pub struct UnstableFeatures {
     #[arg(long)]
     pub ast_stats: bool,
 
+    /// Print a report of the time spent in each compiler pass, and in parsing each file.
+    #[arg(long)]
+    pub time_passes: bool,
+
+    /// Write a trace of the compiler's passes to a file in the given directory.
+    ///
+    /// The trace is in the Chrome trace event format, and can be viewed in `chrome://tracing`.
+    #[arg(long, value_name = "DIR")]
+    pub self_profile: Option<PathBuf>,
+
+    /// Pretty-print the given representation of the input to stdout after parsing.
+    #[arg(long, value_name = "KIND")]
+    pub unpretty: Option<Unpretty>,
+
+    /// Generate EVM Object Format (EOF) containers when targeting an EVM version that supports
+    /// them, such as `osaka`.
+    #[arg(long)]
+    pub eof: bool,
+
+    /// Run the given optimizer pass in addition to the default ones. May be repeated.
+    #[arg(long, value_name = "PASS")]
+    pub enable_pass: Vec<String>,
+
+    /// Do not run the given optimizer pass. May be repeated.
+    #[arg(long, value_name = "PASS")]
+    pub disable_pass: Vec<String>,
+
+    /// Run the given optimizer passes in order instead of the default pipeline.
+    #[arg(long, value_name = "PASSES", value_delimiter = ',')]
+    pub optimizer_pipeline: Option<Vec<String>>,
+
     /// Print help.
     #[arg(long, action = clap::ArgAction::Help)]
     help: (),
@@ -151,6 +405,23 @@ pub enum ErrorFormat {
     Json,
     /// Rustc-like JSON output.
     RustcJson,
+    /// JSON output in exactly the format of the `errors` of solc's standard JSON output.
+    SolcJson,
+    /// SARIF 2.1.0 output, for code scanning tools.
+    Sarif,
+}
+
+/// The minimum severity of displayed diagnostics.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, clap::ValueEnum)]
+#[value(rename_all = "kebab-case")]
+pub enum MessageLevel {
+    /// Only errors.
+    Error,
+    /// Errors and warnings.
+    Warning,
+    /// All diagnostics.
+    #[default]
+    Note,
 }
 
 /// A single import map, AKA remapping: `map=path`.
@@ -218,5 +489,133 @@ mod tests {
 
         let unstable = parse(&["solar", "-Zast-stats", "a.sol"]).unwrap();
         assert!(unstable.ast_stats);
+        let unstable = parse(&["solar", "-Zeof", "a.sol"]).unwrap();
+        assert!(unstable.eof);
+
+        let unstable = parse(&["solar", "-Ztime-passes", "a.sol"]).unwrap();
+        assert!(unstable.time_passes);
+
+        let unstable = parse(&["solar", "-Zself-profile=out", "a.sol"]).unwrap();
+        assert_eq!(unstable.self_profile.as_deref(), Some(std::path::Path::new("out")));
+
+        let unstable = parse(&["solar", "-Zunpretty=ast", "a.sol"]).unwrap();
+        assert_eq!(unstable.unpretty, Some(Unpretty::Ast));
+        let unstable = parse(&["solar", "-Zunpretty=tokens", "a.sol"]).unwrap();
+        assert_eq!(unstable.unpretty, Some(Unpretty::Tokens));
+        assert!(parse(&["solar", "-Zunpretty=hir", "a.sol"]).is_err());
+
+        let unstable =
+            parse(&["solar", "-Zdisable-pass=a", "-Z", "disable-pass=b", "a.sol"]).unwrap();
+        assert_eq!(unstable.disable_pass, ["a", "b"]);
+
+        let unstable = parse(&["solar", "-Zoptimizer-pipeline=a,b,a", "a.sol"]).unwrap();
+        assert_eq!(unstable.optimizer_pipeline.unwrap(), ["a", "b", "a"]);
+    }
+
+    #[test]
+    fn fmt() {
+        let args = Args::try_parse_from(["solar", "fmt", "--check", "--int-types=long", "a.sol"]);
+        let Some(Command::Fmt(args)) = args.unwrap().command else { panic!() };
+        assert!(args.check);
+        assert_eq!(args.int_types, IntTypes::Long);
+        assert_eq!(args.line_width, 120);
+        assert_eq!(args.paths, [PathBuf::from("a.sol")]);
+
+        assert!(Args::try_parse_from(["solar", "fmt"]).is_err());
+    }
+
+    #[test]
+    fn flatten() {
+        let args = Args::try_parse_from(["solar", "flatten", "-o", "out.sol", "-mx/=y/", "a.sol"]);
+        let Some(Command::Flatten(args)) = args.unwrap().command else { panic!() };
+        assert_eq!(args.path, PathBuf::from("a.sol"));
+        assert_eq!(args.output, Some(PathBuf::from("out.sol")));
+        assert_eq!(args.import_map.len(), 1);
+
+        assert!(Args::try_parse_from(["solar", "flatten", "a.sol", "b.sol"]).is_err());
+    }
+
+    #[test]
+    fn fix() {
+        let args = Args::try_parse_from(["solar", "fix", "--dry-run", "a.sol", "b.sol"]);
+        let Some(Command::Fix(args)) = args.unwrap().command else { panic!() };
+        assert!(args.dry_run);
+        assert_eq!(args.paths, [PathBuf::from("a.sol"), PathBuf::from("b.sol")]);
+
+        assert!(Args::try_parse_from(["solar", "fix"]).is_err());
+    }
+
+    #[test]
+    fn error_limit() {
+        let args = Args::try_parse_from(["solar", "a.sol"]).unwrap();
+        assert_eq!(args.error_limit, 100);
+        let args = Args::try_parse_from(["solar", "--error-limit", "0", "a.sol"]).unwrap();
+        assert_eq!(args.error_limit, 0);
+    }
+
+    #[test]
+    fn json_lines() {
+        let args =
+            Args::try_parse_from(["solar", "--error-format", "solc-json", "--json-lines", "a.sol"]);
+        let args = args.unwrap();
+        assert!(matches!(args.error_format, ErrorFormat::SolcJson));
+        assert!(args.json_lines);
+    }
+
+    #[test]
+    fn message_level() {
+        let args = Args::try_parse_from(["solar", "a.sol"]).unwrap();
+        assert_eq!(args.message_level, MessageLevel::Note);
+        assert!(!args.quiet);
+        let args = Args::try_parse_from(["solar", "--message-level", "warning", "a.sol"]).unwrap();
+        assert_eq!(args.message_level, MessageLevel::Warning);
+        let args = Args::try_parse_from(["solar", "-q", "a.sol"]).unwrap();
+        assert!(args.quiet);
+
+        assert!(Args::try_parse_from(["solar", "-q", "--message-level", "note", "a.sol"]).is_err());
+    }
+
+    #[test]
+    fn log() {
+        let args = Args::try_parse_from(["solar", "a.sol"]).unwrap();
+        assert_eq!(args.log, None);
+        let args = Args::try_parse_from(["solar", "--log", "solar_sema=debug,warn", "a.sol"]);
+        assert_eq!(args.unwrap().log.as_deref(), Some("solar_sema=debug,warn"));
+    }
+
+    #[test]
+    fn explain() {
+        let args = Args::try_parse_from(["solar", "--explain", "E2904"]).unwrap();
+        assert_eq!(args.explain.as_deref(), Some("E2904"));
+        assert!(args.input.is_empty());
+    }
+
+    #[test]
+    fn lint_levels() {
+        let args = Args::try_parse_from([
+            "solar",
+            "-A",
+            "shadowing,unused-imports",
+            "-Wnaming-convention",
+            "--deny=unused-variables",
+            "a.sol",
+        ])
+        .unwrap();
+        assert_eq!(args.allow, ["shadowing", "unused-imports"]);
+        assert_eq!(args.warn, ["naming-convention"]);
+        assert_eq!(args.deny, ["unused-variables"]);
+        assert_eq!(args.input, [PathBuf::from("a.sol")]);
+        assert!(!args.deny_warnings);
+
+        let args = Args::try_parse_from(["solar", "--deny-warnings", "-A5740", "a.sol"]).unwrap();
+        assert!(args.deny_warnings);
+        assert_eq!(args.allow, ["5740"]);
+    }
+
+    #[test]
+    fn stdin_name() {
+        let args = Args::try_parse_from(["solar", "-", "--stdin-name", "src/A.sol"]).unwrap();
+        assert_eq!(args.input, [PathBuf::from("-")]);
+        assert_eq!(args.stdin_name, Some(PathBuf::from("src/A.sol")));
     }
 }
@@ -0,0 +1,435 @@
+//! Project configuration file: `solar.toml`.
+//!
+//! Top-level keys are the long names of the command-line flags, like `evm-version` or
+//! `import-path`, and `input` for the files to compile. Flags that can be repeated take arrays.
+//! Relative paths are resolved from the directory of the configuration file. Lint levels are set in
+//! the `[lints]` table:
+//!
+//! ```toml
+//! input = ["src/Counter.sol"]
+//! import-path = ["lib"]
+//! import-map = ["@openzeppelin/=lib/openzeppelin-contracts/"]
+//! evm-version = "paris"
+//! emit = ["abi", "bin"]
+//!
+//! [lints]
+//! unused-imports = "allow"
+//! ```
+//!
+//! Flags given on the command line take precedence over the values of the file.
+//!
+//! When there is no `solar.toml`, the settings of a Foundry project are read from its
+//! `foundry.toml` instead, so that `solar` compiles the project like Forge does. The profile is
+//! selected with the `FOUNDRY_PROFILE` environment variable, like in Forge.
+
+use crate::cli::{Args, ImportMap};
+use clap::{builder::ValueHint, ArgAction, CommandFactory};
+use solar_config::LintLevel;
+use std::path::{Path, PathBuf};
+use toml_edit::{DocumentMut, Item, Value};
+
+/// The name of the project configuration file.
+pub const CONFIG_FILE_NAME: &str = "solar.toml";
+
+/// The name of the configuration file of Foundry projects.
+pub const FOUNDRY_CONFIG_FILE_NAME: &str = "foundry.toml";
+
+/// The name of the file listing import remappings, one per line.
+pub const REMAPPINGS_FILE_NAME: &str = "remappings.txt";
+
+/// The environment variable that selects the `foundry.toml` profile.
+const FOUNDRY_PROFILE_VAR: &str = "FOUNDRY_PROFILE";
+
+/// Flags that cannot be set in the configuration file.
+const EXCLUDED: &[&str] = &["config", "no-config", "help", "version"];
+
+/// Returns the path of the configuration file in `dir` or in its closest ancestor that has one.
+///
+/// `solar.toml` is preferred over `foundry.toml` in the same directory.
+pub fn find_config_file(dir: &Path) -> Option<PathBuf> {
+    dir.ancestors().find_map(|dir| {
+        [CONFIG_FILE_NAME, FOUNDRY_CONFIG_FILE_NAME]
+            .into_iter()
+            .map(|name| dir.join(name))
+            .find(|path| path.is_file())
+    })
+}
+
+/// Returns the `remappings.txt` files of the project at `root`: the ones of its dependencies in
+/// `lib/*/`, sorted, and then its own, so that the remappings of the project come last and take
+/// precedence.
+pub fn remappings_files(root: &Path) -> Vec<PathBuf> {
+    let mut files = std::fs::read_dir(root.join("lib"))
+        .into_iter()
+        .flatten()
+        .filter_map(|entry| Some(entry.ok()?.path().join(REMAPPINGS_FILE_NAME)))
+        .filter(|path| path.is_file())
+        .collect::<Vec<_>>();
+    files.sort();
+    files.extend(Some(root.join(REMAPPINGS_FILE_NAME)).filter(|path| path.is_file()));
+    files
+}
+
+/// Parses the contents of a `remappings.txt` file in `dir`, whose paths are relative to `dir`.
+///
+/// Empty lines and lines starting with `#` are ignored.
+pub fn parse_remappings(src: &str, dir: &Path) -> Result<Vec<ImportMap>, String> {
+    src.lines()
+        .enumerate()
+        .map(|(i, line)| (i, line.trim()))
+        .filter(|(_, line)| !line.is_empty() && !line.starts_with('#'))
+        .map(|(i, line)| {
+            let map = line.parse::<ImportMap>().map_err(|e| format!("line {}: {e}", i + 1))?;
+            Ok(ImportMap { map: map.map, path: dir.join(map.path) })
+        })
+        .collect()
+}
+
+/// A parsed project configuration file.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Config {
+    /// The command-line arguments equivalent to each value of the file, by argument ID.
+    pub args: Vec<(String, Vec<String>)>,
+    /// The levels of the `[lints]` table.
+    pub lints: Vec<(String, LintLevel)>,
+}
+
+impl Config {
+    /// Reads and parses the configuration file at `path`, which may be a `foundry.toml`.
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let src = std::fs::read_to_string(path).map_err(|e| e.to_string())?;
+        let root = path.parent().unwrap_or(Path::new(""));
+        if path.file_name().is_some_and(|name| name == FOUNDRY_CONFIG_FILE_NAME) {
+            let profile = std::env::var(FOUNDRY_PROFILE_VAR);
+            Self::parse_foundry(&src, root, profile.as_deref().unwrap_or("default"))
+        } else {
+            Self::parse(&src, root)
+        }
+    }
+
+    /// Parses a configuration file whose relative paths are relative to `root`.
+    pub fn parse(src: &str, root: &Path) -> Result<Self, String> {
+        let doc = src.parse::<DocumentMut>().map_err(|e| e.to_string())?;
+        let command = Args::command();
+        let mut config = Self::default();
+        for (key, item) in doc.iter() {
+            if key == "lints" {
+                config.lints = parse_lints(item)?;
+                continue;
+            }
+
+            let arg = command.get_arguments().find(|arg| match arg.get_long() {
+                Some(long) => long == key && !EXCLUDED.contains(&long),
+                None => arg.is_positional() && arg.get_id() == key,
+            });
+            let Some(arg) = arg else { return Err(format!("unknown key `{key}`")) };
+            let values = match item.as_value() {
+                Some(Value::Array(array)) => array.iter().map(scalar).collect(),
+                Some(value) => vec![scalar(value)],
+                None => Vec::new(),
+            };
+
+            let mut args = Vec::new();
+            for value in values {
+                let Some(mut value) = value else {
+                    return Err(format!("invalid value for `{key}`: expected a scalar"));
+                };
+                let is_path =
+                    matches!(arg.get_value_hint(), ValueHint::FilePath | ValueHint::DirPath);
+                if is_path || arg.get_id() == "import_map" {
+                    value = resolve_path(root, &value);
+                }
+                if arg.is_positional() {
+                    args.push(value);
+                } else if matches!(arg.get_action(), ArgAction::SetTrue) {
+                    match value.as_str() {
+                        "true" => args.push(format!("--{key}")),
+                        "false" => {}
+                        _ => return Err(format!("invalid value for `{key}`: expected a boolean")),
+                    }
+                } else {
+                    args.push(format!("--{key}={value}"));
+                }
+            }
+            config.args.push((arg.get_id().to_string(), args));
+        }
+        Ok(config)
+    }
+}
+
+impl Config {
+    /// Parses the given profile of a `foundry.toml` whose project root is `root`.
+    ///
+    /// Values of the profile override the ones of the default profile. Only the settings that have
+    /// a command-line equivalent are read, the others are ignored.
+    pub fn parse_foundry(src: &str, root: &Path, profile: &str) -> Result<Self, String> {
+        let doc = src.parse::<DocumentMut>().map_err(|e| e.to_string())?;
+        let profiles = doc.get("profile").and_then(Item::as_table_like);
+        let get_profile = |name: &str| profiles.and_then(|p| p.get(name)?.as_table_like());
+        let (default, selected) = (get_profile("default"), get_profile(profile));
+        let get = |key: &str| {
+            selected.and_then(|p| p.get(key)).or_else(|| default.and_then(|p| p.get(key)))
+        };
+        let get_str = |key: &str| match get(key) {
+            Some(item) => item.as_str().map(Some).ok_or_else(|| invalid(key, "a string")),
+            None => Ok(None),
+        };
+        let get_strs = |key: &str| match get(key) {
+            Some(item) => item
+                .as_array()
+                .and_then(|array| array.iter().map(Value::as_str).collect::<Option<Vec<_>>>())
+                .map(Some)
+                .ok_or_else(|| invalid(key, "an array of strings")),
+            None => Ok(None),
+        };
+        let get_bool = |key: &str| match get(key) {
+            Some(item) => item.as_bool().ok_or_else(|| invalid(key, "a boolean")),
+            None => Ok(false),
+        };
+
+        let mut config = Self::default();
+        let mut push = |id: &str, args: Vec<String>| config.args.push((id.to_string(), args));
+
+        let src_dir = root.join(get_str("src")?.unwrap_or("src"));
+        push("input", sol_files(&src_dir).map_err(|e| format!("{}: {e}", src_dir.display()))?);
+
+        let libs = get_strs("libs")?.unwrap_or_else(|| vec!["lib"]);
+        let import_paths = std::iter::once(root.to_path_buf())
+            .chain(libs.iter().map(|lib| root.join(lib)))
+            .filter(|path| path.is_dir())
+            .map(|path| format!("--import-path={}", path.display()));
+        push("import_path", import_paths.collect());
+
+        if let Some(remappings) = get_strs("remappings")? {
+            let remappings = remappings
+                .iter()
+                .map(|remapping| format!("--import-map={}", resolve_path(root, remapping.trim())));
+            push("import_map", remappings.collect());
+        }
+        if let Some(evm_version) = get_str("evm_version")? {
+            push("evm_version", vec![format!("--evm-version={evm_version}")]);
+        }
+        if let Some(bytecode_hash) = get_str("bytecode_hash")? {
+            push("metadata_hash", vec![format!("--metadata-hash={bytecode_hash}")]);
+        }
+        if get_bool("optimizer")? {
+            push("optimize", vec!["--optimize".into()]);
+        }
+        if let Some(runs) = get("optimizer_runs") {
+            let runs = runs.as_integer().ok_or_else(|| invalid("optimizer_runs", "an integer"))?;
+            push("optimizer_runs", vec![format!("--optimizer-runs={runs}")]);
+        }
+        if get_bool("via_ir")? {
+            push("via_ir", vec!["--via-ir".into()]);
+        }
+        if let Some(libraries) = get_strs("libraries")? {
+            // `<path>:<name>:<address>`.
+            let libraries = libraries.iter().map(|library| match library.rsplit_once(':') {
+                Some((name, address)) => format!("--libraries={name}={address}"),
+                None => format!("--libraries={library}"),
+            });
+            push("libraries", libraries.collect());
+        }
+        if get_bool("deny_warnings")? {
+            push("deny_warnings", vec!["--deny-warnings".into()]);
+        }
+        if let Some(codes) = get("ignored_error_codes").and_then(Item::as_array) {
+            let codes = codes.iter().filter_map(Value::as_integer);
+            push("allow", codes.map(|code| format!("--allow={code}")).collect());
+        }
+        Ok(config)
+    }
+}
+
+fn invalid(key: &str, expected: &str) -> String {
+    format!("invalid value for `{key}`: expected {expected}")
+}
+
+/// Returns the Solidity files in `dir` and its subdirectories, sorted, or nothing if `dir` does not
+/// exist.
+fn sol_files(dir: &Path) -> std::io::Result<Vec<String>> {
+    fn visit(dir: &Path, files: &mut Vec<String>) -> std::io::Result<()> {
+        for entry in std::fs::read_dir(dir)? {
+            let path = entry?.path();
+            if path.is_dir() {
+                visit(&path, files)?;
+            } else if path.extension().is_some_and(|ext| ext == "sol") {
+                files.push(path.display().to_string());
+            }
+        }
+        Ok(())
+    }
+
+    let mut files = Vec::new();
+    if dir.is_dir() {
+        visit(dir, &mut files)?;
+    }
+    files.sort();
+    Ok(files)
+}
+
+fn parse_lints(item: &Item) -> Result<Vec<(String, LintLevel)>, String> {
+    let Some(table) = item.as_table_like() else {
+        return Err("`lints` must be a table".into());
+    };
+    table
+        .iter()
+        .map(|(name, level)| {
+            let level = level.as_str().and_then(|level| level.parse().ok()).ok_or_else(|| {
+                format!("invalid level for lint `{name}`: expected `allow`, `warn` or `deny`")
+            })?;
+            Ok((name.to_string(), level))
+        })
+        .collect()
+}
+
+fn scalar(value: &Value) -> Option<String> {
+    match value {
+        Value::String(s) => Some(s.value().clone()),
+        Value::Integer(i) => Some(i.value().to_string()),
+        Value::Float(f) => Some(f.value().to_string()),
+        Value::Boolean(b) => Some(b.value().to_string()),
+        _ => None,
+    }
+}
+
+/// Resolves a relative path from `root`. The path of remappings, `map=path`, is resolved instead.
+fn resolve_path(root: &Path, value: &str) -> String {
+    if value == "-" {
+        return value.to_string();
+    }
+    match value.split_once('=') {
+        Some((map, path)) => format!("{map}={}", root.join(path).display()),
+        None => root.join(value).display().to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse() {
+        let src = r#"
+            input = ["src/A.sol", "@a/=lib/a/"]
+            import-path = ["lib"]
+            import-map = ["@b/=lib/b/"]
+            evm-version = "paris"
+            emit = ["abi", "bin"]
+            optimize = true
+            optimizer-runs = 1000
+            pretty-json = false
+
+            [lints]
+            unused-imports = "allow"
+            shadowing = "deny"
+        "#;
+        let config = Config::parse(src, Path::new("root")).unwrap();
+        let args = |id: &str| {
+            config.args.iter().find(|(arg, _)| arg == id).map(|(_, args)| args.as_slice())
+        };
+        assert_eq!(args("input").unwrap(), ["root/src/A.sol", "@a/=root/lib/a/"]);
+        assert_eq!(args("import_path").unwrap(), ["--import-path=root/lib"]);
+        assert_eq!(args("import_map").unwrap(), ["--import-map=@b/=root/lib/b/"]);
+        assert_eq!(args("evm_version").unwrap(), ["--evm-version=paris"]);
+        assert_eq!(args("emit").unwrap(), ["--emit=abi", "--emit=bin"]);
+        assert_eq!(args("optimize").unwrap(), ["--optimize"]);
+        assert_eq!(args("optimizer_runs").unwrap(), ["--optimizer-runs=1000"]);
+        assert_eq!(args("pretty_json").unwrap(), [] as [&str; 0]);
+        assert_eq!(
+            config.lints,
+            [("unused-imports".into(), LintLevel::Allow), ("shadowing".into(), LintLevel::Deny)]
+        );
+
+        assert!(Config::parse("unknown = 1", Path::new("")).is_err());
+        assert!(Config::parse("config = \"a.toml\"", Path::new("")).is_err());
+        assert!(Config::parse("optimize = 1", Path::new("")).is_err());
+        assert!(Config::parse("[lints]\nshadowing = \"loud\"", Path::new("")).is_err());
+    }
+
+    #[test]
+    fn remappings() {
+        let src = "# comment\n\n@a/=lib/a/src/\n  ds-test/=lib/ds-test/  \n";
+        let remappings = parse_remappings(src, Path::new("root")).unwrap();
+        let remappings = remappings.iter().map(|m| (m.map.clone(), m.path.clone()));
+        assert_eq!(
+            remappings.collect::<Vec<_>>(),
+            [
+                ("@a/".into(), PathBuf::from("root/lib/a/src/")),
+                ("ds-test/".into(), PathBuf::from("root/lib/ds-test/")),
+            ]
+        );
+        assert_eq!(
+            parse_remappings("@a/=a/\nb", Path::new("")).unwrap_err(),
+            "line 2: missing '='"
+        );
+
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        assert!(remappings_files(root).is_empty());
+        for dep in ["b", "a", "c"] {
+            std::fs::create_dir_all(root.join("lib").join(dep)).unwrap();
+        }
+        for file in ["remappings.txt", "lib/b/remappings.txt", "lib/a/remappings.txt"] {
+            std::fs::write(root.join(file), "").unwrap();
+        }
+        assert_eq!(
+            remappings_files(root),
+            ["lib/a/remappings.txt", "lib/b/remappings.txt", "remappings.txt"]
+                .map(|f| root.join(f))
+        );
+    }
+
+    #[test]
+    fn parse_foundry() {
+        let src = r#"
+            [profile.default]
+            src = "contracts"
+            libs = ["lib", "missing"]
+            remappings = ["@a/=lib/a/src/"]
+            evm_version = "shanghai"
+            optimizer = true
+            optimizer_runs = 10
+            libraries = ["src/L.sol:L:0x000000000000000000000000000000000000dEaD"]
+            via_ir = true
+
+            [profile.ci]
+            evm_version = "paris"
+            optimizer_runs = 1000000
+        "#;
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        std::fs::create_dir_all(root.join("contracts/nested")).unwrap();
+        std::fs::create_dir_all(root.join("lib")).unwrap();
+        for file in ["contracts/B.sol", "contracts/nested/A.sol", "contracts/README.md"] {
+            std::fs::write(root.join(file), "").unwrap();
+        }
+
+        let config = Config::parse_foundry(src, root, "ci").unwrap();
+        let args = |id: &str| {
+            config.args.iter().find(|(arg, _)| arg == id).map(|(_, args)| args.clone()).unwrap()
+        };
+        let path = |path: &str| root.join(path).display().to_string();
+        assert_eq!(args("input"), [path("contracts/B.sol"), path("contracts/nested/A.sol")]);
+        assert_eq!(
+            args("import_path"),
+            [format!("--import-path={}", root.display()), format!("--import-path={}", path("lib"))]
+        );
+        assert_eq!(args("import_map"), [format!("--import-map=@a/={}", path("lib/a/src/"))]);
+        assert_eq!(args("evm_version"), ["--evm-version=paris"]);
+        assert_eq!(args("optimize"), ["--optimize"]);
+        assert_eq!(args("optimizer_runs"), ["--optimizer-runs=1000000"]);
+        assert_eq!(args("via_ir"), ["--via-ir"]);
+        assert_eq!(
+            args("libraries"),
+            ["--libraries=src/L.sol:L=0x000000000000000000000000000000000000dEaD"]
+        );
+
+        let config = Config::parse_foundry(src, root, "default").unwrap();
+        assert!(config
+            .args
+            .contains(&("evm_version".into(), vec!["--evm-version=shanghai".into()])));
+
+        assert!(Config::parse_foundry("[profile.default]\nsrc = 1", root, "default").is_err());
+    }
+}
@@ -0,0 +1,226 @@
+//! The `fix` subcommand, which applies the machine-applicable suggestions of diagnostics.
+
+use crate::cli::FixArgs;
+use solar_interface::{
+    diagnostics::{Applicability, Diagnostic},
+    source_map::FileName,
+    Result, Session,
+};
+use solar_sema::ParsingContext;
+use std::{collections::BTreeMap, io::Write, ops::Range, path::PathBuf};
+
+/// A replacement of a byte range of a file, relative to its original contents.
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct Edit {
+    range: Range<usize>,
+    snippet: String,
+}
+
+/// Compiles the given files, then applies the machine-applicable suggestions of the emitted
+/// diagnostics to the source files in place, or prints them as a diff with `--dry-run`.
+pub fn run(sess: &Session, args: &FixArgs) -> Result<()> {
+    let (result, diagnostics) = sess.dcx.capture(|| compile(sess, args));
+    let edits = collect_edits(sess, &diagnostics);
+    sess.dcx.emit_captured(diagnostics);
+    if edits.is_empty() {
+        return result;
+    }
+
+    let mut stdout = std::io::stdout().lock();
+    let mut fixed = 0;
+    for (path, edits) in &edits {
+        let src = std::fs::read_to_string(path)
+            .map_err(|e| sess.dcx.err(format!("failed to read {}: {e}", path.display())).emit())?;
+        if edits.last().is_some_and(|edit| edit.range.end > src.len()) {
+            let msg = format!("{} was modified during compilation", path.display());
+            return Err(sess.dcx.err(msg).emit());
+        }
+        if args.dry_run {
+            stdout
+                .write_all(diff(&path.display().to_string(), &src, edits).as_bytes())
+                .map_err(|e| sess.dcx.err(format!("failed to write to stdout: {e}")).emit())?;
+        } else {
+            std::fs::write(path, apply(&src, edits)).map_err(|e| {
+                sess.dcx.err(format!("failed to write {}: {e}", path.display())).emit()
+            })?;
+        }
+        fixed += edits.len();
+    }
+    if !args.dry_run {
+        let fixes = if fixed == 1 { "fix" } else { "fixes" };
+        let files = edits.len();
+        let s = solar_interface::pluralize!(files);
+        eprintln!("applied {fixed} {fixes} to {files} file{s}");
+    }
+    Ok(())
+}
+
+fn compile(sess: &Session, args: &FixArgs) -> Result<()> {
+    let mut pcx = ParsingContext::new(sess);
+    pcx.linter = Some(solar_lint::LINTER);
+    let root = (!args.no_auto_remappings).then_some(std::path::Path::new(""));
+    crate::configure_file_resolver(
+        sess,
+        &mut pcx,
+        root,
+        args.import_map.iter().cloned(),
+        &args.import_path,
+    )?;
+    pcx.load_files(&args.paths)?;
+    pcx.parse_and_resolve()
+}
+
+/// Collects the edits of the machine-applicable suggestions of the given diagnostics, by file.
+///
+/// Suggestions that overlap a previous one, or that edit a file that is not on disk, are skipped.
+/// Edits are sorted by position.
+fn collect_edits(sess: &Session, diagnostics: &[Diagnostic]) -> BTreeMap<PathBuf, Vec<Edit>> {
+    let sm = sess.source_map();
+    let mut edits = BTreeMap::<PathBuf, Vec<Edit>>::new();
+    let suggestions = diagnostics.iter().flat_map(|diag| &diag.suggestions);
+    for suggestion in suggestions {
+        if suggestion.applicability != Applicability::MachineApplicable {
+            continue;
+        }
+        let Some(first) = suggestion.parts.first() else { continue };
+        let file = sm.lookup_source_file(first.span.lo());
+        let FileName::Real(path) = &file.name else { continue };
+        let parts = suggestion.parts.iter().map(|part| {
+            if !file.contains(part.span.lo()) || !file.contains(part.span.hi()) {
+                return None;
+            }
+            let lo = file.original_relative_byte_pos(part.span.lo()).to_usize();
+            let hi = file.original_relative_byte_pos(part.span.hi()).to_usize();
+            Some(Edit { range: lo..hi, snippet: part.snippet.clone() })
+        });
+        let Some(new) = parts.collect::<Option<Vec<_>>>() else { continue };
+
+        let file_edits = edits.entry(path.clone()).or_default();
+        let overlaps = |a: &Edit, b: &Edit| {
+            a.range.start < b.range.end && b.range.start < a.range.end
+                || a.range.start == b.range.start
+        };
+        if new.iter().any(|a| file_edits.iter().any(|b| overlaps(a, b))) {
+            continue;
+        }
+        file_edits.extend(new);
+        file_edits.sort_by_key(|edit| edit.range.start);
+    }
+    edits.retain(|_, edits| !edits.is_empty());
+    edits
+}
+
+/// Applies the sorted, non-overlapping edits to `src`.
+fn apply(src: &str, edits: &[Edit]) -> String {
+    let mut out = String::with_capacity(src.len());
+    let mut pos = 0;
+    for edit in edits {
+        out.push_str(&src[pos..edit.range.start]);
+        out.push_str(&edit.snippet);
+        pos = edit.range.end;
+    }
+    out.push_str(&src[pos..]);
+    out
+}
+
+/// Formats the sorted, non-overlapping edits to `src` as a unified diff without context lines.
+///
+/// Edits that touch the same or adjacent lines are grouped into a single hunk.
+fn diff(name: &str, src: &str, edits: &[Edit]) -> String {
+    // Byte offsets of the start of each line.
+    let line_starts =
+        std::iter::once(0).chain(src.match_indices('\n').map(|(i, _)| i + 1)).collect::<Vec<_>>();
+    let line_of = |pos: usize| line_starts.partition_point(|&start| start <= pos) - 1;
+    let line_end = |line: usize| line_starts.get(line + 1).copied().unwrap_or(src.len());
+    // The last line touched by an edit.
+    let last_line_of =
+        |edit: &Edit| line_of(edit.range.end.saturating_sub(1).max(edit.range.start));
+
+    // Group the edits into hunks of whole lines.
+    let mut hunks: Vec<(Range<usize>, Range<usize>)> = Vec::new();
+    let mut i = 0;
+    while i < edits.len() {
+        let first_line = line_of(edits[i].range.start);
+        let mut last_line = last_line_of(&edits[i]);
+        let mut j = i + 1;
+        while j < edits.len() && line_of(edits[j].range.start) <= last_line + 1 {
+            last_line = last_line.max(last_line_of(&edits[j]));
+            j += 1;
+        }
+        hunks.push((first_line..last_line + 1, i..j));
+        i = j;
+    }
+
+    let mut out = format!("--- a/{name}\n+++ b/{name}\n");
+    let mut offset = 0isize;
+    for (lines, edit_range) in hunks {
+        let start = line_starts[lines.start];
+        let end = line_end(lines.end - 1);
+        let old = &src[start..end];
+        let shifted = edits[edit_range]
+            .iter()
+            .map(|edit| Edit {
+                range: edit.range.start - start..edit.range.end - start,
+                snippet: edit.snippet.clone(),
+            })
+            .collect::<Vec<_>>();
+        let new = apply(old, &shifted);
+
+        let old_lines = old.lines().collect::<Vec<_>>();
+        let new_lines = new.lines().collect::<Vec<_>>();
+        let old_start = lines.start + 1;
+        let new_start = (old_start as isize + offset) as usize;
+        let range = |start: usize, len: usize| {
+            // An empty range starts at the line before it.
+            if len == 0 {
+                format!("{},0", start - 1)
+            } else {
+                format!("{start},{len}")
+            }
+        };
+        out.push_str(&format!(
+            "@@ -{} +{} @@\n",
+            range(old_start, old_lines.len()),
+            range(new_start, new_lines.len())
+        ));
+        for line in &old_lines {
+            out.push_str(&format!("-{line}\n"));
+        }
+        for line in &new_lines {
+            out.push_str(&format!("+{line}\n"));
+        }
+        offset += new_lines.len() as isize - old_lines.len() as isize;
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn edit(range: Range<usize>, snippet: &str) -> Edit {
+        Edit { range, snippet: snippet.into() }
+    }
+
+    #[test]
+    fn apply_edits() {
+        let src = "function f(, uint a) {}";
+        assert_eq!(apply(src, &[edit(11..12, "")]), "function f( uint a) {}");
+        assert_eq!(
+            apply(src, &[edit(0..8, "modifier"), edit(18..19, "_a")]),
+            "modifier f(, uint _a) {}"
+        );
+    }
+
+    #[test]
+    fn diff_edits() {
+        let src = "a\nb\nc\nd\ne\n";
+        let edits = [edit(2..3, "B"), edit(4..5, "C"), edit(8..10, "")];
+        assert_eq!(
+            diff("x.sol", src, &edits),
+            "--- a/x.sol\n+++ b/x.sol\n\
+             @@ -2,2 +2,2 @@\n-b\n-c\n+B\n+C\n\
+             @@ -5,1 +4,0 @@\n-e\n"
+        );
+    }
+}
@@ -0,0 +1,43 @@
+//! The `flatten` subcommand, which flattens a Solidity source file and its imports.
+
+use crate::cli::FlattenArgs;
+use solar_interface::{Result, Session};
+use solar_sema::{flatten::FlattenError, ParsingContext};
+use std::{io::Write, path::Path, sync::Arc};
+
+/// Flattens the given file and its imports, and writes the result to the output file, or prints it
+/// to the standard output.
+pub fn run(sess: &Session, args: &FlattenArgs) -> Result<()> {
+    let mut pcx = ParsingContext::new(sess);
+    let root = (!args.no_auto_remappings).then_some(Path::new(""));
+    crate::configure_file_resolver(
+        sess,
+        &mut pcx,
+        root,
+        args.import_map.iter().cloned(),
+        &args.import_path,
+    )?;
+    pcx.load_file(&args.path)?;
+    let file = sess.source_map().files().last().cloned().unwrap();
+
+    let flattened = pcx.parse_and_resolve_with(|gcx| {
+        let id = gcx.hir.source_ids().find(|&id| Arc::ptr_eq(&gcx.hir.source(id).file, &file));
+        solar_sema::flatten::flatten(gcx, id.unwrap()).map_err(|e| {
+            let span = match e {
+                FlattenError::UnitAlias(span) | FlattenError::Rename(span, _) => span,
+                FlattenError::Parse => return sess.dcx.err(e.to_string()).emit(),
+            };
+            sess.dcx.err(e.to_string()).span(span).emit()
+        })
+    })?;
+    let Some(flattened) = flattened else { return Ok(()) };
+    let flattened = flattened?;
+
+    match &args.output {
+        Some(path) => std::fs::write(path, flattened)
+            .map_err(|e| sess.dcx.err(format!("failed to write {}: {e}", path.display())).emit()),
+        None => std::io::stdout()
+            .write_all(flattened.as_bytes())
+            .map_err(|e| sess.dcx.err(format!("failed to write to stdout: {e}")).emit()),
+    }
+}
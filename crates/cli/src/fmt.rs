@@ -0,0 +1,49 @@
+//! The `fmt` subcommand, which formats Solidity source files.
+
+use crate::cli::FmtArgs;
+use solar_interface::{Result, Session};
+use solar_parse::{ast::pretty::FormatConfig, format::format_file};
+use std::{io::Write, path::Path};
+
+/// Formats the given files in place, or checks that they are formatted with `--check`.
+///
+/// `-` formats the standard input and prints the result to the standard output.
+pub fn run(sess: &Session, args: &FmtArgs) -> Result<()> {
+    let config = FormatConfig {
+        line_width: args.line_width,
+        indent_width: args.indent_width,
+        use_tabs: args.use_tabs,
+        bracket_spacing: args.bracket_spacing,
+        int_types: args.int_types,
+    };
+    let mut result = Ok(());
+    for path in &args.paths {
+        result = format_path(sess, path, &config, args.check).and(result);
+    }
+    result
+}
+
+fn format_path(sess: &Session, path: &Path, config: &FormatConfig, check: bool) -> Result<()> {
+    let stdin = path == Path::new("-");
+    let file =
+        if stdin { sess.source_map().load_stdin() } else { sess.source_map().load_file(path) };
+    let file = file.map_err(|e| {
+        let name = if stdin { "<stdin>".into() } else { path.display().to_string() };
+        sess.dcx.err(format!("failed to read {name}: {e}")).emit()
+    })?;
+    let formatted = format_file(sess, &file, config)?;
+    if check {
+        if formatted != *file.src {
+            let msg = format!("{} is not formatted", file.name.display());
+            return Err(sess.dcx.err(msg).emit());
+        }
+    } else if stdin {
+        std::io::stdout()
+            .write_all(formatted.as_bytes())
+            .map_err(|e| sess.dcx.err(format!("failed to write to stdout: {e}")).emit())?;
+    } else if formatted != *file.src {
+        std::fs::write(path, formatted)
+            .map_err(|e| sess.dcx.err(format!("failed to write {}: {e}", path.display())).emit())?;
+    }
+    Ok(())
+}
@@ -5,18 +5,35 @@
 )]
 #![cfg_attr(docsrs, feature(doc_cfg, doc_auto_cfg))]
 
-use clap::Parser as _;
+use clap::{parser::ValueSource, CommandFactory, Parser as _};
 use cli::Args;
 use solar_interface::{
-    diagnostics::{DiagCtxt, DynEmitter, HumanEmitter, JsonEmitter},
+    diagnostics::{
+        DiagCtxt, DiagnosticId, DynEmitter, HumanEmitter, JsonEmitter, Level, SarifEmitter,
+    },
+    lint::LintLevel,
     Result, Session, SourceMap,
 };
-use std::{collections::BTreeSet, num::NonZeroUsize, path::Path, sync::Arc};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    ffi::OsString,
+    num::NonZeroUsize,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
 
 pub mod cli;
+pub mod config;
+pub mod fix;
+pub mod flatten;
+pub mod fmt;
+pub mod lsp;
 pub mod utils;
 pub mod version;
 
+mod self_profile;
+mod watch;
+
 #[cfg(all(unix, any(target_env = "gnu", target_os = "macos")))]
 pub mod sigsegv_handler;
 
@@ -34,20 +51,101 @@ use alloy_primitives as _;
 
 use tracing as _;
 
+/// Parses the command-line arguments, merged with the values of the project configuration file.
+///
+/// See the [`config`] module for more details.
 pub fn parse_args<I, T>(itr: I) -> Result<Args, clap::Error>
 where
     I: IntoIterator<Item = T>,
-    T: Into<std::ffi::OsString> + Clone,
+    T: Into<OsString> + Clone,
 {
-    let mut args = Args::try_parse_from(itr)?;
+    let argv = itr.into_iter().map(Into::into).collect::<Vec<OsString>>();
+    // The input may only be given in the configuration file.
+    let matches = Args::command().arg_required_else_help(false).try_get_matches_from(&argv)?;
+    let config = if matches.subcommand().is_some() || matches.get_flag("no_config") {
+        None
+    } else {
+        let path = match matches.get_one::<PathBuf>("config") {
+            Some(path) => Some(path.clone()),
+            None => std::env::current_dir().ok().and_then(|dir| config::find_config_file(&dir)),
+        };
+        let load = |path: PathBuf| {
+            let config = config::Config::load(&path).map_err(|e| {
+                let msg = format!("invalid configuration file {}: {e}", path.display());
+                clap::Error::raw(clap::error::ErrorKind::InvalidValue, msg)
+            })?;
+            let root = path.parent().unwrap_or(Path::new("")).to_path_buf();
+            Ok::<_, clap::Error>((config, root))
+        };
+        path.map(load).transpose()?
+    };
+
+    let mut args = match config {
+        Some((config, root)) => {
+            let mut merged = argv.iter().take(1).cloned().collect::<Vec<_>>();
+            for (id, values) in &config.args {
+                if matches.value_source(id) != Some(ValueSource::CommandLine) {
+                    merged.extend(values.iter().map(Into::into));
+                }
+            }
+            merged.extend(argv.iter().skip(1).cloned());
+            let mut args = Args::try_parse_from(merged)?;
+            args.config_lints = config.lints;
+            args.project_root = Some(root);
+            args
+        }
+        None => Args::try_parse_from(argv)?,
+    };
     args.finish()?;
     Ok(args)
 }
 
 pub fn run_compiler_args(args: Args) -> Result<()> {
+    if let Some(code) = &args.explain {
+        return explain(code);
+    }
+    match &args.command {
+        Some(cli::Command::Lsp) => {
+            return lsp::run().map_err(|e| {
+                DiagCtxt::new_early().err(format!("language server failed: {e}")).emit()
+            });
+        }
+        Some(cli::Command::Fmt(fmt_args)) => {
+            let fmt_args = fmt_args.clone();
+            return run_compiler_with(args, move |compiler| fmt::run(&compiler.sess, &fmt_args));
+        }
+        Some(cli::Command::Fix(fix_args)) => {
+            let fix_args = fix_args.clone();
+            return run_compiler_with(args, move |compiler| fix::run(&compiler.sess, &fix_args));
+        }
+        Some(cli::Command::Flatten(flatten_args)) => {
+            let flatten_args = flatten_args.clone();
+            return run_compiler_with(args, move |compiler| {
+                flatten::run(&compiler.sess, &flatten_args)
+            });
+        }
+        None => {}
+    }
+    if args.watch {
+        return watch::run(args);
+    }
     run_compiler_with(args, Compiler::run_default)
 }
 
+/// Prints the extended explanation of an error code.
+fn explain(code: &str) -> Result<()> {
+    let Ok(id) = code.parse::<DiagnosticId>() else {
+        let msg = format!("`{code}` is not a valid error code");
+        return Err(DiagCtxt::new_early().err(msg).emit());
+    };
+    let Some(explanation) = id.explanation() else {
+        let msg = format!("no extended information for E{}", id.as_string());
+        return Err(DiagCtxt::new_early().err(msg).emit());
+    };
+    print!("{explanation}");
+    Ok(())
+}
+
 pub struct Compiler {
     pub sess: Session,
     pub args: Args,
@@ -74,20 +172,23 @@ impl Compiler {
             non_stdin_args.filter(|arg| !arg.as_os_str().as_encoded_bytes().contains(&b'='));
 
         let mut pcx = solar_sema::ParsingContext::new(sess);
+        pcx.codegen = Some(solar_codegen::BACKEND);
+        pcx.linter = Some(solar_lint::LINTER);
+        let root = (!args.no_auto_remappings)
+            .then(|| args.project_root.as_deref().unwrap_or(Path::new("")));
         let remappings = arg_remappings.chain(args.import_map.iter().cloned());
-        for map in remappings {
-            pcx.file_resolver.add_import_map(map.map, map.path);
-        }
-        for path in &args.import_path {
-            let new = pcx.file_resolver.add_import_path(path.clone());
-            if !new {
-                let msg = format!("import path {} already specified", path.display());
-                return Err(sess.dcx.err(msg).emit());
-            }
-        }
+        configure_file_resolver(sess, &mut pcx, root, remappings, &args.import_path)?;
 
-        if stdin {
-            pcx.load_stdin()?;
+        match (stdin, &args.stdin_name) {
+            (true, Some(name)) => pcx.load_stdin_as(name)?,
+            (true, None) => pcx.load_stdin()?,
+            (false, Some(_)) => {
+                return Err(sess
+                    .dcx
+                    .err("`--stdin-name` requires reading from stdin with `-`")
+                    .emit());
+            }
+            (false, None) => {}
         }
         pcx.load_files(paths)?;
 
@@ -97,13 +198,125 @@ impl Compiler {
     }
 
     fn finish_diagnostics(&self) -> Result {
+        let suppressed = self.sess.dcx.suppressed_count();
+        if self.args.verbose && suppressed > 0 {
+            let s = solar_interface::pluralize!(suppressed);
+            let msg = format!("{suppressed} lint diagnostic{s} suppressed by comments");
+            self.sess.dcx.note(msg).emit();
+        }
         self.sess.dcx.print_error_count()
     }
 }
 
+/// Adds the import remappings and paths to the file resolver.
+///
+/// If `root` is set, the `remappings.txt` files of the project at `root` are loaded first, so that
+/// the explicit remappings take precedence.
+pub(crate) fn configure_file_resolver(
+    sess: &Session,
+    pcx: &mut solar_sema::ParsingContext<'_>,
+    root: Option<&Path>,
+    remappings: impl IntoIterator<Item = cli::ImportMap>,
+    import_paths: &[PathBuf],
+) -> Result<()> {
+    if let Some(root) = root {
+        for file in config::remappings_files(root) {
+            let dir = file.parent().unwrap_or(Path::new(""));
+            let remappings = std::fs::read_to_string(&file)
+                .map_err(|e| e.to_string())
+                .and_then(|src| config::parse_remappings(&src, dir));
+            match remappings {
+                Ok(remappings) => {
+                    for map in remappings {
+                        pcx.file_resolver.add_import_map(map.map, map.path);
+                    }
+                }
+                Err(e) => {
+                    let msg = format!("ignoring invalid remappings in {}: {e}", file.display());
+                    sess.dcx.warn(msg).emit();
+                }
+            }
+        }
+    }
+    for map in remappings {
+        pcx.file_resolver.add_import_map(map.map, map.path);
+    }
+    for path in import_paths {
+        let new = pcx.file_resolver.add_import_path(path.clone());
+        if !new {
+            let msg = format!("import path {} already specified", path.display());
+            return Err(sess.dcx.err(msg).emit());
+        }
+    }
+
+    Ok(())
+}
+
+/// The name of the group of all warnings in `--allow` and `--deny`.
+const WARNINGS: &str = "warnings";
+
+/// Resolves the lint levels set in the project configuration file, with `--lint-config`,
+/// `--allow`, `--warn` and `--deny`, in this order, so that later levels override earlier ones.
+///
+/// The levels of warning codes are set in the diagnostics context.
+fn lint_levels(sess: &Session, args: &Args) -> Result<BTreeMap<String, LintLevel>> {
+    let mut levels = BTreeMap::new();
+    let mut set = |name: &str, level| {
+        if let Some(lint) = solar_lint::find_lint(name) {
+            levels.insert(lint.name.to_string(), level);
+        } else if let Some(code) = name.parse().ok().and_then(DiagnosticId::new) {
+            sess.dcx.set_code_level(code, level);
+        } else if name != WARNINGS {
+            sess.dcx.warn(format!("unknown lint: `{name}`")).emit();
+        }
+    };
+
+    for (name, level) in &args.config_lints {
+        set(name, *level);
+    }
+
+    if let Some(path) = &args.lint_config {
+        let display = path.display();
+        let config = std::fs::read_to_string(path).map_err(|e| {
+            sess.dcx.err(format!("failed to read lint configuration {display}: {e}")).emit()
+        })?;
+        let config = serde_json::from_str::<BTreeMap<String, String>>(&config).map_err(|e| {
+            sess.dcx.err(format!("invalid lint configuration {display}: {e}")).emit()
+        })?;
+        for (name, level) in &config {
+            let level = level.parse::<LintLevel>().map_err(|_| {
+                let msg = format!("invalid level `{level}` for lint `{name}` in {display}");
+                sess.dcx.err(msg).note("valid levels are `allow`, `warn` and `deny`").emit()
+            })?;
+            set(name, level);
+        }
+    }
+
+    let flags = [
+        (&args.allow, LintLevel::Allow),
+        (&args.warn, LintLevel::Warn),
+        (&args.deny, LintLevel::Deny),
+    ];
+    for (names, level) in flags {
+        for name in names {
+            set(name, level);
+        }
+    }
+    Ok(levels)
+}
+
 fn run_compiler_with(args: Args, f: impl FnOnce(&Compiler) -> Result + Send) -> Result {
+    run_compiler_with_source_map(args, SourceMap::empty(), f)
+}
+
+/// Like [`run_compiler_with`], with the given source map, e.g. to set its file loader.
+fn run_compiler_with_source_map(
+    args: Args,
+    source_map: SourceMap,
+    f: impl FnOnce(&Compiler) -> Result + Send,
+) -> Result {
     let ui_testing = args.unstable.ui_testing;
-    let source_map = Arc::new(SourceMap::empty());
+    let source_map = Arc::new(source_map);
     let emitter: Box<DynEmitter> = match args.error_format {
         cli::ErrorFormat::Human => {
             let color = match args.color {
@@ -116,16 +329,31 @@ fn run_compiler_with(args: Args, f: impl FnOnce(&Compiler) -> Result + Send) ->
                 .ui_testing(ui_testing);
             Box::new(human)
         }
-        cli::ErrorFormat::Json | cli::ErrorFormat::RustcJson => {
+        cli::ErrorFormat::Json | cli::ErrorFormat::RustcJson | cli::ErrorFormat::SolcJson => {
             let writer = Box::new(std::io::BufWriter::new(std::io::stderr()));
             let json = JsonEmitter::new(writer, source_map.clone())
                 .pretty(args.pretty_json_err)
                 .rustc_like(matches!(args.error_format, cli::ErrorFormat::RustcJson))
+                .solc_compatible(matches!(args.error_format, cli::ErrorFormat::SolcJson))
+                .line_delimited(args.json_lines)
                 .ui_testing(ui_testing);
             Box::new(json)
         }
+        cli::ErrorFormat::Sarif => {
+            let writer = Box::new(std::io::BufWriter::new(std::io::stderr()));
+            let sarif = SarifEmitter::new(writer, source_map.clone()).pretty(args.pretty_json_err);
+            Box::new(sarif)
+        }
     };
     let dcx = DiagCtxt::new(emitter).set_flags(|flags| {
+        flags.can_emit_warnings = !args.allow.iter().any(|name| name == WARNINGS);
+        flags.deny_warnings = args.deny_warnings || args.deny.iter().any(|name| name == WARNINGS);
+        flags.error_limit = NonZeroUsize::new(args.error_limit);
+        flags.message_level = match (args.quiet, args.message_level) {
+            (true, _) | (false, cli::MessageLevel::Error) => Level::Error,
+            (false, cli::MessageLevel::Warning) => Level::Warning,
+            (false, cli::MessageLevel::Note) => Level::Note,
+        };
         flags.deduplicate_diagnostics &= !ui_testing;
         flags.track_diagnostics &= !ui_testing;
         flags.track_diagnostics |= args.unstable.track_diagnostics;
@@ -137,6 +365,11 @@ fn run_compiler_with(args: Args, f: impl FnOnce(&Compiler) -> Result + Send) ->
     sess.stop_after = args.stop_after;
     sess.dump = args.unstable.dump.clone();
     sess.ast_stats = args.unstable.ast_stats;
+    sess.unpretty = args.unstable.unpretty;
+    sess.time_passes = args.unstable.time_passes;
+    sess.eof = args.unstable.eof;
+    // Flattening renames the declarations that conflict, along with the names referring to them.
+    sess.record_references = matches!(args.command, Some(cli::Command::Flatten(_)));
     sess.jobs = NonZeroUsize::new(args.threads)
         .unwrap_or_else(|| std::thread::available_parallelism().unwrap_or(NonZeroUsize::MIN));
     if !args.input.is_empty()
@@ -154,12 +387,33 @@ fn run_compiler_with(args: Args, f: impl FnOnce(&Compiler) -> Result + Send) ->
         }
         set
     };
+    sess.legacy_syntax = args.legacy_syntax;
+    sess.bytecode_hash = args.metadata_hash;
+    sess.libraries = args.libraries.clone();
+    sess.via_ir = args.via_ir;
+    sess.optimizer = solar_config::OptimizerSettings {
+        enabled: args.optimize,
+        runs: args.optimizer_runs,
+        enabled_passes: args.unstable.enable_pass.clone(),
+        disabled_passes: args.unstable.disable_pass.clone(),
+        pipeline: args.unstable.optimizer_pipeline.clone(),
+    };
+    if let Err(msg) = solar_codegen::opt::PassManager::from_settings(&sess.optimizer) {
+        return Err(sess.dcx.err(msg).emit());
+    }
     sess.out_dir = args.out_dir.clone();
+    sess.cache_dir = args.cache_dir.clone();
     sess.pretty_json = args.pretty_json;
+    sess.lint_levels = lint_levels(&sess, &args)?;
 
     let compiler = Compiler { sess, args };
     compiler.sess.enter(|| {
         let mut r = f(&compiler);
+        compiler.sess.print_time_passes();
+        // The memory usage is not deterministic, so it is not printed in UI tests.
+        if compiler.args.verbose && !ui_testing {
+            compiler.sess.print_memory_stats();
+        }
         r = compiler.finish_diagnostics().and(r);
         r
     })
@@ -0,0 +1,609 @@
+//! Language server, run with `solar lsp`.
+//!
+//! Implements the [Language Server Protocol] over stdin and stdout. Open documents are
+//! synchronized in full and analyzed on every change, along with the files they import, to publish
+//! their diagnostics. Open documents take precedence over the files on disk.
+//!
+//! Also provides hovers, go to definition, find all references, completions, document symbols and
+//! semantic tokens.
+//!
+//! [Language Server Protocol]: https://microsoft.github.io/language-server-protocol/specifications/lsp/3.17/specification/
+
+use serde_json::{json, Value};
+use solar_interface::{
+    diagnostics::{DiagCtxt, Diagnostic, Emitter, Level},
+    source_map::{FileName, SourceFile},
+    BytePos, Session, SourceMap, Span,
+};
+use solar_parse::Parser;
+use solar_sema::{
+    ast::{self, NatSpecKind},
+    completion::completions,
+    outline::{outline, OutlineItem},
+    semantic_tokens::{semantic_tokens, SemanticTokenKind},
+    ty::{Gcx, Hover},
+    ParsingContext,
+};
+use std::{
+    collections::BTreeMap,
+    fmt::Write as _,
+    io::{self, BufRead, Read, Write},
+    panic::{self, AssertUnwindSafe},
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+};
+
+/// JSON-RPC error code of unknown methods.
+const METHOD_NOT_FOUND: i64 = -32601;
+/// JSON-RPC error code of invalid method parameters.
+const INVALID_PARAMS: i64 = -32602;
+/// JSON-RPC error code of internal errors.
+const INTERNAL_ERROR: i64 = -32603;
+
+/// Runs the language server until the client sends the `exit` notification or closes stdin.
+pub fn run() -> io::Result<()> {
+    let mut server = Server::new(io::stdout());
+    let mut input = io::stdin().lock();
+    while let Some(message) = read_message(&mut input)? {
+        if !server.handle(message)? {
+            break;
+        }
+    }
+    Ok(())
+}
+
+/// Reads a message, returning `None` at the end of the input.
+fn read_message(r: &mut impl BufRead) -> io::Result<Option<Value>> {
+    let mut length = None;
+    loop {
+        let mut line = String::new();
+        if r.read_line(&mut line)? == 0 {
+            return Ok(None);
+        }
+        let line = line.trim_end();
+        if line.is_empty() {
+            break;
+        }
+        if let Some(value) = line.strip_prefix("Content-Length:") {
+            length = value.trim().parse::<usize>().ok();
+        }
+    }
+    let length = length.ok_or_else(|| {
+        io::Error::new(io::ErrorKind::InvalidData, "missing `Content-Length` header")
+    })?;
+    let mut body = vec![0; length];
+    r.read_exact(&mut body)?;
+    serde_json::from_slice(&body).map(Some).map_err(Into::into)
+}
+
+fn write_message(w: &mut impl Write, message: &Value) -> io::Result<()> {
+    let body = serde_json::to_string(message)?;
+    write!(w, "Content-Length: {}\r\n\r\n{body}", body.len())?;
+    w.flush()
+}
+
+struct Server<W> {
+    out: W,
+    /// The text of the open documents, by URI.
+    documents: BTreeMap<String, String>,
+    /// The root of the workspace, used as an import path.
+    root: Option<PathBuf>,
+}
+
+impl<W: Write> Server<W> {
+    fn new(out: W) -> Self {
+        Self { out, documents: BTreeMap::new(), root: None }
+    }
+
+    /// Handles a request or notification, returning `false` if the server should exit.
+    fn handle(&mut self, message: Value) -> io::Result<bool> {
+        // Responses to requests sent by the server have no method.
+        let Some(method) = message["method"].as_str() else { return Ok(true) };
+        let params = &message["params"];
+        let result = match method {
+            "initialize" => {
+                self.root = params["rootUri"].as_str().and_then(uri_to_path);
+                let token_types = SemanticTokenKind::ALL.map(SemanticTokenKind::lsp_name);
+                Ok(json!({
+                    "capabilities": {
+                        "textDocumentSync": { "openClose": true, "change": 1 },
+                        "hoverProvider": true,
+                        "definitionProvider": true,
+                        "referencesProvider": true,
+                        "completionProvider": { "triggerCharacters": ["."] },
+                        "documentSymbolProvider": true,
+                        "semanticTokensProvider": {
+                            "legend": {
+                                "tokenTypes": token_types,
+                                "tokenModifiers": [],
+                            },
+                            "full": true,
+                        },
+                    },
+                    "serverInfo": { "name": "solar", "version": solar_interface::VERSION },
+                }))
+            }
+            "shutdown" => Ok(Value::Null),
+            "exit" => return Ok(false),
+            "textDocument/didOpen" | "textDocument/didChange" => {
+                let uri = params["textDocument"]["uri"].as_str().unwrap_or_default();
+                let text = if method == "textDocument/didOpen" {
+                    params["textDocument"]["text"].as_str()
+                } else {
+                    params["contentChanges"].as_array().and_then(|c| c.last()?["text"].as_str())
+                };
+                if let Some(text) = text {
+                    self.documents.insert(uri.to_string(), text.to_string());
+                    self.publish_diagnostics(uri)?;
+                }
+                return Ok(true);
+            }
+            "textDocument/didClose" => {
+                let uri = params["textDocument"]["uri"].as_str().unwrap_or_default();
+                self.documents.remove(uri);
+                let params = json!({ "uri": uri, "diagnostics": [] });
+                self.notify("textDocument/publishDiagnostics", params)?;
+                return Ok(true);
+            }
+            "textDocument/hover" => self.request(|this| this.hover(params)),
+            "textDocument/definition" => self.request(|this| this.definition(params)),
+            "textDocument/references" => self.request(|this| this.references(params)),
+            "textDocument/completion" => self.request(|this| this.completion(params)),
+            "textDocument/documentSymbol" => self.request(|this| this.document_symbols(params)),
+            "textDocument/semanticTokens/full" => self.request(|this| this.semantic_tokens(params)),
+            _ => {
+                Err(ResponseError::new(METHOD_NOT_FOUND, format!("unsupported method `{method}`")))
+            }
+        };
+        // Notifications have no ID and no response.
+        if let Some(id) = message.get("id") {
+            let response = match result {
+                Ok(result) => json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+                Err(ResponseError { code, message }) => json!({
+                    "jsonrpc": "2.0",
+                    "id": id,
+                    "error": { "code": code, "message": message },
+                }),
+            };
+            write_message(&mut self.out, &response)?;
+        }
+        Ok(true)
+    }
+
+    /// Runs a request handler, turning panics into internal errors so that a bug in the analysis
+    /// does not take down the server.
+    fn request(
+        &self,
+        f: impl FnOnce(&Self) -> Result<Value, ResponseError>,
+    ) -> Result<Value, ResponseError> {
+        panic::catch_unwind(AssertUnwindSafe(|| f(self))).unwrap_or_else(|payload| {
+            let message = match payload.downcast_ref::<&str>() {
+                Some(&s) => s,
+                None => payload.downcast_ref::<String>().map_or("unknown panic", String::as_str),
+            };
+            Err(ResponseError::new(INTERNAL_ERROR, format!("internal error: {message}")))
+        })
+    }
+
+    fn notify(&mut self, method: &str, params: Value) -> io::Result<()> {
+        write_message(
+            &mut self.out,
+            &json!({ "jsonrpc": "2.0", "method": method, "params": params }),
+        )
+    }
+
+    /// Analyzes the document and publishes the diagnostics located in it.
+    fn publish_diagnostics(&mut self, uri: &str) -> io::Result<()> {
+        let ((), analysis) = self.analyze(uri, |_, _| ());
+        let diagnostics = match &analysis.file {
+            Some(file) => analysis
+                .diagnostics
+                .iter()
+                .filter_map(|diagnostic| to_lsp_diagnostic(diagnostic, file))
+                .collect(),
+            None => Vec::new(),
+        };
+        self.notify(
+            "textDocument/publishDiagnostics",
+            json!({ "uri": uri, "diagnostics": diagnostics }),
+        )
+    }
+
+    /// Returns the hover contents at the given position, describing the item that the name there
+    /// refers to.
+    fn hover(&self, params: &Value) -> Result<Value, ResponseError> {
+        let uri = document_uri(params)?;
+        let position = position(params)?;
+        let (hover, _) = self.analyze(uri, |gcx, file| {
+            let hover = gcx.hover(lsp_to_pos(file, position)?)?;
+            Some(json!({
+                "contents": { "kind": "markdown", "value": hover_markdown(&hover) },
+                "range": span_to_lsp(file, hover.span),
+            }))
+        });
+        Ok(hover.flatten().unwrap_or(Value::Null))
+    }
+
+    /// Returns the location of the declaration that the name at the given position refers to.
+    fn definition(&self, params: &Value) -> Result<Value, ResponseError> {
+        let uri = document_uri(params)?;
+        let position = position(params)?;
+        let (location, _) = self.analyze(uri, |gcx, file| {
+            let span = gcx.def_at(lsp_to_pos(file, position)?)?;
+            span_to_location(gcx, span)
+        });
+        Ok(location.flatten().unwrap_or(Value::Null))
+    }
+
+    /// Returns the locations of the names referring to the same declaration as the name at the
+    /// given position.
+    fn references(&self, params: &Value) -> Result<Value, ResponseError> {
+        let uri = document_uri(params)?;
+        let position = position(params)?;
+        let include_declaration = params["context"]["includeDeclaration"].as_bool() == Some(true);
+        let (locations, _) = self.analyze(uri, |gcx, file| {
+            let pos = lsp_to_pos(file, position)?;
+            let res = gcx.res_at(pos)?;
+            let declaration = include_declaration.then(|| gcx.def_at(pos)).flatten();
+            let spans = declaration.into_iter().chain(gcx.references(res));
+            Some(spans.filter_map(|span| span_to_location(gcx, span)).collect::<Vec<_>>())
+        });
+        Ok(locations.flatten().map_or(Value::Null, Value::from))
+    }
+
+    /// Returns the completions at the given position. The document does not need to be valid.
+    fn completion(&self, params: &Value) -> Result<Value, ResponseError> {
+        let uri = document_uri(params)?;
+        let position = position(params)?;
+        let (Some(text), Some(path)) = (self.documents.get(uri), uri_to_path(uri)) else {
+            return Ok(Value::Null);
+        };
+        let offset = SourceMap::empty()
+            .new_source_file(path.clone().into(), || Ok(text.clone()))
+            .ok()
+            .and_then(|file| {
+                let pos = lsp_to_pos(&file, position)?;
+                Some(file.relative_position(pos).to_usize())
+            });
+        let Some(offset) = offset else { return Ok(Value::Null) };
+
+        let documents = &self.documents;
+        let root = self.root.clone();
+        let sess = Session::builder().with_silent_emitter(None).build();
+        let items = sess.enter(|| {
+            // The completed document is loaded by `completions`.
+            for (other, text) in documents.iter().filter(|&(other, _)| other != uri) {
+                if let Some(path) = uri_to_path(other) {
+                    let _ = sess.source_map().new_source_file(path.into(), || Ok(text.clone()));
+                }
+            }
+            let mut pcx = ParsingContext::new(&sess);
+            if let Some(root) = root {
+                pcx.file_resolver.add_import_path(root);
+            }
+            completions(pcx, path.into(), text, offset)
+        });
+        let items = items
+            .iter()
+            .map(|item| json!({ "label": item.label, "kind": item.kind.lsp_kind() }))
+            .collect::<Vec<_>>();
+        Ok(json!({ "isIncomplete": false, "items": items }))
+    }
+
+    /// Returns the outline of the document.
+    fn document_symbols(&self, params: &Value) -> Result<Value, ResponseError> {
+        let outline = self.parse(document_uri(params)?, outline);
+        Ok(outline.map_or(Value::Null, |outline| outline.iter().map(to_document_symbol).collect()))
+    }
+
+    /// Returns the semantic tokens of the document, encoded relative to each other.
+    fn semantic_tokens(&self, params: &Value) -> Result<Value, ResponseError> {
+        let Some(tokens) = self.parse(document_uri(params)?, semantic_tokens) else {
+            return Ok(Value::Null);
+        };
+
+        let mut data = Vec::with_capacity(tokens.len() * 5);
+        let (mut line, mut start) = (0, 0);
+        for token in tokens {
+            if token.line != line {
+                start = 0;
+            }
+            let kind = SemanticTokenKind::ALL.iter().position(|&kind| kind == token.kind);
+            data.extend([token.line - line, token.start - start, token.len, kind.unwrap(), 0]);
+            (line, start) = (token.line, token.start);
+        }
+        Ok(json!({ "data": data }))
+    }
+
+    /// Parses the open document with the given URI, without its imports, then calls `f` with its
+    /// source file and AST.
+    ///
+    /// `f` is not called if the document is not open or cannot be parsed.
+    fn parse<R: Send>(
+        &self,
+        uri: &str,
+        f: impl FnOnce(&SourceFile, &ast::SourceUnit<'_>) -> R + Send,
+    ) -> Option<R> {
+        let (Some(text), Some(path)) = (self.documents.get(uri), uri_to_path(uri)) else {
+            return None;
+        };
+        let sess = Session::builder().with_silent_emitter(None).build();
+        sess.enter(|| {
+            let file = sess.source_map().new_source_file(path.into(), || Ok(text.clone())).ok()?;
+            let arena = ast::Arena::new();
+            let mut parser = Parser::from_source_file(&sess, &arena, &file);
+            let ast = parser.parse_file().map_err(|e| e.emit()).ok()?;
+            Some(f(&file, &ast))
+        })
+    }
+
+    /// Parses and analyzes the document with the given URI, along with the files it imports, then
+    /// calls `f` with the global context and the document's source file.
+    ///
+    /// `f` is not called if the analysis fails, in which case the errors are in the diagnostics.
+    fn analyze<R: Send>(
+        &self,
+        uri: &str,
+        f: impl FnOnce(Gcx<'_>, &SourceFile) -> R + Send,
+    ) -> (Option<R>, Analysis) {
+        let source_map = Arc::new(SourceMap::empty());
+        let diagnostics = Arc::new(Mutex::new(Vec::new()));
+        let emitter =
+            Collector { source_map: source_map.clone(), diagnostics: diagnostics.clone() };
+        let dcx =
+            DiagCtxt::new(Box::new(emitter)).set_flags(|flags| flags.track_diagnostics = false);
+        let mut sess = Session::new(dcx, source_map.clone());
+        sess.record_references = true;
+
+        for (uri, text) in &self.documents {
+            if let Some(path) = uri_to_path(uri) {
+                let _ = source_map.new_source_file(path.into(), || Ok(text.clone()));
+            }
+        }
+        let file = uri_to_path(uri).and_then(|path| source_map.load_file(&path).ok());
+
+        let root = self.root.clone();
+        let r = sess.enter(|| {
+            let file = file.clone()?;
+            let mut pcx = ParsingContext::new(&sess);
+            if let Some(root) = root {
+                pcx.file_resolver.add_import_path(root);
+            }
+            pcx.add_file(file.clone());
+            pcx.parse_and_resolve_with(|gcx| f(gcx, &file)).ok().flatten()
+        });
+        let diagnostics = std::mem::take(&mut *diagnostics.lock().unwrap());
+        (r, Analysis { file, diagnostics })
+    }
+}
+
+/// An error response to a request.
+struct ResponseError {
+    code: i64,
+    message: String,
+}
+
+impl ResponseError {
+    fn new(code: i64, message: impl Into<String>) -> Self {
+        Self { code, message: message.into() }
+    }
+}
+
+/// Returns the `textDocument.uri` parameter of a request.
+fn document_uri(params: &Value) -> Result<&str, ResponseError> {
+    params["textDocument"]["uri"]
+        .as_str()
+        .ok_or_else(|| ResponseError::new(INVALID_PARAMS, "missing `textDocument.uri`"))
+}
+
+/// Returns the `position` parameter of a request.
+fn position(params: &Value) -> Result<&Value, ResponseError> {
+    let position = &params["position"];
+    if position["line"].is_u64() && position["character"].is_u64() {
+        Ok(position)
+    } else {
+        Err(ResponseError::new(INVALID_PARAMS, "missing or invalid `position`"))
+    }
+}
+
+/// The result of analyzing a document.
+struct Analysis {
+    /// The source file of the document.
+    file: Option<Arc<SourceFile>>,
+    /// The diagnostics emitted during the analysis, in all files.
+    diagnostics: Vec<Diagnostic>,
+}
+
+/// Emitter that collects diagnostics.
+struct Collector {
+    source_map: Arc<SourceMap>,
+    diagnostics: Arc<Mutex<Vec<Diagnostic>>>,
+}
+
+impl Emitter for Collector {
+    fn emit_diagnostic(&mut self, diagnostic: &Diagnostic) {
+        self.diagnostics.lock().unwrap().push(diagnostic.clone());
+    }
+
+    fn source_map(&self) -> Option<&Arc<SourceMap>> {
+        Some(&self.source_map)
+    }
+}
+
+/// Returns the Markdown contents of a hover.
+fn hover_markdown(hover: &Hover<'_>) -> String {
+    let mut s = format!("```solidity\n{}\n```", hover.signature);
+    if let Some(ty) = &hover.ty {
+        let _ = write!(s, "\n\nType: `{ty}`");
+    }
+    for doc in hover.docs {
+        s.push_str("\n\n");
+        match doc.kind {
+            NatSpecKind::Notice => {}
+            NatSpecKind::Param { name } => {
+                let _ = write!(s, "*@param* `{name}` ");
+            }
+            kind => {
+                let _ = write!(s, "*@{}* ", kind.tag());
+            }
+        }
+        s.push_str(doc.content.as_str());
+    }
+    s
+}
+
+/// Converts a diagnostic to an LSP diagnostic if its primary span is in the given file.
+fn to_lsp_diagnostic(diagnostic: &Diagnostic, file: &SourceFile) -> Option<Value> {
+    let span = diagnostic.span.primary_span()?;
+    if !file.contains(span.lo()) || !file.contains(span.hi()) {
+        return None;
+    }
+    let severity = match diagnostic.level() {
+        Level::Warning => 2,
+        Level::Note | Level::OnceNote => 3,
+        Level::Help | Level::OnceHelp => 4,
+        _ => 1,
+    };
+    let mut message = diagnostic.label().into_owned();
+    for child in &diagnostic.children {
+        message.push_str(&format!("\n{}: {}", child.level.to_str(), child.label()));
+    }
+    Some(json!({
+        "range": span_to_lsp(file, span),
+        "severity": severity,
+        "code": diagnostic.id(),
+        "source": "solar",
+        "message": message,
+    }))
+}
+
+/// Converts an outline item to an LSP document symbol.
+fn to_document_symbol(item: &OutlineItem) -> Value {
+    json!({
+        "name": item.name,
+        "kind": item.kind.lsp_kind(),
+        "range": item.range,
+        "selectionRange": item.selection_range,
+        "children": item.children.iter().map(to_document_symbol).collect::<Vec<_>>(),
+    })
+}
+
+/// Returns the LSP location of a span in a file on disk.
+fn span_to_location(gcx: Gcx<'_>, span: Span) -> Option<Value> {
+    let file = gcx.sess.source_map().lookup_source_file(span.lo());
+    let FileName::Real(path) = &file.name else { return None };
+    Some(json!({ "uri": path_to_uri(path), "range": span_to_lsp(&file, span) }))
+}
+
+fn span_to_lsp(file: &SourceFile, span: Span) -> Value {
+    json!({ "start": pos_to_lsp(file, span.lo()), "end": pos_to_lsp(file, span.hi()) })
+}
+
+fn pos_to_lsp(file: &SourceFile, pos: BytePos) -> Value {
+    let (line, character) = file.utf16_position(pos);
+    json!({ "line": line, "character": character })
+}
+
+fn lsp_to_pos(file: &SourceFile, position: &Value) -> Option<BytePos> {
+    let line = position["line"].as_u64()?;
+    let character = position["character"].as_u64()?;
+    file.position_from_utf16(line as usize, character as usize)
+}
+
+/// Converts a `file://` URI to a path, decoding percent-encoded bytes.
+fn uri_to_path(uri: &str) -> Option<PathBuf> {
+    let path = uri.strip_prefix("file://")?;
+    let mut bytes = Vec::with_capacity(path.len());
+    let mut iter = path.bytes();
+    while let Some(b) = iter.next() {
+        if b == b'%' {
+            let hex = [iter.next()?, iter.next()?];
+            bytes.push(u8::from_str_radix(std::str::from_utf8(&hex).ok()?, 16).ok()?);
+        } else {
+            bytes.push(b);
+        }
+    }
+    let path = String::from_utf8(bytes).ok()?;
+    // `file:///C:/path` on Windows.
+    let path = match path.strip_prefix('/') {
+        Some(rest) if cfg!(windows) && rest.get(1..2) == Some(":") => rest,
+        _ => &path,
+    };
+    Some(Path::new(path).to_path_buf())
+}
+
+/// Converts a path to a `file://` URI, percent-encoding reserved bytes.
+fn path_to_uri(path: &Path) -> String {
+    let mut path = path.to_string_lossy().into_owned();
+    if cfg!(windows) {
+        path = path.replace('\\', "/");
+    }
+    let mut uri = String::from("file://");
+    if !path.starts_with('/') {
+        uri.push('/');
+    }
+    for b in path.bytes() {
+        if b.is_ascii_alphanumeric() || b"-._~/:".contains(&b) {
+            uri.push(b as char);
+        } else {
+            let _ = write!(uri, "%{b:02X}");
+        }
+    }
+    uri
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn messages() {
+        let mut out = Vec::new();
+        write_message(&mut out, &json!({ "id": 1 })).unwrap();
+        assert_eq!(out, b"Content-Length: 8\r\n\r\n{\"id\":1}");
+        let message = read_message(&mut &out[..]).unwrap();
+        assert_eq!(message, Some(json!({ "id": 1 })));
+        assert_eq!(read_message(&mut &b""[..]).unwrap(), None);
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn uris() {
+        assert_eq!(uri_to_path("file:///a/b%20c.sol"), Some(PathBuf::from("/a/b c.sol")));
+        assert_eq!(uri_to_path("untitled:Untitled-1"), None);
+        assert_eq!(path_to_uri(Path::new("/a/b c.sol")), "file:///a/b%20c.sol");
+    }
+
+    #[test]
+    fn server() {
+        let mut server = Server::new(Vec::new());
+        let request = json!({ "jsonrpc": "2.0", "id": 1, "method": "initialize", "params": {} });
+        assert!(server.handle(request).unwrap());
+        let response = read_message(&mut &server.out[..]).unwrap().unwrap();
+        assert_eq!(response["result"]["capabilities"]["hoverProvider"], true);
+        assert_eq!(response["result"]["capabilities"]["definitionProvider"], true);
+
+        server.out.clear();
+        let request = json!({ "jsonrpc": "2.0", "id": 2, "method": "foo" });
+        assert!(server.handle(request).unwrap());
+        let response = read_message(&mut &server.out[..]).unwrap().unwrap();
+        assert_eq!(response["error"]["code"], METHOD_NOT_FOUND);
+
+        server.out.clear();
+        let request = json!({
+            "jsonrpc": "2.0",
+            "id": 3,
+            "method": "textDocument/hover",
+            "params": { "textDocument": { "uri": "file:///a.sol" } },
+        });
+        assert!(server.handle(request).unwrap());
+        let response = read_message(&mut &server.out[..]).unwrap().unwrap();
+        assert_eq!(response["error"]["code"], INVALID_PARAMS);
+
+        let error = server.request(|_| panic!("oops")).unwrap_err();
+        assert_eq!(error.code, INTERNAL_ERROR);
+        assert_eq!(error.message, "internal error: oops");
+
+        assert!(!server.handle(json!({ "jsonrpc": "2.0", "method": "exit" })).unwrap());
+    }
+}
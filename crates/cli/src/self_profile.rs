@@ -0,0 +1,195 @@
+//! Self-profiling with `-Zself-profile`.
+//!
+//! The compiler's tracing spans are written as a trace in the Chrome trace event format, which can
+//! be viewed in `chrome://tracing` or [Perfetto](https://ui.perfetto.dev).
+
+use serde_json::{json, Map, Value};
+use std::{
+    fs::File,
+    io::{BufWriter, Write},
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, Mutex,
+    },
+    time::Instant,
+};
+use tracing::{
+    field::{Field, Visit},
+    span::{Attributes, Id, Record},
+    Subscriber,
+};
+use tracing_subscriber::{layer::Context, registry::LookupSpan, Layer};
+
+/// A layer that writes the entries and exits of spans as Chrome trace events.
+pub(crate) struct ChromeTraceLayer {
+    trace: Arc<Mutex<Trace>>,
+    start: Instant,
+}
+
+/// Finishes writing the trace when dropped.
+pub(crate) struct FlushGuard(Arc<Mutex<Trace>>);
+
+struct Trace {
+    writer: BufWriter<File>,
+    empty: bool,
+}
+
+/// The recorded fields of a span, stored in its extensions.
+struct SpanArgs(Map<String, Value>);
+
+/// Creates a layer that writes a trace to a new file in `dir`.
+pub(crate) fn layer(dir: &Path) -> Result<(ChromeTraceLayer, FlushGuard), String> {
+    let path = trace_path(dir);
+    std::fs::create_dir_all(dir)
+        .and_then(|()| File::create(&path))
+        .and_then(|file| {
+            let mut writer = BufWriter::new(file);
+            writer.write_all(b"[\n")?;
+            Ok(writer)
+        })
+        .map(|writer| {
+            let trace = Arc::new(Mutex::new(Trace { writer, empty: true }));
+            let layer = ChromeTraceLayer { trace: trace.clone(), start: Instant::now() };
+            (layer, FlushGuard(trace))
+        })
+        .map_err(|e| format!("failed to create self-profile trace {}: {e}", path.display()))
+}
+
+/// Returns the path of the trace file of the current process.
+fn trace_path(dir: &Path) -> PathBuf {
+    dir.join(format!("solar-{}.json", std::process::id()))
+}
+
+impl ChromeTraceLayer {
+    fn write_event<S>(&self, id: &Id, ctx: &Context<'_, S>, phase: &str)
+    where
+        S: Subscriber + for<'a> LookupSpan<'a>,
+    {
+        let Some(span) = ctx.span(id) else { return };
+        let ts = self.start.elapsed().as_nanos() as f64 / 1000.0;
+        let mut event = json!({
+            "name": span.name(),
+            "cat": span.metadata().target(),
+            "ph": phase,
+            "ts": ts,
+            "pid": std::process::id(),
+            "tid": thread_id(),
+        });
+        if phase == "B" {
+            if let Some(SpanArgs(args)) = span.extensions().get::<SpanArgs>() {
+                if !args.is_empty() {
+                    event["args"] = Value::Object(args.clone());
+                }
+            }
+        }
+        self.trace.lock().unwrap().write(&event);
+    }
+}
+
+impl<S> Layer<S> for ChromeTraceLayer
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    fn on_new_span(&self, attrs: &Attributes<'_>, id: &Id, ctx: Context<'_, S>) {
+        let Some(span) = ctx.span(id) else { return };
+        let mut args = Map::new();
+        attrs.record(&mut ArgsVisitor(&mut args));
+        span.extensions_mut().insert(SpanArgs(args));
+    }
+
+    fn on_record(&self, id: &Id, values: &Record<'_>, ctx: Context<'_, S>) {
+        let Some(span) = ctx.span(id) else { return };
+        if let Some(SpanArgs(args)) = span.extensions_mut().get_mut::<SpanArgs>() {
+            values.record(&mut ArgsVisitor(args));
+        }
+    }
+
+    fn on_enter(&self, id: &Id, ctx: Context<'_, S>) {
+        self.write_event(id, &ctx, "B");
+    }
+
+    fn on_exit(&self, id: &Id, ctx: Context<'_, S>) {
+        self.write_event(id, &ctx, "E");
+    }
+}
+
+impl Trace {
+    fn write(&mut self, event: &Value) {
+        // Errors are ignored, since profiling should not make compilation fail.
+        if !std::mem::take(&mut self.empty) {
+            let _ = self.writer.write_all(b",\n");
+        }
+        let _ = serde_json::to_writer(&mut self.writer, event);
+    }
+}
+
+impl Drop for FlushGuard {
+    fn drop(&mut self) {
+        let mut trace = self.0.lock().unwrap_or_else(|e| e.into_inner());
+        let _ = trace.writer.write_all(b"\n]\n");
+        let _ = trace.writer.flush();
+    }
+}
+
+/// Records span fields as JSON values.
+struct ArgsVisitor<'a>(&'a mut Map<String, Value>);
+
+impl Visit for ArgsVisitor<'_> {
+    fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+        self.0.insert(field.name().to_string(), Value::String(format!("{value:?}")));
+    }
+
+    fn record_str(&mut self, field: &Field, value: &str) {
+        self.0.insert(field.name().to_string(), Value::String(value.to_string()));
+    }
+
+    fn record_i64(&mut self, field: &Field, value: i64) {
+        self.0.insert(field.name().to_string(), value.into());
+    }
+
+    fn record_u64(&mut self, field: &Field, value: u64) {
+        self.0.insert(field.name().to_string(), value.into());
+    }
+
+    fn record_bool(&mut self, field: &Field, value: bool) {
+        self.0.insert(field.name().to_string(), value.into());
+    }
+}
+
+/// Returns a small, unique identifier for the current thread.
+fn thread_id() -> u64 {
+    static NEXT: AtomicU64 = AtomicU64::new(0);
+    thread_local! {
+        static ID: u64 = NEXT.fetch_add(1, Ordering::Relaxed);
+    }
+    ID.with(|id| *id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tracing_subscriber::layer::SubscriberExt;
+
+    #[test]
+    fn chrome_trace() {
+        let dir = tempfile::tempdir().unwrap();
+        let (layer, guard) = layer(dir.path()).unwrap();
+        let subscriber = tracing_subscriber::Registry::default().with(layer);
+        tracing::subscriber::with_default(subscriber, || {
+            let outer = tracing::debug_span!("outer", file = "a.sol");
+            outer.in_scope(|| tracing::debug_span!("inner").in_scope(|| {}));
+        });
+        drop(guard);
+
+        let trace = std::fs::read_to_string(trace_path(dir.path())).unwrap();
+        let events: Vec<Value> = serde_json::from_str(&trace).unwrap();
+        let phases = events
+            .iter()
+            .map(|e| format!("{} {}", e["ph"].as_str().unwrap(), e["name"].as_str().unwrap()))
+            .collect::<Vec<_>>();
+        assert_eq!(phases, ["B outer", "B inner", "E inner", "E outer"]);
+        assert_eq!(events[0]["args"]["file"], "a.sol");
+        assert!(events.iter().all(|e| e["tid"] == events[0]["tid"]));
+    }
+}
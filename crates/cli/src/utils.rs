@@ -1,6 +1,8 @@
 //! Utility functions used by the Solar CLI.
 
+use crate::cli::Args;
 use solar_interface::diagnostics::DiagCtxt;
+use std::path::Path;
 
 #[cfg(all(feature = "jemalloc", unix))]
 use tikv_jemallocator as _;
@@ -39,14 +41,44 @@ pub const fn new_allocator() -> Allocator {
 
 /// Initialize the tracing logger.
 pub fn init_logger() -> impl Sized {
-    match try_init_logger() {
+    match try_init_logger(None, None) {
         Ok(guard) => guard,
         Err(e) => DiagCtxt::new_early().fatal(e).emit(),
     }
 }
 
-fn try_init_logger() -> Result<impl Sized, String> {
-    use tracing_subscriber::prelude::*;
+/// Initialize the tracing logger, with the logging and profiling options of the given arguments.
+///
+/// The returned guard must be kept alive until the end of the compilation, so that profiling
+/// output is written in full.
+pub fn init_logger_with(args: &Args) -> impl Sized {
+    match try_init_logger(args.log.as_deref(), args.unstable.self_profile.as_deref()) {
+        Ok(guard) => guard,
+        Err(e) => DiagCtxt::new_early().fatal(e).emit(),
+    }
+}
+
+fn try_init_logger(log: Option<&str>, self_profile: Option<&Path>) -> Result<impl Sized, String> {
+    use tracing_subscriber::{filter::LevelFilter, prelude::*, EnvFilter};
+
+    // `--log` takes precedence over `RUST_LOG`.
+    let env_filter = || match log {
+        Some(filter) => EnvFilter::try_new(filter)
+            .map_err(|e| format!("invalid `--log` filter `{filter}`: {e}")),
+        None => Ok(EnvFilter::from_default_env()),
+    };
+
+    if let Some(dir) = self_profile {
+        // The trace records all compiler passes regardless of the log filter, which only filters
+        // the logged output.
+        let (layer, guard) = crate::self_profile::layer(dir)?;
+        return tracing_subscriber::Registry::default()
+            .with(layer.with_filter(LevelFilter::DEBUG))
+            .with(tracing_subscriber::fmt::layer().with_filter(env_filter()?))
+            .try_init()
+            .map(|()| (None, Some(guard)))
+            .map_err(|e| e.to_string());
+    }
 
     let (profile_layer, guard) = match std::env::var("SOLAR_PROFILE").as_deref() {
         Ok("chrome") => {
@@ -66,11 +98,11 @@ fn try_init_logger() -> Result<impl Sized, String> {
         Err(_) => Default::default(),
     };
     tracing_subscriber::Registry::default()
-        .with(tracing_subscriber::EnvFilter::from_default_env())
+        .with(env_filter()?)
         .with(profile_layer)
         .with(tracing_subscriber::fmt::layer())
         .try_init()
-        .map(|()| guard)
+        .map(|()| (guard, None))
         .map_err(|e| e.to_string())
 }
 
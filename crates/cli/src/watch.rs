@@ -0,0 +1,169 @@
+//! Watch mode, enabled with `--watch`.
+//!
+//! The input is compiled in a new session every time one of the loaded source files changes. The
+//! contents of the files that did not change are reused from the previous compilation instead of
+//! being read again and, combined with `--cache-dir`, so are the outputs of the contracts that do
+//! not depend on the changed files.
+
+use crate::{cli::Args, run_compiler_with_source_map};
+use solar_interface::{
+    diagnostics::DiagCtxt,
+    source_map::{FileLoader, FileName, RealFileLoader},
+    Result, SourceMap,
+};
+use std::{
+    collections::HashMap,
+    io,
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+    time::{Duration, SystemTime},
+};
+
+/// How often to check the watched files for changes.
+const WATCH_INTERVAL: Duration = Duration::from_millis(200);
+
+/// Compiles the input, then compiles it again whenever one of the source files that were loaded
+/// changes, until the process is interrupted.
+pub(crate) fn run(args: Args) -> Result<()> {
+    if args.input.iter().any(|arg| *arg == Path::new("-")) {
+        let msg = "cannot watch standard input";
+        return Err(DiagCtxt::new_early().err(msg).emit());
+    }
+    let loader = CachingFileLoader::default();
+    loop {
+        let mut source_map = SourceMap::empty();
+        source_map.set_file_loader(loader.clone());
+        let mut loaded = Vec::new();
+        let _ = run_compiler_with_source_map(args.clone(), source_map, |compiler| {
+            let r = compiler.run_default();
+            loaded = compiler
+                .sess
+                .source_map()
+                .files()
+                .iter()
+                .filter_map(|file| match &file.name {
+                    FileName::Real(path) => Some(path.clone()),
+                    _ => None,
+                })
+                .collect();
+            r
+        });
+        let files = watched_files(&args, loaded);
+        if files.is_empty() {
+            return Err(DiagCtxt::new_early().err("no files to watch").emit());
+        }
+        eprintln!("watching {} files for changes...", files.len());
+        wait_for_changes(&files);
+    }
+}
+
+/// Returns the files to watch: the source files that were loaded, or the input paths if
+/// compilation failed before loading any of them.
+///
+/// Input paths that do not exist are watched until they are created.
+fn watched_files(args: &Args, loaded: Vec<PathBuf>) -> Vec<PathBuf> {
+    if !loaded.is_empty() {
+        return loaded;
+    }
+    let is_remapping = |arg: &PathBuf| arg.as_os_str().as_encoded_bytes().contains(&b'=');
+    args.input.iter().filter(|arg| !is_remapping(arg)).cloned().collect()
+}
+
+/// Blocks until any of the given files is modified, created or removed.
+fn wait_for_changes(files: &[PathBuf]) {
+    let modified = |path: &PathBuf| -> Option<SystemTime> {
+        std::fs::metadata(path).and_then(|metadata| metadata.modified()).ok()
+    };
+    let initial = files.iter().map(modified).collect::<Vec<_>>();
+    loop {
+        std::thread::sleep(WATCH_INTERVAL);
+        if files.iter().map(modified).ne(initial.iter().copied()) {
+            return;
+        }
+    }
+}
+
+/// A [`FileLoader`] that reuses the contents of the files that were not modified since they were
+/// last loaded.
+///
+/// Clones share the same contents, so that they are reused across sessions.
+#[derive(Clone, Default)]
+struct CachingFileLoader {
+    files: Arc<Mutex<HashMap<PathBuf, (SystemTime, String)>>>,
+}
+
+impl FileLoader for CachingFileLoader {
+    fn canonicalize_path(&self, path: &Path) -> io::Result<PathBuf> {
+        RealFileLoader.canonicalize_path(path)
+    }
+
+    fn load_file(&self, path: &Path) -> io::Result<String> {
+        let modified = std::fs::metadata(path)?.modified()?;
+        let mut files = self.files.lock().unwrap();
+        if let Some((last_modified, src)) = files.get(path) {
+            if *last_modified == modified {
+                return Ok(src.clone());
+            }
+        }
+        let src = RealFileLoader.load_file(path)?;
+        files.insert(path.to_path_buf(), (modified, src.clone()));
+        Ok(src)
+    }
+
+    fn load_stdin(&self) -> io::Result<String> {
+        RealFileLoader.load_stdin()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::Parser;
+    use std::fs::File;
+
+    #[test]
+    fn watched_files_fall_back_to_input() {
+        let args = Args::try_parse_from(["solar", "--watch", "a.sol", "x/=y/", "b.sol"]).unwrap();
+        let loaded = vec![PathBuf::from("c.sol")];
+        assert_eq!(watched_files(&args, loaded.clone()), loaded);
+        assert_eq!(watched_files(&args, Vec::new()), [Path::new("a.sol"), Path::new("b.sol")]);
+    }
+
+    #[test]
+    fn wait_for_missing_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("a.sol");
+        let create = {
+            let path = path.clone();
+            std::thread::spawn(move || {
+                std::thread::sleep(WATCH_INTERVAL * 2);
+                std::fs::write(path, "contract A {}").unwrap();
+            })
+        };
+        wait_for_changes(&[path]);
+        create.join().unwrap();
+    }
+
+    #[test]
+    fn reuse_unchanged_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("a.sol");
+        let loader = CachingFileLoader::default();
+        let set_modified = |time: SystemTime| {
+            File::options().write(true).open(&path).unwrap().set_modified(time).unwrap();
+        };
+        let time = SystemTime::UNIX_EPOCH + Duration::from_secs(1_000_000);
+
+        std::fs::write(&path, "contract A {}").unwrap();
+        set_modified(time);
+        assert_eq!(loader.load_file(&path).unwrap(), "contract A {}");
+
+        // The contents are not read again if the modification time did not change.
+        std::fs::write(&path, "contract B {}").unwrap();
+        set_modified(time);
+        assert_eq!(loader.clone().load_file(&path).unwrap(), "contract A {}");
+
+        set_modified(time + Duration::from_secs(1));
+        assert_eq!(loader.load_file(&path).unwrap(), "contract B {}");
+    }
+}
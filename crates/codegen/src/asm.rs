@@ -0,0 +1,837 @@
+//! EVM assembly.
+
+use crate::{
+    eof::{self, Container, EofError, SectionType},
+    Opcode,
+};
+use alloy_primitives::{hex, U256};
+use solar_config::EvmVersion;
+use solar_interface::{
+    source_map::{SourceFile, SourceMap},
+    Span,
+};
+use std::fmt::Write;
+
+/// A jump destination label, local to an [`Assembly`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Label(u32);
+
+impl Label {
+    /// Returns the index of the label.
+    pub fn index(self) -> usize {
+        self.0 as usize
+    }
+}
+
+/// An assembly item.
+///
+/// Some items are only valid in legacy code, and others only in EOF code, as noted.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AsmItem {
+    /// An opcode without immediate data.
+    Op(Opcode),
+    /// Pushes an immediate value, using the smallest possible `PUSH` instruction.
+    Push(U256),
+    /// Pushes the code offset of a label. Legacy only.
+    PushLabel(Label),
+    /// A jump destination.
+    Label(Label),
+    /// Jumps to a label: `PUSH2 label JUMP` in legacy code, `RJUMP` in EOF code.
+    Jump(Label),
+    /// Jumps to a label if the value on top of the stack is non-zero: `PUSH2 label JUMPI` in
+    /// legacy code, `RJUMPI` in EOF code.
+    JumpIf(Label),
+    /// Starts a new code section with the given number of stack inputs and outputs. EOF only.
+    ///
+    /// The section's entry label must be placed right after it.
+    CodeSection { inputs: u8, outputs: u8 },
+    /// Calls the code section whose entry is the given label. EOF only.
+    CallF(Label),
+    /// Returns the sub-assembly at the given index as the deployed container, appending the
+    /// auxiliary data in memory described by the top two stack items. EOF only.
+    ReturnContract(usize),
+    /// Pushes the code offset of a sub-assembly. Legacy only.
+    PushSubOffset(usize),
+    /// Pushes the size of a sub-assembly, including its auxiliary data. Legacy only.
+    PushSubSize(usize),
+    /// Pushes the size of the whole program, including sub-assemblies and auxiliary data. Legacy
+    /// only.
+    PushProgramSize,
+    /// Pushes the address of the library at the given index in [`Assembly::libraries`], as a
+    /// zero placeholder to be filled in when linking. Legacy only.
+    PushLibrary(usize),
+}
+
+impl From<Opcode> for AsmItem {
+    fn from(op: Opcode) -> Self {
+        Self::Op(op)
+    }
+}
+
+/// An EVM assembly program.
+///
+/// Contains a list of items, each of which is annotated with the source span it was generated
+/// from, and optionally sub-assemblies which are appended to the code as data, such as the
+/// runtime code of a contract in its deploy code.
+///
+/// When the target EVM version supports EOF, the assembly is assembled into an EOFv1 container
+/// instead, in which sub-assemblies are container sections and the auxiliary data is the data
+/// section.
+#[derive(Clone, Debug)]
+pub struct Assembly {
+    evm_version: EvmVersion,
+    items: Vec<(AsmItem, Span)>,
+    subs: Vec<Assembly>,
+    aux_data: Vec<u8>,
+    libraries: Vec<String>,
+    function_entries: Vec<Label>,
+    next_label: u32,
+    span: Span,
+}
+
+impl Assembly {
+    /// Creates a new, empty assembly targeting the given EVM version.
+    pub fn new(evm_version: EvmVersion) -> Self {
+        Self {
+            evm_version,
+            items: Vec::new(),
+            subs: Vec::new(),
+            aux_data: Vec::new(),
+            libraries: Vec::new(),
+            function_entries: Vec::new(),
+            next_label: 0,
+            span: Span::DUMMY,
+        }
+    }
+
+    /// Returns the EVM version this assembly targets.
+    pub fn evm_version(&self) -> EvmVersion {
+        self.evm_version
+    }
+
+    /// Returns `true` if the assembly is assembled into an EOF container.
+    pub fn is_eof(&self) -> bool {
+        self.evm_version.supports_eof()
+    }
+
+    /// Returns the items of the assembly, along with their source spans.
+    pub fn items(&self) -> &[(AsmItem, Span)] {
+        &self.items
+    }
+
+    /// Returns the sub-assemblies.
+    pub fn subs(&self) -> &[Self] {
+        &self.subs
+    }
+
+    /// Returns the auxiliary data appended at the end of the code.
+    pub fn aux_data(&self) -> &[u8] {
+        &self.aux_data
+    }
+
+    /// Sets the auxiliary data appended at the end of the code, such as the CBOR metadata.
+    pub fn set_aux_data(&mut self, data: Vec<u8>) {
+        self.aux_data = data;
+    }
+
+    /// Adds a sub-assembly, returning its index.
+    pub fn add_sub(&mut self, sub: Self) -> usize {
+        self.subs.push(sub);
+        self.subs.len() - 1
+    }
+
+    /// Returns the fully qualified names of the libraries referenced by
+    /// [`AsmItem::PushLibrary`].
+    pub fn libraries(&self) -> &[String] {
+        &self.libraries
+    }
+
+    /// Adds a library referenced by [`AsmItem::PushLibrary`], returning its index.
+    pub fn add_library(&mut self, name: String) -> usize {
+        if let Some(i) = self.libraries.iter().position(|lib| *lib == name) {
+            return i;
+        }
+        self.libraries.push(name);
+        self.libraries.len() - 1
+    }
+
+    /// Returns the span of subsequently appended items.
+    pub fn span(&self) -> Span {
+        self.span
+    }
+
+    /// Sets the span of subsequently appended items, returning the previous one.
+    pub fn set_span(&mut self, span: Span) -> Span {
+        std::mem::replace(&mut self.span, span)
+    }
+
+    /// Creates a new label.
+    pub fn new_label(&mut self) -> Label {
+        let label = Label(self.next_label);
+        self.next_label += 1;
+        label
+    }
+
+    /// Returns the labels created with [`new_function_label`](Self::new_function_label), in
+    /// ascending order.
+    pub fn function_entries(&self) -> &[Label] {
+        &self.function_entries
+    }
+
+    /// Creates a new label for the entry of a function.
+    ///
+    /// Jumps to it are marked as jumps into a function in the source mapping.
+    pub fn new_function_label(&mut self) -> Label {
+        let label = self.new_label();
+        self.function_entries.push(label);
+        label
+    }
+
+    /// Appends an item.
+    pub fn push(&mut self, item: impl Into<AsmItem>) {
+        self.items.push((item.into(), self.span));
+    }
+
+    /// Appends multiple items.
+    pub fn extend(&mut self, items: impl IntoIterator<Item = impl Into<AsmItem>>) {
+        for item in items {
+            self.push(item);
+        }
+    }
+
+    /// Appends a `PUSH` of the given value.
+    pub fn push_value(&mut self, value: impl Into<U256>) {
+        self.push(AsmItem::Push(value.into()));
+    }
+
+    /// Appends a `PUSH` of the given label's offset.
+    pub fn push_label(&mut self, label: Label) {
+        self.push(AsmItem::PushLabel(label));
+    }
+
+    /// Places the given label, emitting a `JUMPDEST`.
+    pub fn place_label(&mut self, label: Label) {
+        self.push(AsmItem::Label(label));
+    }
+
+    /// Appends an unconditional jump to the given label.
+    pub fn jump_to(&mut self, label: Label) {
+        self.push(AsmItem::Jump(label));
+    }
+
+    /// Appends a jump to the given label if the value on top of the stack is non-zero.
+    pub fn jump_if(&mut self, label: Label) {
+        self.push(AsmItem::JumpIf(label));
+    }
+
+    /// Returns `true` if the last item unconditionally ends execution or jumps away.
+    pub fn is_terminated(&self) -> bool {
+        self.items.last().is_some_and(|&(item, _)| is_terminator(item))
+    }
+
+    /// Assembles the program into bytecode.
+    ///
+    /// Labels, sub-assembly offsets and sizes are encoded as `PUSH2`, so the total size of the
+    /// program must not exceed `0xffff` bytes.
+    ///
+    /// When targeting EOF, the program is assembled into a container which is validated before
+    /// being returned.
+    ///
+    /// Library addresses are left as zero placeholders. See
+    /// [`assemble_linkable`](Self::assemble_linkable).
+    pub fn assemble(&self) -> Result<Vec<u8>, AssembleError> {
+        self.assemble_linkable().map(|(code, _)| code)
+    }
+
+    /// Assembles the program into bytecode, like [`assemble`](Self::assemble), also returning
+    /// the byte offsets of the library address placeholders in the code, including those of
+    /// sub-assemblies, along with the fully qualified names of the libraries.
+    pub fn assemble_linkable(&self) -> Result<(Vec<u8>, Vec<(usize, String)>), AssembleError> {
+        if self.is_eof() {
+            return self.assemble_eof().map(|code| (code, Vec::new()));
+        }
+
+        let mut link_references = Vec::new();
+        let mut subs = Vec::with_capacity(self.subs.len());
+        for sub in &self.subs {
+            let (code, refs) = sub.assemble_linkable()?;
+            subs.push((code, refs));
+        }
+
+        // Labels and sizes have a fixed size, so the offsets can be computed in a single pass.
+        let mut label_offsets = vec![None; self.next_label as usize];
+        let mut code_size = 0usize;
+        for &(item, _) in &self.items {
+            if let AsmItem::Label(label) = item {
+                label_offsets[label.index()] = Some(code_size);
+            }
+            code_size += self.item_size(item);
+        }
+        let mut sub_offsets = Vec::with_capacity(subs.len());
+        let mut offset = code_size;
+        for (sub, _) in &subs {
+            sub_offsets.push(offset);
+            offset += sub.len();
+        }
+        let program_size = offset + self.aux_data.len();
+        if program_size > 0xffff {
+            return Err(AssembleError::ProgramTooLarge(program_size));
+        }
+
+        let mut code = Vec::with_capacity(program_size);
+        for &(item, _) in &self.items {
+            let push_u16 = |code: &mut Vec<u8>, value: usize| {
+                code.push(Opcode::PUSH2_BYTE);
+                code.extend_from_slice(&(value as u16).to_be_bytes());
+            };
+            let label_offset = |label: Label| {
+                label_offsets[label.index()].ok_or(AssembleError::UnplacedLabel(label))
+            };
+            match item {
+                AsmItem::Op(op) => code.push(op as u8),
+                AsmItem::Push(value) => self.encode_push(&mut code, value),
+                AsmItem::PushLabel(label) => push_u16(&mut code, label_offset(label)?),
+                AsmItem::Label(_) => code.push(Opcode::JUMPDEST as u8),
+                AsmItem::Jump(label) => {
+                    push_u16(&mut code, label_offset(label)?);
+                    code.push(Opcode::JUMP as u8);
+                }
+                AsmItem::JumpIf(label) => {
+                    push_u16(&mut code, label_offset(label)?);
+                    code.push(Opcode::JUMPI as u8);
+                }
+                AsmItem::PushSubOffset(i) => push_u16(&mut code, sub_offsets[i]),
+                AsmItem::PushSubSize(i) => push_u16(&mut code, subs[i].0.len()),
+                AsmItem::PushProgramSize => push_u16(&mut code, program_size),
+                AsmItem::PushLibrary(i) => {
+                    code.push(Opcode::PUSH20_BYTE);
+                    link_references.push((code.len(), self.libraries[i].clone()));
+                    code.extend_from_slice(&[0; 20]);
+                }
+                AsmItem::CodeSection { .. } | AsmItem::CallF(_) | AsmItem::ReturnContract(_) => {
+                    return Err(AssembleError::UnsupportedItem(item));
+                }
+            }
+        }
+        debug_assert_eq!(code.len(), code_size);
+        for ((sub, refs), &sub_offset) in subs.into_iter().zip(&sub_offsets) {
+            code.extend_from_slice(&sub);
+            link_references
+                .extend(refs.into_iter().map(|(offset, lib)| (sub_offset + offset, lib)));
+        }
+        code.extend_from_slice(&self.aux_data);
+        Ok((code, link_references))
+    }
+
+    /// Assembles the program into an EOF container.
+    ///
+    /// Each code section is assembled separately, after removing the items that are never
+    /// reached, since unreachable code is invalid in EOF.
+    fn assemble_eof(&self) -> Result<Vec<u8>, AssembleError> {
+        let containers = self.subs.iter().map(Self::assemble).collect::<Result<Vec<_>, _>>()?;
+
+        // Split the reachable items into code sections, the first of which is the entry point.
+        let mut sections = vec![(0, eof::NON_RETURNING, Vec::new())];
+        for (item, _) in self.reachable_items() {
+            match item {
+                AsmItem::CodeSection { inputs, outputs } => {
+                    sections.push((inputs, outputs, Vec::new()))
+                }
+                item => sections.last_mut().unwrap().2.push(item),
+            }
+        }
+
+        // Labels are local to their section, except for section entries which are called.
+        let mut label_offsets = vec![None; self.next_label as usize];
+        for (i, (.., items)) in sections.iter().enumerate() {
+            let mut offset = 0;
+            for &item in items {
+                if let AsmItem::Label(label) = item {
+                    label_offsets[label.index()] = Some((i, offset));
+                }
+                offset += self.item_size(item);
+            }
+        }
+
+        let mut code = Vec::with_capacity(sections.len());
+        for (i, (.., items)) in sections.iter().enumerate() {
+            let size = items.iter().map(|&item| self.item_size(item)).sum::<usize>();
+            let mut section = Vec::with_capacity(size);
+            for &item in items {
+                let label_offset = |label: Label| match label_offsets[label.index()] {
+                    Some((j, offset)) if j == i => Ok(offset),
+                    _ => Err(AssembleError::UnplacedLabel(label)),
+                };
+                match item {
+                    AsmItem::Op(op) => section.push(op as u8),
+                    AsmItem::Push(value) => self.encode_push(&mut section, value),
+                    AsmItem::Label(_) => {}
+                    AsmItem::Jump(label) | AsmItem::JumpIf(label) => {
+                        let op = if matches!(item, AsmItem::Jump(_)) {
+                            Opcode::RJUMP
+                        } else {
+                            Opcode::RJUMPI
+                        };
+                        let offset = label_offset(label)? as isize - (section.len() + 3) as isize;
+                        let offset = i16::try_from(offset)
+                            .map_err(|_| AssembleError::ProgramTooLarge(size))?;
+                        section.push(op as u8);
+                        section.extend_from_slice(&offset.to_be_bytes());
+                    }
+                    AsmItem::CallF(label) => {
+                        let target = match label_offsets[label.index()] {
+                            Some((j, 0)) if j != 0 => j,
+                            _ => return Err(AssembleError::UnplacedLabel(label)),
+                        };
+                        section.push(Opcode::CALLF as u8);
+                        section.extend_from_slice(&(target as u16).to_be_bytes());
+                    }
+                    AsmItem::ReturnContract(sub) => {
+                        section.push(Opcode::RETURNCONTRACT as u8);
+                        section.push(sub as u8);
+                    }
+                    AsmItem::PushLabel(_)
+                    | AsmItem::PushSubOffset(_)
+                    | AsmItem::PushSubSize(_)
+                    | AsmItem::PushProgramSize
+                    | AsmItem::PushLibrary(_)
+                    | AsmItem::CodeSection { .. } => {
+                        return Err(AssembleError::UnsupportedItem(item));
+                    }
+                }
+            }
+            if size > 0xffff {
+                return Err(AssembleError::ProgramTooLarge(size));
+            }
+            code.push(section);
+        }
+        if self.aux_data.len() > 0xffff {
+            return Err(AssembleError::ProgramTooLarge(self.aux_data.len()));
+        }
+
+        let types = sections
+            .iter()
+            .map(|&(inputs, outputs, _)| SectionType { inputs, outputs, max_stack_height: 0 })
+            .collect();
+        let mut container = Container { types, code, containers, data: self.aux_data.clone() };
+        let mut called = vec![false; container.code.len()];
+        for i in 0..container.code.len() {
+            container.types[i].max_stack_height =
+                eof::validate_code(&container, i, &mut called).map_err(AssembleError::Eof)?;
+        }
+        let bytes = container.encode();
+        eof::validate(&bytes).map_err(AssembleError::Eof)?;
+        Ok(bytes)
+    }
+
+    /// Returns the items that may be executed, along with the code section markers of the
+    /// sections that are called.
+    ///
+    /// An item is reachable if it follows a reachable item that does not terminate, or if it is
+    /// a label targeted by a reachable jump or call in a reachable code section.
+    fn reachable_items(&self) -> Vec<(AsmItem, Span)> {
+        let mut targets = vec![true; self.next_label as usize];
+        loop {
+            let mut items = Vec::with_capacity(self.items.len());
+            let mut new_targets = vec![false; targets.len()];
+            let mut section_reachable = true;
+            let mut reachable = true;
+            for (i, &(item, span)) in self.items.iter().enumerate() {
+                match item {
+                    AsmItem::CodeSection { .. } => {
+                        section_reachable = matches!(
+                            self.items.get(i + 1),
+                            Some(&(AsmItem::Label(label), _)) if targets[label.index()]
+                        );
+                        reachable = section_reachable;
+                    }
+                    AsmItem::Label(label) if targets[label.index()] => {
+                        reachable = section_reachable
+                    }
+                    _ => {}
+                }
+                if !reachable {
+                    continue;
+                }
+                if let AsmItem::Jump(label) | AsmItem::JumpIf(label) | AsmItem::CallF(label) = item
+                {
+                    new_targets[label.index()] = true;
+                }
+                reachable = !is_terminator(item);
+                items.push((item, span));
+            }
+            if new_targets == targets {
+                return items;
+            }
+            targets = new_targets;
+        }
+    }
+
+    /// Returns the solc-compatible compressed source mapping of the assembled code, excluding
+    /// sub-assemblies.
+    ///
+    /// Each instruction is mapped to `s:l:f:j:m`: the byte offset and length of its source span in
+    /// the file, the index of the file as returned by `source_index`, the jump type (`i` into a
+    /// function, `o` out of a function, `-` otherwise), and the modifier depth, which is not
+    /// tracked and always 0.
+    /// Instructions without a span are mapped to `-1:-1:-1`. Fields equal to those of the previous
+    /// instruction are left empty, and trailing empty fields are omitted.
+    ///
+    /// Reference: <https://docs.soliditylang.org/en/latest/internals/source_mappings.html>
+    pub fn source_mapping(
+        &self,
+        source_map: &SourceMap,
+        source_index: impl Fn(&SourceFile) -> Option<usize>,
+    ) -> String {
+        let items = if self.is_eof() { self.reachable_items() } else { self.items.clone() };
+        let mut entries = Vec::new();
+        let (mut prev_location, mut prev_jump, mut prev_depth) = ([-1; 3], '\0', -1);
+        let depth = 0;
+        for (item, span) in items {
+            let location: [i64; 3] = if span.is_dummy() {
+                [-1; 3]
+            } else {
+                let file = source_map.lookup_byte_offset(span.lo());
+                let index = source_index(&file.sf).map_or(-1, |i| i as i64);
+                let len = (span.hi() - span.lo()).to_usize();
+                [file.pos.to_usize() as i64, len as i64, index]
+            };
+            for &jump in self.jump_types(item) {
+                let fields = [
+                    (location[0] != prev_location[0]).then(|| location[0].to_string()),
+                    (location[1] != prev_location[1]).then(|| location[1].to_string()),
+                    (location[2] != prev_location[2]).then(|| location[2].to_string()),
+                    (jump != prev_jump).then(|| jump.to_string()),
+                    (depth != prev_depth).then(|| depth.to_string()),
+                ];
+                let len = fields.iter().rposition(Option::is_some).map_or(0, |i| i + 1);
+                let fields = fields[..len].iter().map(|field| field.as_deref().unwrap_or(""));
+                entries.push(fields.collect::<Vec<_>>().join(":"));
+                (prev_location, prev_jump, prev_depth) = (location, jump, depth);
+            }
+        }
+        entries.join(";")
+    }
+
+    /// Returns the jump types of the instructions the item is assembled into.
+    fn jump_types(&self, item: AsmItem) -> &'static [char] {
+        let is_entry = |label: Label| self.function_entries.binary_search(&label).is_ok();
+        match item {
+            AsmItem::CodeSection { .. } => &[],
+            AsmItem::Label(_) if self.is_eof() => &[],
+            AsmItem::Op(Opcode::JUMP | Opcode::RETF) => &['o'],
+            AsmItem::CallF(_) => &['i'],
+            AsmItem::Jump(_) | AsmItem::JumpIf(_) if self.is_eof() => &['-'],
+            AsmItem::Jump(label) if is_entry(label) => &['-', 'i'],
+            AsmItem::Jump(_) | AsmItem::JumpIf(_) => &['-', '-'],
+            _ => &['-'],
+        }
+    }
+
+    /// Returns the size of the given item in bytes.
+    pub fn item_size(&self, item: AsmItem) -> usize {
+        match item {
+            AsmItem::Op(_) => 1,
+            AsmItem::Label(_) => (!self.is_eof()) as usize,
+            AsmItem::Push(value) => 1 + self.push_immediate_size(value),
+            AsmItem::Jump(_) | AsmItem::JumpIf(_) if self.is_eof() => 3,
+            AsmItem::Jump(_) | AsmItem::JumpIf(_) => 4,
+            AsmItem::CallF(_) => 3,
+            AsmItem::ReturnContract(_) => 2,
+            AsmItem::CodeSection { .. } => 0,
+            AsmItem::PushLabel(_)
+            | AsmItem::PushSubOffset(_)
+            | AsmItem::PushSubSize(_)
+            | AsmItem::PushProgramSize => 3,
+            AsmItem::PushLibrary(_) => 21,
+        }
+    }
+
+    fn push_immediate_size(&self, value: U256) -> usize {
+        if value.is_zero() {
+            if self.evm_version.has_push0() {
+                0
+            } else {
+                1
+            }
+        } else {
+            value.byte_len()
+        }
+    }
+
+    fn encode_push(&self, code: &mut Vec<u8>, value: U256) {
+        let size = self.push_immediate_size(value);
+        code.push(Opcode::PUSH0_BYTE + size as u8);
+        code.extend_from_slice(&value.to_be_bytes::<32>()[32 - size..]);
+    }
+
+    /// Returns the human-readable text of the assembly, in the style of `solc --asm`.
+    ///
+    /// Items are preceded by a comment with the source code they were generated from whenever it
+    /// changes.
+    pub fn to_text(&self, source_map: &SourceMap) -> String {
+        let mut out = String::new();
+        self.write_text(&mut out, source_map, "");
+        out
+    }
+
+    fn write_text(&self, out: &mut String, source_map: &SourceMap, indent: &str) {
+        let mut prev_span = Span::DUMMY;
+        for &(item, span) in &self.items {
+            if span != prev_span && !span.is_dummy() {
+                let comment = source_comment(source_map, span);
+                writeln!(out, "{indent}    /* {comment} */").unwrap();
+            }
+            prev_span = span;
+            match item {
+                AsmItem::Op(op) => writeln!(out, "{indent}  {}", op.name().to_lowercase()),
+                AsmItem::Push(value) => {
+                    let bytes = value.to_be_bytes::<32>();
+                    let start = 32 - value.byte_len().max(1);
+                    writeln!(out, "{indent}  0x{}", hex::encode(&bytes[start..]))
+                }
+                AsmItem::PushLabel(label) => writeln!(out, "{indent}  tag_{}", label.index() + 1),
+                AsmItem::Label(label) => writeln!(out, "{indent}tag_{}:", label.index() + 1),
+                AsmItem::Jump(label) if self.is_eof() => {
+                    writeln!(out, "{indent}  rjump(tag_{})", label.index() + 1)
+                }
+                AsmItem::JumpIf(label) if self.is_eof() => {
+                    writeln!(out, "{indent}  rjumpi(tag_{})", label.index() + 1)
+                }
+                AsmItem::Jump(label) => {
+                    writeln!(out, "{indent}  tag_{}\n{indent}  jump", label.index() + 1)
+                }
+                AsmItem::JumpIf(label) => {
+                    writeln!(out, "{indent}  tag_{}\n{indent}  jumpi", label.index() + 1)
+                }
+                AsmItem::CodeSection { inputs, outputs } => {
+                    writeln!(out, "\n{indent}code_section({inputs}, {outputs}):")
+                }
+                AsmItem::CallF(label) => {
+                    writeln!(out, "{indent}  callf(tag_{})", label.index() + 1)
+                }
+                AsmItem::ReturnContract(i) => writeln!(out, "{indent}  returncontract(sub_{i})"),
+                AsmItem::PushSubOffset(i) => writeln!(out, "{indent}  dataOffset(sub_{i})"),
+                AsmItem::PushSubSize(i) => writeln!(out, "{indent}  dataSize(sub_{i})"),
+                AsmItem::PushProgramSize => writeln!(out, "{indent}  bytecodeSize"),
+                AsmItem::PushLibrary(i) => {
+                    writeln!(out, "{indent}  linkerSymbol(\"{}\")", self.libraries[i])
+                }
+            }
+            .unwrap();
+        }
+        if !self.subs.is_empty() {
+            if !self.is_eof() {
+                writeln!(out, "{indent}stop").unwrap();
+            }
+            let sub_indent = format!("{indent}    ");
+            for (i, sub) in self.subs.iter().enumerate() {
+                writeln!(out, "\n{indent}sub_{i}: assembly {{").unwrap();
+                sub.write_text(out, source_map, &sub_indent);
+                writeln!(out, "{indent}}}").unwrap();
+            }
+        }
+        if !self.aux_data.is_empty() {
+            writeln!(out, "\n{indent}auxdata: 0x{}", hex::encode(&self.aux_data)).unwrap();
+        }
+    }
+}
+
+/// Formats a source location comment: the file name, the byte range in the file, and the first
+/// line of the source code.
+fn source_comment(source_map: &SourceMap, span: Span) -> String {
+    let file = source_map.lookup_byte_offset(span.lo());
+    let start = file.pos.to_usize();
+    let end = start + (span.hi() - span.lo()).to_usize();
+    let name = file.sf.name.display();
+    let snippet = source_map.span_to_snippet(span).unwrap_or_default();
+    let mut lines = snippet.lines();
+    let first = lines.next().unwrap_or_default().replace("*/", "*\\/");
+    let ellipsis = if lines.next().is_some() { "..." } else { "" };
+    format!("\"{name}\":{start}:{end}  {first}{ellipsis}")
+}
+
+/// Returns `true` if the item unconditionally ends execution or jumps away.
+fn is_terminator(item: AsmItem) -> bool {
+    match item {
+        AsmItem::Op(op) => op.is_terminator(),
+        AsmItem::Jump(_) | AsmItem::ReturnContract(_) => true,
+        _ => false,
+    }
+}
+
+/// An error that occurred while assembling an [`Assembly`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum AssembleError {
+    /// A label was referenced but never placed, or placed in another EOF code section.
+    UnplacedLabel(Label),
+    /// The program is larger than the maximum size addressable by labels.
+    ProgramTooLarge(usize),
+    /// The item is not supported by the code format of the target EVM version.
+    UnsupportedItem(AsmItem),
+    /// The assembled EOF container is invalid.
+    Eof(EofError),
+}
+
+impl std::fmt::Display for AssembleError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::UnplacedLabel(label) => write!(f, "label {} was never placed", label.index()),
+            Self::ProgramTooLarge(size) => {
+                write!(f, "program size of {size} bytes exceeds the maximum of 65535 bytes")
+            }
+            Self::UnsupportedItem(item) => {
+                write!(f, "{item:?} is not supported by the target code format")
+            }
+            Self::Eof(err) => write!(f, "invalid EOF container: {err}"),
+        }
+    }
+}
+
+impl std::error::Error for AssembleError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use solar_interface::{source_map::FileName, BytePos};
+
+    #[test]
+    fn push() {
+        let mut asm = Assembly::new(EvmVersion::Cancun);
+        asm.push_value(0u64);
+        asm.push_value(1u64);
+        asm.push_value(0x1234u64);
+        asm.push_value(U256::MAX);
+        let code = asm.assemble().unwrap();
+        let mut expected = vec![0x5f, 0x60, 0x01, 0x61, 0x12, 0x34, 0x7f];
+        expected.extend([0xff; 32]);
+        assert_eq!(code, expected);
+
+        let mut asm = Assembly::new(EvmVersion::Paris);
+        asm.push_value(0u64);
+        assert_eq!(asm.assemble().unwrap(), [0x60, 0x00]);
+    }
+
+    #[test]
+    fn labels() {
+        let mut asm = Assembly::new(EvmVersion::Cancun);
+        let label = asm.new_label();
+        asm.jump_to(label);
+        asm.push(Opcode::INVALID);
+        asm.place_label(label);
+        asm.push(Opcode::STOP);
+        assert_eq!(asm.assemble().unwrap(), [0x61, 0x00, 0x05, 0x56, 0xfe, 0x5b, 0x00]);
+
+        let mut asm = Assembly::new(EvmVersion::Cancun);
+        let label = asm.new_label();
+        asm.jump_to(label);
+        assert_eq!(asm.assemble(), Err(AssembleError::UnplacedLabel(label)));
+    }
+
+    #[test]
+    fn subs() {
+        let mut sub = Assembly::new(EvmVersion::Cancun);
+        sub.push(Opcode::STOP);
+        sub.set_aux_data(vec![0xaa]);
+        let mut asm = Assembly::new(EvmVersion::Cancun);
+        let i = asm.add_sub(sub);
+        asm.push(AsmItem::PushSubSize(i));
+        asm.push(AsmItem::PushSubOffset(i));
+        asm.push(AsmItem::PushProgramSize);
+        assert_eq!(
+            asm.assemble().unwrap(),
+            [0x61, 0x00, 0x02, 0x61, 0x00, 0x09, 0x61, 0x00, 0x0b, 0x00, 0xaa]
+        );
+    }
+
+    #[test]
+    fn libraries() {
+        let mut sub = Assembly::new(EvmVersion::Cancun);
+        let lib = sub.add_library("L.sol:L".into());
+        sub.push(AsmItem::PushLibrary(lib));
+        let mut asm = Assembly::new(EvmVersion::Cancun);
+        let lib = asm.add_library("L.sol:M".into());
+        assert_eq!(asm.add_library("L.sol:M".into()), lib);
+        asm.push(AsmItem::PushLibrary(lib));
+        asm.add_sub(sub);
+        let (code, refs) = asm.assemble_linkable().unwrap();
+        assert_eq!(code.len(), 42);
+        assert_eq!(code[0], Opcode::PUSH20_BYTE);
+        assert_eq!(code[21], Opcode::PUSH20_BYTE);
+        assert_eq!(refs, [(1, "L.sol:M".to_string()), (22, "L.sol:L".to_string())]);
+    }
+
+    #[test]
+    fn eof() {
+        let mut asm = Assembly::new(EvmVersion::Osaka);
+        let f = asm.new_label();
+        let end = asm.new_label();
+        asm.push_value(1u64);
+        asm.push(AsmItem::CallF(f));
+        asm.jump_if(end);
+        asm.push(Opcode::INVALID);
+        asm.place_label(end);
+        asm.push(Opcode::STOP);
+        // Unreachable.
+        asm.push(Opcode::STOP);
+        asm.push(AsmItem::CodeSection { inputs: 1, outputs: 1 });
+        asm.place_label(f);
+        asm.push(Opcode::ISZERO);
+        asm.push(Opcode::RETF);
+        asm.set_aux_data(vec![0xaa]);
+        let header = [
+            0xef, 0x00, 0x01, 0x01, 0x00, 0x08, 0x02, 0x00, 0x02, 0x00, 0x0a, 0x00, 0x02, 0x04,
+            0x00, 0x01, 0x00,
+        ];
+        let types = [0x00, 0x80, 0x00, 0x01, 0x01, 0x01, 0x00, 0x01];
+        let code = [0x60, 0x01, 0xe3, 0x00, 0x01, 0xe1, 0x00, 0x01, 0xfe, 0x00, 0x15, 0xe4];
+        assert_eq!(asm.assemble().unwrap(), [&header[..], &types, &code, &[0xaa]].concat());
+
+        let mut asm = Assembly::new(EvmVersion::Osaka);
+        asm.push_value(0u64);
+        asm.push(AsmItem::PushProgramSize);
+        let err = AssembleError::UnsupportedItem(AsmItem::PushProgramSize);
+        assert_eq!(asm.assemble(), Err(err));
+    }
+
+    #[test]
+    fn source_mapping() {
+        let source_map = SourceMap::empty();
+        let file = source_map
+            .new_source_file(FileName::Custom("a.sol".into()), || Ok("contract A {}".into()))
+            .unwrap();
+        let span = |lo: u32, hi: u32| {
+            Span::new(BytePos(file.start_pos.0 + lo), BytePos(file.start_pos.0 + hi))
+        };
+        let mut asm = Assembly::new(EvmVersion::Cancun);
+        let f = asm.new_function_label();
+        asm.set_span(span(0, 8));
+        asm.push_value(1u64);
+        asm.jump_to(f);
+        asm.set_span(span(9, 12));
+        asm.place_label(f);
+        asm.set_span(Span::DUMMY);
+        asm.push(Opcode::JUMP);
+        let mapping = asm.source_mapping(&source_map, |_| Some(0));
+        assert_eq!(mapping, "0:8:0:-:0;;:::i;9:3::-;-1:-1:-1:o");
+    }
+
+    #[test]
+    fn text() {
+        let mut sub = Assembly::new(EvmVersion::Cancun);
+        sub.push(Opcode::STOP);
+        sub.set_aux_data(vec![0xaa]);
+        let mut asm = Assembly::new(EvmVersion::Cancun);
+        let label = asm.new_label();
+        asm.push_value(0u64);
+        asm.push_value(0x1234u64);
+        asm.jump_if(label);
+        asm.place_label(label);
+        let i = asm.add_sub(sub);
+        asm.push(AsmItem::PushSubSize(i));
+        let text = asm.to_text(&SourceMap::empty());
+        let expected = "  0x00\n  0x1234\n  tag_1\n  jumpi\ntag_1:\n  dataSize(sub_0)\nstop\n\n\
+                        sub_0: assembly {\n      stop\n\n    auxdata: 0xaa\n}\n";
+        assert_eq!(text, expected);
+    }
+}
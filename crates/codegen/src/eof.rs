@@ -0,0 +1,529 @@
+//! EVM Object Format (EOF) containers.
+//!
+//! Reference: <https://eips.ethereum.org/EIPS/eip-7692>
+
+use crate::Opcode;
+use std::fmt;
+
+/// The magic bytes at the start of every container.
+pub const MAGIC: [u8; 2] = [0xef, 0x00];
+/// The supported container version.
+pub const VERSION: u8 = 1;
+/// The number of outputs of a code section that never returns.
+pub const NON_RETURNING: u8 = 0x80;
+
+const KIND_TYPES: u8 = 0x01;
+const KIND_CODE: u8 = 0x02;
+const KIND_CONTAINER: u8 = 0x03;
+const KIND_DATA: u8 = 0x04;
+const TERMINATOR: u8 = 0x00;
+
+const MAX_CODE_SECTIONS: usize = 1024;
+const MAX_CONTAINER_SECTIONS: usize = 256;
+const MAX_STACK_HEIGHT: u16 = 1023;
+const MAX_INPUTS: u8 = 127;
+
+/// The type of a code section.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SectionType {
+    /// The number of stack items the section consumes.
+    pub inputs: u8,
+    /// The number of stack items the section returns, or [`NON_RETURNING`].
+    pub outputs: u8,
+    /// The maximum stack height reached in the section, including its inputs.
+    pub max_stack_height: u16,
+}
+
+/// An EOFv1 container.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Container {
+    /// The type of each code section.
+    pub types: Vec<SectionType>,
+    /// The code sections. The first one is the entry point.
+    pub code: Vec<Vec<u8>>,
+    /// The encoded subcontainers, referenced by `EOFCREATE` and `RETURNCONTRACT`.
+    pub containers: Vec<Vec<u8>>,
+    /// The data section.
+    pub data: Vec<u8>,
+}
+
+impl Container {
+    /// Encodes the container.
+    ///
+    /// Sections must not exceed the sizes representable in the header, which [`validate`] checks.
+    pub fn encode(&self) -> Vec<u8> {
+        let mut out = Vec::new();
+        out.extend_from_slice(&MAGIC);
+        out.push(VERSION);
+        out.push(KIND_TYPES);
+        out.extend_from_slice(&(4 * self.types.len() as u16).to_be_bytes());
+        out.push(KIND_CODE);
+        out.extend_from_slice(&(self.code.len() as u16).to_be_bytes());
+        for code in &self.code {
+            out.extend_from_slice(&(code.len() as u16).to_be_bytes());
+        }
+        if !self.containers.is_empty() {
+            out.push(KIND_CONTAINER);
+            out.extend_from_slice(&(self.containers.len() as u16).to_be_bytes());
+            for container in &self.containers {
+                out.extend_from_slice(&(container.len() as u32).to_be_bytes());
+            }
+        }
+        out.push(KIND_DATA);
+        out.extend_from_slice(&(self.data.len() as u16).to_be_bytes());
+        out.push(TERMINATOR);
+        for ty in &self.types {
+            out.push(ty.inputs);
+            out.push(ty.outputs);
+            out.extend_from_slice(&ty.max_stack_height.to_be_bytes());
+        }
+        for code in &self.code {
+            out.extend_from_slice(code);
+        }
+        for container in &self.containers {
+            out.extend_from_slice(container);
+        }
+        out.extend_from_slice(&self.data);
+        out
+    }
+
+    /// Decodes a container, validating its header but not its code.
+    pub fn decode(bytes: &[u8]) -> Result<Self, EofError> {
+        let mut r = Reader { bytes, pos: 0 };
+        if r.take(2)? != MAGIC {
+            return Err(EofError::InvalidMagic);
+        }
+        let version = r.u8()?;
+        if version != VERSION {
+            return Err(EofError::InvalidVersion(version));
+        }
+
+        r.expect_kind(KIND_TYPES)?;
+        let types_size = r.u16()? as usize;
+        r.expect_kind(KIND_CODE)?;
+        let num_code = r.u16()? as usize;
+        if num_code == 0 || num_code > MAX_CODE_SECTIONS || types_size != 4 * num_code {
+            return Err(EofError::InvalidSectionCount("code"));
+        }
+        let code_sizes =
+            (0..num_code).map(|_| r.u16().map(usize::from)).collect::<Result<Vec<_>, _>>()?;
+        let mut container_sizes = Vec::new();
+        if r.peek()? == KIND_CONTAINER {
+            r.pos += 1;
+            let num = r.u16()? as usize;
+            if num == 0 || num > MAX_CONTAINER_SECTIONS {
+                return Err(EofError::InvalidSectionCount("container"));
+            }
+            container_sizes =
+                (0..num).map(|_| r.u32().map(|size| size as usize)).collect::<Result<_, _>>()?;
+        }
+        r.expect_kind(KIND_DATA)?;
+        let data_size = r.u16()? as usize;
+        r.expect_kind(TERMINATOR)?;
+        if code_sizes.iter().chain(&container_sizes).any(|&size| size == 0) {
+            return Err(EofError::EmptySection);
+        }
+
+        let mut types = Vec::with_capacity(num_code);
+        for _ in 0..num_code {
+            let ty = SectionType { inputs: r.u8()?, outputs: r.u8()?, max_stack_height: r.u16()? };
+            if ty.inputs > MAX_INPUTS
+                || (ty.outputs > MAX_INPUTS && ty.outputs != NON_RETURNING)
+                || ty.max_stack_height > MAX_STACK_HEIGHT
+            {
+                return Err(EofError::InvalidType(types.len()));
+            }
+            types.push(ty);
+        }
+        if types[0].inputs != 0 || types[0].outputs != NON_RETURNING {
+            return Err(EofError::InvalidType(0));
+        }
+        let code = code_sizes
+            .iter()
+            .map(|&size| r.take(size).map(<[u8]>::to_vec))
+            .collect::<Result<Vec<_>, _>>()?;
+        let containers = container_sizes
+            .iter()
+            .map(|&size| r.take(size).map(<[u8]>::to_vec))
+            .collect::<Result<Vec<_>, _>>()?;
+        let data = r.take(data_size)?.to_vec();
+        if r.pos != bytes.len() {
+            return Err(EofError::TrailingBytes);
+        }
+        Ok(Self { types, code, containers, data })
+    }
+}
+
+/// Validates a container and its subcontainers: the header, the instructions, the jump
+/// destinations, the stack heights and the reachability of the code sections.
+pub fn validate(bytes: &[u8]) -> Result<Container, EofError> {
+    let container = Container::decode(bytes)?;
+    let mut called = vec![false; container.code.len()];
+    called[0] = true;
+    for (section, ty) in container.types.iter().enumerate() {
+        let max_stack_height = validate_code(&container, section, &mut called)?;
+        if max_stack_height != ty.max_stack_height {
+            return Err(EofError::InvalidMaxStackHeight { section, expected: max_stack_height });
+        }
+    }
+    if let Some(section) = called.iter().position(|&called| !called) {
+        return Err(EofError::UnreachableSection(section));
+    }
+    for sub in &container.containers {
+        validate(sub)?;
+    }
+    Ok(container)
+}
+
+/// Validates the instructions and the stack heights of a code section, returning its maximum
+/// stack height.
+///
+/// Marks the code sections it calls or jumps to in `called`. The maximum stack heights of the
+/// other sections are only used to check for stack overflows across calls.
+pub fn validate_code(
+    container: &Container,
+    section: usize,
+    called: &mut [bool],
+) -> Result<u16, EofError> {
+    let code = &container.code[section];
+    let ty = container.types[section];
+    let err = |pc: usize, kind: CodeError| EofError::Code { section, pc, kind };
+
+    // Decode the instructions.
+    let mut instructions = Vec::new();
+    let mut is_start = vec![false; code.len()];
+    let mut pc = 0;
+    while pc < code.len() {
+        let byte = code[pc];
+        let (op, imm) = if (Opcode::PUSH0_BYTE..=Opcode::PUSH0_BYTE + 32).contains(&byte) {
+            (None, (byte - Opcode::PUSH0_BYTE) as usize)
+        } else {
+            match Opcode::from_u8(byte).filter(|op| !op.is_legacy_only()) {
+                Some(op) => (Some(op), op.immediate_size()),
+                None => return Err(err(pc, CodeError::InvalidInstruction(byte))),
+            }
+        };
+        if pc + 1 + imm > code.len() {
+            return Err(err(pc, CodeError::TruncatedImmediate));
+        }
+        is_start[pc] = true;
+        instructions.push((pc, op, imm));
+        pc += 1 + imm;
+    }
+
+    // Check the immediates and compute the stack heights, in a single forward pass. Stack
+    // heights are ranges, which are merged at forward jumps and must match exactly at backward
+    // jumps.
+    let mut heights: Vec<Option<(u16, u16)>> = vec![None; code.len()];
+    heights[0] = Some((ty.inputs as u16, ty.inputs as u16));
+    let mut max_stack_height = ty.inputs as u16;
+    for &(pc, op, imm) in &instructions {
+        let Some((min, max)) = heights[pc] else {
+            return Err(err(pc, CodeError::Unreachable));
+        };
+        let immediate = |i: usize| u16::from_be_bytes([code[pc + 1 + i], code[pc + 2 + i]]);
+        let next = pc + 1 + imm;
+        let (inputs, outputs, jump) = match op {
+            None => (0, 1, None),
+            Some(Opcode::RJUMP | Opcode::RJUMPI) => {
+                let target = next as isize + immediate(0) as i16 as isize;
+                if target < 0 || target as usize >= code.len() || !is_start[target as usize] {
+                    return Err(err(pc, CodeError::InvalidJumpTarget));
+                }
+                (op.unwrap().inputs() as u16, 0, Some(target as usize))
+            }
+            Some(op @ (Opcode::CALLF | Opcode::JUMPF)) => {
+                let target = immediate(0) as usize;
+                let Some(&target_ty) = container.types.get(target) else {
+                    return Err(err(pc, CodeError::InvalidSection(target)));
+                };
+                called[target] = true;
+                let inputs = target_ty.inputs as u16;
+                if min < inputs {
+                    return Err(err(pc, CodeError::StackUnderflow));
+                }
+                if max - inputs + target_ty.max_stack_height > MAX_STACK_HEIGHT {
+                    return Err(err(pc, CodeError::StackOverflow));
+                }
+                if op == Opcode::CALLF {
+                    if target_ty.outputs == NON_RETURNING {
+                        return Err(err(pc, CodeError::InvalidSection(target)));
+                    }
+                    (inputs, target_ty.outputs as u16, None)
+                } else if target_ty.outputs != NON_RETURNING {
+                    // Returning through the target leaves its outputs in place of its inputs.
+                    let expected = ty.outputs as u16 + inputs - target_ty.outputs as u16;
+                    if ty.outputs == NON_RETURNING || (min, max) != (expected, expected) {
+                        return Err(err(pc, CodeError::InvalidReturnHeight));
+                    }
+                    (inputs, 0, None)
+                } else {
+                    (inputs, 0, None)
+                }
+            }
+            Some(Opcode::RETF) => {
+                let outputs = ty.outputs as u16;
+                if ty.outputs == NON_RETURNING || (min, max) != (outputs, outputs) {
+                    return Err(err(pc, CodeError::InvalidReturnHeight));
+                }
+                (outputs, 0, None)
+            }
+            Some(Opcode::DATALOADN) => {
+                if immediate(0) as usize + 32 > container.data.len() {
+                    return Err(err(pc, CodeError::InvalidDataOffset));
+                }
+                (0, 1, None)
+            }
+            Some(op @ (Opcode::EOFCREATE | Opcode::RETURNCONTRACT)) => {
+                let index = code[pc + 1] as usize;
+                if index >= container.containers.len() {
+                    return Err(err(pc, CodeError::InvalidContainer(index)));
+                }
+                (op.inputs() as u16, op.outputs() as u16, None)
+            }
+            Some(op) => (op.inputs() as u16, op.outputs() as u16, None),
+        };
+        if min < inputs {
+            return Err(err(pc, CodeError::StackUnderflow));
+        }
+        let after = (min - inputs + outputs, max - inputs + outputs);
+        max_stack_height = max_stack_height.max(after.1);
+        if max_stack_height > MAX_STACK_HEIGHT {
+            return Err(err(pc, CodeError::StackOverflow));
+        }
+
+        let falls_through = op.is_none_or(|op| !op.is_terminator());
+        if falls_through && next >= code.len() {
+            return Err(err(pc, CodeError::MissingTerminator));
+        }
+        let successors = jump.into_iter().chain(falls_through.then_some(next));
+        for target in successors {
+            let merged = match heights[target] {
+                _ if target <= pc => {
+                    if heights[target] != Some(after) {
+                        return Err(err(pc, CodeError::InconsistentStackHeight));
+                    }
+                    continue;
+                }
+                None => after,
+                Some((min, max)) => (min.min(after.0), max.max(after.1)),
+            };
+            heights[target] = Some(merged);
+        }
+    }
+    Ok(max_stack_height)
+}
+
+/// An error in an EOF container.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum EofError {
+    /// The container does not start with [`MAGIC`].
+    InvalidMagic,
+    /// The container version is not supported.
+    InvalidVersion(u8),
+    /// The container ended unexpectedly.
+    UnexpectedEnd,
+    /// A header section is missing or out of order.
+    MissingSection(u8),
+    /// The number of sections of the given kind is invalid.
+    InvalidSectionCount(&'static str),
+    /// A code or container section is empty.
+    EmptySection,
+    /// The type of the code section at the given index is invalid.
+    InvalidType(usize),
+    /// The container is longer than declared in its header.
+    TrailingBytes,
+    /// The maximum stack height declared for a code section is incorrect.
+    InvalidMaxStackHeight { section: usize, expected: u16 },
+    /// A code section is neither the entry point nor called or jumped to.
+    UnreachableSection(usize),
+    /// The code of a section is invalid.
+    Code { section: usize, pc: usize, kind: CodeError },
+}
+
+/// An error in the code of an EOF code section.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CodeError {
+    /// An undefined instruction, or one that is only valid in legacy code.
+    InvalidInstruction(u8),
+    /// The immediate data of an instruction extends past the end of the code.
+    TruncatedImmediate,
+    /// A relative jump does not point to the start of an instruction in the same section.
+    InvalidJumpTarget,
+    /// A call or jump references a code section that does not exist or cannot be called.
+    InvalidSection(usize),
+    /// A reference to a subcontainer that does not exist.
+    InvalidContainer(usize),
+    /// A `DATALOADN` reads past the end of the data section.
+    InvalidDataOffset,
+    /// An instruction is not reachable from the start of the section.
+    Unreachable,
+    /// Execution can run past the end of the code.
+    MissingTerminator,
+    /// An instruction consumes more stack items than available.
+    StackUnderflow,
+    /// The stack height exceeds the limit of 1023 items.
+    StackOverflow,
+    /// A backward jump reaches an instruction with a different stack height.
+    InconsistentStackHeight,
+    /// The stack height when returning does not match the number of outputs of the section.
+    InvalidReturnHeight,
+}
+
+impl fmt::Display for EofError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidMagic => f.write_str("invalid magic"),
+            Self::InvalidVersion(version) => write!(f, "unsupported version {version}"),
+            Self::UnexpectedEnd => f.write_str("unexpected end of container"),
+            Self::MissingSection(kind) => write!(f, "missing header section of kind {kind}"),
+            Self::InvalidSectionCount(kind) => write!(f, "invalid number of {kind} sections"),
+            Self::EmptySection => f.write_str("empty section"),
+            Self::InvalidType(section) => write!(f, "invalid type of code section {section}"),
+            Self::TrailingBytes => f.write_str("trailing bytes after the container"),
+            Self::InvalidMaxStackHeight { section, expected } => write!(
+                f,
+                "invalid maximum stack height of code section {section}, expected {expected}"
+            ),
+            Self::UnreachableSection(section) => {
+                write!(f, "code section {section} is unreachable")
+            }
+            Self::Code { section, pc, kind } => {
+                write!(f, "invalid code at offset {pc} of code section {section}: ")?;
+                match kind {
+                    CodeError::InvalidInstruction(byte) => {
+                        write!(f, "invalid instruction 0x{byte:02x}")
+                    }
+                    CodeError::TruncatedImmediate => f.write_str("truncated immediate"),
+                    CodeError::InvalidJumpTarget => f.write_str("invalid jump target"),
+                    CodeError::InvalidSection(i) => write!(f, "invalid code section {i}"),
+                    CodeError::InvalidContainer(i) => write!(f, "invalid container section {i}"),
+                    CodeError::InvalidDataOffset => f.write_str("data offset out of bounds"),
+                    CodeError::Unreachable => f.write_str("unreachable instruction"),
+                    CodeError::MissingTerminator => f.write_str("missing terminating instruction"),
+                    CodeError::StackUnderflow => f.write_str("stack underflow"),
+                    CodeError::StackOverflow => f.write_str("stack overflow"),
+                    CodeError::InconsistentStackHeight => {
+                        f.write_str("inconsistent stack height at jump target")
+                    }
+                    CodeError::InvalidReturnHeight => f.write_str("invalid stack height at return"),
+                }
+            }
+        }
+    }
+}
+
+impl std::error::Error for EofError {}
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, n: usize) -> Result<&'a [u8], EofError> {
+        let bytes = self.bytes.get(self.pos..self.pos + n).ok_or(EofError::UnexpectedEnd)?;
+        self.pos += n;
+        Ok(bytes)
+    }
+
+    fn peek(&self) -> Result<u8, EofError> {
+        self.bytes.get(self.pos).copied().ok_or(EofError::UnexpectedEnd)
+    }
+
+    fn u8(&mut self) -> Result<u8, EofError> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn u16(&mut self) -> Result<u16, EofError> {
+        Ok(u16::from_be_bytes(self.take(2)?.try_into().unwrap()))
+    }
+
+    fn u32(&mut self) -> Result<u32, EofError> {
+        Ok(u32::from_be_bytes(self.take(4)?.try_into().unwrap()))
+    }
+
+    fn expect_kind(&mut self, kind: u8) -> Result<(), EofError> {
+        if self.u8()? != kind {
+            return Err(EofError::MissingSection(kind));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn container(code: &[&[u8]], types: &[(u8, u8, u16)]) -> Container {
+        Container {
+            types: types
+                .iter()
+                .map(|&(inputs, outputs, max_stack_height)| SectionType {
+                    inputs,
+                    outputs,
+                    max_stack_height,
+                })
+                .collect(),
+            code: code.iter().map(|code| code.to_vec()).collect(),
+            containers: Vec::new(),
+            data: vec![0xaa],
+        }
+    }
+
+    #[test]
+    fn roundtrip() {
+        // `CALLF 1 STOP`, `PUSH1 1 ADD RETF`
+        let c = container(
+            &[&[0xe3, 0x00, 0x01, 0x00], &[0x60, 0x01, 0x01, 0xe4]],
+            &[(0, NON_RETURNING, 1), (1, 1, 2)],
+        );
+        let bytes = c.encode();
+        assert_eq!(&bytes[..3], &[0xef, 0x00, 0x01]);
+        assert_eq!(Container::decode(&bytes), Ok(c.clone()));
+        assert_eq!(
+            validate(&bytes),
+            Err(EofError::Code { section: 0, pc: 0, kind: CodeError::StackUnderflow })
+        );
+
+        // `PUSH0 CALLF 1 POP STOP`
+        let mut c = c;
+        c.code[0] = vec![0x5f, 0xe3, 0x00, 0x01, 0x50, 0x00];
+        assert_eq!(validate(&c.encode()), Ok(c.clone()));
+        c.types[1].max_stack_height = 3;
+        assert_eq!(
+            validate(&c.encode()),
+            Err(EofError::InvalidMaxStackHeight { section: 1, expected: 2 })
+        );
+    }
+
+    #[test]
+    fn jumps() {
+        // `PUSH0 RJUMPI +1 INVALID STOP`
+        let c = container(&[&[0x5f, 0xe1, 0x00, 0x01, 0xfe, 0x00]], &[(0, NON_RETURNING, 1)]);
+        assert!(validate(&c.encode()).is_ok());
+
+        // `RJUMP +1 INVALID STOP`: `INVALID` is unreachable.
+        let c = container(&[&[0xe0, 0x00, 0x01, 0xfe, 0x00]], &[(0, NON_RETURNING, 0)]);
+        let kind = CodeError::Unreachable;
+        assert_eq!(validate(&c.encode()), Err(EofError::Code { section: 0, pc: 3, kind }));
+
+        // `PUSH1 0 RJUMP -4`: jumps into an immediate.
+        let c = container(&[&[0x60, 0x00, 0xe0, 0xff, 0xfc]], &[(0, NON_RETURNING, 1)]);
+        let kind = CodeError::InvalidJumpTarget;
+        assert_eq!(validate(&c.encode()), Err(EofError::Code { section: 0, pc: 2, kind }));
+
+        // `PUSH0 RJUMP -4`: the stack grows on each iteration.
+        let c = container(&[&[0x5f, 0xe0, 0xff, 0xfc]], &[(0, NON_RETURNING, 1)]);
+        let kind = CodeError::InconsistentStackHeight;
+        assert_eq!(validate(&c.encode()), Err(EofError::Code { section: 0, pc: 1, kind }));
+    }
+
+    #[test]
+    fn legacy_instructions() {
+        // `PUSH0 JUMP`
+        let c = container(&[&[0x5f, 0x56]], &[(0, NON_RETURNING, 1)]);
+        let kind = CodeError::InvalidInstruction(0x56);
+        assert_eq!(validate(&c.encode()), Err(EofError::Code { section: 0, pc: 1, kind }));
+    }
+}
@@ -0,0 +1,294 @@
+//! Static gas estimation.
+//!
+//! Estimates are upper bounds of the gas used by the most expensive path through the generated
+//! code, computed from the static cost of each instruction as returned by [`Opcode::gas`]. Paths
+//! containing loops, recursion, external calls or contract creations are unbounded.
+
+use crate::{
+    asm::{AsmItem, Assembly, Label},
+    ContractCode, Opcode,
+};
+use solar_data_structures::map::{FxHashMap, FxHashSet};
+use solar_sema::{hir, ty::Gcx, CreationGasEstimates, GasEstimates};
+use std::fmt;
+
+/// The gas cost of depositing a byte of runtime code.
+const CODE_DEPOSIT_BYTE_COST: u64 = 200;
+/// The gas cost of `PUSHn`, for `n > 0`.
+const PUSH_COST: u64 = 3;
+/// The gas cost of `PUSH0`.
+const PUSH0_COST: u64 = 2;
+
+/// A gas estimate.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Gas {
+    /// At most the given amount of gas.
+    Bounded(u64),
+    /// Any amount of gas.
+    Unbounded,
+}
+
+impl Gas {
+    fn add(self, other: Self) -> Self {
+        match (self, other) {
+            (Self::Bounded(a), Self::Bounded(b)) => Self::Bounded(a.saturating_add(b)),
+            _ => Self::Unbounded,
+        }
+    }
+
+    fn max(self, other: Self) -> Self {
+        match (self, other) {
+            (Self::Bounded(a), Self::Bounded(b)) => Self::Bounded(a.max(b)),
+            _ => Self::Unbounded,
+        }
+    }
+}
+
+impl fmt::Display for Gas {
+    /// Formats the estimate like solc, with `infinite` for unbounded estimates.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Bounded(gas) => write!(f, "{gas}"),
+            Self::Unbounded => f.write_str("infinite"),
+        }
+    }
+}
+
+/// Returns the gas estimates of the given contract: the cost of its deployment, and of each of
+/// its external and internal functions.
+///
+/// External functions are keyed by signature, with the fallback function keyed by the empty
+/// string. Their estimates include the dispatcher.
+pub fn contract_gas_estimates(gcx: Gcx<'_>, code: &ContractCode) -> GasEstimates {
+    let signature = |id: hir::FunctionId| {
+        let f = gcx.hir.function(id);
+        match f.kind {
+            hir::FunctionKind::Fallback => String::new(),
+            hir::FunctionKind::Receive => "receive()".into(),
+            _ => gcx.item_signature(id.into()).to_string(),
+        }
+    };
+
+    let mut runtime = GasEstimator::new(code.runtime());
+    let external = code
+        .functions
+        .external
+        .iter()
+        .map(|&(id, label)| (signature(id), runtime.external(label).to_string()))
+        .collect();
+    let internal = code
+        .functions
+        .internal
+        .iter()
+        .map(|&(id, label)| (signature(id), runtime.internal(label).to_string()))
+        .collect();
+
+    let runtime_size = code.runtime().assemble().map_or(0, |code| code.len() as u64);
+    let code_deposit_cost = Gas::Bounded(CODE_DEPOSIT_BYTE_COST * runtime_size);
+    let execution_cost = GasEstimator::new(code.deploy()).execution();
+    GasEstimates {
+        creation: CreationGasEstimates {
+            code_deposit_cost: code_deposit_cost.to_string(),
+            execution_cost: execution_cost.to_string(),
+            total_cost: code_deposit_cost.add(execution_cost).to_string(),
+        },
+        external,
+        internal,
+    }
+}
+
+/// Estimates the gas used by the code of an [`Assembly`], excluding its sub-assemblies.
+///
+/// Jumps to function entries, and `CALLF` in EOF, are assumed to return to the following item,
+/// and dynamic jumps, and `RETF` in EOF, to return from the current function.
+pub struct GasEstimator<'a> {
+    asm: &'a Assembly,
+    /// The index of the item at which each label is placed.
+    labels: Vec<Option<usize>>,
+    /// The estimates from each item that has been visited.
+    cache: FxHashMap<usize, Gas>,
+    /// The items whose estimates are being computed, to detect cycles.
+    visiting: FxHashSet<usize>,
+}
+
+impl<'a> GasEstimator<'a> {
+    /// Creates a new estimator for the given assembly.
+    pub fn new(asm: &'a Assembly) -> Self {
+        let mut labels = Vec::new();
+        for (i, &(item, _)) in asm.items().iter().enumerate() {
+            if let AsmItem::Label(label) = item {
+                if labels.len() <= label.index() {
+                    labels.resize(label.index() + 1, None);
+                }
+                labels[label.index()] = Some(i);
+            }
+        }
+        Self { asm, labels, cache: FxHashMap::default(), visiting: FxHashSet::default() }
+    }
+
+    /// Returns the estimate of executing the whole code, such as creation code.
+    pub fn execution(&mut self) -> Gas {
+        self.from(0)
+    }
+
+    /// Returns the estimate of executing an external function, whose entry point is jumped to by
+    /// the dispatcher at the start of the code.
+    ///
+    /// The code preceding the first jump to the entry point is assumed to be executed linearly.
+    pub fn external(&mut self, entry: Label) -> Gas {
+        let asm = self.asm;
+        let items = asm.items();
+        let Some(jump) = items.iter().position(
+            |&(item, _)| matches!(item, AsmItem::Jump(l) | AsmItem::JumpIf(l) if l == entry),
+        ) else {
+            return Gas::Unbounded;
+        };
+        let dispatch = items[..=jump]
+            .iter()
+            .fold(Gas::Bounded(0), |gas, &(item, _)| gas.add(self.item_gas(item)));
+        dispatch.add(self.from_label(entry))
+    }
+
+    /// Returns the estimate of executing an internal function until it returns.
+    pub fn internal(&mut self, entry: Label) -> Gas {
+        self.from_label(entry)
+    }
+
+    fn from_label(&mut self, label: Label) -> Gas {
+        match self.labels.get(label.index()).copied().flatten() {
+            Some(start) => self.from(start),
+            None => Gas::Unbounded,
+        }
+    }
+
+    /// Returns the estimate of executing the code from the given item until the code halts or
+    /// returns from the current function.
+    fn from(&mut self, start: usize) -> Gas {
+        if let Some(&gas) = self.cache.get(&start) {
+            return gas;
+        }
+        if !self.visiting.insert(start) {
+            return Gas::Unbounded;
+        }
+        let gas = self.walk(start);
+        self.visiting.remove(&start);
+        self.cache.insert(start, gas);
+        gas
+    }
+
+    fn walk(&mut self, start: usize) -> Gas {
+        let asm = self.asm;
+        let items = asm.items();
+        let mut gas = Gas::Bounded(0);
+        let mut i = start;
+        while let Some(&(item, _)) = items.get(i) {
+            gas = gas.add(self.item_gas(item));
+            if gas == Gas::Unbounded {
+                return gas;
+            }
+            match item {
+                AsmItem::Jump(label) if self.is_function_entry(label) => {
+                    gas = gas.add(self.from_label(label));
+                }
+                AsmItem::CallF(label) => gas = gas.add(self.from_label(label)),
+                AsmItem::Jump(label) => return gas.add(self.from_label(label)),
+                AsmItem::JumpIf(label) => {
+                    let taken = self.from_label(label);
+                    return gas.add(taken.max(self.from(i + 1)));
+                }
+                item if is_terminator(item) => return gas,
+                _ => {}
+            }
+            i += 1;
+        }
+        gas
+    }
+
+    fn is_function_entry(&self, label: Label) -> bool {
+        !self.asm.is_eof() && self.asm.function_entries().binary_search(&label).is_ok()
+    }
+
+    /// Returns the static gas cost of the instructions the item is assembled into.
+    fn item_gas(&self, item: AsmItem) -> Gas {
+        let eof = self.asm.is_eof();
+        let gas = match item {
+            AsmItem::Op(op) => op.gas(),
+            AsmItem::Push(value) if value.is_zero() && self.asm.evm_version().has_push0() => {
+                Some(PUSH0_COST)
+            }
+            AsmItem::Push(_)
+            | AsmItem::PushLabel(_)
+            | AsmItem::PushSubOffset(_)
+            | AsmItem::PushSubSize(_)
+            | AsmItem::PushProgramSize
+            | AsmItem::PushLibrary(_) => Some(PUSH_COST),
+            AsmItem::Label(_) if eof => Some(0),
+            AsmItem::Label(_) => Opcode::JUMPDEST.gas(),
+            AsmItem::Jump(_) if eof => Opcode::RJUMP.gas(),
+            AsmItem::Jump(_) => Opcode::JUMP.gas().map(|gas| gas + PUSH_COST),
+            AsmItem::JumpIf(_) if eof => Opcode::RJUMPI.gas(),
+            AsmItem::JumpIf(_) => Opcode::JUMPI.gas().map(|gas| gas + PUSH_COST),
+            AsmItem::CallF(_) => Opcode::CALLF.gas(),
+            AsmItem::ReturnContract(_) => Opcode::RETURNCONTRACT.gas(),
+            AsmItem::CodeSection { .. } => Some(0),
+        };
+        gas.map_or(Gas::Unbounded, Gas::Bounded)
+    }
+}
+
+/// Returns `true` if the item halts or returns from the current function.
+fn is_terminator(item: AsmItem) -> bool {
+    matches!(item, AsmItem::Op(op) if op.is_terminator())
+        || matches!(item, AsmItem::ReturnContract(_))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use solar_config::EvmVersion;
+
+    #[test]
+    fn branches_and_calls() {
+        let mut asm = Assembly::new(EvmVersion::Cancun);
+        let f = asm.new_function_label();
+        let ret = asm.new_label();
+        let end = asm.new_label();
+        // 3 + 3 + 11 + 1
+        asm.push_label(ret);
+        asm.push_value(1u64);
+        asm.jump_to(f);
+        asm.place_label(ret);
+        // 13, then either 0 or 2100 + 0
+        asm.jump_if(end);
+        asm.push(Opcode::STOP);
+        asm.place_label(end);
+        asm.push(Opcode::SLOAD);
+        asm.push(Opcode::STOP);
+        // 1 + 3 + 8
+        asm.place_label(f);
+        asm.push(Opcode::ISZERO);
+        asm.push(Opcode::JUMP);
+
+        let mut estimator = GasEstimator::new(&asm);
+        assert_eq!(estimator.internal(f), Gas::Bounded(12));
+        assert_eq!(estimator.execution(), Gas::Bounded(18 + 12 + 13 + 1 + 2100));
+        assert_eq!(estimator.external(f), Gas::Bounded(17 + 12));
+    }
+
+    #[test]
+    fn unbounded() {
+        let mut asm = Assembly::new(EvmVersion::Cancun);
+        let start = asm.new_label();
+        asm.place_label(start);
+        asm.push_value(1u64);
+        asm.jump_if(start);
+        asm.push(Opcode::STOP);
+        assert_eq!(GasEstimator::new(&asm).execution(), Gas::Unbounded);
+
+        let mut asm = Assembly::new(EvmVersion::Cancun);
+        asm.push(Opcode::CALL);
+        asm.push(Opcode::STOP);
+        assert_eq!(GasEstimator::new(&asm).execution(), Gas::Unbounded);
+        assert_eq!(Gas::Unbounded.to_string(), "infinite");
+    }
+}
@@ -0,0 +1,497 @@
+//! A minimal legacy EVM interpreter, used to execute the generated code in tests.
+//!
+//! Only a single contract is executed at a time: calls to other accounts are not supported, the
+//! environment opcodes return fixed values, and gas is not metered, although the number of executed
+//! instructions is bounded.
+
+use crate::Opcode;
+use alloy_primitives::{keccak256, U256};
+use std::collections::HashMap;
+
+/// The maximum number of instructions executed by a single call.
+const STEP_LIMIT: usize = 1_000_000;
+
+/// The address of the executing contract.
+pub(crate) const CONTRACT_ADDRESS: U256 = U256::from_limbs([0xc0de, 0, 0, 0]);
+/// The address of the caller and transaction origin.
+pub(crate) const CALLER_ADDRESS: U256 = U256::from_limbs([0xca11, 0, 0, 0]);
+
+/// The result of a call.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) struct Output {
+    /// `false` if the call reverted, or ran into an invalid instruction.
+    pub(crate) success: bool,
+    /// The return or revert data.
+    pub(crate) data: Vec<u8>,
+}
+
+/// An emitted log.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) struct Log {
+    pub(crate) topics: Vec<U256>,
+    pub(crate) data: Vec<u8>,
+}
+
+/// The state of a single contract, kept across calls.
+#[derive(Debug, Default)]
+pub(crate) struct Evm {
+    /// The runtime code, set by [`deploy`](Self::deploy).
+    pub(crate) code: Vec<u8>,
+    pub(crate) storage: HashMap<U256, U256>,
+    /// The logs emitted by successful calls.
+    pub(crate) logs: Vec<Log>,
+}
+
+impl Evm {
+    /// Runs the given creation code and stores the returned runtime code.
+    ///
+    /// Panics if the deployment fails.
+    pub(crate) fn deploy(creation_code: &[u8]) -> Self {
+        let mut evm = Self::default();
+        let output = evm.execute(creation_code, &[], U256::ZERO);
+        assert!(output.success, "deployment failed: {output:?}");
+        evm.code = output.data;
+        evm
+    }
+
+    /// Calls the runtime code with the given calldata and value.
+    pub(crate) fn call(&mut self, calldata: &[u8], value: U256) -> Output {
+        let code = std::mem::take(&mut self.code);
+        let output = self.execute(&code, calldata, value);
+        self.code = code;
+        output
+    }
+
+    /// Executes `code`, reverting the state changes if it fails.
+    fn execute(&mut self, code: &[u8], calldata: &[u8], value: U256) -> Output {
+        let storage = self.storage.clone();
+        let logs = self.logs.len();
+        let mut frame = Frame {
+            evm: self,
+            code,
+            jumpdests: jumpdests(code),
+            calldata,
+            value,
+            pc: 0,
+            stack: Vec::new(),
+            memory: Vec::new(),
+            transient: HashMap::new(),
+        };
+        let output = frame.run();
+        if !output.success {
+            self.storage = storage;
+            self.logs.truncate(logs);
+        }
+        output
+    }
+}
+
+/// Returns the valid jump destinations of the given code, skipping `PUSH` immediates.
+fn jumpdests(code: &[u8]) -> Vec<bool> {
+    let mut jumpdests = vec![false; code.len()];
+    let mut pc = 0;
+    while pc < code.len() {
+        let byte = code[pc];
+        if byte == Opcode::JUMPDEST as u8 {
+            jumpdests[pc] = true;
+        }
+        pc += 1 + push_size(byte);
+    }
+    jumpdests
+}
+
+/// Returns the number of immediate bytes of the given instruction byte.
+fn push_size(byte: u8) -> usize {
+    match byte {
+        0x60..=0x7f => (byte - Opcode::PUSH0_BYTE) as usize,
+        _ => 0,
+    }
+}
+
+struct Frame<'a> {
+    evm: &'a mut Evm,
+    code: &'a [u8],
+    jumpdests: Vec<bool>,
+    calldata: &'a [u8],
+    value: U256,
+    pc: usize,
+    stack: Vec<U256>,
+    memory: Vec<u8>,
+    transient: HashMap<U256, U256>,
+}
+
+impl Frame<'_> {
+    fn run(&mut self) -> Output {
+        for _ in 0..STEP_LIMIT {
+            let Some(&byte) = self.code.get(self.pc) else { return self.stop() };
+            self.pc += 1;
+            if (Opcode::PUSH0_BYTE..=0x7f).contains(&byte) {
+                let size = push_size(byte);
+                let mut bytes = [0; 32];
+                for i in 0..size {
+                    bytes[32 - size + i] = self.code.get(self.pc + i).copied().unwrap_or(0);
+                }
+                self.pc += size;
+                self.push(U256::from_be_bytes(bytes));
+                continue;
+            }
+            let Some(op) = Opcode::from_u8(byte) else { return self.invalid() };
+            if let Some(output) = self.step(op) {
+                return output;
+            }
+        }
+        panic!("step limit exceeded");
+    }
+
+    /// Executes a single instruction, returning the output if execution halts.
+    fn step(&mut self, op: Opcode) -> Option<Output> {
+        use Opcode::*;
+
+        match op {
+            STOP => return Some(self.stop()),
+            ADD => self.binary(|a, b| a.wrapping_add(b)),
+            MUL => self.binary(|a, b| a.wrapping_mul(b)),
+            SUB => self.binary(|a, b| a.wrapping_sub(b)),
+            DIV => self.binary(|a, b| a.checked_div(b).unwrap_or_default()),
+            SDIV => self.binary(sdiv),
+            MOD => self.binary(|a, b| a.checked_rem(b).unwrap_or_default()),
+            SMOD => self.binary(smod),
+            ADDMOD => {
+                let (a, b, n) = (self.pop(), self.pop(), self.pop());
+                self.push(a.add_mod(b, n));
+            }
+            MULMOD => {
+                let (a, b, n) = (self.pop(), self.pop(), self.pop());
+                self.push(a.mul_mod(b, n));
+            }
+            EXP => self.binary(|a, b| a.wrapping_pow(b)),
+            SIGNEXTEND => self.binary(signextend),
+            LT => self.binary(|a, b| bool_word(a < b)),
+            GT => self.binary(|a, b| bool_word(a > b)),
+            SLT => self.binary(|a, b| bool_word(slt(a, b))),
+            SGT => self.binary(|a, b| bool_word(slt(b, a))),
+            EQ => self.binary(|a, b| bool_word(a == b)),
+            ISZERO => {
+                let a = self.pop();
+                self.push(bool_word(a.is_zero()));
+            }
+            AND => self.binary(|a, b| a & b),
+            OR => self.binary(|a, b| a | b),
+            XOR => self.binary(|a, b| a ^ b),
+            NOT => {
+                let a = self.pop();
+                self.push(!a);
+            }
+            BYTE => self.binary(|i, x| match usize::try_from(i) {
+                Ok(i) if i < 32 => U256::from(x.to_be_bytes::<32>()[i]),
+                _ => U256::ZERO,
+            }),
+            SHL => self.binary(|shift, x| match usize::try_from(shift) {
+                Ok(shift) if shift < 256 => x << shift,
+                _ => U256::ZERO,
+            }),
+            SHR => self.binary(|shift, x| match usize::try_from(shift) {
+                Ok(shift) if shift < 256 => x >> shift,
+                _ => U256::ZERO,
+            }),
+            SAR => self.binary(sar),
+            KECCAK256 => {
+                let (offset, size) = (self.pop(), self.pop());
+                let data = self.memory_slice(offset, size).to_vec();
+                self.push(U256::from_be_bytes(keccak256(data).0));
+            }
+            ADDRESS => self.push(CONTRACT_ADDRESS),
+            CALLER | ORIGIN => self.push(CALLER_ADDRESS),
+            CALLVALUE => self.push(self.value),
+            CALLDATALOAD => {
+                let offset = self.pop();
+                let mut word = [0; 32];
+                copy_padded(&mut word, self.calldata, offset);
+                self.push(U256::from_be_bytes(word));
+            }
+            CALLDATASIZE => self.push(U256::from(self.calldata.len())),
+            CALLDATACOPY => self.copy_to_memory(self.calldata),
+            CODESIZE => self.push(U256::from(self.code.len())),
+            CODECOPY => self.copy_to_memory(self.code),
+            RETURNDATASIZE => self.push(U256::ZERO),
+            RETURNDATACOPY => self.copy_to_memory(&[]),
+            BALANCE | EXTCODESIZE | EXTCODEHASH | BLOCKHASH | BLOBHASH => {
+                self.pop();
+                self.push(U256::ZERO);
+            }
+            GASPRICE | COINBASE | TIMESTAMP | NUMBER | PREVRANDAO | CHAINID | SELFBALANCE
+            | BASEFEE | BLOBBASEFEE => self.push(U256::ZERO),
+            GASLIMIT | GAS => self.push(U256::from(u64::MAX)),
+            POP => {
+                self.pop();
+            }
+            MLOAD => {
+                let offset = self.pop();
+                let word = U256::from_be_slice(self.memory_slice(offset, U256::from(32)));
+                self.push(word);
+            }
+            MSTORE => {
+                let (offset, value) = (self.pop(), self.pop());
+                self.memory_slice(offset, U256::from(32))
+                    .copy_from_slice(&value.to_be_bytes::<32>());
+            }
+            MSTORE8 => {
+                let (offset, value) = (self.pop(), self.pop());
+                self.memory_slice(offset, U256::from(1))[0] = value.byte(0);
+            }
+            SLOAD => {
+                let key = self.pop();
+                self.push(self.evm.storage.get(&key).copied().unwrap_or_default());
+            }
+            SSTORE => {
+                let (key, value) = (self.pop(), self.pop());
+                self.evm.storage.insert(key, value);
+            }
+            TLOAD => {
+                let key = self.pop();
+                self.push(self.transient.get(&key).copied().unwrap_or_default());
+            }
+            TSTORE => {
+                let (key, value) = (self.pop(), self.pop());
+                self.transient.insert(key, value);
+            }
+            JUMP => {
+                let dest = self.pop();
+                return self.jump(dest);
+            }
+            JUMPI => {
+                let (dest, cond) = (self.pop(), self.pop());
+                if !cond.is_zero() {
+                    return self.jump(dest);
+                }
+            }
+            PC => self.push(U256::from(self.pc - 1)),
+            MSIZE => self.push(U256::from(self.memory.len())),
+            JUMPDEST => {}
+            MCOPY => {
+                let (dest, src, size) = (self.pop(), self.pop(), self.pop());
+                let data = self.memory_slice(src, size).to_vec();
+                self.memory_slice(dest, size).copy_from_slice(&data);
+            }
+            LOG0 | LOG1 | LOG2 | LOG3 | LOG4 => {
+                let (offset, size) = (self.pop(), self.pop());
+                let topics = (0..op as u8 - LOG0 as u8).map(|_| self.pop()).collect();
+                let data = self.memory_slice(offset, size).to_vec();
+                self.evm.logs.push(Log { topics, data });
+            }
+            RETURN | REVERT => {
+                let (offset, size) = (self.pop(), self.pop());
+                let data = self.memory_slice(offset, size).to_vec();
+                return Some(Output { success: op == RETURN, data });
+            }
+            INVALID => return Some(self.invalid()),
+            DUP1 | DUP2 | DUP3 | DUP4 | DUP5 | DUP6 | DUP7 | DUP8 | DUP9 | DUP10 | DUP11
+            | DUP12 | DUP13 | DUP14 | DUP15 | DUP16 => {
+                let n = (op as u8 - DUP1 as u8) as usize + 1;
+                let value = self.stack[self.stack.len().checked_sub(n).expect("stack underflow")];
+                self.push(value);
+            }
+            SWAP1 | SWAP2 | SWAP3 | SWAP4 | SWAP5 | SWAP6 | SWAP7 | SWAP8 | SWAP9 | SWAP10
+            | SWAP11 | SWAP12 | SWAP13 | SWAP14 | SWAP15 | SWAP16 => {
+                let n = (op as u8 - SWAP1 as u8) as usize + 1;
+                let top = self.stack.len() - 1;
+                self.stack.swap(top, top.checked_sub(n).expect("stack underflow"));
+            }
+            _ => panic!("unsupported instruction {} at {}", op.name(), self.pc - 1),
+        }
+        None
+    }
+
+    fn stop(&self) -> Output {
+        Output { success: true, data: Vec::new() }
+    }
+
+    fn invalid(&self) -> Output {
+        Output { success: false, data: Vec::new() }
+    }
+
+    fn jump(&mut self, dest: U256) -> Option<Output> {
+        match usize::try_from(dest) {
+            Ok(dest) if self.jumpdests.get(dest).copied().unwrap_or(false) => {
+                self.pc = dest;
+                None
+            }
+            _ => Some(self.invalid()),
+        }
+    }
+
+    fn push(&mut self, value: U256) {
+        assert!(self.stack.len() < 1024, "stack overflow");
+        self.stack.push(value);
+    }
+
+    fn pop(&mut self) -> U256 {
+        self.stack.pop().expect("stack underflow")
+    }
+
+    fn binary(&mut self, f: impl FnOnce(U256, U256) -> U256) {
+        let (a, b) = (self.pop(), self.pop());
+        self.push(f(a, b));
+    }
+
+    /// Returns the given range of memory, expanding it if needed.
+    fn memory_slice(&mut self, offset: U256, size: U256) -> &mut [u8] {
+        let size = to_usize(size);
+        if size == 0 {
+            return &mut [];
+        }
+        let offset = to_usize(offset);
+        let end = (offset + size).div_ceil(32) * 32;
+        if end > self.memory.len() {
+            self.memory.resize(end, 0);
+        }
+        &mut self.memory[offset..offset + size]
+    }
+
+    /// Pops the destination offset, source offset and size, and copies from `data` to memory,
+    /// padding with zeros.
+    fn copy_to_memory(&mut self, data: &[u8]) {
+        let (dest, src, size) = (self.pop(), self.pop(), self.pop());
+        copy_padded(self.memory_slice(dest, size), data, src);
+    }
+}
+
+/// Copies `data[offset..]` into `dest`, padding with zeros.
+fn copy_padded(dest: &mut [u8], data: &[u8], offset: U256) {
+    let offset = usize::try_from(offset).unwrap_or(usize::MAX).min(data.len());
+    let data = &data[offset..];
+    let n = data.len().min(dest.len());
+    dest[..n].copy_from_slice(&data[..n]);
+    dest[n..].fill(0);
+}
+
+fn to_usize(value: U256) -> usize {
+    match usize::try_from(value) {
+        Ok(value) if value <= u32::MAX as usize => value,
+        _ => panic!("memory offset too large: {value}"),
+    }
+}
+
+fn bool_word(b: bool) -> U256 {
+    U256::from(b as u8)
+}
+
+fn is_negative(x: U256) -> bool {
+    x.bit(255)
+}
+
+fn neg(x: U256) -> U256 {
+    (!x).wrapping_add(U256::from(1))
+}
+
+fn abs(x: U256) -> U256 {
+    if is_negative(x) {
+        neg(x)
+    } else {
+        x
+    }
+}
+
+fn sdiv(a: U256, b: U256) -> U256 {
+    if b.is_zero() {
+        return U256::ZERO;
+    }
+    let q = abs(a) / abs(b);
+    if is_negative(a) != is_negative(b) {
+        neg(q)
+    } else {
+        q
+    }
+}
+
+fn smod(a: U256, b: U256) -> U256 {
+    if b.is_zero() {
+        return U256::ZERO;
+    }
+    let r = abs(a) % abs(b);
+    if is_negative(a) {
+        neg(r)
+    } else {
+        r
+    }
+}
+
+fn slt(a: U256, b: U256) -> bool {
+    match (is_negative(a), is_negative(b)) {
+        (true, false) => true,
+        (false, true) => false,
+        _ => a < b,
+    }
+}
+
+fn sar(shift: U256, x: U256) -> U256 {
+    let negative = is_negative(x);
+    match usize::try_from(shift) {
+        Ok(shift) if shift < 256 => {
+            if negative {
+                !((!x) >> shift)
+            } else {
+                x >> shift
+            }
+        }
+        _ if negative => U256::MAX,
+        _ => U256::ZERO,
+    }
+}
+
+fn signextend(b: U256, x: U256) -> U256 {
+    match usize::try_from(b) {
+        Ok(b) if b < 31 => {
+            let bit = b * 8 + 7;
+            let mask = (U256::from(1) << (bit + 1)) - U256::from(1);
+            if x.bit(bit) {
+                x | !mask
+            } else {
+                x & mask
+            }
+        }
+        _ => x,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn int(x: i64) -> U256 {
+        if x < 0 {
+            neg(U256::from(x.unsigned_abs()))
+        } else {
+            U256::from(x)
+        }
+    }
+
+    #[test]
+    fn signed_arithmetic() {
+        assert_eq!(sdiv(int(-7), int(2)), int(-3));
+        assert_eq!(smod(int(-7), int(2)), int(-1));
+        assert_eq!(sdiv(int(7), int(0)), int(0));
+        assert!(slt(int(-1), int(0)));
+        assert!(!slt(int(1), int(-1)));
+        assert_eq!(sar(U256::from(1), int(-3)), int(-2));
+        assert_eq!(sar(U256::from(300), int(-3)), int(-1));
+        assert_eq!(signextend(U256::ZERO, U256::from(0xff)), int(-1));
+        assert_eq!(signextend(U256::ZERO, U256::from(0x17f)), U256::from(0x7f));
+    }
+
+    #[test]
+    fn execute() {
+        // `mstore(0, add(calldataload(0), 1)) return(0, 32)`
+        let code = [0x5f, 0x35, 0x60, 0x01, 0x01, 0x5f, 0x52, 0x60, 0x20, 0x5f, 0xf3];
+        let mut evm = Evm { code: code.to_vec(), ..Default::default() };
+        let output = evm.call(&int(41).to_be_bytes::<32>(), U256::ZERO);
+        assert!(output.success);
+        assert_eq!(output.data, int(42).to_be_bytes::<32>());
+
+        // `sstore(0, 1) jump(0)`: invalid jump destination, so the store is reverted.
+        let code = [0x60, 0x01, 0x5f, 0x55, 0x5f, 0x56];
+        let mut evm = Evm { code: code.to_vec(), ..Default::default() };
+        assert!(!evm.call(&[], U256::ZERO).success);
+        assert!(evm.storage.is_empty());
+    }
+}
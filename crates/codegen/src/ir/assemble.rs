@@ -0,0 +1,655 @@
+//! Assembly of Yul objects into EVM assembly.
+//!
+//! Like the [EVM lowering](crate::lower), the generated code is a straightforward translation:
+//! - variables live at a fixed stack position for the duration of their scope;
+//! - functions are called by pushing zeros for their return variables, the return address and the
+//!   arguments, in that order, and jumping to the function entry. The callee leaves only the
+//!   return variables on the stack before jumping back.
+//!
+//! Arguments are evaluated from right to left, as specified by Yul, which leaves the first argument
+//! on top of the stack, where the instructions of builtins expect it.
+//!
+//! Function names must be unique in each object, which holds for the IR generated by
+//! [`lower_contract`](super::lower_contract) and is preserved by the [optimizer](crate::opt). EOF
+//! is not supported.
+
+use crate::{
+    asm::{AsmItem, Assembly, Label},
+    opt::lit_value,
+    Opcode,
+};
+use alloy_primitives::U256;
+use solar_ast::{yul, Lit, LitKind};
+use solar_data_structures::map::FxHashMap;
+use solar_interface::{diagnostics::ErrorGuaranteed, Ident, Session, Span, Symbol};
+
+/// The entry labels of the functions of a Yul object, by name.
+pub(crate) type FunctionEntries = FxHashMap<Symbol, Label>;
+
+/// Assembles a Yul object and its children.
+///
+/// The `.metadata` data section of an object, if any, is appended to its code as auxiliary data.
+/// Other data sections are not supported.
+pub fn assemble_object(
+    sess: &Session,
+    object: &yul::Object<'_>,
+) -> Result<Assembly, ErrorGuaranteed> {
+    assemble(sess, object).map(|(asm, _)| asm)
+}
+
+/// Assembles a Yul object and its children, also returning the entry labels of the functions
+/// called in its code.
+pub(crate) fn assemble(
+    sess: &Session,
+    object: &yul::Object<'_>,
+) -> Result<(Assembly, FunctionEntries), ErrorGuaranteed> {
+    let mut children = Vec::with_capacity(object.children.len());
+    let mut error = None;
+    for child in object.children.iter() {
+        match assemble_object(sess, child) {
+            Ok(asm) => children.push(asm),
+            Err(guar) => error = Some(guar),
+        }
+    }
+    if let Some(guar) = error {
+        return Err(guar);
+    }
+    assemble_with_children(sess, object, children)
+}
+
+/// Assembles the code of a Yul object whose children have already been assembled, in order.
+pub(crate) fn assemble_with_children(
+    sess: &Session,
+    object: &yul::Object<'_>,
+    children: Vec<Assembly>,
+) -> Result<(Assembly, FunctionEntries), ErrorGuaranteed> {
+    debug_assert_eq!(object.children.len(), children.len());
+    let mut cx = ObjectCx::new(sess, object);
+    if cx.asm.is_eof() {
+        let msg = "generating code through the IR is not yet supported when targeting EOF";
+        return Err(sess.dcx.err(msg).span(object.span).emit());
+    }
+    for (child, asm) in object.children.iter().zip(children) {
+        let index = cx.asm.add_sub(asm);
+        cx.subs.insert(child.name.value, index);
+    }
+    for data in object.data.iter() {
+        match &data.data.kind {
+            LitKind::Str(_, bytes) if data.name.value.as_str() == ".metadata" => {
+                cx.asm.set_aux_data(bytes.to_vec());
+            }
+            _ => cx.err(data.span, "data sections other than `.metadata` are not yet supported"),
+        }
+    }
+    cx.collect_functions(&object.code.code);
+    cx.finish()
+}
+
+/// Returns the instruction of the given Yul builtin, if it is one.
+///
+/// Stack manipulation and jump instructions are not accessible from Yul.
+fn builtin_opcode(name: &str) -> Option<Opcode> {
+    (0..=u8::MAX)
+        .filter_map(Opcode::from_u8)
+        .find(|op| op.name().eq_ignore_ascii_case(name))
+        .filter(|&op| {
+            !matches!(op, Opcode::JUMP | Opcode::JUMPI | Opcode::JUMPDEST | Opcode::PC)
+                && !(Opcode::DUP1 as u8..=Opcode::SWAP16 as u8).contains(&(op as u8))
+                && op.immediate_size() == 0
+        })
+}
+
+/// Returns the value of a number, boolean or string literal.
+///
+/// Strings are left-aligned, and can be at most 32 bytes long.
+fn literal(lit: &Lit) -> Option<U256> {
+    match &lit.kind {
+        LitKind::Str(_, bytes) if bytes.len() <= 32 => {
+            let mut word = [0; 32];
+            word[..bytes.len()].copy_from_slice(bytes);
+            Some(U256::from_be_bytes(word))
+        }
+        _ => lit_value(lit),
+    }
+}
+
+/// A jump target along with the stack height expected there.
+#[derive(Clone, Copy, Debug)]
+struct Target {
+    label: Label,
+    height: usize,
+}
+
+/// The targets of `break` and `continue` statements.
+#[derive(Clone, Copy, Debug)]
+struct LoopTargets {
+    break_: Target,
+    continue_: Target,
+}
+
+/// The state of the assembly of a single Yul object.
+struct ObjectCx<'a, 'ast> {
+    sess: &'a Session,
+    object: &'a yul::Object<'ast>,
+    asm: Assembly,
+    /// The functions defined in the object's code, along with the span of their definition.
+    definitions: FxHashMap<Symbol, (&'a yul::Function<'ast>, Span)>,
+    /// The entry labels of the functions called so far.
+    functions: FunctionEntries,
+    /// The functions whose body has not been generated yet.
+    queue: Vec<Symbol>,
+    /// The sub-assembly of each child object, by name.
+    subs: FxHashMap<Symbol, usize>,
+    /// The current stack height, relative to the start of the frame.
+    height: usize,
+    /// The stack position of each variable in scope.
+    vars: FxHashMap<Symbol, usize>,
+    /// The enclosing loops.
+    loops: Vec<LoopTargets>,
+    /// The target of `leave` statements, if in a function.
+    leave: Option<Target>,
+    /// Whether a stack too deep error was emitted for this frame, which is only reported once.
+    reported_stack_too_deep: bool,
+    /// Set if an error was emitted.
+    error: Option<ErrorGuaranteed>,
+}
+
+impl<'a, 'ast> ObjectCx<'a, 'ast> {
+    fn new(sess: &'a Session, object: &'a yul::Object<'ast>) -> Self {
+        Self {
+            sess,
+            object,
+            asm: Assembly::new(sess.evm_version),
+            definitions: FxHashMap::default(),
+            functions: FunctionEntries::default(),
+            queue: Vec::new(),
+            subs: FxHashMap::default(),
+            height: 0,
+            vars: FxHashMap::default(),
+            loops: Vec::new(),
+            leave: None,
+            reported_stack_too_deep: false,
+            error: None,
+        }
+    }
+
+    fn err(&mut self, span: Span, msg: impl Into<String>) {
+        let msg = msg.into();
+        self.error = Some(self.sess.dcx.err(msg).span(span).emit());
+    }
+
+    /// Records the functions defined in `block` and its nested blocks.
+    fn collect_functions(&mut self, block: &'a yul::Block<'ast>) {
+        for stmt in block.iter() {
+            match &stmt.kind {
+                yul::StmtKind::FunctionDef(f) => {
+                    if self.definitions.insert(f.name.name, (f, stmt.span)).is_some() {
+                        let msg = format!("function `{}` is defined more than once", f.name);
+                        self.err(f.name.span, msg);
+                    }
+                    self.collect_functions(&f.body);
+                }
+                yul::StmtKind::Block(block) | yul::StmtKind::If(_, block) => {
+                    self.collect_functions(block)
+                }
+                yul::StmtKind::For { init, step, body, .. } => {
+                    self.collect_functions(init);
+                    self.collect_functions(step);
+                    self.collect_functions(body);
+                }
+                yul::StmtKind::Switch(switch) => {
+                    for case in switch.branches.iter() {
+                        self.collect_functions(&case.body);
+                    }
+                    if let Some(default) = &switch.default_case {
+                        self.collect_functions(default);
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    /// Generates the code of the object, followed by the called functions.
+    fn finish(mut self) -> Result<(Assembly, FunctionEntries), ErrorGuaranteed> {
+        let object = self.object;
+        let code = &object.code;
+        self.asm.set_span(code.span);
+        self.lower_block(&code.code);
+        // Execution stops at the end of the code.
+        if !self.asm.is_terminated() {
+            self.asm.set_span(code.span);
+            self.op(Opcode::STOP);
+        }
+        while let Some(name) = self.queue.pop() {
+            self.lower_function(name);
+        }
+        match self.error {
+            Some(guar) => Err(guar),
+            None => Ok((self.asm, self.functions)),
+        }
+    }
+
+    // --- Emission helpers ---
+
+    /// Appends an opcode, keeping track of the stack height.
+    fn op(&mut self, op: Opcode) {
+        if !op.is_available(self.asm.evm_version()) {
+            let msg = format!(
+                "the `{op}` instruction is not available in the selected EVM version ({})",
+                self.asm.evm_version()
+            );
+            let span = self.asm.span();
+            self.err(span, msg);
+        }
+        debug_assert!(self.height >= op.inputs() as usize, "stack underflow at {op}");
+        self.height = self.height.saturating_sub(op.inputs() as usize) + op.outputs() as usize;
+        self.asm.push(op);
+    }
+
+    /// Pushes a value.
+    fn push(&mut self, value: impl Into<U256>) {
+        self.height += 1;
+        self.asm.push_value(value);
+    }
+
+    /// Pushes `n` zeros.
+    fn push_zeros(&mut self, n: usize) {
+        for _ in 0..n {
+            self.push(0u64);
+        }
+    }
+
+    /// Jumps to the given label if the value on top of the stack is non-zero, consuming it.
+    fn jump_if(&mut self, label: Label) {
+        debug_assert!(self.height >= 1, "stack underflow at jump");
+        self.height = self.height.saturating_sub(1);
+        self.asm.jump_if(label);
+    }
+
+    /// Pops values until the stack has the given height.
+    fn pop_to(&mut self, height: usize) {
+        while self.height > height {
+            self.op(Opcode::POP);
+        }
+    }
+
+    /// Pops the variables declared since the stack had the given height, and forgets them.
+    fn end_scope(&mut self, height: usize) {
+        self.pop_to(height);
+        self.vars.retain(|_, &mut pos| pos < height);
+    }
+
+    /// Jumps to a target, popping the values above its stack height first.
+    ///
+    /// The stack height is left unchanged for the unreachable code that follows.
+    fn jump_out(&mut self, target: Target) {
+        let height = self.height;
+        self.pop_to(target.height);
+        self.asm.jump_to(target.label);
+        self.height = height;
+    }
+
+    /// Pushes a copy of the given variable.
+    fn load_var(&mut self, ident: Ident) {
+        let Some(&pos) = self.vars.get(&ident.name) else {
+            self.err(ident.span, format!("unknown variable `{ident}`"));
+            self.push(0u64);
+            return;
+        };
+        match Opcode::dup(self.height - pos) {
+            Some(op) => self.op(op),
+            None => {
+                self.stack_too_deep(ident, self.height - pos);
+                self.height += 1;
+            }
+        }
+    }
+
+    /// Moves the value on top of the stack to the given variable, replacing its value.
+    fn store_var(&mut self, ident: Ident) {
+        match self.vars.get(&ident.name) {
+            Some(&pos) => match Opcode::swap(self.height - 1 - pos) {
+                Some(op) => self.op(op),
+                None => self.stack_too_deep(ident, self.height - pos),
+            },
+            None => self.err(ident.span, format!("unknown variable `{ident}`")),
+        }
+        self.op(Opcode::POP);
+    }
+
+    /// Reports that a variable is out of reach of `DUP16` and `SWAP16`.
+    fn stack_too_deep(&mut self, ident: Ident, depth: usize) {
+        if std::mem::replace(&mut self.reported_stack_too_deep, true) {
+            return;
+        }
+        let span = self.asm.span();
+        let msg = format!(
+            "stack too deep: `{ident}` is {depth} slots deep, but only the top 16 slots are \
+             reachable"
+        );
+        let diag = self.sess.dcx.err(msg).span(span).help(
+            "reduce the number of variables in scope, for example by moving some into a nested \
+             block, a struct in memory or a separate function",
+        );
+        self.error = Some(diag.emit());
+    }
+
+    // --- Functions ---
+
+    /// Returns the entry label of the given function, scheduling its body to be generated.
+    fn function_label(&mut self, name: Symbol) -> Label {
+        *self.functions.entry(name).or_insert_with(|| {
+            self.queue.push(name);
+            self.asm.new_function_label()
+        })
+    }
+
+    /// Generates the body of the given function.
+    ///
+    /// The frame is `returns..., return address, parameters...`, with the parameters in reverse
+    /// order.
+    fn lower_function(&mut self, name: Symbol) {
+        let (f, span) = self.definitions[&name];
+        let label = self.functions[&name];
+        self.asm.set_span(span);
+        self.asm.place_label(label);
+
+        let (params, returns) = (f.parameters.len(), f.returns.len());
+        self.vars.clear();
+        for (i, ret) in f.returns.iter().enumerate() {
+            self.vars.insert(ret.name, i);
+        }
+        for (i, param) in f.parameters.iter().enumerate() {
+            self.vars.insert(param.name, returns + params - i);
+        }
+        self.height = returns + 1 + params;
+        self.reported_stack_too_deep = false;
+        let exit = Target { label: self.asm.new_label(), height: self.height };
+        self.leave = Some(exit);
+
+        self.lower_block(&f.body);
+
+        self.asm.set_span(span);
+        self.asm.place_label(exit.label);
+        self.pop_to(returns + 1);
+        self.op(Opcode::JUMP);
+        self.leave = None;
+    }
+
+    /// Lowers a call, leaving exactly `n` values on the stack.
+    fn lower_call_to(&mut self, call: &yul::ExprCall<'_>, n: usize) {
+        let height = self.height;
+        match self.lower_call(call) {
+            Some(results) if results == n => return,
+            Some(results) => {
+                let msg = format!("`{}` returns {results} values, but {n} are expected", call.name);
+                self.err(call.name.span, msg);
+                self.pop_to(height);
+            }
+            None => {}
+        }
+        self.push_zeros(n);
+    }
+
+    /// Lowers a call, leaving its results on the stack, and returns their number.
+    ///
+    /// Returns `None`, leaving the stack unchanged, if an error was emitted.
+    fn lower_call(&mut self, call: &yul::ExprCall<'_>) -> Option<usize> {
+        let name = call.name;
+        let check_args = |this: &mut Self, expected: usize| {
+            if call.arguments.len() == expected {
+                return true;
+            }
+            let msg = format!(
+                "`{name}` expects {expected} arguments, but {} were given",
+                call.arguments.len()
+            );
+            this.err(name.span, msg);
+            false
+        };
+
+        if let Some(&(f, _)) = self.definitions.get(&name.name) {
+            if !check_args(self, f.parameters.len()) {
+                return None;
+            }
+            let height = self.height;
+            let ret = self.asm.new_label();
+            self.push_zeros(f.returns.len());
+            self.height += 1;
+            self.asm.push_label(ret);
+            self.lower_args(call);
+            let entry = self.function_label(name.name);
+            self.asm.jump_to(entry);
+            self.asm.place_label(ret);
+            self.height = height + f.returns.len();
+            return Some(f.returns.len());
+        }
+
+        match name.as_str() {
+            "datasize" | "dataoffset" => {
+                if !check_args(self, 1) {
+                    return None;
+                }
+                let item = self.data_item(call)?;
+                self.height += 1;
+                self.asm.push(item);
+                return Some(1);
+            }
+            "datacopy" => {
+                if !check_args(self, 3) {
+                    return None;
+                }
+                self.lower_args(call);
+                self.op(Opcode::CODECOPY);
+                return Some(0);
+            }
+            _ => {}
+        }
+
+        let Some(op) = builtin_opcode(name.as_str()) else {
+            self.err(name.span, format!("unknown Yul function `{name}`"));
+            return None;
+        };
+        if !check_args(self, op.inputs() as usize) {
+            return None;
+        }
+        self.lower_args(call);
+        self.op(op);
+        Some(op.outputs() as usize)
+    }
+
+    /// Pushes the arguments of a call, from last to first.
+    fn lower_args(&mut self, call: &yul::ExprCall<'_>) {
+        for arg in call.arguments.iter().rev() {
+            self.lower_expr(arg);
+        }
+    }
+
+    /// Returns the item pushing the argument of `datasize` or `dataoffset`, which must be the
+    /// name of the object or of one of its children.
+    fn data_item(&mut self, call: &yul::ExprCall<'_>) -> Option<AsmItem> {
+        let arg = &call.arguments[0];
+        let name = match &arg.kind {
+            yul::ExprKind::Lit(lit) => match &lit.kind {
+                LitKind::Str(_, bytes) => std::str::from_utf8(bytes).ok(),
+                _ => None,
+            },
+            _ => None,
+        };
+        let Some(name) = name else {
+            self.err(arg.span, format!("the argument of `{}` must be an object name", call.name));
+            return None;
+        };
+        let size = call.name.as_str() == "datasize";
+        if name == self.object.name.value.as_str() {
+            return Some(if size { AsmItem::PushProgramSize } else { AsmItem::Push(U256::ZERO) });
+        }
+        match self.subs.get(&Symbol::intern(name)) {
+            Some(&sub) => {
+                Some(if size { AsmItem::PushSubSize(sub) } else { AsmItem::PushSubOffset(sub) })
+            }
+            None => {
+                self.err(arg.span, format!("unknown object `{name}`"));
+                None
+            }
+        }
+    }
+
+    // --- Statements ---
+
+    /// Lowers a block, popping the variables declared in it at the end.
+    fn lower_block(&mut self, block: &yul::Block<'_>) {
+        let height = self.height;
+        for stmt in block.iter() {
+            self.lower_stmt(stmt);
+        }
+        self.end_scope(height);
+    }
+
+    fn lower_stmt(&mut self, stmt: &yul::Stmt<'_>) {
+        let prev_span = self.asm.set_span(stmt.span);
+        match &stmt.kind {
+            yul::StmtKind::Block(block) => self.lower_block(block),
+            yul::StmtKind::AssignSingle(path, expr) => {
+                self.lower_expr(expr);
+                self.store_var(*path.last());
+            }
+            yul::StmtKind::AssignMulti(paths, call) => {
+                self.lower_call_to(call, paths.len());
+                // The last result is on top of the stack.
+                for path in paths.iter().rev() {
+                    self.store_var(*path.last());
+                }
+            }
+            yul::StmtKind::Expr(call) => self.lower_call_to(call, 0),
+            yul::StmtKind::If(cond, body) => {
+                let end = self.asm.new_label();
+                self.lower_expr(cond);
+                self.op(Opcode::ISZERO);
+                self.jump_if(end);
+                self.lower_block(body);
+                self.asm.place_label(end);
+            }
+            yul::StmtKind::For { init, cond, step, body } => self.lower_for(init, cond, step, body),
+            yul::StmtKind::Switch(switch) => self.lower_switch(switch),
+            yul::StmtKind::Leave => match self.leave {
+                Some(target) => self.jump_out(target),
+                None => self.err(stmt.span, "`leave` outside of function"),
+            },
+            yul::StmtKind::Break | yul::StmtKind::Continue => match self.loops.last() {
+                Some(&targets) => {
+                    let is_break = matches!(stmt.kind, yul::StmtKind::Break);
+                    self.jump_out(if is_break { targets.break_ } else { targets.continue_ });
+                }
+                None => self.err(stmt.span, "`break` or `continue` outside of loop"),
+            },
+            // Functions are generated after the code.
+            yul::StmtKind::FunctionDef(_) => {}
+            yul::StmtKind::VarDecl(names, value) => {
+                match value {
+                    Some(yul::Expr { kind: yul::ExprKind::Call(call), .. }) => {
+                        self.lower_call_to(call, names.len())
+                    }
+                    Some(expr) if names.len() == 1 => self.lower_expr(expr),
+                    Some(expr) => {
+                        self.err(expr.span, "multiple variables must be initialized by a call");
+                        self.push_zeros(names.len());
+                    }
+                    None => self.push_zeros(names.len()),
+                }
+                for (i, name) in names.iter().enumerate() {
+                    self.vars.insert(name.name, self.height - names.len() + i);
+                }
+            }
+        }
+        self.asm.set_span(prev_span);
+    }
+
+    /// Lowers `for { init } cond { step } { body }`.
+    ///
+    /// The variables declared in `init` are in scope for the whole loop.
+    fn lower_for(
+        &mut self,
+        init: &yul::Block<'_>,
+        cond: &yul::Expr<'_>,
+        step: &yul::Block<'_>,
+        body: &yul::Block<'_>,
+    ) {
+        let height = self.height;
+        for stmt in init.iter() {
+            self.lower_stmt(stmt);
+        }
+        let loop_height = self.height;
+        let start = self.asm.new_label();
+        let break_ = Target { label: self.asm.new_label(), height: loop_height };
+        let continue_ = Target { label: self.asm.new_label(), height: loop_height };
+        self.asm.place_label(start);
+        self.lower_expr(cond);
+        self.op(Opcode::ISZERO);
+        self.jump_if(break_.label);
+        self.loops.push(LoopTargets { break_, continue_ });
+        self.lower_block(body);
+        self.loops.pop();
+        self.asm.place_label(continue_.label);
+        self.lower_block(step);
+        self.asm.jump_to(start);
+        self.asm.place_label(break_.label);
+        self.end_scope(height);
+    }
+
+    /// Lowers a `switch` statement, comparing the selector with each case in order.
+    fn lower_switch(&mut self, switch: &yul::StmtSwitch<'_>) {
+        let height = self.height;
+        self.lower_expr(&switch.selector);
+        let end = self.asm.new_label();
+        let mut cases = Vec::with_capacity(switch.branches.len());
+        for case in switch.branches.iter() {
+            let Some(value) = literal(case.constant) else {
+                self.err(case.constant.span, "unsupported case literal");
+                continue;
+            };
+            let label = self.asm.new_label();
+            self.op(Opcode::DUP1);
+            self.push(value);
+            self.op(Opcode::EQ);
+            self.jump_if(label);
+            cases.push((label, &case.body));
+        }
+
+        self.op(Opcode::POP);
+        if let Some(default) = &switch.default_case {
+            self.lower_block(default);
+        }
+        self.asm.jump_to(end);
+        for (label, body) in cases {
+            // The selector is still on the stack.
+            self.height = height + 1;
+            self.asm.place_label(label);
+            self.op(Opcode::POP);
+            self.lower_block(body);
+            self.asm.jump_to(end);
+        }
+        self.height = height;
+        self.asm.place_label(end);
+    }
+
+    // --- Expressions ---
+
+    /// Lowers an expression, leaving its value on the stack.
+    fn lower_expr(&mut self, expr: &yul::Expr<'_>) {
+        let prev_span = self.asm.set_span(expr.span);
+        match &expr.kind {
+            yul::ExprKind::Path(path) => self.load_var(*path.last()),
+            yul::ExprKind::Lit(lit) => match literal(lit) {
+                Some(value) => self.push(value),
+                None => {
+                    self.err(lit.span, "unsupported literal");
+                    self.push(0u64);
+                }
+            },
+            yul::ExprKind::Call(call) => self.lower_call_to(call, 1),
+        }
+        self.asm.set_span(prev_span);
+    }
+}
@@ -0,0 +1,266 @@
+//! Construction of Yul AST nodes.
+
+use alloy_primitives::U256;
+use solar_ast::{
+    yul::{self, Block, Expr, ExprCall, ExprKind, Stmt, StmtKind},
+    Arena, Lit, LitKind, PathSlice, StrKind,
+};
+use solar_data_structures::BumpExt;
+use solar_interface::{Ident, Span, Symbol};
+
+/// A value that can be used as an operand any number of times: a variable or a constant.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(super) enum Value {
+    /// A Yul variable.
+    Var(Symbol),
+    /// A number.
+    Const(U256),
+}
+
+impl Value {
+    /// The zero constant.
+    pub(super) const ZERO: Self = Self::Const(U256::ZERO);
+
+    /// Returns a constant with the given value.
+    pub(super) fn from_u64(value: u64) -> Self {
+        Self::Const(U256::from(value))
+    }
+}
+
+/// Allocates Yul AST nodes in an arena.
+///
+/// All nodes are annotated with the current span.
+#[derive(Clone, Copy)]
+pub(super) struct Builder<'ast> {
+    arena: &'ast Arena,
+    span: Span,
+}
+
+impl<'ast> Builder<'ast> {
+    pub(super) fn new(arena: &'ast Arena) -> Self {
+        Self { arena, span: Span::DUMMY }
+    }
+
+    /// Sets the span of subsequently built nodes, returning the previous one.
+    pub(super) fn set_span(&mut self, span: Span) -> Span {
+        std::mem::replace(&mut self.span, span)
+    }
+
+    /// Returns the span of subsequently built nodes.
+    pub(super) fn span(&self) -> Span {
+        self.span
+    }
+
+    pub(super) fn ident(&self, name: Symbol) -> Ident {
+        Ident::new(name, self.span)
+    }
+
+    fn path(&self, name: Symbol) -> yul::AstPath<'ast> {
+        PathSlice::from_mut_slice(self.arena.alloc_slice_copy(&[self.ident(name)]))
+    }
+
+    fn lit(&self, symbol: Symbol, kind: LitKind) -> &'ast mut Lit {
+        self.arena.literals.alloc(Lit { span: self.span, symbol, kind })
+    }
+
+    /// Returns a number literal.
+    pub(super) fn num_lit(&self, value: U256) -> &'ast mut Lit {
+        num_lit(self.arena, self.span, value)
+    }
+
+    /// Returns a string literal.
+    pub(super) fn str_lit(&self, value: &str) -> &'ast mut Lit {
+        self.lit(Symbol::intern(value), LitKind::Str(StrKind::Str, value.as_bytes().into()))
+    }
+
+    /// Returns a hex string literal.
+    pub(super) fn hex_lit(&self, value: &[u8]) -> &'ast mut Lit {
+        let symbol = Symbol::intern(&alloy_primitives::hex::encode(value));
+        self.lit(symbol, LitKind::Str(StrKind::Hex, value.into()))
+    }
+
+    pub(super) fn expr(&self, kind: ExprKind<'ast>) -> Expr<'ast> {
+        Expr { span: self.span, kind }
+    }
+
+    /// Returns an expression evaluating to the given value.
+    pub(super) fn value(&self, value: Value) -> Expr<'ast> {
+        match value {
+            Value::Var(name) => self.expr(ExprKind::Path(self.path(name))),
+            Value::Const(value) => self.num(value),
+        }
+    }
+
+    /// Returns a number literal expression.
+    pub(super) fn num(&self, value: impl Into<U256>) -> Expr<'ast> {
+        self.expr(ExprKind::Lit(self.num_lit(value.into())))
+    }
+
+    /// Returns a string literal expression.
+    pub(super) fn str(&self, value: &str) -> Expr<'ast> {
+        self.expr(ExprKind::Lit(self.str_lit(value)))
+    }
+
+    fn expr_call(&self, name: &str, args: impl IntoIterator<Item = Expr<'ast>>) -> ExprCall<'ast> {
+        ExprCall {
+            name: self.ident(Symbol::intern(name)),
+            arguments: self.arena.alloc_from_iter(args.into_iter()),
+        }
+    }
+
+    /// Returns a call expression.
+    pub(super) fn call(
+        &self,
+        name: &str,
+        args: impl IntoIterator<Item = Expr<'ast>>,
+    ) -> Expr<'ast> {
+        self.expr(ExprKind::Call(self.expr_call(name, args)))
+    }
+
+    /// Returns a call of a builtin or function whose operands are all values.
+    pub(super) fn call_values(&self, name: &str, args: &[Value]) -> Expr<'ast> {
+        self.call(name, args.iter().map(|&arg| self.value(arg)))
+    }
+
+    pub(super) fn stmt(&self, kind: StmtKind<'ast>) -> Stmt<'ast> {
+        Stmt { docs: Default::default(), span: self.span, kind }
+    }
+
+    pub(super) fn block(&self, stmts: Vec<Stmt<'ast>>) -> Block<'ast> {
+        self.arena.alloc_vec(stmts)
+    }
+
+    /// Returns a call statement. `expr` must be a call expression.
+    pub(super) fn expr_stmt(&self, expr: Expr<'ast>) -> Stmt<'ast> {
+        let ExprKind::Call(call) = expr.kind else {
+            unreachable!("expression statement must be a call")
+        };
+        self.stmt(StmtKind::Expr(call))
+    }
+
+    /// Returns a call statement.
+    pub(super) fn call_stmt(
+        &self,
+        name: &str,
+        args: impl IntoIterator<Item = Expr<'ast>>,
+    ) -> Stmt<'ast> {
+        self.stmt(StmtKind::Expr(self.expr_call(name, args)))
+    }
+
+    /// Returns `let names := value`, or `let names` if `value` is `None`.
+    pub(super) fn let_(&self, names: &[Symbol], value: Option<Expr<'ast>>) -> Stmt<'ast> {
+        let names = self.arena.alloc_from_iter(names.iter().map(|&name| self.ident(name)));
+        self.stmt(StmtKind::VarDecl(names, value))
+    }
+
+    /// Returns `names := value`.
+    pub(super) fn assign(&self, names: &[Symbol], value: Expr<'ast>) -> Stmt<'ast> {
+        match names {
+            &[name] => self.stmt(StmtKind::AssignSingle(self.path(name), value)),
+            _ => {
+                let ExprKind::Call(call) = value.kind else {
+                    unreachable!("multi-assignment from a non-call expression")
+                };
+                let paths = self.arena.alloc_from_iter(names.iter().map(|&name| self.path(name)));
+                self.stmt(StmtKind::AssignMulti(paths, call))
+            }
+        }
+    }
+
+    /// Returns `if cond { body }`.
+    pub(super) fn if_(&self, cond: Expr<'ast>, body: Vec<Stmt<'ast>>) -> Stmt<'ast> {
+        self.stmt(StmtKind::If(cond, self.block(body)))
+    }
+
+    /// Returns `switch selector case 0 { zero } default { nonzero }`.
+    pub(super) fn if_else(
+        &self,
+        cond: Expr<'ast>,
+        nonzero: Vec<Stmt<'ast>>,
+        zero: Vec<Stmt<'ast>>,
+    ) -> Stmt<'ast> {
+        self.switch(cond, vec![(U256::ZERO, zero)], Some(nonzero))
+    }
+
+    /// Returns a `switch` statement.
+    pub(super) fn switch(
+        &self,
+        selector: Expr<'ast>,
+        cases: Vec<(U256, Vec<Stmt<'ast>>)>,
+        default: Option<Vec<Stmt<'ast>>>,
+    ) -> Stmt<'ast> {
+        let branches = self.arena.alloc_from_iter(cases.into_iter().map(|(value, body)| {
+            yul::StmtSwitchCase { constant: self.num_lit(value), body: self.block(body) }
+        }));
+        let default_case = default.map(|body| self.block(body));
+        self.stmt(StmtKind::Switch(yul::StmtSwitch { selector, branches, default_case }))
+    }
+
+    /// Returns `for {} cond { step } { body }`.
+    pub(super) fn for_(
+        &self,
+        cond: Expr<'ast>,
+        step: Vec<Stmt<'ast>>,
+        body: Vec<Stmt<'ast>>,
+    ) -> Stmt<'ast> {
+        self.stmt(StmtKind::For {
+            init: self.block(Vec::new()),
+            cond,
+            step: self.block(step),
+            body: self.block(body),
+        })
+    }
+
+    /// Returns a function definition.
+    pub(super) fn function(
+        &self,
+        name: &str,
+        parameters: &[Symbol],
+        returns: &[Symbol],
+        body: Vec<Stmt<'ast>>,
+    ) -> Stmt<'ast> {
+        self.stmt(StmtKind::FunctionDef(yul::Function {
+            name: self.ident(Symbol::intern(name)),
+            parameters: self.arena.alloc_from_iter(parameters.iter().map(|&p| self.ident(p))),
+            returns: self.arena.alloc_from_iter(returns.iter().map(|&r| self.ident(r))),
+            body: self.block(body),
+        }))
+    }
+
+    /// Returns an object.
+    pub(super) fn object(
+        &self,
+        name: &str,
+        code: Vec<Stmt<'ast>>,
+        children: Vec<yul::Object<'ast>>,
+        data: Vec<(&str, &[u8])>,
+    ) -> yul::Object<'ast> {
+        let str_lit =
+            |value: &str| solar_ast::StrLit { span: self.span, value: Symbol::intern(value) };
+        yul::Object {
+            docs: Default::default(),
+            span: self.span,
+            name: str_lit(name),
+            code: yul::CodeBlock { span: self.span, code: self.block(code) },
+            children: self.arena.alloc_vec(children),
+            data: self.arena.alloc_from_iter(data.into_iter().map(|(name, data)| yul::Data {
+                span: self.span,
+                name: str_lit(name),
+                data: self.hex_lit(data),
+            })),
+        }
+    }
+}
+
+/// Allocates a number literal.
+///
+/// Small numbers are written in decimal, others in hexadecimal.
+pub(crate) fn num_lit(arena: &Arena, span: Span, value: U256) -> &mut Lit {
+    let symbol = if value < U256::from(1u64 << 32) {
+        Symbol::intern(&value.to_string())
+    } else {
+        Symbol::intern(&format!("{value:#x}"))
+    };
+    let n = num_bigint::BigInt::from_bytes_be(num_bigint::Sign::Plus, &value.to_be_bytes::<32>());
+    arena.literals.alloc(Lit { span, symbol, kind: LitKind::Number(n) })
+}
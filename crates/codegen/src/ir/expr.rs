@@ -0,0 +1,1450 @@
+use super::{type_name, FunctionCx, Value};
+use crate::{
+    lower::{Panic, FREE_MEMORY_POINTER},
+    resolve::{
+        builtin_member, int_info, is_abi_value_type, is_memory_ref, is_storage_ref, peel_udvt,
+        stack_slots, string_literal, Callee,
+    },
+};
+use alloy_primitives::{keccak256, U256};
+use solar_ast::{yul, BinOpKind, ElementaryType, LitKind, UnOpKind};
+use solar_interface::{sym, Ident, Span, Symbol};
+use solar_sema::{
+    builtins::Builtin,
+    eval::ConstantEvaluator,
+    hir,
+    ty::{Ty, TyKind},
+};
+use std::borrow::Cow;
+
+/// An assignable location.
+#[derive(Clone, Copy, Debug)]
+enum Place<'gcx> {
+    /// A local variable.
+    Var(Symbol),
+    /// A value at the given byte offset in a storage slot.
+    Storage(Value, u8, Ty<'gcx>),
+    /// An unsupported place, for which an error has already been emitted. Loads return zero and
+    /// stores are discarded.
+    Err,
+}
+
+impl<'ast, 'gcx> FunctionCx<'_, 'ast, 'gcx> {
+    /// Lowers an expression, returning its value, or one value per component for tuples.
+    pub(super) fn lower_expr(&mut self, expr: &hir::Expr<'_>) -> (Vec<Value>, Ty<'gcx>) {
+        let prev_span = self.b.set_span(expr.span);
+        let (values, ty) = self.lower_expr_kind(expr);
+        debug_assert_eq!(values.len(), stack_slots(ty), "{expr:?}");
+        self.b.set_span(prev_span);
+        (values, ty)
+    }
+
+    /// Lowers an expression with a single value.
+    pub(super) fn lower_value(&mut self, expr: &hir::Expr<'_>) -> (Value, Ty<'gcx>) {
+        let (values, ty) = self.lower_expr(expr);
+        (values.first().copied().unwrap_or(Value::ZERO), ty)
+    }
+
+    /// Lowers an expression that is implicitly converted to `ty`.
+    ///
+    /// Only literals need to be converted explicitly: all other values are clean, and implicit
+    /// conversions between value types don't change their representation.
+    pub(super) fn lower_value_to(&mut self, expr: &hir::Expr<'_>, ty: Ty<'gcx>) -> Value {
+        let prev_span = self.b.set_span(expr.span);
+        let peeled = expr.peel_parens();
+        let value = if let Some(value) = self.literal_value(peeled, ty) {
+            Value::Const(value)
+        } else if let hir::ExprKind::Ternary(cond, t, f) = peeled.kind {
+            self.lower_ternary(cond, t, f, ty)
+        } else if is_memory_ref(ty) && is_storage_ref(self.expr_ty(expr)) {
+            self.unsupported_expr(expr, "copying storage values to memory");
+            Value::ZERO
+        } else {
+            self.lower_value(expr).0
+        };
+        self.b.set_span(prev_span);
+        value
+    }
+
+    fn lower_expr_kind(&mut self, expr: &hir::Expr<'_>) -> (Vec<Value>, Ty<'gcx>) {
+        let b = self.b;
+        if self.is_literal(expr) {
+            let ty = self.expr_ty(expr);
+            let value = self.literal_value(expr, ty).unwrap();
+            return (vec![Value::Const(value)], ty);
+        }
+        let single = |(value, ty)| (vec![value], ty);
+        match expr.kind {
+            hir::ExprKind::Array(_) => self.unsupported_expr(expr, "array literals"),
+            hir::ExprKind::Assign(lhs, op, rhs) => self.lower_assign(lhs, op, rhs),
+            hir::ExprKind::Binary(l, op, r) => single(self.lower_binary(l, op.kind, r)),
+            hir::ExprKind::Call(callee, ref args) => self.lower_call(expr, callee, args),
+            hir::ExprKind::CallOptions(..) => self.unsupported_expr(expr, "external calls"),
+            hir::ExprKind::Delete(x) => {
+                let (place, ty) = self.lower_place(x);
+                if ty.is_value_type() {
+                    self.store(place, Value::ZERO);
+                } else {
+                    self.cx.unsupported(expr.span, "deleting reference types");
+                }
+                (Vec::new(), self.gcx.types.unit)
+            }
+            hir::ExprKind::Ident(res) => self.lower_ident(expr, res),
+            hir::ExprKind::Index(base, Some(index)) => {
+                let base_ty = self.expr_ty(base);
+                if let TyKind::Elementary(ElementaryType::FixedBytes(size)) = base_ty.kind {
+                    let (base, _) = self.lower_value(base);
+                    let index = self.lower_value_to(index, self.gcx.types.uint(256));
+                    let in_bounds = b.call("lt", [b.value(index), b.num(size.bytes() as u64)]);
+                    self.panic_if(b.call("iszero", [in_bounds]), Panic::IndexOutOfBounds);
+                    let byte = b.call_values("byte", &[index, base]);
+                    let value = self.define(self.shl_const(248, byte));
+                    return (vec![value], self.gcx.types.fixed_bytes(1));
+                }
+                let (place, ty) = self.lower_place(expr);
+                (vec![self.load(place)], ty)
+            }
+            hir::ExprKind::Index(_, None) | hir::ExprKind::Slice(..) => {
+                self.unsupported_expr(expr, "slices")
+            }
+            hir::ExprKind::Lit(lit) => self.lower_lit(expr, lit),
+            hir::ExprKind::Member(base, name) => self.lower_member(expr, base, name),
+            hir::ExprKind::New(_) => self.unsupported_expr(expr, "`new` expressions"),
+            hir::ExprKind::Payable(x) => {
+                let (value, _) = self.lower_value(x);
+                (vec![value], self.gcx.types.address_payable)
+            }
+            hir::ExprKind::Ternary(cond, t, f) => {
+                let ty = self.common_ty(t, f);
+                (vec![self.lower_ternary(cond, t, f, ty)], ty)
+            }
+            hir::ExprKind::Tuple(&[Some(x)]) => self.lower_expr(x),
+            hir::ExprKind::Tuple(exprs) => {
+                if exprs.iter().any(Option::is_none) {
+                    return self.unsupported_expr(expr, "tuples with empty components");
+                }
+                let (values, tys): (Vec<_>, Vec<_>) =
+                    exprs.iter().map(|x| self.lower_value(x.unwrap())).unzip();
+                (values, self.gcx.mk_ty(TyKind::Tuple(self.gcx.mk_tys(&tys))))
+            }
+            hir::ExprKind::TypeCall(_) | hir::ExprKind::Type(_) => {
+                self.unsupported_expr(expr, "types as values")
+            }
+            hir::ExprKind::Unary(op, x) => single(self.lower_unary(op.kind, x)),
+            hir::ExprKind::Err(_) => (Vec::new(), self.gcx.types.unit),
+        }
+    }
+
+    /// Emits an "unsupported" error and returns zeros in place of the expression's value.
+    fn unsupported_expr(&mut self, expr: &hir::Expr<'_>, what: &str) -> (Vec<Value>, Ty<'gcx>) {
+        self.cx.unsupported(expr.span, what);
+        let ty = self.expr_ty(expr);
+        (vec![Value::ZERO; stack_slots(ty)], ty)
+    }
+
+    /// Emits an "unsupported" error and returns zero in place of the expression's value.
+    fn unsupported_value(&mut self, expr: &hir::Expr<'_>, what: &str) -> (Value, Ty<'gcx>) {
+        self.cx.unsupported(expr.span, what);
+        (Value::ZERO, self.expr_ty(expr))
+    }
+
+    // --- Literals ---
+
+    /// Returns the value of a literal converted to `ty`, if `expr` is a number literal or a
+    /// string literal converted to a fixed-size byte array.
+    fn literal_value(&mut self, expr: &hir::Expr<'_>, ty: Ty<'gcx>) -> Option<U256> {
+        let ty = peel_udvt(ty);
+        if let hir::ExprKind::Lit(lit) = expr.kind {
+            if let (LitKind::Str(_, bytes), TyKind::Elementary(ElementaryType::FixedBytes(size))) =
+                (&lit.kind, ty.kind)
+            {
+                let mut word = [0u8; 32];
+                let len = bytes.len().min(size.bytes() as usize);
+                word[..len].copy_from_slice(&bytes[..len]);
+                return Some(U256::from_be_bytes(word));
+            }
+        }
+        if !self.is_literal(expr) {
+            return None;
+        }
+        let mut evaluator = ConstantEvaluator::new(self.gcx);
+        let value = match evaluator.try_eval(expr) {
+            Ok(value) => value.data,
+            Err(err) => {
+                self.cx.error = Some(evaluator.emit_eval_error(expr, err));
+                U256::ZERO
+            }
+        };
+        Some(match ty.kind {
+            TyKind::Elementary(ElementaryType::FixedBytes(size)) => {
+                value << (256 - size.bits() as usize)
+            }
+            TyKind::Elementary(ElementaryType::Bool) => U256::from(!value.is_zero() as u8),
+            _ => value,
+        })
+    }
+
+    fn lower_lit(&mut self, expr: &hir::Expr<'_>, lit: &hir::Lit) -> (Vec<Value>, Ty<'gcx>) {
+        let types = &self.gcx.types;
+        match lit.kind {
+            LitKind::Address(address) => {
+                (vec![Value::Const(U256::from_be_slice(address.as_slice()))], types.address)
+            }
+            LitKind::Bool(value) => (vec![Value::from_u64(value as u64)], types.bool),
+            LitKind::Str(..) => self.unsupported_expr(expr, "string literals"),
+            LitKind::Number(_) | LitKind::Rational(_) => unreachable!("handled by `is_literal`"),
+            LitKind::Err(_) => (vec![Value::ZERO], types.unit),
+        }
+    }
+
+    // --- Places ---
+
+    /// Lowers an assignable expression.
+    fn lower_place(&mut self, expr: &hir::Expr<'_>) -> (Place<'gcx>, Ty<'gcx>) {
+        let gcx = self.gcx;
+        let b = self.b;
+        let expr = expr.peel_parens();
+        match expr.kind {
+            hir::ExprKind::Ident(&[hir::Res::Item(hir::ItemId::Variable(id)), ..]) => {
+                let ty = gcx.type_of_item(id.into());
+                if let Some(&var) = self.vars.get(&id) {
+                    return (Place::Var(var), ty);
+                }
+                let var = gcx.hir.variable(id);
+                if var.is_constant() || var.is_immutable() {
+                    self.cx.err(expr.span, "cannot assign to a constant or immutable variable");
+                    return (Place::Err, ty);
+                }
+                match self.cx.storage.get(&id) {
+                    Some(&(slot, offset)) => (Place::Storage(Value::Const(slot), offset, ty), ty),
+                    None => {
+                        self.cx.unsupported(expr.span, "transient storage variables");
+                        (Place::Err, ty)
+                    }
+                }
+            }
+            hir::ExprKind::Index(base, Some(index)) => self.lower_index_place(expr, base, index),
+            hir::ExprKind::Member(base, name)
+                if matches!(self.expr_ty(base).peel_refs().kind, TyKind::Struct(_)) =>
+            {
+                let (base, base_ty) = self.lower_value(base);
+                let TyKind::Struct(id) = base_ty.peel_refs().kind else { unreachable!() };
+                if !is_storage_ref(base_ty) {
+                    self.cx.unsupported(expr.span, "memory and calldata structs");
+                    return (Place::Err, self.expr_ty(expr));
+                }
+                let layout = gcx.struct_storage_layout(id);
+                let item = layout
+                    .items
+                    .iter()
+                    .find(|item| gcx.hir.variable(item.id).name == Some(name))
+                    .expect("struct field not found");
+                let slot = if item.slot.is_zero() {
+                    base
+                } else {
+                    self.define(b.call("add", [b.value(base), b.num(item.slot)]))
+                };
+                (Place::Storage(slot, item.offset, item.ty), item.ty)
+            }
+            _ => {
+                self.cx.unsupported(expr.span, "assigning to this expression");
+                (Place::Err, self.expr_ty(expr))
+            }
+        }
+    }
+
+    /// Lowers `base[index]` where `base` is a storage mapping or array.
+    fn lower_index_place(
+        &mut self,
+        expr: &hir::Expr<'_>,
+        base: &hir::Expr<'_>,
+        index: &hir::Expr<'_>,
+    ) -> (Place<'gcx>, Ty<'gcx>) {
+        let gcx = self.gcx;
+        let b = self.b;
+        let (slot, base_ty) = self.lower_value(base);
+        if !is_storage_ref(base_ty) {
+            self.cx.unsupported(expr.span, "indexing memory and calldata values");
+            return (Place::Err, self.expr_ty(expr));
+        }
+        match base_ty.peel_refs().kind {
+            TyKind::Mapping(key, value) => {
+                if !key.is_value_type() {
+                    self.cx.unsupported(expr.span, "mappings with reference type keys");
+                    return (Place::Err, value);
+                }
+                let key = self.lower_value_to(index, key);
+                let f = self.mapping_index_helper();
+                let slot = self.define(b.call_values(&f, &[slot, key]));
+                (Place::Storage(slot, 0, value), value)
+            }
+            TyKind::Array(elem, _) | TyKind::DynArray(elem) => {
+                if elem.is_value_type() && gcx.type_storage_bytes(elem) <= 16 {
+                    self.cx.unsupported(expr.span, "indexing packed storage arrays");
+                    return (Place::Err, elem);
+                }
+                let index = self.lower_value_to(index, gcx.types.uint(256));
+                let length = match base_ty.peel_refs().kind {
+                    TyKind::Array(_, len) => b.num(len),
+                    _ => b.call_values("sload", &[slot]),
+                };
+                let in_bounds = b.call("lt", [b.value(index), length]);
+                self.panic_if(b.call("iszero", [in_bounds]), Panic::IndexOutOfBounds);
+                let data = match base_ty.peel_refs().kind {
+                    TyKind::DynArray(_) => self.array_data_slot(slot),
+                    _ => slot,
+                };
+                let offset = self.elem_offset(elem, index);
+                let slot = self.define(b.call("add", [b.value(data), offset]));
+                (Place::Storage(slot, 0, elem), elem)
+            }
+            _ => {
+                self.cx.unsupported(expr.span, "indexing storage byte arrays");
+                (Place::Err, self.expr_ty(expr))
+            }
+        }
+    }
+
+    /// Returns the name of the helper computing the slot of a mapping value.
+    fn mapping_index_helper(&mut self) -> String {
+        self.cx.helper("mapping_index_access".into(), |cx, name| {
+            let b = cx.b;
+            let (slot, key, data) = (sym::slot, sym::key, sym::data);
+            b.function(
+                name,
+                &[slot, key],
+                &[data],
+                vec![
+                    b.call_stmt("mstore", [b.num(0u64), b.value(Value::Var(key))]),
+                    b.call_stmt("mstore", [b.num(0x20u64), b.value(Value::Var(slot))]),
+                    b.assign(&[data], b.call("keccak256", [b.num(0u64), b.num(0x40u64)])),
+                ],
+            )
+        })
+    }
+
+    /// Returns the offset in slots of the element at `index` in a storage array.
+    fn elem_offset(&self, elem: Ty<'gcx>, index: Value) -> yul::Expr<'ast> {
+        let b = self.b;
+        let slots = self.gcx.type_storage_slots(elem);
+        if slots == U256::from(1) {
+            b.value(index)
+        } else {
+            b.call("mul", [b.value(index), b.num(slots)])
+        }
+    }
+
+    /// Returns the slot of the first element of a dynamic storage array.
+    fn array_data_slot(&mut self, slot: Value) -> Value {
+        let f = self.cx.helper("array_data_slot".into(), |cx, name| {
+            let b = cx.b;
+            let (slot, data) = (sym::slot, sym::data);
+            b.function(
+                name,
+                &[slot],
+                &[data],
+                vec![
+                    b.call_stmt("mstore", [b.num(0u64), b.value(Value::Var(slot))]),
+                    b.assign(&[data], b.call("keccak256", [b.num(0u64), b.num(0x20u64)])),
+                ],
+            )
+        });
+        self.define(self.b.call_values(&f, &[slot]))
+    }
+
+    /// Loads the value of a place.
+    ///
+    /// Storage places of reference types are returned as their slot.
+    fn load(&mut self, place: Place<'gcx>) -> Value {
+        let b = self.b;
+        match place {
+            // Copy the variable, as it may be assigned before the value is used.
+            Place::Var(var) => self.define(b.value(Value::Var(var))),
+            Place::Storage(slot, _, ty) if !ty.is_value_type() => slot,
+            Place::Storage(slot, offset, ty) => {
+                let bytes = self.gcx.type_storage_bytes(ty);
+                if bytes == 32 {
+                    return self.define(b.call_values("sload", &[slot]));
+                }
+                let f = self.storage_read_helper(offset, ty);
+                self.define(b.call_values(&f, &[slot]))
+            }
+            Place::Err => Value::ZERO,
+        }
+    }
+
+    /// Returns the name of the helper reading a packed value from storage.
+    fn storage_read_helper(&mut self, offset: u8, ty: Ty<'gcx>) -> String {
+        let name = format!("read_from_storage_offset_{offset}_{}", type_name(ty));
+        let bytes = self.gcx.type_storage_bytes(ty);
+        self.cx.helper(name, |cx, name| {
+            let fcx = cx.fcx(None);
+            let b = fcx.b;
+            let (slot, value) = (sym::slot, sym::value);
+            let mut word = b.call_values("sload", &[Value::Var(slot)]);
+            if offset != 0 {
+                word = fcx.shr_const(8 * offset as u32, word);
+            }
+            let mask = (U256::from(1) << (8 * bytes as usize)) - U256::from(1);
+            let mut result = b.call("and", [word, b.num(mask)]);
+            match peel_udvt(ty).kind {
+                // Fixed-size byte arrays are stored right-aligned.
+                TyKind::Elementary(ElementaryType::FixedBytes(_)) => {
+                    result = fcx.shl_const(256 - 8 * bytes as u32, result)
+                }
+                TyKind::Elementary(ElementaryType::Int(_)) => result = fcx.cleanup(ty, result),
+                _ => {}
+            }
+            b.function(name, &[slot], &[value], vec![b.assign(&[value], result)])
+        })
+    }
+
+    /// Stores a value in a place.
+    fn store(&mut self, place: Place<'gcx>, value: Value) {
+        let b = self.b;
+        match place {
+            Place::Var(var) => self.emit(b.assign(&[var], b.value(value))),
+            Place::Storage(slot, offset, ty) => {
+                if !ty.is_value_type() {
+                    self.cx.unsupported(b.span(), "assigning reference types in storage");
+                    return;
+                }
+                if self.gcx.type_storage_bytes(ty) == 32 {
+                    self.emit(b.call_stmt("sstore", [b.value(slot), b.value(value)]));
+                    return;
+                }
+                let f = self.storage_write_helper(offset, ty);
+                self.emit(b.call_stmt(&f, [b.value(slot), b.value(value)]));
+            }
+            Place::Err => {}
+        }
+    }
+
+    /// Returns the name of the helper writing a packed value to storage.
+    fn storage_write_helper(&mut self, offset: u8, ty: Ty<'gcx>) -> String {
+        let name = format!("update_storage_value_offset_{offset}_{}", type_name(ty));
+        let bytes = self.gcx.type_storage_bytes(ty);
+        self.cx.helper(name, |cx, name| {
+            let fcx = cx.fcx(None);
+            let b = fcx.b;
+            let (slot, value) = (sym::slot, sym::value);
+            let mask = (U256::from(1) << (8 * bytes as usize)) - U256::from(1);
+            let shifted = match peel_udvt(ty).kind {
+                TyKind::Elementary(ElementaryType::FixedBytes(_)) => {
+                    fcx.shr_const(256 - 8 * bytes as u32, b.value(Value::Var(value)))
+                }
+                _ => b.call("and", [b.value(Value::Var(value)), b.num(mask)]),
+            };
+            let shifted =
+                if offset != 0 { fcx.shl_const(8 * offset as u32, shifted) } else { shifted };
+            let kept = b.call(
+                "and",
+                [
+                    b.call_values("sload", &[Value::Var(slot)]),
+                    b.num(!(mask << (8 * offset as usize))),
+                ],
+            );
+            b.function(
+                name,
+                &[slot, value],
+                &[],
+                vec![b.call_stmt(
+                    "sstore",
+                    [b.value(Value::Var(slot)), b.call("or", [kept, shifted])],
+                )],
+            )
+        })
+    }
+
+    /// Stores the value of `init` in a state variable.
+    pub(super) fn store_state_var(
+        &mut self,
+        id: hir::VariableId,
+        ty: Ty<'gcx>,
+        init: &hir::Expr<'_>,
+    ) {
+        let span = self.gcx.hir.variable(id).span;
+        let Some(&(slot, offset)) = self.cx.storage.get(&id) else {
+            self.cx.unsupported(span, "transient storage variables");
+            return;
+        };
+        if !ty.is_value_type() {
+            self.cx.unsupported(span, "initializing reference type state variables");
+            return;
+        }
+        let value = self.lower_value_to(init, ty);
+        self.store(Place::Storage(Value::Const(slot), offset, ty), value);
+    }
+
+    // --- Identifiers and members ---
+
+    fn lower_ident(&mut self, expr: &hir::Expr<'_>, res: &[hir::Res]) -> (Vec<Value>, Ty<'gcx>) {
+        let gcx = self.gcx;
+        match *res {
+            [hir::Res::Item(hir::ItemId::Variable(id)), ..] => {
+                let var = gcx.hir.variable(id);
+                if !self.vars.contains_key(&id) && var.is_constant() {
+                    let ty = gcx.type_of_item(id.into());
+                    return (vec![self.lower_constant(id)], ty);
+                }
+                if !self.vars.contains_key(&id) && var.is_immutable() {
+                    return self.unsupported_expr(expr, "immutable variables");
+                }
+                let (place, ty) = self.lower_place(expr);
+                (vec![self.load(place)], ty)
+            }
+            [hir::Res::Builtin(Builtin::This)] => {
+                let value = self.define(self.b.call("address", []));
+                (vec![value], gcx.mk_ty(TyKind::Contract(self.contract)))
+            }
+            [hir::Res::Item(hir::ItemId::Function(_)), ..] => {
+                self.unsupported_expr(expr, "function pointers")
+            }
+            _ => self.unsupported_expr(expr, "this expression"),
+        }
+    }
+
+    /// Lowers the value of a constant variable by inlining its initializer.
+    fn lower_constant(&mut self, id: hir::VariableId) -> Value {
+        let ty = self.gcx.type_of_item(id.into());
+        let init = self.gcx.hir.variable(id).initializer.expect("constant without initializer");
+        self.lower_value_to(init, ty)
+    }
+
+    fn lower_member(
+        &mut self,
+        expr: &hir::Expr<'_>,
+        base: &hir::Expr<'_>,
+        name: Ident,
+    ) -> (Vec<Value>, Ty<'gcx>) {
+        let gcx = self.gcx;
+        let b = self.b;
+        let single = |(value, ty)| (vec![value], ty);
+        match base.peel_parens().kind {
+            hir::ExprKind::Ident(res) => {
+                for &res in res {
+                    match res {
+                        hir::Res::Builtin(b) => {
+                            if let Some(member) = builtin_member(b, name) {
+                                return single(self.lower_builtin_value(expr, member));
+                            }
+                        }
+                        hir::Res::Item(hir::ItemId::Enum(id)) => {
+                            let variants = gcx.hir.enumm(id).variants;
+                            let i = variants.iter().position(|v| *v == name).unwrap();
+                            return (vec![Value::from_u64(i as u64)], gcx.mk_ty(TyKind::Enum(id)));
+                        }
+                        hir::Res::Item(hir::ItemId::Contract(c)) => {
+                            if let Some(var) = self.contract_constant(c, name) {
+                                let ty = gcx.type_of_item(var.into());
+                                return (vec![self.lower_constant(var)], ty);
+                            }
+                        }
+                        hir::Res::Item(hir::ItemId::Error(id)) => {
+                            let selector = gcx.function_selector(id);
+                            let value = U256::from_be_slice(selector.as_slice()) << 224;
+                            return (vec![Value::Const(value)], gcx.types.fixed_bytes(4));
+                        }
+                        hir::Res::Item(hir::ItemId::Event(id)) => {
+                            let value = U256::from_be_bytes(gcx.event_selector(id).0);
+                            return (vec![Value::Const(value)], gcx.types.fixed_bytes(32));
+                        }
+                        _ => {}
+                    }
+                }
+            }
+            hir::ExprKind::TypeCall(ref ty) => {
+                let ty = gcx.type_of_hir_ty(ty);
+                return single(self.lower_type_member(expr, ty, name));
+            }
+            hir::ExprKind::Member(inner, inner_name)
+                if inner_name.name == Builtin::AddressCode.name()
+                    && name.name == Builtin::ArrayLength.name() =>
+            {
+                let (address, _) = self.lower_value(inner);
+                let value = self.define(b.call_values("extcodesize", &[address]));
+                return (vec![value], gcx.types.uint(256));
+            }
+            hir::ExprKind::Member(inner, function)
+                if name.name == Builtin::ErrorSelector.name() =>
+            {
+                let Some(selector) = self.external_function_selector(inner, function) else {
+                    return self.unsupported_expr(expr, "this selector");
+                };
+                let value = U256::from_be_slice(selector.as_slice()) << 224;
+                return (vec![Value::Const(value)], gcx.types.fixed_bytes(4));
+            }
+            _ => {}
+        }
+
+        let base_ty = self.expr_ty(base);
+        match base_ty.peel_refs().kind {
+            TyKind::Elementary(ElementaryType::Address(_)) => {
+                let f = if name.name == Builtin::AddressBalance.name() {
+                    "balance"
+                } else if name.name == Builtin::AddressCodehash.name() {
+                    "extcodehash"
+                } else {
+                    return self.unsupported_expr(expr, "this member");
+                };
+                let (address, _) = self.lower_value(base);
+                let value = self.define(b.call_values(f, &[address]));
+                (vec![value], self.expr_ty(expr))
+            }
+            TyKind::Elementary(ElementaryType::FixedBytes(size)) => {
+                (vec![Value::from_u64(size.bytes() as u64)], gcx.types.uint(8))
+            }
+            TyKind::Array(_, len) if name.name == Builtin::ArrayLength.name() => {
+                (vec![Value::Const(len)], gcx.types.uint(256))
+            }
+            TyKind::DynArray(_) if name.name == Builtin::ArrayLength.name() => {
+                let (slot, ty) = self.lower_value(base);
+                if !is_storage_ref(ty) {
+                    return self.unsupported_expr(expr, "memory and calldata arrays");
+                }
+                let value = self.define(b.call_values("sload", &[slot]));
+                (vec![value], gcx.types.uint(256))
+            }
+            TyKind::Struct(_) => {
+                let (place, ty) = self.lower_place(expr);
+                (vec![self.load(place)], ty)
+            }
+            _ => self.unsupported_expr(expr, "this member"),
+        }
+    }
+
+    /// Lowers a member of `type(T)`.
+    fn lower_type_member(
+        &mut self,
+        expr: &hir::Expr<'_>,
+        ty: Ty<'gcx>,
+        name: Ident,
+    ) -> (Value, Ty<'gcx>) {
+        let gcx = self.gcx;
+        let value = if name.name == Builtin::TypeMin.name() || name.name == Builtin::TypeMax.name()
+        {
+            let max = name.name == Builtin::TypeMax.name();
+            match ty.kind {
+                TyKind::Elementary(ElementaryType::UInt(size)) => {
+                    if max {
+                        U256::MAX >> (256 - size.bits() as usize)
+                    } else {
+                        U256::ZERO
+                    }
+                }
+                TyKind::Elementary(ElementaryType::Int(size)) => {
+                    if max {
+                        U256::MAX >> (257 - size.bits() as usize)
+                    } else {
+                        U256::MAX << (size.bits() as usize - 1)
+                    }
+                }
+                TyKind::Enum(id) if max => U256::from(gcx.hir.enumm(id).variants.len() - 1),
+                TyKind::Enum(_) => U256::ZERO,
+                _ => return self.unsupported_value(expr, "this type member"),
+            }
+        } else if name.name == Builtin::InterfaceId.name() {
+            let TyKind::Contract(id) = ty.kind else { unreachable!() };
+            let id = gcx.interface_id(id);
+            return (
+                Value::Const(U256::from_be_slice(id.as_slice()) << 224),
+                gcx.types.fixed_bytes(4),
+            );
+        } else {
+            return self.unsupported_value(expr, "this type member");
+        };
+        (Value::Const(value), ty)
+    }
+
+    /// Lowers a member of a builtin module, such as `msg.sender`.
+    fn lower_builtin_value(&mut self, expr: &hir::Expr<'_>, b: Builtin) -> (Value, Ty<'gcx>) {
+        let f = match b {
+            Builtin::BlockCoinbase => "coinbase",
+            Builtin::BlockTimestamp => "timestamp",
+            Builtin::BlockDifficulty | Builtin::BlockPrevrandao => {
+                if self.gcx.sess.evm_version.has_prev_randao() {
+                    "prevrandao"
+                } else {
+                    "difficulty"
+                }
+            }
+            Builtin::BlockNumber => "number",
+            Builtin::BlockGaslimit => "gaslimit",
+            Builtin::BlockChainid => "chainid",
+            Builtin::BlockBasefee => "basefee",
+            Builtin::BlockBlobbasefee => "blobbasefee",
+            Builtin::MsgSender => "caller",
+            Builtin::MsgGas => "gas",
+            Builtin::MsgValue => "callvalue",
+            Builtin::MsgSig => {
+                let ty = self.gcx.types.fixed_bytes(4);
+                let word = self.b.call("calldataload", [self.b.num(0u64)]);
+                return (self.define(self.cleanup(ty, word)), ty);
+            }
+            Builtin::TxOrigin => "origin",
+            Builtin::TxGasPrice => "gasprice",
+            _ => return self.unsupported_value(expr, &format!("`{}`", b.name())),
+        };
+        (self.define(self.b.call(f, [])), b.ty(self.gcx))
+    }
+
+    // --- Operators ---
+
+    fn lower_assign(
+        &mut self,
+        lhs: &hir::Expr<'_>,
+        op: Option<hir::BinOp>,
+        rhs: &hir::Expr<'_>,
+    ) -> (Vec<Value>, Ty<'gcx>) {
+        if let hir::ExprKind::Tuple(lhs) = lhs.peel_parens().kind {
+            if lhs.len() != 1 {
+                self.lower_tuple_assign(lhs, rhs);
+                return (Vec::new(), self.gcx.types.unit);
+            }
+        }
+
+        let (place, ty) = self.lower_place(lhs);
+        let value = match op {
+            Some(op) => {
+                let current = self.load(place);
+                let rhs_ty = match op.kind {
+                    BinOpKind::Shl | BinOpKind::Shr | BinOpKind::Sar | BinOpKind::Pow => {
+                        self.expr_ty(rhs)
+                    }
+                    _ => ty,
+                };
+                let rhs = self.lower_value_to(rhs, rhs_ty);
+                self.binary_op(op.kind, ty, current, rhs)
+            }
+            None => self.lower_value_to(rhs, ty),
+        };
+        self.store(place, value);
+        (vec![value], ty)
+    }
+
+    /// Lowers `(a, b) = rhs`.
+    fn lower_tuple_assign(&mut self, lhs: &[Option<&hir::Expr<'_>>], rhs: &hir::Expr<'_>) {
+        // Evaluate the right-hand side first, then assign the components.
+        let values = match rhs.peel_parens().kind {
+            hir::ExprKind::Tuple(rhs) if rhs.len() == lhs.len() => lhs
+                .iter()
+                .zip(rhs)
+                .map(|(l, r)| {
+                    let r = r.expect("empty tuple component");
+                    let ty = l.map_or_else(|| self.expr_ty(r), |l| self.expr_ty(l));
+                    self.lower_value_to(r, ty)
+                })
+                .collect(),
+            _ => self.lower_expr(rhs).0,
+        };
+        for (l, &value) in lhs.iter().zip(&values).rev() {
+            if let Some(l) = l {
+                let (place, ty) = self.lower_place(l);
+                self.store(place, value);
+            }
+        }
+    }
+
+    fn lower_binary(
+        &mut self,
+        l: &hir::Expr<'_>,
+        op: BinOpKind,
+        r: &hir::Expr<'_>,
+    ) -> (Value, Ty<'gcx>) {
+        let b = self.b;
+        let bool = self.gcx.types.bool;
+        match op {
+            BinOpKind::And | BinOpKind::Or => {
+                // Short-circuit: evaluate `r` only if `l` doesn't determine the result.
+                let l = self.lower_value_to(l, bool);
+                let Value::Var(result) = self.define(b.value(l)) else { unreachable!() };
+                let body = self.block(|fcx| {
+                    let r = fcx.lower_value_to(r, bool);
+                    fcx.emit(b.assign(&[result], b.value(r)));
+                });
+                let cond = b.value(Value::Var(result));
+                let cond = if op == BinOpKind::And { cond } else { b.call("iszero", [cond]) };
+                self.emit(b.if_(cond, body));
+                (Value::Var(result), bool)
+            }
+            BinOpKind::Lt
+            | BinOpKind::Le
+            | BinOpKind::Gt
+            | BinOpKind::Ge
+            | BinOpKind::Eq
+            | BinOpKind::Ne => {
+                let ty = self.common_ty(l, r);
+                let l = self.lower_value_to(l, ty);
+                let r = self.lower_value_to(r, ty);
+                (self.binary_op(op, ty, l, r), bool)
+            }
+            BinOpKind::Shl | BinOpKind::Shr | BinOpKind::Sar | BinOpKind::Pow => {
+                let ty = self.expr_ty(l);
+                let r_ty = self.expr_ty(r);
+                let l = self.lower_value_to(l, ty);
+                let r = self.lower_value_to(r, r_ty);
+                (self.binary_op(op, ty, l, r), ty)
+            }
+            _ => {
+                let ty = self.common_ty(l, r);
+                let l = self.lower_value_to(l, ty);
+                let r = self.lower_value_to(r, ty);
+                (self.binary_op(op, ty, l, r), ty)
+            }
+        }
+    }
+
+    /// Applies a binary operation. `ty` is the type of `l`.
+    fn binary_op(&mut self, op: BinOpKind, ty: Ty<'gcx>, l: Value, r: Value) -> Value {
+        let b = self.b;
+        let signed = int_info(ty).is_some_and(|(signed, _)| signed);
+        let (lt, gt) = if signed { ("slt", "sgt") } else { ("lt", "gt") };
+        let result = match op {
+            BinOpKind::Lt => b.call_values(lt, &[l, r]),
+            BinOpKind::Gt => b.call_values(gt, &[l, r]),
+            BinOpKind::Le => b.call("iszero", [b.call_values(gt, &[l, r])]),
+            BinOpKind::Ge => b.call("iszero", [b.call_values(lt, &[l, r])]),
+            BinOpKind::Eq => b.call_values("eq", &[l, r]),
+            BinOpKind::Ne => b.call("iszero", [b.call_values("eq", &[l, r])]),
+            BinOpKind::Shl => {
+                let shifted = if self.has_shifts() {
+                    b.call_values("shl", &[r, l])
+                } else {
+                    b.call("mul", [b.value(l), b.call("exp", [b.num(2u64), b.value(r)])])
+                };
+                self.cleanup(ty, shifted)
+            }
+            BinOpKind::Shr | BinOpKind::Sar => {
+                if signed {
+                    b.call_values("sar", &[r, l])
+                } else if self.has_shifts() {
+                    b.call_values("shr", &[r, l])
+                } else {
+                    b.call("div", [b.value(l), b.call("exp", [b.num(2u64), b.value(r)])])
+                }
+            }
+            BinOpKind::BitAnd => b.call_values("and", &[l, r]),
+            BinOpKind::BitOr => b.call_values("or", &[l, r]),
+            BinOpKind::BitXor => b.call_values("xor", &[l, r]),
+            BinOpKind::Add
+            | BinOpKind::Sub
+            | BinOpKind::Mul
+            | BinOpKind::Div
+            | BinOpKind::Rem
+            | BinOpKind::Pow => return self.arith(op, ty, l, r),
+            BinOpKind::And | BinOpKind::Or => unreachable!("short-circuiting operator"),
+        };
+        self.define(result)
+    }
+
+    /// Applies an arithmetic operation.
+    ///
+    /// In checked mode, calls a helper that panics on overflow; otherwise wraps around to the
+    /// width of `ty`.
+    fn arith(&mut self, op: BinOpKind, ty: Ty<'gcx>, l: Value, r: Value) -> Value {
+        let b = self.b;
+        if !self.unchecked {
+            let f = self.checked_arith_helper(op, ty);
+            return self.define(b.call_values(&f, &[l, r]));
+        }
+        let (signed, _) = int_info(ty).unwrap_or((false, 256));
+        if matches!(op, BinOpKind::Div | BinOpKind::Rem) {
+            self.panic_if(b.call_values("iszero", &[r]), Panic::DivisionByZero);
+        }
+        let f = match (op, signed) {
+            (BinOpKind::Add, _) => "add",
+            (BinOpKind::Sub, _) => "sub",
+            (BinOpKind::Mul, _) => "mul",
+            (BinOpKind::Div, false) => "div",
+            (BinOpKind::Div, true) => "sdiv",
+            (BinOpKind::Rem, false) => "mod",
+            (BinOpKind::Rem, true) => "smod",
+            (BinOpKind::Pow, _) => "exp",
+            _ => unreachable!("not an arithmetic operator: {op:?}"),
+        };
+        let result = b.call_values(f, &[l, r]);
+        self.define(self.cleanup(ty, result))
+    }
+
+    /// Returns the name of the helper applying a checked arithmetic operation.
+    fn checked_arith_helper(&mut self, op: BinOpKind, ty: Ty<'gcx>) -> String {
+        let op_name = match op {
+            BinOpKind::Add => "add",
+            BinOpKind::Sub => "sub",
+            BinOpKind::Mul => "mul",
+            BinOpKind::Div => "div",
+            BinOpKind::Rem => "mod",
+            BinOpKind::Pow => "exp",
+            _ => unreachable!("not an arithmetic operator: {op:?}"),
+        };
+        let name = format!("checked_{op_name}_{}", type_name(ty));
+        if op == BinOpKind::Pow {
+            return self.checked_exp_helper(name, ty);
+        }
+        self.cx.helper(name, |cx, name| {
+            let mut fcx = cx.fcx(None);
+            let b = fcx.b;
+            let (signed, bits) = int_info(ty).unwrap_or((false, 256));
+            let (x_var, y_var, r_var) =
+                (Symbol::intern("x"), Symbol::intern("y"), Symbol::intern("r"));
+            let (x, y, r) = (Value::Var(x_var), Value::Var(y_var), Value::Var(r_var));
+            let min = b.num(U256::from(1) << 255);
+            let overflow = Panic::Arithmetic;
+            let mut range_check = bits < 256;
+            match op {
+                BinOpKind::Add => {
+                    fcx.emit(b.assign(&[r_var], b.call_values("add", &[x, y])));
+                    if bits == 256 {
+                        let cond = if signed {
+                            // Overflow iff `slt(r, x) != slt(y, 0)`.
+                            b.call(
+                                "xor",
+                                [
+                                    b.call_values("slt", &[r, x]),
+                                    b.call_values("slt", &[y, Value::ZERO]),
+                                ],
+                            )
+                        } else {
+                            b.call_values("gt", &[x, r])
+                        };
+                        fcx.panic_if(cond, overflow);
+                    }
+                }
+                BinOpKind::Sub if !signed => {
+                    fcx.panic_if(b.call_values("lt", &[x, y]), overflow);
+                    fcx.emit(b.assign(&[r_var], b.call_values("sub", &[x, y])));
+                    range_check = false;
+                }
+                BinOpKind::Sub => {
+                    fcx.emit(b.assign(&[r_var], b.call_values("sub", &[x, y])));
+                    if bits == 256 {
+                        // Overflow iff `sgt(r, x) != slt(y, 0)`.
+                        let cond = b.call(
+                            "xor",
+                            [
+                                b.call_values("sgt", &[r, x]),
+                                b.call_values("slt", &[y, Value::ZERO]),
+                            ],
+                        );
+                        fcx.panic_if(cond, overflow);
+                    }
+                }
+                BinOpKind::Mul => {
+                    fcx.emit(b.assign(&[r_var], b.call_values("mul", &[x, y])));
+                    if bits > 128 {
+                        // Overflow iff `x != 0 && r / x != y`, or `x == -1 && y == MIN`.
+                        let div = if signed { "sdiv" } else { "div" };
+                        let cond = b.call(
+                            "iszero",
+                            [b.call(
+                                "or",
+                                [
+                                    b.call_values("iszero", &[x]),
+                                    b.call("eq", [b.value(y), b.call_values(div, &[r, x])]),
+                                ],
+                            )],
+                        );
+                        fcx.panic_if(cond, overflow);
+                        if signed && bits == 256 {
+                            let cond = b.call(
+                                "and",
+                                [
+                                    b.call("eq", [b.value(x), b.call("not", [b.num(0u64)])]),
+                                    b.call("eq", [b.value(y), min]),
+                                ],
+                            );
+                            fcx.panic_if(cond, overflow);
+                        }
+                    }
+                }
+                BinOpKind::Div | BinOpKind::Rem => {
+                    fcx.panic_if(b.call_values("iszero", &[y]), Panic::DivisionByZero);
+                    if signed && bits == 256 && op == BinOpKind::Div {
+                        // `MIN / -1` overflows.
+                        let cond = b.call(
+                            "and",
+                            [
+                                b.call("eq", [b.value(x), min]),
+                                b.call("eq", [b.value(y), b.call("not", [b.num(0u64)])]),
+                            ],
+                        );
+                        fcx.panic_if(cond, overflow);
+                    }
+                    let f = match (op, signed) {
+                        (BinOpKind::Div, false) => "div",
+                        (BinOpKind::Div, true) => "sdiv",
+                        (_, false) => "mod",
+                        (_, true) => "smod",
+                    };
+                    fcx.emit(b.assign(&[r_var], b.call_values(f, &[x, y])));
+                    range_check &= signed && op == BinOpKind::Div;
+                }
+                _ => unreachable!(),
+            }
+            if range_check {
+                fcx.range_check(ty, r);
+            }
+            let body = std::mem::take(&mut fcx.stmts);
+            b.function(name, &[x_var, y_var], &[r_var], body)
+        })
+    }
+
+    /// Returns the name of the helper computing a checked exponentiation by repeated squaring.
+    fn checked_exp_helper(&mut self, name: String, ty: Ty<'gcx>) -> String {
+        let mul = self.checked_arith_helper(BinOpKind::Mul, ty);
+        self.cx.helper(name, |cx, name| {
+            let fcx = cx.fcx(None);
+            let b = fcx.b;
+            let (base, exponent, power) =
+                (Symbol::intern("base"), Symbol::intern("exponent"), Symbol::intern("power"));
+            let (base_v, exponent_v, power_v) =
+                (Value::Var(base), Value::Var(exponent), Value::Var(power));
+            let body = vec![
+                b.if_(
+                    b.call_values("and", &[exponent_v, Value::from_u64(1)]),
+                    vec![b.assign(&[power], b.call_values(&mul, &[power_v, base_v]))],
+                ),
+                b.assign(&[exponent], fcx.shr_const(1, b.value(exponent_v))),
+                b.if_(
+                    b.value(exponent_v),
+                    vec![b.assign(&[base], b.call_values(&mul, &[base_v, base_v]))],
+                ),
+            ];
+            b.function(
+                name,
+                &[base, exponent],
+                &[power],
+                vec![
+                    b.assign(&[power], b.num(1u64)),
+                    b.for_(b.value(exponent_v), Vec::new(), body),
+                ],
+            )
+        })
+    }
+
+    /// Panics if `value` is not in the range of `ty`.
+    fn range_check(&mut self, ty: Ty<'gcx>, value: Value) {
+        let b = self.b;
+        let cleaned = self.cleanup(ty, b.value(value));
+        self.panic_if(
+            b.call("iszero", [b.call("eq", [b.value(value), cleaned])]),
+            Panic::Arithmetic,
+        );
+    }
+
+    fn lower_unary(&mut self, op: UnOpKind, x: &hir::Expr<'_>) -> (Value, Ty<'gcx>) {
+        let b = self.b;
+        match op {
+            UnOpKind::Not => {
+                let x = self.lower_value_to(x, self.gcx.types.bool);
+                (self.define(b.call_values("iszero", &[x])), self.gcx.types.bool)
+            }
+            UnOpKind::BitNot => {
+                let (x, ty) = self.lower_value(x);
+                (self.define(self.cleanup(ty, b.call_values("not", &[x]))), ty)
+            }
+            UnOpKind::Neg => {
+                let (x, ty) = self.lower_value(x);
+                if self.unchecked {
+                    let negated = b.call_values("sub", &[Value::ZERO, x]);
+                    return (self.define(self.cleanup(ty, negated)), ty);
+                }
+                let f = self.checked_negate_helper(ty);
+                (self.define(b.call_values(&f, &[x])), ty)
+            }
+            UnOpKind::PreInc | UnOpKind::PreDec | UnOpKind::PostInc | UnOpKind::PostDec => {
+                let (place, ty) = self.lower_place(x);
+                let arith = match op {
+                    UnOpKind::PreInc | UnOpKind::PostInc => BinOpKind::Add,
+                    _ => BinOpKind::Sub,
+                };
+                let old = self.load(place);
+                let new = self.arith(arith, ty, old, Value::from_u64(1));
+                self.store(place, new);
+                match op {
+                    UnOpKind::PreInc | UnOpKind::PreDec => (new, ty),
+                    _ => (old, ty),
+                }
+            }
+        }
+    }
+
+    /// Returns the name of the helper computing a checked negation.
+    fn checked_negate_helper(&mut self, ty: Ty<'gcx>) -> String {
+        let name = format!("negate_{}", type_name(ty));
+        self.cx.helper(name, |cx, name| {
+            let mut fcx = cx.fcx(None);
+            let b = fcx.b;
+            let bits = int_info(ty).map_or(256, |(_, bits)| bits);
+            let (value, r) = (Symbol::intern("value"), Symbol::intern("r"));
+            if bits == 256 {
+                let cond = b.call("eq", [b.value(Value::Var(value)), b.num(U256::from(1) << 255)]);
+                fcx.panic_if(cond, Panic::Arithmetic);
+            }
+            fcx.emit(b.assign(&[r], b.call_values("sub", &[Value::ZERO, Value::Var(value)])));
+            if bits < 256 {
+                fcx.range_check(ty, Value::Var(r));
+            }
+            let body = std::mem::take(&mut fcx.stmts);
+            b.function(name, &[value], &[r], body)
+        })
+    }
+
+    fn lower_ternary(
+        &mut self,
+        cond: &hir::Expr<'_>,
+        t: &hir::Expr<'_>,
+        f: &hir::Expr<'_>,
+        ty: Ty<'gcx>,
+    ) -> Value {
+        let b = self.b;
+        let cond = self.lower_value_to(cond, self.gcx.types.bool);
+        let result = self.temporary();
+        self.emit(b.let_(&[result], None));
+        let t = self.block(|fcx| {
+            let value = fcx.lower_value_to(t, ty);
+            fcx.emit(b.assign(&[result], b.value(value)));
+        });
+        let f = self.block(|fcx| {
+            let value = fcx.lower_value_to(f, ty);
+            fcx.emit(b.assign(&[result], b.value(value)));
+        });
+        self.emit(b.if_else(b.value(cond), t, f));
+        Value::Var(result)
+    }
+
+    // --- Calls ---
+
+    fn lower_call(
+        &mut self,
+        expr: &hir::Expr<'_>,
+        callee: &hir::Expr<'_>,
+        args: &hir::CallArgs<'_>,
+    ) -> (Vec<Value>, Ty<'gcx>) {
+        let gcx = self.gcx;
+        match self.resolve_callee(callee, args) {
+            Callee::Conversion(to) => {
+                let arg = args.exprs().next().expect("conversion without argument");
+                let arg_peeled = arg.peel_parens();
+                if !to.is_value_type() {
+                    return self.unsupported_expr(expr, "conversions to reference types");
+                }
+                let value = if self.is_literal(arg_peeled)
+                    || matches!(arg_peeled.kind, hir::ExprKind::Lit(_))
+                {
+                    self.lower_value_to(arg, to)
+                } else {
+                    let (value, from) = self.lower_value(arg);
+                    self.convert(from, to, value, expr.span)
+                };
+                (vec![value], to)
+            }
+            Callee::Internal(id) => {
+                let f = gcx.hir.function(id);
+                let args = self.ordered_args(f.parameters, args);
+                let args = f
+                    .parameters
+                    .iter()
+                    .zip(&args)
+                    .map(|(&param, arg)| self.lower_value_to(arg, gcx.type_of_item(param.into())))
+                    .collect::<Vec<_>>();
+                let name = self.cx.function(id);
+                let values = self.define_multi(f.returns.len(), self.b.call_values(&name, &args));
+                (values, self.return_ty(id))
+            }
+            Callee::Builtin(b, base) => self.lower_builtin_call(expr, b, base, args),
+            Callee::ArrayPush(base) => self.lower_array_push(expr, base, args),
+            Callee::ArrayPop(base) => self.lower_array_pop(expr, base),
+            Callee::Library(_) => self.unsupported_expr(expr, "external library calls"),
+            Callee::Unsupported(what) => self.unsupported_expr(expr, what),
+        }
+    }
+
+    /// Converts a value from `from` to `to`.
+    fn convert(&mut self, from: Ty<'gcx>, to: Ty<'gcx>, value: Value, span: Span) -> Value {
+        use ElementaryType::*;
+
+        let b = self.b;
+        let (from, to) = (peel_udvt(from), peel_udvt(to));
+        if from == to {
+            return value;
+        }
+        let result = match (from.kind, to.kind) {
+            (TyKind::Elementary(FixedBytes(a)), TyKind::Elementary(FixedBytes(b_))) => {
+                if b_.bytes() >= a.bytes() {
+                    return value;
+                }
+                self.cleanup(to, b.value(value))
+            }
+            // Sizes are equal, checked by the type checker.
+            (TyKind::Elementary(FixedBytes(a)), _) => {
+                self.shr_const(256 - a.bits() as u32, b.value(value))
+            }
+            (_, TyKind::Elementary(FixedBytes(size))) => {
+                self.shl_const(256 - size.bits() as u32, b.value(value))
+            }
+            (_, TyKind::Enum(id)) => {
+                let variants = self.gcx.hir.enumm(id).variants.len();
+                let in_range = b.call("lt", [b.value(value), b.num(variants as u64)]);
+                self.panic_if(b.call("iszero", [in_range]), Panic::EnumConversion);
+                return value;
+            }
+            (TyKind::Enum(_), _)
+            | (
+                TyKind::Elementary(Address(_)) | TyKind::Contract(_),
+                TyKind::Elementary(Address(_)) | TyKind::Contract(_),
+            ) => return value,
+            (TyKind::Elementary(UInt(a)), TyKind::Elementary(UInt(b_)))
+                if b_.bits() >= a.bits() =>
+            {
+                return value
+            }
+            _ if from.is_value_type() => self.cleanup(to, b.value(value)),
+            _ => {
+                self.cx.unsupported(span, "this conversion");
+                return value;
+            }
+        };
+        self.define(result)
+    }
+
+    fn lower_builtin_call(
+        &mut self,
+        expr: &hir::Expr<'_>,
+        builtin: Builtin,
+        base: Option<&hir::Expr<'_>>,
+        args: &hir::CallArgs<'_>,
+    ) -> (Vec<Value>, Ty<'gcx>) {
+        let gcx = self.gcx;
+        let types = &gcx.types;
+        let b = self.b;
+        let args = args.exprs().collect::<Vec<_>>();
+        let single = |value: Value, ty| (vec![value], ty);
+        match builtin {
+            Builtin::Require | Builtin::RequireMsg => {
+                let cond = self.lower_value_to(args[0], types.bool);
+                let message = match args.get(1).map(|&msg| string_literal(msg)) {
+                    None => None,
+                    Some(Some(msg)) => Some(msg),
+                    Some(None) => {
+                        self.cx.unsupported(args[1].span, "non-literal revert reasons");
+                        return (Vec::new(), types.unit);
+                    }
+                };
+                let body = self.block(|fcx| fcx.lower_revert_message(message.as_deref()));
+                self.emit(b.if_(b.call_values("iszero", &[cond]), body));
+            }
+            Builtin::Assert => {
+                let cond = self.lower_value_to(args[0], types.bool);
+                self.panic_if(b.call_values("iszero", &[cond]), Panic::Assert);
+            }
+            Builtin::Revert => self.lower_revert_message(None),
+            Builtin::RevertMsg => match string_literal(args[0]) {
+                Some(msg) => self.lower_revert_message(Some(&msg)),
+                None => self.cx.unsupported(args[0].span, "non-literal revert reasons"),
+            },
+            Builtin::AddMod | Builtin::MulMod => {
+                let values = args
+                    .iter()
+                    .map(|arg| self.lower_value_to(arg, types.uint(256)))
+                    .collect::<Vec<_>>();
+                self.panic_if(b.call_values("iszero", &values[2..]), Panic::DivisionByZero);
+                let f = if builtin == Builtin::AddMod { "addmod" } else { "mulmod" };
+                return single(self.define(b.call_values(f, &values)), types.uint(256));
+            }
+            Builtin::Gasleft => return single(self.define(b.call("gas", [])), types.uint(256)),
+            Builtin::Blockhash | Builtin::Blobhash => {
+                let n = self.lower_value_to(args[0], types.uint(256));
+                let f = if builtin == Builtin::Blockhash { "blockhash" } else { "blobhash" };
+                return single(self.define(b.call_values(f, &[n])), types.fixed_bytes(32));
+            }
+            Builtin::Keccak256 => return self.lower_keccak256(expr, args[0]),
+            Builtin::AddressPayableTransfer | Builtin::AddressPayableSend => {
+                let send = builtin == Builtin::AddressPayableSend;
+                let success = self.lower_transfer(base.unwrap(), args[0], send);
+                if send {
+                    return single(success, types.bool);
+                }
+            }
+            _ => return self.unsupported_expr(expr, &format!("`{}`", builtin.name())),
+        }
+        (Vec::new(), types.unit)
+    }
+
+    /// Lowers `keccak256(arg)`.
+    fn lower_keccak256(
+        &mut self,
+        expr: &hir::Expr<'_>,
+        arg: &hir::Expr<'_>,
+    ) -> (Vec<Value>, Ty<'gcx>) {
+        let ty = self.gcx.types.fixed_bytes(32);
+        if let Some(bytes) = string_literal(arg) {
+            return (vec![Value::Const(U256::from_be_bytes(keccak256(&bytes).0))], ty);
+        }
+        if let hir::ExprKind::Call(callee, ref args) = arg.peel_parens().kind {
+            if let Callee::Builtin(b @ (Builtin::AbiEncode | Builtin::AbiEncodePacked), None) =
+                self.resolve_callee(callee, args)
+            {
+                let hash = self.lower_abi_encode_hash(args, b == Builtin::AbiEncodePacked);
+                return (vec![hash], ty);
+            }
+        }
+        self.unsupported_expr(expr, "hashing memory values")
+    }
+
+    /// Lowers `keccak256(abi.encode(args))` or `keccak256(abi.encodePacked(args))`, encoding the
+    /// arguments in scratch memory.
+    fn lower_abi_encode_hash(&mut self, args: &hir::CallArgs<'_>, packed: bool) -> Value {
+        enum Part<'a, 'gcx> {
+            Value(Value, Ty<'gcx>),
+            Bytes(Cow<'a, [u8]>),
+        }
+
+        let gcx = self.gcx;
+        let b = self.b;
+        let mut parts = Vec::with_capacity(args.len());
+        for arg in args.exprs() {
+            if packed {
+                if let Some(bytes) = string_literal(arg) {
+                    parts.push(Part::Bytes(bytes));
+                    continue;
+                }
+            }
+            let ty = self.expr_ty(arg);
+            if !is_abi_value_type(ty) {
+                self.cx.unsupported(arg.span, "ABI encoding of reference types");
+                return Value::ZERO;
+            }
+            let value = self.lower_value_to(arg, ty);
+            parts.push(Part::Value(value, ty));
+        }
+
+        // Packed encoding is written as right-aligned words, each overwriting the unused bytes of
+        // the previous one, so it starts one word into the scratch space.
+        let ptr = self.define(b.call("mload", [b.num(FREE_MEMORY_POINTER)]));
+        let start = if packed { 32 } else { 0 };
+        let sizes = parts
+            .iter()
+            .map(|part| match *part {
+                Part::Value(_, ty) if packed => gcx.type_storage_bytes(ty) as u64,
+                Part::Value(..) => 32,
+                Part::Bytes(ref bytes) => bytes.len() as u64,
+            })
+            .collect::<Vec<_>>();
+        let total = sizes.iter().sum::<u64>();
+        let mut end = start + total;
+        let mstore = |fcx: &mut Self, offset: u64, value: yul::Expr<'ast>| {
+            let ptr = b.call("add", [b.value(ptr), b.num(offset)]);
+            fcx.emit(b.call_stmt("mstore", [ptr, value]));
+        };
+        for (part, &size) in parts.iter().zip(&sizes).rev() {
+            match *part {
+                Part::Value(value, ty) => {
+                    let mut value = b.value(value);
+                    if let TyKind::Elementary(ElementaryType::FixedBytes(size)) = peel_udvt(ty).kind
+                    {
+                        if packed {
+                            value = self.shr_const(256 - size.bits() as u32, value);
+                        }
+                    }
+                    mstore(self, end - 32, value);
+                }
+                Part::Bytes(ref bytes) => {
+                    let part_start = end - size;
+                    for (i, chunk) in bytes.chunks(32).enumerate().rev() {
+                        let chunk_end = part_start + 32 * i as u64 + chunk.len() as u64;
+                        mstore(self, chunk_end - 32, b.num(U256::from_be_slice(chunk)));
+                    }
+                }
+            }
+            end -= size;
+        }
+        let data = b.call("add", [b.value(ptr), b.num(start)]);
+        self.define(b.call("keccak256", [data, b.num(total)]))
+    }
+
+    /// Lowers `address.transfer(value)` and `address.send(value)`, returning the success flag.
+    fn lower_transfer(
+        &mut self,
+        address: &hir::Expr<'_>,
+        value: &hir::Expr<'_>,
+        send: bool,
+    ) -> Value {
+        let b = self.b;
+        let (address, _) = self.lower_value(address);
+        let value = self.lower_value_to(value, self.gcx.types.uint(256));
+        let gas = b.call("mul", [b.call_values("iszero", &[value]), b.num(2300u64)]);
+        let zero = || b.num(0u64);
+        let success = self.define(
+            b.call("call", [gas, b.value(address), b.value(value), zero(), zero(), zero(), zero()]),
+        );
+        if !send {
+            // Bubble up the revert data.
+            self.emit(b.if_(
+                b.call_values("iszero", &[success]),
+                vec![
+                    b.call_stmt("returndatacopy", [zero(), zero(), b.call("returndatasize", [])]),
+                    b.call_stmt("revert", [zero(), b.call("returndatasize", [])]),
+                ],
+            ));
+        }
+        success
+    }
+
+    /// Lowers `array.push(value)` on a storage array.
+    fn lower_array_push(
+        &mut self,
+        expr: &hir::Expr<'_>,
+        base: &hir::Expr<'_>,
+        args: &hir::CallArgs<'_>,
+    ) -> (Vec<Value>, Ty<'gcx>) {
+        let b = self.b;
+        let base_ty = self.expr_ty(base);
+        let TyKind::DynArray(elem) = base_ty.peel_refs().kind else { unreachable!() };
+        let Some(value) = args.exprs().next() else {
+            return self.unsupported_expr(expr, "`push()` without arguments");
+        };
+        if !is_storage_ref(base_ty)
+            || !elem.is_value_type()
+            || self.gcx.type_storage_bytes(elem) <= 16
+        {
+            return self.unsupported_expr(expr, "`push` on this array");
+        }
+        let (slot, _) = self.lower_value(base);
+        let value = self.lower_value_to(value, elem);
+        let length = self.define(b.call_values("sload", &[slot]));
+        let new_length = b.call_values("add", &[length, Value::from_u64(1)]);
+        self.emit(b.call_stmt("sstore", [b.value(slot), new_length]));
+        let data = self.array_data_slot(slot);
+        let offset = self.elem_offset(elem, length);
+        let elem_slot = self.define(b.call("add", [b.value(data), offset]));
+        self.store(Place::Storage(elem_slot, 0, elem), value);
+        (Vec::new(), self.gcx.types.unit)
+    }
+
+    /// Lowers `array.pop()` on a storage array.
+    fn lower_array_pop(
+        &mut self,
+        expr: &hir::Expr<'_>,
+        base: &hir::Expr<'_>,
+    ) -> (Vec<Value>, Ty<'gcx>) {
+        let b = self.b;
+        let base_ty = self.expr_ty(base);
+        let TyKind::DynArray(elem) = base_ty.peel_refs().kind else { unreachable!() };
+        if !is_storage_ref(base_ty)
+            || !elem.is_value_type()
+            || self.gcx.type_storage_bytes(elem) <= 16
+        {
+            return self.unsupported_expr(expr, "`pop` on this array");
+        }
+        let (slot, _) = self.lower_value(base);
+        let length = self.define(b.call_values("sload", &[slot]));
+        self.panic_if(b.call_values("iszero", &[length]), Panic::PopEmptyArray);
+        let new_length = self.define(b.call_values("sub", &[length, Value::from_u64(1)]));
+        self.emit(b.call_stmt("sstore", [b.value(slot), b.value(new_length)]));
+        let data = self.array_data_slot(slot);
+        let offset = self.elem_offset(elem, new_length);
+        self.emit(b.call_stmt("sstore", [b.call("add", [b.value(data), offset]), b.num(0u64)]));
+        (Vec::new(), self.gcx.types.unit)
+    }
+}
@@ -0,0 +1,746 @@
+//! Lowering of the HIR to Yul IR.
+//!
+//! The IR follows the layout of the IR generated by `solc --ir`: a contract is lowered to an
+//! object `<name>_<id>` containing the creation code, with the runtime code in a child object
+//! `<name>_<id>_deployed`, followed by the contract metadata as a `.metadata` data section.
+//!
+//! Values are represented as in the [EVM lowering](crate::lower): one clean word per value, and
+//! storage references as their slot. Every intermediate result is bound to a fresh variable, as
+//! Yul evaluates function arguments from right to left while Solidity evaluates operands from
+//! left to right.
+//!
+//! The same subset of the language as the EVM lowering is supported.
+
+use crate::{
+    asm::Assembly,
+    lower::{FunctionLabels, Panic, FREE_MEMORY_POINTER, MEMORY_START, PANIC_SELECTOR},
+    opt::PassManager,
+    resolve::{is_abi_value_type, peel_udvt, Resolver},
+};
+use alloy_primitives::U256;
+use solar_ast::{yul, Arena, ElementaryType, StateMutability};
+use solar_data_structures::map::{FxHashMap, FxHashSet, FxIndexMap};
+use solar_interface::{diagnostics::ErrorGuaranteed, Span, Symbol};
+use solar_sema::{
+    hir, metadata,
+    ty::{Gcx, Ty, TyKind},
+};
+
+mod assemble;
+pub use assemble::assemble_object;
+
+mod builder;
+pub(crate) use builder::num_lit;
+use builder::{Builder, Value};
+
+mod expr;
+mod stmt;
+
+mod print;
+pub use print::print_object;
+
+/// Lowers the given contract to a Yul object, allocated in `arena`.
+///
+/// The contract must be deployable, i.e. a non-abstract contract or a library.
+#[instrument(level = "debug", skip_all)]
+pub fn lower_contract<'ast>(
+    arena: &'ast Arena,
+    gcx: Gcx<'_>,
+    id: hir::ContractId,
+) -> Result<yul::Object<'ast>, ErrorGuaranteed> {
+    let name = object_name(gcx, id);
+    let runtime_name = format!("{name}_deployed");
+
+    let mut runtime = ObjectCx::new(arena, gcx, id);
+    runtime.lower_runtime();
+    let runtime_code = runtime.finish();
+
+    let mut deploy = ObjectCx::new(arena, gcx, id);
+    deploy.lower_deploy(&name, &runtime_name);
+    let deploy_code = deploy.finish();
+    let (runtime_code, deploy_code) = (runtime_code?, deploy_code?);
+
+    let b = Builder::new(arena);
+    let metadata = metadata::contract_metadata(gcx, id);
+    let cbor = metadata::cbor_metadata(&metadata, gcx.sess.bytecode_hash);
+    let runtime =
+        b.object(&runtime_name, runtime_code, Vec::new(), vec![(".metadata", cbor.as_slice())]);
+    Ok(b.object(&name, deploy_code, vec![runtime], Vec::new()))
+}
+
+/// Lowers the given contract to Yul and returns its textual representation.
+///
+/// The IR is optimized with the default pipeline if the optimizer is enabled in the session. See
+/// [`lower_contract`].
+pub fn contract_ir(gcx: Gcx<'_>, id: hir::ContractId) -> Result<String, ErrorGuaranteed> {
+    let settings = &gcx.sess.optimizer;
+    if !settings.enabled {
+        let arena = Arena::new();
+        return Ok(print_object(&lower_contract(&arena, gcx, id)?));
+    }
+    let mut pm = PassManager::from_settings(settings).map_err(|msg| gcx.dcx().err(msg).emit())?;
+    contract_ir_with(gcx, id, &mut pm)
+}
+
+/// Lowers the given contract to Yul, optimizes it with the given pass manager, and returns its
+/// textual representation.
+pub fn contract_ir_with(
+    gcx: Gcx<'_>,
+    id: hir::ContractId,
+    pm: &mut PassManager,
+) -> Result<String, ErrorGuaranteed> {
+    let arena = Arena::new();
+    let mut object = lower_contract(&arena, gcx, id)?;
+    pm.run(&arena, &mut object);
+    Ok(print_object(&object))
+}
+
+/// Assembles the object of the given contract, generated by [`lower_contract`], into its creation
+/// code, also returning the entry labels of the functions in the runtime code.
+///
+/// Functions that were inlined by the optimizer have no entry label.
+pub(crate) fn assemble_contract(
+    gcx: Gcx<'_>,
+    id: hir::ContractId,
+    object: &yul::Object<'_>,
+) -> Result<(Assembly, FunctionLabels), ErrorGuaranteed> {
+    let [runtime] = &object.children[..] else {
+        unreachable!("contract object without a runtime object")
+    };
+    let (runtime, entries) = assemble::assemble(gcx.sess, runtime)?;
+    let (deploy, _) = assemble::assemble_with_children(gcx.sess, object, vec![runtime])?;
+
+    let entry = |name: String| entries.get(&Symbol::intern(&name)).copied();
+    let (receive, fallback) = receive_and_fallback(gcx, id);
+    let external = gcx
+        .interface_functions(id)
+        .all()
+        .iter()
+        .map(|f| f.id)
+        .chain(receive)
+        .chain(fallback)
+        .filter_map(|f| Some((f, entry(format!("external_{}", function_name(gcx, f)))?)))
+        .collect();
+    let mut internal = gcx
+        .hir
+        .item_ids()
+        .filter_map(|id| id.as_function())
+        .filter_map(|f| Some((f, entry(function_name(gcx, f))?)))
+        .collect::<Vec<_>>();
+    internal.sort_unstable_by_key(|&(_, label)| label);
+    Ok((deploy, FunctionLabels { external, internal }))
+}
+
+/// Returns the receive and fallback functions of the given contract, which may be inherited.
+fn receive_and_fallback(
+    gcx: Gcx<'_>,
+    id: hir::ContractId,
+) -> (Option<hir::FunctionId>, Option<hir::FunctionId>) {
+    let c = gcx.hir.contract(id);
+    let receive = c
+        .receive
+        .or_else(|| c.linearized_bases.iter().find_map(|&base| gcx.hir.contract(base).receive));
+    let fallback = c
+        .fallback
+        .or_else(|| c.linearized_bases.iter().find_map(|&base| gcx.hir.contract(base).fallback));
+    (receive, fallback)
+}
+
+/// Returns the name of the object of the given contract.
+fn object_name(gcx: Gcx<'_>, id: hir::ContractId) -> String {
+    format!("{}_{}", gcx.hir.contract(id).name, id.get())
+}
+
+/// Returns the name of the Yul function of the given function.
+fn function_name(gcx: Gcx<'_>, id: hir::FunctionId) -> String {
+    let f = gcx.hir.function(id);
+    match f.name {
+        Some(name) => format!("fun_{name}_{}", id.get()),
+        None => format!("fun_{}_{}", f.kind.to_str(), id.get()),
+    }
+}
+
+/// Returns the name of the Yul variable of the given variable.
+fn variable_name(gcx: Gcx<'_>, id: hir::VariableId) -> Symbol {
+    let name = gcx.hir.variable(id).name.map(|name| name.to_string()).unwrap_or_default();
+    Symbol::intern(&format!("var_{name}_{}", id.get()))
+}
+
+/// Returns the name of a value type used in helper function names, such as `t_uint256`.
+///
+/// Types with the same representation have the same name.
+fn type_name(ty: Ty<'_>) -> String {
+    match peel_udvt(ty).kind {
+        TyKind::Elementary(ElementaryType::Address(_)) | TyKind::Contract(_) => "t_address".into(),
+        TyKind::Elementary(ty) => format!("t_{}", ty.to_abi_str()),
+        TyKind::Enum(_) => "t_uint8".into(),
+        _ => "t_ref".into(),
+    }
+}
+
+/// The state of the lowering of a single Yul object.
+struct ObjectCx<'ast, 'gcx> {
+    gcx: Gcx<'gcx>,
+    /// The contract being compiled.
+    contract: hir::ContractId,
+    /// Builds helper function definitions, which have no span.
+    b: Builder<'ast>,
+    /// The storage slot and offset of each state variable.
+    storage: FxHashMap<hir::VariableId, (U256, u8)>,
+    /// The top-level code of the object.
+    code: Vec<yul::Stmt<'ast>>,
+    /// The functions referenced so far.
+    functions: FxHashSet<hir::FunctionId>,
+    /// The functions whose definition has not been generated yet.
+    queue: Vec<hir::FunctionId>,
+    /// The function definitions generated from the HIR.
+    definitions: Vec<yul::Stmt<'ast>>,
+    /// The helper function definitions, by name.
+    helpers: FxIndexMap<String, yul::Stmt<'ast>>,
+    /// The number of temporary variables created so far.
+    temporaries: usize,
+    /// Set if an error was emitted.
+    error: Option<ErrorGuaranteed>,
+}
+
+impl<'ast, 'gcx> ObjectCx<'ast, 'gcx> {
+    fn new(arena: &'ast Arena, gcx: Gcx<'gcx>, contract: hir::ContractId) -> Self {
+        let storage = gcx
+            .storage_layout(contract)
+            .items
+            .iter()
+            .map(|item| (item.id, (item.slot, item.offset)))
+            .collect();
+        Self {
+            gcx,
+            contract,
+            b: Builder::new(arena),
+            storage,
+            code: Vec::new(),
+            functions: FxHashSet::default(),
+            queue: Vec::new(),
+            definitions: Vec::new(),
+            helpers: FxIndexMap::default(),
+            temporaries: 0,
+            error: None,
+        }
+    }
+
+    fn err(&mut self, span: Span, msg: impl Into<String>) {
+        let msg = msg.into();
+        self.error = Some(self.gcx.dcx().err(msg).span(span).emit());
+    }
+
+    fn unsupported(&mut self, span: Span, what: &str) {
+        self.err(span, format!("{what} is not yet supported by the code generator"));
+    }
+
+    /// Returns the name of the Yul function of the given function, scheduling its definition to
+    /// be generated.
+    fn function(&mut self, id: hir::FunctionId) -> String {
+        if self.functions.insert(id) {
+            self.queue.push(id);
+        }
+        function_name(self.gcx, id)
+    }
+
+    /// Returns the name of a helper function, building its definition with `build` if it has not
+    /// been built yet.
+    fn helper(
+        &mut self,
+        name: String,
+        build: impl FnOnce(&mut Self, &str) -> yul::Stmt<'ast>,
+    ) -> String {
+        if !self.helpers.contains_key(&name) {
+            let def = build(self, &name);
+            self.helpers.insert(name.clone(), def);
+        }
+        name
+    }
+
+    /// Returns the name of the helper that panics with the given code.
+    fn panic_helper(&mut self, panic: Panic) -> String {
+        self.helper(format!("panic_error_{:#04x}", panic as u8), |cx, name| {
+            let b = cx.b;
+            b.function(
+                name,
+                &[],
+                &[],
+                vec![
+                    b.call_stmt("mstore", [b.num(0u64), b.num(U256::from(PANIC_SELECTOR) << 224)]),
+                    b.call_stmt("mstore", [b.num(4u64), b.num(panic as u64)]),
+                    b.call_stmt("revert", [b.num(0u64), b.num(0x24u64)]),
+                ],
+            )
+        })
+    }
+
+    fn fcx(&mut self, function: Option<hir::FunctionId>) -> FunctionCx<'_, 'ast, 'gcx> {
+        FunctionCx {
+            gcx: self.gcx,
+            resolver: Resolver::new(self.gcx, self.contract, function),
+            b: self.b,
+            cx: self,
+            stmts: Vec::new(),
+            vars: FxHashMap::default(),
+            placeholder: None,
+            unchecked: false,
+        }
+    }
+
+    /// Generates the referenced function definitions, and returns the code of the object.
+    fn finish(mut self) -> Result<Vec<yul::Stmt<'ast>>, ErrorGuaranteed> {
+        while let Some(id) = self.queue.pop() {
+            self.fcx(Some(id)).lower_function(id);
+        }
+        if let Some(guar) = self.error {
+            return Err(guar);
+        }
+        let mut code = self.code;
+        code.extend(self.definitions);
+        code.extend(self.helpers.into_values());
+        Ok(code)
+    }
+
+    fn lower_deploy(&mut self, name: &str, runtime_name: &str) {
+        let gcx = self.gcx;
+        let c = gcx.hir.contract(self.contract);
+        let mut fcx = self.fcx(None);
+        fcx.b.set_span(c.span);
+        let b = fcx.b;
+        fcx.init_memory();
+        let ctor = c.ctor.map(|id| gcx.hir.function(id));
+        if ctor.is_none_or(|f| f.state_mutability != StateMutability::Payable) {
+            fcx.check_callvalue();
+        }
+
+        // Constructor arguments are appended to the creation code.
+        let params = ctor.map(|f| f.parameters).unwrap_or_default();
+        let mut args = Vec::with_capacity(params.len());
+        if !params.is_empty() {
+            let program_size = fcx.define(b.call("datasize", [b.str(name)]));
+            let size = fcx.define(b.call("sub", [b.call("codesize", []), b.value(program_size)]));
+            fcx.emit(b.call_stmt(
+                "codecopy",
+                [b.num(MEMORY_START), b.value(program_size), b.value(size)],
+            ));
+            fcx.revert_if(b.call("lt", [b.value(size), b.num(32 * params.len() as u64)]));
+            fcx.emit(b.call_stmt(
+                "mstore",
+                [b.num(FREE_MEMORY_POINTER), b.call("add", [b.num(MEMORY_START), b.value(size)])],
+            ));
+            for (i, &param) in params.iter().enumerate() {
+                let ty = gcx.type_of_item(param.into());
+                let value = fcx.define(b.call("mload", [b.num(MEMORY_START + 32 * i as u64)]));
+                fcx.validate_abi_value(ty, value, gcx.hir.variable(param).span);
+                args.push(value);
+            }
+        }
+
+        // State variable initializers and constructors, from the most base contract.
+        for &base_id in c.linearized_bases.iter().rev() {
+            let base = gcx.hir.contract(base_id);
+            for var_id in base.variables() {
+                let var = gcx.hir.variable(var_id);
+                let Some(init) = var.initializer else { continue };
+                if var.is_constant() {
+                    continue;
+                }
+                if var.is_immutable() {
+                    fcx.cx.unsupported(var.span, "immutable variables");
+                    continue;
+                }
+                let prev = fcx.b.set_span(var.span);
+                let ty = gcx.type_of_item(var_id.into());
+                fcx.store_state_var(var_id, ty, init);
+                fcx.b.set_span(prev);
+            }
+
+            let Some(ctor_id) = base.ctor else { continue };
+            let ctor = gcx.hir.function(ctor_id);
+            if base_id != self.contract && !ctor.parameters.is_empty() {
+                fcx.cx.unsupported(ctor.span, "base constructor arguments");
+                continue;
+            }
+            let prev = fcx.b.set_span(ctor.span);
+            let f = fcx.cx.function(ctor_id);
+            let b = fcx.b;
+            fcx.emit(b.call_stmt(&f, args.iter().map(|&arg| b.value(arg))));
+            fcx.b.set_span(prev);
+        }
+
+        // Return the runtime code.
+        fcx.emit(b.call_stmt(
+            "codecopy",
+            [
+                b.num(0u64),
+                b.call("dataoffset", [b.str(runtime_name)]),
+                b.call("datasize", [b.str(runtime_name)]),
+            ],
+        ));
+        fcx.emit(b.call_stmt("return", [b.num(0u64), b.call("datasize", [b.str(runtime_name)])]));
+        self.code = std::mem::take(&mut fcx.stmts);
+    }
+
+    fn lower_runtime(&mut self) {
+        let gcx = self.gcx;
+        let c = gcx.hir.contract(self.contract);
+        let mut fcx = self.fcx(None);
+        fcx.b.set_span(c.span);
+        let b = fcx.b;
+        fcx.init_memory();
+
+        let functions = gcx.interface_functions(self.contract).all();
+        if !functions.is_empty() {
+            let cases = functions
+                .iter()
+                .map(|f| {
+                    let stub = fcx.cx.lower_external_stub(f.id);
+                    let selector = U256::from_be_slice(f.selector.as_slice());
+                    (selector, vec![b.call_stmt(&stub, [])])
+                })
+                .collect();
+            let dispatch = fcx.block(|fcx| {
+                let selector =
+                    fcx.define(fcx.shr_const(224, b.call("calldataload", [b.num(0u64)])));
+                fcx.emit(b.switch(b.value(selector), cases, Some(Vec::new())));
+            });
+            fcx.emit(b.if_(
+                b.call("iszero", [b.call("lt", [b.call("calldatasize", []), b.num(4u64)])]),
+                dispatch,
+            ));
+        }
+
+        let (receive, fallback) = receive_and_fallback(gcx, self.contract);
+        if let Some(receive) = receive {
+            let stub = fcx.cx.lower_external_stub(receive);
+            fcx.emit(
+                b.if_(b.call("iszero", [b.call("calldatasize", [])]), vec![b.call_stmt(&stub, [])]),
+            );
+        }
+        match fallback {
+            Some(fallback) => {
+                let stub = fcx.cx.lower_external_stub(fallback);
+                fcx.emit(b.call_stmt(&stub, []));
+            }
+            None => fcx.emit(b.call_stmt("revert", [b.num(0u64), b.num(0u64)])),
+        }
+        self.code = std::mem::take(&mut fcx.stmts);
+    }
+
+    /// Lowers the ABI entry point of the given function, returning its name.
+    fn lower_external_stub(&mut self, id: hir::FunctionId) -> String {
+        let gcx = self.gcx;
+        let f = gcx.hir.function(id);
+        let name = format!("external_{}", function_name(gcx, id));
+        let mut fcx = self.fcx(None);
+        fcx.b.set_span(f.span);
+        let b = fcx.b;
+        fcx.lower_external_stub_body(id);
+        let body = std::mem::take(&mut fcx.stmts);
+        let def = b.function(&name, &[], &[], body);
+        self.definitions.push(def);
+        name
+    }
+}
+
+/// The state of the lowering of a function body, or of other code in a Yul block, such as state
+/// variable initializers.
+struct FunctionCx<'a, 'ast, 'gcx> {
+    gcx: Gcx<'gcx>,
+    cx: &'a mut ObjectCx<'ast, 'gcx>,
+    /// Resolves expressions in the function being lowered, if any.
+    resolver: Resolver<'gcx>,
+    b: Builder<'ast>,
+    /// The statements of the current block.
+    stmts: Vec<yul::Stmt<'ast>>,
+    /// The Yul variable of each local variable, parameter and return variable in scope.
+    vars: FxHashMap<hir::VariableId, Symbol>,
+    /// The function called by `_` in the modifier being lowered.
+    placeholder: Option<String>,
+    /// Whether arithmetic is unchecked.
+    unchecked: bool,
+}
+
+impl<'gcx> std::ops::Deref for FunctionCx<'_, '_, 'gcx> {
+    type Target = Resolver<'gcx>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.resolver
+    }
+}
+
+impl<'ast, 'gcx> FunctionCx<'_, 'ast, 'gcx> {
+    // --- Emission helpers ---
+
+    /// Appends a statement to the current block.
+    fn emit(&mut self, stmt: yul::Stmt<'ast>) {
+        self.stmts.push(stmt);
+    }
+
+    /// Lowers the statements appended by `f` into a separate block.
+    fn block(&mut self, f: impl FnOnce(&mut Self)) -> Vec<yul::Stmt<'ast>> {
+        let prev = std::mem::take(&mut self.stmts);
+        f(self);
+        std::mem::replace(&mut self.stmts, prev)
+    }
+
+    /// Returns a new temporary variable name.
+    fn temporary(&mut self) -> Symbol {
+        self.cx.temporaries += 1;
+        Symbol::intern(&format!("_{}", self.cx.temporaries))
+    }
+
+    /// Binds the value of an expression to a new temporary variable.
+    fn define(&mut self, value: yul::Expr<'ast>) -> Value {
+        let name = self.temporary();
+        self.emit(self.b.let_(&[name], Some(value)));
+        Value::Var(name)
+    }
+
+    /// Binds the results of a call returning `n` values to new temporary variables.
+    fn define_multi(&mut self, n: usize, call: yul::Expr<'ast>) -> Vec<Value> {
+        match n {
+            0 => {
+                self.emit(self.b.expr_stmt(call));
+                Vec::new()
+            }
+            1 => vec![self.define(call)],
+            _ => {
+                let names = (0..n).map(|_| self.temporary()).collect::<Vec<_>>();
+                self.emit(self.b.let_(&names, Some(call)));
+                names.into_iter().map(Value::Var).collect()
+            }
+        }
+    }
+
+    /// Panics with the given code if `cond` is non-zero.
+    fn panic_if(&mut self, cond: yul::Expr<'ast>, panic: Panic) {
+        let f = self.cx.panic_helper(panic);
+        self.emit(self.b.if_(cond, vec![self.b.call_stmt(&f, [])]));
+    }
+
+    /// Reverts without data if `cond` is non-zero.
+    fn revert_if(&mut self, cond: yul::Expr<'ast>) {
+        let b = self.b;
+        self.emit(b.if_(cond, vec![b.call_stmt("revert", [b.num(0u64), b.num(0u64)])]));
+    }
+
+    /// Initializes the free memory pointer.
+    fn init_memory(&mut self) {
+        let b = self.b;
+        self.emit(b.call_stmt("mstore", [b.num(FREE_MEMORY_POINTER), b.num(MEMORY_START)]));
+    }
+
+    /// Reverts if the call has a value attached.
+    fn check_callvalue(&mut self) {
+        self.revert_if(self.b.call("callvalue", []));
+    }
+
+    /// Returns `value << bits`.
+    fn shl_const(&self, bits: u32, value: yul::Expr<'ast>) -> yul::Expr<'ast> {
+        let b = self.b;
+        if self.has_shifts() {
+            b.call("shl", [b.num(bits as u64), value])
+        } else {
+            b.call("mul", [value, b.num(U256::from(1) << bits)])
+        }
+    }
+
+    /// Returns `value >> bits`.
+    fn shr_const(&self, bits: u32, value: yul::Expr<'ast>) -> yul::Expr<'ast> {
+        let b = self.b;
+        if self.has_shifts() {
+            b.call("shr", [b.num(bits as u64), value])
+        } else {
+            b.call("div", [value, b.num(U256::from(1) << bits)])
+        }
+    }
+
+    fn has_shifts(&self) -> bool {
+        self.gcx.sess.evm_version.has_bitwise_shifting()
+    }
+
+    // --- Functions ---
+
+    /// Lowers the definition of an internal function.
+    ///
+    /// Functions with modifiers are lowered to one Yul function per modifier, each calling the
+    /// next one at `_`, with the function body in the last one. All of them have the signature of
+    /// the function, so that `return` in a modifier is `leave`.
+    fn lower_function(&mut self, id: hir::FunctionId) {
+        let gcx = self.gcx;
+        let f = gcx.hir.function(id);
+        self.b.set_span(f.span);
+        let b = self.b;
+        let name = function_name(gcx, id);
+
+        let params = f.parameters.iter().map(|&p| variable_name(gcx, p)).collect::<Vec<_>>();
+        let returns = f.returns.iter().map(|&r| variable_name(gcx, r)).collect::<Vec<_>>();
+        for (&var, &sym) in f.parameters.iter().zip(&params).chain(f.returns.iter().zip(&returns)) {
+            self.vars.insert(var, sym);
+        }
+        for &ret in f.returns {
+            let ty = gcx.type_of_item(ret.into());
+            if !ty.is_value_type() {
+                self.cx.unsupported(gcx.hir.variable(ret).span, "returning reference types");
+            }
+        }
+        let Some(body) = f.body else {
+            self.cx.unsupported(f.span, "calling functions without an implementation");
+            self.cx.definitions.push(b.function(&name, &params, &returns, Vec::new()));
+            return;
+        };
+
+        // Base constructors are called separately.
+        let modifiers = f
+            .modifiers
+            .iter()
+            .filter_map(|&m| match m {
+                hir::ItemId::Function(m) => Some(self.resolve_virtual(m)),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        let level_name = |level: usize| {
+            if level == 0 {
+                name.clone()
+            } else if level == modifiers.len() {
+                format!("{name}_inner")
+            } else {
+                format!("{name}_modifier_{level}")
+            }
+        };
+        for (level, &m) in modifiers.iter().enumerate() {
+            let modifier = gcx.hir.function(m);
+            let prev_span = self.b.set_span(modifier.span);
+            let next = level_name(level + 1);
+            let stmts = self.block(|fcx| {
+                if !modifier.parameters.is_empty() {
+                    fcx.cx.unsupported(modifier.span, "modifiers with parameters");
+                } else if let Some(body) = modifier.body {
+                    fcx.placeholder = Some(next.clone());
+                    fcx.lower_stmts(body);
+                    fcx.placeholder = None;
+                }
+            });
+            self.cx.definitions.push(b.function(&level_name(level), &params, &returns, stmts));
+            self.b.set_span(prev_span);
+        }
+        let stmts = self.block(|fcx| fcx.lower_stmts(body));
+        self.cx.definitions.push(b.function(
+            &level_name(modifiers.len()),
+            &params,
+            &returns,
+            stmts,
+        ));
+    }
+
+    /// Lowers a `_` placeholder statement in the current modifier.
+    fn lower_placeholder(&mut self) {
+        let next = self.placeholder.clone().expect("placeholder outside of modifier");
+        let gcx = self.gcx;
+        let f = gcx.hir.function(self.function.expect("placeholder outside of function"));
+        let b = self.b;
+        let args = f.parameters.iter().map(|&p| b.value(Value::Var(self.vars[&p])));
+        let call = b.call(&next, args.collect::<Vec<_>>());
+        if f.returns.is_empty() {
+            self.emit(b.expr_stmt(call));
+        } else {
+            let returns = f.returns.iter().map(|r| self.vars[r]).collect::<Vec<_>>();
+            self.emit(b.assign(&returns, call));
+        }
+    }
+
+    /// Lowers the body of the ABI entry point of the given function.
+    fn lower_external_stub_body(&mut self, id: hir::FunctionId) {
+        let gcx = self.gcx;
+        let b = self.b;
+        let f = gcx.hir.function(id);
+        if f.state_mutability != StateMutability::Payable {
+            self.check_callvalue();
+        }
+        if f.kind.is_fallback() && !f.parameters.is_empty() {
+            self.cx.unsupported(f.span, "fallback functions with parameters");
+            return;
+        }
+        for &param in f.parameters.iter().chain(f.returns) {
+            let ty = gcx.type_of_item(param.into());
+            if !is_abi_value_type(ty) {
+                self.cx
+                    .unsupported(gcx.hir.variable(param).span, "ABI encoding of reference types");
+                return;
+            }
+        }
+
+        if !f.parameters.is_empty() {
+            let size = 4 + 32 * f.parameters.len() as u64;
+            self.revert_if(b.call("lt", [b.call("calldatasize", []), b.num(size)]));
+        }
+        let mut args = Vec::with_capacity(f.parameters.len());
+        for (i, &param) in f.parameters.iter().enumerate() {
+            let ty = gcx.type_of_item(param.into());
+            let value = self.define(b.call("calldataload", [b.num(4 + 32 * i as u64)]));
+            self.validate_abi_value(ty, value, gcx.hir.variable(param).span);
+            args.push(value);
+        }
+        let fun = self.cx.function(id);
+        let rets = self.define_multi(f.returns.len(), b.call_values(&fun, &args));
+
+        if rets.is_empty() {
+            self.emit(b.call_stmt("return", [b.num(0u64), b.num(0u64)]));
+            return;
+        }
+        // Encode the return values at the free memory pointer.
+        let pos = self.define(b.call("mload", [b.num(FREE_MEMORY_POINTER)]));
+        for (i, &ret) in rets.iter().enumerate() {
+            let offset = b.call("add", [b.value(pos), b.num(32 * i as u64)]);
+            self.emit(b.call_stmt("mstore", [offset, b.value(ret)]));
+        }
+        self.emit(b.call_stmt("return", [b.value(pos), b.num(32 * rets.len() as u64)]));
+    }
+
+    // --- Values ---
+
+    /// Returns the cleaned value of `value`, i.e. with the unused bits zeroed or sign-extended.
+    fn cleanup(&self, ty: Ty<'gcx>, value: yul::Expr<'ast>) -> yul::Expr<'ast> {
+        let b = self.b;
+        match ty.kind {
+            TyKind::Elementary(ElementaryType::Bool) => {
+                b.call("iszero", [b.call("iszero", [value])])
+            }
+            TyKind::Elementary(ElementaryType::UInt(size)) if size.bits() < 256 => {
+                b.call("and", [value, b.num((U256::from(1) << size.bits()) - U256::from(1))])
+            }
+            TyKind::Elementary(ElementaryType::Int(size)) if size.bits() < 256 => {
+                b.call("signextend", [b.num(size.bytes() as u64 - 1), value])
+            }
+            TyKind::Elementary(ElementaryType::Address(_)) | TyKind::Contract(_) => {
+                b.call("and", [value, b.num((U256::from(1) << 160) - U256::from(1))])
+            }
+            TyKind::Elementary(ElementaryType::FixedBytes(size)) if size.bytes() < 32 => {
+                let mask = !((U256::from(1) << (256 - size.bits() as usize)) - U256::from(1));
+                b.call("and", [value, b.num(mask)])
+            }
+            TyKind::Udvt(inner, _) => self.cleanup(inner, value),
+            _ => value,
+        }
+    }
+
+    /// Reverts if the ABI-decoded value is not valid for the given type.
+    fn validate_abi_value(&mut self, ty: Ty<'gcx>, value: Value, span: Span) {
+        let b = self.b;
+        if !is_abi_value_type(ty) {
+            self.cx.unsupported(span, "ABI decoding of reference types");
+            return;
+        }
+        if let TyKind::Enum(id) = ty.kind {
+            let variants = self.gcx.hir.enumm(id).variants.len();
+            self.revert_if(
+                b.call("iszero", [b.call("lt", [b.value(value), b.num(variants as u64)])]),
+            );
+            return;
+        }
+        let cleaned = self.cleanup(ty, b.value(value));
+        self.revert_if(b.call("iszero", [b.call("eq", [b.value(value), cleaned])]));
+    }
+}
@@ -0,0 +1,199 @@
+//! Printing of Yul objects.
+
+use solar_ast::{
+    yul::{Block, Expr, ExprCall, ExprKind, Function, Object, Stmt, StmtKind},
+    Lit, LitKind, StrKind,
+};
+use std::fmt::Write;
+
+/// Returns the textual representation of a Yul object.
+pub fn print_object(object: &Object<'_>) -> String {
+    let mut p = Printer { out: String::new(), indent: 0 };
+    p.object(object);
+    p.out
+}
+
+struct Printer {
+    out: String,
+    indent: usize,
+}
+
+impl Printer {
+    fn line(&mut self) {
+        self.out.push('\n');
+        for _ in 0..self.indent {
+            self.out.push_str("    ");
+        }
+    }
+
+    fn object(&mut self, object: &Object<'_>) {
+        write!(self.out, "object \"{}\" {{", object.name.value).unwrap();
+        self.indent += 1;
+        self.line();
+        self.out.push_str("code ");
+        self.block(&object.code.code);
+        for child in object.children.iter() {
+            self.line();
+            self.object(child);
+        }
+        for data in object.data.iter() {
+            self.line();
+            write!(self.out, "data \"{}\" ", data.name.value).unwrap();
+            self.lit(data.data);
+        }
+        self.indent -= 1;
+        self.line();
+        self.out.push('}');
+    }
+
+    fn block(&mut self, block: &Block<'_>) {
+        if block.is_empty() {
+            self.out.push_str("{ }");
+            return;
+        }
+        self.out.push('{');
+        self.indent += 1;
+        for stmt in block.iter() {
+            self.line();
+            self.stmt(stmt);
+        }
+        self.indent -= 1;
+        self.line();
+        self.out.push('}');
+    }
+
+    fn stmt(&mut self, stmt: &Stmt<'_>) {
+        match &stmt.kind {
+            StmtKind::Block(block) => self.block(block),
+            StmtKind::AssignSingle(path, expr) => {
+                write!(self.out, "{path} := ").unwrap();
+                self.expr(expr);
+            }
+            StmtKind::AssignMulti(paths, call) => {
+                for (i, path) in paths.iter().enumerate() {
+                    if i > 0 {
+                        self.out.push_str(", ");
+                    }
+                    write!(self.out, "{path}").unwrap();
+                }
+                self.out.push_str(" := ");
+                self.call(call);
+            }
+            StmtKind::Expr(call) => self.call(call),
+            StmtKind::If(cond, body) => {
+                self.out.push_str("if ");
+                self.expr(cond);
+                self.out.push(' ');
+                self.block(body);
+            }
+            StmtKind::For { init, cond, step, body } => {
+                self.out.push_str("for ");
+                self.block(init);
+                self.out.push(' ');
+                self.expr(cond);
+                self.out.push(' ');
+                self.block(step);
+                self.out.push(' ');
+                self.block(body);
+            }
+            StmtKind::Switch(switch) => {
+                self.out.push_str("switch ");
+                self.expr(&switch.selector);
+                for case in switch.branches.iter() {
+                    self.line();
+                    self.out.push_str("case ");
+                    self.lit(case.constant);
+                    self.out.push(' ');
+                    self.block(&case.body);
+                }
+                if let Some(default) = &switch.default_case {
+                    self.line();
+                    self.out.push_str("default ");
+                    self.block(default);
+                }
+            }
+            StmtKind::Leave => self.out.push_str("leave"),
+            StmtKind::Break => self.out.push_str("break"),
+            StmtKind::Continue => self.out.push_str("continue"),
+            StmtKind::FunctionDef(f) => self.function(f),
+            StmtKind::VarDecl(names, value) => {
+                self.out.push_str("let ");
+                for (i, name) in names.iter().enumerate() {
+                    if i > 0 {
+                        self.out.push_str(", ");
+                    }
+                    write!(self.out, "{name}").unwrap();
+                }
+                if let Some(value) = value {
+                    self.out.push_str(" := ");
+                    self.expr(value);
+                }
+            }
+        }
+    }
+
+    fn function(&mut self, f: &Function<'_>) {
+        write!(self.out, "function {}(", f.name).unwrap();
+        for (i, param) in f.parameters.iter().enumerate() {
+            if i > 0 {
+                self.out.push_str(", ");
+            }
+            write!(self.out, "{param}").unwrap();
+        }
+        self.out.push(')');
+        if !f.returns.is_empty() {
+            self.out.push_str(" -> ");
+            for (i, ret) in f.returns.iter().enumerate() {
+                if i > 0 {
+                    self.out.push_str(", ");
+                }
+                write!(self.out, "{ret}").unwrap();
+            }
+        }
+        self.line();
+        self.block(&f.body);
+        self.line();
+    }
+
+    fn expr(&mut self, expr: &Expr<'_>) {
+        match &expr.kind {
+            ExprKind::Path(path) => write!(self.out, "{path}").unwrap(),
+            ExprKind::Call(call) => self.call(call),
+            ExprKind::Lit(lit) => self.lit(lit),
+        }
+    }
+
+    fn call(&mut self, call: &ExprCall<'_>) {
+        write!(self.out, "{}(", call.name).unwrap();
+        for (i, arg) in call.arguments.iter().enumerate() {
+            if i > 0 {
+                self.out.push_str(", ");
+            }
+            self.expr(arg);
+        }
+        self.out.push(')');
+    }
+
+    fn lit(&mut self, lit: &Lit) {
+        match &lit.kind {
+            LitKind::Str(StrKind::Hex, bytes) => {
+                write!(self.out, "hex\"{}\"", alloy_primitives::hex::encode(bytes)).unwrap()
+            }
+            LitKind::Str(_, bytes) => {
+                self.out.push('"');
+                for &byte in bytes.iter() {
+                    match byte {
+                        b'"' => self.out.push_str("\\\""),
+                        b'\\' => self.out.push_str("\\\\"),
+                        b'\n' => self.out.push_str("\\n"),
+                        0x20..=0x7e => self.out.push(byte as char),
+                        _ => write!(self.out, "\\x{byte:02x}").unwrap(),
+                    }
+                }
+                self.out.push('"');
+            }
+            LitKind::Bool(value) => write!(self.out, "{value}").unwrap(),
+            _ => write!(self.out, "{}", lit.symbol).unwrap(),
+        }
+    }
+}
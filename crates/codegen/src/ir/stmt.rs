@@ -0,0 +1,254 @@
+use super::{variable_name, FunctionCx, Value};
+use crate::{
+    lower::{ERROR_SELECTOR, FREE_MEMORY_POINTER},
+    resolve::is_abi_value_type,
+};
+use alloy_primitives::U256;
+use solar_ast::yul;
+use solar_interface::Span;
+use solar_sema::hir;
+
+impl<'ast> FunctionCx<'_, 'ast, '_> {
+    /// Lowers the statements of a block into the current Yul block.
+    pub(super) fn lower_stmts(&mut self, block: hir::Block<'_>) {
+        for stmt in block {
+            self.lower_stmt(stmt);
+        }
+    }
+
+    /// Lowers a block into a nested Yul block, which scopes the variables declared in it.
+    fn lower_block(&mut self, block: hir::Block<'_>) {
+        let stmts = self.block(|fcx| fcx.lower_stmts(block));
+        if !stmts.is_empty() {
+            let b = self.b;
+            self.emit(b.stmt(yul::StmtKind::Block(b.block(stmts))));
+        }
+    }
+
+    fn lower_stmt(&mut self, stmt: &hir::Stmt<'_>) {
+        let prev_span = self.b.set_span(stmt.span);
+        match stmt.kind {
+            hir::StmtKind::DeclSingle(id) => self.lower_decl(id),
+            hir::StmtKind::DeclMulti(vars, init) => {
+                let values = match init.peel_parens().kind {
+                    hir::ExprKind::Tuple(exprs) if exprs.len() == vars.len() => exprs
+                        .iter()
+                        .zip(vars)
+                        .map(|(expr, var)| {
+                            let expr = expr.expect("empty tuple component");
+                            match *var {
+                                Some(var) => {
+                                    let ty = self.gcx.type_of_item(var.into());
+                                    self.lower_value_to(expr, ty)
+                                }
+                                None => self.lower_value(expr).0,
+                            }
+                        })
+                        .collect(),
+                    _ => self.lower_expr(init).0,
+                };
+                for (var, value) in vars.iter().zip(values) {
+                    if let Some(var) = *var {
+                        self.declare(var, Some(value));
+                    }
+                }
+            }
+            hir::StmtKind::Block(block) => self.lower_block(block),
+            hir::StmtKind::UncheckedBlock(block) => {
+                let prev = std::mem::replace(&mut self.unchecked, true);
+                self.lower_block(block);
+                self.unchecked = prev;
+            }
+            hir::StmtKind::Emit(res, ref args) => self.lower_emit(res, args, stmt.span),
+            hir::StmtKind::Revert(res, ref args) => self.lower_revert_error(res, args, stmt.span),
+            hir::StmtKind::Return(expr) => self.lower_return(expr),
+            hir::StmtKind::Break => self.emit(self.b.stmt(yul::StmtKind::Break)),
+            hir::StmtKind::Continue => self.emit(self.b.stmt(yul::StmtKind::Continue)),
+            hir::StmtKind::Loop(block, _) => self.lower_loop(block),
+            hir::StmtKind::If(cond, then, else_) => {
+                let b = self.b;
+                let cond = self.lower_value_to(cond, self.gcx.types.bool);
+                let then = self.lower_scoped(then);
+                match else_ {
+                    Some(else_) => {
+                        let else_ = self.lower_scoped(else_);
+                        self.emit(b.if_else(b.value(cond), then, else_));
+                    }
+                    None => self.emit(b.if_(b.value(cond), then)),
+                }
+            }
+            hir::StmtKind::Try(_) => self.cx.unsupported(stmt.span, "`try` statements"),
+            hir::StmtKind::Expr(expr) => _ = self.lower_expr(expr),
+            hir::StmtKind::Placeholder => self.lower_placeholder(),
+            hir::StmtKind::Err(_) => {}
+        }
+        self.b.set_span(prev_span);
+    }
+
+    /// Lowers a statement that is the body of a control flow statement into a separate block.
+    fn lower_scoped(&mut self, stmt: &hir::Stmt<'_>) -> Vec<yul::Stmt<'ast>> {
+        self.block(|fcx| match stmt.kind {
+            hir::StmtKind::Block(block) => fcx.lower_stmts(block),
+            _ => fcx.lower_stmt(stmt),
+        })
+    }
+
+    fn lower_decl(&mut self, id: hir::VariableId) {
+        let var = self.gcx.hir.variable(id);
+        let ty = self.gcx.type_of_item(id.into());
+        let value = match var.initializer {
+            Some(init) => Some(self.lower_value_to(init, ty)),
+            None if ty.is_value_type() => None,
+            None => {
+                self.cx.unsupported(var.span, "uninitialized reference type variables");
+                None
+            }
+        };
+        self.declare(id, value);
+    }
+
+    /// Declares the Yul variable of a local variable, initialized to `value` or zero.
+    fn declare(&mut self, id: hir::VariableId, value: Option<Value>) {
+        let b = self.b;
+        let name = variable_name(self.gcx, id);
+        self.emit(b.let_(&[name], value.map(|value| b.value(value))));
+        self.vars.insert(id, name);
+    }
+
+    fn lower_return(&mut self, expr: Option<&hir::Expr<'_>>) {
+        let b = self.b;
+        let f = self.gcx.hir.function(self.function.expect("`return` outside of function"));
+        if let Some(expr) = expr {
+            let ret_tys =
+                f.returns.iter().map(|&ret| self.gcx.type_of_item(ret.into())).collect::<Vec<_>>();
+            let values = match (&expr.peel_parens().kind, &ret_tys[..]) {
+                (_, [ty]) => vec![self.lower_value_to(expr, *ty)],
+                (hir::ExprKind::Tuple(exprs), _) if exprs.len() == ret_tys.len() => exprs
+                    .iter()
+                    .zip(&ret_tys)
+                    .map(|(expr, &ty)| {
+                        self.lower_value_to(expr.expect("empty tuple component"), ty)
+                    })
+                    .collect(),
+                _ => self.lower_expr(expr).0,
+            };
+            for (ret, value) in f.returns.iter().zip(values) {
+                self.emit(b.assign(&[self.vars[ret]], b.value(value)));
+            }
+        }
+        self.emit(b.stmt(yul::StmtKind::Leave));
+    }
+
+    /// Lowers a loop to `for {} 1 { next } { body }`.
+    ///
+    /// If the body has more than one statement, the last one is the continuation, which is where
+    /// `continue` jumps to. See [`hir::StmtKind::Loop`].
+    fn lower_loop(&mut self, block: hir::Block<'_>) {
+        let b = self.b;
+        let (body, next) = match block {
+            [body @ .., next] if !body.is_empty() => {
+                (self.block(|fcx| fcx.lower_stmts(body)), self.block(|fcx| fcx.lower_stmt(next)))
+            }
+            _ => (self.block(|fcx| fcx.lower_stmts(block)), Vec::new()),
+        };
+        self.emit(b.for_(b.num(1u64), next, body));
+    }
+
+    /// Lowers `emit Event(args)`.
+    fn lower_emit(&mut self, res: &[hir::Res], args: &hir::CallArgs<'_>, span: Span) {
+        let Some(id) = self.select_overload(res, args, |id| match id {
+            hir::ItemId::Event(id) => Some(id),
+            _ => None,
+        }) else {
+            self.cx.unsupported(span, "this event");
+            return;
+        };
+        let b = self.b;
+        let event = self.gcx.hir.event(id);
+        let params = self.ordered_args(event.parameters, args);
+
+        // The non-indexed arguments are ABI-encoded in scratch memory.
+        let mut data = Vec::new();
+        let mut topics = Vec::new();
+        if !event.anonymous {
+            topics.push(b.num(U256::from_be_bytes(self.gcx.event_selector(id).0)));
+        }
+        for (&param, arg) in event.parameters.iter().zip(&params) {
+            let ty = self.gcx.type_of_item(param.into());
+            if !is_abi_value_type(ty) {
+                self.cx.unsupported(arg.span, "events with reference type parameters");
+                return;
+            }
+            let value = self.lower_value_to(arg, ty);
+            if self.gcx.hir.variable(param).indexed {
+                topics.push(b.value(value));
+            } else {
+                data.push(value);
+            }
+        }
+        let ptr = self.define(b.call("mload", [b.num(FREE_MEMORY_POINTER)]));
+        for (i, &value) in data.iter().enumerate() {
+            let offset = b.call("add", [b.value(ptr), b.num(32 * i as u64)]);
+            self.emit(b.call_stmt("mstore", [offset, b.value(value)]));
+        }
+        let log = format!("log{}", topics.len());
+        let args = [b.value(ptr), b.num(32 * data.len() as u64)].into_iter().chain(topics);
+        self.emit(b.call_stmt(&log, args));
+    }
+
+    /// Lowers `revert Error(args)`.
+    fn lower_revert_error(&mut self, res: &[hir::Res], args: &hir::CallArgs<'_>, span: Span) {
+        let Some(id) = self.select_overload(res, args, |id| match id {
+            hir::ItemId::Error(id) => Some(id),
+            _ => None,
+        }) else {
+            self.cx.unsupported(span, "this error");
+            return;
+        };
+        let b = self.b;
+        let error = self.gcx.hir.error(id);
+        let params = self.ordered_args(error.parameters, args);
+        let mut values = Vec::with_capacity(params.len());
+        for (&param, arg) in error.parameters.iter().zip(&params) {
+            let ty = self.gcx.type_of_item(param.into());
+            if !is_abi_value_type(ty) {
+                self.cx.unsupported(arg.span, "errors with reference type parameters");
+                return;
+            }
+            values.push(self.lower_value_to(arg, ty));
+        }
+        let selector = self.gcx.function_selector(id);
+        let ptr = self.define(b.call("mload", [b.num(FREE_MEMORY_POINTER)]));
+        let selector = U256::from_be_slice(selector.as_slice()) << 224;
+        self.emit(b.call_stmt("mstore", [b.value(ptr), b.num(selector)]));
+        for (i, &value) in values.iter().enumerate() {
+            let offset = b.call("add", [b.value(ptr), b.num(4 + 32 * i as u64)]);
+            self.emit(b.call_stmt("mstore", [offset, b.value(value)]));
+        }
+        self.emit(b.call_stmt("revert", [b.value(ptr), b.num(4 + 32 * values.len() as u64)]));
+    }
+
+    /// Reverts with `Error(message)`, or without data if `message` is `None`.
+    pub(super) fn lower_revert_message(&mut self, message: Option<&[u8]>) {
+        let b = self.b;
+        let Some(message) = message else {
+            self.emit(b.call_stmt("revert", [b.num(0u64), b.num(0u64)]));
+            return;
+        };
+        let ptr = self.define(b.call("mload", [b.num(FREE_MEMORY_POINTER)]));
+        let store = |fcx: &mut Self, offset: u64, value: U256| {
+            let offset = b.call("add", [b.value(ptr), b.num(offset)]);
+            fcx.emit(b.call_stmt("mstore", [offset, b.num(value)]));
+        };
+        store(self, 0, U256::from(ERROR_SELECTOR) << 224);
+        store(self, 4, U256::from(0x20));
+        store(self, 0x24, U256::from(message.len()));
+        for (i, chunk) in message.chunks(32).enumerate() {
+            let mut word = [0u8; 32];
+            word[..chunk.len()].copy_from_slice(chunk);
+            store(self, 0x44 + 32 * i as u64, U256::from_be_bytes(word));
+        }
+        let size = 0x44 + 32 * message.len().div_ceil(32) as u64;
+        self.emit(b.call_stmt("revert", [b.value(ptr), b.num(size)]));
+    }
+}
@@ -0,0 +1,399 @@
+#![doc = include_str!("../README.md")]
+#![doc(
+    html_logo_url = "https://raw.githubusercontent.com/paradigmxyz/solar/main/assets/logo.png",
+    html_favicon_url = "https://raw.githubusercontent.com/paradigmxyz/solar/main/assets/favicon.ico"
+)]
+#![cfg_attr(docsrs, feature(doc_cfg, doc_auto_cfg))]
+
+#[macro_use]
+extern crate tracing;
+
+use solar_ast::Arena;
+use solar_interface::{diagnostics::ErrorGuaranteed, source_map::SourceFile};
+use solar_sema::{hir, link::Bytecode, metadata, ty::Gcx, Codegen, ContractBytecode, GasEstimates};
+
+pub mod asm;
+use asm::{AssembleError, Assembly};
+use opt::PassManager;
+
+pub mod eof;
+
+pub mod gas;
+
+pub mod ir;
+
+#[cfg(test)]
+mod interp;
+
+mod lower;
+
+mod opcode;
+pub use opcode::Opcode;
+
+pub mod opt;
+
+mod resolve;
+
+mod size;
+
+/// The generated code of a contract.
+///
+/// Return type of [`compile_contract`].
+#[derive(Clone, Debug)]
+pub struct ContractCode {
+    deploy: Assembly,
+    functions: lower::FunctionLabels,
+}
+
+impl ContractCode {
+    /// Returns the assembly of the creation code. The runtime code is its first sub-assembly.
+    pub fn deploy(&self) -> &Assembly {
+        &self.deploy
+    }
+
+    /// Returns the assembly of the runtime code.
+    pub fn runtime(&self) -> &Assembly {
+        &self.deploy.subs()[0]
+    }
+
+    /// Assembles the creation bytecode, with unlinked library references.
+    pub fn bytecode(&self) -> Result<Bytecode, AssembleError> {
+        let (code, references) = self.deploy.assemble_linkable()?;
+        Ok(Bytecode::new(code, references))
+    }
+
+    /// Assembles the runtime bytecode, with unlinked library references.
+    pub fn runtime_bytecode(&self) -> Result<Bytecode, AssembleError> {
+        let (code, references) = self.runtime().assemble_linkable()?;
+        Ok(Bytecode::new(code, references))
+    }
+}
+
+/// Generates the creation and runtime code of the given contract, targeting the session's EVM
+/// version.
+///
+/// The runtime code is suffixed with the CBOR-encoded contract metadata, as configured by the
+/// session's `bytecode_hash`.
+///
+/// The code is generated by assembling the [Yul IR](ir) if the session's `via_ir` is set or the
+/// optimizer is enabled, in which case the IR is optimized first, and by lowering the HIR to EVM
+/// assembly directly otherwise.
+///
+/// Generating [EOF](eof) containers is unstable, and is an error unless the session's `eof` is set.
+///
+/// Generating code with the session's `legacy_syntax` set is an error, since the types of legacy
+/// `var` declarations are not inferred.
+///
+/// The contract must be deployable, i.e. a non-abstract contract or a library.
+#[instrument(level = "debug", skip_all)]
+pub fn compile_contract(
+    gcx: Gcx<'_>,
+    id: hir::ContractId,
+) -> Result<ContractCode, ErrorGuaranteed> {
+    let contract = gcx.hir.contract(id);
+    assert!(
+        contract.can_be_deployed() && !contract.is_abstract(),
+        "{} cannot be deployed",
+        contract.name
+    );
+    if gcx.sess.legacy_syntax {
+        let msg = "cannot generate code with legacy syntax enabled";
+        return Err(gcx.dcx().err(msg).span(contract.name.span).emit());
+    }
+    if gcx.sess.evm_version.supports_eof() && !gcx.sess.eof {
+        let msg = format!(
+            "generating EOF code for the `{}` EVM version is unstable",
+            gcx.sess.evm_version
+        );
+        let help = "pass `-Zeof` to enable it, or target an earlier EVM version";
+        return Err(gcx.dcx().err(msg).span(contract.name.span).help(help).emit());
+    }
+
+    if gcx.sess.codegen_via_ir() {
+        let arena = Arena::new();
+        let mut object = ir::lower_contract(&arena, gcx, id)?;
+        let settings = &gcx.sess.optimizer;
+        if settings.enabled {
+            let mut pm =
+                PassManager::from_settings(settings).map_err(|msg| gcx.dcx().err(msg).emit())?;
+            pm.run(&arena, &mut object);
+        }
+        let (deploy, functions) = ir::assemble_contract(gcx, id, &object)?;
+        return Ok(ContractCode { deploy, functions });
+    }
+
+    let (mut runtime, functions) = lower::lower_runtime(gcx, id)?;
+    let metadata = metadata::contract_metadata(gcx, id);
+    runtime.set_aux_data(metadata::cbor_metadata(&metadata, gcx.sess.bytecode_hash));
+    let deploy = lower::lower_deploy(gcx, id, runtime)?;
+    Ok(ContractCode { deploy, functions })
+}
+
+/// The code generation backend, to be used with
+/// [`ParsingContext::codegen`](solar_sema::ParsingContext::codegen).
+pub const BACKEND: Codegen = Codegen { bytecode: codegen, ir, asm, gas_estimates };
+
+/// Generates the creation and runtime bytecode of the given contract, emitting an error if it
+/// fails, and a warning if it exceeds the code size limits.
+///
+/// This is the [`Codegen::bytecode`] function of [`BACKEND`].
+pub fn codegen(gcx: Gcx<'_>, id: hir::ContractId) -> Option<ContractBytecode> {
+    let code = compile_contract(gcx, id).ok()?;
+    let sources = gcx.source_list();
+    let source_index = |file: &SourceFile| {
+        sources.iter().position(|&id| gcx.hir.source(id).file.stable_id == file.stable_id)
+    };
+    let source_map = gcx.sess.source_map();
+    let assemble = || {
+        Ok(ContractBytecode {
+            bytecode: code.bytecode()?,
+            runtime_bytecode: code.runtime_bytecode()?,
+            source_map: code.deploy().source_mapping(source_map, source_index),
+            runtime_source_map: code.runtime().source_mapping(source_map, source_index),
+        })
+    };
+    let bytecode = assemble()
+        .map_err(|e: AssembleError| {
+            let msg = format!("failed to assemble contract `{}`: {e}", gcx.hir.contract(id).name);
+            gcx.dcx().err(msg).span(gcx.hir.contract(id).name.span).emit()
+        })
+        .ok()?;
+    size::check_code_size(gcx, id, &code, &bytecode);
+    Some(bytecode)
+}
+
+/// Generates the Yul IR of the given contract. Errors are emitted to the session.
+///
+/// This is the [`Codegen::ir`] function of [`BACKEND`].
+pub fn ir(gcx: Gcx<'_>, id: hir::ContractId) -> Option<String> {
+    ir::contract_ir(gcx, id).ok()
+}
+
+/// Generates the human-readable EVM assembly of the given contract, with source location
+/// comments. Errors are emitted to the session.
+///
+/// This is the [`Codegen::asm`] function of [`BACKEND`].
+pub fn asm(gcx: Gcx<'_>, id: hir::ContractId) -> Option<String> {
+    let code = compile_contract(gcx, id).ok()?;
+    Some(code.deploy().to_text(gcx.sess.source_map()))
+}
+
+/// Returns the gas estimates of the given contract. Errors are emitted to the session.
+///
+/// This is the [`Codegen::gas_estimates`] function of [`BACKEND`].
+pub fn gas_estimates(gcx: Gcx<'_>, id: hir::ContractId) -> Option<GasEstimates> {
+    let code = compile_contract(gcx, id).ok()?;
+    Some(gas::contract_gas_estimates(gcx, &code))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::interp::{Evm, Output, CALLER_ADDRESS};
+    use alloy_primitives::{keccak256, U256};
+    use solar_config::{BytecodeHash, OptimizerSettings};
+    use solar_interface::{source_map::FileName, ColorChoice, Session, SessionBuilder};
+    use solar_sema::ParsingContext;
+
+    const SRC: &str = "
+contract Counter {
+    uint256 public count = 1;
+    mapping(address => uint256) public balances;
+
+    event Incremented(address indexed by, uint256 count);
+
+    function inc(uint256 by) external returns (uint256) {
+        count = add(count, by);
+        balances[msg.sender] += by;
+        emit Incremented(msg.sender, count);
+        return count;
+    }
+
+    function add(uint256 a, uint256 b) internal pure returns (uint256) {
+        return a + b;
+    }
+
+    function sumOdd(uint256 n) external pure returns (uint256 sum) {
+        for (uint256 i; i < n; i++) {
+            if (i % 2 == 0) continue;
+            sum += i;
+        }
+    }
+
+    function div(int256 a, int256 b) external pure returns (int256) {
+        return a / b;
+    }
+
+    function units() external pure returns (uint256, uint256) {
+        return (3 gwei, 2 days);
+    }
+
+    function check(uint256 x) external pure {
+        require(x != 0, \"zero\");
+    }
+}
+";
+
+    /// Returns a session generating code through the IR if `via_ir` is set, optimized if
+    /// `optimize` is set.
+    fn session(via_ir: bool, optimize: bool) -> SessionBuilder {
+        let optimizer = OptimizerSettings { enabled: optimize, ..Default::default() };
+        Session::builder().with_test_emitter().via_ir(via_ir).optimizer(optimizer)
+    }
+
+    /// Compiles the last contract in `src` and returns its creation code.
+    fn compile(sess: SessionBuilder, src: &str) -> Vec<u8> {
+        let sess = sess.build();
+        sess.enter(|| {
+            let mut pcx = ParsingContext::new(&sess);
+            let name = FileName::Custom("test.sol".into());
+            let file = sess.source_map().new_source_file(name, || Ok(src.into())).unwrap();
+            pcx.add_file(file);
+            pcx.parse_and_resolve_with(|gcx| {
+                let id = gcx.hir.contract_ids().last().unwrap();
+                compile_contract(gcx, id).unwrap().bytecode().unwrap().code
+            })
+            .unwrap()
+            .unwrap()
+        })
+    }
+
+    /// Deploys the last contract in `src`, compiled without the IR, and through the IR with and
+    /// without the optimizer.
+    fn deploy(src: &str) -> [Evm; 3] {
+        [(false, false), (true, false), (false, true)]
+            .map(|(via_ir, optimize)| Evm::deploy(&compile(session(via_ir, optimize), src)))
+    }
+
+    fn call(evm: &mut Evm, signature: &str, args: &[U256]) -> Output {
+        let mut calldata = keccak256(signature)[..4].to_vec();
+        for arg in args {
+            calldata.extend_from_slice(&arg.to_be_bytes::<32>());
+        }
+        evm.call(&calldata, U256::ZERO)
+    }
+
+    /// Returns the ABI-encoded words of a successful call.
+    #[track_caller]
+    fn words(output: Output) -> Vec<U256> {
+        assert!(output.success, "call failed: {output:?}");
+        output.data.chunks(32).map(U256::from_be_slice).collect()
+    }
+
+    #[track_caller]
+    fn assert_panic(output: Output, code: u64) {
+        assert!(!output.success);
+        assert_eq!(output.data[..4], lower::PANIC_SELECTOR.to_be_bytes());
+        assert_eq!(U256::from_be_slice(&output.data[4..]), U256::from(code));
+    }
+
+    fn int(x: i64) -> U256 {
+        let abs = U256::from(x.unsigned_abs());
+        if x < 0 {
+            U256::ZERO.wrapping_sub(abs)
+        } else {
+            abs
+        }
+    }
+
+    #[test]
+    fn storage_and_events() {
+        for mut evm in deploy(SRC) {
+            assert_eq!(words(call(&mut evm, "count()", &[])), [U256::from(1)]);
+            assert_eq!(words(call(&mut evm, "inc(uint256)", &[U256::from(41)])), [U256::from(42)]);
+            assert_eq!(words(call(&mut evm, "count()", &[])), [U256::from(42)]);
+            assert_eq!(
+                words(call(&mut evm, "balances(address)", &[CALLER_ADDRESS])),
+                [U256::from(41)]
+            );
+
+            let [log] = &evm.logs[..] else { panic!("{:?}", evm.logs) };
+            let topic = U256::from_be_bytes(keccak256("Incremented(address,uint256)").0);
+            assert_eq!(log.topics, [topic, CALLER_ADDRESS]);
+            assert_eq!(log.data, U256::from(42).to_be_bytes::<32>());
+        }
+    }
+
+    #[test]
+    fn checked_arithmetic() {
+        for mut evm in deploy(SRC) {
+            assert_panic(
+                call(&mut evm, "inc(uint256)", &[U256::MAX]),
+                lower::Panic::Arithmetic as u64,
+            );
+            // The failed call doesn't change the state.
+            assert_eq!(words(call(&mut evm, "count()", &[])), [U256::from(1)]);
+            assert!(evm.logs.is_empty());
+
+            assert_eq!(words(call(&mut evm, "div(int256,int256)", &[int(-7), int(2)])), [int(-3)]);
+            assert_panic(
+                call(&mut evm, "div(int256,int256)", &[int(1), int(0)]),
+                lower::Panic::DivisionByZero as u64,
+            );
+        }
+    }
+
+    #[test]
+    fn loops() {
+        for mut evm in deploy(SRC) {
+            let sum_odd =
+                |evm: &mut Evm, n: u64| words(call(evm, "sumOdd(uint256)", &[U256::from(n)]));
+            assert_eq!(sum_odd(&mut evm, 0), [U256::ZERO]);
+            assert_eq!(sum_odd(&mut evm, 10), [U256::from(25)]);
+        }
+    }
+
+    #[test]
+    fn sub_denominations() {
+        for mut evm in deploy(SRC) {
+            let units = words(call(&mut evm, "units()", &[]));
+            assert_eq!(units, [U256::from(3_000_000_000u64), U256::from(2 * 86400)]);
+        }
+    }
+
+    #[test]
+    fn legacy_syntax() {
+        let sess =
+            Session::builder().with_buffer_emitter(ColorChoice::Never).legacy_syntax(true).build();
+        sess.enter(|| {
+            let mut pcx = ParsingContext::new(&sess);
+            let name = FileName::Custom("legacy.sol".into());
+            let src = "contract C { function f() public { var x = 1; } }";
+            pcx.add_file(sess.source_map().new_source_file(name, || Ok(src.into())).unwrap());
+            pcx.parse_and_resolve_with(|gcx| {
+                let id = gcx.hir.contract_ids().last().unwrap();
+                assert!(compile_contract(gcx, id).is_err());
+            })
+            .unwrap();
+        });
+        let emitted = sess.dcx.emitted_diagnostics().unwrap().to_string();
+        assert!(emitted.contains("cannot generate code with legacy syntax enabled"), "{emitted}");
+    }
+
+    #[test]
+    fn optimizer() {
+        // Enabling the optimizer implies generating code through the IR.
+        let hash = |sess: SessionBuilder| sess.bytecode_hash(BytecodeHash::None);
+        let unoptimized = compile(hash(session(true, false)), SRC);
+        let optimized = compile(hash(session(false, true)), SRC);
+        assert_ne!(optimized, unoptimized);
+    }
+
+    #[test]
+    fn reverts() {
+        for mut evm in deploy(SRC) {
+            assert!(words(call(&mut evm, "check(uint256)", &[U256::from(1)])).is_empty());
+
+            let output = call(&mut evm, "check(uint256)", &[U256::ZERO]);
+            assert!(!output.success);
+            assert_eq!(output.data[..4], lower::ERROR_SELECTOR.to_be_bytes());
+
+            // Non-payable functions reject value, and unknown selectors revert without a fallback.
+            let calldata = keccak256("count()")[..4].to_vec();
+            assert_eq!(evm.call(&calldata, U256::from(1)), Output { success: false, data: vec![] });
+            assert!(!call(&mut evm, "unknown()", &[]).success);
+            // Missing arguments.
+            assert!(!evm.call(&keccak256("inc(uint256)")[..4], U256::ZERO).success);
+        }
+    }
+}
@@ -0,0 +1,1543 @@
+use super::{FunctionCx, Panic};
+use crate::{
+    resolve::{
+        builtin_member, int_info, is_abi_value_type, is_memory_ref, is_storage_ref, peel_udvt,
+        stack_slots, string_literal, Callee,
+    },
+    Opcode,
+};
+use alloy_primitives::{keccak256, U256};
+use solar_ast::{BinOpKind, ElementaryType, LitKind, UnOpKind};
+use solar_interface::{Ident, Span};
+use solar_sema::{
+    builtins::Builtin,
+    eval::ConstantEvaluator,
+    hir,
+    ty::{Ty, TyKind},
+};
+use std::borrow::Cow;
+
+/// An assignable location.
+#[derive(Clone, Copy, Debug)]
+enum Place<'gcx> {
+    /// A local variable at the given stack position.
+    Stack(usize),
+    /// A value at the given byte offset in the storage slot on top of the stack.
+    Storage(u8, Ty<'gcx>),
+    /// An unsupported place, for which an error has already been emitted. Loads push zero and
+    /// stores discard the value.
+    Err,
+}
+
+impl<'gcx> FunctionCx<'_, 'gcx> {
+    /// Lowers an expression, pushing its value, or one value per component for tuples.
+    pub(super) fn lower_expr(&mut self, expr: &hir::Expr<'_>) -> Ty<'gcx> {
+        let prev_span = self.cx.asm.set_span(expr.span);
+        let height = self.height;
+        let ty = self.lower_expr_kind(expr);
+        debug_assert_eq!(self.height, height + stack_slots(ty), "{expr:?}");
+        self.cx.asm.set_span(prev_span);
+        ty
+    }
+
+    /// Lowers an expression that is implicitly converted to `ty`.
+    ///
+    /// Only literals need to be converted explicitly: all other values are clean, and implicit
+    /// conversions between value types don't change their representation.
+    pub(super) fn lower_expr_to(&mut self, expr: &hir::Expr<'_>, ty: Ty<'gcx>) {
+        let prev_span = self.cx.asm.set_span(expr.span);
+        let peeled = expr.peel_parens();
+        if let Some(value) = self.literal_value(peeled, ty) {
+            self.push(value);
+        } else if let hir::ExprKind::Ternary(cond, t, f) = peeled.kind {
+            self.lower_ternary(cond, t, f, ty);
+        } else if is_memory_ref(ty) && is_storage_ref(self.expr_ty(expr)) {
+            self.unsupported_expr(expr, "copying storage values to memory");
+        } else {
+            self.lower_expr(expr);
+        }
+        self.cx.asm.set_span(prev_span);
+    }
+
+    fn lower_expr_kind(&mut self, expr: &hir::Expr<'_>) -> Ty<'gcx> {
+        if self.is_literal(expr) {
+            let ty = self.expr_ty(expr);
+            let value = self.literal_value(expr, ty).unwrap();
+            self.push(value);
+            return ty;
+        }
+        match expr.kind {
+            hir::ExprKind::Array(_) => self.unsupported_expr(expr, "array literals"),
+            hir::ExprKind::Assign(lhs, op, rhs) => self.lower_assign(lhs, op, rhs),
+            hir::ExprKind::Binary(l, op, r) => self.lower_binary(l, op.kind, r),
+            hir::ExprKind::Call(callee, ref args) => self.lower_call(expr, callee, args),
+            hir::ExprKind::CallOptions(..) => self.unsupported_expr(expr, "external calls"),
+            hir::ExprKind::Delete(x) => {
+                let (place, ty) = self.lower_place(x);
+                if ty.is_value_type() {
+                    self.push(0u64);
+                    self.store(place, ty);
+                } else {
+                    self.discard_place(place);
+                    self.cx.unsupported(expr.span, "deleting reference types");
+                }
+                self.gcx.types.unit
+            }
+            hir::ExprKind::Ident(res) => self.lower_ident(expr, res),
+            hir::ExprKind::Index(base, Some(index)) => {
+                let base_ty = self.expr_ty(base);
+                if let TyKind::Elementary(ElementaryType::FixedBytes(size)) = base_ty.kind {
+                    self.lower_expr(base);
+                    self.lower_expr_to(index, self.gcx.types.uint(256));
+                    self.push(size.bytes() as u64);
+                    self.dup(2);
+                    self.op(Opcode::LT);
+                    self.op(Opcode::ISZERO);
+                    self.panic_if(Panic::IndexOutOfBounds);
+                    self.op(Opcode::BYTE);
+                    self.shl_const(248);
+                    return self.gcx.types.fixed_bytes(1);
+                }
+                let (place, ty) = self.lower_place(expr);
+                self.load(place, ty);
+                ty
+            }
+            hir::ExprKind::Index(_, None) | hir::ExprKind::Slice(..) => {
+                self.unsupported_expr(expr, "slices")
+            }
+            hir::ExprKind::Lit(lit) => self.lower_lit(expr, lit),
+            hir::ExprKind::Member(base, name) => self.lower_member(expr, base, name),
+            hir::ExprKind::New(_) => self.unsupported_expr(expr, "`new` expressions"),
+            hir::ExprKind::Payable(x) => {
+                self.lower_expr(x);
+                self.gcx.types.address_payable
+            }
+            hir::ExprKind::Ternary(cond, t, f) => {
+                let ty = self.common_ty(t, f);
+                self.lower_ternary(cond, t, f, ty);
+                ty
+            }
+            hir::ExprKind::Tuple(&[Some(x)]) => self.lower_expr(x),
+            hir::ExprKind::Tuple(exprs) => {
+                if exprs.iter().any(Option::is_none) {
+                    return self.unsupported_expr(expr, "tuples with empty components");
+                }
+                let tys = exprs.iter().map(|x| self.lower_expr(x.unwrap())).collect::<Vec<_>>();
+                self.gcx.mk_ty(TyKind::Tuple(self.gcx.mk_tys(&tys)))
+            }
+            hir::ExprKind::TypeCall(_) | hir::ExprKind::Type(_) => {
+                self.unsupported_expr(expr, "types as values")
+            }
+            hir::ExprKind::Unary(op, x) => self.lower_unary(op.kind, x),
+            hir::ExprKind::Err(_) => self.gcx.types.unit,
+        }
+    }
+
+    /// Emits an "unsupported" error and pushes zeros in place of the expression's value.
+    fn unsupported_expr(&mut self, expr: &hir::Expr<'_>, what: &str) -> Ty<'gcx> {
+        self.cx.unsupported(expr.span, what);
+        let ty = self.expr_ty(expr);
+        self.push_zeros(stack_slots(ty));
+        ty
+    }
+
+    // --- Literals ---
+
+    /// Returns the value of a literal converted to `ty`, if `expr` is a number literal or a
+    /// string literal converted to a fixed-size byte array.
+    fn literal_value(&mut self, expr: &hir::Expr<'_>, ty: Ty<'gcx>) -> Option<U256> {
+        let ty = peel_udvt(ty);
+        if let hir::ExprKind::Lit(lit) = expr.kind {
+            if let (LitKind::Str(_, bytes), TyKind::Elementary(ElementaryType::FixedBytes(size))) =
+                (&lit.kind, ty.kind)
+            {
+                let mut word = [0u8; 32];
+                let len = bytes.len().min(size.bytes() as usize);
+                word[..len].copy_from_slice(&bytes[..len]);
+                return Some(U256::from_be_bytes(word));
+            }
+        }
+        if !self.is_literal(expr) {
+            return None;
+        }
+        let mut evaluator = ConstantEvaluator::new(self.gcx);
+        let value = match evaluator.try_eval(expr) {
+            Ok(value) => value.data,
+            Err(err) => {
+                self.cx.error = Some(evaluator.emit_eval_error(expr, err));
+                U256::ZERO
+            }
+        };
+        Some(match ty.kind {
+            TyKind::Elementary(ElementaryType::FixedBytes(size)) => {
+                value << (256 - size.bits() as usize)
+            }
+            TyKind::Elementary(ElementaryType::Bool) => U256::from(!value.is_zero() as u8),
+            _ => value,
+        })
+    }
+
+    fn lower_lit(&mut self, expr: &hir::Expr<'_>, lit: &hir::Lit) -> Ty<'gcx> {
+        match lit.kind {
+            LitKind::Address(address) => {
+                self.push(U256::from_be_slice(address.as_slice()));
+                self.gcx.types.address
+            }
+            LitKind::Bool(value) => {
+                self.push(value as u64);
+                self.gcx.types.bool
+            }
+            LitKind::Str(..) => self.unsupported_expr(expr, "string literals"),
+            LitKind::Number(_) | LitKind::Rational(_) => unreachable!("handled by `is_literal`"),
+            LitKind::Err(_) => {
+                self.push(0u64);
+                self.gcx.types.unit
+            }
+        }
+    }
+
+    // --- Types ---
+
+    // --- Places ---
+
+    /// Lowers an assignable expression. Pushes the storage slot for storage places.
+    fn lower_place(&mut self, expr: &hir::Expr<'_>) -> (Place<'gcx>, Ty<'gcx>) {
+        let gcx = self.gcx;
+        let expr = expr.peel_parens();
+        match expr.kind {
+            hir::ExprKind::Ident(&[hir::Res::Item(hir::ItemId::Variable(id)), ..]) => {
+                let ty = gcx.type_of_item(id.into());
+                if let Some(&pos) = self.vars.get(&id) {
+                    return (Place::Stack(pos), ty);
+                }
+                let var = gcx.hir.variable(id);
+                if var.is_constant() || var.is_immutable() {
+                    self.cx.err(expr.span, "cannot assign to a constant or immutable variable");
+                    return (Place::Err, ty);
+                }
+                match self.cx.storage.get(&id) {
+                    Some(&(slot, offset)) => {
+                        self.push(slot);
+                        (Place::Storage(offset, ty), ty)
+                    }
+                    None => {
+                        self.cx.unsupported(expr.span, "transient storage variables");
+                        (Place::Err, ty)
+                    }
+                }
+            }
+            hir::ExprKind::Index(base, Some(index)) => self.lower_index_place(expr, base, index),
+            hir::ExprKind::Member(base, name)
+                if matches!(self.expr_ty(base).peel_refs().kind, TyKind::Struct(_)) =>
+            {
+                let base_ty = self.lower_expr(base);
+                let TyKind::Struct(id) = base_ty.peel_refs().kind else { unreachable!() };
+                if !is_storage_ref(base_ty) {
+                    self.op(Opcode::POP);
+                    self.cx.unsupported(expr.span, "memory and calldata structs");
+                    return (Place::Err, self.expr_ty(expr));
+                }
+                let layout = gcx.struct_storage_layout(id);
+                let item = layout
+                    .items
+                    .iter()
+                    .find(|item| gcx.hir.variable(item.id).name == Some(name))
+                    .expect("struct field not found");
+                if !item.slot.is_zero() {
+                    self.push(item.slot);
+                    self.op(Opcode::ADD);
+                }
+                (Place::Storage(item.offset, item.ty), item.ty)
+            }
+            _ => {
+                self.cx.unsupported(expr.span, "assigning to this expression");
+                (Place::Err, self.expr_ty(expr))
+            }
+        }
+    }
+
+    /// Lowers `base[index]` where `base` is a storage mapping or array.
+    fn lower_index_place(
+        &mut self,
+        expr: &hir::Expr<'_>,
+        base: &hir::Expr<'_>,
+        index: &hir::Expr<'_>,
+    ) -> (Place<'gcx>, Ty<'gcx>) {
+        let gcx = self.gcx;
+        let base_ty = self.lower_expr(base);
+        if !is_storage_ref(base_ty) {
+            self.op(Opcode::POP);
+            self.cx.unsupported(expr.span, "indexing memory and calldata values");
+            return (Place::Err, self.expr_ty(expr));
+        }
+        match base_ty.peel_refs().kind {
+            TyKind::Mapping(key, value) => {
+                if !key.is_value_type() {
+                    self.op(Opcode::POP);
+                    self.cx.unsupported(expr.span, "mappings with reference type keys");
+                    return (Place::Err, value);
+                }
+                // `keccak256(key . slot)`
+                self.lower_expr_to(index, key);
+                self.push(0u64);
+                self.op(Opcode::MSTORE);
+                self.push(0x20u64);
+                self.op(Opcode::MSTORE);
+                self.push(0x40u64);
+                self.push(0u64);
+                self.op(Opcode::KECCAK256);
+                (Place::Storage(0, value), value)
+            }
+            TyKind::Array(elem, _) | TyKind::DynArray(elem) => {
+                if elem.is_value_type() && gcx.type_storage_bytes(elem) <= 16 {
+                    self.op(Opcode::POP);
+                    self.cx.unsupported(expr.span, "indexing packed storage arrays");
+                    return (Place::Err, elem);
+                }
+                self.lower_expr_to(index, gcx.types.uint(256));
+                // `if iszero(lt(index, length)) { panic }`
+                match base_ty.peel_refs().kind {
+                    TyKind::Array(_, len) => self.push(len),
+                    _ => {
+                        self.dup(2);
+                        self.op(Opcode::SLOAD);
+                    }
+                }
+                self.dup(2);
+                self.op(Opcode::LT);
+                self.op(Opcode::ISZERO);
+                self.panic_if(Panic::IndexOutOfBounds);
+                self.elem_offset(elem);
+                self.swap(1);
+                if let TyKind::DynArray(_) = base_ty.peel_refs().kind {
+                    self.array_data_slot();
+                }
+                self.op(Opcode::ADD);
+                (Place::Storage(0, elem), elem)
+            }
+            _ => {
+                self.op(Opcode::POP);
+                self.cx.unsupported(expr.span, "indexing storage byte arrays");
+                (Place::Err, self.expr_ty(expr))
+            }
+        }
+    }
+
+    /// Multiplies the array index on top of the stack by the number of slots per element.
+    fn elem_offset(&mut self, elem: Ty<'gcx>) {
+        let slots = self.gcx.type_storage_slots(elem);
+        if slots != U256::from(1) {
+            self.push(slots);
+            self.op(Opcode::MUL);
+        }
+    }
+
+    /// Replaces the slot of a dynamic storage array on top of the stack with the slot of its
+    /// first element.
+    fn array_data_slot(&mut self) {
+        self.push(0u64);
+        self.op(Opcode::MSTORE);
+        self.push(0x20u64);
+        self.push(0u64);
+        self.op(Opcode::KECCAK256);
+    }
+
+    /// Loads the value of a place, consuming the storage slot.
+    ///
+    /// Storage places of reference types are left as their slot.
+    fn load(&mut self, place: Place<'gcx>, ty: Ty<'gcx>) {
+        match place {
+            Place::Stack(pos) => self.dup_at(pos),
+            Place::Storage(offset, ty) => {
+                if !ty.is_value_type() {
+                    return;
+                }
+                self.op(Opcode::SLOAD);
+                self.shr_const(8 * offset as u32);
+                let bytes = self.gcx.type_storage_bytes(ty);
+                if bytes < 32 {
+                    self.push((U256::from(1) << (8 * bytes as usize)) - U256::from(1));
+                    self.op(Opcode::AND);
+                    match peel_udvt(ty).kind {
+                        // Fixed-size byte arrays are stored right-aligned.
+                        TyKind::Elementary(ElementaryType::FixedBytes(_)) => {
+                            self.shl_const(256 - 8 * bytes as u32)
+                        }
+                        TyKind::Elementary(ElementaryType::Int(_)) => self.cleanup(ty),
+                        _ => {}
+                    }
+                }
+            }
+            Place::Err => self.push_zeros(stack_slots(ty)),
+        }
+    }
+
+    /// Loads the value of a place, keeping the storage slot.
+    fn load_copy(&mut self, place: Place<'gcx>, ty: Ty<'gcx>) {
+        if let Place::Storage(..) = place {
+            self.dup(1);
+        }
+        self.load(place, ty);
+    }
+
+    /// Stores the value on top of the stack in a place, consuming the value and the storage slot
+    /// below it.
+    fn store(&mut self, place: Place<'gcx>, ty: Ty<'gcx>) {
+        match place {
+            Place::Stack(pos) => self.store_at(pos),
+            Place::Storage(offset, ty) => {
+                if !ty.is_value_type() {
+                    let span = self.cx.asm.span();
+                    self.cx.unsupported(span, "assigning reference types in storage");
+                    self.op(Opcode::POP);
+                    self.op(Opcode::POP);
+                    return;
+                }
+                let bytes = self.gcx.type_storage_bytes(ty);
+                if bytes == 32 {
+                    self.swap(1);
+                    self.op(Opcode::SSTORE);
+                    return;
+                }
+                // Read-modify-write the packed slot.
+                let mask = (U256::from(1) << (8 * bytes as usize)) - U256::from(1);
+                match peel_udvt(ty).kind {
+                    TyKind::Elementary(ElementaryType::FixedBytes(_)) => {
+                        self.shr_const(256 - 8 * bytes as u32)
+                    }
+                    _ => {
+                        self.push(mask);
+                        self.op(Opcode::AND);
+                    }
+                }
+                self.shl_const(8 * offset as u32);
+                self.dup(2);
+                self.op(Opcode::SLOAD);
+                self.push(!(mask << (8 * offset as usize)));
+                self.op(Opcode::AND);
+                self.op(Opcode::OR);
+                self.swap(1);
+                self.op(Opcode::SSTORE);
+            }
+            Place::Err => self.pop_to(self.height - stack_slots(ty)),
+        }
+    }
+
+    /// Stores the value on top of the stack in a place, leaving a copy of the value.
+    fn store_keep(&mut self, place: Place<'gcx>, ty: Ty<'gcx>) {
+        match place {
+            Place::Stack(_) => self.dup(1),
+            Place::Storage(..) => {
+                // `[slot value]` -> `[value slot value]`
+                self.dup(1);
+                self.swap(2);
+                self.swap(1);
+            }
+            Place::Err => return,
+        }
+        self.store(place, ty);
+    }
+
+    /// Pops the storage slot of a place, if any.
+    fn discard_place(&mut self, place: Place<'gcx>) {
+        if let Place::Storage(..) = place {
+            self.op(Opcode::POP);
+        }
+    }
+
+    /// Stores the value pushed by `value` in a state variable.
+    pub(super) fn store_state_var_with(
+        &mut self,
+        id: hir::VariableId,
+        ty: Ty<'gcx>,
+        value: impl FnOnce(&mut Self),
+    ) {
+        let span = self.gcx.hir.variable(id).span;
+        let Some(&(slot, offset)) = self.cx.storage.get(&id) else {
+            self.cx.unsupported(span, "transient storage variables");
+            return;
+        };
+        if !ty.is_value_type() {
+            self.cx.unsupported(span, "initializing reference type state variables");
+            return;
+        }
+        self.push(slot);
+        value(self);
+        self.store(Place::Storage(offset, ty), ty);
+    }
+
+    // --- Identifiers and members ---
+
+    fn lower_ident(&mut self, expr: &hir::Expr<'_>, res: &[hir::Res]) -> Ty<'gcx> {
+        let gcx = self.gcx;
+        match *res {
+            [hir::Res::Item(hir::ItemId::Variable(id)), ..] => {
+                let ty = gcx.type_of_item(id.into());
+                if let Some(&pos) = self.vars.get(&id) {
+                    self.dup_at(pos);
+                    return ty;
+                }
+                let var = gcx.hir.variable(id);
+                if var.is_constant() {
+                    return self.lower_constant(id);
+                }
+                if var.is_immutable() {
+                    return self.unsupported_expr(expr, "immutable variables");
+                }
+                let (place, ty) = self.lower_place(expr);
+                self.load(place, ty);
+                ty
+            }
+            [hir::Res::Builtin(Builtin::This)] => {
+                self.op(Opcode::ADDRESS);
+                gcx.mk_ty(TyKind::Contract(self.cx.contract))
+            }
+            [hir::Res::Item(hir::ItemId::Function(_)), ..] => {
+                self.unsupported_expr(expr, "function pointers")
+            }
+            _ => self.unsupported_expr(expr, "this expression"),
+        }
+    }
+
+    /// Lowers the value of a constant variable by inlining its initializer.
+    fn lower_constant(&mut self, id: hir::VariableId) -> Ty<'gcx> {
+        let ty = self.gcx.type_of_item(id.into());
+        let init = self.gcx.hir.variable(id).initializer.expect("constant without initializer");
+        self.lower_expr_to(init, ty);
+        ty
+    }
+
+    fn lower_member(
+        &mut self,
+        expr: &hir::Expr<'_>,
+        base: &hir::Expr<'_>,
+        name: Ident,
+    ) -> Ty<'gcx> {
+        let gcx = self.gcx;
+        match base.peel_parens().kind {
+            hir::ExprKind::Ident(res) => {
+                for &res in res {
+                    match res {
+                        hir::Res::Builtin(b) => {
+                            if let Some(member) = builtin_member(b, name) {
+                                return self.lower_builtin_value(expr, member);
+                            }
+                        }
+                        hir::Res::Item(hir::ItemId::Enum(id)) => {
+                            let variants = gcx.hir.enumm(id).variants;
+                            let i = variants.iter().position(|v| *v == name).unwrap();
+                            self.push(i as u64);
+                            return gcx.mk_ty(TyKind::Enum(id));
+                        }
+                        hir::Res::Item(hir::ItemId::Contract(c)) => {
+                            if let Some(var) = self.contract_constant(c, name) {
+                                return self.lower_constant(var);
+                            }
+                        }
+                        hir::Res::Item(hir::ItemId::Error(id)) => {
+                            let selector = gcx.function_selector(id);
+                            self.push(U256::from_be_slice(selector.as_slice()) << 224);
+                            return gcx.types.fixed_bytes(4);
+                        }
+                        hir::Res::Item(hir::ItemId::Event(id)) => {
+                            self.push(U256::from_be_bytes(gcx.event_selector(id).0));
+                            return gcx.types.fixed_bytes(32);
+                        }
+                        _ => {}
+                    }
+                }
+            }
+            hir::ExprKind::TypeCall(ref ty) => {
+                let ty = gcx.type_of_hir_ty(ty);
+                return self.lower_type_member(expr, ty, name);
+            }
+            hir::ExprKind::Member(inner, inner_name)
+                if inner_name.name == Builtin::AddressCode.name()
+                    && name.name == Builtin::ArrayLength.name() =>
+            {
+                self.lower_expr(inner);
+                self.op(Opcode::EXTCODESIZE);
+                return gcx.types.uint(256);
+            }
+            hir::ExprKind::Member(inner, function)
+                if name.name == Builtin::ErrorSelector.name() =>
+            {
+                let Some(selector) = self.external_function_selector(inner, function) else {
+                    return self.unsupported_expr(expr, "this selector");
+                };
+                self.push(U256::from_be_slice(selector.as_slice()) << 224);
+                return gcx.types.fixed_bytes(4);
+            }
+            _ => {}
+        }
+
+        let base_ty = self.expr_ty(base);
+        match base_ty.peel_refs().kind {
+            TyKind::Elementary(ElementaryType::Address(_)) => {
+                let op = if name.name == Builtin::AddressBalance.name() {
+                    Opcode::BALANCE
+                } else if name.name == Builtin::AddressCodehash.name() {
+                    Opcode::EXTCODEHASH
+                } else {
+                    return self.unsupported_expr(expr, "this member");
+                };
+                self.lower_expr(base);
+                self.op(op);
+                self.expr_ty(expr)
+            }
+            TyKind::Elementary(ElementaryType::FixedBytes(size)) => {
+                self.push(size.bytes() as u64);
+                gcx.types.uint(8)
+            }
+            TyKind::Array(_, len) if name.name == Builtin::ArrayLength.name() => {
+                self.push(len);
+                gcx.types.uint(256)
+            }
+            TyKind::DynArray(_) if name.name == Builtin::ArrayLength.name() => {
+                let ty = self.lower_expr(base);
+                if !is_storage_ref(ty) {
+                    self.op(Opcode::POP);
+                    return self.unsupported_expr(expr, "memory and calldata arrays");
+                }
+                self.op(Opcode::SLOAD);
+                gcx.types.uint(256)
+            }
+            TyKind::Struct(_) => {
+                let (place, ty) = self.lower_place(expr);
+                self.load(place, ty);
+                ty
+            }
+            _ => self.unsupported_expr(expr, "this member"),
+        }
+    }
+
+    /// Lowers a member of `type(T)`.
+    fn lower_type_member(&mut self, expr: &hir::Expr<'_>, ty: Ty<'gcx>, name: Ident) -> Ty<'gcx> {
+        let gcx = self.gcx;
+        let value = if name.name == Builtin::TypeMin.name() || name.name == Builtin::TypeMax.name()
+        {
+            let max = name.name == Builtin::TypeMax.name();
+            match ty.kind {
+                TyKind::Elementary(ElementaryType::UInt(size)) => {
+                    if max {
+                        U256::MAX >> (256 - size.bits() as usize)
+                    } else {
+                        U256::ZERO
+                    }
+                }
+                TyKind::Elementary(ElementaryType::Int(size)) => {
+                    if max {
+                        U256::MAX >> (257 - size.bits() as usize)
+                    } else {
+                        U256::MAX << (size.bits() as usize - 1)
+                    }
+                }
+                TyKind::Enum(id) if max => U256::from(gcx.hir.enumm(id).variants.len() - 1),
+                TyKind::Enum(_) => U256::ZERO,
+                _ => return self.unsupported_expr(expr, "this type member"),
+            }
+        } else if name.name == Builtin::InterfaceId.name() {
+            let TyKind::Contract(id) = ty.kind else { unreachable!() };
+            let id = gcx.interface_id(id);
+            self.push(U256::from_be_slice(id.as_slice()) << 224);
+            return gcx.types.fixed_bytes(4);
+        } else {
+            return self.unsupported_expr(expr, "this type member");
+        };
+        self.push(value);
+        ty
+    }
+
+    /// Lowers a member of a builtin module, such as `msg.sender`.
+    fn lower_builtin_value(&mut self, expr: &hir::Expr<'_>, b: Builtin) -> Ty<'gcx> {
+        let op = match b {
+            Builtin::BlockCoinbase => Opcode::COINBASE,
+            Builtin::BlockTimestamp => Opcode::TIMESTAMP,
+            Builtin::BlockDifficulty | Builtin::BlockPrevrandao => Opcode::PREVRANDAO,
+            Builtin::BlockNumber => Opcode::NUMBER,
+            Builtin::BlockGaslimit => Opcode::GASLIMIT,
+            Builtin::BlockChainid => Opcode::CHAINID,
+            Builtin::BlockBasefee => Opcode::BASEFEE,
+            Builtin::BlockBlobbasefee => Opcode::BLOBBASEFEE,
+            Builtin::MsgSender => Opcode::CALLER,
+            Builtin::MsgGas => Opcode::GAS,
+            Builtin::MsgValue => Opcode::CALLVALUE,
+            Builtin::MsgSig => {
+                let ty = self.gcx.types.fixed_bytes(4);
+                self.push(0u64);
+                self.op(Opcode::CALLDATALOAD);
+                self.cleanup(ty);
+                return ty;
+            }
+            Builtin::TxOrigin => Opcode::ORIGIN,
+            Builtin::TxGasPrice => Opcode::GASPRICE,
+            _ => {
+                return self.unsupported_expr(expr, &format!("`{}`", b.name()));
+            }
+        };
+        self.op(op);
+        b.ty(self.gcx)
+    }
+
+    // --- Operators ---
+
+    fn lower_assign(
+        &mut self,
+        lhs: &hir::Expr<'_>,
+        op: Option<hir::BinOp>,
+        rhs: &hir::Expr<'_>,
+    ) -> Ty<'gcx> {
+        if let hir::ExprKind::Tuple(lhs) = lhs.peel_parens().kind {
+            if lhs.len() != 1 {
+                self.lower_tuple_assign(lhs, rhs);
+                return self.gcx.types.unit;
+            }
+        }
+
+        let (place, ty) = self.lower_place(lhs);
+        match op {
+            Some(op) => {
+                self.load_copy(place, ty);
+                let rhs_ty = match op.kind {
+                    BinOpKind::Shl | BinOpKind::Shr | BinOpKind::Sar | BinOpKind::Pow => {
+                        self.expr_ty(rhs)
+                    }
+                    _ => ty,
+                };
+                self.lower_expr_to(rhs, rhs_ty);
+                self.binary_op(op.kind, ty);
+            }
+            None => self.lower_expr_to(rhs, ty),
+        }
+        self.store_keep(place, ty);
+        ty
+    }
+
+    /// Lowers `(a, b) = rhs`.
+    fn lower_tuple_assign(&mut self, lhs: &[Option<&hir::Expr<'_>>], rhs: &hir::Expr<'_>) {
+        // Evaluate the right-hand side first, then assign the components in reverse order.
+        match rhs.peel_parens().kind {
+            hir::ExprKind::Tuple(rhs) if rhs.len() == lhs.len() => {
+                for (l, r) in lhs.iter().zip(rhs) {
+                    let r = r.expect("empty tuple component");
+                    let ty = l.map_or_else(|| self.expr_ty(r), |l| self.expr_ty(l));
+                    self.lower_expr_to(r, ty);
+                }
+            }
+            _ => _ = self.lower_expr(rhs),
+        }
+        for l in lhs.iter().rev() {
+            match l {
+                Some(l) => {
+                    let (place, ty) = self.lower_place(l);
+                    if let Place::Storage(..) = place {
+                        self.swap(1);
+                    }
+                    self.store(place, ty);
+                }
+                None => self.op(Opcode::POP),
+            }
+        }
+    }
+
+    fn lower_binary(&mut self, l: &hir::Expr<'_>, op: BinOpKind, r: &hir::Expr<'_>) -> Ty<'gcx> {
+        let bool = self.gcx.types.bool;
+        match op {
+            BinOpKind::And | BinOpKind::Or => {
+                // Short-circuit: evaluate `r` only if `l` doesn't determine the result.
+                let end = self.new_label();
+                self.lower_expr_to(l, bool);
+                self.dup(1);
+                if op == BinOpKind::And {
+                    self.op(Opcode::ISZERO);
+                }
+                self.jump_if(end);
+                self.op(Opcode::POP);
+                self.lower_expr_to(r, bool);
+                self.place_label(end);
+                bool
+            }
+            BinOpKind::Lt
+            | BinOpKind::Le
+            | BinOpKind::Gt
+            | BinOpKind::Ge
+            | BinOpKind::Eq
+            | BinOpKind::Ne => {
+                let ty = self.common_ty(l, r);
+                self.lower_expr_to(l, ty);
+                self.lower_expr_to(r, ty);
+                self.binary_op(op, ty);
+                bool
+            }
+            BinOpKind::Shl | BinOpKind::Shr | BinOpKind::Sar | BinOpKind::Pow => {
+                let ty = self.expr_ty(l);
+                let r_ty = self.expr_ty(r);
+                self.lower_expr_to(l, ty);
+                self.lower_expr_to(r, r_ty);
+                self.binary_op(op, ty);
+                ty
+            }
+            _ => {
+                let ty = self.common_ty(l, r);
+                self.lower_expr_to(l, ty);
+                self.lower_expr_to(r, ty);
+                self.binary_op(op, ty);
+                ty
+            }
+        }
+    }
+
+    /// Applies a binary operation to `[a b]`, leaving `a op b`. `ty` is the type of `a`.
+    fn binary_op(&mut self, op: BinOpKind, ty: Ty<'gcx>) {
+        let signed = int_info(ty).is_some_and(|(signed, _)| signed);
+        let (lt, gt) = if signed { (Opcode::SLT, Opcode::SGT) } else { (Opcode::LT, Opcode::GT) };
+        match op {
+            // The operands are reversed on the stack.
+            BinOpKind::Lt => self.op(gt),
+            BinOpKind::Gt => self.op(lt),
+            BinOpKind::Le => {
+                self.op(lt);
+                self.op(Opcode::ISZERO);
+            }
+            BinOpKind::Ge => {
+                self.op(gt);
+                self.op(Opcode::ISZERO);
+            }
+            BinOpKind::Eq => self.op(Opcode::EQ),
+            BinOpKind::Ne => {
+                self.op(Opcode::EQ);
+                self.op(Opcode::ISZERO);
+            }
+            BinOpKind::Shl => {
+                if self.has_shifts() {
+                    self.op(Opcode::SHL);
+                } else {
+                    self.push(2u64);
+                    self.op(Opcode::EXP);
+                    self.op(Opcode::MUL);
+                }
+                self.cleanup(ty);
+            }
+            BinOpKind::Shr | BinOpKind::Sar => {
+                if signed {
+                    self.op(Opcode::SAR);
+                } else if self.has_shifts() {
+                    self.op(Opcode::SHR);
+                } else {
+                    self.push(2u64);
+                    self.op(Opcode::EXP);
+                    self.swap(1);
+                    self.op(Opcode::DIV);
+                }
+            }
+            BinOpKind::BitAnd => self.op(Opcode::AND),
+            BinOpKind::BitOr => self.op(Opcode::OR),
+            BinOpKind::BitXor => self.op(Opcode::XOR),
+            BinOpKind::Add
+            | BinOpKind::Sub
+            | BinOpKind::Mul
+            | BinOpKind::Div
+            | BinOpKind::Rem
+            | BinOpKind::Pow => self.arith(op, ty),
+            BinOpKind::And | BinOpKind::Or => unreachable!("short-circuiting operator"),
+        }
+    }
+
+    /// Applies an arithmetic operation to `[a b]`, leaving `a op b`.
+    ///
+    /// In checked mode, panics on overflow; otherwise wraps around to the width of `ty`.
+    fn arith(&mut self, op: BinOpKind, ty: Ty<'gcx>) {
+        let (signed, bits) = int_info(ty).unwrap_or((false, 256));
+        let checked = !self.unchecked;
+        let min = U256::from(1) << 255;
+        match op {
+            BinOpKind::Add if checked && bits == 256 => {
+                if signed {
+                    // Overflow iff `slt(r, a) != slt(b, 0)`.
+                    self.dup(2);
+                    self.dup(2);
+                    self.op(Opcode::ADD);
+                    self.dup(3);
+                    self.dup(2);
+                    self.op(Opcode::SLT);
+                    self.push(0u64);
+                    self.dup(4);
+                    self.op(Opcode::SLT);
+                    self.op(Opcode::XOR);
+                    self.panic_if(Panic::Arithmetic);
+                    self.swap(2);
+                    self.op(Opcode::POP);
+                    self.op(Opcode::POP);
+                } else {
+                    // Overflow iff `lt(r, a)`.
+                    self.dup(2);
+                    self.op(Opcode::ADD);
+                    self.swap(1);
+                    self.dup(2);
+                    self.op(Opcode::LT);
+                    self.panic_if(Panic::Arithmetic);
+                }
+            }
+            BinOpKind::Sub if checked && bits == 256 => {
+                if signed {
+                    // Overflow iff `sgt(r, a) != slt(b, 0)`.
+                    self.dup(1);
+                    self.dup(3);
+                    self.op(Opcode::SUB);
+                    self.dup(3);
+                    self.dup(2);
+                    self.op(Opcode::SGT);
+                    self.push(0u64);
+                    self.dup(4);
+                    self.op(Opcode::SLT);
+                    self.op(Opcode::XOR);
+                    self.panic_if(Panic::Arithmetic);
+                    self.swap(2);
+                    self.op(Opcode::POP);
+                    self.op(Opcode::POP);
+                } else {
+                    // Underflow iff `lt(a, b)`.
+                    self.dup(1);
+                    self.dup(3);
+                    self.op(Opcode::LT);
+                    self.panic_if(Panic::Arithmetic);
+                    self.swap(1);
+                    self.op(Opcode::SUB);
+                }
+            }
+            BinOpKind::Add => self.op(Opcode::ADD),
+            BinOpKind::Sub => {
+                self.swap(1);
+                self.op(Opcode::SUB);
+            }
+            BinOpKind::Mul if checked && bits > 128 => {
+                // Overflow iff `a != 0 && r / a != b`, or `a == -1 && b == MIN` for `int256`.
+                self.dup(2);
+                self.dup(2);
+                self.op(Opcode::MUL);
+                self.dup(3);
+                self.op(Opcode::ISZERO);
+                self.dup(4);
+                self.dup(3);
+                self.op(if signed { Opcode::SDIV } else { Opcode::DIV });
+                self.dup(4);
+                self.op(Opcode::EQ);
+                self.op(Opcode::OR);
+                self.op(Opcode::ISZERO);
+                self.panic_if(Panic::Arithmetic);
+                if signed && bits == 256 {
+                    self.dup(3);
+                    self.op(Opcode::NOT);
+                    self.op(Opcode::ISZERO);
+                    self.dup(3);
+                    self.push(min);
+                    self.op(Opcode::EQ);
+                    self.op(Opcode::AND);
+                    self.panic_if(Panic::Arithmetic);
+                }
+                self.swap(2);
+                self.op(Opcode::POP);
+                self.op(Opcode::POP);
+            }
+            BinOpKind::Mul => self.op(Opcode::MUL),
+            BinOpKind::Div | BinOpKind::Rem => {
+                self.dup(1);
+                self.op(Opcode::ISZERO);
+                self.panic_if(Panic::DivisionByZero);
+                if checked && signed && bits == 256 && op == BinOpKind::Div {
+                    // `MIN / -1` overflows.
+                    self.dup(1);
+                    self.op(Opcode::NOT);
+                    self.op(Opcode::ISZERO);
+                    self.dup(3);
+                    self.push(min);
+                    self.op(Opcode::EQ);
+                    self.op(Opcode::AND);
+                    self.panic_if(Panic::Arithmetic);
+                }
+                self.swap(1);
+                self.op(match (op, signed) {
+                    (BinOpKind::Div, false) => Opcode::DIV,
+                    (BinOpKind::Div, true) => Opcode::SDIV,
+                    (_, false) => Opcode::MOD,
+                    (_, true) => Opcode::SMOD,
+                });
+            }
+            BinOpKind::Pow if checked => self.checked_exp(ty),
+            BinOpKind::Pow => {
+                self.swap(1);
+                self.op(Opcode::EXP);
+            }
+            _ => unreachable!("not an arithmetic operator: {op:?}"),
+        }
+
+        let may_overflow = match op {
+            BinOpKind::Add | BinOpKind::Sub | BinOpKind::Mul => true,
+            BinOpKind::Div => signed,
+            // The checked exponentiation loop checks every multiplication.
+            BinOpKind::Pow => !checked,
+            _ => false,
+        };
+        if bits < 256 && may_overflow {
+            if checked {
+                self.range_check(ty);
+            } else {
+                self.cleanup(ty);
+            }
+        }
+    }
+
+    /// Panics if the value on top of the stack is not in the range of `ty`.
+    fn range_check(&mut self, ty: Ty<'gcx>) {
+        self.dup(1);
+        self.dup(1);
+        self.cleanup(ty);
+        self.op(Opcode::EQ);
+        self.op(Opcode::ISZERO);
+        self.panic_if(Panic::Arithmetic);
+    }
+
+    /// Computes `a ** b` for `[a b]` by repeated squaring, checking each multiplication.
+    fn checked_exp(&mut self, ty: Ty<'gcx>) {
+        let start = self.new_label();
+        let skip = self.new_label();
+        let end = self.new_label();
+        // `[a b r]`
+        self.push(1u64);
+        self.place_label(start);
+        // `if iszero(b) { break }`
+        self.dup(2);
+        self.op(Opcode::ISZERO);
+        self.jump_if(end);
+        // `if and(b, 1) { r := mul(r, a) }`
+        self.dup(2);
+        self.push(1u64);
+        self.op(Opcode::AND);
+        self.op(Opcode::ISZERO);
+        self.jump_if(skip);
+        self.dup(3);
+        self.arith(BinOpKind::Mul, ty);
+        self.place_label(skip);
+        // `b := shr(1, b)`
+        self.swap(1);
+        self.shr_const(1);
+        self.swap(1);
+        // `if iszero(b) { break }`
+        self.dup(2);
+        self.op(Opcode::ISZERO);
+        self.jump_if(end);
+        // `a := mul(a, a)`
+        self.swap(2);
+        self.dup(1);
+        self.arith(BinOpKind::Mul, ty);
+        self.swap(2);
+        self.jump_to(start);
+        self.place_label(end);
+        self.swap(2);
+        self.op(Opcode::POP);
+        self.op(Opcode::POP);
+    }
+
+    fn lower_unary(&mut self, op: UnOpKind, x: &hir::Expr<'_>) -> Ty<'gcx> {
+        match op {
+            UnOpKind::Not => {
+                self.lower_expr_to(x, self.gcx.types.bool);
+                self.op(Opcode::ISZERO);
+                self.gcx.types.bool
+            }
+            UnOpKind::BitNot => {
+                let ty = self.lower_expr(x);
+                self.op(Opcode::NOT);
+                self.cleanup(ty);
+                ty
+            }
+            UnOpKind::Neg => {
+                let ty = self.lower_expr(x);
+                let checked = !self.unchecked;
+                let bits = int_info(ty).map_or(256, |(_, bits)| bits);
+                if checked && bits == 256 {
+                    self.dup(1);
+                    self.push(U256::from(1) << 255);
+                    self.op(Opcode::EQ);
+                    self.panic_if(Panic::Arithmetic);
+                }
+                self.push(0u64);
+                self.op(Opcode::SUB);
+                if bits < 256 {
+                    if checked {
+                        self.range_check(ty);
+                    } else {
+                        self.cleanup(ty);
+                    }
+                }
+                ty
+            }
+            UnOpKind::PreInc | UnOpKind::PreDec | UnOpKind::PostInc | UnOpKind::PostDec => {
+                let (place, ty) = self.lower_place(x);
+                let arith = match op {
+                    UnOpKind::PreInc | UnOpKind::PostInc => BinOpKind::Add,
+                    _ => BinOpKind::Sub,
+                };
+                self.load_copy(place, ty);
+                if matches!(op, UnOpKind::PreInc | UnOpKind::PreDec) {
+                    self.push(1u64);
+                    self.arith(arith, ty);
+                    self.store_keep(place, ty);
+                    return ty;
+                }
+                // `[slot old]` -> `[old slot new]`
+                self.dup(1);
+                self.push(1u64);
+                self.arith(arith, ty);
+                match place {
+                    Place::Stack(_) => {}
+                    Place::Storage(..) => {
+                        self.swap(1);
+                        self.swap(2);
+                        self.swap(1);
+                    }
+                    Place::Err => {
+                        self.op(Opcode::POP);
+                        return ty;
+                    }
+                }
+                self.store(place, ty);
+                ty
+            }
+        }
+    }
+
+    fn lower_ternary(
+        &mut self,
+        cond: &hir::Expr<'_>,
+        t: &hir::Expr<'_>,
+        f: &hir::Expr<'_>,
+        ty: Ty<'gcx>,
+    ) {
+        let else_ = self.new_label();
+        let end = self.new_label();
+        self.lower_expr_to(cond, self.gcx.types.bool);
+        self.op(Opcode::ISZERO);
+        self.jump_if(else_);
+        let height = self.height;
+        self.lower_expr_to(t, ty);
+        self.jump_to(end);
+        self.height = height;
+        self.place_label(else_);
+        self.lower_expr_to(f, ty);
+        self.place_label(end);
+    }
+
+    // --- Calls ---
+
+    fn lower_call(
+        &mut self,
+        expr: &hir::Expr<'_>,
+        callee: &hir::Expr<'_>,
+        args: &hir::CallArgs<'_>,
+    ) -> Ty<'gcx> {
+        let gcx = self.gcx;
+        match self.resolve_callee(callee, args) {
+            Callee::Conversion(to) => {
+                let arg = args.exprs().next().expect("conversion without argument");
+                let arg_peeled = arg.peel_parens();
+                if !to.is_value_type() {
+                    return self.unsupported_expr(expr, "conversions to reference types");
+                }
+                if self.is_literal(arg_peeled) || matches!(arg_peeled.kind, hir::ExprKind::Lit(_)) {
+                    self.lower_expr_to(arg, to);
+                } else {
+                    let from = self.lower_expr(arg);
+                    self.convert(from, to, expr.span);
+                }
+                to
+            }
+            Callee::Internal(id) => {
+                let f = gcx.hir.function(id);
+                let args = self.ordered_args(f.parameters, args);
+                self.call_function(id, |fcx| {
+                    for (&param, arg) in f.parameters.iter().zip(&args) {
+                        let ty = fcx.gcx.type_of_item(param.into());
+                        fcx.lower_expr_to(arg, ty);
+                    }
+                });
+                self.return_ty(id)
+            }
+            Callee::Library(id) => self.lower_library_call(expr, id, args),
+            Callee::Builtin(b, base) => self.lower_builtin_call(expr, b, base, args),
+            Callee::ArrayPush(base) => self.lower_array_push(expr, base, args),
+            Callee::ArrayPop(base) => self.lower_array_pop(expr, base),
+            Callee::Unsupported(what) => self.unsupported_expr(expr, what),
+        }
+    }
+
+    /// Converts the value on top of the stack from `from` to `to`.
+    fn convert(&mut self, from: Ty<'gcx>, to: Ty<'gcx>, span: Span) {
+        use ElementaryType::*;
+
+        let (from, to) = (peel_udvt(from), peel_udvt(to));
+        if from == to {
+            return;
+        }
+        match (from.kind, to.kind) {
+            (TyKind::Elementary(FixedBytes(a)), TyKind::Elementary(FixedBytes(b))) => {
+                if b.bytes() < a.bytes() {
+                    self.cleanup(to);
+                }
+            }
+            // Sizes are equal, checked by the type checker.
+            (TyKind::Elementary(FixedBytes(a)), _) => self.shr_const(256 - a.bits() as u32),
+            (_, TyKind::Elementary(FixedBytes(b))) => self.shl_const(256 - b.bits() as u32),
+            (_, TyKind::Enum(id)) => {
+                let variants = self.gcx.hir.enumm(id).variants.len();
+                self.push(variants as u64);
+                self.dup(2);
+                self.op(Opcode::LT);
+                self.op(Opcode::ISZERO);
+                self.panic_if(Panic::EnumConversion);
+            }
+            (TyKind::Enum(_), _)
+            | (
+                TyKind::Elementary(Address(_)) | TyKind::Contract(_),
+                TyKind::Elementary(Address(_)) | TyKind::Contract(_),
+            ) => {}
+            (TyKind::Elementary(UInt(a)), TyKind::Elementary(UInt(b))) if b.bits() >= a.bits() => {}
+            _ if from.is_value_type() => self.cleanup(to),
+            _ => self.cx.unsupported(span, "this conversion"),
+        }
+    }
+
+    fn lower_builtin_call(
+        &mut self,
+        expr: &hir::Expr<'_>,
+        b: Builtin,
+        base: Option<&hir::Expr<'_>>,
+        args: &hir::CallArgs<'_>,
+    ) -> Ty<'gcx> {
+        let gcx = self.gcx;
+        let types = &gcx.types;
+        let args = args.exprs().collect::<Vec<_>>();
+        match b {
+            Builtin::Require | Builtin::RequireMsg => {
+                self.lower_expr_to(args[0], types.bool);
+                match args.get(1).map(|&msg| string_literal(msg)) {
+                    None => self.lower_require(None),
+                    Some(Some(msg)) => self.lower_require(Some(&msg)),
+                    Some(None) => {
+                        self.op(Opcode::POP);
+                        self.cx.unsupported(args[1].span, "non-literal revert reasons");
+                    }
+                }
+            }
+            Builtin::Assert => {
+                self.lower_expr_to(args[0], types.bool);
+                self.lower_assert();
+            }
+            Builtin::Revert => self.lower_revert_message(None),
+            Builtin::RevertMsg => match string_literal(args[0]) {
+                Some(msg) => self.lower_revert_message(Some(&msg)),
+                None => self.cx.unsupported(args[0].span, "non-literal revert reasons"),
+            },
+            Builtin::AddMod | Builtin::MulMod => {
+                for &arg in &args {
+                    self.lower_expr_to(arg, types.uint(256));
+                }
+                // `[a b m]`
+                self.dup(1);
+                self.op(Opcode::ISZERO);
+                self.panic_if(Panic::DivisionByZero);
+                self.swap(2);
+                self.op(if b == Builtin::AddMod { Opcode::ADDMOD } else { Opcode::MULMOD });
+                return types.uint(256);
+            }
+            Builtin::Gasleft => {
+                self.op(Opcode::GAS);
+                return types.uint(256);
+            }
+            Builtin::Blockhash | Builtin::Blobhash => {
+                self.lower_expr_to(args[0], types.uint(256));
+                self.op(if b == Builtin::Blockhash { Opcode::BLOCKHASH } else { Opcode::BLOBHASH });
+                return types.fixed_bytes(32);
+            }
+            Builtin::Keccak256 => return self.lower_keccak256(expr, args[0]),
+            Builtin::AddressPayableTransfer | Builtin::AddressPayableSend => {
+                let send = b == Builtin::AddressPayableSend;
+                self.lower_transfer(base.unwrap(), args[0], send);
+                if send {
+                    return types.bool;
+                }
+            }
+            _ => return self.unsupported_expr(expr, &format!("`{}`", b.name())),
+        }
+        types.unit
+    }
+
+    /// Lowers `keccak256(arg)`.
+    fn lower_keccak256(&mut self, expr: &hir::Expr<'_>, arg: &hir::Expr<'_>) -> Ty<'gcx> {
+        let ty = self.gcx.types.fixed_bytes(32);
+        if let Some(bytes) = string_literal(arg) {
+            self.push(U256::from_be_bytes(keccak256(&bytes).0));
+            return ty;
+        }
+        if let hir::ExprKind::Call(callee, ref args) = arg.peel_parens().kind {
+            if let Callee::Builtin(b @ (Builtin::AbiEncode | Builtin::AbiEncodePacked), None) =
+                self.resolve_callee(callee, args)
+            {
+                self.lower_abi_encode_hash(args, b == Builtin::AbiEncodePacked);
+                return ty;
+            }
+        }
+        self.unsupported_expr(expr, "hashing memory values")
+    }
+
+    /// Lowers `keccak256(abi.encode(args))` or `keccak256(abi.encodePacked(args))`, encoding the
+    /// arguments in scratch memory.
+    fn lower_abi_encode_hash(&mut self, args: &hir::CallArgs<'_>, packed: bool) {
+        enum Part<'a, 'gcx> {
+            Value(Ty<'gcx>),
+            Bytes(Cow<'a, [u8]>),
+        }
+
+        let gcx = self.gcx;
+        let height = self.height;
+        let mut parts = Vec::with_capacity(args.len());
+        for arg in args.exprs() {
+            if packed {
+                if let Some(bytes) = string_literal(arg) {
+                    parts.push(Part::Bytes(bytes));
+                    continue;
+                }
+            }
+            let ty = self.expr_ty(arg);
+            if !is_abi_value_type(ty) {
+                self.cx.unsupported(arg.span, "ABI encoding of reference types");
+                self.pop_to(height);
+                self.push(0u64);
+                return;
+            }
+            self.lower_expr_to(arg, ty);
+            parts.push(Part::Value(ty));
+        }
+
+        // Packed encoding is written as right-aligned words, each overwriting the unused bytes of
+        // the previous one, so it starts one word into the scratch space.
+        let start = if packed { 32 } else { 0 };
+        let sizes = parts
+            .iter()
+            .map(|part| match *part {
+                Part::Value(ty) if packed => gcx.type_storage_bytes(ty) as u64,
+                Part::Value(_) => 32,
+                Part::Bytes(ref bytes) => bytes.len() as u64,
+            })
+            .collect::<Vec<_>>();
+        let total = sizes.iter().sum::<u64>();
+        let mut end = start + total;
+        for (part, &size) in parts.iter().zip(&sizes).rev() {
+            match *part {
+                Part::Value(ty) => {
+                    if let TyKind::Elementary(ElementaryType::FixedBytes(size)) = peel_udvt(ty).kind
+                    {
+                        if packed {
+                            self.shr_const(256 - size.bits() as u32);
+                        }
+                    }
+                    self.store_scratch(end - 32);
+                }
+                Part::Bytes(ref bytes) => {
+                    let part_start = end - size;
+                    for (i, chunk) in bytes.chunks(32).enumerate().rev() {
+                        self.push(U256::from_be_slice(chunk));
+                        let chunk_end = part_start + 32 * i as u64 + chunk.len() as u64;
+                        self.store_scratch(chunk_end - 32);
+                    }
+                }
+            }
+            end -= size;
+        }
+        self.push(total);
+        self.push_scratch_ptr();
+        if start != 0 {
+            self.push(start);
+            self.op(Opcode::ADD);
+        }
+        self.op(Opcode::KECCAK256);
+    }
+
+    /// Lowers `address.transfer(value)` and `address.send(value)`.
+    fn lower_transfer(&mut self, address: &hir::Expr<'_>, value: &hir::Expr<'_>, send: bool) {
+        self.lower_expr(address);
+        self.lower_expr_to(value, self.gcx.types.uint(256));
+        // `call(mul(iszero(value), 2300), address, value, 0, 0, 0, 0)`
+        self.push_zeros(4);
+        self.dup(5);
+        self.dup(7);
+        self.dup(2);
+        self.op(Opcode::ISZERO);
+        self.push(2300u64);
+        self.op(Opcode::MUL);
+        self.op(Opcode::CALL);
+        self.swap(2);
+        self.op(Opcode::POP);
+        self.op(Opcode::POP);
+        if send {
+            return;
+        }
+        // Bubble up the revert data.
+        let ok = self.new_label();
+        self.jump_if(ok);
+        self.op(Opcode::RETURNDATASIZE);
+        self.push(0u64);
+        self.push(0u64);
+        self.op(Opcode::RETURNDATACOPY);
+        self.op(Opcode::RETURNDATASIZE);
+        self.push(0u64);
+        self.op(Opcode::REVERT);
+        self.place_label(ok);
+    }
+
+    /// Lowers a call of a public or external library function, with a `DELEGATECALL` to the
+    /// library's address, which is filled in when linking.
+    fn lower_library_call(
+        &mut self,
+        expr: &hir::Expr<'_>,
+        id: hir::FunctionId,
+        args: &hir::CallArgs<'_>,
+    ) -> Ty<'gcx> {
+        let gcx = self.gcx;
+        let f = gcx.hir.function(id);
+        if self.cx.asm.is_eof() {
+            return self.unsupported_expr(expr, "external library calls in EOF");
+        }
+        // Enums and contracts are encoded by name in library function signatures.
+        let supported = f.parameters.iter().chain(f.returns).all(|&var| {
+            let ty = gcx.type_of_item(var.into());
+            is_abi_value_type(ty) && !matches!(ty.kind, TyKind::Enum(_) | TyKind::Contract(_))
+        });
+        if !supported {
+            return self
+                .unsupported_expr(expr, "library calls with these parameter or return types");
+        }
+
+        // Encode the arguments in scratch memory.
+        let params = self.ordered_args(f.parameters, args);
+        for (&param, arg) in f.parameters.iter().zip(&params) {
+            let ty = gcx.type_of_item(param.into());
+            self.lower_expr_to(arg, ty);
+        }
+        let selector = gcx.function_selector(id);
+        self.push(U256::from_be_slice(selector.as_slice()) << 224);
+        self.store_scratch(0);
+        for i in (0..params.len()).rev() {
+            self.store_scratch(4 + 32 * i as u64);
+        }
+
+        // `delegatecall(gas(), library, ptr, 4 + 32 * params, ptr, 32 * returns)`
+        self.push(32 * f.returns.len() as u64);
+        self.push_scratch_ptr();
+        self.push(4 + 32 * params.len() as u64);
+        self.dup(2);
+        self.push_library(f.contract.unwrap());
+        self.op(Opcode::GAS);
+        self.op(Opcode::DELEGATECALL);
+
+        // Bubble up the revert data.
+        let ok = self.new_label();
+        self.jump_if(ok);
+        self.op(Opcode::RETURNDATASIZE);
+        self.push(0u64);
+        self.push(0u64);
+        self.op(Opcode::RETURNDATACOPY);
+        self.op(Opcode::RETURNDATASIZE);
+        self.push(0u64);
+        self.op(Opcode::REVERT);
+        self.place_label(ok);
+
+        // Decode the return values.
+        if !f.returns.is_empty() {
+            self.push(32 * f.returns.len() as u64);
+            self.op(Opcode::RETURNDATASIZE);
+            self.op(Opcode::LT);
+            self.revert_if();
+        }
+        for (i, &ret) in f.returns.iter().enumerate() {
+            self.load_scratch(32 * i as u64);
+            self.validate_abi_value(gcx.type_of_item(ret.into()), expr.span);
+        }
+        self.return_ty(id)
+    }
+
+    /// Lowers `array.push(value)` on a storage array.
+    fn lower_array_push(
+        &mut self,
+        expr: &hir::Expr<'_>,
+        base: &hir::Expr<'_>,
+        args: &hir::CallArgs<'_>,
+    ) -> Ty<'gcx> {
+        let base_ty = self.expr_ty(base);
+        let TyKind::DynArray(elem) = base_ty.peel_refs().kind else { unreachable!() };
+        let Some(value) = args.exprs().next() else {
+            return self.unsupported_expr(expr, "`push()` without arguments");
+        };
+        if !is_storage_ref(base_ty)
+            || !elem.is_value_type()
+            || self.gcx.type_storage_bytes(elem) <= 16
+        {
+            return self.unsupported_expr(expr, "`push` on this array");
+        }
+        // `[slot value length]`
+        self.lower_expr(base);
+        self.lower_expr_to(value, elem);
+        self.dup(2);
+        self.op(Opcode::SLOAD);
+        // `sstore(slot, add(length, 1))`
+        self.dup(1);
+        self.push(1u64);
+        self.op(Opcode::ADD);
+        self.dup(4);
+        self.op(Opcode::SSTORE);
+        // `[slot element_slot value]`
+        self.elem_offset(elem);
+        self.dup(3);
+        self.array_data_slot();
+        self.op(Opcode::ADD);
+        self.swap(1);
+        self.store(Place::Storage(0, elem), elem);
+        self.op(Opcode::POP);
+        self.gcx.types.unit
+    }
+
+    /// Lowers `array.pop()` on a storage array.
+    fn lower_array_pop(&mut self, expr: &hir::Expr<'_>, base: &hir::Expr<'_>) -> Ty<'gcx> {
+        let base_ty = self.expr_ty(base);
+        let TyKind::DynArray(elem) = base_ty.peel_refs().kind else { unreachable!() };
+        if !is_storage_ref(base_ty)
+            || !elem.is_value_type()
+            || self.gcx.type_storage_bytes(elem) <= 16
+        {
+            return self.unsupported_expr(expr, "`pop` on this array");
+        }
+        // `[slot length]`
+        self.lower_expr(base);
+        self.dup(1);
+        self.op(Opcode::SLOAD);
+        self.dup(1);
+        self.op(Opcode::ISZERO);
+        self.panic_if(Panic::PopEmptyArray);
+        // `sstore(slot, sub(length, 1))`
+        self.push(1u64);
+        self.swap(1);
+        self.op(Opcode::SUB);
+        self.dup(1);
+        self.dup(3);
+        self.op(Opcode::SSTORE);
+        // `sstore(element_slot, 0)`
+        self.elem_offset(elem);
+        self.swap(1);
+        self.array_data_slot();
+        self.op(Opcode::ADD);
+        self.push(0u64);
+        self.swap(1);
+        self.op(Opcode::SSTORE);
+        self.gcx.types.unit
+    }
+}
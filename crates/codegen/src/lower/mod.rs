@@ -0,0 +1,899 @@
+//! Lowering of the HIR to EVM assembly.
+//!
+//! The generated code is a straightforward, unoptimized translation of the HIR:
+//! - all values are kept on the stack, one word per value, and are always clean, i.e. integers
+//!   are sign- or zero-extended and fixed-size byte arrays are left-aligned;
+//! - local variables live at a fixed stack position for the duration of their scope;
+//! - storage references are represented by their slot;
+//! - internal functions are called by pushing the return values, the return address and the
+//!   arguments, in that order, and jumping to the function entry. The callee leaves only the
+//!   return values on the stack before jumping back.
+//!
+//! When targeting EOF, internal functions are separate code sections called with `CALLF`, without
+//! a return address, and the code of each section uses its own copy of the shared panic and revert
+//! routines, since jumps cannot cross sections.
+//!
+//! Memory is only used as scratch space, starting at the free memory pointer, which is never
+//! bumped.
+
+use crate::{
+    asm::{AsmItem, Assembly, Label},
+    resolve::{is_abi_value_type, Resolver},
+    Opcode,
+};
+use alloy_primitives::U256;
+use solar_ast::{ElementaryType, StateMutability};
+use solar_data_structures::map::FxHashMap;
+use solar_interface::{diagnostics::ErrorGuaranteed, Span};
+use solar_sema::{
+    hir,
+    ty::{Gcx, Ty, TyKind},
+};
+
+mod expr;
+mod stmt;
+
+/// The free memory pointer.
+pub(crate) const FREE_MEMORY_POINTER: u64 = 0x40;
+/// The initial value of the free memory pointer.
+pub(crate) const MEMORY_START: u64 = 0x80;
+
+/// The selector of `Error(string)`.
+pub(crate) const ERROR_SELECTOR: u32 = 0x08c379a0;
+/// The selector of `Panic(uint256)`.
+pub(crate) const PANIC_SELECTOR: u32 = 0x4e487b71;
+
+/// Panic codes.
+///
+/// Reference: <https://docs.soliditylang.org/en/latest/control-structures.html#panic-via-assert-and-error-via-require>
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum Panic {
+    Assert = 0x01,
+    Arithmetic = 0x11,
+    DivisionByZero = 0x12,
+    EnumConversion = 0x21,
+    PopEmptyArray = 0x31,
+    IndexOutOfBounds = 0x32,
+}
+
+/// The entry labels of the functions of a contract in its runtime code.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct FunctionLabels {
+    /// The ABI entry points of the external functions, including the receive and fallback
+    /// functions, which are jumped to by the dispatcher.
+    pub(crate) external: Vec<(hir::FunctionId, Label)>,
+    /// The entries of the internal functions that are called.
+    pub(crate) internal: Vec<(hir::FunctionId, Label)>,
+}
+
+/// Lowers the runtime code of the given contract.
+pub(crate) fn lower_runtime(
+    gcx: Gcx<'_>,
+    id: hir::ContractId,
+) -> Result<(Assembly, FunctionLabels), ErrorGuaranteed> {
+    let mut cx = ContractCx::new(gcx, id);
+    let external = cx.lower_runtime();
+    let (asm, internal) = cx.finish()?;
+    let mut internal = internal.into_iter().collect::<Vec<_>>();
+    internal.sort_unstable_by_key(|&(_, label)| label);
+    Ok((asm, FunctionLabels { external, internal }))
+}
+
+/// Lowers the creation code of the given contract, which deploys `runtime`.
+pub(crate) fn lower_deploy(
+    gcx: Gcx<'_>,
+    id: hir::ContractId,
+    runtime: Assembly,
+) -> Result<Assembly, ErrorGuaranteed> {
+    let mut cx = ContractCx::new(gcx, id);
+    cx.lower_deploy(runtime);
+    cx.finish().map(|(asm, _)| asm)
+}
+
+/// The state of the lowering of a single assembly.
+struct ContractCx<'gcx> {
+    gcx: Gcx<'gcx>,
+    /// The contract being compiled.
+    contract: hir::ContractId,
+    asm: Assembly,
+    /// The storage slot and offset of each state variable.
+    storage: FxHashMap<hir::VariableId, (U256, u8)>,
+    /// The entry labels of the internal functions referenced so far.
+    functions: FxHashMap<hir::FunctionId, Label>,
+    /// The functions whose body has not been generated yet.
+    queue: Vec<hir::FunctionId>,
+    /// The labels of the shared panic routines.
+    panics: Vec<(Panic, Label)>,
+    /// The label of the shared `revert(0, 0)` routine.
+    revert: Option<Label>,
+    /// Set if an error was emitted.
+    error: Option<ErrorGuaranteed>,
+}
+
+impl<'gcx> ContractCx<'gcx> {
+    fn new(gcx: Gcx<'gcx>, contract: hir::ContractId) -> Self {
+        let storage = gcx
+            .storage_layout(contract)
+            .items
+            .iter()
+            .map(|item| (item.id, (item.slot, item.offset)))
+            .collect();
+        Self {
+            gcx,
+            contract,
+            asm: Assembly::new(gcx.sess.evm_version),
+            storage,
+            functions: FxHashMap::default(),
+            queue: Vec::new(),
+            panics: Vec::new(),
+            revert: None,
+            error: None,
+        }
+    }
+
+    fn err(&mut self, span: Span, msg: impl Into<String>) {
+        let msg = msg.into();
+        self.error = Some(self.gcx.dcx().err(msg).span(span).emit());
+    }
+
+    fn unsupported(&mut self, span: Span, what: &str) {
+        self.err(span, format!("{what} is not yet supported by the code generator"));
+    }
+
+    /// Returns the entry label of the given internal function, scheduling its body to be
+    /// generated.
+    fn function_label(&mut self, id: hir::FunctionId) -> Label {
+        *self.functions.entry(id).or_insert_with(|| {
+            self.queue.push(id);
+            self.asm.new_function_label()
+        })
+    }
+
+    fn panic_label(&mut self, panic: Panic) -> Label {
+        if let Some(&(_, label)) = self.panics.iter().find(|(p, _)| *p == panic) {
+            return label;
+        }
+        let label = self.asm.new_label();
+        self.panics.push((panic, label));
+        label
+    }
+
+    fn revert_label(&mut self) -> Label {
+        *self.revert.get_or_insert_with(|| self.asm.new_label())
+    }
+
+    fn fcx(&mut self, function: Option<hir::FunctionId>) -> FunctionCx<'_, 'gcx> {
+        FunctionCx {
+            gcx: self.gcx,
+            resolver: Resolver::new(self.gcx, self.contract, function),
+            cx: self,
+            height: 0,
+            vars: FxHashMap::default(),
+            loops: Vec::new(),
+            ret: None,
+            level: 0,
+            unchecked: false,
+            reported_stack_too_deep: false,
+        }
+    }
+
+    /// Generates the queued function bodies and the shared routines, and returns the assembly
+    /// along with the entry labels of the internal functions.
+    fn finish(mut self) -> Result<(Assembly, FxHashMap<hir::FunctionId, Label>), ErrorGuaranteed> {
+        let eof = self.asm.is_eof();
+        if eof {
+            self.lower_routines();
+        }
+        while let Some(id) = self.queue.pop() {
+            let label = self.functions[&id];
+            self.fcx(Some(id)).lower_function(id, label);
+            if eof {
+                self.lower_routines();
+            }
+        }
+        if !eof {
+            self.lower_routines();
+        }
+
+        match self.error {
+            Some(guar) => Err(guar),
+            None => Ok((self.asm, self.functions)),
+        }
+    }
+
+    /// Generates the shared routines referenced since the last call.
+    fn lower_routines(&mut self) {
+        self.asm.set_span(Span::DUMMY);
+        for (panic, label) in std::mem::take(&mut self.panics) {
+            self.asm.place_label(label);
+            self.asm.push_value(U256::from(PANIC_SELECTOR) << 224);
+            self.asm.push_value(0u64);
+            self.asm.push(Opcode::MSTORE);
+            self.asm.push_value(panic as u64);
+            self.asm.push_value(4u64);
+            self.asm.push(Opcode::MSTORE);
+            self.asm.push_value(0x24u64);
+            self.asm.push_value(0u64);
+            self.asm.push(Opcode::REVERT);
+        }
+        if let Some(label) = self.revert.take() {
+            self.asm.place_label(label);
+            self.asm.push_value(0u64);
+            self.asm.push(Opcode::DUP1);
+            self.asm.push(Opcode::REVERT);
+        }
+    }
+
+    /// Initializes the free memory pointer.
+    fn init_memory(&mut self) {
+        self.asm.push_value(MEMORY_START);
+        self.asm.push_value(FREE_MEMORY_POINTER);
+        self.asm.push(Opcode::MSTORE);
+    }
+
+    /// Reverts if the call has a value attached.
+    fn check_callvalue(&mut self) {
+        let revert = self.revert_label();
+        self.asm.push(Opcode::CALLVALUE);
+        self.asm.jump_if(revert);
+    }
+
+    fn lower_deploy(&mut self, runtime: Assembly) {
+        let gcx = self.gcx;
+        let c = gcx.hir.contract(self.contract);
+        self.asm.set_span(c.span);
+        self.init_memory();
+        let ctor = c.ctor.map(|id| gcx.hir.function(id));
+        if ctor.is_none_or(|f| f.state_mutability != StateMutability::Payable) {
+            self.check_callvalue();
+        }
+
+        // Constructor arguments are appended to the creation code, or passed as calldata in EOF.
+        let params = ctor.map(|f| f.parameters).unwrap_or_default();
+        if !params.is_empty() {
+            let revert = self.revert_label();
+            if self.asm.is_eof() {
+                // `calldatacopy(0x80, 0, calldatasize())`
+                self.asm.push(Opcode::CALLDATASIZE);
+                self.asm.push(Opcode::DUP1);
+                self.asm.push_value(0u64);
+                self.asm.push_value(MEMORY_START);
+                self.asm.push(Opcode::CALLDATACOPY);
+            } else {
+                // `codecopy(0x80, programSize, sub(codesize(), programSize))`
+                self.asm.push(AsmItem::PushProgramSize);
+                self.asm.push(Opcode::CODESIZE);
+                self.asm.push(Opcode::SUB);
+                self.asm.push(Opcode::DUP1);
+                self.asm.push(AsmItem::PushProgramSize);
+                self.asm.push_value(MEMORY_START);
+                self.asm.push(Opcode::CODECOPY);
+            }
+            // `if lt(size, 32 * params) { revert(0, 0) }`
+            self.asm.push_value(32 * params.len() as u64);
+            self.asm.push(Opcode::DUP2);
+            self.asm.push(Opcode::LT);
+            self.asm.jump_if(revert);
+            // `mstore(0x40, add(0x80, size))`
+            self.asm.push_value(MEMORY_START);
+            self.asm.push(Opcode::ADD);
+            self.asm.push_value(FREE_MEMORY_POINTER);
+            self.asm.push(Opcode::MSTORE);
+        }
+
+        // State variable initializers and constructors, from the most base contract.
+        for &base_id in c.linearized_bases.iter().rev() {
+            let base = gcx.hir.contract(base_id);
+            for var_id in base.variables() {
+                let var = gcx.hir.variable(var_id);
+                let Some(init) = var.initializer else { continue };
+                if var.is_constant() {
+                    continue;
+                }
+                let mut fcx = self.fcx(None);
+                if var.is_immutable() {
+                    fcx.cx.unsupported(var.span, "immutable variables");
+                    continue;
+                }
+                let prev = fcx.cx.asm.set_span(var.span);
+                let ty = gcx.type_of_item(var_id.into());
+                fcx.store_state_var_with(var_id, ty, |fcx| {
+                    fcx.lower_expr_to(init, ty);
+                });
+                fcx.cx.asm.set_span(prev);
+            }
+
+            let Some(ctor_id) = base.ctor else { continue };
+            let ctor = gcx.hir.function(ctor_id);
+            if base_id != self.contract && !ctor.parameters.is_empty() {
+                self.unsupported(ctor.span, "base constructor arguments");
+                continue;
+            }
+            self.asm.set_span(ctor.span);
+            let mut fcx = self.fcx(None);
+            fcx.call_function(ctor_id, |fcx| {
+                for (i, &param) in ctor.parameters.iter().enumerate() {
+                    let ty = fcx.gcx.type_of_item(param.into());
+                    fcx.push(MEMORY_START + 32 * i as u64);
+                    fcx.op(Opcode::MLOAD);
+                    fcx.validate_abi_value(ty, gcx.hir.variable(param).span);
+                }
+            });
+        }
+
+        // Return the runtime code.
+        self.asm.set_span(c.span);
+        let sub = self.asm.add_sub(runtime);
+        if self.asm.is_eof() {
+            // The auxiliary data is already part of the runtime container.
+            self.asm.push_value(0u64);
+            self.asm.push_value(0u64);
+            self.asm.push(AsmItem::ReturnContract(sub));
+            return;
+        }
+        self.asm.push(AsmItem::PushSubSize(sub));
+        self.asm.push(Opcode::DUP1);
+        self.asm.push(AsmItem::PushSubOffset(sub));
+        self.asm.push_value(0u64);
+        self.asm.push(Opcode::CODECOPY);
+        self.asm.push_value(0u64);
+        self.asm.push(Opcode::RETURN);
+    }
+
+    /// Lowers the dispatcher and the ABI entry points of the external functions, returning their
+    /// labels.
+    fn lower_runtime(&mut self) -> Vec<(hir::FunctionId, Label)> {
+        let gcx = self.gcx;
+        let c = gcx.hir.contract(self.contract);
+        self.asm.set_span(c.span);
+        self.init_memory();
+
+        let functions = gcx.interface_functions(self.contract).all();
+        let no_selector = self.asm.new_label();
+        let mut stubs = Vec::with_capacity(functions.len());
+        if !functions.is_empty() {
+            // `if lt(calldatasize(), 4) { goto no_selector }`
+            self.asm.push_value(4u64);
+            self.asm.push(Opcode::CALLDATASIZE);
+            self.asm.push(Opcode::LT);
+            self.asm.jump_if(no_selector);
+            // `selector := shr(224, calldataload(0))`
+            self.asm.push_value(0u64);
+            self.asm.push(Opcode::CALLDATALOAD);
+            let mut fcx = self.fcx(None);
+            fcx.height = 1;
+            fcx.shr_const(224);
+            for f in functions {
+                let stub = self.asm.new_label();
+                self.asm.push(Opcode::DUP1);
+                self.asm.push_value(U256::from_be_slice(f.selector.as_slice()));
+                self.asm.push(Opcode::EQ);
+                self.asm.jump_if(stub);
+                stubs.push((f.id, stub));
+            }
+            self.asm.push(Opcode::POP);
+        }
+        self.asm.place_label(no_selector);
+
+        let receive = c
+            .receive
+            .or_else(|| c.linearized_bases.iter().find_map(|&base| gcx.hir.contract(base).receive));
+        let fallback = c.fallback.or_else(|| {
+            c.linearized_bases.iter().find_map(|&base| gcx.hir.contract(base).fallback)
+        });
+        if let Some(receive) = receive {
+            let label = self.asm.new_label();
+            self.asm.push(Opcode::CALLDATASIZE);
+            self.asm.push(Opcode::ISZERO);
+            self.asm.jump_if(label);
+            stubs.push((receive, label));
+        }
+        match fallback {
+            Some(fallback) => {
+                let label = self.asm.new_label();
+                self.asm.jump_to(label);
+                stubs.push((fallback, label));
+            }
+            None => {
+                let revert = self.revert_label();
+                self.asm.jump_to(revert);
+            }
+        }
+
+        for &(id, label) in &stubs {
+            self.lower_external_stub(id, label);
+        }
+        stubs
+    }
+
+    /// Lowers the ABI entry point of the given function.
+    fn lower_external_stub(&mut self, id: hir::FunctionId, label: Label) {
+        let gcx = self.gcx;
+        let f = gcx.hir.function(id);
+        self.asm.set_span(f.span);
+        self.asm.place_label(label);
+        if f.kind.is_ordinary() {
+            // Pop the selector.
+            self.asm.push(Opcode::POP);
+        }
+        if f.state_mutability != StateMutability::Payable {
+            self.check_callvalue();
+        }
+        if f.kind.is_fallback() && !f.parameters.is_empty() {
+            self.unsupported(f.span, "fallback functions with parameters");
+            return;
+        }
+
+        for &param in f.parameters.iter().chain(f.returns) {
+            let var = gcx.hir.variable(param);
+            let ty = gcx.type_of_item(param.into());
+            if !is_abi_value_type(ty) {
+                self.unsupported(var.span, "ABI encoding of reference types");
+                return;
+            }
+        }
+
+        if !f.parameters.is_empty() {
+            // `if lt(calldatasize(), add(4, mul(32, params))) { revert(0, 0) }`
+            let revert = self.revert_label();
+            self.asm.push_value(4 + 32 * f.parameters.len() as u64);
+            self.asm.push(Opcode::CALLDATASIZE);
+            self.asm.push(Opcode::LT);
+            self.asm.jump_if(revert);
+        }
+
+        let mut fcx = self.fcx(None);
+        fcx.call_function(id, |fcx| {
+            for (i, &param) in f.parameters.iter().enumerate() {
+                let ty = fcx.gcx.type_of_item(param.into());
+                fcx.push(4 + 32 * i as u64);
+                fcx.op(Opcode::CALLDATALOAD);
+                fcx.validate_abi_value(ty, gcx.hir.variable(param).span);
+            }
+        });
+
+        if f.returns.is_empty() {
+            fcx.op(Opcode::STOP);
+            return;
+        }
+        // Encode the return values at the free memory pointer.
+        for i in (0..f.returns.len()).rev() {
+            fcx.push(FREE_MEMORY_POINTER);
+            fcx.op(Opcode::MLOAD);
+            fcx.push(32 * i as u64);
+            fcx.op(Opcode::ADD);
+            fcx.op(Opcode::MSTORE);
+        }
+        fcx.push(32 * f.returns.len() as u64);
+        fcx.push(FREE_MEMORY_POINTER);
+        fcx.op(Opcode::MLOAD);
+        fcx.op(Opcode::RETURN);
+    }
+}
+
+/// A jump target along with the stack height expected there.
+#[derive(Clone, Copy, Debug)]
+struct Target {
+    label: Label,
+    height: usize,
+}
+
+/// The targets of `break` and `continue` statements.
+#[derive(Clone, Copy, Debug)]
+struct LoopTargets {
+    break_: Target,
+    continue_: Target,
+}
+
+/// The state of the lowering of a function body, or of other code that uses the stack, such as
+/// state variable initializers.
+struct FunctionCx<'a, 'gcx> {
+    gcx: Gcx<'gcx>,
+    cx: &'a mut ContractCx<'gcx>,
+    /// Resolves expressions in the function being lowered, if any.
+    resolver: Resolver<'gcx>,
+    /// The current stack height, relative to the start of the frame.
+    height: usize,
+    /// The stack position of each local variable, parameter and return variable in scope.
+    vars: FxHashMap<hir::VariableId, usize>,
+    /// The enclosing loops.
+    loops: Vec<LoopTargets>,
+    /// The target of `return` statements.
+    ret: Option<Target>,
+    /// The index of the modifier being lowered. Equal to the number of modifiers when lowering
+    /// the function body.
+    level: usize,
+    /// Whether arithmetic is unchecked.
+    unchecked: bool,
+    /// Whether a stack too deep error was emitted for this frame, which is only reported once.
+    reported_stack_too_deep: bool,
+}
+
+impl<'gcx> std::ops::Deref for FunctionCx<'_, 'gcx> {
+    type Target = Resolver<'gcx>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.resolver
+    }
+}
+
+impl<'gcx> FunctionCx<'_, 'gcx> {
+    // --- Emission helpers ---
+
+    /// Appends an opcode, keeping track of the stack height.
+    fn op(&mut self, op: Opcode) {
+        if !op.is_available(self.cx.asm.evm_version()) {
+            let msg = format!(
+                "the `{op}` instruction is not available in the selected EVM version ({})",
+                self.cx.asm.evm_version()
+            );
+            let span = self.cx.asm.span();
+            self.cx.err(span, msg);
+        }
+        debug_assert!(self.height >= op.inputs() as usize, "stack underflow at {op}");
+        self.height = self.height.saturating_sub(op.inputs() as usize) + op.outputs() as usize;
+        self.cx.asm.push(op);
+    }
+
+    /// Pushes a value.
+    fn push(&mut self, value: impl Into<U256>) {
+        self.height += 1;
+        self.cx.asm.push_value(value);
+    }
+
+    /// Pushes `n` zeros.
+    fn push_zeros(&mut self, n: usize) {
+        for _ in 0..n {
+            self.push(0u64);
+        }
+    }
+
+    /// Pushes the offset of a label.
+    fn push_label(&mut self, label: Label) {
+        self.height += 1;
+        self.cx.asm.push_label(label);
+    }
+
+    /// Pushes the address of the given library, to be filled in when linking.
+    fn push_library(&mut self, id: hir::ContractId) {
+        let name = self.gcx.contract_fully_qualified_name(id).to_string();
+        let library = self.cx.asm.add_library(name);
+        self.height += 1;
+        self.cx.asm.push(AsmItem::PushLibrary(library));
+    }
+
+    fn new_label(&mut self) -> Label {
+        self.cx.asm.new_label()
+    }
+
+    fn place_label(&mut self, label: Label) {
+        self.cx.asm.place_label(label);
+    }
+
+    fn jump_to(&mut self, label: Label) {
+        self.cx.asm.jump_to(label);
+    }
+
+    /// Jumps to the given label if the value on top of the stack is non-zero, consuming it.
+    fn jump_if(&mut self, label: Label) {
+        debug_assert!(self.height >= 1, "stack underflow at jump");
+        self.height = self.height.saturating_sub(1);
+        self.cx.asm.jump_if(label);
+    }
+
+    /// Panics with the given code if the value on top of the stack is non-zero, consuming it.
+    fn panic_if(&mut self, panic: Panic) {
+        let label = self.cx.panic_label(panic);
+        self.jump_if(label);
+    }
+
+    /// Reverts without data if the value on top of the stack is non-zero, consuming it.
+    fn revert_if(&mut self) {
+        let label = self.cx.revert_label();
+        self.jump_if(label);
+    }
+
+    /// Appends `DUPn`.
+    fn dup(&mut self, n: usize) {
+        match Opcode::dup(n) {
+            Some(op) => self.op(op),
+            None => {
+                self.stack_too_deep(None);
+                self.height += 1;
+            }
+        }
+    }
+
+    /// Appends `SWAPn`.
+    fn swap(&mut self, n: usize) {
+        match Opcode::swap(n) {
+            Some(op) => self.op(op),
+            None => self.stack_too_deep(None),
+        }
+    }
+
+    /// Pushes a copy of the value at the given stack position.
+    fn dup_at(&mut self, pos: usize) {
+        match Opcode::dup(self.height - pos) {
+            Some(op) => self.op(op),
+            None => {
+                self.stack_too_deep(Some(pos));
+                self.height += 1;
+            }
+        }
+    }
+
+    /// Moves the value on top of the stack to the given stack position, replacing its value.
+    fn store_at(&mut self, pos: usize) {
+        match Opcode::swap(self.height - 1 - pos) {
+            Some(op) => self.op(op),
+            None => self.stack_too_deep(Some(pos)),
+        }
+        self.op(Opcode::POP);
+    }
+
+    /// Reports that the value at the given stack position, or a temporary value if `None`, is out
+    /// of reach of `DUP16` and `SWAP16`.
+    ///
+    /// The diagnostic points at the variable being accessed and at the variables in scope that
+    /// were pushed after it, since those are the ones that push it out of reach.
+    fn stack_too_deep(&mut self, pos: Option<usize>) {
+        if std::mem::replace(&mut self.reported_stack_too_deep, true) {
+            return;
+        }
+        let gcx = self.gcx;
+        let span = self.cx.asm.span();
+        let mut vars = self.vars.iter().map(|(&id, &pos)| (pos, id)).collect::<Vec<_>>();
+        vars.sort_unstable_by_key(|&(pos, _)| pos);
+        let describe = |id: hir::VariableId| match gcx.hir.variable(id).name {
+            Some(name) => format!("`{name}`"),
+            None => "an unnamed variable".to_string(),
+        };
+
+        let target = pos.and_then(|pos| vars.iter().find(|&&(p, _)| p == pos).map(|&(_, id)| id));
+        let (msg, pressure) = match (pos, target) {
+            (Some(pos), Some(id)) => {
+                let msg = format!(
+                    "stack too deep: {} is {} slots deep, but only the top 16 slots are reachable",
+                    describe(id),
+                    self.height - pos,
+                );
+                (msg, vars.iter().filter(|&&(p, _)| p > pos).map(|&(_, id)| id).collect())
+            }
+            _ => (
+                "stack too deep: a temporary value is out of reach of the top 16 stack slots"
+                    .to_string(),
+                vars.iter().map(|&(_, id)| id).collect::<Vec<_>>(),
+            ),
+        };
+
+        let mut diag = gcx.dcx().err(msg).span(span).span_label(span, "accessed here");
+        if let Some(id) = target {
+            diag = diag
+                .span_note(gcx.hir.variable(id).span, format!("{} is declared here", describe(id)));
+        }
+        if !pressure.is_empty() {
+            let spans = pressure.iter().map(|&id| gcx.hir.variable(id).span).collect::<Vec<_>>();
+            let names = pressure.iter().map(|&id| describe(id)).collect::<Vec<_>>().join(", ");
+            let msg = format!(
+                "{} {} on the stack {}: {names}",
+                pressure.len(),
+                if pressure.len() == 1 { "variable is" } else { "variables are" },
+                if target.is_some() { "above it" } else { "in this frame" },
+            );
+            diag = diag.span_note(spans, msg);
+        }
+        let diag = diag.help(
+            "reduce the number of variables in scope, for example by moving some into a nested \
+             block, a struct in memory or a separate function",
+        );
+        self.cx.error = Some(diag.emit());
+    }
+
+    /// Pops values until the stack has the given height.
+    fn pop_to(&mut self, height: usize) {
+        while self.height > height {
+            self.op(Opcode::POP);
+        }
+    }
+
+    /// Shifts the value on top of the stack left by a constant amount.
+    fn shl_const(&mut self, bits: u32) {
+        if bits == 0 {
+            return;
+        }
+        if self.has_shifts() {
+            self.push(bits as u64);
+            self.op(Opcode::SHL);
+        } else {
+            self.push(U256::from(1) << bits);
+            self.op(Opcode::MUL);
+        }
+    }
+
+    /// Shifts the value on top of the stack right by a constant amount.
+    fn shr_const(&mut self, bits: u32) {
+        if bits == 0 {
+            return;
+        }
+        if self.has_shifts() {
+            self.push(bits as u64);
+            self.op(Opcode::SHR);
+        } else {
+            self.push(U256::from(1) << bits);
+            self.swap(1);
+            self.op(Opcode::DIV);
+        }
+    }
+
+    fn has_shifts(&self) -> bool {
+        Opcode::SHL.is_available(self.cx.asm.evm_version())
+    }
+
+    // --- Functions ---
+
+    /// Calls an internal function. `args` must push the arguments.
+    ///
+    /// Leaves the return values on the stack.
+    fn call_function(&mut self, id: hir::FunctionId, args: impl FnOnce(&mut Self)) {
+        let f = self.gcx.hir.function(id);
+        let height = self.height;
+        self.push_zeros(f.returns.len());
+        if self.cx.asm.is_eof() {
+            args(self);
+            debug_assert_eq!(self.height, height + f.returns.len() + f.parameters.len());
+            let entry = self.cx.function_label(id);
+            self.cx.asm.push(AsmItem::CallF(entry));
+        } else {
+            let ret = self.new_label();
+            self.push_label(ret);
+            args(self);
+            debug_assert_eq!(self.height, height + f.returns.len() + 1 + f.parameters.len());
+            let entry = self.cx.function_label(id);
+            self.jump_to(entry);
+            self.place_label(ret);
+        }
+        self.height = height + f.returns.len();
+    }
+
+    /// Lowers the body of an internal function, placing it at the given label.
+    fn lower_function(&mut self, id: hir::FunctionId, label: Label) {
+        let f = self.gcx.hir.function(id);
+        let prev_span = self.cx.asm.set_span(f.span);
+        let eof = self.cx.asm.is_eof();
+        if eof {
+            let inputs = f.returns.len() + f.parameters.len();
+            if inputs > 127 {
+                self.cx.err(
+                    f.span,
+                    "functions can have at most 127 parameters and return values in EOF",
+                );
+            }
+            self.cx.asm.push(AsmItem::CodeSection {
+                inputs: inputs.min(127) as u8,
+                outputs: f.returns.len().min(127) as u8,
+            });
+        }
+        self.place_label(label);
+
+        // The frame is `returns..., return address, parameters...`, without the return address
+        // in EOF.
+        for (i, &ret) in f.returns.iter().enumerate() {
+            self.vars.insert(ret, i);
+        }
+        let frame_end = f.returns.len() + (!eof) as usize;
+        for (i, &param) in f.parameters.iter().enumerate() {
+            self.vars.insert(param, frame_end + i);
+        }
+        self.height = frame_end + f.parameters.len();
+
+        // Return variables of reference types are unsupported, but value types default to zero,
+        // which is what the caller pushed.
+        for &ret in f.returns {
+            let ty = self.gcx.type_of_item(ret.into());
+            if !ty.is_value_type() {
+                self.cx.unsupported(self.gcx.hir.variable(ret).span, "returning reference types");
+            }
+        }
+
+        match f.body {
+            Some(_) => self.lower_modifier_level(id, 0),
+            None => self.cx.unsupported(f.span, "calling functions without an implementation"),
+        }
+
+        self.pop_to(frame_end);
+        self.op(if eof { Opcode::RETF } else { Opcode::JUMP });
+        self.cx.asm.set_span(prev_span);
+    }
+
+    /// Lowers the modifier at the given index of the modifier chain, or the function body if it is
+    /// past the last modifier.
+    ///
+    /// `return` statements jump to the end of the current modifier or body, after which control
+    /// flow continues after the `_` of the enclosing modifier.
+    fn lower_modifier_level(&mut self, id: hir::FunctionId, level: usize) {
+        let f = self.gcx.hir.function(id);
+        let end = self.new_label();
+        let prev_ret = self.ret.replace(Target { label: end, height: self.height });
+        let prev_level = std::mem::replace(&mut self.level, level);
+        if let Some(&modifier) = f.modifiers.get(level) {
+            match modifier {
+                hir::ItemId::Function(m) => {
+                    let m = self.resolve_virtual(m);
+                    let modifier = self.gcx.hir.function(m);
+                    if !modifier.parameters.is_empty() {
+                        self.cx.unsupported(modifier.span, "modifiers with parameters");
+                    } else if let Some(body) = modifier.body {
+                        self.lower_block(body);
+                    }
+                }
+                // Base constructors are called separately.
+                _ => self.lower_modifier_level(id, level + 1),
+            }
+        } else {
+            self.lower_block(f.body.unwrap());
+        }
+        self.place_label(end);
+        self.level = prev_level;
+        self.ret = prev_ret;
+    }
+
+    /// Lowers a `_` placeholder statement in the current modifier.
+    fn lower_placeholder(&mut self) {
+        let id = self.function.expect("placeholder outside of function");
+        self.lower_modifier_level(id, self.level + 1);
+    }
+
+    // --- Values ---
+
+    /// Cleans the value on top of the stack, i.e. zeroes or sign-extends the unused bits.
+    fn cleanup(&mut self, ty: Ty<'gcx>) {
+        match ty.kind {
+            TyKind::Elementary(ElementaryType::Bool) => {
+                self.op(Opcode::ISZERO);
+                self.op(Opcode::ISZERO);
+            }
+            TyKind::Elementary(ElementaryType::UInt(size)) if size.bits() < 256 => {
+                self.push((U256::from(1) << size.bits()) - U256::from(1));
+                self.op(Opcode::AND);
+            }
+            TyKind::Elementary(ElementaryType::Int(size)) if size.bits() < 256 => {
+                self.push(size.bytes() as u64 - 1);
+                self.op(Opcode::SIGNEXTEND);
+            }
+            TyKind::Elementary(ElementaryType::Address(_)) | TyKind::Contract(_) => {
+                self.push((U256::from(1) << 160) - U256::from(1));
+                self.op(Opcode::AND);
+            }
+            TyKind::Elementary(ElementaryType::FixedBytes(size)) if size.bytes() < 32 => {
+                self.push(!((U256::from(1) << (256 - size.bits() as usize)) - U256::from(1)));
+                self.op(Opcode::AND);
+            }
+            TyKind::Udvt(inner, _) => self.cleanup(inner),
+            _ => {}
+        }
+    }
+
+    /// Reverts if the ABI-decoded value on top of the stack is not valid for the given type.
+    fn validate_abi_value(&mut self, ty: Ty<'gcx>, span: Span) {
+        if !is_abi_value_type(ty) {
+            self.cx.unsupported(span, "ABI decoding of reference types");
+            return;
+        }
+        if let TyKind::Enum(id) = ty.kind {
+            let variants = self.gcx.hir.enumm(id).variants.len();
+            self.push(variants as u64);
+            self.dup(2);
+            self.op(Opcode::LT);
+            self.op(Opcode::ISZERO);
+            self.revert_if();
+            return;
+        }
+        self.dup(1);
+        self.dup(1);
+        self.cleanup(ty);
+        self.op(Opcode::EQ);
+        self.op(Opcode::ISZERO);
+        self.revert_if();
+    }
+}
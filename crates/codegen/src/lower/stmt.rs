@@ -0,0 +1,340 @@
+use super::{FunctionCx, LoopTargets, Panic, Target};
+use crate::{
+    resolve::{is_abi_value_type, stack_slots},
+    Opcode,
+};
+use alloy_primitives::U256;
+use solar_interface::Span;
+use solar_sema::hir;
+
+impl<'gcx> FunctionCx<'_, 'gcx> {
+    /// Lowers a block, popping the variables declared in it at the end.
+    pub(super) fn lower_block(&mut self, block: hir::Block<'_>) {
+        let height = self.height;
+        for stmt in block {
+            self.lower_stmt(stmt);
+        }
+        self.end_scope(height);
+    }
+
+    fn lower_stmt(&mut self, stmt: &hir::Stmt<'_>) {
+        let prev_span = self.cx.asm.set_span(stmt.span);
+        match stmt.kind {
+            hir::StmtKind::DeclSingle(id) => self.lower_decl(id),
+            hir::StmtKind::DeclMulti(vars, init) => {
+                let height = self.height;
+                match init.peel_parens().kind {
+                    hir::ExprKind::Tuple(exprs) if exprs.len() == vars.len() => {
+                        for (expr, var) in exprs.iter().zip(vars) {
+                            let expr = expr.expect("empty tuple component");
+                            match *var {
+                                Some(var) => {
+                                    let ty = self.gcx.type_of_item(var.into());
+                                    self.lower_expr_to(expr, ty);
+                                }
+                                None => _ = self.lower_expr(expr),
+                            }
+                        }
+                    }
+                    _ => _ = self.lower_expr(init),
+                }
+                // Unnamed components are left on the stack until the end of the block.
+                for (i, var) in vars.iter().enumerate() {
+                    if let Some(var) = *var {
+                        self.vars.insert(var, height + i);
+                    }
+                }
+            }
+            hir::StmtKind::Block(block) => self.lower_block(block),
+            hir::StmtKind::UncheckedBlock(block) => {
+                let prev = std::mem::replace(&mut self.unchecked, true);
+                self.lower_block(block);
+                self.unchecked = prev;
+            }
+            hir::StmtKind::Emit(res, ref args) => self.lower_emit(res, args, stmt.span),
+            hir::StmtKind::Revert(res, ref args) => self.lower_revert_error(res, args, stmt.span),
+            hir::StmtKind::Return(expr) => self.lower_return(expr),
+            hir::StmtKind::Break => {
+                let target = self.loops.last().expect("`break` outside of loop").break_;
+                self.jump_out(target);
+            }
+            hir::StmtKind::Continue => {
+                let target = self.loops.last().expect("`continue` outside of loop").continue_;
+                self.jump_out(target);
+            }
+            hir::StmtKind::Loop(block, _) => self.lower_loop(block),
+            hir::StmtKind::If(cond, then, else_) => {
+                let else_label = self.new_label();
+                self.lower_expr(cond);
+                self.op(Opcode::ISZERO);
+                self.jump_if(else_label);
+                self.lower_stmt_scoped(then);
+                if let Some(else_) = else_ {
+                    let end = self.new_label();
+                    self.jump_to(end);
+                    self.place_label(else_label);
+                    self.lower_stmt_scoped(else_);
+                    self.place_label(end);
+                } else {
+                    self.place_label(else_label);
+                }
+            }
+            hir::StmtKind::Try(_) => self.cx.unsupported(stmt.span, "`try` statements"),
+            hir::StmtKind::Expr(expr) => {
+                let height = self.height;
+                self.lower_expr(expr);
+                self.pop_to(height);
+            }
+            hir::StmtKind::Placeholder => self.lower_placeholder(),
+            hir::StmtKind::Err(_) => {}
+        }
+        self.cx.asm.set_span(prev_span);
+    }
+
+    /// Lowers a statement that is the body of a control flow statement, which is its own scope.
+    fn lower_stmt_scoped(&mut self, stmt: &hir::Stmt<'_>) {
+        let height = self.height;
+        self.lower_stmt(stmt);
+        self.end_scope(height);
+    }
+
+    /// Pops the variables declared since the stack had the given height, and forgets them.
+    fn end_scope(&mut self, height: usize) {
+        self.pop_to(height);
+        self.vars.retain(|_, &mut pos| pos < height);
+    }
+
+    fn lower_decl(&mut self, id: hir::VariableId) {
+        let var = self.gcx.hir.variable(id);
+        let ty = self.gcx.type_of_item(id.into());
+        match var.initializer {
+            Some(init) => self.lower_expr_to(init, ty),
+            None if ty.is_value_type() => self.push(0u64),
+            None => {
+                self.cx.unsupported(var.span, "uninitialized reference type variables");
+                self.push(0u64);
+            }
+        }
+        self.vars.insert(id, self.height - 1);
+    }
+
+    fn lower_return(&mut self, expr: Option<&hir::Expr<'_>>) {
+        let target = self.ret.expect("`return` outside of function");
+        if let Some(expr) = expr {
+            let f = self.gcx.hir.function(self.function.expect("`return` outside of function"));
+            let ret_tys =
+                f.returns.iter().map(|&ret| self.gcx.type_of_item(ret.into())).collect::<Vec<_>>();
+            match (&expr.peel_parens().kind, &ret_tys[..]) {
+                (_, [ty]) => self.lower_expr_to(expr, *ty),
+                (hir::ExprKind::Tuple(exprs), _) if exprs.len() == ret_tys.len() => {
+                    for (expr, &ty) in exprs.iter().zip(&ret_tys) {
+                        self.lower_expr_to(expr.expect("empty tuple component"), ty);
+                    }
+                }
+                _ => {
+                    let ty = self.lower_expr(expr);
+                    debug_assert_eq!(stack_slots(ty), ret_tys.len());
+                }
+            }
+            // The return variables are at the bottom of the frame.
+            for i in (0..f.returns.len()).rev() {
+                self.store_at(self.vars[&f.returns[i]]);
+            }
+        }
+        self.jump_out(target);
+    }
+
+    /// Jumps to a target outside of the current scope, popping the values in between.
+    ///
+    /// The stack height is restored afterwards, as the following code is either unreachable or
+    /// reached from elsewhere with the current height.
+    fn jump_out(&mut self, target: Target) {
+        let height = self.height;
+        self.pop_to(target.height);
+        self.jump_to(target.label);
+        self.height = height;
+    }
+
+    /// Lowers a loop.
+    ///
+    /// If the body has more than one statement, the last one is the continuation, which is where
+    /// `continue` jumps to. See [`hir::StmtKind::Loop`].
+    fn lower_loop(&mut self, block: hir::Block<'_>) {
+        let height = self.height;
+        let start = self.new_label();
+        let break_ = Target { label: self.new_label(), height };
+        self.place_label(start);
+        match block {
+            [body @ .., next] if !body.is_empty() => {
+                let continue_ = Target { label: self.new_label(), height };
+                self.loops.push(LoopTargets { break_, continue_ });
+                for stmt in body {
+                    self.lower_stmt(stmt);
+                }
+                self.pop_to(height);
+                self.place_label(continue_.label);
+                self.lower_stmt(next);
+            }
+            _ => {
+                let continue_ = Target { label: start, height };
+                self.loops.push(LoopTargets { break_, continue_ });
+                for stmt in block {
+                    self.lower_stmt(stmt);
+                }
+            }
+        }
+        self.loops.pop();
+        self.pop_to(height);
+        self.jump_to(start);
+        self.place_label(break_.label);
+    }
+
+    /// Lowers `emit Event(args)`.
+    fn lower_emit(&mut self, res: &[hir::Res], args: &hir::CallArgs<'_>, span: Span) {
+        let Some(id) = self.select_overload(res, args, |id| match id {
+            hir::ItemId::Event(id) => Some(id),
+            _ => None,
+        }) else {
+            self.cx.unsupported(span, "this event");
+            return;
+        };
+        let event = self.gcx.hir.event(id);
+        let params = self.ordered_args(event.parameters, args);
+
+        // Store all the arguments in memory, then push the topics back from memory:
+        // `data..., topics...`.
+        let indexed = event.parameters.iter().filter(|&&p| self.gcx.hir.variable(p).indexed);
+        let n_topics = indexed.count();
+        let n_data = event.parameters.len() - n_topics;
+        let mut offsets = Vec::with_capacity(params.len());
+        let (mut data_i, mut topic_i) = (0, n_data);
+        for (&param, arg) in event.parameters.iter().zip(&params) {
+            let ty = self.gcx.type_of_item(param.into());
+            if !is_abi_value_type(ty) {
+                self.cx.unsupported(arg.span, "events with reference type parameters");
+                return;
+            }
+            self.lower_expr_to(arg, ty);
+            if self.gcx.hir.variable(param).indexed {
+                offsets.push(topic_i);
+                topic_i += 1;
+            } else {
+                offsets.push(data_i);
+                data_i += 1;
+            }
+        }
+        for &offset in offsets.iter().rev() {
+            self.store_scratch(32 * offset as u64);
+        }
+        for i in (0..n_topics).rev() {
+            self.load_scratch(32 * (n_data + i) as u64);
+        }
+        if !event.anonymous {
+            let topic = self.gcx.event_selector(id);
+            self.push(U256::from_be_bytes(topic.0));
+        }
+        self.push(32 * n_data as u64);
+        self.push_scratch_ptr();
+        let topics = n_topics + !event.anonymous as usize;
+        self.op(Opcode::log(topics).unwrap());
+    }
+
+    /// Lowers `revert Error(args)`.
+    fn lower_revert_error(&mut self, res: &[hir::Res], args: &hir::CallArgs<'_>, span: Span) {
+        let Some(id) = self.select_overload(res, args, |id| match id {
+            hir::ItemId::Error(id) => Some(id),
+            _ => None,
+        }) else {
+            self.cx.unsupported(span, "this error");
+            return;
+        };
+        let error = self.gcx.hir.error(id);
+        let params = self.ordered_args(error.parameters, args);
+        for (&param, arg) in error.parameters.iter().zip(&params) {
+            let ty = self.gcx.type_of_item(param.into());
+            if !is_abi_value_type(ty) {
+                self.cx.unsupported(arg.span, "errors with reference type parameters");
+                return;
+            }
+            self.lower_expr_to(arg, ty);
+        }
+        let selector = self.gcx.function_selector(id);
+        self.push(U256::from_be_slice(selector.as_slice()) << 224);
+        self.store_scratch(0);
+        for i in (0..params.len()).rev() {
+            self.store_scratch(4 + 32 * i as u64);
+        }
+        self.push(4 + 32 * params.len() as u64);
+        self.push_scratch_ptr();
+        self.op(Opcode::REVERT);
+    }
+
+    /// Lowers `assert(cond)`.
+    pub(super) fn lower_assert(&mut self) {
+        self.op(Opcode::ISZERO);
+        self.panic_if(Panic::Assert);
+    }
+
+    /// Lowers `require(cond)` and `require(cond, "message")`, with the condition on the stack.
+    pub(super) fn lower_require(&mut self, message: Option<&[u8]>) {
+        let ok = self.new_label();
+        self.jump_if(ok);
+        self.lower_revert_message(message);
+        self.place_label(ok);
+    }
+
+    /// Reverts with `Error(message)`, or without data if `message` is `None`.
+    pub(super) fn lower_revert_message(&mut self, message: Option<&[u8]>) {
+        let height = self.height;
+        match message {
+            Some(message) => {
+                self.push(U256::from(super::ERROR_SELECTOR) << 224);
+                self.store_scratch(0);
+                self.push(0x20u64);
+                self.store_scratch(4);
+                self.push(message.len() as u64);
+                self.store_scratch(0x24);
+                for (i, chunk) in message.chunks(32).enumerate() {
+                    let mut word = [0u8; 32];
+                    word[..chunk.len()].copy_from_slice(chunk);
+                    self.push(U256::from_be_bytes(word));
+                    self.store_scratch(0x44 + 32 * i as u64);
+                }
+                self.push(0x44 + 32 * message.len().div_ceil(32) as u64);
+                self.push_scratch_ptr();
+                self.op(Opcode::REVERT);
+            }
+            None => {
+                let revert = self.cx.revert_label();
+                self.jump_to(revert);
+            }
+        }
+        self.height = height;
+    }
+
+    /// Pushes the scratch memory pointer, i.e. the free memory pointer.
+    pub(super) fn push_scratch_ptr(&mut self) {
+        self.push(super::FREE_MEMORY_POINTER);
+        self.op(Opcode::MLOAD);
+    }
+
+    /// Stores the value on top of the stack in scratch memory at the given offset.
+    pub(super) fn store_scratch(&mut self, offset: u64) {
+        self.push_scratch_ptr();
+        if offset != 0 {
+            self.push(offset);
+            self.op(Opcode::ADD);
+        }
+        self.op(Opcode::MSTORE);
+    }
+
+    /// Loads the word in scratch memory at the given offset.
+    pub(super) fn load_scratch(&mut self, offset: u64) {
+        self.push_scratch_ptr();
+        if offset != 0 {
+            self.push(offset);
+            self.op(Opcode::ADD);
+        }
+        self.op(Opcode::MLOAD);
+    }
+}
@@ -0,0 +1,436 @@
+use solar_config::EvmVersion;
+use std::fmt;
+
+macro_rules! opcodes {
+    ($($(#[$attr:meta])* $name:ident = $byte:literal, $inputs:literal => $outputs:literal $(, $version:ident)?;)*) => {
+        /// An EVM opcode, excluding the `PUSH` family which is represented by immediate values.
+        ///
+        /// Reference: <https://www.evm.codes>
+        #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+        #[allow(missing_docs)]
+        #[repr(u8)]
+        pub enum Opcode {
+            $(
+                $(#[$attr])*
+                $name = $byte,
+            )*
+        }
+
+        impl Opcode {
+            /// Returns the opcode for the given byte, if any.
+            pub const fn from_u8(byte: u8) -> Option<Self> {
+                match byte {
+                    $($byte => Some(Self::$name),)*
+                    _ => None,
+                }
+            }
+
+            /// Returns the mnemonic of the opcode.
+            pub const fn name(self) -> &'static str {
+                match self {
+                    $(Self::$name => stringify!($name),)*
+                }
+            }
+
+            /// Returns the number of stack items the opcode consumes.
+            pub const fn inputs(self) -> u8 {
+                match self {
+                    $(Self::$name => $inputs,)*
+                }
+            }
+
+            /// Returns the number of stack items the opcode produces.
+            pub const fn outputs(self) -> u8 {
+                match self {
+                    $(Self::$name => $outputs,)*
+                }
+            }
+
+            /// Returns the first EVM version that supports this opcode, if it is not supported by
+            /// all versions.
+            pub const fn min_evm_version(self) -> Option<EvmVersion> {
+                match self {
+                    $($(Self::$name => Some(EvmVersion::$version),)?)*
+                    _ => None,
+                }
+            }
+        }
+    };
+}
+
+opcodes! {
+    STOP = 0x00, 0 => 0;
+    ADD = 0x01, 2 => 1;
+    MUL = 0x02, 2 => 1;
+    SUB = 0x03, 2 => 1;
+    DIV = 0x04, 2 => 1;
+    SDIV = 0x05, 2 => 1;
+    MOD = 0x06, 2 => 1;
+    SMOD = 0x07, 2 => 1;
+    ADDMOD = 0x08, 3 => 1;
+    MULMOD = 0x09, 3 => 1;
+    EXP = 0x0a, 2 => 1;
+    SIGNEXTEND = 0x0b, 2 => 1;
+
+    LT = 0x10, 2 => 1;
+    GT = 0x11, 2 => 1;
+    SLT = 0x12, 2 => 1;
+    SGT = 0x13, 2 => 1;
+    EQ = 0x14, 2 => 1;
+    ISZERO = 0x15, 1 => 1;
+    AND = 0x16, 2 => 1;
+    OR = 0x17, 2 => 1;
+    XOR = 0x18, 2 => 1;
+    NOT = 0x19, 1 => 1;
+    BYTE = 0x1a, 2 => 1;
+    SHL = 0x1b, 2 => 1, Constantinople;
+    SHR = 0x1c, 2 => 1, Constantinople;
+    SAR = 0x1d, 2 => 1, Constantinople;
+
+    KECCAK256 = 0x20, 2 => 1;
+
+    ADDRESS = 0x30, 0 => 1;
+    BALANCE = 0x31, 1 => 1;
+    ORIGIN = 0x32, 0 => 1;
+    CALLER = 0x33, 0 => 1;
+    CALLVALUE = 0x34, 0 => 1;
+    CALLDATALOAD = 0x35, 1 => 1;
+    CALLDATASIZE = 0x36, 0 => 1;
+    CALLDATACOPY = 0x37, 3 => 0;
+    CODESIZE = 0x38, 0 => 1;
+    CODECOPY = 0x39, 3 => 0;
+    GASPRICE = 0x3a, 0 => 1;
+    EXTCODESIZE = 0x3b, 1 => 1;
+    EXTCODECOPY = 0x3c, 4 => 0;
+    RETURNDATASIZE = 0x3d, 0 => 1, Byzantium;
+    RETURNDATACOPY = 0x3e, 3 => 0, Byzantium;
+    EXTCODEHASH = 0x3f, 1 => 1, Constantinople;
+
+    BLOCKHASH = 0x40, 1 => 1;
+    COINBASE = 0x41, 0 => 1;
+    TIMESTAMP = 0x42, 0 => 1;
+    NUMBER = 0x43, 0 => 1;
+    /// `DIFFICULTY` before Paris.
+    PREVRANDAO = 0x44, 0 => 1;
+    GASLIMIT = 0x45, 0 => 1;
+    CHAINID = 0x46, 0 => 1, Istanbul;
+    SELFBALANCE = 0x47, 0 => 1, Istanbul;
+    BASEFEE = 0x48, 0 => 1, London;
+    BLOBHASH = 0x49, 1 => 1, Cancun;
+    BLOBBASEFEE = 0x4a, 0 => 1, Cancun;
+
+    POP = 0x50, 1 => 0;
+    MLOAD = 0x51, 1 => 1;
+    MSTORE = 0x52, 2 => 0;
+    MSTORE8 = 0x53, 2 => 0;
+    SLOAD = 0x54, 1 => 1;
+    SSTORE = 0x55, 2 => 0;
+    JUMP = 0x56, 1 => 0;
+    JUMPI = 0x57, 2 => 0;
+    PC = 0x58, 0 => 1;
+    MSIZE = 0x59, 0 => 1;
+    GAS = 0x5a, 0 => 1;
+    JUMPDEST = 0x5b, 0 => 0;
+    TLOAD = 0x5c, 1 => 1, Cancun;
+    TSTORE = 0x5d, 2 => 0, Cancun;
+    MCOPY = 0x5e, 3 => 0, Cancun;
+
+    DUP1 = 0x80, 1 => 2;
+    DUP2 = 0x81, 2 => 3;
+    DUP3 = 0x82, 3 => 4;
+    DUP4 = 0x83, 4 => 5;
+    DUP5 = 0x84, 5 => 6;
+    DUP6 = 0x85, 6 => 7;
+    DUP7 = 0x86, 7 => 8;
+    DUP8 = 0x87, 8 => 9;
+    DUP9 = 0x88, 9 => 10;
+    DUP10 = 0x89, 10 => 11;
+    DUP11 = 0x8a, 11 => 12;
+    DUP12 = 0x8b, 12 => 13;
+    DUP13 = 0x8c, 13 => 14;
+    DUP14 = 0x8d, 14 => 15;
+    DUP15 = 0x8e, 15 => 16;
+    DUP16 = 0x8f, 16 => 17;
+
+    SWAP1 = 0x90, 2 => 2;
+    SWAP2 = 0x91, 3 => 3;
+    SWAP3 = 0x92, 4 => 4;
+    SWAP4 = 0x93, 5 => 5;
+    SWAP5 = 0x94, 6 => 6;
+    SWAP6 = 0x95, 7 => 7;
+    SWAP7 = 0x96, 8 => 8;
+    SWAP8 = 0x97, 9 => 9;
+    SWAP9 = 0x98, 10 => 10;
+    SWAP10 = 0x99, 11 => 11;
+    SWAP11 = 0x9a, 12 => 12;
+    SWAP12 = 0x9b, 13 => 13;
+    SWAP13 = 0x9c, 14 => 14;
+    SWAP14 = 0x9d, 15 => 15;
+    SWAP15 = 0x9e, 16 => 16;
+    SWAP16 = 0x9f, 17 => 17;
+
+    LOG0 = 0xa0, 2 => 0;
+    LOG1 = 0xa1, 3 => 0;
+    LOG2 = 0xa2, 4 => 0;
+    LOG3 = 0xa3, 5 => 0;
+    LOG4 = 0xa4, 6 => 0;
+
+    DATALOAD = 0xd0, 1 => 1, Osaka;
+    DATALOADN = 0xd1, 0 => 1, Osaka;
+    DATASIZE = 0xd2, 0 => 1, Osaka;
+    DATACOPY = 0xd3, 3 => 0, Osaka;
+
+    RJUMP = 0xe0, 0 => 0, Osaka;
+    RJUMPI = 0xe1, 1 => 0, Osaka;
+    /// The stack effect depends on the type of the called code section.
+    CALLF = 0xe3, 0 => 0, Osaka;
+    RETF = 0xe4, 0 => 0, Osaka;
+    /// The stack effect depends on the type of the target code section.
+    JUMPF = 0xe5, 0 => 0, Osaka;
+    EOFCREATE = 0xec, 4 => 1, Osaka;
+    RETURNCONTRACT = 0xee, 2 => 0, Osaka;
+
+    CREATE = 0xf0, 3 => 1;
+    CALL = 0xf1, 7 => 1;
+    CALLCODE = 0xf2, 7 => 1;
+    RETURN = 0xf3, 2 => 0;
+    DELEGATECALL = 0xf4, 6 => 1;
+    CREATE2 = 0xf5, 4 => 1, Constantinople;
+    RETURNDATALOAD = 0xf7, 1 => 1, Osaka;
+    EXTCALL = 0xf8, 4 => 1, Osaka;
+    EXTDELEGATECALL = 0xf9, 3 => 1, Osaka;
+    STATICCALL = 0xfa, 6 => 1, Byzantium;
+    EXTSTATICCALL = 0xfb, 3 => 1, Osaka;
+    REVERT = 0xfd, 2 => 0, Byzantium;
+    INVALID = 0xfe, 0 => 0;
+    SELFDESTRUCT = 0xff, 1 => 0;
+}
+
+impl Opcode {
+    /// The byte of the `PUSH0` instruction. `PUSHn` is encoded as `PUSH0_BYTE + n`, followed by
+    /// `n` bytes of immediate data.
+    pub const PUSH0_BYTE: u8 = 0x5f;
+    pub(crate) const PUSH2_BYTE: u8 = Self::PUSH0_BYTE + 2;
+    pub(crate) const PUSH20_BYTE: u8 = Self::PUSH0_BYTE + 20;
+
+    /// Returns the `DUPn` opcode for the given `n`, which must be in `1..=16`.
+    pub fn dup(n: usize) -> Option<Self> {
+        (1..=16).contains(&n).then(|| Self::from_u8(Self::DUP1 as u8 + n as u8 - 1).unwrap())
+    }
+
+    /// Returns the `SWAPn` opcode for the given `n`, which must be in `1..=16`.
+    pub fn swap(n: usize) -> Option<Self> {
+        (1..=16).contains(&n).then(|| Self::from_u8(Self::SWAP1 as u8 + n as u8 - 1).unwrap())
+    }
+
+    /// Returns the `LOGn` opcode for the given number of topics, which must be in `0..=4`.
+    pub fn log(topics: usize) -> Option<Self> {
+        (topics <= 4).then(|| Self::from_u8(Self::LOG0 as u8 + topics as u8).unwrap())
+    }
+
+    /// Returns `true` if the opcode is available in the given EVM version.
+    ///
+    /// Opcodes that are only valid in legacy code are unavailable when targeting EOF.
+    pub fn is_available(self, evm_version: EvmVersion) -> bool {
+        self.min_evm_version().is_none_or(|min| evm_version >= min)
+            && !(evm_version.supports_eof() && self.is_legacy_only())
+    }
+
+    /// Returns `true` if the opcode is not valid in EOF code.
+    ///
+    /// Reference: <https://eips.ethereum.org/EIPS/eip-3670>
+    pub fn is_legacy_only(self) -> bool {
+        matches!(
+            self,
+            Self::CODESIZE
+                | Self::CODECOPY
+                | Self::EXTCODESIZE
+                | Self::EXTCODECOPY
+                | Self::EXTCODEHASH
+                | Self::JUMP
+                | Self::JUMPI
+                | Self::PC
+                | Self::GAS
+                | Self::JUMPDEST
+                | Self::CREATE
+                | Self::CALL
+                | Self::CALLCODE
+                | Self::DELEGATECALL
+                | Self::CREATE2
+                | Self::STATICCALL
+                | Self::SELFDESTRUCT
+        )
+    }
+
+    /// Returns the size of the immediate data following the opcode in EOF code.
+    pub fn immediate_size(self) -> usize {
+        match self {
+            Self::DATALOADN | Self::RJUMP | Self::RJUMPI | Self::CALLF | Self::JUMPF => 2,
+            Self::EOFCREATE | Self::RETURNCONTRACT => 1,
+            _ => 0,
+        }
+    }
+
+    /// Returns the static gas cost of the opcode as of the Cancun hard fork, or `None` if its cost
+    /// is unbounded, as for calls and contract creations.
+    ///
+    /// Accounts and storage slots are assumed to be cold, and `SSTORE` to set a zero slot.
+    /// Dynamic costs, such as memory expansion and the per-byte costs of copying, hashing and
+    /// logging, are not included, except for `EXP` whose exponent is assumed to be 32 bytes long.
+    ///
+    /// Reference: <https://www.evm.codes>
+    pub fn gas(self) -> Option<u64> {
+        Some(match self {
+            Self::STOP | Self::RETURN | Self::REVERT | Self::INVALID | Self::RETURNCONTRACT => 0,
+            Self::JUMPDEST => 1,
+            Self::ADDRESS
+            | Self::ORIGIN
+            | Self::CALLER
+            | Self::CALLVALUE
+            | Self::CALLDATASIZE
+            | Self::CODESIZE
+            | Self::GASPRICE
+            | Self::RETURNDATASIZE
+            | Self::COINBASE
+            | Self::TIMESTAMP
+            | Self::NUMBER
+            | Self::PREVRANDAO
+            | Self::GASLIMIT
+            | Self::CHAINID
+            | Self::BASEFEE
+            | Self::BLOBBASEFEE
+            | Self::POP
+            | Self::PC
+            | Self::MSIZE
+            | Self::GAS
+            | Self::DATASIZE
+            | Self::RJUMP => 2,
+            Self::ADD
+            | Self::SUB
+            | Self::LT
+            | Self::GT
+            | Self::SLT
+            | Self::SGT
+            | Self::EQ
+            | Self::ISZERO
+            | Self::AND
+            | Self::OR
+            | Self::XOR
+            | Self::NOT
+            | Self::BYTE
+            | Self::SHL
+            | Self::SHR
+            | Self::SAR
+            | Self::CALLDATALOAD
+            | Self::CALLDATACOPY
+            | Self::CODECOPY
+            | Self::RETURNDATACOPY
+            | Self::BLOBHASH
+            | Self::MLOAD
+            | Self::MSTORE
+            | Self::MSTORE8
+            | Self::MCOPY
+            | Self::DATALOADN
+            | Self::DATACOPY
+            | Self::RETF
+            | Self::RETURNDATALOAD => 3,
+            Self::DATALOAD | Self::RJUMPI => 4,
+            Self::MUL
+            | Self::DIV
+            | Self::SDIV
+            | Self::MOD
+            | Self::SMOD
+            | Self::SIGNEXTEND
+            | Self::SELFBALANCE
+            | Self::CALLF
+            | Self::JUMPF => 5,
+            Self::ADDMOD | Self::MULMOD | Self::JUMP => 8,
+            Self::JUMPI => 10,
+            Self::BLOCKHASH => 20,
+            Self::KECCAK256 => 30,
+            Self::TLOAD | Self::TSTORE => 100,
+            Self::EXP => 10 + 50 * 32,
+            Self::SLOAD => 2100,
+            Self::BALANCE | Self::EXTCODESIZE | Self::EXTCODECOPY | Self::EXTCODEHASH => 2600,
+            Self::SSTORE => 22100,
+            Self::LOG0 | Self::LOG1 | Self::LOG2 | Self::LOG3 | Self::LOG4 => {
+                375 * (1 + (self as u64 - Self::LOG0 as u64))
+            }
+            Self::SELFDESTRUCT => 5000 + 2600 + 25000,
+            Self::EOFCREATE
+            | Self::CREATE
+            | Self::CALL
+            | Self::CALLCODE
+            | Self::DELEGATECALL
+            | Self::CREATE2
+            | Self::EXTCALL
+            | Self::EXTDELEGATECALL
+            | Self::STATICCALL
+            | Self::EXTSTATICCALL => return None,
+            // `DUPn` and `SWAPn`.
+            _ => 3,
+        })
+    }
+
+    /// Returns `true` if the opcode unconditionally ends execution or jumps away.
+    pub fn is_terminator(self) -> bool {
+        matches!(
+            self,
+            Self::STOP
+                | Self::JUMP
+                | Self::RETURN
+                | Self::REVERT
+                | Self::INVALID
+                | Self::SELFDESTRUCT
+                | Self::RJUMP
+                | Self::RETF
+                | Self::JUMPF
+                | Self::RETURNCONTRACT
+        )
+    }
+}
+
+impl fmt::Display for Opcode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn roundtrip() {
+        for byte in 0..=u8::MAX {
+            if let Some(op) = Opcode::from_u8(byte) {
+                assert_eq!(op as u8, byte, "{op}");
+            }
+        }
+        assert_eq!(Opcode::dup(16), Some(Opcode::DUP16));
+        assert_eq!(Opcode::swap(17), None);
+        assert_eq!(Opcode::log(2), Some(Opcode::LOG2));
+    }
+
+    #[test]
+    fn availability() {
+        assert!(!Opcode::SHR.is_available(EvmVersion::Byzantium));
+        assert!(Opcode::SHR.is_available(EvmVersion::Constantinople));
+        assert!(Opcode::ADD.is_available(EvmVersion::Homestead));
+        assert!(!Opcode::TSTORE.is_available(EvmVersion::Shanghai));
+        assert!(Opcode::JUMP.is_available(EvmVersion::Prague));
+        assert!(!Opcode::JUMP.is_available(EvmVersion::Osaka));
+        assert!(!Opcode::RJUMP.is_available(EvmVersion::Prague));
+        assert!(Opcode::RJUMP.is_available(EvmVersion::Osaka));
+    }
+
+    #[test]
+    fn gas() {
+        assert_eq!(Opcode::ADD.gas(), Some(3));
+        assert_eq!(Opcode::DUP16.gas(), Some(3));
+        assert_eq!(Opcode::SWAP1.gas(), Some(3));
+        assert_eq!(Opcode::JUMPDEST.gas(), Some(1));
+        assert_eq!(Opcode::LOG2.gas(), Some(1125));
+        assert_eq!(Opcode::CALL.gas(), None);
+    }
+}
@@ -0,0 +1,43 @@
+use super::{take_stmt, visit_blocks_mut, Pass, PassCx};
+use solar_ast::yul;
+
+/// Inlines nested blocks into their parent block: `{ a { b } c }` becomes `{ a b c }`.
+///
+/// Blocks containing function definitions are kept, as their functions are only visible in the
+/// block.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct BlockFlattener;
+
+impl Pass for BlockFlattener {
+    fn name(&self) -> &'static str {
+        "block-flattener"
+    }
+
+    fn run<'ast>(&mut self, cx: &PassCx<'_, 'ast>, object: &mut yul::Object<'ast>) -> bool {
+        visit_blocks_mut(&mut object.code.code, &mut |block| {
+            if !block.iter().any(is_flattenable) {
+                return false;
+            }
+            let mut stmts = Vec::with_capacity(block.len());
+            for stmt in block.iter_mut() {
+                if !is_flattenable(stmt) {
+                    stmts.push(take_stmt(stmt));
+                    continue;
+                }
+                let yul::StmtKind::Block(inner) = &mut stmt.kind else { unreachable!() };
+                stmts.extend(inner.iter_mut().map(take_stmt));
+            }
+            *block = cx.arena.alloc_vec(stmts);
+            true
+        })
+    }
+}
+
+fn is_flattenable(stmt: &yul::Stmt<'_>) -> bool {
+    match &stmt.kind {
+        yul::StmtKind::Block(block) => {
+            !block.iter().any(|stmt| matches!(stmt.kind, yul::StmtKind::FunctionDef(_)))
+        }
+        _ => false,
+    }
+}
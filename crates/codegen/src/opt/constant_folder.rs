@@ -0,0 +1,340 @@
+use super::{literal_value, visit_blocks_mut, visit_exprs_mut, Pass, PassCx};
+use crate::ir::num_lit;
+use alloy_primitives::U256;
+use solar_ast::yul;
+use solar_data_structures::map::{FxHashMap, FxHashSet};
+use solar_interface::Symbol;
+
+/// Evaluates calls whose arguments are all literals, and propagates the values of variables that
+/// are initialized to a literal and never reassigned.
+///
+/// Builtins are evaluated with the EVM's wrapping 256-bit semantics. Calls to the checked
+/// arithmetic helpers of the IR generator are only folded if they do not panic, so that overflows
+/// are still reported at runtime.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct ConstantFolder;
+
+impl Pass for ConstantFolder {
+    fn name(&self) -> &'static str {
+        "constant-folder"
+    }
+
+    fn run<'ast>(&mut self, cx: &PassCx<'_, 'ast>, object: &mut yul::Object<'ast>) -> bool {
+        let constants = constant_variables(&mut object.code.code);
+        visit_exprs_mut(&mut object.code.code, &mut |expr| {
+            let value = match &expr.kind {
+                yul::ExprKind::Path(path) => constants.get(&path.last().name).copied(),
+                yul::ExprKind::Call(call) => {
+                    let args = call.arguments.iter().map(literal_value).collect::<Option<Vec<_>>>();
+                    args.and_then(|args| eval_call(call.name.as_str(), &args))
+                }
+                yul::ExprKind::Lit(_) => None,
+            };
+            let Some(value) = value else { return false };
+            expr.kind = yul::ExprKind::Lit(num_lit(cx.arena, expr.span, value));
+            true
+        })
+    }
+}
+
+/// Returns the variables that are declared once with a literal value and never assigned to.
+///
+/// Function parameters and return variables are never constant.
+fn constant_variables(code: &mut yul::Block<'_>) -> FxHashMap<Symbol, U256> {
+    let mut constants = FxHashMap::default();
+    let mut excluded = FxHashSet::default();
+    visit_blocks_mut(code, &mut |block| {
+        for stmt in block.iter() {
+            match &stmt.kind {
+                yul::StmtKind::VarDecl(names, value) => {
+                    let value = value.as_ref().and_then(literal_value);
+                    for name in names.iter() {
+                        match value {
+                            Some(value) if names.len() == 1 => {
+                                if constants.insert(name.name, value).is_some() {
+                                    excluded.insert(name.name);
+                                }
+                            }
+                            _ => _ = excluded.insert(name.name),
+                        }
+                    }
+                }
+                yul::StmtKind::AssignSingle(path, _) => _ = excluded.insert(path.last().name),
+                yul::StmtKind::AssignMulti(paths, _) => {
+                    excluded.extend(paths.iter().map(|path| path.last().name))
+                }
+                yul::StmtKind::FunctionDef(function) => excluded.extend(
+                    function.parameters.iter().chain(function.returns.iter()).map(|id| id.name),
+                ),
+                _ => {}
+            }
+        }
+        false
+    });
+    constants.retain(|name, _| !excluded.contains(name));
+    constants
+}
+
+/// Evaluates a call to a builtin or a checked arithmetic helper with the given arguments.
+fn eval_call(name: &str, args: &[U256]) -> Option<U256> {
+    if let Some(ty) = name.strip_prefix("negate_t_int") {
+        let bits = ty.parse().ok()?;
+        return match *args {
+            [x] => eval_checked_negate(bits, x),
+            _ => None,
+        };
+    }
+    if let Some(rest) = name.strip_prefix("checked_") {
+        let (op, ty) = rest.split_once("_t_")?;
+        let (signed, bits) = match ty.strip_prefix("int") {
+            Some(bits) => (true, bits),
+            None => (false, ty.strip_prefix("uint")?),
+        };
+        let bits = bits.parse().ok()?;
+        return match *args {
+            [x, y] => eval_checked(op, signed, bits, x, y),
+            _ => None,
+        };
+    }
+    eval_builtin(name, args)
+}
+
+const SIGN_BIT: U256 = U256::from_limbs([0, 0, 0, 1 << 63]);
+
+/// Evaluates a pure EVM builtin.
+fn eval_builtin(name: &str, args: &[U256]) -> Option<U256> {
+    let flag = |b: bool| U256::from(b as u8);
+    let shift = |s: U256| usize::try_from(s).unwrap_or(usize::MAX);
+    let value = match (name, *args) {
+        ("add", [x, y]) => x.wrapping_add(y),
+        ("sub", [x, y]) => x.wrapping_sub(y),
+        ("mul", [x, y]) => x.wrapping_mul(y),
+        ("div", [x, y]) => x.checked_div(y).unwrap_or_default(),
+        ("mod", [x, y]) => x.checked_rem(y).unwrap_or_default(),
+        ("sdiv", [x, y]) => sdiv(x, y),
+        ("smod", [x, y]) => smod(x, y),
+        ("exp", [x, y]) => x.wrapping_pow(y),
+        ("addmod", [x, y, m]) => x.add_mod(y, m),
+        ("mulmod", [x, y, m]) => x.mul_mod(y, m),
+        ("signextend", [b, x]) => signextend(b, x),
+        ("not", [x]) => !x,
+        ("iszero", [x]) => flag(x.is_zero()),
+        ("lt", [x, y]) => flag(x < y),
+        ("gt", [x, y]) => flag(x > y),
+        ("slt", [x, y]) => flag((x ^ SIGN_BIT) < (y ^ SIGN_BIT)),
+        ("sgt", [x, y]) => flag((x ^ SIGN_BIT) > (y ^ SIGN_BIT)),
+        ("eq", [x, y]) => flag(x == y),
+        ("and", [x, y]) => x & y,
+        ("or", [x, y]) => x | y,
+        ("xor", [x, y]) => x ^ y,
+        ("byte", [i, x]) => match usize::try_from(i) {
+            Ok(i) if i < 32 => U256::from(x.byte(31 - i)),
+            _ => U256::ZERO,
+        },
+        ("shl", [s, x]) => x.wrapping_shl(shift(s)),
+        ("shr", [s, x]) => x.wrapping_shr(shift(s)),
+        ("sar", [s, x]) => x.arithmetic_shr(shift(s)),
+        _ => return None,
+    };
+    Some(value)
+}
+
+fn is_negative(x: U256) -> bool {
+    x.bit(255)
+}
+
+fn abs(x: U256) -> U256 {
+    if is_negative(x) {
+        x.wrapping_neg()
+    } else {
+        x
+    }
+}
+
+fn sdiv(x: U256, y: U256) -> U256 {
+    if y.is_zero() {
+        return U256::ZERO;
+    }
+    let q = abs(x) / abs(y);
+    if is_negative(x) != is_negative(y) {
+        q.wrapping_neg()
+    } else {
+        q
+    }
+}
+
+fn smod(x: U256, y: U256) -> U256 {
+    if y.is_zero() {
+        return U256::ZERO;
+    }
+    let r = abs(x) % abs(y);
+    if is_negative(x) {
+        r.wrapping_neg()
+    } else {
+        r
+    }
+}
+
+fn signextend(b: U256, x: U256) -> U256 {
+    let Ok(b) = usize::try_from(b) else { return x };
+    if b >= 31 {
+        return x;
+    }
+    let bit = 8 * b + 7;
+    let mask = (U256::from(1) << (bit + 1)) - U256::from(1);
+    if x.bit(bit) {
+        x | !mask
+    } else {
+        x & mask
+    }
+}
+
+/// Returns `true` if `x` is a valid value of the integer type.
+fn in_range(signed: bool, bits: usize, x: U256) -> bool {
+    match (signed, bits) {
+        (_, 256) => true,
+        (false, _) => x.bit_len() <= bits,
+        (true, _) => signextend(U256::from(bits / 8 - 1), x) == x,
+    }
+}
+
+/// Evaluates a `checked_{op}_t_{type}` helper, returning `None` if it panics.
+///
+/// This mirrors the checks emitted by the IR generator.
+fn eval_checked(op: &str, signed: bool, bits: usize, x: U256, y: U256) -> Option<U256> {
+    if !(8..=256).contains(&bits) || bits % 8 != 0 || !in_range(signed, bits, x) {
+        return None;
+    }
+    if op == "exp" {
+        let mut power = U256::from(1);
+        let (mut base, mut exponent) = (x, y);
+        while !exponent.is_zero() {
+            if exponent.bit(0) {
+                power = eval_checked("mul", signed, bits, power, base)?;
+            }
+            exponent >>= 1usize;
+            if !exponent.is_zero() {
+                base = eval_checked("mul", signed, bits, base, base)?;
+            }
+        }
+        return Some(power);
+    }
+    if !in_range(signed, bits, y) {
+        return None;
+    }
+    let slt = |a: U256, b: U256| (a ^ SIGN_BIT) < (b ^ SIGN_BIT);
+    let r = match op {
+        "add" => {
+            let r = x.wrapping_add(y);
+            let overflow = if signed { slt(r, x) != slt(y, U256::ZERO) } else { r < x };
+            if bits == 256 && overflow {
+                return None;
+            }
+            r
+        }
+        "sub" if !signed => x.checked_sub(y)?,
+        "sub" => {
+            let r = x.wrapping_sub(y);
+            if bits == 256 && slt(x, r) != slt(y, U256::ZERO) {
+                return None;
+            }
+            r
+        }
+        "mul" => {
+            let r = x.wrapping_mul(y);
+            if bits > 128 {
+                let div = if signed { sdiv(r, x) } else { r.checked_div(x).unwrap_or_default() };
+                if !x.is_zero() && div != y {
+                    return None;
+                }
+                if signed && bits == 256 && x == U256::MAX && y == SIGN_BIT {
+                    return None;
+                }
+            }
+            r
+        }
+        "div" | "mod" if y.is_zero() => return None,
+        "div" if signed => {
+            if bits == 256 && x == SIGN_BIT && y == U256::MAX {
+                return None;
+            }
+            sdiv(x, y)
+        }
+        "div" => x / y,
+        "mod" if signed => smod(x, y),
+        "mod" => x % y,
+        _ => return None,
+    };
+    in_range(signed, bits, r).then_some(r)
+}
+
+/// Evaluates a `negate_t_int{bits}` helper, returning `None` if it panics.
+fn eval_checked_negate(bits: usize, x: U256) -> Option<U256> {
+    if !(8..=256).contains(&bits) || bits % 8 != 0 || !in_range(true, bits, x) {
+        return None;
+    }
+    let r = x.wrapping_neg();
+    (in_range(true, bits, r) && !(bits == 256 && x == SIGN_BIT)).then_some(r)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::opt::tests::optimize;
+
+    fn neg(x: u64) -> U256 {
+        U256::from(x).wrapping_neg()
+    }
+
+    #[test]
+    fn builtins() {
+        let n = U256::from;
+        assert_eq!(eval_builtin("add", &[U256::MAX, n(2)]), Some(n(1)));
+        assert_eq!(eval_builtin("sub", &[n(0), n(1)]), Some(U256::MAX));
+        assert_eq!(eval_builtin("div", &[n(7), n(0)]), Some(n(0)));
+        assert_eq!(eval_builtin("sdiv", &[neg(7), n(2)]), Some(neg(3)));
+        assert_eq!(eval_builtin("sdiv", &[SIGN_BIT, U256::MAX]), Some(SIGN_BIT));
+        assert_eq!(eval_builtin("smod", &[neg(7), n(2)]), Some(neg(1)));
+        assert_eq!(eval_builtin("exp", &[n(2), n(256)]), Some(n(0)));
+        assert_eq!(eval_builtin("slt", &[neg(1), n(0)]), Some(n(1)));
+        assert_eq!(eval_builtin("byte", &[n(31), n(0x1234)]), Some(n(0x34)));
+        assert_eq!(eval_builtin("shl", &[n(256), n(1)]), Some(n(0)));
+        assert_eq!(eval_builtin("sar", &[n(4), neg(32)]), Some(neg(2)));
+        assert_eq!(eval_builtin("signextend", &[n(0), n(0xff)]), Some(U256::MAX));
+        assert_eq!(eval_builtin("addmod", &[U256::MAX, n(2), n(10)]), Some(n(7)));
+        assert_eq!(eval_builtin("mload", &[n(0)]), None);
+    }
+
+    #[test]
+    fn checked() {
+        let n = U256::from;
+        assert_eq!(eval_call("checked_add_t_uint8", &[n(200), n(55)]), Some(n(255)));
+        assert_eq!(eval_call("checked_add_t_uint8", &[n(200), n(56)]), None);
+        assert_eq!(eval_call("checked_add_t_uint256", &[U256::MAX, n(1)]), None);
+        assert_eq!(eval_call("checked_sub_t_uint256", &[n(0), n(1)]), None);
+        assert_eq!(eval_call("checked_sub_t_int8", &[neg(128), n(1)]), None);
+        assert_eq!(eval_call("checked_sub_t_int8", &[neg(127), n(1)]), Some(neg(128)));
+        assert_eq!(eval_call("checked_mul_t_uint256", &[SIGN_BIT, n(2)]), None);
+        assert_eq!(eval_call("checked_div_t_int256", &[SIGN_BIT, U256::MAX]), None);
+        assert_eq!(eval_call("checked_div_t_uint256", &[n(1), n(0)]), None);
+        assert_eq!(eval_call("checked_exp_t_uint8", &[n(2), n(7)]), Some(n(128)));
+        assert_eq!(eval_call("checked_exp_t_uint8", &[n(2), n(8)]), None);
+        assert_eq!(eval_call("checked_exp_t_int16", &[neg(2), n(3)]), Some(neg(8)));
+        assert_eq!(eval_call("negate_t_int8", &[neg(128)]), None);
+        assert_eq!(eval_call("negate_t_int8", &[n(127)]), Some(neg(127)));
+        assert_eq!(eval_call("checked_add_t_address", &[n(1), n(1)]), None);
+    }
+
+    #[test]
+    fn propagation() {
+        let src = r#"object "a" { code {
+            let x := 2
+            let y := add(x, mul(3, 4))
+            sstore(checked_add_t_uint8(200, y), checked_add_t_uint8(250, 6))
+        } }"#;
+        assert_eq!(
+            optimize(src, &["constant-folder"]),
+            r#"object "a" { code { let x := 2 let y := 14 sstore(214, checked_add_t_uint8(250, 6)) } }"#
+        );
+    }
+}
@@ -0,0 +1,223 @@
+use super::{
+    call_counts, is_side_effect_free, lit_value, literal_value, take_stmt, visit_blocks_mut,
+    visit_exprs_mut, Pass, PassCx,
+};
+use solar_ast::yul;
+use solar_data_structures::map::{FxHashMap, FxHashSet};
+use solar_interface::{kw, Symbol};
+
+/// Removes code that is never executed or whose result is never used:
+/// - statements following a statement that does not continue to the next one, such as `leave` or
+///   `revert(..)`;
+/// - `if`, `switch` and `for` statements with a literal condition, which are replaced by the code
+///   they run, if any;
+/// - variables that are never read, along with their assignments, if their values have no side
+///   effects;
+/// - functions that are never called.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct DeadCodeEliminator;
+
+impl Pass for DeadCodeEliminator {
+    fn name(&self) -> &'static str {
+        "dead-code-eliminator"
+    }
+
+    fn run<'ast>(&mut self, cx: &PassCx<'_, 'ast>, object: &mut yul::Object<'ast>) -> bool {
+        let usage = Usage::collect(&mut object.code.code);
+        visit_blocks_mut(&mut object.code.code, &mut |block| {
+            let mut reachable = true;
+            let actions = block
+                .iter()
+                .map(|stmt| {
+                    let action = if reachable { usage.action(stmt) } else { Action::Unreachable };
+                    reachable &= !terminates(stmt);
+                    action
+                })
+                .collect::<Vec<_>>();
+            if actions.iter().all(|action| matches!(action, Action::Keep)) {
+                return false;
+            }
+
+            let mut stmts = Vec::with_capacity(block.len());
+            for (stmt, action) in block.iter_mut().zip(actions) {
+                let branch = match action {
+                    Action::Keep => {
+                        stmts.push(take_stmt(stmt));
+                        continue;
+                    }
+                    Action::Unreachable if matches!(stmt.kind, yul::StmtKind::FunctionDef(_)) => {
+                        stmts.push(take_stmt(stmt));
+                        continue;
+                    }
+                    Action::Unreachable | Action::Remove => continue,
+                    Action::Inline(branch) => branch,
+                };
+                let span = stmt.span;
+                let inlined = match (branch, &mut stmt.kind) {
+                    (Branch::Body, yul::StmtKind::If(_, body)) => body,
+                    (Branch::Case(i), yul::StmtKind::Switch(switch)) => {
+                        &mut switch.branches[i].body
+                    }
+                    (Branch::Default, yul::StmtKind::Switch(switch)) => {
+                        switch.default_case.as_mut().unwrap()
+                    }
+                    (Branch::Init, yul::StmtKind::For { init, .. }) => init,
+                    _ => unreachable!(),
+                };
+                let kind = yul::StmtKind::Block(std::mem::take(inlined));
+                stmts.push(yul::Stmt { docs: Default::default(), span, kind });
+            }
+            *block = cx.arena.alloc_vec(stmts);
+            true
+        })
+    }
+}
+
+/// What to do with a statement.
+enum Action {
+    Keep,
+    Remove,
+    /// The statement is unreachable. Function definitions are kept.
+    Unreachable,
+    /// Replace the statement with one of its blocks.
+    Inline(Branch),
+}
+
+/// A block of a control flow statement.
+#[derive(Clone, Copy)]
+enum Branch {
+    /// The body of an `if`.
+    Body,
+    /// The body of a `switch` case.
+    Case(usize),
+    /// The body of the `default` case of a `switch`.
+    Default,
+    /// The initialization block of a `for` loop.
+    Init,
+}
+
+/// How variables and functions are used in an object.
+struct Usage {
+    /// The number of calls of each function.
+    calls: FxHashMap<Symbol, usize>,
+    /// The variables that are never read and only assigned values without side effects.
+    removable: FxHashSet<Symbol>,
+}
+
+impl Usage {
+    fn collect(code: &mut yul::Block<'_>) -> Self {
+        let mut usage = Self { calls: call_counts(code), removable: FxHashSet::default() };
+        let mut reads = FxHashSet::default();
+        visit_exprs_mut(code, &mut |expr| {
+            if let yul::ExprKind::Path(path) = &expr.kind {
+                reads.insert(path.last().name);
+            }
+            false
+        });
+
+        let mut pinned = FxHashSet::default();
+        visit_blocks_mut(code, &mut |block| {
+            for stmt in block.iter() {
+                match &stmt.kind {
+                    yul::StmtKind::VarDecl(names, value) => {
+                        if names.len() == 1 && value.as_ref().is_none_or(is_side_effect_free) {
+                            usage.removable.insert(names[0].name);
+                        } else {
+                            pinned.extend(names.iter().map(|name| name.name));
+                        }
+                    }
+                    yul::StmtKind::AssignSingle(path, value) => {
+                        if !is_side_effect_free(value) {
+                            pinned.insert(path.last().name);
+                        }
+                    }
+                    yul::StmtKind::AssignMulti(paths, _) => {
+                        pinned.extend(paths.iter().map(|path| path.last().name));
+                    }
+                    yul::StmtKind::FunctionDef(f) => {
+                        pinned.extend(f.parameters.iter().chain(f.returns.iter()).map(|n| n.name));
+                    }
+                    _ => {}
+                }
+            }
+            false
+        });
+        usage.removable.retain(|name| !reads.contains(name) && !pinned.contains(name));
+        usage
+    }
+
+    fn action(&self, stmt: &yul::Stmt<'_>) -> Action {
+        match &stmt.kind {
+            yul::StmtKind::Block(block) if block.is_empty() => Action::Remove,
+            yul::StmtKind::VarDecl(names, _)
+                if names.iter().all(|name| self.removable.contains(&name.name)) =>
+            {
+                Action::Remove
+            }
+            yul::StmtKind::AssignSingle(path, _) if self.removable.contains(&path.last().name) => {
+                Action::Remove
+            }
+            yul::StmtKind::Expr(call)
+                if call.name.name == kw::Pop && call.arguments.iter().all(is_side_effect_free) =>
+            {
+                Action::Remove
+            }
+            yul::StmtKind::FunctionDef(f) if !self.calls.contains_key(&f.name.name) => {
+                Action::Remove
+            }
+            yul::StmtKind::If(cond, body) => match literal_value(cond) {
+                Some(value) if value.is_zero() => Action::Remove,
+                Some(_) => Action::Inline(Branch::Body),
+                None if body.is_empty() && is_side_effect_free(cond) => Action::Remove,
+                None => Action::Keep,
+            },
+            yul::StmtKind::Switch(switch) => {
+                let Some(value) = literal_value(&switch.selector) else { return Action::Keep };
+                let case =
+                    switch.branches.iter().position(|case| lit_value(case.constant) == Some(value));
+                match (case, &switch.default_case) {
+                    (Some(i), _) => Action::Inline(Branch::Case(i)),
+                    (None, Some(_)) => Action::Inline(Branch::Default),
+                    (None, None) => Action::Remove,
+                }
+            }
+            yul::StmtKind::For { cond, .. } if literal_value(cond).is_some_and(|v| v.is_zero()) => {
+                Action::Inline(Branch::Init)
+            }
+            _ => Action::Keep,
+        }
+    }
+}
+
+/// Returns `true` if control never flows from the statement to the next one.
+fn terminates(stmt: &yul::Stmt<'_>) -> bool {
+    match &stmt.kind {
+        yul::StmtKind::Leave | yul::StmtKind::Break | yul::StmtKind::Continue => true,
+        yul::StmtKind::Expr(call) => {
+            matches!(call.name.as_str(), "revert" | "return" | "stop" | "invalid")
+        }
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::opt::tests::optimize;
+
+    #[test]
+    fn unused_and_unreachable() {
+        let src = r#"object "a" { code {
+            let unused := mload(0)
+            let used := calldataload(0)
+            if 0 { sstore(0, 1) }
+            if 1 { sstore(1, used) }
+            f()
+            function f() { revert(0, 0) sstore(0, 0) }
+            function g() { }
+        } }"#;
+        assert_eq!(
+            optimize(src, &["dead-code-eliminator"]),
+            r#"object "a" { code { let used := calldataload(0) { sstore(1, used) } f() function f() { revert(0, 0) } } }"#
+        );
+    }
+}
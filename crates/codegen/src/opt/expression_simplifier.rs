@@ -0,0 +1,126 @@
+use super::{is_side_effect_free, literal_value, visit_exprs_mut, Pass, PassCx};
+use crate::ir::num_lit;
+use alloy_primitives::U256;
+use solar_ast::yul;
+use solar_interface::{Ident, Symbol};
+
+/// Applies algebraic identities to calls of builtins with a literal or repeated operand, such as
+/// `add(x, 0)` to `x`, `mul(x, 0)` to `0` and `eq(x, 0)` to `iszero(x)`.
+///
+/// Operands are only dropped if they have no side effects.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct ExpressionSimplifier;
+
+impl Pass for ExpressionSimplifier {
+    fn name(&self) -> &'static str {
+        "expression-simplifier"
+    }
+
+    fn run<'ast>(&mut self, cx: &PassCx<'_, 'ast>, object: &mut yul::Object<'ast>) -> bool {
+        visit_exprs_mut(&mut object.code.code, &mut |expr| {
+            let yul::ExprKind::Call(call) = &mut expr.kind else { return false };
+            let Some(simplified) = simplify(call) else { return false };
+            let span = expr.span;
+            let name_span = call.name.span;
+            // Moves an argument out of the call.
+            let mut take_arg = |i: usize| {
+                let zero = num_lit(cx.arena, span, U256::ZERO);
+                std::mem::replace(
+                    &mut call.arguments[i],
+                    yul::Expr { span, kind: yul::ExprKind::Lit(zero) },
+                )
+            };
+            expr.kind = match simplified {
+                Simplified::Const(value) => yul::ExprKind::Lit(num_lit(cx.arena, span, value)),
+                Simplified::Arg(i) => take_arg(i).kind,
+                Simplified::Call(name, i) => {
+                    let name = Ident::new(Symbol::intern(name), name_span);
+                    let arguments = cx.arena.alloc_from_iter(std::iter::once(take_arg(i)));
+                    yul::ExprKind::Call(yul::ExprCall { name, arguments })
+                }
+            };
+            true
+        })
+    }
+}
+
+/// The simplified form of a call.
+enum Simplified {
+    /// A constant.
+    Const(U256),
+    /// The argument at the given index.
+    Arg(usize),
+    /// A call of a unary builtin with the argument at the given index.
+    Call(&'static str, usize),
+}
+
+fn simplify(call: &yul::ExprCall<'_>) -> Option<Simplified> {
+    use Simplified::*;
+
+    let args = &call.arguments[..];
+    let lits = args.iter().map(literal_value).collect::<Vec<_>>();
+    let pure = |i: usize| is_side_effect_free(&args[i]);
+    let same_var = args.len() == 2
+        && match (&args[0].kind, &args[1].kind) {
+            (yul::ExprKind::Path(a), yul::ExprKind::Path(b)) => a.last().name == b.last().name,
+            _ => false,
+        };
+    let is = |i: usize, value: U256| lits[i] == Some(value);
+    let (zero, one, max) = (U256::ZERO, U256::from(1), U256::MAX);
+
+    let simplified = match (call.name.as_str(), args.len()) {
+        // Identities.
+        ("add" | "or" | "xor", 2) if is(1, zero) => Arg(0),
+        ("add" | "or" | "xor", 2) if is(0, zero) => Arg(1),
+        ("sub", 2) if is(1, zero) => Arg(0),
+        ("mul", 2) if is(1, one) => Arg(0),
+        ("mul", 2) if is(0, one) => Arg(1),
+        ("div" | "sdiv" | "exp", 2) if is(1, one) => Arg(0),
+        ("and", 2) if is(1, max) => Arg(0),
+        ("and", 2) if is(0, max) => Arg(1),
+        ("shl" | "shr" | "sar", 2) if is(0, zero) => Arg(1),
+
+        // Absorbing elements.
+        ("mul" | "and", 2) if is(1, zero) && pure(0) => Const(zero),
+        ("mul" | "and", 2) if is(0, zero) && pure(1) => Const(zero),
+        ("div" | "sdiv" | "mod" | "smod", 2) if is(1, zero) && pure(0) => Const(zero),
+        ("div" | "sdiv" | "mod" | "smod", 2) if is(0, zero) && pure(1) => Const(zero),
+        ("mod" | "smod", 2) if is(1, one) && pure(0) => Const(zero),
+        ("or", 2) if is(1, max) && pure(0) => Const(max),
+        ("or", 2) if is(0, max) && pure(1) => Const(max),
+        ("exp", 2) if is(1, zero) && pure(0) => Const(one),
+        ("lt", 2) if is(1, zero) && pure(0) => Const(zero),
+        ("gt", 2) if is(0, zero) && pure(1) => Const(zero),
+        ("shl" | "shr", 2) if lits[0].is_some_and(|s| s >= U256::from(256)) && pure(1) => {
+            Const(zero)
+        }
+
+        // Repeated operands.
+        ("and" | "or", 2) if same_var => Arg(0),
+        ("sub" | "xor" | "lt" | "gt" | "slt" | "sgt", 2) if same_var => Const(zero),
+        ("eq", 2) if same_var => Const(one),
+
+        // Cheaper equivalents.
+        ("eq", 2) if is(1, zero) => Call("iszero", 0),
+        ("eq", 2) if is(0, zero) => Call("iszero", 1),
+        _ => return None,
+    };
+    Some(simplified)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::opt::tests::optimize;
+
+    #[test]
+    fn identities() {
+        let src = r#"object "a" { code {
+            sstore(add(calldataload(0), 0), mul(callvalue(), 0))
+            mstore(0, eq(caller(), 0))
+        } }"#;
+        assert_eq!(
+            optimize(src, &["expression-simplifier"]),
+            r#"object "a" { code { sstore(calldataload(0), 0) mstore(0, iszero(caller())) } }"#
+        );
+    }
+}
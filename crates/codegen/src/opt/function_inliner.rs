@@ -0,0 +1,334 @@
+use super::{call_counts, single_path, take_stmt, visit_blocks_mut, NameDispenser, Pass, PassCx};
+use solar_ast::{yul, Arena, AstPath, Lit, PathSlice};
+use solar_data_structures::map::FxHashMap;
+use solar_interface::{Ident, Symbol};
+
+/// Functions with at most this many statements and expressions are inlined at every call site
+/// with the default number of optimizer runs. Larger functions are only inlined if they are called
+/// once.
+const SMALL_FUNCTION_SIZE: usize = 8;
+
+/// Returns the size up to which functions are inlined at every call site.
+///
+/// Inlining saves the cost of the call at the expense of code size, so the limit is halved when
+/// optimizing for deployment cost, with less than 200 runs, and doubled for more than 10000 runs.
+fn small_function_size(runs: u64) -> usize {
+    match runs {
+        0..200 => SMALL_FUNCTION_SIZE / 2,
+        200..=10_000 => SMALL_FUNCTION_SIZE,
+        _ => SMALL_FUNCTION_SIZE * 2,
+    }
+}
+
+/// Replaces calls of functions that are small or called only once with a copy of their body.
+///
+/// Only calls that are statements on their own, such as `let x := f(a, b)` or `f(a)`, and whose
+/// arguments are variables or literals are inlined. Functions that `leave`, define other functions
+/// or call themselves are never inlined.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct FunctionInliner;
+
+impl Pass for FunctionInliner {
+    fn name(&self) -> &'static str {
+        "function-inliner"
+    }
+
+    fn run<'ast>(&mut self, cx: &PassCx<'_, 'ast>, object: &mut yul::Object<'ast>) -> bool {
+        let code = &mut object.code.code;
+        let calls = call_counts(code);
+        let small_size = small_function_size(cx.settings.runs);
+
+        // Copy the functions to inline, as their definitions may themselves contain call sites.
+        let mut templates = FxHashMap::default();
+        for stmt in code.iter() {
+            let yul::StmtKind::FunctionDef(f) = &stmt.kind else { continue };
+            let mut size = 0;
+            if !is_inlinable(&f.body, f.name.name, &mut size) {
+                continue;
+            }
+            match calls.get(&f.name.name) {
+                Some(1) => {}
+                Some(_) if size <= small_size => {}
+                _ => continue,
+            }
+            let mut copier = Copier::new(cx.arena, None);
+            let template = yul::Function {
+                name: f.name,
+                parameters: cx.arena.alloc_slice_copy(&f.parameters[..]),
+                returns: cx.arena.alloc_slice_copy(&f.returns[..]),
+                body: copier.block(&f.body),
+            };
+            templates.insert(f.name.name, template);
+        }
+        if templates.is_empty() {
+            return false;
+        }
+
+        let template_for = |stmt: &yul::Stmt<'ast>| {
+            let call = inlinable_call(stmt)?;
+            let f = templates.get(&call.name.name)?;
+            let simple_args = call
+                .arguments
+                .iter()
+                .all(|arg| matches!(arg.kind, yul::ExprKind::Path(_) | yul::ExprKind::Lit(_)));
+            (simple_args && call.arguments.len() == f.parameters.len()).then_some(f)
+        };
+        let mut names = NameDispenser::new(code);
+        visit_blocks_mut(code, &mut |block| {
+            if !block.iter().any(|stmt| template_for(stmt).is_some()) {
+                return false;
+            }
+            let mut stmts = Vec::with_capacity(block.len());
+            for stmt in block.iter_mut() {
+                match template_for(stmt) {
+                    Some(f) => inline(cx.arena, &mut names, stmt, f, &mut stmts),
+                    None => stmts.push(take_stmt(stmt)),
+                }
+            }
+            *block = cx.arena.alloc_vec(stmts);
+            true
+        })
+    }
+}
+
+/// Returns the call of a statement that can be inlined.
+fn inlinable_call<'a, 'ast>(stmt: &'a yul::Stmt<'ast>) -> Option<&'a yul::ExprCall<'ast>> {
+    match &stmt.kind {
+        yul::StmtKind::VarDecl(_, Some(yul::Expr { kind: yul::ExprKind::Call(call), .. }))
+        | yul::StmtKind::AssignSingle(_, yul::Expr { kind: yul::ExprKind::Call(call), .. })
+        | yul::StmtKind::AssignMulti(_, call)
+        | yul::StmtKind::Expr(call) => Some(call),
+        _ => None,
+    }
+}
+
+/// Replaces the call statement `stmt` with the body of `f`:
+///
+/// ```yul
+/// let param_1 := arg
+/// let ret_1
+/// { body }
+/// x := ret_1
+/// ```
+fn inline<'ast>(
+    arena: &'ast Arena,
+    names: &mut NameDispenser,
+    stmt: &yul::Stmt<'ast>,
+    f: &yul::Function<'ast>,
+    stmts: &mut Vec<yul::Stmt<'ast>>,
+) {
+    let span = stmt.span;
+    let mk_stmt = |kind| yul::Stmt { docs: Default::default(), span, kind };
+    let var = |name: Symbol| yul::Expr {
+        span,
+        kind: yul::ExprKind::Path(single_path(arena, Ident::new(name, span))),
+    };
+    let call = inlinable_call(stmt).unwrap();
+
+    // The arguments refer to the variables of the caller, so they are copied without renaming.
+    let args =
+        call.arguments.iter().map(|arg| Copier::new(arena, None).expr(arg)).collect::<Vec<_>>();
+    let mut copier = Copier::new(arena, Some(names));
+    for (param, arg) in f.parameters.iter().zip(args) {
+        let param = copier.declare(*param);
+        stmts.push(mk_stmt(yul::StmtKind::VarDecl(arena.alloc_slice_copy(&[param]), Some(arg))));
+    }
+    let returns = f.returns.iter().map(|&ret| copier.declare(ret)).collect::<Vec<_>>();
+    if !returns.is_empty() {
+        stmts.push(mk_stmt(yul::StmtKind::VarDecl(arena.alloc_slice_copy(&returns[..]), None)));
+    }
+    stmts.push(mk_stmt(yul::StmtKind::Block(copier.block(&f.body))));
+
+    let mut results = returns.iter().map(|ret| var(ret.name));
+    match &stmt.kind {
+        yul::StmtKind::VarDecl(names, _) => {
+            for (&name, value) in names.iter().zip(results) {
+                let kind = yul::StmtKind::VarDecl(arena.alloc_slice_copy(&[name]), Some(value));
+                stmts.push(mk_stmt(kind));
+            }
+        }
+        yul::StmtKind::AssignSingle(path, _) => {
+            let value = results.next().expect("assignment from a function without returns");
+            stmts.push(mk_stmt(yul::StmtKind::AssignSingle(copy_path(arena, path), value)));
+        }
+        yul::StmtKind::AssignMulti(paths, _) => {
+            for (path, value) in paths.iter().zip(results) {
+                stmts.push(mk_stmt(yul::StmtKind::AssignSingle(copy_path(arena, path), value)));
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Returns `true` if the function with the given name and body can be inlined, and adds the
+/// number of statements and expressions in the body to `size`.
+fn is_inlinable(block: &yul::Block<'_>, name: Symbol, size: &mut usize) -> bool {
+    let expr = |expr: &yul::Expr<'_>, size: &mut usize| expr_is_inlinable(expr, name, size);
+    block.iter().all(|stmt| {
+        *size += 1;
+        match &stmt.kind {
+            yul::StmtKind::Leave | yul::StmtKind::FunctionDef(_) => false,
+            yul::StmtKind::Break | yul::StmtKind::Continue | yul::StmtKind::VarDecl(_, None) => {
+                true
+            }
+            yul::StmtKind::Block(block) => is_inlinable(block, name, size),
+            yul::StmtKind::AssignSingle(_, value) | yul::StmtKind::VarDecl(_, Some(value)) => {
+                expr(value, size)
+            }
+            yul::StmtKind::AssignMulti(_, call) | yul::StmtKind::Expr(call) => {
+                call_is_inlinable(call, name, size)
+            }
+            yul::StmtKind::If(cond, body) => expr(cond, size) && is_inlinable(body, name, size),
+            yul::StmtKind::For { init, cond, step, body } => {
+                is_inlinable(init, name, size)
+                    && expr(cond, size)
+                    && is_inlinable(step, name, size)
+                    && is_inlinable(body, name, size)
+            }
+            yul::StmtKind::Switch(switch) => {
+                expr(&switch.selector, size)
+                    && switch.branches.iter().all(|case| is_inlinable(&case.body, name, size))
+                    && switch
+                        .default_case
+                        .as_ref()
+                        .is_none_or(|body| is_inlinable(body, name, size))
+            }
+        }
+    })
+}
+
+fn expr_is_inlinable(expr: &yul::Expr<'_>, name: Symbol, size: &mut usize) -> bool {
+    *size += 1;
+    match &expr.kind {
+        yul::ExprKind::Call(call) => call_is_inlinable(call, name, size),
+        yul::ExprKind::Path(_) | yul::ExprKind::Lit(_) => true,
+    }
+}
+
+fn call_is_inlinable(call: &yul::ExprCall<'_>, name: Symbol, size: &mut usize) -> bool {
+    call.name.name != name && call.arguments.iter().all(|arg| expr_is_inlinable(arg, name, size))
+}
+
+fn copy_path<'ast>(arena: &'ast Arena, path: &PathSlice) -> AstPath<'ast> {
+    PathSlice::from_mut_slice(arena.alloc_slice_copy(path.segments()))
+}
+
+/// Copies code into an arena, optionally renaming the variables it declares.
+struct Copier<'a, 'ast> {
+    arena: &'ast Arena,
+    /// Generates the new names of the declared variables, if they are renamed.
+    names: Option<&'a mut NameDispenser>,
+    renames: FxHashMap<Symbol, Symbol>,
+}
+
+impl<'a, 'ast> Copier<'a, 'ast> {
+    fn new(arena: &'ast Arena, names: Option<&'a mut NameDispenser>) -> Self {
+        Self { arena, names, renames: FxHashMap::default() }
+    }
+
+    /// Declares a variable, returning its new name.
+    fn declare(&mut self, ident: Ident) -> Ident {
+        let Some(names) = &mut self.names else { return ident };
+        let name = names.fresh(ident.name);
+        self.renames.insert(ident.name, name);
+        Ident::new(name, ident.span)
+    }
+
+    fn block(&mut self, block: &yul::Block<'_>) -> yul::Block<'ast> {
+        let stmts = block.iter().map(|stmt| self.stmt(stmt)).collect::<Vec<_>>();
+        self.arena.alloc_vec(stmts)
+    }
+
+    fn stmt(&mut self, stmt: &yul::Stmt<'_>) -> yul::Stmt<'ast> {
+        let kind = match &stmt.kind {
+            yul::StmtKind::Block(block) => yul::StmtKind::Block(self.block(block)),
+            yul::StmtKind::AssignSingle(path, value) => {
+                yul::StmtKind::AssignSingle(self.path(path), self.expr(value))
+            }
+            yul::StmtKind::AssignMulti(paths, call) => {
+                let paths = paths.iter().map(|path| self.path(path)).collect::<Vec<_>>();
+                yul::StmtKind::AssignMulti(self.arena.alloc_vec(paths), self.call(call))
+            }
+            yul::StmtKind::Expr(call) => yul::StmtKind::Expr(self.call(call)),
+            yul::StmtKind::If(cond, body) => yul::StmtKind::If(self.expr(cond), self.block(body)),
+            yul::StmtKind::For { init, cond, step, body } => {
+                // The variables declared in `init` must be renamed first.
+                let init = self.block(init);
+                let cond = self.expr(cond);
+                yul::StmtKind::For { init, cond, step: self.block(step), body: self.block(body) }
+            }
+            yul::StmtKind::Switch(switch) => {
+                let selector = self.expr(&switch.selector);
+                let branches = switch
+                    .branches
+                    .iter()
+                    .map(|case| yul::StmtSwitchCase {
+                        constant: self.lit(case.constant),
+                        body: self.block(&case.body),
+                    })
+                    .collect::<Vec<_>>();
+                let default_case = switch.default_case.as_ref().map(|body| self.block(body));
+                yul::StmtKind::Switch(yul::StmtSwitch {
+                    selector,
+                    branches: self.arena.alloc_vec(branches),
+                    default_case,
+                })
+            }
+            yul::StmtKind::Leave => yul::StmtKind::Leave,
+            yul::StmtKind::Break => yul::StmtKind::Break,
+            yul::StmtKind::Continue => yul::StmtKind::Continue,
+            yul::StmtKind::FunctionDef(_) => unreachable!("inlined functions define no functions"),
+            yul::StmtKind::VarDecl(names, value) => {
+                let value = value.as_ref().map(|value| self.expr(value));
+                let names = names.iter().map(|&name| self.declare(name)).collect::<Vec<_>>();
+                yul::StmtKind::VarDecl(self.arena.alloc_vec(names), value)
+            }
+        };
+        yul::Stmt { docs: Default::default(), span: stmt.span, kind }
+    }
+
+    fn expr(&mut self, expr: &yul::Expr<'_>) -> yul::Expr<'ast> {
+        let kind = match &expr.kind {
+            yul::ExprKind::Path(path) => yul::ExprKind::Path(self.path(path)),
+            yul::ExprKind::Call(call) => yul::ExprKind::Call(self.call(call)),
+            yul::ExprKind::Lit(lit) => yul::ExprKind::Lit(self.lit(lit)),
+        };
+        yul::Expr { span: expr.span, kind }
+    }
+
+    fn call(&mut self, call: &yul::ExprCall<'_>) -> yul::ExprCall<'ast> {
+        let arguments = call.arguments.iter().map(|arg| self.expr(arg)).collect::<Vec<_>>();
+        yul::ExprCall { name: call.name, arguments: self.arena.alloc_vec(arguments) }
+    }
+
+    fn path(&self, path: &PathSlice) -> AstPath<'ast> {
+        let ident = *path.last();
+        match self.renames.get(&ident.name) {
+            Some(&name) => single_path(self.arena, Ident::new(name, ident.span)),
+            None => copy_path(self.arena, path),
+        }
+    }
+
+    fn lit(&self, lit: &Lit) -> &'ast mut Lit {
+        self.arena.literals.alloc(lit.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::opt::tests::optimize;
+
+    #[test]
+    fn inline_once() {
+        let src = r#"object "a" { code {
+            let a := calldataload(0)
+            let x := double(a)
+            sstore(0, x)
+            function double(v) -> r { r := add(v, v) }
+        } }"#;
+        assert_eq!(
+            optimize(src, &["function-inliner"]),
+            r#"object "a" { code { let a := calldataload(0) let v_1 := a let r_1 { r_1 := add(v_1, v_1) } let x := r_1 sstore(0, x) function double(v) -> r { r := add(v, v) } } }"#
+        );
+    }
+}
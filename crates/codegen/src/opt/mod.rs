@@ -0,0 +1,574 @@
+//! Yul IR optimizer.
+//!
+//! The optimizer is a sequence of [`Pass`]es run by a [`PassManager`] on each object of the IR
+//! generated by [`ir::lower_contract`](crate::ir::lower_contract).
+//!
+//! Passes may assume that the IR is disambiguated, i.e. that every variable and function name is
+//! declared only once in each function and object, which holds for the generated IR.
+//!
+//! The default pipeline is built from the session's [`OptimizerSettings`] with
+//! [`PassManager::from_settings`], which can also replace it with an explicit sequence of passes
+//! given with `-Zoptimizer-pipeline`. Downstream tools can add custom passes to it before running it
+//! with [`ir::contract_ir_with`](crate::ir::contract_ir_with).
+
+use alloy_primitives::U256;
+use solar_ast::{yul, Arena, AstPath, Lit, LitKind, PathSlice};
+use solar_config::OptimizerSettings;
+use solar_data_structures::map::{FxHashMap, FxHashSet};
+use solar_interface::{Ident, Symbol};
+
+mod block_flattener;
+pub use block_flattener::BlockFlattener;
+
+mod constant_folder;
+pub use constant_folder::ConstantFolder;
+
+mod dead_code;
+pub use dead_code::DeadCodeEliminator;
+
+mod expression_simplifier;
+pub use expression_simplifier::ExpressionSimplifier;
+
+mod function_inliner;
+pub use function_inliner::FunctionInliner;
+
+mod ssa_transform;
+pub use ssa_transform::SsaTransform;
+
+/// An optimizer pass over a Yul object.
+pub trait Pass {
+    /// Returns the name of the pass, used to enable or disable it with `-Zenable-pass` and
+    /// `-Zdisable-pass`.
+    fn name(&self) -> &'static str;
+
+    /// Runs the pass on the code of the object, not including its children.
+    ///
+    /// Returns `true` if the code was changed.
+    fn run<'ast>(&mut self, cx: &PassCx<'_, 'ast>, object: &mut yul::Object<'ast>) -> bool;
+}
+
+/// The context of a [`Pass`].
+pub struct PassCx<'a, 'ast> {
+    /// The arena in which new IR nodes are allocated.
+    pub arena: &'ast Arena,
+    /// The optimizer settings.
+    pub settings: &'a OptimizerSettings,
+}
+
+/// The names of the built-in passes, along with whether they are run by default, in the order of
+/// the default pipeline.
+///
+/// The SSA transform is not run by default, as no pass undoes the variables it introduces yet.
+const BUILTIN_PASSES: &[(&str, bool)] = &[
+    ("ssa-transform", false),
+    ("expression-simplifier", true),
+    ("constant-folder", true),
+    ("function-inliner", true),
+    ("dead-code-eliminator", true),
+    ("block-flattener", true),
+];
+
+/// Creates the built-in pass with the given name.
+fn builtin_pass(name: &str) -> Option<Box<dyn Pass>> {
+    Some(match name {
+        "ssa-transform" => Box::new(SsaTransform),
+        "expression-simplifier" => Box::new(ExpressionSimplifier),
+        "constant-folder" => Box::new(ConstantFolder),
+        "function-inliner" => Box::new(FunctionInliner),
+        "dead-code-eliminator" => Box::new(DeadCodeEliminator),
+        "block-flattener" => Box::new(BlockFlattener),
+        _ => return None,
+    })
+}
+
+/// Runs a sequence of optimizer passes until the code stops changing.
+pub struct PassManager {
+    passes: Vec<Box<dyn Pass>>,
+    settings: OptimizerSettings,
+    max_iterations: usize,
+}
+
+impl Default for PassManager {
+    fn default() -> Self {
+        Self::new(OptimizerSettings::default())
+    }
+}
+
+impl PassManager {
+    /// The default maximum number of times the pipeline is repeated.
+    pub const DEFAULT_MAX_ITERATIONS: usize = 8;
+
+    /// Creates a new pass manager without any passes.
+    pub fn new(settings: OptimizerSettings) -> Self {
+        Self { passes: Vec::new(), settings, max_iterations: Self::DEFAULT_MAX_ITERATIONS }
+    }
+
+    /// Creates the pass manager of the default pipeline, or of the pipeline given in the settings,
+    /// with the passes enabled and disabled by the settings.
+    ///
+    /// Returns an error message if a pass name is unknown.
+    pub fn from_settings(settings: &OptimizerSettings) -> Result<Self, String> {
+        let names = settings.enabled_passes.iter().chain(&settings.disabled_passes);
+        for name in names.chain(settings.pipeline.iter().flatten()) {
+            if builtin_pass(name).is_none() {
+                let known = BUILTIN_PASSES.iter().map(|&(name, _)| name).collect::<Vec<_>>();
+                return Err(format!(
+                    "unknown optimizer pass `{name}`; known passes are: {}",
+                    known.join(", ")
+                ));
+            }
+        }
+
+        let is_disabled = |name: &str| settings.disabled_passes.iter().any(|p| p == name);
+        let names = match &settings.pipeline {
+            Some(pipeline) => pipeline.iter().map(String::as_str).collect::<Vec<_>>(),
+            None => BUILTIN_PASSES
+                .iter()
+                .filter(|&&(name, default)| {
+                    default || settings.enabled_passes.iter().any(|p| p == name)
+                })
+                .map(|&(name, _)| name)
+                .collect(),
+        };
+        let mut pm = Self::new(settings.clone());
+        for name in names {
+            if !is_disabled(name) {
+                pm.passes.push(builtin_pass(name).unwrap());
+            }
+        }
+        Ok(pm)
+    }
+
+    /// Returns the settings passed to the passes.
+    pub fn settings(&self) -> &OptimizerSettings {
+        &self.settings
+    }
+
+    /// Sets the maximum number of times the pipeline is repeated.
+    pub fn set_max_iterations(&mut self, max_iterations: usize) {
+        self.max_iterations = max_iterations;
+    }
+
+    /// Returns the names of the passes, in order.
+    pub fn pass_names(&self) -> impl Iterator<Item = &'static str> + '_ {
+        self.passes.iter().map(|pass| pass.name())
+    }
+
+    /// Appends a pass to the pipeline.
+    pub fn add_pass(&mut self, pass: impl Pass + 'static) {
+        self.passes.push(Box::new(pass));
+    }
+
+    /// Inserts a pass before the pass with the given name, or at the end of the pipeline if there
+    /// is no such pass.
+    pub fn insert_pass_before(&mut self, name: &str, pass: impl Pass + 'static) {
+        let index = self.passes.iter().position(|p| p.name() == name).unwrap_or(self.passes.len());
+        self.passes.insert(index, Box::new(pass));
+    }
+
+    /// Removes the pass with the given name. Returns `true` if it was present.
+    pub fn remove_pass(&mut self, name: &str) -> bool {
+        let len = self.passes.len();
+        self.passes.retain(|pass| pass.name() != name);
+        self.passes.len() != len
+    }
+
+    /// Runs the pipeline on an object and its children.
+    ///
+    /// The pipeline is repeated until no pass changes the code, up to the maximum number of
+    /// iterations.
+    #[instrument(level = "debug", skip_all)]
+    pub fn run<'ast>(&mut self, arena: &'ast Arena, object: &mut yul::Object<'ast>) {
+        let settings = self.settings.clone();
+        let cx = PassCx { arena, settings: &settings };
+        self.run_object(&cx, object);
+    }
+
+    fn run_object<'ast>(&mut self, cx: &PassCx<'_, 'ast>, object: &mut yul::Object<'ast>) {
+        for iteration in 0..self.max_iterations {
+            let mut changed = false;
+            for pass in &mut self.passes {
+                let pass_changed = pass.run(cx, object);
+                trace!(object = %object.name.value, iteration, pass = pass.name(), pass_changed);
+                changed |= pass_changed;
+            }
+            if !changed {
+                break;
+            }
+        }
+        for child in object.children.iter_mut() {
+            self.run_object(cx, child);
+        }
+    }
+}
+
+/// Moves a statement out of a block, leaving an empty block statement in its place.
+pub fn take_stmt<'ast>(stmt: &mut yul::Stmt<'ast>) -> yul::Stmt<'ast> {
+    let empty = yul::Stmt {
+        docs: Default::default(),
+        span: stmt.span,
+        kind: yul::StmtKind::Block(Default::default()),
+    };
+    std::mem::replace(stmt, empty)
+}
+
+/// Calls `f` on every block nested in `block`, innermost first, and then on `block` itself.
+///
+/// Returns `true` if any call returned `true`.
+pub fn visit_blocks_mut<'ast>(
+    block: &mut yul::Block<'ast>,
+    f: &mut impl FnMut(&mut yul::Block<'ast>) -> bool,
+) -> bool {
+    let mut changed = false;
+    for stmt in block.iter_mut() {
+        changed |= match &mut stmt.kind {
+            yul::StmtKind::Block(block) | yul::StmtKind::If(_, block) => visit_blocks_mut(block, f),
+            yul::StmtKind::For { init, step, body, .. } => {
+                visit_blocks_mut(init, f) | visit_blocks_mut(step, f) | visit_blocks_mut(body, f)
+            }
+            yul::StmtKind::Switch(switch) => {
+                let mut changed = false;
+                for case in switch.branches.iter_mut() {
+                    changed |= visit_blocks_mut(&mut case.body, f);
+                }
+                if let Some(default) = &mut switch.default_case {
+                    changed |= visit_blocks_mut(default, f);
+                }
+                changed
+            }
+            yul::StmtKind::FunctionDef(function) => visit_blocks_mut(&mut function.body, f),
+            _ => false,
+        };
+    }
+    changed | f(block)
+}
+
+/// Calls `f` on every expression in `block` and its nested blocks, arguments before the calls
+/// they are passed to.
+///
+/// Returns `true` if any call returned `true`.
+pub fn visit_exprs_mut<'ast>(
+    block: &mut yul::Block<'ast>,
+    f: &mut impl FnMut(&mut yul::Expr<'ast>) -> bool,
+) -> bool {
+    let mut changed = false;
+    for stmt in block.iter_mut() {
+        changed |= match &mut stmt.kind {
+            yul::StmtKind::Block(block) => visit_exprs_mut(block, f),
+            yul::StmtKind::AssignSingle(_, expr) => visit_expr_mut(expr, f),
+            yul::StmtKind::AssignMulti(_, call) | yul::StmtKind::Expr(call) => {
+                visit_call_args_mut(call, f)
+            }
+            yul::StmtKind::If(cond, body) => visit_expr_mut(cond, f) | visit_exprs_mut(body, f),
+            yul::StmtKind::For { init, cond, step, body } => {
+                visit_exprs_mut(init, f)
+                    | visit_expr_mut(cond, f)
+                    | visit_exprs_mut(step, f)
+                    | visit_exprs_mut(body, f)
+            }
+            yul::StmtKind::Switch(switch) => {
+                let mut changed = visit_expr_mut(&mut switch.selector, f);
+                for case in switch.branches.iter_mut() {
+                    changed |= visit_exprs_mut(&mut case.body, f);
+                }
+                if let Some(default) = &mut switch.default_case {
+                    changed |= visit_exprs_mut(default, f);
+                }
+                changed
+            }
+            yul::StmtKind::FunctionDef(function) => visit_exprs_mut(&mut function.body, f),
+            yul::StmtKind::VarDecl(_, Some(value)) => visit_expr_mut(value, f),
+            yul::StmtKind::VarDecl(_, None)
+            | yul::StmtKind::Leave
+            | yul::StmtKind::Break
+            | yul::StmtKind::Continue => false,
+        };
+    }
+    changed
+}
+
+fn visit_expr_mut<'ast>(
+    expr: &mut yul::Expr<'ast>,
+    f: &mut impl FnMut(&mut yul::Expr<'ast>) -> bool,
+) -> bool {
+    let changed = match &mut expr.kind {
+        yul::ExprKind::Call(call) => visit_call_args_mut(call, f),
+        yul::ExprKind::Path(_) | yul::ExprKind::Lit(_) => false,
+    };
+    changed | f(expr)
+}
+
+fn visit_call_args_mut<'ast>(
+    call: &mut yul::ExprCall<'ast>,
+    f: &mut impl FnMut(&mut yul::Expr<'ast>) -> bool,
+) -> bool {
+    let mut changed = false;
+    for arg in call.arguments.iter_mut() {
+        changed |= visit_expr_mut(arg, f);
+    }
+    changed
+}
+
+/// Returns a path consisting of a single identifier.
+pub fn single_path<'ast>(arena: &'ast Arena, ident: Ident) -> AstPath<'ast> {
+    PathSlice::from_mut_slice(arena.alloc_slice_copy(&[ident]))
+}
+
+/// Returns the names of the variables assigned to in `block` and its nested blocks.
+pub fn assigned_variables(block: &mut yul::Block<'_>) -> FxHashSet<Symbol> {
+    let mut assigned = FxHashSet::default();
+    visit_blocks_mut(block, &mut |block| {
+        for stmt in block.iter() {
+            match &stmt.kind {
+                yul::StmtKind::AssignSingle(path, _) => _ = assigned.insert(path.last().name),
+                yul::StmtKind::AssignMulti(paths, _) => {
+                    assigned.extend(paths.iter().map(|path| path.last().name))
+                }
+                _ => {}
+            }
+        }
+        false
+    });
+    assigned
+}
+
+/// Returns the number of calls of each function and builtin in `block` and its nested blocks.
+pub fn call_counts(block: &mut yul::Block<'_>) -> FxHashMap<Symbol, usize> {
+    let mut calls = FxHashMap::<Symbol, usize>::default();
+    visit_exprs_mut(block, &mut |expr| {
+        if let yul::ExprKind::Call(call) = &expr.kind {
+            *calls.entry(call.name.name).or_default() += 1;
+        }
+        false
+    });
+    visit_blocks_mut(block, &mut |block| {
+        for stmt in block.iter() {
+            if let yul::StmtKind::AssignMulti(_, call) | yul::StmtKind::Expr(call) = &stmt.kind {
+                *calls.entry(call.name.name).or_default() += 1;
+            }
+        }
+        false
+    });
+    calls
+}
+
+/// Builtins that have no side effects and cannot revert.
+const SIDE_EFFECT_FREE_BUILTINS: &[&str] = &[
+    "add",
+    "sub",
+    "mul",
+    "div",
+    "sdiv",
+    "mod",
+    "smod",
+    "exp",
+    "addmod",
+    "mulmod",
+    "signextend",
+    "not",
+    "lt",
+    "gt",
+    "slt",
+    "sgt",
+    "eq",
+    "iszero",
+    "and",
+    "or",
+    "xor",
+    "byte",
+    "shl",
+    "shr",
+    "sar",
+    "keccak256",
+    "address",
+    "balance",
+    "selfbalance",
+    "origin",
+    "caller",
+    "callvalue",
+    "calldataload",
+    "calldatasize",
+    "codesize",
+    "gasprice",
+    "extcodesize",
+    "extcodehash",
+    "returndatasize",
+    "blockhash",
+    "coinbase",
+    "timestamp",
+    "number",
+    "prevrandao",
+    "gaslimit",
+    "chainid",
+    "basefee",
+    "blobbasefee",
+    "gas",
+    "mload",
+    "sload",
+    "tload",
+    "msize",
+];
+
+/// Returns `true` if evaluating the expression has no side effects and cannot revert, so that
+/// it can be removed if its value is unused.
+pub fn is_side_effect_free(expr: &yul::Expr<'_>) -> bool {
+    match &expr.kind {
+        yul::ExprKind::Path(_) | yul::ExprKind::Lit(_) => true,
+        yul::ExprKind::Call(call) => {
+            SIDE_EFFECT_FREE_BUILTINS.contains(&call.name.as_str())
+                && call.arguments.iter().all(is_side_effect_free)
+        }
+    }
+}
+
+/// Generates variable names that are not used anywhere in a block.
+pub struct NameDispenser {
+    used: FxHashSet<Symbol>,
+}
+
+impl NameDispenser {
+    /// Creates a new name dispenser avoiding the names declared in `code`.
+    pub fn new(code: &mut yul::Block<'_>) -> Self {
+        let mut used = FxHashSet::default();
+        visit_blocks_mut(code, &mut |block| {
+            for stmt in block.iter() {
+                match &stmt.kind {
+                    yul::StmtKind::VarDecl(names, _) => {
+                        used.extend(names.iter().map(|name| name.name))
+                    }
+                    yul::StmtKind::FunctionDef(f) => {
+                        used.insert(f.name.name);
+                        used.extend(f.parameters.iter().chain(f.returns.iter()).map(|n| n.name));
+                    }
+                    _ => {}
+                }
+            }
+            false
+        });
+        Self { used }
+    }
+
+    /// Returns a new name derived from `base`.
+    pub fn fresh(&mut self, base: Symbol) -> Symbol {
+        (1..)
+            .map(|i| Symbol::intern(&format!("{base}_{i}")))
+            .find(|&name| self.used.insert(name))
+            .unwrap()
+    }
+}
+
+/// Returns the value of a number or boolean literal expression.
+pub fn literal_value(expr: &yul::Expr<'_>) -> Option<U256> {
+    match &expr.kind {
+        yul::ExprKind::Lit(lit) => lit_value(lit),
+        _ => None,
+    }
+}
+
+/// Returns the value of a number or boolean literal.
+pub fn lit_value(lit: &Lit) -> Option<U256> {
+    match &lit.kind {
+        LitKind::Number(n) => {
+            let (sign, bytes) = n.to_bytes_be();
+            (sign != num_bigint::Sign::Minus && bytes.len() <= 32)
+                .then(|| U256::from_be_slice(&bytes))
+        }
+        LitKind::Bool(b) => Some(U256::from(*b as u8)),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use solar_interface::{source_map::FileName, Session};
+
+    /// Parses a Yul object, runs the given passes on it, and returns the printed result with
+    /// normalized whitespace.
+    pub(super) fn optimize(src: &str, pipeline: &[&str]) -> String {
+        let mut out = String::new();
+        solar_interface::enter(|| -> solar_interface::Result {
+            let sess = Session::builder().with_test_emitter().build();
+            let arena = Arena::new();
+            let name = FileName::Custom("test".into());
+            let mut parser =
+                solar_parse::Parser::from_source_code(&sess, &arena, name, src.into())?;
+            let mut object = parser.parse_yul_file_object().map_err(|e| e.emit())?;
+            let settings = OptimizerSettings {
+                pipeline: Some(pipeline.iter().map(ToString::to_string).collect()),
+                ..Default::default()
+            };
+            PassManager::from_settings(&settings).unwrap().run(&arena, &mut object);
+            out = crate::ir::print_object(&object);
+            Ok(())
+        })
+        .unwrap();
+        out.split_whitespace().collect::<Vec<_>>().join(" ")
+    }
+
+    #[test]
+    fn from_settings() {
+        let settings = OptimizerSettings::default();
+        let pm = PassManager::from_settings(&settings).unwrap();
+        assert_eq!(
+            pm.pass_names().collect::<Vec<_>>(),
+            [
+                "expression-simplifier",
+                "constant-folder",
+                "function-inliner",
+                "dead-code-eliminator",
+                "block-flattener"
+            ]
+        );
+
+        let settings = OptimizerSettings {
+            enabled_passes: vec!["ssa-transform".into()],
+            disabled_passes: vec!["function-inliner".into(), "block-flattener".into()],
+            ..Default::default()
+        };
+        let pm = PassManager::from_settings(&settings).unwrap();
+        assert_eq!(
+            pm.pass_names().collect::<Vec<_>>(),
+            ["ssa-transform", "expression-simplifier", "constant-folder", "dead-code-eliminator"]
+        );
+
+        let settings = OptimizerSettings {
+            pipeline: Some(vec!["constant-folder".into(), "block-flattener".into()]),
+            disabled_passes: vec!["block-flattener".into()],
+            ..Default::default()
+        };
+        let pm = PassManager::from_settings(&settings).unwrap();
+        assert_eq!(pm.pass_names().collect::<Vec<_>>(), ["constant-folder"]);
+
+        let settings =
+            OptimizerSettings { enabled_passes: vec!["unknown".into()], ..Default::default() };
+        assert!(PassManager::from_settings(&settings).is_err());
+        let settings =
+            OptimizerSettings { pipeline: Some(vec!["unknown".into()]), ..Default::default() };
+        assert!(PassManager::from_settings(&settings).is_err());
+
+        for &(name, _) in BUILTIN_PASSES {
+            assert_eq!(builtin_pass(name).unwrap().name(), name);
+        }
+    }
+
+    #[test]
+    fn insert_pass() {
+        let mut pm = PassManager::default();
+        pm.add_pass(BlockFlattener);
+        struct Custom;
+        impl Pass for Custom {
+            fn name(&self) -> &'static str {
+                "custom"
+            }
+
+            fn run<'ast>(&mut self, _: &PassCx<'_, 'ast>, _: &mut yul::Object<'ast>) -> bool {
+                false
+            }
+        }
+        pm.insert_pass_before("block-flattener", Custom);
+        assert_eq!(pm.pass_names().collect::<Vec<_>>(), ["custom", "block-flattener"]);
+        assert!(pm.remove_pass("custom"));
+        assert!(!pm.remove_pass("custom"));
+    }
+}
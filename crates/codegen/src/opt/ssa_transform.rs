@@ -0,0 +1,219 @@
+use super::{assigned_variables, single_path, take_stmt, NameDispenser, Pass, PassCx};
+use solar_ast::{yul, Arena};
+use solar_data_structures::map::{FxHashMap, FxHashSet};
+use solar_interface::{Ident, Span, Symbol};
+
+/// Introduces a new variable for every value assigned to a variable: `a := v` becomes
+/// `let a_1 := v a := a_1`, and the following reads of `a` in the same control flow path read
+/// `a_1` instead.
+///
+/// The new variables are never reassigned, which lets other passes propagate and remove them.
+/// Assignments of literals and of variables that are never reassigned are kept as is.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct SsaTransform;
+
+impl Pass for SsaTransform {
+    fn name(&self) -> &'static str {
+        "ssa-transform"
+    }
+
+    fn run<'ast>(&mut self, cx: &PassCx<'_, 'ast>, object: &mut yul::Object<'ast>) -> bool {
+        let code = &mut object.code.code;
+        let mut ssa = Ssa {
+            arena: cx.arena,
+            names: NameDispenser::new(code),
+            assigned: assigned_variables(code),
+            changed: false,
+        };
+        ssa.block(code, &mut FxHashMap::default());
+        ssa.changed
+    }
+}
+
+/// The current SSA variable of each reassigned variable.
+type Current = FxHashMap<Symbol, Symbol>;
+
+struct Ssa<'ast> {
+    arena: &'ast Arena,
+    names: NameDispenser,
+    /// The variables that are assigned to anywhere in the code, before the transform.
+    assigned: FxHashSet<Symbol>,
+    changed: bool,
+}
+
+impl<'ast> Ssa<'ast> {
+    fn block(&mut self, block: &mut yul::Block<'ast>, current: &mut Current) {
+        // Statements to insert before and after the statement at each index.
+        let mut insertions = Vec::new();
+        for (i, stmt) in block.iter_mut().enumerate() {
+            let span = stmt.span;
+            match &mut stmt.kind {
+                yul::StmtKind::AssignSingle(path, value) => {
+                    self.expr(value, current);
+                    let name = path.last().name;
+                    if matches!(value.kind, yul::ExprKind::Call(_)) {
+                        let fresh = self.names.fresh(name);
+                        let value = std::mem::replace(value, self.var(fresh, span));
+                        insertions.push((i, vec![self.let_(&[fresh], value, span)], Vec::new()));
+                        current.insert(name, fresh);
+                        continue;
+                    }
+                    let alias = match &value.kind {
+                        yul::ExprKind::Path(value) => {
+                            Some(value.last().name).filter(|name| !self.assigned.contains(name))
+                        }
+                        _ => None,
+                    };
+                    match alias {
+                        Some(alias) => _ = current.insert(name, alias),
+                        None => _ = current.remove(&name),
+                    }
+                }
+                yul::StmtKind::AssignMulti(paths, call) => {
+                    self.call(call, current);
+                    let names = paths.iter().map(|path| path.last().name).collect::<Vec<_>>();
+                    let fresh =
+                        names.iter().map(|&name| self.names.fresh(name)).collect::<Vec<_>>();
+                    let call = std::mem::replace(
+                        call,
+                        yul::ExprCall { name: call.name, arguments: Default::default() },
+                    );
+                    let value = yul::Expr { span, kind: yul::ExprKind::Call(call) };
+                    *stmt = self.let_(&fresh, value, span);
+                    let mut after = Vec::with_capacity(names.len());
+                    for (&name, &fresh) in names.iter().zip(&fresh) {
+                        let path = single_path(self.arena, Ident::new(name, span));
+                        let assign = yul::StmtKind::AssignSingle(path, self.var(fresh, span));
+                        after.push(yul::Stmt { docs: Default::default(), span, kind: assign });
+                        current.insert(name, fresh);
+                    }
+                    insertions.push((i, Vec::new(), after));
+                }
+                yul::StmtKind::VarDecl(names, value) => {
+                    if let Some(value) = value {
+                        self.expr(value, current);
+                    }
+                    for name in names.iter() {
+                        current.remove(&name.name);
+                    }
+                }
+                yul::StmtKind::Expr(call) => self.call(call, current),
+                yul::StmtKind::Block(inner) => self.nested(inner, current),
+                yul::StmtKind::If(cond, body) => {
+                    self.expr(cond, current);
+                    self.nested(body, current);
+                }
+                yul::StmtKind::Switch(switch) => {
+                    self.expr(&mut switch.selector, current);
+                    let mut assigned = FxHashSet::default();
+                    for case in switch.branches.iter_mut() {
+                        self.block(&mut case.body, &mut current.clone());
+                        assigned.extend(assigned_variables(&mut case.body));
+                    }
+                    if let Some(default) = &mut switch.default_case {
+                        self.block(default, &mut current.clone());
+                        assigned.extend(assigned_variables(default));
+                    }
+                    current.retain(|name, _| !assigned.contains(name));
+                }
+                yul::StmtKind::For { init, cond, step, body } => {
+                    // The variables declared in `init` are only visible in the loop, and the
+                    // variables assigned in the loop have different values in each iteration.
+                    let mut inner = current.clone();
+                    self.block(init, &mut inner);
+                    let mut assigned = assigned_variables(step);
+                    assigned.extend(assigned_variables(body));
+                    inner.retain(|name, _| !assigned.contains(name));
+                    self.expr(cond, &inner);
+                    self.block(body, &mut inner.clone());
+                    self.block(step, &mut inner);
+                    assigned.extend(assigned_variables(init));
+                    current.retain(|name, _| !assigned.contains(name));
+                }
+                yul::StmtKind::FunctionDef(function) => {
+                    self.block(&mut function.body, &mut Current::default())
+                }
+                yul::StmtKind::Leave | yul::StmtKind::Break | yul::StmtKind::Continue => {}
+            }
+        }
+        if insertions.is_empty() {
+            return;
+        }
+
+        let mut stmts = Vec::with_capacity(block.len() + insertions.len() * 2);
+        let mut insertions = insertions.into_iter().peekable();
+        for (i, stmt) in block.iter_mut().enumerate() {
+            match insertions.next_if(|&(j, ..)| j == i) {
+                Some((_, before, after)) => {
+                    stmts.extend(before);
+                    stmts.push(take_stmt(stmt));
+                    stmts.extend(after);
+                }
+                None => stmts.push(take_stmt(stmt)),
+            }
+        }
+        *block = self.arena.alloc_vec(stmts);
+        self.changed = true;
+    }
+
+    /// Transforms a nested block, whose variables go out of scope at its end.
+    fn nested(&mut self, block: &mut yul::Block<'ast>, current: &mut Current) {
+        self.block(block, &mut current.clone());
+        let assigned = assigned_variables(block);
+        current.retain(|name, _| !assigned.contains(name));
+    }
+
+    fn expr(&mut self, expr: &mut yul::Expr<'ast>, current: &Current) {
+        match &mut expr.kind {
+            yul::ExprKind::Path(path) => {
+                if let Some(&name) = current.get(&path.last().name) {
+                    *path = single_path(self.arena, Ident::new(name, path.last().span));
+                    self.changed = true;
+                }
+            }
+            yul::ExprKind::Call(call) => self.call(call, current),
+            yul::ExprKind::Lit(_) => {}
+        }
+    }
+
+    fn call(&mut self, call: &mut yul::ExprCall<'ast>, current: &Current) {
+        for arg in call.arguments.iter_mut() {
+            self.expr(arg, current);
+        }
+    }
+
+    fn var(&self, name: Symbol, span: Span) -> yul::Expr<'ast> {
+        yul::Expr {
+            span,
+            kind: yul::ExprKind::Path(single_path(self.arena, Ident::new(name, span))),
+        }
+    }
+
+    /// Returns `let names := value`.
+    fn let_(&self, names: &[Symbol], value: yul::Expr<'ast>, span: Span) -> yul::Stmt<'ast> {
+        let names = self.arena.alloc_from_iter(names.iter().map(|&name| Ident::new(name, span)));
+        yul::Stmt {
+            docs: Default::default(),
+            span,
+            kind: yul::StmtKind::VarDecl(names, Some(value)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::opt::tests::optimize;
+
+    #[test]
+    fn reassignment() {
+        let src = r#"object "a" { code {
+            let a := calldataload(0)
+            a := add(a, 1)
+            sstore(0, a)
+        } }"#;
+        assert_eq!(
+            optimize(src, &["ssa-transform"]),
+            r#"object "a" { code { let a := calldataload(0) let a_1 := add(a, 1) a := a_1 sstore(0, a_1) } }"#
+        );
+    }
+}
@@ -0,0 +1,579 @@
+//! Name and type resolution of HIR expressions, shared by the code generators.
+
+use alloy_primitives::Selector;
+use solar_ast::{BinOpKind, DataLocation, ElementaryType, LitKind, UnOpKind, Visibility};
+use solar_interface::{kw, sym, Ident};
+use solar_sema::{
+    builtins::Builtin,
+    hir,
+    ty::{Gcx, Ty, TyKind},
+};
+use std::borrow::Cow;
+
+/// The target of a call expression.
+pub(crate) enum Callee<'a, 'gcx> {
+    /// A conversion to the given type.
+    Conversion(Ty<'gcx>),
+    /// An internal function call, already resolved to its implementation.
+    Internal(hir::FunctionId),
+    /// A call of a public or external library function, executed with `DELEGATECALL` in the
+    /// context of the calling contract.
+    Library(hir::FunctionId),
+    /// A builtin function, along with the expression it is a member of, if any.
+    Builtin(Builtin, Option<&'a hir::Expr<'a>>),
+    /// `push` on a storage array.
+    ArrayPush(&'a hir::Expr<'a>),
+    /// `pop` on a storage array.
+    ArrayPop(&'a hir::Expr<'a>),
+    /// An unsupported call.
+    Unsupported(&'static str),
+}
+
+/// Resolves the types and call targets of expressions in the context of a contract being
+/// compiled.
+#[derive(Clone, Copy)]
+pub(crate) struct Resolver<'gcx> {
+    pub(crate) gcx: Gcx<'gcx>,
+    /// The contract being compiled.
+    pub(crate) contract: hir::ContractId,
+    /// The function being lowered, if any.
+    pub(crate) function: Option<hir::FunctionId>,
+}
+
+impl<'gcx> Resolver<'gcx> {
+    pub(crate) fn new(
+        gcx: Gcx<'gcx>,
+        contract: hir::ContractId,
+        function: Option<hir::FunctionId>,
+    ) -> Self {
+        Self { gcx, contract, function }
+    }
+
+    /// Returns `true` if the expression is a number literal, or an operation on number literals,
+    /// which is evaluated at compile time.
+    pub(crate) fn is_literal(&self, expr: &hir::Expr<'_>) -> bool {
+        match expr.peel_parens().kind {
+            hir::ExprKind::Lit(lit) => {
+                matches!(lit.kind, LitKind::Number(_) | LitKind::Rational(_))
+            }
+            hir::ExprKind::Unary(op, x) => {
+                matches!(op.kind, UnOpKind::Neg | UnOpKind::BitNot) && self.is_literal(x)
+            }
+            hir::ExprKind::Binary(l, _, r) => self.is_literal(l) && self.is_literal(r),
+            _ => false,
+        }
+    }
+
+    /// Returns the type of an expression, without lowering it.
+    pub(crate) fn expr_ty(&self, expr: &hir::Expr<'_>) -> Ty<'gcx> {
+        let gcx = self.gcx;
+        let types = &gcx.types;
+        match expr.kind {
+            hir::ExprKind::Assign(lhs, ..) => self.expr_ty(lhs),
+            hir::ExprKind::Binary(l, op, r) => match op.kind {
+                BinOpKind::Lt
+                | BinOpKind::Le
+                | BinOpKind::Gt
+                | BinOpKind::Ge
+                | BinOpKind::Eq
+                | BinOpKind::Ne
+                | BinOpKind::And
+                | BinOpKind::Or => types.bool,
+                BinOpKind::Shl | BinOpKind::Shr | BinOpKind::Sar | BinOpKind::Pow => {
+                    self.expr_ty(l)
+                }
+                _ => self.common_ty(l, r),
+            },
+            hir::ExprKind::Call(callee, ref args) => match self.resolve_callee(callee, args) {
+                Callee::Conversion(ty) => ty,
+                Callee::Internal(id) | Callee::Library(id) => self.return_ty(id),
+                Callee::Builtin(b, _) => match b.ty(gcx).kind {
+                    TyKind::FnPtr(f) => match f.returns {
+                        [ret] => *ret,
+                        rets => gcx.mk_ty(TyKind::Tuple(rets)),
+                    },
+                    _ => types.unit,
+                },
+                Callee::ArrayPush(_) | Callee::ArrayPop(_) | Callee::Unsupported(_) => types.unit,
+            },
+            hir::ExprKind::Ident(res) => match res {
+                [hir::Res::Item(id), ..] => gcx.type_of_item(*id),
+                [hir::Res::Builtin(Builtin::This)] => gcx.mk_ty(TyKind::Contract(self.contract)),
+                [hir::Res::Builtin(
+                    Builtin::Super
+                    | Builtin::TypeMin
+                    | Builtin::TypeMax
+                    | Builtin::UdvtWrap
+                    | Builtin::UdvtUnwrap,
+                )] => types.unit,
+                [hir::Res::Builtin(b), ..] => b.ty(gcx),
+                _ => types.unit,
+            },
+            hir::ExprKind::Index(base, _) => match self.expr_ty(base).peel_refs().kind {
+                TyKind::Mapping(_, value) => value,
+                TyKind::Array(elem, _) | TyKind::DynArray(elem) => elem,
+                TyKind::Elementary(_) => types.fixed_bytes(1),
+                _ => types.unit,
+            },
+            hir::ExprKind::Lit(lit) => match lit.kind {
+                LitKind::Address(_) => types.address,
+                LitKind::Bool(_) => types.bool,
+                LitKind::Str(..) => types.string_ref.memory,
+                _ => self.literal_ty(expr),
+            },
+            hir::ExprKind::Member(base, name) => self.member_ty(base, name),
+            hir::ExprKind::Payable(_) => types.address_payable,
+            hir::ExprKind::Ternary(_, t, f) => self.common_ty(t, f),
+            hir::ExprKind::Tuple(&[Some(x)]) => self.expr_ty(x),
+            hir::ExprKind::Tuple(exprs) => gcx.mk_ty(TyKind::Tuple(
+                gcx.mk_ty_iter(exprs.iter().map(|x| x.map_or(types.unit, |x| self.expr_ty(x)))),
+            )),
+            hir::ExprKind::Unary(op, x) => match op.kind {
+                UnOpKind::Not => types.bool,
+                _ if self.is_literal(expr) => self.literal_ty(expr),
+                _ => self.expr_ty(x),
+            },
+            _ => types.unit,
+        }
+    }
+
+    /// Returns the type of a literal expression that is not implicitly converted to another type.
+    pub(crate) fn literal_ty(&self, expr: &hir::Expr<'_>) -> Ty<'gcx> {
+        match expr.peel_parens().kind {
+            hir::ExprKind::Unary(op, _) if op.kind == UnOpKind::Neg => self.gcx.types.int(256),
+            _ => self.gcx.types.uint(256),
+        }
+    }
+
+    /// Returns the type both operands of a binary operation are converted to.
+    pub(crate) fn common_ty(&self, l: &hir::Expr<'_>, r: &hir::Expr<'_>) -> Ty<'gcx> {
+        match (self.is_literal(l), self.is_literal(r)) {
+            (true, false) => self.expr_ty(r),
+            (false, true) => self.expr_ty(l),
+            _ => {
+                let (a, b) = (self.expr_ty(l), self.expr_ty(r));
+                match (int_info(a), int_info(b)) {
+                    (Some((_, a_bits)), Some((_, b_bits))) if b_bits > a_bits => b,
+                    _ => a,
+                }
+            }
+        }
+    }
+
+    pub(crate) fn member_ty(&self, base: &hir::Expr<'_>, name: Ident) -> Ty<'gcx> {
+        let gcx = self.gcx;
+        let types = &gcx.types;
+        match base.peel_parens().kind {
+            hir::ExprKind::Ident(res) => {
+                for &res in res {
+                    match res {
+                        hir::Res::Builtin(b) => {
+                            if let Some(member) = builtin_member(b, name) {
+                                return member.ty(gcx);
+                            }
+                        }
+                        hir::Res::Item(hir::ItemId::Enum(id)) => {
+                            return gcx.mk_ty(TyKind::Enum(id));
+                        }
+                        hir::Res::Item(hir::ItemId::Contract(c)) => {
+                            if let Some(var) = self.contract_constant(c, name) {
+                                return gcx.type_of_item(var.into());
+                            }
+                        }
+                        hir::Res::Item(hir::ItemId::Error(_)) => return types.fixed_bytes(4),
+                        hir::Res::Item(hir::ItemId::Event(_)) => return types.fixed_bytes(32),
+                        _ => {}
+                    }
+                }
+            }
+            hir::ExprKind::TypeCall(ref ty) => {
+                if name.name == Builtin::InterfaceId.name() {
+                    return types.fixed_bytes(4);
+                }
+                return gcx.type_of_hir_ty(ty);
+            }
+            hir::ExprKind::Member(..) if name.name == Builtin::ErrorSelector.name() => {
+                return types.fixed_bytes(4);
+            }
+            _ => {}
+        }
+        let base_ty = self.expr_ty(base);
+        match base_ty.peel_refs().kind {
+            TyKind::Struct(id) => self
+                .struct_field(id, name)
+                .map(|field| gcx.type_of_item(field.into()))
+                .unwrap_or(types.unit),
+            TyKind::Elementary(ElementaryType::FixedBytes(_))
+                if name.name == Builtin::FixedBytesLength.name() =>
+            {
+                types.uint(8)
+            }
+            _ if name.name == Builtin::ArrayLength.name() => types.uint(256),
+            _ if name.name == Builtin::AddressBalance.name() => types.uint(256),
+            _ if name.name == Builtin::AddressCodehash.name() => types.fixed_bytes(32),
+            _ => types.unit,
+        }
+    }
+
+    /// Returns the type of the value returned by a call to the given function.
+    pub(crate) fn return_ty(&self, id: hir::FunctionId) -> Ty<'gcx> {
+        let gcx = self.gcx;
+        match gcx.hir.function(id).returns {
+            [ret] => gcx.type_of_item((*ret).into()),
+            rets => gcx.mk_ty(TyKind::Tuple(
+                gcx.mk_ty_iter(rets.iter().map(|&ret| gcx.type_of_item(ret.into()))),
+            )),
+        }
+    }
+
+    /// Finds a constant named `name` in the given contract or its bases.
+    pub(crate) fn contract_constant(
+        &self,
+        c: hir::ContractId,
+        name: Ident,
+    ) -> Option<hir::VariableId> {
+        let gcx = self.gcx;
+        gcx.hir.contract(c).linearized_bases.iter().find_map(|&base| {
+            gcx.hir.contract(base).variables().find(|&var| {
+                let var = gcx.hir.variable(var);
+                var.is_constant() && var.name == Some(name)
+            })
+        })
+    }
+
+    pub(crate) fn struct_field(&self, id: hir::StructId, name: Ident) -> Option<hir::VariableId> {
+        let gcx = self.gcx;
+        gcx.hir.strukt(id).fields.iter().copied().find(|&f| gcx.hir.variable(f).name == Some(name))
+    }
+
+    /// Returns the selector of `C.f` or `c.f`, where `C` is a contract and `c` a contract value.
+    pub(crate) fn external_function_selector(
+        &self,
+        contract: &hir::Expr<'_>,
+        name: Ident,
+    ) -> Option<Selector> {
+        let gcx = self.gcx;
+        let id = match contract.peel_parens().kind {
+            hir::ExprKind::Ident(&[hir::Res::Item(hir::ItemId::Contract(id))]) => id,
+            _ => match self.expr_ty(contract).kind {
+                TyKind::Contract(id) => id,
+                _ => return None,
+            },
+        };
+        gcx.interface_functions(id)
+            .all()
+            .iter()
+            .find(|f| gcx.hir.function(f.id).name == Some(name))
+            .map(|f| f.selector)
+    }
+
+    /// Selects the overload in `res` that `f` maps to `Some` and that matches the arguments.
+    pub(crate) fn select_overload<T>(
+        &self,
+        res: &[hir::Res],
+        args: &hir::CallArgs<'_>,
+        f: impl Fn(hir::ItemId) -> Option<T>,
+    ) -> Option<T> {
+        let mut candidates = res
+            .iter()
+            .filter_map(|res| match *res {
+                hir::Res::Item(id) => Some((id, f(id)?)),
+                _ => None,
+            })
+            .filter(|&(id, _)| self.gcx.item_parameters(id).len() == args.len())
+            .collect::<Vec<_>>();
+        if candidates.len() > 1 {
+            // Disambiguate by the types of the arguments.
+            candidates.retain(|&(id, _)| self.args_match(id, args));
+        }
+        candidates.into_iter().next().map(|(_, t)| t)
+    }
+
+    pub(crate) fn args_match(&self, id: hir::ItemId, args: &hir::CallArgs<'_>) -> bool {
+        let hir::CallArgs::Unnamed(exprs) = *args else { return true };
+        self.gcx.item_parameter_types(id).iter().zip(exprs).all(|(param, arg)| {
+            self.is_literal(arg) || self.expr_ty(arg).peel_refs() == param.peel_refs()
+        })
+    }
+
+    /// Returns the arguments of a call in the order of the parameters.
+    pub(crate) fn ordered_args<'e>(
+        &self,
+        params: &[hir::VariableId],
+        args: &'e hir::CallArgs<'e>,
+    ) -> Vec<&'e hir::Expr<'e>> {
+        match *args {
+            hir::CallArgs::Unnamed(exprs) => exprs.iter().collect(),
+            hir::CallArgs::Named(named) => params
+                .iter()
+                .map(|&param| {
+                    let name = self.gcx.hir.variable(param).name;
+                    &named
+                        .iter()
+                        .find(|arg| Some(arg.name) == name)
+                        .expect("missing argument")
+                        .value
+                })
+                .collect(),
+        }
+    }
+
+    /// Resolves the target of a call.
+    pub(crate) fn resolve_callee<'a>(
+        &self,
+        callee: &'a hir::Expr<'a>,
+        args: &hir::CallArgs<'_>,
+    ) -> Callee<'a, 'gcx> {
+        let gcx = self.gcx;
+        match callee.peel_parens().kind {
+            hir::ExprKind::Ident(res) => {
+                if let Some(id) = self.select_overload(res, args, |id| match id {
+                    hir::ItemId::Function(id) => Some(id),
+                    _ => None,
+                }) {
+                    return Callee::Internal(self.resolve_virtual(id));
+                }
+                match res.first() {
+                    Some(&hir::Res::Builtin(b)) => {
+                        let b = match (b, args.len()) {
+                            (Builtin::Require | Builtin::RequireMsg, 2) => Builtin::RequireMsg,
+                            (Builtin::Require | Builtin::RequireMsg, _) => Builtin::Require,
+                            (Builtin::Revert | Builtin::RevertMsg, 0) => Builtin::Revert,
+                            (Builtin::Revert | Builtin::RevertMsg, _) => Builtin::RevertMsg,
+                            (b, _) => b,
+                        };
+                        Callee::Builtin(b, None)
+                    }
+                    Some(&hir::Res::Item(hir::ItemId::Contract(id))) => {
+                        Callee::Conversion(gcx.mk_ty(TyKind::Contract(id)))
+                    }
+                    Some(&hir::Res::Item(hir::ItemId::Enum(id))) => {
+                        Callee::Conversion(gcx.mk_ty(TyKind::Enum(id)))
+                    }
+                    Some(&hir::Res::Item(hir::ItemId::Struct(_))) => {
+                        Callee::Unsupported("struct constructors")
+                    }
+                    Some(&hir::Res::Item(hir::ItemId::Variable(_))) => {
+                        Callee::Unsupported("calling function pointers")
+                    }
+                    _ => Callee::Unsupported("this call"),
+                }
+            }
+            hir::ExprKind::Type(ref ty) => Callee::Conversion(gcx.type_of_hir_ty(ty)),
+            hir::ExprKind::Member(base, name) => self.resolve_member_callee(base, name, args),
+            hir::ExprKind::New(_) => Callee::Unsupported("`new` expressions"),
+            hir::ExprKind::CallOptions(..) => Callee::Unsupported("external calls"),
+            _ => Callee::Unsupported("calling function pointers"),
+        }
+    }
+
+    pub(crate) fn resolve_member_callee<'a>(
+        &self,
+        base: &'a hir::Expr<'a>,
+        name: Ident,
+        args: &hir::CallArgs<'_>,
+    ) -> Callee<'a, 'gcx> {
+        let gcx = self.gcx;
+        if let hir::ExprKind::Ident(res) = base.peel_parens().kind {
+            match *res {
+                [hir::Res::Builtin(Builtin::Super)] => {
+                    let Some(f) = self.function else { return Callee::Unsupported("`super`") };
+                    let from = gcx.hir.function(f).contract;
+                    let bases = gcx.hir.contract(self.contract).linearized_bases;
+                    let pos = bases.iter().position(|&b| Some(b) == from).unwrap_or(0);
+                    let found = bases[pos + 1..].iter().find_map(|&base| {
+                        gcx.hir.contract(base).functions().find(|&id| {
+                            let f = gcx.hir.function(id);
+                            f.name == Some(name)
+                                && f.body.is_some()
+                                && f.parameters.len() == args.len()
+                        })
+                    });
+                    return match found {
+                        Some(id) => Callee::Internal(id),
+                        None => Callee::Unsupported("this `super` call"),
+                    };
+                }
+                [hir::Res::Builtin(b)] => {
+                    if let Some(member) = builtin_member(b, name) {
+                        return Callee::Builtin(member, None);
+                    }
+                }
+                [hir::Res::Item(hir::ItemId::Contract(c))] => {
+                    let contract = gcx.hir.contract(c);
+                    let res = contract
+                        .functions()
+                        .filter(|&id| gcx.hir.function(id).name == Some(name))
+                        .map(|id| hir::Res::Item(id.into()))
+                        .collect::<Vec<_>>();
+                    let Some(id) = self.select_overload(&res, args, |id| match id {
+                        hir::ItemId::Function(id) => Some(id),
+                        _ => None,
+                    }) else {
+                        return Callee::Unsupported("this call");
+                    };
+                    let f = gcx.hir.function(id);
+                    if contract.kind.is_library()
+                        && matches!(f.visibility, Visibility::Public | Visibility::External)
+                    {
+                        return Callee::Library(id);
+                    }
+                    if f.body.is_none() {
+                        return Callee::Unsupported("external calls");
+                    }
+                    return Callee::Internal(id);
+                }
+                [hir::Res::Item(hir::ItemId::Udvt(id))] => {
+                    let ty = gcx.type_of_item(id.into());
+                    if name.name == Builtin::UdvtWrap.name() {
+                        return Callee::Conversion(ty);
+                    }
+                    if name.name == Builtin::UdvtUnwrap.name() {
+                        let TyKind::Udvt(inner, _) = ty.kind else { unreachable!() };
+                        return Callee::Conversion(inner);
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        let base_ty = self.expr_ty(base);
+        match base_ty.peel_refs().kind {
+            TyKind::Elementary(ElementaryType::Address(_)) => {
+                if name.name == Builtin::AddressPayableTransfer.name() {
+                    Callee::Builtin(Builtin::AddressPayableTransfer, Some(base))
+                } else if name.name == Builtin::AddressPayableSend.name() {
+                    Callee::Builtin(Builtin::AddressPayableSend, Some(base))
+                } else {
+                    Callee::Unsupported("low-level calls")
+                }
+            }
+            TyKind::DynArray(_) if name.name == sym::push => Callee::ArrayPush(base),
+            TyKind::DynArray(_) if name.name == kw::Pop => Callee::ArrayPop(base),
+            TyKind::Contract(_) => Callee::Unsupported("external calls"),
+            TyKind::Type(_) => Callee::Unsupported("`concat`"),
+            _ => Callee::Unsupported("calling functions attached with `using for`"),
+        }
+    }
+
+    /// Resolves a virtual function or modifier in the context of the contract being compiled.
+    pub(crate) fn resolve_virtual(&self, id: hir::FunctionId) -> hir::FunctionId {
+        let f = self.gcx.hir.function(id);
+        if !f.virtual_ || f.contract.is_none() {
+            return id;
+        }
+        let c = self.gcx.hir.contract(self.contract);
+        self.find_in_bases(c.linearized_bases, id).unwrap_or(id)
+    }
+
+    /// Finds the first function in `bases` with the same name and parameter types as `id`.
+    pub(crate) fn find_in_bases(
+        &self,
+        bases: &[hir::ContractId],
+        id: hir::FunctionId,
+    ) -> Option<hir::FunctionId> {
+        let f = self.gcx.hir.function(id);
+        let params = self.gcx.item_parameter_types(id);
+        bases.iter().find_map(|&base| {
+            let base = self.gcx.hir.contract(base);
+            base.functions().find(|&other| {
+                let g = self.gcx.hir.function(other);
+                g.kind == f.kind
+                    && g.name.map(|n| n.name) == f.name.map(|n| n.name)
+                    && g.body.is_some()
+                    && self.gcx.item_parameter_types(other) == params
+            })
+        })
+    }
+}
+
+/// Returns the member of a builtin module with the given name.
+pub(crate) fn builtin_member(b: Builtin, name: Ident) -> Option<Builtin> {
+    b.members()?.iter().copied().find(|member| member.name() == name.name)
+}
+
+/// Returns the bytes of a string literal expression.
+///
+/// `string.concat` and `bytes.concat` calls whose arguments are all string literals are folded.
+pub(crate) fn string_literal<'a>(expr: &'a hir::Expr<'_>) -> Option<Cow<'a, [u8]>> {
+    match expr.peel_parens().kind {
+        hir::ExprKind::Lit(hir::Lit { kind: LitKind::Str(_, bytes), .. }) => {
+            Some(Cow::Borrowed(bytes))
+        }
+        hir::ExprKind::Call(callee, ref args) if is_concat(callee) => {
+            let mut bytes = Vec::new();
+            for arg in args.exprs() {
+                bytes.extend_from_slice(&string_literal(arg)?);
+            }
+            Some(Cow::Owned(bytes))
+        }
+        _ => None,
+    }
+}
+
+/// Returns `true` if the expression is `string.concat` or `bytes.concat`.
+fn is_concat(callee: &hir::Expr<'_>) -> bool {
+    let hir::ExprKind::Member(base, name) = callee.peel_parens().kind else { return false };
+    name.name == sym::concat
+        && matches!(
+            base.peel_parens().kind,
+            hir::ExprKind::Type(hir::Type {
+                kind: hir::TypeKind::Elementary(ElementaryType::String | ElementaryType::Bytes),
+                ..
+            })
+        )
+}
+
+/// Returns the signedness and size in bits of an integer type.
+pub(crate) fn int_info(ty: Ty<'_>) -> Option<(bool, u16)> {
+    match ty.kind {
+        TyKind::Elementary(ElementaryType::Int(size)) => Some((true, size.bits())),
+        TyKind::Elementary(ElementaryType::UInt(size)) => Some((false, size.bits())),
+        TyKind::Udvt(inner, _) => int_info(inner),
+        _ => None,
+    }
+}
+
+/// Returns the underlying type of a user-defined value type.
+pub(crate) fn peel_udvt(ty: Ty<'_>) -> Ty<'_> {
+    match ty.kind {
+        TyKind::Udvt(inner, _) => inner,
+        _ => ty,
+    }
+}
+
+/// Returns `true` if values of the given type are represented by a storage slot.
+pub(crate) fn is_storage_ref(ty: Ty<'_>) -> bool {
+    match ty.kind {
+        TyKind::Ref(_, loc) => loc == DataLocation::Storage,
+        TyKind::Mapping(..) => true,
+        // Reference types nested in storage, such as mapping values.
+        _ => ty.is_reference_type(),
+    }
+}
+
+/// Returns `true` if the given type is a memory or calldata reference.
+pub(crate) fn is_memory_ref(ty: Ty<'_>) -> bool {
+    matches!(ty.kind, TyKind::Ref(_, DataLocation::Memory | DataLocation::Calldata))
+}
+
+/// Returns `true` if the given type is encoded as a single word in the ABI, and is supported by
+/// the code generator.
+pub(crate) fn is_abi_value_type(ty: Ty<'_>) -> bool {
+    match ty.kind {
+        TyKind::Elementary(ty) => {
+            ty.is_value_type()
+                && !matches!(ty, ElementaryType::Fixed(..) | ElementaryType::UFixed(..))
+        }
+        TyKind::Contract(_) | TyKind::Enum(_) | TyKind::Udvt(..) => true,
+        _ => false,
+    }
+}
+
+/// Returns the number of stack slots a value of the given type occupies.
+pub(crate) fn stack_slots(ty: Ty<'_>) -> usize {
+    match ty.kind {
+        TyKind::Tuple(tys) => tys.len(),
+        _ => 1,
+    }
+}
@@ -0,0 +1,183 @@
+//! Contract code size limits.
+
+use crate::{
+    asm::{AsmItem, Assembly},
+    ContractCode,
+};
+use solar_data_structures::map::FxHashMap;
+use solar_interface::{diagnostics::DiagnosticBuilder, Span};
+use solar_sema::{hir, ty::Gcx, ContractBytecode};
+use std::cmp::Reverse;
+
+/// The maximum size of runtime code, introduced in Spurious Dragon.
+///
+/// Reference: <https://eips.ethereum.org/EIPS/eip-170>
+const MAX_CODE_SIZE: usize = 24576;
+
+/// The maximum size of initcode, introduced in Shanghai.
+///
+/// Reference: <https://eips.ethereum.org/EIPS/eip-3860>
+const MAX_INITCODE_SIZE: usize = 2 * MAX_CODE_SIZE;
+
+/// The minimum amount of constant data pushed by an expression for it to be reported.
+const LARGE_CONSTANT_SIZE: usize = 64;
+
+/// The maximum number of functions and expressions reported.
+const MAX_CULPRITS: usize = 3;
+
+/// Warns if the bytecode of the given contract exceeds the size limits of the session's EVM
+/// version, breaking down its size and pointing out the functions and expressions that contribute
+/// the most to it.
+pub(crate) fn check_code_size(
+    gcx: Gcx<'_>,
+    id: hir::ContractId,
+    code: &ContractCode,
+    bytecode: &ContractBytecode,
+) {
+    let evm_version = gcx.sess.evm_version;
+    let contract = gcx.hir.contract(id);
+    let runtime_size = bytecode.runtime_bytecode.code.len();
+    let initcode_size = bytecode.bytecode.code.len();
+
+    if evm_version.has_code_size_limit() && runtime_size > MAX_CODE_SIZE {
+        let metadata_size = code.runtime().aux_data().len();
+        let msg = format!(
+            "contract code size is {runtime_size} bytes and exceeds {MAX_CODE_SIZE} bytes \
+             (a limit introduced in Spurious Dragon)"
+        );
+        let note = format!(
+            "the runtime code of `{}` consists of {} bytes of instructions and {metadata_size} \
+             bytes of metadata",
+            contract.name,
+            runtime_size - metadata_size,
+        );
+        let diag = gcx
+            .dcx()
+            .warn(msg)
+            .span(contract.name.span)
+            .note(note)
+            .note("this contract may not be deployable on mainnet");
+        Culprits::collect(gcx, id, &[code.runtime()]).add_notes(gcx, diag).emit();
+    }
+
+    if evm_version.has_initcode_size_limit() && initcode_size > MAX_INITCODE_SIZE {
+        let msg = format!(
+            "contract initcode size is {initcode_size} bytes and exceeds {MAX_INITCODE_SIZE} \
+             bytes (a limit introduced in Shanghai)"
+        );
+        let note = format!(
+            "the initcode of `{}` consists of {} bytes of creation code and {runtime_size} bytes \
+             of runtime code",
+            contract.name,
+            initcode_size - runtime_size,
+        );
+        let diag = gcx
+            .dcx()
+            .warn(msg)
+            .span(contract.name.span)
+            .note(note)
+            .note("this contract may not be deployable on mainnet");
+        Culprits::collect(gcx, id, &[code.deploy(), code.runtime()]).add_notes(gcx, diag).emit();
+    }
+}
+
+/// The parts of a contract that contribute the most to the size of its code.
+struct Culprits {
+    /// The largest functions and modifiers, with the size of the code generated for them and the
+    /// number of functions that use them.
+    ///
+    /// Modifiers are inlined, so their size includes every use.
+    functions: Vec<(hir::FunctionId, usize, usize)>,
+    /// The expressions that push the most constant data, such as large string literals, with the
+    /// size of the data.
+    constants: Vec<(Span, usize)>,
+}
+
+impl Culprits {
+    /// Attributes the items of the given assemblies, excluding their sub-assemblies, to the
+    /// functions and expressions they were generated for.
+    fn collect(gcx: Gcx<'_>, id: hir::ContractId, assemblies: &[&Assembly]) -> Self {
+        let all_functions = gcx
+            .hir
+            .contract(id)
+            .linearized_bases
+            .iter()
+            .flat_map(|&base| gcx.hir.contract(base).all_functions())
+            .collect::<Vec<_>>();
+
+        let mut functions = FxHashMap::<hir::FunctionId, usize>::default();
+        let mut constants = FxHashMap::<Span, usize>::default();
+        for asm in assemblies {
+            for &(item, span) in asm.items() {
+                if span.is_dummy() {
+                    continue;
+                }
+                let size = asm.item_size(item);
+                if let Some(&f) =
+                    all_functions.iter().find(|&&f| gcx.hir.function(f).span.contains(span))
+                {
+                    *functions.entry(f).or_default() += size;
+                }
+                if let AsmItem::Push(_) = item {
+                    *constants.entry(span).or_default() += size - 1;
+                }
+            }
+        }
+
+        let uses = |id: hir::FunctionId| {
+            let id = hir::ItemId::from(id);
+            all_functions.iter().filter(|&&f| gcx.hir.function(f).modifiers.contains(&id)).count()
+        };
+        let mut functions =
+            functions.into_iter().map(|(f, size)| (f, size, uses(f))).collect::<Vec<_>>();
+        functions.sort_unstable_by_key(|&(f, size, _)| (Reverse(size), f));
+        functions.truncate(MAX_CULPRITS);
+        let mut constants = constants
+            .into_iter()
+            .filter(|&(_, size)| size >= LARGE_CONSTANT_SIZE)
+            .collect::<Vec<_>>();
+        constants.sort_unstable_by_key(|&(span, size)| (Reverse(size), span));
+        constants.truncate(MAX_CULPRITS);
+        Self { functions, constants }
+    }
+
+    /// Adds a note for each culprit to the diagnostic, followed by suggestions to reduce the size
+    /// of the code.
+    fn add_notes<'a>(
+        &self,
+        gcx: Gcx<'_>,
+        mut diag: DiagnosticBuilder<'a, ()>,
+    ) -> DiagnosticBuilder<'a, ()> {
+        let mut inlined_modifiers = false;
+        for &(id, size, uses) in &self.functions {
+            let f = gcx.hir.function(id);
+            let name = match f.name {
+                Some(name) => format!("{} `{name}`", f.kind),
+                None => f.kind.to_string(),
+            };
+            let msg = if f.kind.is_modifier() && uses > 1 {
+                inlined_modifiers = true;
+                format!(
+                    "{name} generates {size} bytes of code, as it is inlined in {uses} functions"
+                )
+            } else {
+                format!("{name} generates {size} bytes of code")
+            };
+            diag = diag.span_note(f.span, msg);
+        }
+        for &(span, size) in &self.constants {
+            diag = diag.span_note(span, format!("this expression embeds {size} bytes of data"));
+        }
+
+        if !self.constants.is_empty() {
+            diag = diag.help("consider shortening revert strings or using custom errors instead");
+        }
+        if inlined_modifiers {
+            diag = diag.help(
+                "consider moving the body of large modifiers into an internal function that they \
+                 call",
+            );
+        }
+        diag.help("consider splitting the contract or moving functionality into libraries")
+    }
+}
@@ -58,10 +58,15 @@ str_enum! {
         #[default]
         Cancun,
         Prague,
+        /// Enables the EVM Object Format (EOF). Generating EOF code is unstable.
+        Osaka,
     }
 }
 
 impl EvmVersion {
+    pub fn has_code_size_limit(self) -> bool {
+        self >= Self::SpuriousDragon
+    }
     pub fn supports_returndata(self) -> bool {
         self >= Self::Byzantium
     }
@@ -95,6 +100,44 @@ impl EvmVersion {
     pub fn has_push0(self) -> bool {
         self >= Self::Shanghai
     }
+    pub fn has_initcode_size_limit(self) -> bool {
+        self >= Self::Shanghai
+    }
+    /// Returns `true` if contracts are deployed as EVM Object Format (EOF) containers, as
+    /// specified by EIP-7692, instead of legacy bytecode.
+    pub fn supports_eof(self) -> bool {
+        self >= Self::Osaka
+    }
+}
+
+str_enum! {
+    /// The hash of the metadata JSON to append to the bytecode in the CBOR-encoded trailer.
+    #[derive(Default)]
+    #[strum(serialize_all = "lowercase")]
+    pub enum BytecodeHash {
+        /// IPFS multihash (CIDv0).
+        #[default]
+        Ipfs,
+        /// Swarm hash.
+        Bzzr1,
+        /// Do not include the metadata hash.
+        None,
+    }
+}
+
+str_enum! {
+    /// How the formatter prints `int` and `uint` types.
+    #[derive(Default)]
+    #[strum(serialize_all = "lowercase")]
+    pub enum IntTypes {
+        /// Print the types as written.
+        #[default]
+        Preserve,
+        /// Print the explicitly sized types: `uint256`.
+        Long,
+        /// Print the aliases of the 256-bit types: `uint`.
+        Short,
+    }
 }
 
 str_enum! {
@@ -103,12 +146,139 @@ str_enum! {
     pub enum CompilerOutput {
         /// JSON ABI.
         Abi,
-        // /// Creation bytecode.
-        // Bin,
-        // /// Runtime bytecode.
-        // BinRuntime,
+        /// Creation bytecode.
+        Bin,
+        /// Runtime bytecode.
+        BinRuntime,
+        /// Source mapping of the creation bytecode.
+        Srcmap,
+        /// Source mapping of the runtime bytecode.
+        SrcmapRuntime,
+        /// Yul intermediate representation.
+        Ir,
+        /// Human-readable EVM assembly.
+        Asm,
         /// Function signature hashes.
         Hashes,
+        /// solc-compatible AST JSON, including node IDs.
+        AstJson,
+        /// NatSpec user and developer documentation (`userdoc` and `devdoc`).
+        Docs,
+        /// Storage layout of state variables.
+        StorageLayout,
+        /// Contract metadata JSON.
+        Metadata,
+        /// Gas estimates of the deployment and of each function.
+        GasEstimates,
+        /// Graph of the imports between source files, with the import cycles.
+        ImportGraph,
+    }
+}
+
+impl CompilerOutput {
+    /// Returns `true` if the output is produced by the code generation backend.
+    pub fn requires_codegen(self) -> bool {
+        matches!(
+            self,
+            Self::Bin
+                | Self::BinRuntime
+                | Self::Srcmap
+                | Self::SrcmapRuntime
+                | Self::Ir
+                | Self::Asm
+                | Self::GasEstimates
+        )
+    }
+
+    /// Returns `true` if the output is emitted for each contract, rather than for each source.
+    pub fn is_per_contract(self) -> bool {
+        !matches!(self, Self::AstJson | Self::ImportGraph)
+    }
+}
+
+str_enum! {
+    /// The level of a lint, which decides how its diagnostics are emitted.
+    #[strum(serialize_all = "lowercase")]
+    pub enum LintLevel {
+        /// Do not emit the lint.
+        Allow,
+        /// Emit the lint as a warning.
+        Warn,
+        /// Emit the lint as an error.
+        Deny,
+    }
+}
+
+/// Optimizer settings.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct OptimizerSettings {
+    /// Whether to run the optimizer.
+    pub enabled: bool,
+    /// The number of times each opcode of the deployed code is expected to be executed over the
+    /// lifetime of the contract. Lower values optimize for deployment cost, higher values for
+    /// runtime cost.
+    pub runs: u64,
+    /// Names of optimizer passes to run in addition to the default ones.
+    pub enabled_passes: Vec<String>,
+    /// Names of optimizer passes not to run.
+    pub disabled_passes: Vec<String>,
+    /// Names of the optimizer passes to run, in order, instead of the default pipeline.
+    ///
+    /// A pass may appear more than once. Disabled passes are still removed.
+    pub pipeline: Option<Vec<String>>,
+}
+
+impl Default for OptimizerSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            runs: 200,
+            enabled_passes: Vec::new(),
+            disabled_passes: Vec::new(),
+            pipeline: None,
+        }
+    }
+}
+
+/// `--libraries <name>=<address>`: the address of a deployed library, which is linked into the
+/// bytecode of the contracts that call it.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Library {
+    /// The name of the library, optionally qualified with the path of its source file as
+    /// `<path>:<name>`.
+    pub name: String,
+    /// The address of the library.
+    pub address: [u8; 20],
+}
+
+impl Library {
+    /// Returns `true` if this is the library with the given source file path and name.
+    pub fn matches(&self, path: &str, name: &str) -> bool {
+        match self.name.rsplit_once(':') {
+            Some((p, n)) => p == path && n == name,
+            None => self.name == name,
+        }
+    }
+}
+
+impl std::str::FromStr for Library {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let Some((name, address)) = s.rsplit_once('=') else {
+            return Err(format!("expected `<name>=<address>`, found `{s}`"));
+        };
+        let hex = address.strip_prefix("0x").unwrap_or(address);
+        let invalid = || format!("invalid library address `{address}`");
+        if name.is_empty() || hex.len() != 40 {
+            return Err(invalid());
+        }
+        let mut bytes = [0u8; 20];
+        for (byte, chunk) in bytes.iter_mut().zip(hex.as_bytes().chunks(2)) {
+            let chunk = std::str::from_utf8(chunk).map_err(|_| invalid())?;
+            *byte = u8::from_str_radix(chunk, 16).map_err(|_| invalid())?;
+        }
+        Ok(Self { name: name.to_string(), address: bytes })
     }
 }
 
@@ -147,6 +317,18 @@ str_enum! {
     }
 }
 
+str_enum! {
+    /// What to pretty-print with `-Zunpretty`.
+    #[derive(EnumIs)]
+    #[strum(serialize_all = "kebab-case")]
+    pub enum Unpretty {
+        /// Print the AST of each source file in debug form.
+        Ast,
+        /// Print the tokens of each source file, with their kinds, symbols and locations.
+        Tokens,
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -155,6 +337,22 @@ mod tests {
     #[cfg(not(feature = "serde"))]
     use serde_json as _;
 
+    #[test]
+    fn library() {
+        let lib = "src/L.sol:L=0x000000000000000000000000000000000000dEaD".parse::<Library>();
+        let mut address = [0; 20];
+        address[18..].copy_from_slice(&[0xde, 0xad]);
+        assert_eq!(lib, Ok(Library { name: "src/L.sol:L".into(), address }));
+        assert!(lib.as_ref().unwrap().matches("src/L.sol", "L"));
+        assert!(!lib.as_ref().unwrap().matches("L.sol", "L"));
+
+        let lib = "L=000000000000000000000000000000000000dead".parse::<Library>().unwrap();
+        assert!(lib.matches("src/L.sol", "L"));
+        assert!("L".parse::<Library>().is_err());
+        assert!("L=0x1234".parse::<Library>().is_err());
+        assert!("L=0x000000000000000000000000000000000000deag".parse::<Library>().is_err());
+    }
+
     #[test]
     fn string_enum() {
         for value in EvmVersion::iter() {
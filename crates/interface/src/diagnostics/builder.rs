@@ -1,8 +1,8 @@
 use super::{
-    BugAbort, DiagCtxt, Diagnostic, DiagnosticId, DiagnosticMessage, ErrorGuaranteed, ExplicitBug,
-    FatalAbort, Level, MultiSpan, Style,
+    Applicability, BugAbort, DiagCtxt, Diagnostic, DiagnosticId, DiagnosticMessage,
+    ErrorGuaranteed, ExplicitBug, FatalAbort, Level, MultiSpan, Style,
 };
-use crate::Span;
+use crate::{lint::Lint, Span};
 use solar_data_structures::Never;
 use std::{
     fmt,
@@ -202,8 +202,11 @@ impl<G: EmissionGuarantee> DiagnosticBuilder<'_, G> {
     forward! {
         pub fn span(span: impl Into<MultiSpan>);
         pub fn code(code: impl Into<DiagnosticId>);
+        pub fn lint(lint: &'static Lint);
+        pub fn group(key: impl std::hash::Hash);
 
         pub fn span_label(span: Span, label: impl Into<DiagnosticMessage>);
+        pub fn primary_span_label(span: Span, label: impl Into<DiagnosticMessage>);
         pub fn span_labels(spans: impl IntoIterator<Item = Span>, label: impl Into<DiagnosticMessage>);
 
         pub fn warn(msg: impl Into<DiagnosticMessage>);
@@ -219,5 +222,17 @@ impl<G: EmissionGuarantee> DiagnosticBuilder<'_, G> {
         pub fn help_once(msg: impl Into<DiagnosticMessage>);
         pub fn highlighted_help(messages: Vec<(impl Into<DiagnosticMessage>, Style)>);
         pub fn span_help(span: impl Into<MultiSpan>, msg: impl Into<DiagnosticMessage>);
+
+        pub fn span_suggestion(
+            span: Span,
+            msg: impl Into<DiagnosticMessage>,
+            suggestion: impl Into<String>,
+            applicability: Applicability,
+        );
+        pub fn multipart_suggestion(
+            msg: impl Into<DiagnosticMessage>,
+            parts: Vec<(Span, String)>,
+            applicability: Applicability,
+        );
     }
 }
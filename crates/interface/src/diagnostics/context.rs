@@ -1,21 +1,36 @@
 use super::{
-    emitter::HumanEmitter, BugAbort, Diagnostic, DiagnosticBuilder, DiagnosticMessage, DynEmitter,
-    EmissionGuarantee, EmittedDiagnostics, ErrorGuaranteed, FatalAbort, HumanBufferEmitter, Level,
-    SilentEmitter,
+    emitter::HumanEmitter, BufferedDiagnostic, BufferedEmitter, BugAbort, Diagnostic,
+    DiagnosticBuilder, DiagnosticId, DiagnosticMessage, DynEmitter, EmissionGuarantee,
+    EmittedDiagnostics, ErrorGuaranteed, FatalAbort, HumanBufferEmitter, Level, MultiSpan,
+    SilentEmitter, Suppression,
 };
-use crate::{Result, SourceMap};
+use crate::{lint::LintLevel, source_map::FileName, Result, SourceMap, Span};
 use anstream::ColorChoice;
-use solar_data_structures::{map::FxHashSet, sync::Lock};
-use std::{borrow::Cow, hash::BuildHasher, num::NonZeroUsize, sync::Arc};
+use solar_data_structures::{
+    map::{rustc_hash::FxBuildHasher, FxHashMap, FxHashSet},
+    sync::Lock,
+};
+use std::{borrow::Cow, cell::RefCell, hash::BuildHasher, num::NonZeroUsize, sync::Arc};
 
 /// Flags that control the behaviour of a [`DiagCtxt`].
 #[derive(Clone, Copy)]
 pub struct DiagCtxtFlags {
     /// If false, warning-level lints are suppressed.
     pub can_emit_warnings: bool,
+    /// If true, warnings are emitted as errors.
+    pub deny_warnings: bool,
     /// If Some, the Nth error-level diagnostic is upgraded to bug-level.
     pub treat_err_as_bug: Option<NonZeroUsize>,
+    /// If Some, compilation is aborted once this many errors have been emitted, not counting
+    /// duplicates, by panicking with [`FatalAbort`] in the call that emits the last one.
+    pub error_limit: Option<NonZeroUsize>,
+    /// Diagnostics less severe than this level are counted, but not emitted. Errors are always
+    /// emitted, and notes and help messages are equally severe.
+    pub message_level: Level,
     /// If true, identical diagnostics are reported only once.
+    ///
+    /// Diagnostics in the same file loaded under multiple names, for example through different
+    /// import paths, are also considered identical.
     pub deduplicate_diagnostics: bool,
     /// Track where errors are created. Enabled with `-Ztrack-diagnostics`, and by default in debug
     /// builds.
@@ -26,13 +41,21 @@ impl Default for DiagCtxtFlags {
     fn default() -> Self {
         Self {
             can_emit_warnings: true,
+            deny_warnings: false,
             treat_err_as_bug: None,
+            error_limit: None,
+            message_level: Level::Note,
             deduplicate_diagnostics: true,
             track_diagnostics: cfg!(debug_assertions),
         }
     }
 }
 
+thread_local! {
+    /// The diagnostics captured on the current thread by [`DiagCtxt::capture`].
+    static CAPTURED: RefCell<Option<Vec<Diagnostic>>> = const { RefCell::new(None) };
+}
+
 /// A handler deals with errors and other compiler output.
 /// Certain errors (fatal, bug, unimpl) may cause immediate exit,
 /// others log errors for later reporting.
@@ -58,6 +81,25 @@ struct DiagCtxtInner {
     /// This set contains a hash of every diagnostic that has been emitted by this `DiagCtxt`.
     /// These hashes are used to avoid emitting the same error twice.
     emitted_diagnostics: FxHashSet<u64>,
+    /// The identities of the source files of the spans of emitted diagnostics, by start position.
+    ///
+    /// A file loaded under multiple names has the same identity, its canonical path.
+    file_identities: FxHashMap<u32, u64>,
+    /// The last emitted diagnostic of a group, with its group key.
+    ///
+    /// It is held back until a diagnostic that is not part of its group is emitted, so that the
+    /// primary spans of the next diagnostics of the group can be added to it.
+    pending_group: Option<(u64, Diagnostic)>,
+
+    /// The ranges of source code in which lints are suppressed by comments.
+    suppressions: Vec<Suppression>,
+    /// The number of lint diagnostics that were not emitted because of `suppressions`.
+    suppressed_count: usize,
+    /// The number of diagnostics that were not emitted because of `flags.message_level`.
+    hidden_count: usize,
+
+    /// The levels of warnings with specific codes, overriding `flags.deny_warnings`.
+    code_levels: FxHashMap<u32, LintLevel>,
 }
 
 impl DiagCtxt {
@@ -72,6 +114,12 @@ impl DiagCtxt {
                 warn_count: 0,
                 deduplicated_warn_count: 0,
                 emitted_diagnostics: FxHashSet::default(),
+                file_identities: FxHashMap::default(),
+                pending_group: None,
+                suppressions: Vec::new(),
+                suppressed_count: 0,
+                hidden_count: 0,
+                code_levels: FxHashMap::default(),
             }),
         }
     }
@@ -114,6 +162,14 @@ impl DiagCtxt {
         Self::new(Box::new(HumanBufferEmitter::new(color_choice).source_map(source_map)))
     }
 
+    /// Creates a new `DiagCtxt` with an emitter that stores diagnostics along with their resolved
+    /// locations.
+    ///
+    /// See [`buffered_diagnostics`](Self::buffered_diagnostics).
+    pub fn with_buffered_emitter(source_map: Option<Arc<SourceMap>>) -> Self {
+        Self::new(Box::new(BufferedEmitter::new(source_map)))
+    }
+
     /// Gets the source map associated with this context.
     pub fn source_map(&self) -> Option<Arc<SourceMap>> {
         self.inner.lock().emitter.source_map().cloned()
@@ -141,6 +197,11 @@ impl DiagCtxt {
     }
 
     /// Emits the given diagnostic with this context.
+    ///
+    /// # Panics
+    ///
+    /// Panics with [`FatalAbort`] after emitting the diagnostic if it is the error that reaches
+    /// the [error limit](DiagCtxtFlags::error_limit).
     #[inline]
     pub fn emit_diagnostic(&self, mut diagnostic: Diagnostic) -> Result<(), ErrorGuaranteed> {
         self.emit_diagnostic_without_consuming(&mut diagnostic)
@@ -154,7 +215,89 @@ impl DiagCtxt {
         &self,
         diagnostic: &mut Diagnostic,
     ) -> Result<(), ErrorGuaranteed> {
-        self.inner.lock().emit_diagnostic_without_consuming(diagnostic)
+        // Bugs and fatal errors abort compilation, so they are never captured.
+        if !matches!(diagnostic.level, Level::Bug | Level::Fatal) {
+            let captured = CAPTURED.with_borrow_mut(|captured| {
+                captured.as_mut().map(|captured| captured.push(diagnostic.clone()))
+            });
+            if captured.is_some() {
+                return if diagnostic.is_error() {
+                    Err(ErrorGuaranteed::new_unchecked())
+                } else {
+                    Ok(())
+                };
+            }
+        }
+        let mut inner = self.inner.lock();
+        let prev_err_count = inner.deduplicated_err_count;
+        let r = inner.emit_diagnostic_without_consuming(diagnostic);
+        if let Some(limit) = inner.flags.error_limit {
+            if prev_err_count < limit.get() && inner.deduplicated_err_count >= limit.get() {
+                inner.flush_group();
+                let mut fatal =
+                    Diagnostic::new(Level::Fatal, "too many errors emitted, stopping now");
+                fatal.note(format!("the error limit is {limit}"));
+                inner.emitter.emit_diagnostic(&fatal);
+                drop(inner);
+                std::panic::panic_any(FatalAbort);
+            }
+        }
+        r
+    }
+
+    /// Calls `f`, capturing the diagnostics that it emits on the current thread instead of emitting
+    /// them, and returns them along with its result.
+    ///
+    /// This is used to emit the diagnostics of work done in parallel in a deterministic order, with
+    /// [`emit_captured`](Self::emit_captured). Bugs and fatal errors are emitted immediately.
+    ///
+    /// The captured diagnostics are not counted until they are emitted: inside `f`,
+    /// [`err_count`](Self::err_count) and [`has_errors`](Self::has_errors) do not include the
+    /// errors emitted by `f` itself, and the error limit cannot be reached.
+    pub fn capture<R>(&self, f: impl FnOnce() -> R) -> (R, Vec<Diagnostic>) {
+        let prev = CAPTURED.replace(Some(Vec::new()));
+        let r = f();
+        let captured = CAPTURED.replace(prev).unwrap_or_default();
+        (r, captured)
+    }
+
+    /// Emits the diagnostics returned by [`capture`](Self::capture), counting them.
+    ///
+    /// # Panics
+    ///
+    /// Panics with [`FatalAbort`] if one of the errors reaches the
+    /// [error limit](DiagCtxtFlags::error_limit). The diagnostics after it are not emitted.
+    pub fn emit_captured(&self, diagnostics: Vec<Diagnostic>) {
+        for diagnostic in diagnostics {
+            let _ = self.emit_diagnostic(diagnostic);
+        }
+    }
+
+    /// Adds ranges of source code in which lints are suppressed.
+    ///
+    /// Lint diagnostics whose primary span starts in one of these ranges are counted, but not
+    /// emitted. See [`suppressed_count`](Self::suppressed_count).
+    pub fn add_suppressions(&self, suppressions: impl IntoIterator<Item = Suppression>) {
+        self.inner.lock().suppressions.extend(suppressions);
+    }
+
+    /// Sets the level of the warnings with the given code: allowed warnings are not emitted, and
+    /// denied warnings are emitted as errors.
+    ///
+    /// This takes precedence over [`DiagCtxtFlags::deny_warnings`].
+    pub fn set_code_level(&self, code: DiagnosticId, level: LintLevel) {
+        self.inner.lock().code_levels.insert(code.id, level);
+    }
+
+    /// Returns the number of lint diagnostics that were suppressed by comments.
+    pub fn suppressed_count(&self) -> usize {
+        self.inner.lock().suppressed_count
+    }
+
+    /// Returns the number of diagnostics that were not emitted because they are less severe than
+    /// [`DiagCtxtFlags::message_level`].
+    pub fn hidden_count(&self) -> usize {
+        self.inner.lock().hidden_count
     }
 
     /// Returns the number of errors that have been emitted, including duplicates.
@@ -162,6 +305,11 @@ impl DiagCtxt {
         self.inner.lock().err_count
     }
 
+    /// Returns the number of warnings that have been emitted, including duplicates.
+    pub fn warn_count(&self) -> usize {
+        self.inner.lock().warn_count
+    }
+
     /// Returns `Err` if any errors have been emitted.
     pub fn has_errors(&self) -> Result<(), ErrorGuaranteed> {
         if self.inner.lock().has_errors() {
@@ -176,7 +324,8 @@ impl DiagCtxt {
     /// Returns `None` if the underlying emitter is not a human buffer emitter created with
     /// [`with_buffer_emitter`](Self::with_buffer_emitter).
     pub fn emitted_diagnostics(&self) -> Option<EmittedDiagnostics> {
-        let inner = self.inner.lock();
+        let mut inner = self.inner.lock();
+        inner.flush_group();
         Some(EmittedDiagnostics(inner.emitter.local_buffer()?.to_string()))
     }
 
@@ -185,11 +334,22 @@ impl DiagCtxt {
     /// Returns `None` if the underlying emitter is not a human buffer emitter created with
     /// [`with_buffer_emitter`](Self::with_buffer_emitter).
     pub fn emitted_errors(&self) -> Option<Result<(), EmittedDiagnostics>> {
-        let inner = self.inner.lock();
+        let mut inner = self.inner.lock();
+        inner.flush_group();
         let buffer = inner.emitter.local_buffer()?;
         Some(if inner.has_errors() { Err(EmittedDiagnostics(buffer.to_string())) } else { Ok(()) })
     }
 
+    /// Returns the diagnostics emitted so far, along with their resolved locations.
+    ///
+    /// Returns `None` if the underlying emitter is not a buffered emitter created with
+    /// [`with_buffered_emitter`](Self::with_buffered_emitter).
+    pub fn buffered_diagnostics(&self) -> Option<Vec<BufferedDiagnostic>> {
+        let mut inner = self.inner.lock();
+        inner.flush_group();
+        inner.emitter.buffered_diagnostics().map(<[_]>::to_vec)
+    }
+
     /// Emits a diagnostic if any warnings or errors have been emitted.
     pub fn print_error_count(&self) -> Result {
         self.inner.lock().print_error_count()
@@ -249,6 +409,12 @@ impl DiagCtxt {
     }
 }
 
+impl Drop for DiagCtxtInner {
+    fn drop(&mut self) {
+        self.flush_group();
+    }
+}
+
 impl DiagCtxtInner {
     fn emit_diagnostic(&mut self, mut diagnostic: Diagnostic) -> Result<(), ErrorGuaranteed> {
         self.emit_diagnostic_without_consuming(&mut diagnostic)
@@ -258,6 +424,8 @@ impl DiagCtxtInner {
         &mut self,
         diagnostic: &mut Diagnostic,
     ) -> Result<(), ErrorGuaranteed> {
+        self.resolve_warning_level(diagnostic);
+
         if diagnostic.level == Level::Warning && !self.flags.can_emit_warnings {
             return Ok(());
         }
@@ -266,11 +434,29 @@ impl DiagCtxtInner {
             return Ok(());
         }
 
+        if self.is_suppressed(diagnostic) {
+            self.suppressed_count += 1;
+            return Ok(());
+        }
+
         if matches!(diagnostic.level, Level::Error | Level::Fatal) && self.treat_err_as_bug() {
             diagnostic.level = Level::Bug;
         }
 
-        let already_emitted = self.insert_diagnostic(diagnostic);
+        // Add the diagnostic to the pending one if they are part of the same group.
+        if let (Some(key), Some((pending_key, pending))) =
+            (diagnostic.group, &mut self.pending_group)
+        {
+            if key == *pending_key && diagnostic.level == pending.level {
+                for &span in diagnostic.span.primary_spans() {
+                    pending.span.push_primary_span(span);
+                }
+                return self.bump_count(diagnostic);
+            }
+        }
+        self.flush_group();
+
+        let already_emitted = self.insert_top_level_diagnostic(diagnostic);
         if !(self.flags.deduplicate_diagnostics && already_emitted) {
             // Remove duplicate `Once*` subdiagnostics.
             diagnostic.children.retain(|sub| {
@@ -287,7 +473,10 @@ impl DiagCtxtInner {
             //     );
             // }
 
-            self.emitter.emit_diagnostic(diagnostic);
+            match diagnostic.group {
+                Some(key) => self.pending_group = Some((key, diagnostic.clone())),
+                None => self.emit_to_emitter(diagnostic),
+            }
             if diagnostic.is_error() {
                 self.deduplicated_err_count += 1;
             } else if diagnostic.level == Level::Warning {
@@ -295,6 +484,10 @@ impl DiagCtxtInner {
             }
         }
 
+        self.bump_count(diagnostic)
+    }
+
+    fn bump_count(&mut self, diagnostic: &Diagnostic) -> Result<(), ErrorGuaranteed> {
         if diagnostic.is_error() {
             self.bump_err_count();
             Err(ErrorGuaranteed::new_unchecked())
@@ -304,8 +497,34 @@ impl DiagCtxtInner {
         }
     }
 
+    /// Emits the pending diagnostic of a group, if any.
+    fn flush_group(&mut self) {
+        if let Some((_, diagnostic)) = self.pending_group.take() {
+            self.emit_to_emitter(&diagnostic);
+        }
+    }
+
+    /// Passes the given diagnostic to the emitter, unless it is less severe than
+    /// `flags.message_level`.
+    fn emit_to_emitter(&mut self, diagnostic: &Diagnostic) {
+        let severity = |level: Level| {
+            if level.is_error() {
+                0
+            } else if level == Level::Warning {
+                1
+            } else {
+                2
+            }
+        };
+        if severity(diagnostic.level) > severity(self.flags.message_level) {
+            self.hidden_count += 1;
+            return;
+        }
+        self.emitter.emit_diagnostic(diagnostic);
+    }
+
     fn print_error_count(&mut self) -> Result {
-        // self.emit_stashed_diagnostics();
+        self.flush_group();
 
         if self.treat_err_as_bug() {
             return Ok(());
@@ -322,27 +541,102 @@ impl DiagCtxtInner {
             count => Cow::from(format!("aborting due to {count} previous errors")),
         };
 
+        let hidden = |msg: Cow<'static, str>| match self.hidden_count {
+            0 => msg,
+            count => Cow::from(format!("{msg} ({count} not shown)")),
+        };
+
         match (self.deduplicated_err_count, self.deduplicated_warn_count) {
             (0, 0) => Ok(()),
             (0, w) => {
-                self.emitter.emit_diagnostic(&Diagnostic::new(Level::Warning, warnings(w)));
+                let msg = hidden(warnings(w));
+                self.emitter.emit_diagnostic(&Diagnostic::new(Level::Warning, msg));
                 Ok(())
             }
-            (e, 0) => self.emit_diagnostic(Diagnostic::new(Level::Error, errors(e))),
-            (e, w) => self.emit_diagnostic(Diagnostic::new(
-                Level::Error,
-                format!("{}; {}", errors(e), warnings(w)),
-            )),
+            (e, 0) => {
+                let msg = hidden(errors(e));
+                self.emit_diagnostic(Diagnostic::new(Level::Error, msg))
+            }
+            (e, w) => {
+                let msg = hidden(format!("{}; {}", errors(e), warnings(w)).into());
+                self.emit_diagnostic(Diagnostic::new(Level::Error, msg))
+            }
+        }
+    }
+
+    /// Applies the configured code levels and `deny_warnings` to the given diagnostic, if it is a
+    /// warning.
+    fn resolve_warning_level(&self, diagnostic: &mut Diagnostic) {
+        if diagnostic.level != Level::Warning {
+            return;
         }
+        let code_level =
+            diagnostic.code.as_ref().and_then(|code| self.code_levels.get(&code.id).copied());
+        diagnostic.level = match code_level {
+            Some(LintLevel::Allow) => Level::Allow,
+            Some(LintLevel::Warn) => Level::Warning,
+            Some(LintLevel::Deny) => Level::Error,
+            None if self.flags.deny_warnings => Level::Error,
+            None => Level::Warning,
+        };
+    }
+
+    /// Returns `true` if the given diagnostic is a lint that is suppressed by a comment.
+    fn is_suppressed(&self, diagnostic: &Diagnostic) -> bool {
+        let (Some(lint), Some(span)) = (diagnostic.lint, diagnostic.span.primary_span()) else {
+            return false;
+        };
+        self.suppressions.iter().any(|suppression| suppression.suppresses(lint, span))
     }
 
     /// Inserts the given diagnostic into the set of emitted diagnostics.
     /// Returns `true` if the diagnostic was already emitted.
     fn insert_diagnostic<H: std::hash::Hash>(&mut self, diag: &H) -> bool {
-        let hash = solar_data_structures::map::rustc_hash::FxBuildHasher.hash_one(diag);
+        let hash = FxBuildHasher.hash_one(diag);
         !self.emitted_diagnostics.insert(hash)
     }
 
+    /// Inserts the given top-level diagnostic into the set of emitted diagnostics, identifying
+    /// its spans by source file identity and relative position.
+    /// Returns `true` if the diagnostic was already emitted.
+    fn insert_top_level_diagnostic(&mut self, diag: &Diagnostic) -> bool {
+        let Some(sm) = self.emitter.source_map().cloned() else {
+            return self.insert_diagnostic(diag);
+        };
+        let mut spans = Vec::new();
+        let mut push_spans = |msp: &MultiSpan| {
+            for label in msp.span_labels() {
+                let text = label.label.as_ref().map(|label| label.as_str().to_string());
+                spans.push((self.relative_span(&sm, label.span), text));
+            }
+        };
+        push_spans(&diag.span);
+        for sub in &diag.children {
+            push_spans(&sub.span);
+        }
+        let subs = diag.children.iter().map(|sub| (sub.level, &sub.messages)).collect::<Vec<_>>();
+        self.insert_diagnostic(&(diag.level, &diag.messages, &diag.code, spans, subs))
+    }
+
+    /// Returns the identity of the source file of the given span, and its relative position.
+    fn relative_span(&mut self, sm: &SourceMap, span: Span) -> (u64, u32, u32) {
+        if span.is_dummy() {
+            return (0, span.lo().0, span.hi().0);
+        }
+        let file = sm.lookup_source_file(span.lo());
+        let identity = *self.file_identities.entry(file.start_pos.0).or_insert_with(|| {
+            let canonical = match &file.name {
+                FileName::Real(path) => sm.file_loader().canonicalize_path(path).ok(),
+                _ => None,
+            };
+            match canonical {
+                Some(path) => FxBuildHasher.hash_one(path),
+                None => FxBuildHasher.hash_one(&file.name),
+            }
+        });
+        (identity, span.lo().0 - file.start_pos.0, span.hi().0 - file.start_pos.0)
+    }
+
     fn treat_err_as_bug(&self) -> bool {
         self.flags.treat_err_as_bug.is_some_and(|c| self.err_count >= c.get())
     }
@@ -371,3 +665,142 @@ impl DiagCtxtInner {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::BytePos;
+
+    fn span(file: &crate::source_map::SourceFile, lo: u32, hi: u32) -> Span {
+        Span::new(file.start_pos + BytePos(lo), file.start_pos + BytePos(hi))
+    }
+
+    #[test]
+    fn deduplicate_across_file_names() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir(dir.path().join("sub")).unwrap();
+        let path = dir.path().join("a.sol");
+        std::fs::write(&path, "contract C {}").unwrap();
+
+        let sm = Arc::new(SourceMap::empty());
+        let a = sm.load_file(&path).unwrap();
+        let b = sm.load_file(&dir.path().join("sub/../a.sol")).unwrap();
+        assert!(!Arc::ptr_eq(&a, &b));
+
+        let dcx = DiagCtxt::with_buffer_emitter(Some(sm), ColorChoice::Never);
+        dcx.warn("unnamed contract").span(span(&a, 9, 10)).emit();
+        dcx.warn("unnamed contract").span(span(&b, 9, 10)).emit();
+        dcx.warn("unnamed contract").span(span(&b, 0, 8)).emit();
+        let emitted = dcx.emitted_diagnostics().unwrap().0;
+        assert_eq!(emitted.matches("warning: unnamed contract").count(), 2, "{emitted}");
+    }
+
+    #[test]
+    fn group() {
+        let sm = Arc::new(SourceMap::empty());
+        let file = sm.new_dummy_source_file("a.sol".into(), "uint x;\nuint x;\nuint x;".into());
+        let file = file.unwrap();
+
+        let dcx = DiagCtxt::with_buffer_emitter(Some(sm), ColorChoice::Never);
+        for lo in [8, 16] {
+            let _ =
+                dcx.err("`x` already declared").span(span(&file, lo + 5, lo + 6)).group(1).emit();
+        }
+        let _ = dcx.err("other").emit();
+        let _ = dcx.err("`x` already declared").span(span(&file, 21, 22)).group(1).emit();
+        assert_eq!(dcx.err_count(), 4);
+
+        let emitted = dcx.emitted_diagnostics().unwrap().0;
+        assert_eq!(emitted.matches("already declared").count(), 2, "{emitted}");
+        assert_eq!(emitted.matches('^').count(), 3, "{emitted}");
+        assert!(emitted.find("other").unwrap() > emitted.find("already declared").unwrap());
+    }
+
+    #[test]
+    fn primary_span_labels() {
+        let sm = Arc::new(SourceMap::empty());
+        let a = sm.new_dummy_source_file("a.sol".into(), "uint x;".into()).unwrap();
+        let b = sm.new_dummy_source_file("b.sol".into(), "\nuint x;".into()).unwrap();
+
+        let dcx = DiagCtxt::with_buffer_emitter(Some(sm), ColorChoice::Never);
+        let _ = dcx
+            .err("identifier already declared")
+            .primary_span_label(span(&b, 6, 7), "conflicting declaration here")
+            .primary_span_label(span(&a, 5, 6), "first declared here")
+            .emit();
+        let emitted = dcx.emitted_diagnostics().unwrap().0;
+        assert_eq!(emitted.matches("identifier already declared").count(), 1, "{emitted}");
+        assert_eq!(emitted.matches('^').count(), 2, "{emitted}");
+        let first = emitted.find("a.sol:1:6").expect(&emitted);
+        let conflicting = emitted.find("b.sol:2:6").expect(&emitted);
+        assert!(first < conflicting, "{emitted}");
+        assert!(emitted.contains("^ first declared here"), "{emitted}");
+        assert!(emitted.contains("^ conflicting declaration here"), "{emitted}");
+    }
+
+    #[test]
+    fn message_level() {
+        let dcx = DiagCtxt::with_buffer_emitter(None, ColorChoice::Never)
+            .set_flags(|flags| flags.message_level = Level::Error);
+        dcx.warn("a").emit();
+        dcx.note("b").emit();
+        let _ = dcx.err("c").emit();
+        assert_eq!(dcx.hidden_count(), 2);
+        let _ = dcx.print_error_count();
+
+        let emitted = dcx.emitted_diagnostics().unwrap().0;
+        assert!(!emitted.contains("warning: a"), "{emitted}");
+        assert!(!emitted.contains("note: b"), "{emitted}");
+        assert!(emitted.contains("error: c"), "{emitted}");
+        assert!(
+            emitted.contains("aborting due to 1 previous error; 1 warning emitted (2 not shown)"),
+            "{emitted}"
+        );
+    }
+
+    #[test]
+    fn error_limit() {
+        let dcx = DiagCtxt::with_buffer_emitter(None, ColorChoice::Never)
+            .set_flags(|flags| flags.error_limit = NonZeroUsize::new(2));
+        let r = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            let _ = dcx.err("a").emit();
+            let _ = dcx.err("a").emit();
+            let _ = dcx.err("b").emit();
+            let _ = dcx.err("c").emit();
+        }));
+        assert!(r.unwrap_err().is::<FatalAbort>());
+
+        let emitted = dcx.emitted_diagnostics().unwrap().0;
+        assert!(!emitted.contains("error: c"), "{emitted}");
+        assert!(emitted.contains("too many errors emitted"), "{emitted}");
+        assert!(emitted.contains("the error limit is 2"), "{emitted}");
+    }
+
+    #[test]
+    fn capture() {
+        let dcx = DiagCtxt::with_buffer_emitter(None, ColorChoice::Never)
+            .set_flags(|flags| flags.error_limit = NonZeroUsize::new(2));
+        let ((), captured) = dcx.capture(|| {
+            for msg in ["a", "b", "c"] {
+                let _ = dcx.err(msg).emit();
+            }
+            dcx.warn("d").emit();
+            // Captured diagnostics are only counted once they are emitted.
+            assert_eq!(dcx.err_count(), 0);
+            assert!(dcx.has_errors().is_ok());
+        });
+        assert_eq!(captured.len(), 4);
+        assert_eq!(dcx.emitted_diagnostics().unwrap().0, "");
+
+        // The error limit is checked when the captured diagnostics are emitted.
+        let r = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            dcx.emit_captured(captured);
+        }));
+        assert!(r.unwrap_err().is::<FatalAbort>());
+        assert_eq!(dcx.err_count(), 2);
+        let emitted = dcx.emitted_diagnostics().unwrap().0;
+        assert!(emitted.contains("error: b"), "{emitted}");
+        assert!(!emitted.contains("error: c"), "{emitted}");
+        assert!(emitted.contains("too many errors emitted"), "{emitted}");
+    }
+}
@@ -0,0 +1,92 @@
+use super::Emitter;
+use crate::{
+    diagnostics::Diagnostic,
+    source_map::{FileName, SourceMap},
+    Span,
+};
+use std::sync::Arc;
+
+/// Diagnostic emitter that stores emitted diagnostics along with their resolved locations.
+///
+/// This allows inspecting diagnostics programmatically after compilation, instead of parsing their
+/// rendered text. Use it with [`DiagCtxt::with_buffered_emitter`] and retrieve the diagnostics with
+/// [`DiagCtxt::buffered_diagnostics`].
+///
+/// [`DiagCtxt::with_buffered_emitter`]: crate::diagnostics::DiagCtxt::with_buffered_emitter
+/// [`DiagCtxt::buffered_diagnostics`]: crate::diagnostics::DiagCtxt::buffered_diagnostics
+pub struct BufferedEmitter {
+    source_map: Option<Arc<SourceMap>>,
+    diagnostics: Vec<BufferedDiagnostic>,
+}
+
+/// A diagnostic stored by a [`BufferedEmitter`].
+#[derive(Clone, Debug)]
+pub struct BufferedDiagnostic {
+    /// The diagnostic.
+    pub diagnostic: Diagnostic,
+    /// The location of the primary span of the diagnostic.
+    ///
+    /// This is `None` if the diagnostic has no primary span, or if the emitter has no source map.
+    pub location: Option<DiagnosticLocation>,
+}
+
+/// A resolved source location of a diagnostic.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DiagnosticLocation {
+    /// The name of the file.
+    pub file: FileName,
+    /// The (1-based) start line number.
+    pub line: usize,
+    /// The (1-based) start column number, in characters.
+    pub col: usize,
+    /// The (1-based) end line number.
+    pub end_line: usize,
+    /// The (1-based) end column number, in characters. Exclusive.
+    pub end_col: usize,
+}
+
+impl DiagnosticLocation {
+    /// Resolves the location of the given span.
+    pub fn new(sm: &SourceMap, span: Span) -> Self {
+        let start = sm.lookup_char_pos(span.lo());
+        let end = sm.lookup_char_pos(span.hi());
+        Self {
+            file: start.file.name.clone(),
+            line: start.line,
+            col: start.col.0 + 1,
+            end_line: end.line,
+            end_col: end.col.0 + 1,
+        }
+    }
+}
+
+impl BufferedEmitter {
+    /// Creates a new `BufferedEmitter`.
+    pub fn new(source_map: Option<Arc<SourceMap>>) -> Self {
+        Self { source_map, diagnostics: Vec::new() }
+    }
+
+    /// Returns a reference to the emitted diagnostics.
+    pub fn diagnostics(&self) -> &[BufferedDiagnostic] {
+        &self.diagnostics
+    }
+
+    /// Consumes the emitter and returns the emitted diagnostics.
+    pub fn into_diagnostics(self) -> Vec<BufferedDiagnostic> {
+        self.diagnostics
+    }
+}
+
+impl Emitter for BufferedEmitter {
+    fn emit_diagnostic(&mut self, diagnostic: &Diagnostic) {
+        let location = match (&self.source_map, diagnostic.span.primary_span()) {
+            (Some(sm), Some(span)) if !span.is_dummy() => Some(DiagnosticLocation::new(sm, span)),
+            _ => None,
+        };
+        self.diagnostics.push(BufferedDiagnostic { diagnostic: diagnostic.clone(), location });
+    }
+
+    fn source_map(&self) -> Option<&Arc<SourceMap>> {
+        self.source_map.as_ref()
+    }
+}
@@ -41,7 +41,7 @@ unsafe impl Send for HumanEmitter {}
 
 impl Emitter for HumanEmitter {
     fn emit_diagnostic(&mut self, diagnostic: &Diagnostic) {
-        self.snippet(diagnostic, |this, snippet| {
+        self.snippet(&diagnostic.with_rendered_suggestions(), |this, snippet| {
             writeln!(this.writer, "{}\n", this.renderer.render(snippet))?;
             this.writer.flush()
         })
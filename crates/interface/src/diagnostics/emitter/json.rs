@@ -1,6 +1,6 @@
 use super::{human::HumanBufferEmitter, io_panic, Emitter};
 use crate::{
-    diagnostics::{Level, MultiSpan, SpanLabel},
+    diagnostics::{CodeSuggestion, Level, MultiSpan, SpanLabel},
     source_map::{LineInfo, SourceFile},
     SourceMap, Span,
 };
@@ -9,21 +9,36 @@ use serde::Serialize;
 use std::{io, sync::Arc};
 
 /// Diagnostic emitter that emits diagnostics as JSON.
+///
+/// By default, each diagnostic is written as soon as it is emitted, as a JSON object on its own
+/// line, so that the output can be consumed as a stream while compiling.
 pub struct JsonEmitter {
     writer: Box<dyn io::Write + Send>,
     pretty: bool,
     rustc_like: bool,
+    solc_compatible: bool,
+    line_delimited: bool,
+    /// The diagnostics to emit at once, when solc-compatible and not line-delimited.
+    solc_errors: Vec<SolcError>,
 
     human_emitter: HumanBufferEmitter,
 }
 
 impl Emitter for JsonEmitter {
     fn emit_diagnostic(&mut self, diagnostic: &crate::diagnostics::Diagnostic) {
-        if self.rustc_like {
+        if self.solc_compatible {
+            let error = self.solc_error(&diagnostic.with_rendered_suggestions());
+            if self.line_delimited {
+                self.emit(&error)
+            } else {
+                self.solc_errors.push(error);
+                Ok(())
+            }
+        } else if self.rustc_like {
             let diagnostic = self.diagnostic(diagnostic);
             self.emit(&EmitTyped::Diagnostic(diagnostic))
         } else {
-            let diagnostic = self.solc_diagnostic(diagnostic);
+            let diagnostic = self.solc_diagnostic(&diagnostic.with_rendered_suggestions());
             self.emit(&diagnostic)
         }
         .unwrap_or_else(|e| io_panic(e));
@@ -34,6 +49,24 @@ impl Emitter for JsonEmitter {
     }
 }
 
+impl Drop for JsonEmitter {
+    fn drop(&mut self) {
+        if !self.solc_compatible || self.line_delimited {
+            return;
+        }
+        let errors = std::mem::take(&mut self.solc_errors);
+        #[derive(Serialize)]
+        struct Output {
+            errors: Vec<SolcError>,
+        }
+        if let Err(e) = self.emit(&Output { errors }) {
+            if !std::thread::panicking() {
+                io_panic(e);
+            }
+        }
+    }
+}
+
 impl JsonEmitter {
     /// Creates a new `JsonEmitter` that writes to given writer.
     pub fn new(writer: Box<dyn io::Write + Send>, source_map: Arc<SourceMap>) -> Self {
@@ -41,6 +74,9 @@ impl JsonEmitter {
             writer,
             pretty: false,
             rustc_like: false,
+            solc_compatible: false,
+            line_delimited: false,
+            solc_errors: Vec::new(),
             human_emitter: HumanBufferEmitter::new(ColorChoice::Never).source_map(Some(source_map)),
         }
     }
@@ -59,6 +95,27 @@ impl JsonEmitter {
         self
     }
 
+    /// Sets whether to emit diagnostics exactly like the `errors` array of solc's standard JSON
+    /// output, with messages formatted like solc's.
+    ///
+    /// The diagnostics are buffered, and written as `{"errors": [...]}` when the emitter is
+    /// dropped, unless [`line_delimited`](Self::line_delimited) is set.
+    pub fn solc_compatible(mut self, yes: bool) -> Self {
+        self.solc_compatible = yes;
+        self
+    }
+
+    /// Sets whether to always write each diagnostic as soon as it is emitted, as a JSON object on
+    /// its own line.
+    ///
+    /// This overrides [`pretty`](Self::pretty), and makes the
+    /// [solc-compatible](Self::solc_compatible) output a stream of error objects instead of a
+    /// single document.
+    pub fn line_delimited(mut self, yes: bool) -> Self {
+        self.line_delimited = yes;
+        self
+    }
+
     /// Sets whether to emit diagnostics in a way that is suitable for UI testing.
     pub fn ui_testing(mut self, yes: bool) -> Self {
         self.human_emitter = self.human_emitter.ui_testing(yes);
@@ -72,10 +129,18 @@ impl JsonEmitter {
     fn diagnostic(&mut self, diagnostic: &crate::diagnostics::Diagnostic) -> Diagnostic {
         Diagnostic {
             message: diagnostic.label().into_owned(),
-            code: diagnostic.id().map(|code| DiagnosticCode { code, explanation: None }),
+            code: diagnostic.code.as_ref().map(|code| DiagnosticCode {
+                code: code.as_string(),
+                explanation: code.explanation(),
+            }),
             level: diagnostic.level.to_str(),
             spans: self.spans(&diagnostic.span),
-            children: diagnostic.children.iter().map(|sub| self.sub_diagnostic(sub)).collect(),
+            children: diagnostic
+                .children
+                .iter()
+                .map(|sub| self.sub_diagnostic(sub))
+                .chain(diagnostic.suggestions.iter().map(|sugg| self.suggestion(sugg)))
+                .collect(),
             rendered: Some(self.emit_diagnostic_to_buffer(diagnostic)),
         }
     }
@@ -91,6 +156,29 @@ impl JsonEmitter {
         }
     }
 
+    fn suggestion(&self, suggestion: &CodeSuggestion) -> Diagnostic {
+        let spans = suggestion
+            .parts
+            .iter()
+            .map(|part| {
+                let label = SpanLabel { span: part.span, is_primary: true, label: None };
+                DiagnosticSpan {
+                    suggested_replacement: Some(part.snippet.clone()),
+                    suggestion_applicability: Some(suggestion.applicability.to_str()),
+                    ..self.span(&label)
+                }
+            })
+            .collect();
+        Diagnostic {
+            message: suggestion.msg.as_str().into(),
+            code: None,
+            level: Level::Help.to_str(),
+            spans,
+            children: vec![],
+            rendered: None,
+        }
+    }
+
     fn spans(&self, msp: &MultiSpan) -> Vec<DiagnosticSpan> {
         msp.span_labels().iter().map(|label| self.span(label)).collect()
     }
@@ -111,6 +199,8 @@ impl JsonEmitter {
             is_primary: label.is_primary,
             text: self.span_lines(span),
             label: label.label.as_ref().map(|msg| msg.as_str().into()),
+            suggested_replacement: None,
+            suggestion_applicability: None,
         }
     }
 
@@ -194,13 +284,96 @@ impl JsonEmitter {
         }
     }
 
+    fn solc_error(&self, diagnostic: &crate::diagnostics::Diagnostic) -> SolcError {
+        let ty = match diagnostic.level {
+            Level::Bug => "InternalCompilerError",
+            Level::Fatal => "FatalError",
+            Level::Error => "Exception",
+            Level::Warning => "Warning",
+            _ => "Info",
+        };
+        let location = |span: Span, message: Option<String>| {
+            let sm = &**self.source_map();
+            let start = sm.lookup_char_pos(span.lo());
+            SourceLocation {
+                file: sm.filename_for_diagnostics(&start.file.name).to_string(),
+                start: start.file.original_relative_byte_pos(span.lo()).0,
+                end: start.file.original_relative_byte_pos(span.hi()).0,
+                message,
+            }
+        };
+
+        let mut formatted_message = format!("{ty}: {}\n", diagnostic.label());
+        let primary = diagnostic.span.primary_span();
+        if let Some(span) = primary {
+            self.solc_format_span(&mut formatted_message, span);
+        }
+        let mut secondary_source_locations = Vec::new();
+        for sub in &diagnostic.children {
+            let label = sub.label().into_owned();
+            let level = match sub.level {
+                Level::Help | Level::OnceHelp => "Help",
+                _ => "Note",
+            };
+            formatted_message.push_str(&format!("{level}: {label}\n"));
+            if let Some(span) = sub.span.primary_span() {
+                self.solc_format_span(&mut formatted_message, span);
+                secondary_source_locations.push(location(span, Some(label)));
+            }
+        }
+        formatted_message.push('\n');
+
+        SolcError {
+            source_location: primary.map(|span| location(span, None)),
+            secondary_source_locations,
+            r#type: ty,
+            component: "general",
+            severity: to_severity(diagnostic.level),
+            error_code: diagnostic.id(),
+            message: diagnostic.label().into_owned(),
+            formatted_message,
+        }
+    }
+
+    /// Formats a source location like solc:
+    ///
+    /// ```text
+    ///  --> a.sol:4:9:
+    ///   |
+    /// 4 |         uint x = 1;
+    ///   |         ^^^^^^
+    /// ```
+    fn solc_format_span(&self, out: &mut String, span: Span) {
+        let sm = &**self.source_map();
+        let start = sm.lookup_char_pos(span.lo());
+        let end = sm.lookup_char_pos(span.hi());
+        let name = sm.filename_for_diagnostics(&start.file.name);
+        let line_number = start.line.to_string();
+        let pad = " ".repeat(line_number.len());
+        let col = start.col.0;
+        out.push_str(&format!("{pad}--> {name}:{}:{}:\n{pad} |\n", start.line, col + 1));
+        let text = start.file.get_line(start.line - 1).unwrap_or_default().trim_end();
+        out.push_str(&format!("{line_number} | {text}\n"));
+        let indent = text.chars().take(col).map(|c| if c == '\t' { '\t' } else { ' ' });
+        let indent = indent.collect::<String>();
+        if start.line == end.line {
+            let carets = "^".repeat(end.col.0.saturating_sub(col).max(1));
+            out.push_str(&format!("{pad} | {indent}{carets}\n"));
+        } else {
+            out.push_str(&format!(
+                "{pad} | {indent}^ (Relevant source part starts here and spans across multiple \
+                 lines).\n"
+            ));
+        }
+    }
+
     fn emit_diagnostic_to_buffer(&mut self, diagnostic: &crate::diagnostics::Diagnostic) -> String {
         self.human_emitter.emit_diagnostic(diagnostic);
         std::mem::take(self.human_emitter.buffer_mut())
     }
 
     fn emit<T: ?Sized + Serialize>(&mut self, value: &T) -> io::Result<()> {
-        if self.pretty {
+        if self.pretty && !self.line_delimited {
             serde_json::to_writer_pretty(&mut *self.writer, value)
         } else {
             serde_json::to_writer(&mut *self.writer, value)
@@ -250,9 +423,11 @@ struct DiagnosticSpan {
     text: Vec<DiagnosticSpanLine>,
     /// Label that should be placed at this location (if any)
     label: Option<String>,
-    // /// If we are suggesting a replacement, this will contain text
-    // /// that should be sliced in atop this span.
-    // suggested_replacement: Option<String>,
+    /// If we are suggesting a replacement, this will contain text
+    /// that should be sliced in atop this span.
+    suggested_replacement: Option<String>,
+    /// If the suggestion is approximate.
+    suggestion_applicability: Option<&'static str>,
 }
 
 #[derive(Serialize)]
@@ -288,6 +463,23 @@ struct SolcDiagnostic {
     formatted_message: Option<String>,
 }
 
+/// The exact format of the errors of solc's standard JSON output.
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SolcError {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    source_location: Option<SourceLocation>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    secondary_source_locations: Vec<SourceLocation>,
+    r#type: &'static str,
+    component: &'static str,
+    severity: Severity,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error_code: Option<String>,
+    message: String,
+    formatted_message: String,
+}
+
 #[derive(Serialize)]
 struct SourceLocation {
     file: String,
@@ -316,3 +508,137 @@ fn to_severity(level: Level) -> Severity {
         Level::Allow => Severity::Info,
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{diagnostics::Diagnostic, error_code, BytePos};
+    use std::sync::Mutex;
+
+    #[derive(Clone, Default)]
+    struct Buffer(Arc<Mutex<Vec<u8>>>);
+
+    impl io::Write for Buffer {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.lock().unwrap().write(buf)
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn solc_compatible() {
+        let sm = Arc::new(SourceMap::empty());
+        let src = "contract C {\n    function f() public {\n        uint x = 1;\n    }\n}";
+        let file = sm.new_dummy_source_file("a.sol".into(), src.into()).unwrap();
+        let span = |lo: usize, hi: usize| {
+            let pos = |i| file.start_pos + BytePos::from_usize(i);
+            Span::new(pos(lo), pos(hi))
+        };
+
+        let buffer = Buffer::default();
+        let mut emitter =
+            JsonEmitter::new(Box::new(buffer.clone()), sm.clone()).solc_compatible(true);
+        let mut diag = Diagnostic::new(Level::Warning, "Unused local variable.");
+        diag.span(span(47, 53)).code(error_code!(2072));
+        diag.span_note(span(0, 10), "Contract declared here.");
+        emitter.emit_diagnostic(&diag);
+        emitter.emit_diagnostic(&Diagnostic::new(Level::Error, "aborting"));
+        drop(emitter);
+
+        let output: serde_json::Value = serde_json::from_slice(&buffer.0.lock().unwrap()).unwrap();
+        assert_eq!(
+            output,
+            serde_json::json!({
+                "errors": [
+                    {
+                        "sourceLocation": { "file": "a.sol", "start": 47, "end": 53 },
+                        "secondarySourceLocations": [
+                            {
+                                "file": "a.sol",
+                                "start": 0,
+                                "end": 10,
+                                "message": "Contract declared here.",
+                            },
+                        ],
+                        "type": "Warning",
+                        "component": "general",
+                        "severity": "warning",
+                        "errorCode": "2072",
+                        "message": "Unused local variable.",
+                        "formattedMessage": "Warning: Unused local variable.\n \
+                                             --> a.sol:3:9:\n  |\n\
+                                             3 |         uint x = 1;\n  |         ^^^^^^\n\
+                                             Note: Contract declared here.\n \
+                                             --> a.sol:1:1:\n  |\n\
+                                             1 | contract C {\n  | ^^^^^^^^^^\n\n",
+                    },
+                    {
+                        "type": "Exception",
+                        "component": "general",
+                        "severity": "error",
+                        "message": "aborting",
+                        "formattedMessage": "Exception: aborting\n\n",
+                    },
+                ],
+            })
+        );
+    }
+
+    #[test]
+    fn line_delimited() {
+        let sm = Arc::new(SourceMap::empty());
+        let buffer = Buffer::default();
+        let mut emitter = JsonEmitter::new(Box::new(buffer.clone()), sm)
+            .pretty(true)
+            .solc_compatible(true)
+            .line_delimited(true);
+        let lines = || {
+            let output = String::from_utf8(buffer.0.lock().unwrap().clone()).unwrap();
+            output.lines().map(|line| serde_json::from_str(line).unwrap()).collect::<Vec<_>>()
+        };
+
+        emitter.emit_diagnostic(&Diagnostic::new(Level::Warning, "first"));
+        let output: Vec<serde_json::Value> = lines();
+        assert_eq!(output.len(), 1);
+        assert_eq!(output[0]["message"], "first");
+
+        emitter.emit_diagnostic(&Diagnostic::new(Level::Error, "second"));
+        drop(emitter);
+        let output: Vec<serde_json::Value> = lines();
+        assert_eq!(output.len(), 2);
+        assert_eq!(output[1]["message"], "second");
+        assert_eq!(output[1]["severity"], "error");
+    }
+
+    #[test]
+    fn suggestions() {
+        let sm = Arc::new(SourceMap::empty());
+        let file = sm.new_dummy_source_file("a.sol".into(), "uint x = 1;".into()).unwrap();
+        let span = Span::new(file.start_pos + BytePos(5), file.start_pos + BytePos(6));
+
+        let buffer = Buffer::default();
+        let mut emitter = JsonEmitter::new(Box::new(buffer.clone()), sm.clone()).rustc_like(true);
+        let mut diag = Diagnostic::new(Level::Warning, "unused local variable");
+        diag.span(span).span_suggestion(
+            span,
+            "prefix it with an underscore",
+            "_x",
+            crate::diagnostics::Applicability::MaybeIncorrect,
+        );
+        emitter.emit_diagnostic(&diag);
+        drop(emitter);
+
+        let output: serde_json::Value = serde_json::from_slice(&buffer.0.lock().unwrap()).unwrap();
+        let child = &output["children"][0];
+        assert_eq!(child["level"], "help");
+        assert_eq!(child["message"], "prefix it with an underscore");
+        assert_eq!(child["spans"][0]["byte_start"], 5);
+        assert_eq!(child["spans"][0]["suggested_replacement"], "_x");
+        assert_eq!(child["spans"][0]["suggestion_applicability"], "MaybeIncorrect");
+        let rendered = output["rendered"].as_str().unwrap();
+        assert!(rendered.contains("help: prefix it with an underscore: `_x`"), "{rendered}");
+    }
+}
@@ -2,6 +2,9 @@ use super::{DiagCtxt, Diagnostic, Level};
 use crate::SourceMap;
 use std::{any::Any, sync::Arc};
 
+mod buffered;
+pub use buffered::{BufferedDiagnostic, BufferedEmitter, DiagnosticLocation};
+
 mod human;
 pub use human::{HumanBufferEmitter, HumanEmitter};
 
@@ -10,12 +13,64 @@ mod json;
 #[cfg(feature = "json")]
 pub use json::JsonEmitter;
 
+#[cfg(feature = "json")]
+mod sarif;
+#[cfg(feature = "json")]
+pub use sarif::SarifEmitter;
+
 mod rustc;
 
 /// Dynamic diagnostic emitter. See [`Emitter`].
 pub type DynEmitter = dyn Emitter + Send;
 
 /// Diagnostic emitter.
+///
+/// Implement this trait to handle diagnostics in a custom way instead of printing them, and set it
+/// with [`SessionBuilder::with_emitter`](crate::SessionBuilder::with_emitter) or
+/// [`DiagCtxt::new`].
+///
+/// Diagnostics are emitted after their level has been resolved, and after deduplication and lint
+/// suppression. Emitters are called from multiple threads, but never concurrently.
+///
+/// # Examples
+///
+/// Collect the messages and locations of all the emitted diagnostics:
+///
+/// ```
+/// use solar_interface::{
+///     diagnostics::{Diagnostic, Emitter},
+///     Session, SourceMap,
+/// };
+/// use std::sync::{Arc, Mutex};
+///
+/// struct Collector {
+///     source_map: Arc<SourceMap>,
+///     messages: Arc<Mutex<Vec<String>>>,
+/// }
+///
+/// impl Emitter for Collector {
+///     fn emit_diagnostic(&mut self, diagnostic: &Diagnostic) {
+///         let location = match diagnostic.span.primary_span() {
+///             Some(span) => self.source_map.span_to_diagnostic_string(span),
+///             None => "<unknown>".into(),
+///         };
+///         let level = diagnostic.level().to_str();
+///         let msg = format!("{location}: {level}: {}", diagnostic.label());
+///         self.messages.lock().unwrap().push(msg);
+///     }
+///
+///     fn source_map(&self) -> Option<&Arc<SourceMap>> {
+///         Some(&self.source_map)
+///     }
+/// }
+///
+/// let messages = Arc::new(Mutex::new(Vec::new()));
+/// let sess = Session::builder()
+///     .with_emitter(|source_map| Collector { source_map, messages: messages.clone() })
+///     .build();
+/// sess.dcx.warn("something happened").emit();
+/// assert_eq!(*messages.lock().unwrap(), ["<unknown>: warning: something happened"]);
+/// ```
 pub trait Emitter: Any {
     /// Emits a diagnostic.
     fn emit_diagnostic(&mut self, diagnostic: &Diagnostic);
@@ -38,6 +93,10 @@ impl DynEmitter {
         self.downcast_ref::<HumanBufferEmitter>().map(HumanBufferEmitter::buffer)
     }
 
+    pub(crate) fn buffered_diagnostics(&self) -> Option<&[BufferedDiagnostic]> {
+        self.downcast_ref::<BufferedEmitter>().map(BufferedEmitter::diagnostics)
+    }
+
     // TODO: Remove when dyn trait upcasting is stable.
     fn downcast_ref<T: Any>(&self) -> Option<&T> {
         if self.type_id() == std::any::TypeId::of::<T>() {
@@ -0,0 +1,361 @@
+use super::{io_panic, Emitter};
+use crate::{
+    diagnostics::{Diagnostic, Level, MultiSpan},
+    SourceMap, Span,
+};
+use serde::Serialize;
+use std::{io, path::Path, sync::Arc};
+
+/// Diagnostic emitter that emits diagnostics as a [SARIF 2.1.0] log.
+///
+/// SARIF is understood by code scanning tools, such as GitHub code scanning. Since a log is a
+/// single JSON document, diagnostics are buffered and the log is written when the emitter is
+/// dropped.
+///
+/// [SARIF 2.1.0]: https://docs.oasis-open.org/sarif/sarif/v2.1.0/sarif-v2.1.0.html
+pub struct SarifEmitter {
+    writer: Box<dyn io::Write + Send>,
+    source_map: Arc<SourceMap>,
+    pretty: bool,
+
+    rules: Vec<Rule>,
+    results: Vec<SarifResult>,
+}
+
+impl Emitter for SarifEmitter {
+    fn emit_diagnostic(&mut self, diagnostic: &Diagnostic) {
+        let result = self.result(diagnostic);
+        self.results.push(result);
+    }
+
+    fn source_map(&self) -> Option<&Arc<SourceMap>> {
+        Some(&self.source_map)
+    }
+}
+
+impl Drop for SarifEmitter {
+    fn drop(&mut self) {
+        if let Err(e) = self.write() {
+            if !std::thread::panicking() {
+                io_panic(e);
+            }
+        }
+    }
+}
+
+impl SarifEmitter {
+    /// Creates a new `SarifEmitter` that writes to given writer.
+    pub fn new(writer: Box<dyn io::Write + Send>, source_map: Arc<SourceMap>) -> Self {
+        Self { writer, source_map, pretty: false, rules: Vec::new(), results: Vec::new() }
+    }
+
+    /// Sets whether to pretty print the JSON.
+    pub fn pretty(mut self, pretty: bool) -> Self {
+        self.pretty = pretty;
+        self
+    }
+
+    fn result(&mut self, diagnostic: &Diagnostic) -> SarifResult {
+        let diagnostic = &*diagnostic.with_rendered_suggestions();
+        let rule_id = diagnostic.id().or_else(|| diagnostic.lint.map(|lint| lint.name.into()));
+        if let Some(id) = &rule_id {
+            if !self.rules.iter().any(|rule| rule.id == *id) {
+                let short_description =
+                    diagnostic.lint.map(|lint| Message { text: lint.desc.into() });
+                self.rules.push(Rule { id: id.clone(), short_description });
+            }
+        }
+
+        let mut text = diagnostic.label().into_owned();
+        let mut related_locations = Vec::new();
+        for sub in &diagnostic.children {
+            match sub.span.primary_span() {
+                Some(span) => {
+                    let mut location = self.location(span);
+                    location.id = Some(related_locations.len());
+                    location.message = Some(Message { text: sub.label().into_owned() });
+                    related_locations.push(location);
+                }
+                None => text.push_str(&format!("\n{}: {}", sub.level.to_str(), sub.label())),
+            }
+        }
+        // Secondary labels of the main diagnostic.
+        for label in diagnostic.span.span_labels() {
+            if label.is_primary {
+                continue;
+            }
+            let mut location = self.location(label.span);
+            location.id = Some(related_locations.len());
+            location.message =
+                label.label.as_ref().map(|msg| Message { text: msg.as_str().into() });
+            related_locations.push(location);
+        }
+
+        SarifResult {
+            rule_id,
+            level: match diagnostic.level {
+                Level::Bug | Level::Fatal | Level::Error => "error",
+                Level::Warning => "warning",
+                Level::Note | Level::OnceNote | Level::FailureNote => "note",
+                Level::Help | Level::OnceHelp | Level::Allow => "none",
+            },
+            message: Message { text },
+            locations: self.locations(&diagnostic.span),
+            related_locations,
+        }
+    }
+
+    fn locations(&self, span: &MultiSpan) -> Vec<Location> {
+        span.primary_spans().iter().map(|&span| self.location(span)).collect()
+    }
+
+    fn location(&self, span: Span) -> Location {
+        let sm = &*self.source_map;
+        let start = sm.lookup_char_pos(span.lo());
+        let end = sm.lookup_char_pos(span.hi());
+        let byte_offset = start.file.original_relative_byte_pos(span.lo()).0;
+        let byte_end = start.file.original_relative_byte_pos(span.hi()).0;
+        let name = sm.filename_for_diagnostics(&start.file.name).to_string();
+        Location {
+            id: None,
+            physical_location: PhysicalLocation {
+                artifact_location: ArtifactLocation { uri: uri(&name) },
+                region: Region {
+                    start_line: start.line,
+                    start_column: start.col.0 + 1,
+                    end_line: end.line,
+                    end_column: end.col.0 + 1,
+                    byte_offset,
+                    byte_length: byte_end.saturating_sub(byte_offset),
+                },
+            },
+            message: None,
+        }
+    }
+
+    fn write(&mut self) -> io::Result<()> {
+        let log = Log {
+            schema: "https://json.schemastore.org/sarif-2.1.0.json",
+            version: "2.1.0",
+            runs: [Run {
+                tool: Tool {
+                    driver: Driver {
+                        name: "solar",
+                        version: crate::VERSION,
+                        information_uri: "https://github.com/paradigmxyz/solar",
+                        rules: std::mem::take(&mut self.rules),
+                    },
+                },
+                column_kind: "unicodeCodePoints",
+                results: std::mem::take(&mut self.results),
+            }],
+        };
+        if self.pretty {
+            serde_json::to_writer_pretty(&mut *self.writer, &log)
+        } else {
+            serde_json::to_writer(&mut *self.writer, &log)
+        }?;
+        self.writer.write_all(b"\n")?;
+        self.writer.flush()
+    }
+}
+
+/// Returns the URI of a file name: relative to the current directory if possible, with forward
+/// slashes.
+fn uri(name: &str) -> String {
+    let path = Path::new(name);
+    let relative = std::env::current_dir()
+        .ok()
+        .and_then(|dir| path.strip_prefix(dir).ok().map(|path| path.display().to_string()));
+    let uri = relative.unwrap_or_else(|| name.to_string()).replace('\\', "/");
+    if Path::new(&uri).is_absolute() {
+        format!("file://{}{uri}", if uri.starts_with('/') { "" } else { "/" })
+    } else {
+        uri
+    }
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Log {
+    #[serde(rename = "$schema")]
+    schema: &'static str,
+    version: &'static str,
+    runs: [Run; 1],
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Run {
+    tool: Tool,
+    column_kind: &'static str,
+    results: Vec<SarifResult>,
+}
+
+#[derive(Serialize)]
+struct Tool {
+    driver: Driver,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Driver {
+    name: &'static str,
+    version: &'static str,
+    information_uri: &'static str,
+    rules: Vec<Rule>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Rule {
+    id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    short_description: Option<Message>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SarifResult {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    rule_id: Option<String>,
+    /// "error", "warning", "note" or "none".
+    level: &'static str,
+    message: Message,
+    locations: Vec<Location>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    related_locations: Vec<Location>,
+}
+
+#[derive(Serialize)]
+struct Message {
+    text: String,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Location {
+    /// Only set for related locations.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    id: Option<usize>,
+    physical_location: PhysicalLocation,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    message: Option<Message>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct PhysicalLocation {
+    artifact_location: ArtifactLocation,
+    region: Region,
+}
+
+#[derive(Serialize)]
+struct ArtifactLocation {
+    uri: String,
+}
+
+/// Lines and columns are 1-based, columns are in characters.
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Region {
+    start_line: usize,
+    start_column: usize,
+    end_line: usize,
+    end_column: usize,
+    byte_offset: u32,
+    byte_length: u32,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{error_code, lint::LintLevel, BytePos};
+    use std::sync::Mutex;
+
+    static LINT: crate::lint::Lint =
+        crate::lint::Lint { name: "a", default_level: LintLevel::Warn, desc: "detects a" };
+
+    #[derive(Clone, Default)]
+    struct Buffer(Arc<Mutex<Vec<u8>>>);
+
+    impl io::Write for Buffer {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.lock().unwrap().write(buf)
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn sarif() {
+        let sm = Arc::new(SourceMap::empty());
+        let file = sm.new_dummy_source_file("a.sol".into(), "contract C {\n    uint x;\n}".into());
+        let file = file.unwrap();
+        let span = |lo: usize, hi: usize| {
+            let pos = |i| file.start_pos + BytePos::from_usize(i);
+            Span::new(pos(lo), pos(hi))
+        };
+
+        let buffer = Buffer::default();
+        let mut emitter = SarifEmitter::new(Box::new(buffer.clone()), sm.clone());
+        let mut diag = Diagnostic::new(Level::Warning, "unused");
+        diag.span(span(22, 23)).code(error_code!(2072)).lint(&LINT);
+        diag.span_note(span(0, 10), "declared in");
+        diag.help("remove it");
+        emitter.emit_diagnostic(&diag);
+        emitter.emit_diagnostic(&Diagnostic::new(Level::Error, "aborting"));
+        drop(emitter);
+
+        let log: serde_json::Value = serde_json::from_slice(&buffer.0.lock().unwrap()).unwrap();
+        assert_eq!(log["version"], "2.1.0");
+        let run = &log["runs"][0];
+        assert_eq!(
+            run["tool"]["driver"]["rules"],
+            serde_json::json!([{
+                "id": "2072",
+                "shortDescription": { "text": "detects a" },
+            }])
+        );
+        assert_eq!(
+            run["results"][0],
+            serde_json::json!({
+                "ruleId": "2072",
+                "level": "warning",
+                "message": { "text": "unused\nhelp: remove it" },
+                "locations": [{
+                    "physicalLocation": {
+                        "artifactLocation": { "uri": "a.sol" },
+                        "region": {
+                            "startLine": 2,
+                            "startColumn": 10,
+                            "endLine": 2,
+                            "endColumn": 11,
+                            "byteOffset": 22,
+                            "byteLength": 1,
+                        },
+                    },
+                }],
+                "relatedLocations": [{
+                    "id": 0,
+                    "physicalLocation": {
+                        "artifactLocation": { "uri": "a.sol" },
+                        "region": {
+                            "startLine": 1,
+                            "startColumn": 1,
+                            "endLine": 1,
+                            "endColumn": 11,
+                            "byteOffset": 0,
+                            "byteLength": 10,
+                        },
+                    },
+                    "message": { "text": "declared in" },
+                }],
+            })
+        );
+        assert_eq!(run["results"][1]["level"], "error");
+        assert_eq!(run["results"][1]["locations"], serde_json::json!([]));
+    }
+}
@@ -0,0 +1,51 @@
+//! Registry of the extended explanations of error codes.
+//!
+//! Each explanation is a Markdown file named after its code in the `error_codes` directory.
+//! Codes reuse the ones of solc when there is an equivalent diagnostic.
+
+use super::DiagnosticId;
+
+macro_rules! error_codes {
+    ($($code:literal),* $(,)?) => {
+        /// All the error codes with an explanation, sorted by code.
+        static ERROR_CODES: &[(u32, &str)] = &[
+            $(($code, include_str!(concat!("error_codes/", stringify!($code), ".md"))),)*
+        ];
+    };
+}
+
+error_codes! {
+    1686, 1878, 2046, 2072, 2319, 2333, 2519, 2904, 3445, 3716, 4334, 4822, 5667, 5740, 6162,
+    6959, 7576, 9098, 9592,
+}
+
+impl DiagnosticId {
+    /// Returns the extended explanation of the error code, if any.
+    pub fn explanation(&self) -> Option<&'static str> {
+        ERROR_CODES.binary_search_by_key(&self.id, |&(code, _)| code).ok().map(|i| ERROR_CODES[i].1)
+    }
+
+    /// Returns an iterator over all the error codes that have an explanation.
+    pub fn all_explained() -> impl Iterator<Item = Self> {
+        ERROR_CODES.iter().map(|&(id, _)| Self { id })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sorted() {
+        assert!(ERROR_CODES.windows(2).all(|w| w[0].0 < w[1].0));
+    }
+
+    #[test]
+    fn explanations() {
+        assert!(crate::error_code!(2904).explanation().unwrap().contains("not declared"));
+        assert_eq!(crate::error_code!(1234).explanation(), None);
+        for (code, explanation) in ERROR_CODES {
+            assert!(explanation.contains("```solidity"), "{code} has no example");
+        }
+    }
+}
@@ -76,6 +76,12 @@ impl MultiSpan {
         Self { primary_spans: vec, span_labels: vec![] }
     }
 
+    /// Adds a primary span, keeping the primary spans sorted.
+    pub fn push_primary_span(&mut self, span: Span) {
+        let idx = self.primary_spans.partition_point(|&sp| sp <= span);
+        self.primary_spans.insert(idx, span);
+    }
+
     pub fn push_span_label(&mut self, span: Span, label: impl Into<DiagnosticMessage>) {
         self.span_labels.push((span, label.into()));
     }
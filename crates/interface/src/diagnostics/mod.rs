@@ -2,7 +2,7 @@
 //!
 //! Modified from [`rustc_errors`](https://github.com/rust-lang/rust/blob/520e30be83b4ed57b609d33166c988d1512bf4f3/compiler/rustc_errors/src/diagnostic.rs).
 
-use crate::Span;
+use crate::{lint::Lint, Span};
 use anstyle::{AnsiColor, Color};
 use std::{borrow::Cow, fmt, panic::Location};
 
@@ -12,16 +12,22 @@ pub use builder::{DiagnosticBuilder, EmissionGuarantee};
 mod context;
 pub use context::{DiagCtxt, DiagCtxtFlags};
 
+mod error_codes;
+
 mod emitter;
-#[cfg(feature = "json")]
-pub use emitter::JsonEmitter;
 pub use emitter::{
-    DynEmitter, Emitter, HumanBufferEmitter, HumanEmitter, LocalEmitter, SilentEmitter,
+    BufferedDiagnostic, BufferedEmitter, DiagnosticLocation, DynEmitter, Emitter,
+    HumanBufferEmitter, HumanEmitter, LocalEmitter, SilentEmitter,
 };
+#[cfg(feature = "json")]
+pub use emitter::{JsonEmitter, SarifEmitter};
 
 mod message;
 pub use message::{DiagnosticMessage, MultiSpan, SpanLabel};
 
+mod suppression;
+pub use suppression::{Suppression, SuppressionCollector};
+
 /// Represents all the diagnostics emitted up to a certain point.
 ///
 /// Returned by [`DiagCtxt::emitted_diagnostics`].
@@ -97,6 +103,15 @@ pub struct DiagnosticId {
 }
 
 impl DiagnosticId {
+    /// Creates an error code diagnostic ID, returning `None` if it is not in range 0001-9999.
+    pub const fn new(id: u32) -> Option<Self> {
+        if id >= 1 && id <= 9999 {
+            Some(Self { id })
+        } else {
+            None
+        }
+    }
+
     /// Creates an error code diagnostic ID.
     ///
     /// Use [`error_code!`](crate::error_code) instead.
@@ -113,6 +128,19 @@ impl DiagnosticId {
     }
 }
 
+impl std::str::FromStr for DiagnosticId {
+    type Err = ();
+
+    /// Parses an error code, with an optional `E` prefix, like `E1234` or `1234`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let digits = s.strip_prefix(['E', 'e']).unwrap_or(s);
+        if digits.len() != 4 || !digits.bytes().all(|b| b.is_ascii_digit()) {
+            return Err(());
+        }
+        digits.parse().ok().and_then(Self::new).ok_or(())
+    }
+}
+
 /// Used for creating an error code. The input must be exactly 4 decimal digits.
 ///
 /// # Examples
@@ -311,6 +339,73 @@ impl SubDiagnostic {
     }
 }
 
+/// Indicates the confidence in the correctness of a suggestion.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Applicability {
+    /// The suggestion is definitely what the user intended, or maintains the exact meaning of
+    /// the code. It can be applied automatically.
+    MachineApplicable,
+    /// The suggestion may be what the user intended, but it is uncertain. It should compile if
+    /// applied.
+    MaybeIncorrect,
+    /// The suggestion contains placeholders like `(...)` or `{ /* fields */ }`. It will not
+    /// compile if applied as is.
+    HasPlaceholders,
+    /// The applicability of the suggestion is unknown.
+    Unspecified,
+}
+
+impl Applicability {
+    /// Returns the string representation of the applicability, like rustc's.
+    pub fn to_str(self) -> &'static str {
+        match self {
+            Self::MachineApplicable => "MachineApplicable",
+            Self::MaybeIncorrect => "MaybeIncorrect",
+            Self::HasPlaceholders => "HasPlaceholders",
+            Self::Unspecified => "Unspecified",
+        }
+    }
+}
+
+/// A replacement of the source code of a span, part of a [`CodeSuggestion`].
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct SubstitutionPart {
+    /// The span to replace.
+    pub span: Span,
+    /// The replacement text. Empty to remove the span.
+    pub snippet: String,
+}
+
+/// A suggestion of how to fix a diagnostic, as a set of replacements of the source code.
+#[derive(Clone, Debug, PartialEq, Hash)]
+pub struct CodeSuggestion {
+    /// The replacements to apply together. They must not overlap.
+    pub parts: Vec<SubstitutionPart>,
+    /// The message describing the suggestion, like "remove this comma".
+    pub msg: DiagnosticMessage,
+    /// The confidence in the correctness of the suggestion.
+    pub applicability: Applicability,
+}
+
+impl CodeSuggestion {
+    /// Returns the help message that is rendered for this suggestion in human-readable output.
+    ///
+    /// A single short replacement is rendered inline as "help: msg: `replacement`", other
+    /// suggestions as a help message pointing at the replaced spans.
+    fn to_sub_diagnostic(&self) -> SubDiagnostic {
+        let (msg, span) = match &self.parts[..] {
+            [part] if !part.snippet.is_empty() && !part.snippet.contains('\n') => {
+                (format!("{}: `{}`", self.msg.as_str(), part.snippet), MultiSpan::new())
+            }
+            parts => (
+                self.msg.as_str().to_string(),
+                MultiSpan::from_spans(parts.iter().map(|part| part.span).collect()),
+            ),
+        };
+        SubDiagnostic { level: Level::Help, messages: vec![(msg.into(), Style::NoStyle)], span }
+    }
+}
+
 /// A compiler diagnostic.
 #[must_use]
 #[derive(Clone, Debug)]
@@ -321,6 +416,13 @@ pub struct Diagnostic {
     pub span: MultiSpan,
     pub children: Vec<SubDiagnostic>,
     pub code: Option<DiagnosticId>,
+    /// The lint that this diagnostic was emitted for, if any.
+    pub lint: Option<&'static Lint>,
+    /// Suggestions of how to fix this diagnostic.
+    pub suggestions: Vec<CodeSuggestion>,
+    /// The key of the group of related diagnostics that this diagnostic is part of, if any.
+    /// See [`Diagnostic::group`].
+    pub group: Option<u64>,
 
     pub created_at: &'static Location<'static>,
 }
@@ -351,9 +453,11 @@ impl Diagnostic {
             level,
             messages,
             code: None,
+            lint: None,
             span: MultiSpan::new(),
             children: vec![],
-            // suggestions: Ok(vec![]),
+            suggestions: vec![],
+            group: None,
             // args: Default::default(),
             // sort_span: DUMMY_SP,
             // is_lint: false,
@@ -387,6 +491,17 @@ impl Diagnostic {
         self.code.as_ref().map(|code| code.as_string())
     }
 
+    /// Returns this diagnostic with its suggestions rendered as help sub-diagnostics, for
+    /// emitters that do not support suggestions.
+    pub fn with_rendered_suggestions(&self) -> Cow<'_, Self> {
+        if self.suggestions.is_empty() {
+            return Cow::Borrowed(self);
+        }
+        let mut diag = self.clone();
+        diag.children.extend(self.suggestions.iter().map(CodeSuggestion::to_sub_diagnostic));
+        Cow::Owned(diag)
+    }
+
     /// Fields used for `PartialEq` and `Hash` implementations.
     fn keys(&self) -> impl PartialEq + std::hash::Hash + '_ {
         (
@@ -395,7 +510,7 @@ impl Diagnostic {
             // self.args().collect(),
             &self.code,
             &self.span,
-            // &self.suggestions,
+            &self.suggestions,
             // (if self.is_lint { None } else { Some(&self.children) }),
             &self.children,
         )
@@ -416,6 +531,25 @@ impl Diagnostic {
         self
     }
 
+    /// Sets the lint that this diagnostic is emitted for.
+    pub fn lint(&mut self, lint: &'static Lint) -> &mut Self {
+        self.lint = Some(lint);
+        self
+    }
+
+    /// Sets the group of related diagnostics that this diagnostic is part of.
+    ///
+    /// Diagnostics of the same group and level that are emitted consecutively are emitted as a
+    /// single diagnostic, with the primary spans of all of them and the sub-diagnostics of the
+    /// first one. For example, all the redeclarations of an identifier can be reported in a single
+    /// error pointing to the first declaration.
+    pub fn group(&mut self, key: impl std::hash::Hash) -> &mut Self {
+        use std::hash::BuildHasher;
+        let hasher = solar_data_structures::map::rustc_hash::FxBuildHasher;
+        self.group = Some(hasher.hash_one(key));
+        self
+    }
+
     /// Adds a span/label to be included in the resulting snippet.
     ///
     /// This is pushed onto the [`MultiSpan`] that was created when the diagnostic
@@ -423,12 +557,30 @@ impl Diagnostic {
     /// span/label, *not* a span added by one of the `span_{note,warn,help,suggestions}` methods.
     ///
     /// This span is *not* considered a ["primary span"][`MultiSpan`]; only
-    /// the `Span` supplied when creating the diagnostic is primary.
+    /// the `Span` supplied when creating the diagnostic is primary. Use
+    /// [`Self::primary_span_label()`] to add a labeled primary span.
     pub fn span_label(&mut self, span: Span, label: impl Into<DiagnosticMessage>) -> &mut Self {
         self.span.push_span_label(span, label);
         self
     }
 
+    /// Adds a primary span with a label to be included in the resulting snippet.
+    ///
+    /// This allows pointing to multiple locations with distinct labels in a single diagnostic,
+    /// such as both declarations of a conflicting identifier. The spans may be in different
+    /// files, in which case a snippet is rendered for each file.
+    pub fn primary_span_label(
+        &mut self,
+        span: Span,
+        label: impl Into<DiagnosticMessage>,
+    ) -> &mut Self {
+        if !self.span.primary_spans().contains(&span) {
+            self.span.push_primary_span(span);
+        }
+        self.span.push_span_label(span, label);
+        self
+    }
+
     /// Labels all the given spans with the provided label.
     /// See [`Self::span_label()`] for more information.
     pub fn span_labels(
@@ -538,6 +690,40 @@ impl Diagnostic {
         self.sub(Level::Help, msg, span)
     }
 
+    /// Adds a suggestion to replace the source code of `span` with `suggestion`.
+    ///
+    /// An empty `suggestion` suggests to remove the span.
+    pub fn span_suggestion(
+        &mut self,
+        span: Span,
+        msg: impl Into<DiagnosticMessage>,
+        suggestion: impl Into<String>,
+        applicability: Applicability,
+    ) -> &mut Self {
+        self.multipart_suggestion(msg, vec![(span, suggestion.into())], applicability)
+    }
+
+    /// Adds a suggestion made of multiple replacements, which must be applied together.
+    pub fn multipart_suggestion(
+        &mut self,
+        msg: impl Into<DiagnosticMessage>,
+        parts: Vec<(Span, String)>,
+        applicability: Applicability,
+    ) -> &mut Self {
+        debug_assert!(!parts.is_empty(), "empty suggestion");
+        let mut parts = parts
+            .into_iter()
+            .map(|(span, snippet)| SubstitutionPart { span, snippet })
+            .collect::<Vec<_>>();
+        parts.sort_unstable_by_key(|part| part.span.lo());
+        debug_assert!(
+            parts.windows(2).all(|w| w[0].span.hi() <= w[1].span.lo()),
+            "suggestion parts overlap"
+        );
+        self.suggestions.push(CodeSuggestion { parts, msg: msg.into(), applicability });
+        self
+    }
+
     fn sub(
         &mut self,
         level: Level,
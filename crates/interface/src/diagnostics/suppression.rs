@@ -0,0 +1,163 @@
+//! Suppression of lints with comments in the source code.
+//!
+//! The following comments are recognized, with lint names separated by commas or whitespace:
+//! - `// solar-disable-next-line [lints...]`: suppresses the lints on the next line;
+//! - `// solar-disable [lints...]`: suppresses the lints until the next `solar-enable` comment
+//!   that names them, or until the end of the file;
+//! - `// solar-enable [lints...]`: ends the suppression of the given lints. Without lints, ends all
+//!   the suppressions.
+//!
+//! Without lints, `solar-disable` comments suppress all the lints.
+
+use crate::{
+    lint::{normalize_name, Lint},
+    BytePos, Span,
+};
+
+/// A range of source code in which some lints are suppressed.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Suppression {
+    /// The range in which the lints are suppressed.
+    pub span: Span,
+    /// The names of the suppressed lints. All lints are suppressed if empty.
+    pub lints: Vec<String>,
+}
+
+impl Suppression {
+    /// Returns `true` if this suppresses a diagnostic of the given lint that starts at `span`.
+    pub fn suppresses(&self, lint: &Lint, span: Span) -> bool {
+        (self.span.lo()..self.span.hi()).contains(&span.lo())
+            && (self.lints.is_empty() || self.lints.iter().any(|name| name == lint.name))
+    }
+}
+
+/// Collects the [`Suppression`]s of a source file from its comments.
+#[derive(Debug)]
+pub struct SuppressionCollector<'src> {
+    src: &'src str,
+    start_pos: BytePos,
+    /// The `solar-disable` comments that have not been ended yet, with their start position.
+    open: Vec<(BytePos, Vec<String>)>,
+    suppressions: Vec<Suppression>,
+}
+
+impl<'src> SuppressionCollector<'src> {
+    /// Creates a new collector for the given source file text, starting at `start_pos`.
+    pub fn new(src: &'src str, start_pos: BytePos) -> Self {
+        Self { src, start_pos, open: Vec::new(), suppressions: Vec::new() }
+    }
+
+    /// Adds a comment with the given span and text, excluding its delimiters.
+    ///
+    /// Comments must be added in source order.
+    pub fn add_comment(&mut self, span: Span, text: &str) {
+        let mut words =
+            text.split(|c: char| c == ',' || c.is_whitespace()).filter(|s| !s.is_empty());
+        let directive = words.next().unwrap_or_default();
+        if !directive.starts_with("solar-") {
+            return;
+        }
+        let lints = words.map(normalize_name).collect::<Vec<_>>();
+        match directive {
+            "solar-disable-next-line" => {
+                if let Some(span) = self.next_line(span) {
+                    self.suppressions.push(Suppression { span, lints });
+                }
+            }
+            "solar-disable" => self.open.push((span.hi(), lints)),
+            "solar-enable" => self.enable(span.lo(), &lints),
+            _ => {}
+        }
+    }
+
+    /// Finishes collecting, ending the suppressions that are still open at the end of the file.
+    pub fn finish(mut self) -> Vec<Suppression> {
+        let end = self.start_pos + BytePos::from_usize(self.src.len());
+        for (lo, lints) in std::mem::take(&mut self.open) {
+            self.suppressions.push(Suppression { span: Span::new(lo, end), lints });
+        }
+        self.suppressions
+    }
+
+    /// Ends the suppression of the given lints, or of all lints if empty, at `pos`.
+    fn enable(&mut self, pos: BytePos, lints: &[String]) {
+        let mut still_open = Vec::new();
+        for (lo, mut open) in std::mem::take(&mut self.open) {
+            let ends = if lints.is_empty() {
+                true
+            } else {
+                !open.is_empty() && open.iter().any(|name| lints.contains(name))
+            };
+            if !ends {
+                still_open.push((lo, open));
+                continue;
+            }
+            self.suppressions.push(Suppression { span: Span::new(lo, pos), lints: open.clone() });
+            open.retain(|name| !lints.contains(name));
+            if !open.is_empty() {
+                still_open.push((pos, open));
+            }
+        }
+        self.open = still_open;
+    }
+
+    /// Returns the span of the line after the one that contains the end of `span`.
+    fn next_line(&self, span: Span) -> Option<Span> {
+        let rel = (span.hi() - self.start_pos).to_usize();
+        let start = rel + self.src[rel..].find('\n')? + 1;
+        let end = self.src[start..].find('\n').map_or(self.src.len(), |i| start + i);
+        let pos = |i| self.start_pos + BytePos::from_usize(i);
+        Some(Span::new(pos(start), pos(end)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lint::LintLevel;
+
+    static A: Lint = Lint { name: "a", default_level: LintLevel::Warn, desc: "" };
+    static B: Lint = Lint { name: "b", default_level: LintLevel::Warn, desc: "" };
+
+    fn collect(src: &str) -> Vec<Suppression> {
+        let mut collector = SuppressionCollector::new(src, BytePos(0));
+        let mut pos = 0;
+        while let Some(start) = src[pos..].find("//") {
+            let start = pos + start;
+            let end = src[start..].find('\n').map_or(src.len(), |i| start + i);
+            let span = Span::new(BytePos::from_usize(start), BytePos::from_usize(end));
+            collector.add_comment(span, &src[start + 2..end]);
+            pos = end;
+        }
+        collector.finish()
+    }
+
+    fn is_suppressed(suppressions: &[Suppression], lint: &Lint, src: &str, needle: &str) -> bool {
+        let lo = BytePos::from_usize(src.find(needle).unwrap());
+        suppressions.iter().any(|s| s.suppresses(lint, Span::new(lo, lo)))
+    }
+
+    #[test]
+    fn next_line() {
+        let src = "x\n// solar-disable-next-line a\ny\nz";
+        let suppressions = collect(src);
+        assert!(!is_suppressed(&suppressions, &A, src, "x"));
+        assert!(is_suppressed(&suppressions, &A, src, "y"));
+        assert!(!is_suppressed(&suppressions, &B, src, "y"));
+        assert!(!is_suppressed(&suppressions, &A, src, "z"));
+    }
+
+    #[test]
+    fn ranges() {
+        let src = "// solar-disable a, b\nx\n// solar-enable a\ny\n// solar-enable\nz\n\
+                   // solar-disable\nw";
+        let suppressions = collect(src);
+        assert!(is_suppressed(&suppressions, &A, src, "x"));
+        assert!(is_suppressed(&suppressions, &B, src, "x"));
+        assert!(!is_suppressed(&suppressions, &A, src, "y"));
+        assert!(is_suppressed(&suppressions, &B, src, "y"));
+        assert!(!is_suppressed(&suppressions, &B, src, "z"));
+        assert!(is_suppressed(&suppressions, &A, src, "w"));
+        assert!(is_suppressed(&suppressions, &B, src, "w"));
+    }
+}
@@ -9,6 +9,9 @@ scoped_tls::scoped_thread_local!(static SESSION_GLOBALS: SessionGlobals);
 /// This struct is stored in thread-local storage in such a way that it is accessible without any
 /// kind of handle to all threads within the compilation session, but is not accessible outside the
 /// session.
+///
+/// Each [`Session`](crate::Session) owns an instance, which is set by
+/// [`Session::enter`](crate::Session::enter).
 pub struct SessionGlobals {
     pub(crate) symbol_interner: crate::symbol::Interner,
     /// A reference to the source map in the `Session`. It's an `Option`
@@ -34,6 +37,10 @@ impl SessionGlobals {
     }
 
     /// Sets this instance as the global instance for the duration of the closure.
+    ///
+    /// # Panics
+    ///
+    /// Panics if session globals are already set on the current thread.
     #[inline]
     pub fn set<R>(&self, f: impl FnOnce() -> R) -> R {
         if SESSION_GLOBALS.is_set() {
@@ -42,6 +49,22 @@ impl SessionGlobals {
         SESSION_GLOBALS.set(self, f)
     }
 
+    /// Sets this instance as the global instance for the duration of the closure.
+    ///
+    /// Unlike [`set`](Self::set), this does not panic if other session globals are already set on
+    /// the current thread: they are shadowed until the closure returns, and then restored. This is
+    /// how [`Session::enter`](crate::Session::enter) enters a session while another one is entered.
+    #[inline]
+    pub(crate) fn set_replacing<R>(&self, f: impl FnOnce() -> R) -> R {
+        SESSION_GLOBALS.set(self, f)
+    }
+
+    /// Returns `true` if this instance is the one currently set on this thread.
+    #[inline]
+    pub(crate) fn is_current(&self) -> bool {
+        Self::is_set() && Self::with(|globals| std::ptr::eq(globals, self))
+    }
+
     /// Insert `source_map` into the session globals for the duration of the closure's execution.
     pub fn with_source_map<R>(source_map: Arc<SourceMap>, f: impl FnOnce() -> R) -> R {
         let prev = Self::with(|g| g.source_map.lock().replace(source_map));
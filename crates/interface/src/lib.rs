@@ -15,6 +15,11 @@ use diagnostics::ErrorGuaranteed;
 mod globals;
 pub use globals::SessionGlobals;
 
+pub mod lint;
+
+mod memory;
+pub use memory::MemoryStats;
+
 mod pos;
 pub use pos::{BytePos, CharPos, RelativeBytePos};
 
@@ -30,6 +35,9 @@ pub use span::Span;
 mod symbol;
 pub use symbol::{kw, sym, Ident, Symbol};
 
+mod timings;
+pub use timings::PassTiming;
+
 pub mod panic_hook;
 
 pub use anstream::ColorChoice;
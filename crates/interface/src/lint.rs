@@ -0,0 +1,68 @@
+//! Lint definitions.
+//!
+//! A lint is a named diagnostic whose [level](LintLevel) can be configured by the user. Lints are
+//! declared with [`declare_lint!`](crate::declare_lint) and emitted with [`Session::lint`].
+
+use crate::{diagnostics::Level, Session};
+
+pub use solar_config::LintLevel;
+
+/// A lint.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Lint {
+    /// The name of the lint, in kebab-case.
+    pub name: &'static str,
+    /// The level of the lint when it is not configured.
+    pub default_level: LintLevel,
+    /// A short description of the lint.
+    pub desc: &'static str,
+}
+
+impl Lint {
+    /// Returns the level of this lint in the given session.
+    #[inline]
+    pub fn level(&self, sess: &Session) -> LintLevel {
+        sess.lint_level(self)
+    }
+}
+
+/// Returns the diagnostic level at which lints of the given level are emitted.
+pub fn diagnostic_level(level: LintLevel) -> Level {
+    match level {
+        LintLevel::Allow => Level::Allow,
+        LintLevel::Warn => Level::Warning,
+        LintLevel::Deny => Level::Error,
+    }
+}
+
+/// Normalizes a lint name as written by the user, accepting underscores in place of dashes.
+pub fn normalize_name(name: &str) -> String {
+    name.trim().replace('_', "-").to_lowercase()
+}
+
+/// Declares a static [`Lint`].
+///
+/// # Examples
+///
+/// ```
+/// solar_interface::declare_lint! {
+///     /// Warns about contracts without functions.
+///     pub EMPTY_CONTRACTS, "empty-contracts", Warn, "contracts without functions"
+/// }
+///
+/// assert_eq!(EMPTY_CONTRACTS.name, "empty-contracts");
+/// ```
+#[macro_export]
+macro_rules! declare_lint {
+    (
+        $(#[$attr:meta])*
+        $vis:vis $name:ident, $lint_name:literal, $level:ident, $desc:literal $(,)?
+    ) => {
+        $(#[$attr])*
+        $vis static $name: $crate::lint::Lint = $crate::lint::Lint {
+            name: $lint_name,
+            default_level: $crate::lint::LintLevel::$level,
+            desc: $desc,
+        };
+    };
+}
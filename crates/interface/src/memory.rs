@@ -0,0 +1,134 @@
+//! Memory usage statistics, reported with `--verbose`.
+
+use crate::SessionGlobals;
+use std::{
+    fmt::Write,
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+/// Memory usage statistics of a compilation session.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct MemoryStats {
+    /// The peak resident set size of the process, in bytes.
+    ///
+    /// This is `None` if it is not supported on the current platform.
+    pub peak_rss: Option<usize>,
+    /// The number of bytes allocated by the AST arenas.
+    pub ast_arena_bytes: usize,
+    /// The number of bytes allocated by the HIR arenas.
+    pub hir_arena_bytes: usize,
+    /// The number of interned symbols.
+    pub symbols: usize,
+    /// The number of bytes used by the strings of the interned symbols.
+    pub interner_bytes: usize,
+}
+
+/// Collects the sizes of the arenas of a session.
+#[derive(Default)]
+pub(crate) struct ArenaUsage {
+    ast: AtomicUsize,
+    hir: AtomicUsize,
+}
+
+impl ArenaUsage {
+    pub(crate) fn record_ast(&self, bytes: usize) {
+        self.ast.fetch_add(bytes, Ordering::Relaxed);
+    }
+
+    pub(crate) fn record_hir(&self, bytes: usize) {
+        self.hir.fetch_add(bytes, Ordering::Relaxed);
+    }
+
+    pub(crate) fn stats(&self, globals: &SessionGlobals) -> MemoryStats {
+        MemoryStats {
+            peak_rss: peak_rss(),
+            ast_arena_bytes: self.ast.load(Ordering::Relaxed),
+            hir_arena_bytes: self.hir.load(Ordering::Relaxed),
+            symbols: globals.symbol_interner.len(),
+            interner_bytes: globals.symbol_interner.memory_usage(),
+        }
+    }
+}
+
+/// Formats the given statistics as a report.
+pub(crate) fn report(stats: &MemoryStats, prefix: &str) -> String {
+    let mut out = String::new();
+    let mut line = |name: &str, value: String| {
+        let _ = writeln!(out, "{prefix} {name:<24} {value:>14}");
+    };
+    line("peak RSS", stats.peak_rss.map(fmt_bytes).unwrap_or_else(|| "unknown".into()));
+    line("AST arenas", fmt_bytes(stats.ast_arena_bytes));
+    line("HIR arenas", fmt_bytes(stats.hir_arena_bytes));
+    line("interned symbols", stats.symbols.to_string());
+    line("symbol interner", fmt_bytes(stats.interner_bytes));
+    out
+}
+
+fn fmt_bytes(bytes: usize) -> String {
+    format!("{:.2} MiB", bytes as f64 / (1024.0 * 1024.0))
+}
+
+/// Returns the peak resident set size of the current process, in bytes.
+#[cfg(target_os = "linux")]
+fn peak_rss() -> Option<usize> {
+    let status = std::fs::read_to_string("/proc/self/status").ok()?;
+    let line = status.lines().find_map(|line| line.strip_prefix("VmHWM:"))?;
+    let kib = line.trim().strip_suffix("kB")?.trim().parse::<usize>().ok()?;
+    Some(kib * 1024)
+}
+
+/// Returns the peak resident set size of the current process, in bytes.
+#[cfg(not(target_os = "linux"))]
+fn peak_rss() -> Option<usize> {
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Symbol;
+
+    #[test]
+    fn arena_usage() {
+        let usage = ArenaUsage::default();
+        usage.record_ast(1024);
+        usage.record_ast(2048);
+        usage.record_hir(512);
+        let globals = SessionGlobals::new();
+        let before = usage.stats(&globals);
+        assert_eq!(before.ast_arena_bytes, 3072);
+        assert_eq!(before.hir_arena_bytes, 512);
+        assert!(before.symbols > 0);
+
+        globals.set(|| Symbol::intern("a_symbol_that_is_not_preinterned"));
+        let after = usage.stats(&globals);
+        assert_eq!(after.symbols, before.symbols + 1);
+        assert!(after.interner_bytes >= before.interner_bytes);
+        if cfg!(target_os = "linux") {
+            assert!(after.peak_rss.unwrap() > 0);
+        }
+    }
+
+    #[test]
+    fn report_stats() {
+        let stats = MemoryStats {
+            peak_rss: None,
+            ast_arena_bytes: 3 * 1024 * 1024,
+            hir_arena_bytes: 512 * 1024,
+            symbols: 42,
+            interner_bytes: 1024 * 1024,
+        };
+        let report = report(&stats, "memory");
+        let lines = report.lines().map(|line| line.split_whitespace().collect::<Vec<_>>());
+        assert_eq!(
+            lines.collect::<Vec<_>>(),
+            [
+                vec!["memory", "peak", "RSS", "unknown"],
+                vec!["memory", "AST", "arenas", "3.00", "MiB"],
+                vec!["memory", "HIR", "arenas", "0.50", "MiB"],
+                vec!["memory", "interned", "symbols", "42"],
+                vec!["memory", "symbol", "interner", "1.00", "MiB"],
+            ]
+        );
+    }
+}
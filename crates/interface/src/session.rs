@@ -1,9 +1,21 @@
 use crate::{
-    diagnostics::{DiagCtxt, EmittedDiagnostics},
-    ColorChoice, SessionGlobals, SourceMap,
+    diagnostics::{DiagCtxt, DiagnosticBuilder, DiagnosticMessage, EmittedDiagnostics, Emitter},
+    lint::{self, Lint},
+    memory::{self, ArenaUsage},
+    source_map::FileName,
+    timings::{self, Timings},
+    ColorChoice, MemoryStats, PassTiming, SessionGlobals, SourceMap,
+};
+use solar_config::{
+    BytecodeHash, CompilerOutput, CompilerStage, Dump, EvmVersion, Language, Library, LintLevel,
+    OptimizerSettings, Unpretty,
+};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    num::NonZeroUsize,
+    path::PathBuf,
+    sync::Arc,
 };
-use solar_config::{CompilerOutput, CompilerStage, Dump, EvmVersion, Language};
-use std::{collections::BTreeSet, num::NonZeroUsize, path::PathBuf, sync::Arc};
 
 /// Information about the current compiler session.
 #[derive(derive_builder::Builder)]
@@ -25,15 +37,39 @@ pub struct Session {
     /// Source code language.
     #[builder(default)]
     pub language: Language,
+    /// Whether to accept syntax that was removed before Solidity 0.8, to analyze legacy code.
+    ///
+    /// This accepts the `now` global, `throw` statements, `var` declarations, constructors named
+    /// after their contract, and `constant` functions. The types of `var` declarations are not
+    /// inferred, so a warning is emitted for each such declaration statement, and code generation
+    /// is rejected in this mode.
+    #[builder(default)]
+    pub legacy_syntax: bool,
     /// Stop execution after the given compiler stage.
     #[builder(default)]
     pub stop_after: Option<CompilerStage>,
     /// Types of output to emit.
     #[builder(default)]
     pub emit: BTreeSet<CompilerOutput>,
+    /// The metadata hash to append to the bytecode.
+    #[builder(default)]
+    pub bytecode_hash: BytecodeHash,
+    /// Optimizer settings.
+    #[builder(default)]
+    pub optimizer: OptimizerSettings,
+    /// Whether to generate bytecode by assembling the Yul IR, instead of lowering the HIR to EVM
+    /// assembly directly. Implied by enabling the optimizer. See [`Self::codegen_via_ir`].
+    #[builder(default)]
+    pub via_ir: bool,
+    /// The addresses of the libraries to link into the bytecode.
+    #[builder(default)]
+    pub libraries: Vec<Library>,
     /// Output directory.
     #[builder(default)]
     pub out_dir: Option<PathBuf>,
+    /// Directory in which to cache code generation outputs across runs.
+    #[builder(default)]
+    pub cache_dir: Option<PathBuf>,
     /// Internal state to dump to stdout.
     #[builder(default)]
     pub dump: Option<Dump>,
@@ -41,11 +77,40 @@ pub struct Session {
     #[builder(default)]
     pub pretty_json: bool,
     /// Number of threads to use. Already resolved to a non-zero value.
+    ///
+    /// Diagnostics and outputs are produced in the same order regardless of this value.
     #[builder(default = "NonZeroUsize::MIN")]
     pub jobs: NonZeroUsize,
+    /// What to pretty-print to stdout after parsing.
+    #[builder(default)]
+    pub unpretty: Option<Unpretty>,
     /// Whether to emit AST stats.
     #[builder(default)]
     pub ast_stats: bool,
+    /// Whether to generate EVM Object Format (EOF) containers when targeting an EVM version that
+    /// supports them. Unstable.
+    #[builder(default)]
+    pub eof: bool,
+    /// Whether to record the declarations that names refer to during name resolution, for IDE
+    /// queries like go to definition and find all references. Set by the language server.
+    #[builder(default)]
+    pub record_references: bool,
+    /// Whether to record the time spent in compiler passes. See [`Session::time`].
+    #[builder(default)]
+    pub time_passes: bool,
+    /// The recorded pass timings.
+    #[builder(default, setter(skip))]
+    timings: Timings,
+    /// The recorded arena sizes.
+    #[builder(default, setter(skip))]
+    arena_usage: ArenaUsage,
+    /// The session globals, set by [`Session::enter`].
+    #[builder(default, setter(skip))]
+    globals: SessionGlobals,
+    /// The configured levels of lints, by name. Lints that are not present use their default
+    /// level.
+    #[builder(default)]
+    pub lint_levels: BTreeMap<String, LintLevel>,
 }
 
 #[derive(Debug)]
@@ -83,6 +148,28 @@ impl SessionBuilder {
         self.dcx(DiagCtxt::with_buffer_emitter(Some(sm), color_choice))
     }
 
+    /// Sets the diagnostic context to one that stores diagnostics along with their resolved
+    /// locations, which can be retrieved with [`Session::buffered_diagnostics`].
+    #[inline]
+    pub fn with_buffered_emitter(mut self) -> Self {
+        let sm = self.get_source_map();
+        self.dcx(DiagCtxt::with_buffered_emitter(Some(sm)))
+    }
+
+    /// Sets the diagnostic context to one that emits diagnostics with a custom emitter, to route
+    /// them into logging or telemetry for example.
+    ///
+    /// The emitter is created with the source map of the session, which it can use to resolve the
+    /// spans of diagnostics. See [`Emitter`](crate::diagnostics::Emitter) for an example.
+    #[inline]
+    pub fn with_emitter<E: Emitter + Send>(
+        mut self,
+        make_emitter: impl FnOnce(Arc<SourceMap>) -> E,
+    ) -> Self {
+        let sm = self.get_source_map();
+        self.dcx(DiagCtxt::new(Box::new(make_emitter(sm))))
+    }
+
     /// Sets the diagnostic context to a silent emitter.
     #[inline]
     pub fn with_silent_emitter(self, fatal_note: Option<String>) -> Self {
@@ -146,6 +233,15 @@ impl Session {
         self.dcx.emitted_diagnostics()
     }
 
+    /// Returns the diagnostics emitted so far, along with their resolved locations.
+    ///
+    /// Returns `None` if the underlying emitter is not a buffered emitter created with
+    /// [`with_buffered_emitter`](SessionBuilder::with_buffered_emitter).
+    #[inline]
+    pub fn buffered_diagnostics(&self) -> Option<Vec<BufferedDiagnostic>> {
+        self.dcx.buffered_diagnostics()
+    }
+
     /// Returns `Err` with the printed diagnostics if any errors have been emitted.
     ///
     /// Returns `None` if the underlying emitter is not a human buffer emitter created with
@@ -155,6 +251,70 @@ impl Session {
         self.dcx.emitted_errors()
     }
 
+    /// Calls `f`, recording the time it takes as the given compiler pass if
+    /// [`time_passes`](Self::time_passes) is enabled.
+    #[inline]
+    pub fn time<R>(&self, pass: &'static str, f: impl FnOnce() -> R) -> R {
+        self.time_inner(pass, None, f)
+    }
+
+    /// Like [`time`](Self::time), but for a pass run on a single file.
+    #[inline]
+    pub fn time_file<R>(&self, pass: &'static str, file: &FileName, f: impl FnOnce() -> R) -> R {
+        self.time_inner(pass, Some(file), f)
+    }
+
+    fn time_inner<R>(
+        &self,
+        pass: &'static str,
+        file: Option<&FileName>,
+        f: impl FnOnce() -> R,
+    ) -> R {
+        if !self.time_passes {
+            return f();
+        }
+        let start = std::time::Instant::now();
+        let r = f();
+        let duration = start.elapsed();
+        self.timings.record(PassTiming { pass, file: file.cloned(), duration });
+        r
+    }
+
+    /// Returns the pass timings recorded so far, in order.
+    ///
+    /// This is empty unless [`time_passes`](Self::time_passes) is enabled.
+    pub fn pass_timings(&self) -> Vec<PassTiming> {
+        self.timings.get()
+    }
+
+    /// Prints a report of the recorded pass timings to stderr, if
+    /// [`time_passes`](Self::time_passes) is enabled.
+    pub fn print_time_passes(&self) {
+        if self.time_passes {
+            eprint!("{}", timings::report(&self.pass_timings(), "time-passes"));
+        }
+    }
+
+    /// Records the number of bytes allocated in an AST arena.
+    pub fn record_ast_arena_bytes(&self, bytes: usize) {
+        self.arena_usage.record_ast(bytes);
+    }
+
+    /// Records the number of bytes allocated in a HIR arena.
+    pub fn record_hir_arena_bytes(&self, bytes: usize) {
+        self.arena_usage.record_hir(bytes);
+    }
+
+    /// Returns the memory usage statistics of the session so far.
+    pub fn memory_stats(&self) -> MemoryStats {
+        self.arena_usage.stats(&self.globals)
+    }
+
+    /// Prints a report of the memory usage statistics to stderr.
+    pub fn print_memory_stats(&self) {
+        eprint!("{}", memory::report(&self.memory_stats(), "memory"));
+    }
+
     /// Returns a reference to the source map.
     #[inline]
     pub fn source_map(&self) -> &SourceMap {
@@ -173,6 +333,24 @@ impl Session {
         self.stop_after >= Some(stage)
     }
 
+    /// Returns the level of the given lint.
+    #[inline]
+    pub fn lint_level(&self, lint: &Lint) -> LintLevel {
+        self.lint_levels.get(lint.name).copied().unwrap_or(lint.default_level)
+    }
+
+    /// Creates a builder for a diagnostic of the given lint, at the level configured for it.
+    ///
+    /// Diagnostics of allowed lints are not emitted.
+    #[track_caller]
+    pub fn lint(
+        &self,
+        lint: &'static Lint,
+        msg: impl Into<DiagnosticMessage>,
+    ) -> DiagnosticBuilder<'_, ()> {
+        self.dcx.diag(lint::diagnostic_level(self.lint_level(lint)), msg).lint(lint)
+    }
+
     /// Returns `true` if parallelism is not enabled.
     #[inline]
     pub fn is_sequential(&self) -> bool {
@@ -185,6 +363,14 @@ impl Session {
         !self.is_sequential()
     }
 
+    /// Returns `true` if bytecode is generated through the Yul IR.
+    ///
+    /// This is the case if `via_ir` is set, or if the optimizer is enabled, as it optimizes the IR.
+    #[inline]
+    pub fn codegen_via_ir(&self) -> bool {
+        self.via_ir || self.optimizer.enabled
+    }
+
     /// Returns `true` if the given output should be emitted.
     #[inline]
     pub fn do_emit(&self, output: CompilerOutput) -> bool {
@@ -236,25 +422,41 @@ impl Session {
     /// Sets up the thread pool and session globals if they doesn't exist already and then
     /// executes the given closure.
     ///
+    /// The session globals are owned by the session, so that symbols interned in one call remain
+    /// valid in the next, even if it is made from another thread. Independent sessions can be
+    /// entered concurrently from different threads, and one session can be entered while another
+    /// one is: session globals that are already set on the current thread, by another session or
+    /// with [`SessionGlobals::set`], are replaced for the duration of the call.
+    ///
     /// This also calls [`SessionGlobals::with_source_map`].
     #[inline]
     pub fn enter<R: Send>(&self, f: impl FnOnce() -> R + Send) -> R {
-        SessionGlobals::with_or_default(|session_globals| {
+        let reentrant = self.globals.is_current();
+        let run = || {
             SessionGlobals::with_source_map(self.clone_source_map(), || {
-                run_in_thread_pool_with_globals(self.jobs.get(), session_globals, f)
+                run_in_thread_pool_with_globals(self.jobs.get(), &self.globals, reentrant, f)
             })
-        })
+        };
+        if reentrant {
+            run()
+        } else {
+            self.globals.set_replacing(run)
+        }
     }
 }
 
 /// Runs the given closure in a thread pool with the given number of threads.
+///
+/// `reentrant` is `true` if the session globals were already set on the current thread.
 fn run_in_thread_pool_with_globals<R: Send>(
     threads: usize,
     session_globals: &SessionGlobals,
+    reentrant: bool,
     f: impl FnOnce() -> R + Send,
 ) -> R {
-    // Avoid panicking below if this is a recursive call.
-    if rayon::current_thread_index().is_some() {
+    // Avoid panicking below if this is a recursive call. The threads of a pool created by another
+    // session use its globals, so a new pool is needed to run work in parallel.
+    if rayon::current_thread_index().is_some() && (reentrant || threads == 1) {
         return f();
     }
 
@@ -309,6 +511,63 @@ mod tests {
         let _ = Session::builder().with_stderr_emitter().build();
     }
 
+    #[test]
+    fn buffered_emitter() {
+        use crate::{diagnostics::DiagnosticLocation, source_map::FileName, BytePos, Span};
+
+        let sess = Session::builder().with_buffered_emitter().build();
+        assert!(sess.emitted_diagnostics().is_none());
+        sess.enter(|| {
+            let file = sess
+                .source_map()
+                .new_source_file(FileName::Custom("a.sol".into()), || Ok("a\nbcd\n".into()))
+                .unwrap();
+            let lo = file.start_pos + BytePos(3);
+            sess.dcx.err("first").span(Span::new(lo, lo + BytePos(2))).emit();
+            sess.dcx.warn("second").emit();
+        });
+
+        let diagnostics = sess.buffered_diagnostics().unwrap();
+        assert_eq!(diagnostics.len(), 2);
+        assert_eq!(diagnostics[0].diagnostic.label(), "first");
+        assert_eq!(
+            diagnostics[0].location,
+            Some(DiagnosticLocation {
+                file: FileName::Custom("a.sol".into()),
+                line: 2,
+                col: 2,
+                end_line: 2,
+                end_col: 4,
+            })
+        );
+        assert_eq!(diagnostics[1].diagnostic.label(), "second");
+        assert_eq!(diagnostics[1].location, None);
+    }
+
+    #[test]
+    fn custom_emitter() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        struct Counter(Arc<SourceMap>, Arc<AtomicUsize>);
+        impl Emitter for Counter {
+            fn emit_diagnostic(&mut self, _diagnostic: &crate::diagnostics::Diagnostic) {
+                self.1.fetch_add(1, Ordering::Relaxed);
+            }
+
+            fn source_map(&self) -> Option<&Arc<SourceMap>> {
+                Some(&self.0)
+            }
+        }
+
+        let count = Arc::new(AtomicUsize::new(0));
+        let sess = Session::builder().with_emitter(|sm| Counter(sm, count.clone())).build();
+        assert!(std::ptr::eq(sess.source_map(), &*sess.dcx.source_map().unwrap()));
+        sess.dcx.warn("a").emit();
+        sess.dcx.err("b").emit();
+        assert_eq!(count.load(Ordering::Relaxed), 2);
+        assert!(sess.dcx.has_errors().is_err());
+    }
+
     #[test]
     fn empty() {
         let _ = Session::empty(DiagCtxt::with_stderr_emitter(None));
@@ -375,6 +634,56 @@ mod tests {
         assert!(sess.dcx.emitted_errors().unwrap().is_ok());
     }
 
+    #[test]
+    fn enter_reuses_globals() {
+        use crate::Symbol;
+
+        let sess = Session::builder().with_test_emitter().build();
+        let sym = sess.enter(|| Symbol::intern("enter_reuses_globals"));
+        sess.enter(|| assert_eq!(sym.as_str(), "enter_reuses_globals"));
+        std::thread::scope(|s| {
+            s.spawn(|| sess.enter(|| assert_eq!(sym.as_str(), "enter_reuses_globals")));
+        });
+    }
+
+    #[test]
+    fn enter_other_session() {
+        use crate::Symbol;
+
+        let a = Session::builder().with_test_emitter().build();
+        let b = Session::builder().with_test_emitter().build();
+        let sym = b.enter(|| Symbol::intern("enter_other_session"));
+        a.enter(|| {
+            let a_sym = Symbol::intern("a");
+            // The globals of `b` are installed, not reused from `a`.
+            b.enter(|| assert_eq!(sym.as_str(), "enter_other_session"));
+            assert_eq!(a_sym.as_str(), "a");
+            assert!(a.globals.is_current());
+            // Unlike `enter`, `set` does not replace the current globals.
+            let set = std::panic::catch_unwind(|| SessionGlobals::new().set(|| {}));
+            assert!(set.is_err());
+        });
+        assert!(!SessionGlobals::is_set());
+    }
+
+    #[test]
+    fn concurrent_sessions() {
+        use crate::Symbol;
+
+        let sessions = [(); 4].map(|()| Session::builder().with_test_emitter().build());
+        std::thread::scope(|s| {
+            for (i, sess) in sessions.iter().enumerate() {
+                s.spawn(move || {
+                    let name = format!("concurrent_sessions_{i}");
+                    let sym = sess.enter(|| Symbol::intern(&name));
+                    sess.enter(|| assert_eq!(sym.as_str(), name));
+                });
+            }
+        });
+        let counts = sessions.map(|sess| sess.memory_stats().symbols);
+        assert!(counts.iter().all(|&count| count == counts[0]), "{counts:?}");
+    }
+
     #[test]
     fn enter_diags() {
         let sess = Session::builder().with_buffer_emitter(ColorChoice::Never).build();
@@ -1,4 +1,4 @@
-use crate::{pos::RelativeBytePos, BytePos, CharPos};
+use crate::{pos::RelativeBytePos, BytePos, CharPos, Span};
 use std::{
     fmt, io,
     ops::RangeInclusive,
@@ -85,6 +85,11 @@ pub enum FileName {
     Stdin,
     /// Custom sources for explicit parser calls from plugins and drivers.
     Custom(String),
+    /// Sources generated by the compiler or by tools, such as flattened output or synthesized
+    /// code. See [`SourceMap::new_generated_source_file`].
+    ///
+    /// [`SourceMap::new_generated_source_file`]: super::SourceMap::new_generated_source_file
+    Generated(String),
 }
 
 impl PartialEq<Path> for FileName {
@@ -131,6 +136,11 @@ impl FileName {
         Self::Custom(s.into())
     }
 
+    /// Creates a new generated `FileName` from a string.
+    pub fn generated(s: impl Into<String>) -> Self {
+        Self::Generated(s.into())
+    }
+
     /// Displays the filename.
     #[inline]
     pub fn display(&self) -> FileNameDisplay<'_> {
@@ -148,6 +158,7 @@ impl fmt::Display for FileNameDisplay<'_> {
             FileName::Real(path) => path.display().fmt(f),
             FileName::Stdin => f.write_str("<stdin>"),
             FileName::Custom(s) => write!(f, "<{s}>"),
+            FileName::Generated(s) => write!(f, "<generated: {s}>"),
         }
     }
 }
@@ -182,13 +193,26 @@ impl StableSourceFileId {
     }
 }
 
-/// Sum of all file lengths is over [`u32::MAX`].
+/// A file is larger than [`u32::MAX`], or the sum of all file lengths is.
 #[derive(Debug)]
-pub struct OffsetOverflowError(pub(crate) ());
+pub struct OffsetOverflowError(pub(crate) OffsetOverflowKind);
+
+#[derive(Debug)]
+pub(crate) enum OffsetOverflowKind {
+    /// A single file is larger than 4GiB.
+    File,
+    /// The file fits, but not after all the files that were already loaded.
+    Total,
+}
 
 impl fmt::Display for OffsetOverflowError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        f.write_str("files larger than 4GiB are not supported")
+        f.write_str(match self.0 {
+            OffsetOverflowKind::File => "the source file is larger than the supported 4GiB",
+            OffsetOverflowKind::Total => {
+                "the total size of all source files exceeds the supported 4GiB"
+            }
+        })
     }
 }
 
@@ -225,6 +249,8 @@ pub struct SourceFile {
     /// files within the crate graph and for speeding up hashing in incremental
     /// compilation.
     pub stable_id: StableSourceFileId,
+    /// The span of the code that this source was generated from, if it was generated.
+    pub origin: Option<Span>,
 }
 
 impl SourceFile {
@@ -239,7 +265,8 @@ impl SourceFile {
 
         let stable_id = StableSourceFileId::from_filename_in_current_crate(&name);
         let source_len = src.len();
-        let source_len = u32::try_from(source_len).map_err(|_| OffsetOverflowError(()))?;
+        let source_len =
+            u32::try_from(source_len).map_err(|_| OffsetOverflowError(OffsetOverflowKind::File))?;
 
         let (lines, multibyte_chars, non_narrow_chars) = super::analyze::analyze_source_file(&src);
 
@@ -254,6 +281,7 @@ impl SourceFile {
             multibyte_chars,
             non_narrow_chars,
             stable_id,
+            origin: None,
         })
     }
 
@@ -408,6 +436,45 @@ impl SourceFile {
         Some(get_until_newline(&self.src, start, end))
     }
 
+    /// Returns the 0-based line and 0-based column, in UTF-16 code units, of the given position.
+    ///
+    /// This is the position encoding used by default in the Language Server Protocol.
+    pub fn utf16_position(&self, pos: BytePos) -> (usize, usize) {
+        let pos = self.relative_position(pos);
+        let line = self.lookup_line(pos).unwrap_or(0);
+        let line_start = self.lines().get(line).copied().unwrap_or_default();
+        (line, self.utf16_len(line_start, pos))
+    }
+
+    /// Returns the number of UTF-16 code units between the given positions, using the
+    /// pre-computed multi-byte characters instead of re-encoding the source.
+    pub fn utf16_len(&self, lo: RelativeBytePos, hi: RelativeBytePos) -> usize {
+        let start = self.multibyte_chars.partition_point(|mbc| mbc.pos < lo);
+        let end = self.multibyte_chars.partition_point(|mbc| mbc.pos < hi);
+        // Characters of up to 3 bytes are a single code unit, and the others are two.
+        self.multibyte_chars[start..end].iter().fold((hi - lo).to_usize(), |len, mbc| {
+            len - mbc.bytes as usize + if mbc.bytes == 4 { 2 } else { 1 }
+        })
+    }
+
+    /// Returns the position at the given 0-based line and 0-based column, in UTF-16 code units.
+    ///
+    /// Columns past the end of the line are clamped to the end of the line. Returns `None` if the
+    /// line is out of bounds.
+    pub fn position_from_utf16(&self, line: usize, col: usize) -> Option<BytePos> {
+        let line_start = self.line_position(line)?;
+        let mut offset = line_start;
+        let mut units = 0;
+        for c in self.src[line_start..].chars() {
+            if units >= col || c == '\n' {
+                break;
+            }
+            units += c.len_utf16();
+            offset += c.len_utf8();
+        }
+        Some(self.absolute_position(RelativeBytePos::from_usize(offset)))
+    }
+
     /// Returns whether or not the file contains the given `SourceMap` byte
     /// position. The position one past the end of the file is considered to be
     /// contained by the file. This implies that files for which `is_empty`
@@ -417,6 +484,12 @@ impl SourceFile {
         byte_pos >= self.start_pos && byte_pos <= self.end_position()
     }
 
+    /// Returns `true` if this source was generated. See [`FileName::Generated`].
+    #[inline]
+    pub fn is_generated(&self) -> bool {
+        matches!(self.name, FileName::Generated(_))
+    }
+
     #[inline]
     pub fn is_empty(&self) -> bool {
         self.source_len.to_u32() == 0
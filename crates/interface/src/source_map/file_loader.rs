@@ -0,0 +1,123 @@
+//! File loaders.
+
+use normalize_path::NormalizePath;
+use solar_data_structures::{map::FxHashMap, sync::RwLock};
+use std::{
+    io::{self, Read},
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+/// Loads the source code of files for a [`SourceMap`](super::SourceMap).
+///
+/// This is how the [`FileResolver`](super::FileResolver) accesses files while resolving imports,
+/// so that library users can retrieve sources from somewhere other than the file system, such as a
+/// database or the network. See [`SourceMap::set_file_loader`](super::SourceMap::set_file_loader).
+pub trait FileLoader: Send + Sync {
+    /// Returns the canonical name of the file or directory at `path`, or an error if it doesn't
+    /// exist.
+    ///
+    /// Files with the same canonical name are loaded only once.
+    fn canonicalize_path(&self, path: &Path) -> io::Result<PathBuf>;
+
+    /// Returns the source code of the file at `path`.
+    fn load_file(&self, path: &Path) -> io::Result<String>;
+
+    /// Returns the source code read from stdin.
+    fn load_stdin(&self) -> io::Result<String>;
+}
+
+/// The default [`FileLoader`], which reads files from the file system.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct RealFileLoader;
+
+impl FileLoader for RealFileLoader {
+    fn canonicalize_path(&self, path: &Path) -> io::Result<PathBuf> {
+        crate::canonicalize(path)
+    }
+
+    fn load_file(&self, path: &Path) -> io::Result<String> {
+        std::fs::read_to_string(path)
+    }
+
+    fn load_stdin(&self) -> io::Result<String> {
+        let mut src = String::new();
+        io::stdin().read_to_string(&mut src)?;
+        Ok(src)
+    }
+}
+
+/// A [`FileLoader`] that serves sources registered in memory, without ever touching the file
+/// system.
+///
+/// Paths are normalized, so that relative imports between the registered files resolve like they
+/// would on disk. Clones share the same files, so more can be added after the loader has been
+/// [set on the source map](super::SourceMap::set_file_loader).
+///
+/// # Examples
+///
+/// ```
+/// use solar_interface::source_map::{InMemoryFileLoader, SourceMap};
+///
+/// let files = InMemoryFileLoader::new();
+/// files.insert("src/A.sol", "import \"./B.sol\";");
+/// files.insert("src/B.sol", "contract B {}");
+///
+/// let mut source_map = SourceMap::empty();
+/// source_map.set_file_loader(files.clone());
+/// assert!(source_map.load_file("src/B.sol".as_ref()).is_ok());
+/// assert!(source_map.load_file("src/C.sol".as_ref()).is_err());
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct InMemoryFileLoader {
+    files: Arc<RwLock<FxHashMap<PathBuf, Arc<str>>>>,
+}
+
+impl InMemoryFileLoader {
+    /// Creates a new empty loader.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registers the source code of the file at `path`, replacing any previous one.
+    ///
+    /// Files that have already been loaded into a source map are not updated.
+    pub fn insert(&self, path: impl AsRef<Path>, src: impl Into<String>) {
+        let src = Arc::from(src.into());
+        self.files.write().insert(path.as_ref().normalize(), src);
+    }
+
+    /// Removes the file at `path`. Returns `true` if it was registered.
+    pub fn remove(&self, path: impl AsRef<Path>) -> bool {
+        self.files.write().remove(&path.as_ref().normalize()).is_some()
+    }
+
+    /// Returns `true` if there is a file at `path`.
+    pub fn contains(&self, path: impl AsRef<Path>) -> bool {
+        self.files.read().contains_key(&path.as_ref().normalize())
+    }
+}
+
+impl FileLoader for InMemoryFileLoader {
+    fn canonicalize_path(&self, path: &Path) -> io::Result<PathBuf> {
+        let path = path.normalize();
+        // Directories exist if they contain a file.
+        let exists = self.files.read().keys().any(|file| file.starts_with(&path));
+        if exists {
+            Ok(path)
+        } else {
+            Err(io::Error::new(io::ErrorKind::NotFound, "file not found in memory"))
+        }
+    }
+
+    fn load_file(&self, path: &Path) -> io::Result<String> {
+        match self.files.read().get(&path.normalize()) {
+            Some(src) => Ok(src.to_string()),
+            None => Err(io::Error::new(io::ErrorKind::NotFound, "file not found in memory")),
+        }
+    }
+
+    fn load_stdin(&self) -> io::Result<String> {
+        Err(io::Error::new(io::ErrorKind::Unsupported, "stdin is not available in memory"))
+    }
+}
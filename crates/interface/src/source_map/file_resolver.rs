@@ -2,7 +2,7 @@
 //!
 //! Modified from [`solang`](https://github.com/hyperledger/solang/blob/0f032dcec2c6e96797fd66fa0175a02be0aba71c/src/file_resolver.rs).
 
-use super::SourceFile;
+use super::{FileName, SourceFile};
 use crate::SourceMap;
 use itertools::Itertools;
 use normalize_path::NormalizePath;
@@ -26,15 +26,54 @@ pub enum ResolveError {
     MultipleMatches(PathBuf, Vec<Arc<SourceFile>>),
 }
 
+/// Resolves import paths to the canonical name and source code of the imported file.
+///
+/// While a [`FileLoader`](super::FileLoader) only provides the contents of the paths computed by
+/// the [`FileResolver`], an import resolver takes over the resolution itself, for example to look
+/// up imports in a package registry. See [`FileResolver::set_import_resolver`].
+pub trait ImportResolver: Send + Sync {
+    /// Resolves `path`, imported by the file at `parent`, if any.
+    ///
+    /// Returns `None` to fall back to the default resolution, using the import paths and
+    /// remappings.
+    fn resolve_import(
+        &self,
+        path: &Path,
+        parent: Option<&Path>,
+    ) -> io::Result<Option<ResolvedImport>>;
+}
+
+/// A file resolved by an [`ImportResolver`].
+#[derive(Clone, Debug)]
+pub struct ResolvedImport {
+    /// The canonical name of the file.
+    ///
+    /// Files with the same canonical name are loaded only once, and the name is used to resolve
+    /// the relative imports of the file.
+    pub name: PathBuf,
+    /// The source code of the file.
+    pub src: String,
+}
+
+/// Resolves import paths to source files, using the import paths and remappings.
+///
+/// Files are loaded with the [`FileLoader`](super::FileLoader) of the source map, unless an
+/// [`ImportResolver`] is set.
 pub struct FileResolver<'a> {
     source_map: &'a SourceMap,
     import_paths: Vec<(Option<PathBuf>, PathBuf)>,
+    import_resolver: Option<Box<dyn ImportResolver>>,
 }
 
 impl<'a> FileResolver<'a> {
     /// Creates a new file resolver.
     pub fn new(source_map: &'a SourceMap) -> Self {
-        Self { source_map, import_paths: Vec::new() }
+        Self { source_map, import_paths: Vec::new(), import_resolver: None }
+    }
+
+    /// Sets the import resolver, which is tried before the import paths and remappings.
+    pub fn set_import_resolver(&mut self, import_resolver: impl ImportResolver + 'static) {
+        self.import_resolver = Some(Box::new(import_resolver));
     }
 
     /// Returns the source map.
@@ -72,6 +111,11 @@ impl<'a> FileResolver<'a> {
         self.import_paths.as_slice()
     }
 
+    /// Returns the import remappings as `(map, path)` pairs, in insertion order.
+    pub fn import_maps(&self) -> impl Iterator<Item = (&Path, &Path)> {
+        self.import_paths.iter().filter_map(|(map, path)| Some((map.as_deref()?, path.as_path())))
+    }
+
     /// Get the import path corresponding to a map
     pub fn get_import_map(&self, map: &Path) -> Option<&PathBuf> {
         self.import_paths.iter().find(|(m, _)| m.as_deref() == Some(map)).map(|(_, pb)| pb)
@@ -84,6 +128,18 @@ impl<'a> FileResolver<'a> {
         path: &Path,
         parent: Option<&Path>,
     ) -> Result<Arc<SourceFile>, ResolveError> {
+        if let Some(resolver) = &self.import_resolver {
+            let read_err = |e| ResolveError::ReadFile(path.into(), e);
+            if let Some(ResolvedImport { name, src }) =
+                resolver.resolve_import(path, parent).map_err(read_err)?
+            {
+                return self
+                    .source_map()
+                    .new_source_file(FileName::Real(name), || Ok(src))
+                    .map_err(read_err);
+            }
+        }
+
         // https://docs.soliditylang.org/en/latest/path-resolution.html
         // Only when the path starts with ./ or ../ are relative paths considered; this means
         // that `import "b.sol";` will check the import paths for b.sol, while `import "./b.sol";`
@@ -133,6 +189,13 @@ impl<'a> FileResolver<'a> {
             }
         }
 
+        // Finally, look for the file in the `node_modules` directories, like Node.js does.
+        if result.is_empty() && !path.is_absolute() {
+            if let Some(file) = self.try_node_modules(&path, parent)? {
+                result.push(file);
+            }
+        }
+
         match result.len() {
             0 => Err(ResolveError::NotFound(original_path.into())),
             1 => Ok(result.pop().unwrap()),
@@ -155,11 +218,37 @@ impl<'a> FileResolver<'a> {
         remapped
     }
 
+    /// Looks for `path` in the `node_modules` directory of the directory of `parent`, or of the
+    /// current directory if there is no parent, and then of each of its ancestors.
+    fn try_node_modules(
+        &self,
+        path: &Path,
+        parent: Option<&Path>,
+    ) -> Result<Option<Arc<SourceFile>>, ResolveError> {
+        let base = parent.and_then(Path::parent).filter(|base| !base.as_os_str().is_empty());
+        let file_loader = self.source_map().file_loader();
+        let Ok(base) = file_loader.canonicalize_path(base.unwrap_or(Path::new("."))) else {
+            return Ok(None);
+        };
+        for dir in base.ancestors() {
+            if let Some(file) = self.try_file(&dir.join("node_modules").join(path))? {
+                return Ok(Some(file));
+            }
+        }
+        Ok(None)
+    }
+
     /// Loads stdin into the source map.
     pub fn load_stdin(&self) -> Result<Arc<SourceFile>, ResolveError> {
         self.source_map().load_stdin().map_err(ResolveError::ReadStdin)
     }
 
+    /// Loads stdin into the source map as the file at `path`.
+    pub fn load_stdin_as(&self, path: &Path) -> Result<Arc<SourceFile>, ResolveError> {
+        let filename = FileName::Real(path.to_path_buf());
+        self.source_map().load_stdin_as(filename).map_err(ResolveError::ReadStdin)
+    }
+
     /// Loads `path` into the source map. Returns `None` if the file doesn't exist.
     #[instrument(level = "debug", skip_all)]
     pub fn try_file(&self, path: &Path) -> Result<Option<Arc<SourceFile>>, ResolveError> {
@@ -169,7 +258,7 @@ impl<'a> FileResolver<'a> {
             return Ok(Some(file));
         }
 
-        if let Ok(path) = crate::canonicalize(path) {
+        if let Ok(path) = self.source_map().file_loader().canonicalize_path(path) {
             // TODO: avoids loading the same file twice by canonicalizing,
             // and then not displaying the full path in the error message
             let mut path = path.as_path();
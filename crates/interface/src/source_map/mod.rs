@@ -1,12 +1,12 @@
 //! SourceMap related types and operations.
 
-use crate::{BytePos, CharPos, Span};
+use crate::{BytePos, CharPos, RelativeBytePos, Span};
 use solar_data_structures::{
     map::FxBuildHasher,
     sync::{ReadGuard, RwLock},
 };
 use std::{
-    io::{self, Read},
+    io,
     path::{Path, PathBuf},
     sync::Arc,
 };
@@ -16,8 +16,11 @@ mod analyze;
 mod file;
 pub use file::*;
 
+mod file_loader;
+pub use file_loader::{FileLoader, InMemoryFileLoader, RealFileLoader};
+
 mod file_resolver;
-pub use file_resolver::{FileResolver, ResolveError};
+pub use file_resolver::{FileResolver, ImportResolver, ResolveError, ResolvedImport};
 
 #[cfg(test)]
 mod tests;
@@ -64,6 +67,17 @@ pub struct Loc {
     pub col_display: usize,
 }
 
+/// A source code location in UTF-16 code units, as used by the Language Server Protocol.
+#[derive(Clone, Debug)]
+pub struct Utf16Loc {
+    /// Information about the original source.
+    pub file: Arc<SourceFile>,
+    /// The (0-based) line number.
+    pub line: usize,
+    /// The (0-based) column offset, in UTF-16 code units.
+    pub col: usize,
+}
+
 // Used to be structural records.
 #[derive(Debug)]
 pub struct SourceFileAndLine {
@@ -90,16 +104,59 @@ pub struct LineInfo {
     pub end_col: CharPos,
 }
 
+/// Lines of source code surrounding a span. See [`SourceMap::span_to_context`].
+#[derive(Clone, Debug)]
+pub struct SpanContext {
+    /// Information about the original source.
+    pub file: Arc<SourceFile>,
+    /// The (1-based) line number of the first line in `lines`.
+    pub first_line: usize,
+    /// The lines, without their line terminators.
+    pub lines: Vec<String>,
+}
+
 pub struct FileLines {
     pub file: Arc<SourceFile>,
     pub lines: Vec<LineInfo>,
 }
 
+/// Stores all the source files of a session, and maps global [`BytePos`] positions to them.
+///
+/// The source map can be shared between threads behind an [`Arc`]: files can be added and spans
+/// resolved concurrently, and files with the same name are only added once.
 pub struct SourceMap {
     // INVARIANT: The only operation allowed on `source_files` is `push`.
     source_files: RwLock<Vec<Arc<SourceFile>>>,
     stable_id_to_source_file: scc::HashIndex<StableSourceFileId, Arc<SourceFile>, FxBuildHasher>,
     hash_kind: SourceFileHashAlgorithm,
+    file_loader: Box<dyn FileLoader>,
+}
+
+/// Returns the start position of a file of length `len` added after `last_file`.
+///
+/// Returns an error instead of overflowing if the file does not fit in the remaining `BytePos`
+/// space.
+fn next_start_pos(
+    last_file: Option<&SourceFile>,
+    len: RelativeBytePos,
+) -> Result<BytePos, OffsetOverflowError> {
+    let start = match last_file {
+        // Add one so there is some space between files. This lets us distinguish
+        // positions in the `SourceMap`, even in the presence of zero-length files.
+        Some(last_file) => last_file.end_position().0.checked_add(1),
+        None => Some(0),
+    };
+    start
+        .filter(|start| start.checked_add(len.to_u32()).is_some())
+        .map(BytePos)
+        .ok_or(OffsetOverflowError(OffsetOverflowKind::Total))
+}
+
+#[cfg(test)]
+fn _source_map_traits() {
+    fn assert_send_sync<T: Send + Sync>() {}
+    assert_send_sync::<SourceMap>();
+    assert_send_sync::<SourceFile>();
 }
 
 impl Default for SourceMap {
@@ -115,6 +172,7 @@ impl SourceMap {
             source_files: RwLock::new(Vec::new()),
             stable_id_to_source_file: Default::default(),
             hash_kind,
+            file_loader: Box::new(RealFileLoader),
         }
     }
 
@@ -123,19 +181,31 @@ impl SourceMap {
         Self::new(SourceFileHashAlgorithm::default())
     }
 
+    /// Returns the file loader.
+    pub fn file_loader(&self) -> &dyn FileLoader {
+        &*self.file_loader
+    }
+
+    /// Sets the file loader, which is used to load files and stdin. Defaults to [`RealFileLoader`].
+    pub fn set_file_loader(&mut self, file_loader: impl FileLoader + 'static) {
+        self.file_loader = Box::new(file_loader);
+    }
+
     /// Loads a file from the given path.
     pub fn load_file(&self, path: &Path) -> io::Result<Arc<SourceFile>> {
         let filename = path.to_owned().into();
-        self.new_source_file(filename, || std::fs::read_to_string(path))
+        self.new_source_file(filename, || self.file_loader.load_file(path))
     }
 
     /// Loads `stdin`.
     pub fn load_stdin(&self) -> io::Result<Arc<SourceFile>> {
-        self.new_source_file(FileName::Stdin, || {
-            let mut src = String::new();
-            io::stdin().read_to_string(&mut src)?;
-            Ok(src)
-        })
+        self.load_stdin_as(FileName::Stdin)
+    }
+
+    /// Loads `stdin` with the given file name, which is displayed in diagnostics and used to
+    /// resolve relative imports.
+    pub fn load_stdin_as(&self, filename: FileName) -> io::Result<Arc<SourceFile>> {
+        self.new_source_file(filename, || self.file_loader.load_stdin())
     }
 
     /// Loads a file with the given source string.
@@ -150,19 +220,46 @@ impl SourceMap {
     /// If a file already exists in the `SourceMap` with the same ID, that file is returned
     /// unmodified.
     ///
-    /// Returns an error if the file is larger than 4GiB or other errors occur while creating the
-    /// `SourceFile`.
+    /// Returns an error if the file is larger than 4GiB, if it does not fit in the 4GiB shared by
+    /// all the files of the source map, or if other errors occur while creating the `SourceFile`.
     #[instrument(level = "debug", skip_all, fields(filename = %filename.display()))]
     pub fn new_source_file(
         &self,
         filename: FileName,
         get_src: impl FnOnce() -> io::Result<String>,
+    ) -> io::Result<Arc<SourceFile>> {
+        self.new_source_file_with_origin(filename, get_src, None)
+    }
+
+    /// Creates a new generated `SourceFile`, such as flattened output or synthesized code, with
+    /// a [`FileName::Generated`] name.
+    ///
+    /// `origin` is the span of the code that the source was generated from, if any. Diagnostics
+    /// can point at the generated code itself, or at its origin with
+    /// [`original_span`](Self::original_span).
+    ///
+    /// If a generated file with the same name already exists, that file is returned unmodified.
+    pub fn new_generated_source_file(
+        &self,
+        name: impl Into<String>,
+        src: String,
+        origin: Option<Span>,
+    ) -> io::Result<Arc<SourceFile>> {
+        self.new_source_file_with_origin(FileName::Generated(name.into()), || Ok(src), origin)
+    }
+
+    fn new_source_file_with_origin(
+        &self,
+        filename: FileName,
+        get_src: impl FnOnce() -> io::Result<String>,
+        origin: Option<Span>,
     ) -> io::Result<Arc<SourceFile>> {
         let stable_id = StableSourceFileId::from_filename_in_current_crate(&filename);
         match self.stable_id_to_source_file.entry(stable_id) {
             scc::hash_index::Entry::Occupied(entry) => Ok(entry.get().clone()),
             scc::hash_index::Entry::Vacant(entry) => {
-                let file = SourceFile::new(filename, get_src()?, self.hash_kind)?;
+                let mut file = SourceFile::new(filename, get_src()?, self.hash_kind)?;
+                file.origin = origin;
                 let file = self.new_source_file_inner(file, stable_id)?;
                 entry.insert_entry(file.clone());
                 Ok(file)
@@ -183,13 +280,7 @@ impl SourceMap {
 
         let mut source_files = self.source_files.write();
 
-        file.start_pos = BytePos(if let Some(last_file) = source_files.last() {
-            // Add one so there is some space between files. This lets us distinguish
-            // positions in the `SourceMap`, even in the presence of zero-length files.
-            last_file.end_position().0.checked_add(1).ok_or(OffsetOverflowError(()))?
-        } else {
-            0
-        });
+        file.start_pos = next_start_pos(source_files.last().map(|f| &**f), file.source_len)?;
 
         let file = Arc::new(file);
         source_files.push(file.clone());
@@ -230,7 +321,25 @@ impl SourceMap {
         f.lookup_line(lo) != f.lookup_line(hi)
     }
 
+    /// Returns the span of the code that the given span was generated from, following the origins
+    /// of generated sources, or the span itself if it is not in a generated source with an origin.
+    pub fn original_span(&self, mut span: Span) -> Span {
+        while !span.is_dummy() && !self.files().is_empty() {
+            let file = self.lookup_source_file(span.lo());
+            // Origins are always in files that were added before, so this terminates.
+            match file.origin {
+                Some(origin) if file.is_generated() && origin.hi() < file.start_pos => {
+                    span = origin
+                }
+                _ => break,
+            }
+        }
+        span
+    }
+
     /// Returns the source snippet as `String` corresponding to the given `Span`.
+    ///
+    /// Returns an error if the span is not valid, or if it spans multiple files.
     pub fn span_to_snippet(&self, span: Span) -> Result<String, SpanSnippetError> {
         self.span_to_source(span, |src, start_index, end_index| {
             src.get(start_index..end_index)
@@ -239,6 +348,45 @@ impl SourceMap {
         })
     }
 
+    /// Extends the given span to the start of its first line and to the end of its last line,
+    /// excluding the line terminator.
+    ///
+    /// Returns the span unchanged if it is not valid.
+    pub fn span_extend_to_line(&self, sp: Span) -> Span {
+        let extended = self.span_to_source(sp, |src, start_index, end_index| {
+            let start = src[..start_index].rfind('\n').map_or(0, |i| i + 1);
+            let end = src[end_index..].find('\n').map_or(src.len(), |i| end_index + i);
+            Ok((start_index - start, end - end_index))
+        });
+        match extended {
+            Ok((before, after)) => Span::new(
+                sp.lo() - BytePos::from_usize(before),
+                sp.hi() + BytePos::from_usize(after),
+            ),
+            Err(_) => sp,
+        }
+    }
+
+    /// Returns the lines of the given span, along with up to `context` lines before and after
+    /// it.
+    ///
+    /// This can be used to render custom annotated snippets. Returns an error if the span is not
+    /// valid, or if it spans multiple files.
+    pub fn span_to_context(
+        &self,
+        sp: Span,
+        context: usize,
+    ) -> Result<SpanContext, SpanSnippetError> {
+        self.span_to_source(sp, |_, _, _| Ok(()))?;
+        let lo = self.lookup_char_pos(sp.lo());
+        let hi = self.lookup_char_pos(sp.hi());
+        let first = lo.line.saturating_sub(1).saturating_sub(context);
+        let last =
+            (hi.line.saturating_sub(1) + context).min(lo.file.count_lines().saturating_sub(1));
+        let lines = (first..=last).filter_map(|i| lo.file.get_line(i)).map(String::from).collect();
+        Ok(SpanContext { file: lo.file, first_line: first + 1, lines })
+    }
+
     /// For a global `BytePos`, computes the local offset within the containing `SourceFile`.
     pub fn lookup_byte_offset(&self, bpos: BytePos) -> SourceFileAndBytePos {
         let idx = self.lookup_source_file_idx(bpos);
@@ -267,6 +415,33 @@ impl SourceMap {
         Loc { file: sf, line, col, col_display }
     }
 
+    /// Looks up the location of a `BytePos` in UTF-16 code units.
+    pub fn lookup_utf16_pos(&self, pos: BytePos) -> Utf16Loc {
+        let sf = self.lookup_source_file(pos);
+        let (line, col) = sf.utf16_position(pos);
+        Utf16Loc { file: sf, line, col }
+    }
+
+    /// Looks up the locations of the start and end of a span in UTF-16 code units.
+    pub fn span_to_utf16(&self, sp: Span) -> Result<(Utf16Loc, Utf16Loc), SpanLinesError> {
+        self.is_valid_span(sp)?;
+        Ok((self.lookup_utf16_pos(sp.lo()), self.lookup_utf16_pos(sp.hi())))
+    }
+
+    /// Returns the position at the given 0-based line and 0-based column, in UTF-16 code units, in
+    /// the file with the given name.
+    ///
+    /// Returns `None` if the file is not in the source map or if the line is out of bounds. See
+    /// [`SourceFile::position_from_utf16`].
+    pub fn utf16_to_byte_pos(
+        &self,
+        filename: &FileName,
+        line: usize,
+        col: usize,
+    ) -> Option<BytePos> {
+        self.source_file_by_file_name(filename)?.position_from_utf16(line, col)
+    }
+
     /// If the corresponding `SourceFile` is empty, does not return a line number.
     pub fn lookup_line(&self, pos: BytePos) -> Result<SourceFileAndLine, Arc<SourceFile>> {
         let f = self.lookup_source_file(pos);
@@ -182,6 +182,34 @@ fn span_to_snippet_and_lines_spanning_multiple_lines() {
     assert_eq!(lines.lines, expected);
 }
 
+#[test]
+fn span_extend_to_line_and_context() {
+    let sm = SourceMap::empty();
+    let inputtext = "aaaaa\nbbbbBB\nCCC\nDDDDDddddd\neee\n";
+    let selection = "     \n    ~~\n~~~\n~~~~~     \n   \n";
+    sm.new_dummy_source_file(Path::new("blork.rs").to_owned(), inputtext.to_string()).unwrap();
+    let span = span_from_selection(inputtext, selection);
+
+    let line = sm.span_extend_to_line(span);
+    assert_eq!(sm.span_to_snippet(line).unwrap(), "bbbbBB\nCCC\nDDDDDddddd");
+    assert_eq!(sm.span_extend_to_line(line), line);
+
+    let context = sm.span_to_context(span, 0).unwrap();
+    assert_eq!(context.first_line, 2);
+    assert_eq!(context.lines, ["bbbbBB", "CCC", "DDDDDddddd"]);
+    let context = sm.span_to_context(span, 1).unwrap();
+    assert_eq!(context.first_line, 1);
+    assert_eq!(context.lines, ["aaaaa", "bbbbBB", "CCC", "DDDDDddddd", "eee"]);
+    let context = sm.span_to_context(span, 10).unwrap();
+    assert_eq!(context.first_line, 1);
+    assert_eq!(context.lines.len(), 6);
+
+    let sm = init_source_map();
+    let span = Span::new(BytePos(2), BytePos(25));
+    assert_eq!(sm.span_extend_to_line(span), span);
+    assert!(sm.span_to_context(span, 0).is_err());
+}
+
 /// Test span_to_snippet for a span ending at the end of a `SourceFile`.
 #[test]
 fn t8() {
@@ -625,3 +653,254 @@ fn read_binary_file_handles_lying_stat() {
     let bin = RealFileLoader.read_binary_file(kernel_max).unwrap();
     assert_eq!(&real[..], &bin[..]);
 }
+
+#[test]
+fn utf16_positions() {
+    let sm = SourceMap::empty();
+    let file = sm
+        .new_dummy_source_file(PathBuf::from("utf16.sol"), "a\n\u{1F600}b\u{e9}c".to_string())
+        .unwrap();
+    let pos = |offset: u32| file.start_pos + offset;
+
+    assert_eq!(file.utf16_position(pos(0)), (0, 0));
+    assert_eq!(file.utf16_position(pos(2)), (1, 0));
+    // The emoji is 4 bytes in UTF-8 and 2 code units in UTF-16.
+    assert_eq!(file.utf16_position(pos(6)), (1, 2));
+    assert_eq!(file.utf16_position(pos(9)), (1, 4));
+
+    assert_eq!(file.position_from_utf16(1, 2), Some(pos(6)));
+    assert_eq!(file.position_from_utf16(1, 4), Some(pos(9)));
+    assert_eq!(file.position_from_utf16(0, 10), Some(pos(1)));
+    assert_eq!(file.position_from_utf16(2, 0), None);
+}
+
+#[test]
+fn utf16_source_map() {
+    let sm = init_source_map();
+    let name = FileName::Real(PathBuf::from("utf16.sol"));
+    let src = "// \u{e9}\u{4e2d}\u{1F600}\nx \u{1F600} y\n".to_string();
+    let file = sm.new_source_file(name.clone(), || Ok(src.clone())).unwrap();
+    let pos = |s: &str| file.start_pos + BytePos::from_usize(src.find(s).unwrap());
+
+    // `é` and `中` are one code unit each, and the emoji is two.
+    let loc = sm.lookup_utf16_pos(pos("\n"));
+    assert!(Arc::ptr_eq(&loc.file, &file));
+    assert_eq!((loc.line, loc.col), (0, 7));
+    assert_eq!(file.utf16_len(RelativeBytePos(0), file.source_len), 15);
+
+    let (lo, hi) = sm.span_to_utf16(Span::new(pos("x"), pos("y"))).unwrap();
+    assert_eq!((lo.line, lo.col, hi.line, hi.col), (1, 0, 1, 5));
+    assert!(sm.span_to_utf16(Span::new(BytePos(0), pos("y"))).is_err());
+
+    assert_eq!(sm.utf16_to_byte_pos(&name, 1, 5), Some(pos("y")));
+    assert_eq!(sm.utf16_to_byte_pos(&name, 3, 0), None);
+    assert_eq!(sm.utf16_to_byte_pos(&FileName::Real(PathBuf::from("missing.sol")), 0, 0), None);
+}
+
+#[test]
+fn generated_files() {
+    let sm = init_source_map();
+    let real = sm.files()[0].clone();
+    let origin = Span::new(real.start_pos, real.start_pos + BytePos(5));
+    let getter = sm.new_generated_source_file("getter", "function f() {}".into(), Some(origin));
+    let getter = getter.unwrap();
+    assert!(getter.is_generated() && !real.is_generated());
+    assert_eq!(getter.name.display().to_string(), "<generated: getter>");
+    let same = sm.new_generated_source_file("getter", String::new(), None).unwrap();
+    assert!(Arc::ptr_eq(&getter, &same));
+
+    let span = Span::new(getter.start_pos + BytePos(9), getter.start_pos + BytePos(10));
+    assert_eq!(sm.span_to_snippet(span).unwrap(), "f");
+    assert_eq!(sm.original_span(span), origin);
+    assert_eq!(sm.span_to_snippet(sm.original_span(span)).unwrap(), "first");
+
+    // Origins are followed transitively.
+    let nested = sm.new_generated_source_file("nested", "x".into(), Some(span)).unwrap();
+    let nested_span = Span::new(nested.start_pos, nested.end_position());
+    assert_eq!(sm.original_span(nested_span), origin);
+
+    let flattened = sm.new_generated_source_file("flattened", "y".into(), None).unwrap();
+    let flattened_span = Span::new(flattened.start_pos, flattened.end_position());
+    assert_eq!(sm.original_span(flattened_span), flattened_span);
+    assert_eq!(sm.original_span(origin), origin);
+}
+
+#[test]
+fn offset_overflow() {
+    let len = |n: u32| RelativeBytePos(n);
+    assert_eq!(next_start_pos(None, len(u32::MAX)).unwrap(), BytePos(0));
+
+    let mut file =
+        SourceFile::new(FileName::custom("a"), "abc".into(), SourceFileHashAlgorithm::None)
+            .unwrap();
+    file.start_pos = BytePos(u32::MAX - 5);
+    assert_eq!(next_start_pos(Some(&file), len(1)).unwrap(), BytePos(u32::MAX - 1));
+    let err = next_start_pos(Some(&file), len(2)).unwrap_err();
+    let err = io::Error::from(err);
+    assert_eq!(err.kind(), io::ErrorKind::FileTooLarge);
+    assert!(err.to_string().contains("total size of all source files"), "{err}");
+
+    file.start_pos = BytePos(u32::MAX - 3);
+    assert!(next_start_pos(Some(&file), len(0)).is_err());
+
+    let err = OffsetOverflowError(OffsetOverflowKind::File);
+    assert_eq!(err.to_string(), "the source file is larger than the supported 4GiB");
+}
+
+#[test]
+fn concurrent_files() {
+    let sm = Arc::new(SourceMap::empty());
+    let files = std::thread::scope(|s| {
+        let handles = (0..8)
+            .map(|i| {
+                let sm = sm.clone();
+                s.spawn(move || {
+                    // Every other thread adds the same file.
+                    let name = PathBuf::from(format!("{}.sol", i % 4));
+                    let file = sm.new_dummy_source_file(name, format!("contract C{}", i % 4));
+                    let file = file.unwrap();
+                    let loc = sm.lookup_char_pos(file.start_pos + BytePos(9));
+                    assert!(Arc::ptr_eq(&loc.file, &file));
+                    assert_eq!((loc.line, loc.col), (1, CharPos(9)));
+                    file
+                })
+            })
+            .collect::<Vec<_>>();
+        handles.into_iter().map(|h| h.join().unwrap()).collect::<Vec<_>>()
+    });
+
+    assert_eq!(sm.files().len(), 4);
+    for (i, file) in files.iter().enumerate() {
+        assert!(Arc::ptr_eq(file, &files[i % 4]));
+        let span = Span::new(file.start_pos, file.end_position());
+        assert_eq!(sm.span_to_snippet(span).unwrap(), format!("contract C{}", i % 4));
+    }
+}
+
+#[test]
+fn node_modules() {
+    let dir = tempfile::tempdir().unwrap();
+    let root = dir.path();
+    let lib = root.join("node_modules/@scope/pkg/Lib.sol");
+    std::fs::create_dir_all(lib.parent().unwrap()).unwrap();
+    std::fs::write(&lib, "").unwrap();
+    let parent = root.join("contracts/nested/A.sol");
+    std::fs::create_dir_all(parent.parent().unwrap()).unwrap();
+    std::fs::write(&parent, "").unwrap();
+
+    let sm = SourceMap::empty();
+    let resolver = FileResolver::new(&sm);
+    let file = resolver.resolve_file(Path::new("@scope/pkg/Lib.sol"), Some(&parent)).unwrap();
+    assert_eq!(file.name, FileName::Real(crate::canonicalize(&lib).unwrap()));
+    let missing = resolver.resolve_file(Path::new("@scope/pkg/Missing.sol"), Some(&parent));
+    assert!(matches!(missing, Err(ResolveError::NotFound(_))));
+}
+
+#[test]
+fn file_loader() {
+    struct Loader;
+    impl FileLoader for Loader {
+        fn canonicalize_path(&self, path: &Path) -> io::Result<PathBuf> {
+            match path.to_str() {
+                Some("lib/A.sol" | "./lib/A.sol") => Ok(PathBuf::from("db://A.sol")),
+                _ => Err(io::ErrorKind::NotFound.into()),
+            }
+        }
+        fn load_file(&self, path: &Path) -> io::Result<String> {
+            match path.to_str() {
+                Some("db://A.sol") => Ok("contract A {}".into()),
+                _ => Err(io::ErrorKind::NotFound.into()),
+            }
+        }
+        fn load_stdin(&self) -> io::Result<String> {
+            Ok("contract Stdin {}".into())
+        }
+    }
+
+    let mut sm = SourceMap::empty();
+    sm.set_file_loader(Loader);
+    let mut resolver = FileResolver::new(&sm);
+    resolver.add_import_path(PathBuf::from("lib"));
+    let file = resolver.resolve_file(Path::new("A.sol"), Some(Path::new("src/B.sol"))).unwrap();
+    assert_eq!(file.name, FileName::Real(PathBuf::from("db://A.sol")));
+    assert_eq!(*file.src, "contract A {}");
+    assert!(resolver.resolve_file(Path::new("B.sol"), None).is_err());
+    assert_eq!(*sm.load_stdin().unwrap().src, "contract Stdin {}");
+}
+
+#[test]
+fn import_resolver() {
+    struct Registry;
+    impl ImportResolver for Registry {
+        fn resolve_import(
+            &self,
+            path: &Path,
+            _parent: Option<&Path>,
+        ) -> io::Result<Option<ResolvedImport>> {
+            match path.to_str() {
+                Some("registry:A.sol") => Ok(Some(ResolvedImport {
+                    name: PathBuf::from("registry/A/1.0.0/A.sol"),
+                    src: "contract A {}".into(),
+                })),
+                Some("registry:Missing.sol") => Err(io::ErrorKind::NotFound.into()),
+                _ => Ok(None),
+            }
+        }
+    }
+
+    let files = InMemoryFileLoader::new();
+    files.insert("src/B.sol", "contract B {}");
+    let mut sm = SourceMap::empty();
+    sm.set_file_loader(files);
+    let mut resolver = FileResolver::new(&sm);
+    resolver.set_import_resolver(Registry);
+
+    let file = resolver.resolve_file(Path::new("registry:A.sol"), Some(Path::new("src/B.sol")));
+    let file = file.unwrap();
+    assert_eq!(file.name, FileName::Real(PathBuf::from("registry/A/1.0.0/A.sol")));
+    assert_eq!(*file.src, "contract A {}");
+    // Files with the same canonical name are loaded only once.
+    let again = resolver.resolve_file(Path::new("registry:A.sol"), None).unwrap();
+    assert!(Arc::ptr_eq(&file, &again));
+
+    // Other paths fall back to the default resolution.
+    let file = resolver.resolve_file(Path::new("src/B.sol"), None).unwrap();
+    assert_eq!(*file.src, "contract B {}");
+    let missing = resolver.resolve_file(Path::new("registry:Missing.sol"), None);
+    assert!(matches!(missing, Err(ResolveError::ReadFile(..))));
+}
+
+#[test]
+fn in_memory_files() {
+    let files = InMemoryFileLoader::new();
+    files.insert("./src/A.sol", "import \"./nested/B.sol\";");
+    files.insert("src/nested/B.sol", "import \"../../lib/C.sol\";");
+    files.insert("lib/C.sol", "");
+    files.insert("node_modules/@scope/D.sol", "");
+
+    let mut sm = SourceMap::empty();
+    sm.set_file_loader(files.clone());
+    let resolver = FileResolver::new(&sm);
+    let resolve = |path: &str, parent: Option<&str>| {
+        let file = resolver.resolve_file(Path::new(path), parent.map(Path::new))?;
+        Ok::<_, ResolveError>(file.name.clone())
+    };
+    let real = |path: &str| FileName::Real(PathBuf::from(path));
+
+    assert_eq!(resolve("src/A.sol", None).unwrap(), real("src/A.sol"));
+    assert_eq!(resolve("./nested/B.sol", Some("src/A.sol")).unwrap(), real("src/nested/B.sol"));
+    assert_eq!(resolve("../../lib/C.sol", Some("src/nested/B.sol")).unwrap(), real("lib/C.sol"));
+    assert_eq!(
+        resolve("@scope/D.sol", Some("src/A.sol")).unwrap(),
+        real("node_modules/@scope/D.sol")
+    );
+    // Files on disk are not visible.
+    assert!(resolve("Cargo.toml", None).is_err());
+    assert!(sm.load_stdin().is_err());
+
+    files.insert("src/E.sol", "contract E {}");
+    assert!(files.contains("src/E.sol"));
+    assert_eq!(*sm.load_file(Path::new("src/E.sol")).unwrap().src, "contract E {}");
+    assert!(files.remove("src/E.sol"));
+    assert!(!files.contains("src/E.sol"));
+}
@@ -124,12 +124,63 @@ impl Span {
         self.lo() <= other.lo() && other.hi() <= self.hi()
     }
 
+    /// Returns `true` if `pos` is in `self`, excluding `self.hi()`.
+    #[inline]
+    pub fn contains_pos(self, pos: BytePos) -> bool {
+        self.lo() <= pos && pos < self.hi()
+    }
+
     /// Returns `true` if `self` touches `other`.
     #[inline]
     pub fn overlaps(self, other: Self) -> bool {
         self.lo() < other.hi() && other.lo() < self.hi()
     }
 
+    /// Returns the part of `self` that is also in `other`, or `None` if they are disjoint.
+    ///
+    /// Spans that only touch at one position intersect in an empty span.
+    ///
+    /// ```text
+    ///     ________________
+    ///     lorem ipsum dolor sit
+    ///                 _________
+    ///                 ^^^^^
+    /// ```
+    #[inline]
+    pub fn intersect(self, other: Self) -> Option<Self> {
+        let lo = cmp::max(self.lo(), other.lo());
+        let hi = cmp::min(self.hi(), other.hi());
+        (lo <= hi).then(|| Self::new(lo, hi))
+    }
+
+    /// Returns `self` with the part that ends at the end of `other` trimmed from its start, or
+    /// `None` if `other` ends at or after the end of `self`.
+    ///
+    /// ```text
+    ///     _____
+    ///     lorem ipsum dolor
+    ///     _________________
+    ///          ^^^^^^^^^^^^
+    /// ```
+    #[inline]
+    pub fn trim_start(self, other: Self) -> Option<Self> {
+        (self.hi() > other.hi()).then(|| self.with_lo(cmp::max(self.lo(), other.hi())))
+    }
+
+    /// Returns `self` with the part that starts at the start of `other` trimmed from its end, or
+    /// `None` if `other` starts at or before the start of `self`.
+    ///
+    /// ```text
+    ///                 _____
+    ///     lorem ipsum dolor
+    ///     _________________
+    ///     ^^^^^^^^^^^^
+    /// ```
+    #[inline]
+    pub fn trim_end(self, other: Self) -> Option<Self> {
+        (self.lo() < other.lo()).then(|| self.with_hi(cmp::min(self.hi(), other.lo())))
+    }
+
     /// Returns `true` if `self` and `other` are equal.
     #[inline]
     pub fn is_empty(self, other: Self) -> bool {
@@ -185,3 +236,55 @@ impl Span {
         Self::new(self.lo(), end.lo())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn span(lo: u32, hi: u32) -> Span {
+        Span::new(BytePos(lo), BytePos(hi))
+    }
+
+    #[test]
+    fn containment() {
+        assert!(span(2, 8).contains(span(2, 8)));
+        assert!(span(2, 8).contains(span(3, 5)));
+        assert!(!span(2, 8).contains(span(1, 5)));
+
+        assert!(span(2, 8).contains_pos(BytePos(2)));
+        assert!(span(2, 8).contains_pos(BytePos(7)));
+        assert!(!span(2, 8).contains_pos(BytePos(8)));
+        assert!(!span(2, 2).contains_pos(BytePos(2)));
+    }
+
+    #[test]
+    fn overlap() {
+        assert!(span(2, 8).overlaps(span(7, 10)));
+        assert!(!span(2, 8).overlaps(span(8, 10)));
+
+        assert_eq!(span(2, 8).intersect(span(5, 10)), Some(span(5, 8)));
+        assert_eq!(span(2, 8).intersect(span(3, 4)), Some(span(3, 4)));
+        assert_eq!(span(2, 8).intersect(span(8, 10)), Some(span(8, 8)));
+        assert_eq!(span(2, 8).intersect(span(9, 10)), None);
+    }
+
+    #[test]
+    fn trim() {
+        assert_eq!(span(2, 8).trim_start(span(0, 5)), Some(span(5, 8)));
+        assert_eq!(span(2, 8).trim_start(span(0, 1)), Some(span(2, 8)));
+        assert_eq!(span(2, 8).trim_start(span(0, 8)), None);
+
+        assert_eq!(span(2, 8).trim_end(span(5, 10)), Some(span(2, 5)));
+        assert_eq!(span(2, 8).trim_end(span(9, 10)), Some(span(2, 8)));
+        assert_eq!(span(2, 8).trim_end(span(2, 10)), None);
+    }
+
+    #[test]
+    fn split_and_join() {
+        assert_eq!(span(2, 8).split_at(2), (span(2, 4), span(4, 8)));
+        assert_eq!(span(2, 4).to(span(6, 8)), span(2, 8));
+        assert_eq!(span(6, 8).to(span(2, 4)), span(2, 8));
+        assert_eq!(span(2, 4).between(span(6, 8)), span(4, 6));
+        assert_eq!(span(2, 4).until(span(6, 8)), span(2, 6));
+    }
+}
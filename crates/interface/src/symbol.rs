@@ -379,6 +379,16 @@ impl Interner {
     fn get(&self, symbol: Symbol) -> &str {
         self.0.get(symbol)
     }
+
+    /// Returns the number of interned symbols.
+    pub(crate) fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    /// Returns the number of bytes used by the interned strings.
+    pub(crate) fn memory_usage(&self) -> usize {
+        self.0.memory_usage()
+    }
 }
 
 // TODO: We could finalize the interner after parsing to a `RodeoResolver`, making it read-only.
@@ -412,6 +422,14 @@ impl LassoInterner {
     fn get(&self, symbol: Symbol) -> &str {
         self.0.resolve(&symbol)
     }
+
+    fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    fn memory_usage(&self) -> usize {
+        self.0.current_memory_usage()
+    }
 }
 
 unsafe impl lasso::Key for Symbol {
@@ -845,6 +863,8 @@ symbols! {
     // There is currently no checking that all symbols are used; that would be
     // nice to have.
     Symbols {
+        ABIEncoderV2,
+        SMTChecker,
         X,
         __tmp_struct,
         abi,
@@ -856,6 +876,9 @@ symbols! {
         concat,
         creationCode,
         data,
+        datacopy,
+        dataoffset,
+        datasize,
         decode,
         ecrecover,
         encode,
@@ -869,22 +892,32 @@ symbols! {
         gasleft,
         global,
         interfaceId,
+        key,
         length,
+        linkersymbol,
+        loadimmutable,
         max,
+        memoryguard,
         min,
         msg,
         name,
+        now,
         object,
+        offset,
         push,
         require,
         ripemd160,
         runtimeCode,
+        salt,
         selector,
         send,
         sender,
+        setimmutable,
         sha256,
         sig,
+        slot,
         solidity,
+        suicide,
         super_: "super",
         this,
         transfer,
@@ -892,6 +925,8 @@ symbols! {
         tx,
         underscore: "_",
         unwrap,
+        v1,
+        v2,
         value,
         wrap,
         x,
@@ -930,4 +965,22 @@ mod tests {
             assert_eq!(Ident::DUMMY.to_string(), "");
         });
     }
+    #[test]
+    fn preinterned() {
+        crate::enter(|| {
+            for (s, sym) in [
+                ("msg", sym::msg),
+                ("selector", sym::selector),
+                ("datasize", sym::datasize),
+                ("ABIEncoderV2", sym::ABIEncoderV2),
+                ("keccak256", kw::Keccak256),
+                ("pop", kw::Pop),
+            ] {
+                assert_eq!(Symbol::intern(s), sym);
+                assert_eq!(sym.as_str(), s);
+                assert!(sym.is_preinterned());
+            }
+            assert!(!Symbol::intern("not_a_builtin").is_preinterned());
+        });
+    }
 }
@@ -0,0 +1,111 @@
+//! Timing of compiler passes, reported with `-Ztime-passes`.
+
+use crate::source_map::FileName;
+use solar_data_structures::sync::Lock;
+use std::{fmt::Write, time::Duration};
+
+/// The time spent in a compiler pass, or in a pass on a single file.
+#[derive(Clone, Debug)]
+pub struct PassTiming {
+    /// The name of the pass.
+    pub pass: &'static str,
+    /// The file that the pass was run on, if it was run on a single file.
+    pub file: Option<FileName>,
+    /// The time spent in the pass.
+    pub duration: Duration,
+}
+
+/// Collects [`PassTiming`]s.
+#[derive(Default)]
+pub(crate) struct Timings {
+    timings: Lock<Vec<PassTiming>>,
+}
+
+impl Timings {
+    pub(crate) fn record(&self, timing: PassTiming) {
+        self.timings.lock().push(timing);
+    }
+
+    pub(crate) fn get(&self) -> Vec<PassTiming> {
+        self.timings.lock().clone()
+    }
+}
+
+/// Formats the given timings as a report, in which passes are listed in the order they were first
+/// run, each followed by its per-file timings from slowest to fastest.
+///
+/// The time of a pass is the sum of its recorded times, or of its per-file times if it was only
+/// timed per file.
+pub(crate) fn report(timings: &[PassTiming], prefix: &str) -> String {
+    let mut passes = Vec::<&'static str>::new();
+    for timing in timings {
+        if !passes.contains(&timing.pass) {
+            passes.push(timing.pass);
+        }
+    }
+
+    let mut out = String::new();
+    let _ = writeln!(out, "{prefix} {:<40} {:>12}", "Pass", "Time");
+    let _ = writeln!(out, "{prefix} {}", "-".repeat(53));
+    let mut total = Duration::ZERO;
+    for pass in passes {
+        let (whole, mut files): (Vec<_>, Vec<_>) =
+            timings.iter().filter(|t| t.pass == pass).partition(|t| t.file.is_none());
+        let pass_total = if whole.is_empty() {
+            files.iter().map(|t| t.duration).sum()
+        } else {
+            whole.iter().map(|t| t.duration).sum()
+        };
+        total += pass_total;
+        let _ = writeln!(out, "{prefix} {pass:<40} {:>12}", fmt_duration(pass_total));
+        files.sort_by(|a, b| b.duration.cmp(&a.duration));
+        for timing in files {
+            let name = timing.file.as_ref().unwrap().display().to_string();
+            let _ = writeln!(out, "{prefix} - {name:<38} {:>12}", fmt_duration(timing.duration));
+        }
+    }
+    let _ = writeln!(out, "{prefix} {}", "-".repeat(53));
+    let _ = writeln!(out, "{prefix} {:<40} {:>12}", "Total", fmt_duration(total));
+    out
+}
+
+fn fmt_duration(duration: Duration) -> String {
+    format!("{:.3}ms", duration.as_secs_f64() * 1000.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn report_passes() {
+        let timing = |pass, file: Option<&str>, ms| PassTiming {
+            pass,
+            file: file.map(|f| FileName::Real(f.into())),
+            duration: Duration::from_millis(ms),
+        };
+        let timings = [
+            timing("parse", Some("a.sol"), 1),
+            timing("parse", Some("b.sol"), 2),
+            timing("parse", None, 4),
+            timing("typeck", None, 8),
+            timing("parse", None, 1),
+        ];
+        let report = report(&timings, "time");
+        let lines = report.lines().map(|line| line.split_whitespace().collect::<Vec<_>>());
+        let rule = "-".repeat(53);
+        assert_eq!(
+            lines.collect::<Vec<_>>(),
+            [
+                vec!["time", "Pass", "Time"],
+                vec!["time", rule.as_str()],
+                vec!["time", "parse", "5.000ms"],
+                vec!["time", "-", "b.sol", "2.000ms"],
+                vec!["time", "-", "a.sol", "1.000ms"],
+                vec!["time", "typeck", "8.000ms"],
+                vec!["time", rule.as_str()],
+                vec!["time", "Total", "13.000ms"],
+            ]
+        );
+    }
+}
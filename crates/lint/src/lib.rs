@@ -0,0 +1,105 @@
+#![doc = include_str!("../README.md")]
+#![doc(
+    html_logo_url = "https://raw.githubusercontent.com/paradigmxyz/solar/main/assets/logo.png",
+    html_favicon_url = "https://raw.githubusercontent.com/paradigmxyz/solar/main/assets/favicon.ico"
+)]
+#![cfg_attr(docsrs, feature(doc_cfg, doc_auto_cfg))]
+
+use solar_interface::{lint::normalize_name, Session};
+use solar_sema::{ast, ty::Gcx, Linter};
+
+pub use solar_interface::lint::{Lint, LintLevel};
+
+mod passes;
+
+/// A lint pass, which checks the code for a set of lints.
+///
+/// To add a lint, declare it with [`declare_lint!`](solar_interface::declare_lint) and register
+/// the pass that emits it in [`PASSES`].
+#[derive(Clone, Copy, Debug)]
+pub struct LintPass {
+    /// The name of the pass.
+    pub name: &'static str,
+    /// The lints emitted by the pass.
+    pub lints: &'static [&'static Lint],
+    /// The representation that the pass checks.
+    pub kind: LintPassKind,
+}
+
+/// The representation that a [`LintPass`] checks.
+#[derive(Clone, Copy, Debug)]
+pub enum LintPassKind {
+    /// Checks the AST of each source, before it is lowered.
+    Ast(for<'sess, 'ast> fn(&'sess Session, &ast::SourceUnit<'ast>)),
+    /// Checks the HIR, once it has been type checked without errors.
+    Hir(for<'gcx> fn(Gcx<'gcx>)),
+}
+
+impl LintPass {
+    /// Returns `true` if any of the lints of this pass is not allowed in the given session.
+    pub fn is_enabled(&self, sess: &Session) -> bool {
+        self.lints.iter().any(|lint| sess.lint_level(lint) != LintLevel::Allow)
+    }
+}
+
+/// All the registered lint passes.
+pub static PASSES: &[LintPass] = passes::PASSES;
+
+/// The linter that runs the enabled [`PASSES`]. See [`ParsingContext::linter`].
+///
+/// [`ParsingContext::linter`]: solar_sema::ParsingContext::linter
+pub const LINTER: Linter = Linter { check_ast, check_hir };
+
+/// Returns an iterator over all the lints, including the ones emitted during semantic analysis.
+pub fn lints() -> impl Iterator<Item = &'static Lint> {
+    let passes = PASSES.iter().flat_map(|pass| pass.lints.iter().copied());
+    solar_sema::lints::LINTS.iter().copied().chain(passes)
+}
+
+/// Returns the lint with the given name, if any. Underscores are accepted in place of dashes.
+pub fn find_lint(name: &str) -> Option<&'static Lint> {
+    let name = normalize_name(name);
+    lints().find(|lint| lint.name == name)
+}
+
+fn check_ast(sess: &Session, ast: &ast::SourceUnit<'_>) {
+    for pass in PASSES {
+        if let LintPassKind::Ast(check) = pass.kind {
+            if pass.is_enabled(sess) {
+                check(sess, ast);
+            }
+        }
+    }
+}
+
+fn check_hir(gcx: Gcx<'_>) {
+    for pass in PASSES {
+        if let LintPassKind::Hir(check) = pass.kind {
+            if pass.is_enabled(gcx.sess) {
+                check(gcx);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+
+    #[test]
+    fn unique_names() {
+        let mut names = HashSet::new();
+        for lint in lints() {
+            assert!(names.insert(lint.name), "duplicate lint `{}`", lint.name);
+            assert_eq!(normalize_name(lint.name), lint.name, "lint names must be kebab-case");
+        }
+    }
+
+    #[test]
+    fn find() {
+        assert_eq!(find_lint("unused-variables").map(|lint| lint.name), Some("unused-variables"));
+        assert_eq!(find_lint("UNUSED_VARIABLES").map(|lint| lint.name), Some("unused-variables"));
+        assert!(find_lint("unknown").is_none());
+    }
+}
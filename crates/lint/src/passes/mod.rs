@@ -0,0 +1,19 @@
+//! The registered lint passes.
+
+use crate::{LintPass, LintPassKind};
+
+mod naming;
+mod unicode;
+
+pub(crate) static PASSES: &[LintPass] = &[
+    LintPass {
+        name: "naming",
+        lints: &[&naming::NAMING_CONVENTION],
+        kind: LintPassKind::Ast(naming::check),
+    },
+    LintPass {
+        name: "unicode",
+        lints: &[&unicode::MISLEADING_UNICODE],
+        kind: LintPassKind::Ast(unicode::check),
+    },
+];
@@ -0,0 +1,92 @@
+//! Naming conventions from the Solidity style guide.
+
+use solar_ast::{self as ast, FunctionKind, ItemKind};
+use solar_interface::{declare_lint, Ident, Session};
+
+declare_lint! {
+    /// Contracts, structs, enums, user-defined value types, events and errors should be named in
+    /// `CapWords`, and functions and modifiers in `mixedCase`.
+    pub NAMING_CONVENTION,
+    "naming-convention",
+    Allow,
+    "detects names that do not follow the naming conventions of the style guide"
+}
+
+pub(super) fn check(sess: &Session, ast: &ast::SourceUnit<'_>) {
+    check_items(sess, ast.items.iter());
+}
+
+fn check_items<'a, 'ast: 'a>(sess: &Session, items: impl Iterator<Item = &'a ast::Item<'ast>>) {
+    for item in items {
+        match &item.kind {
+            ItemKind::Contract(contract) => {
+                check_cap_words(sess, contract.kind.to_str(), contract.name);
+                check_items(sess, contract.body.iter());
+            }
+            ItemKind::Function(function) => {
+                let kind = match function.kind {
+                    FunctionKind::Function => "function",
+                    FunctionKind::Modifier => "modifier",
+                    _ => continue,
+                };
+                if let Some(name) = function.header.name {
+                    if !is_mixed_case(name.as_str()) {
+                        emit(sess, kind, name, "mixedCase");
+                    }
+                }
+            }
+            ItemKind::Struct(item) => check_cap_words(sess, "struct", item.name),
+            ItemKind::Enum(item) => check_cap_words(sess, "enum", item.name),
+            ItemKind::Udvt(item) => check_cap_words(sess, "type", item.name),
+            ItemKind::Event(item) => check_cap_words(sess, "event", item.name),
+            ItemKind::Error(item) => check_cap_words(sess, "error", item.name),
+            ItemKind::Pragma(_)
+            | ItemKind::Import(_)
+            | ItemKind::Using(_)
+            | ItemKind::Variable(_) => {}
+        }
+    }
+}
+
+fn check_cap_words(sess: &Session, kind: &str, name: Ident) {
+    if !is_cap_words(name.as_str()) {
+        emit(sess, kind, name, "CapWords");
+    }
+}
+
+fn emit(sess: &Session, kind: &str, name: Ident, case: &str) {
+    sess.lint(&NAMING_CONVENTION, format!("{kind} name `{name}` should be in {case}"))
+        .span(name.span)
+        .emit();
+}
+
+/// Returns `true` if `s` is in `mixedCase`, ignoring leading and trailing underscores.
+fn is_mixed_case(s: &str) -> bool {
+    let s = s.trim_matches('_');
+    !s.starts_with(|c: char| c.is_ascii_uppercase()) && !s.contains('_')
+}
+
+/// Returns `true` if `s` is in `CapWords`, ignoring leading and trailing underscores.
+fn is_cap_words(s: &str) -> bool {
+    let s = s.trim_matches('_');
+    !s.starts_with(|c: char| c.is_ascii_lowercase()) && !s.contains('_')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cases() {
+        assert!(is_mixed_case("transferFrom"));
+        assert!(is_mixed_case("_transfer"));
+        assert!(is_mixed_case("x"));
+        assert!(!is_mixed_case("TransferFrom"));
+        assert!(!is_mixed_case("transfer_from"));
+
+        assert!(is_cap_words("ERC20"));
+        assert!(is_cap_words("Ownable"));
+        assert!(!is_cap_words("ownable"));
+        assert!(!is_cap_words("Safe_Math"));
+    }
+}
@@ -0,0 +1,126 @@
+//! Unicode codepoints that make source code display differently from how it is compiled.
+//!
+//! Reference: <https://trojansource.codes>
+
+use solar_ast as ast;
+use solar_interface::{declare_lint, BytePos, Session, Span};
+
+declare_lint! {
+    /// Bidirectional text control codepoints, which can reorder how the surrounding code is
+    /// displayed, and invisible codepoints, which can hide text. Both can make code appear to do
+    /// something other than what it does, as in "Trojan Source" attacks.
+    ///
+    /// Identifiers cannot contain these, nor any other non-ASCII characters, so they can only
+    /// appear in comments and string literals.
+    pub MISLEADING_UNICODE,
+    "misleading-unicode",
+    Warn,
+    "detects bidirectional text control and invisible Unicode codepoints"
+}
+
+/// The codepoints detected by [`MISLEADING_UNICODE`], with their names.
+static CODEPOINTS: &[(char, &str)] = &[
+    ('\u{061C}', "ARABIC LETTER MARK"),
+    ('\u{200B}', "ZERO WIDTH SPACE"),
+    ('\u{200C}', "ZERO WIDTH NON-JOINER"),
+    ('\u{200D}', "ZERO WIDTH JOINER"),
+    ('\u{200E}', "LEFT-TO-RIGHT MARK"),
+    ('\u{200F}', "RIGHT-TO-LEFT MARK"),
+    ('\u{202A}', "LEFT-TO-RIGHT EMBEDDING"),
+    ('\u{202B}', "RIGHT-TO-LEFT EMBEDDING"),
+    ('\u{202C}', "POP DIRECTIONAL FORMATTING"),
+    ('\u{202D}', "LEFT-TO-RIGHT OVERRIDE"),
+    ('\u{202E}', "RIGHT-TO-LEFT OVERRIDE"),
+    ('\u{2060}', "WORD JOINER"),
+    ('\u{2066}', "LEFT-TO-RIGHT ISOLATE"),
+    ('\u{2067}', "RIGHT-TO-LEFT ISOLATE"),
+    ('\u{2068}', "FIRST STRONG ISOLATE"),
+    ('\u{2069}', "POP DIRECTIONAL ISOLATE"),
+    ('\u{FEFF}', "ZERO WIDTH NO-BREAK SPACE"),
+];
+
+pub(super) fn check(sess: &Session, ast: &ast::SourceUnit<'_>) {
+    // Sources without items cannot contain code that is displayed misleadingly.
+    let Some(item) = ast.items.first() else { return };
+    let file = sess.source_map().lookup_source_file(item.span.lo());
+    let mut line_start = 0;
+    for line in file.src.split_inclusive('\n') {
+        let offset = line_start;
+        line_start += line.len();
+        let mut found = find_codepoints(line);
+        if offset == 0 {
+            // Byte order mark.
+            found.retain(|&(i, c, _)| !(i == 0 && c == '\u{FEFF}'));
+        }
+        if found.is_empty() {
+            continue;
+        }
+
+        let span = |i: usize, c: char| {
+            let pos = |i: usize| file.start_pos + BytePos::from_usize(offset + i);
+            Span::new(pos(i), pos(i + c.len_utf8()))
+        };
+        let spans = found.iter().map(|&(i, c, _)| span(i, c)).collect::<Vec<_>>();
+        let msg = "this line contains Unicode codepoints that change how it is displayed";
+        let mut diag = sess.lint(&MISLEADING_UNICODE, msg).span(spans);
+        for &(i, c, name) in &found {
+            diag = diag.span_label(span(i, c), format!("`{}` {name}", c.escape_unicode()));
+        }
+        diag.note(format!("with the codepoints escaped, this line reads: `{}`", escape(line)))
+            .help("if their presence was not intentional, remove them")
+            .emit();
+    }
+}
+
+/// Returns the byte offsets, characters and names of the [`CODEPOINTS`] in `line`.
+fn find_codepoints(line: &str) -> Vec<(usize, char, &'static str)> {
+    line.char_indices()
+        .filter_map(|(i, c)| {
+            let &(_, name) = CODEPOINTS.iter().find(|&&(cp, _)| cp == c)?;
+            Some((i, c, name))
+        })
+        .collect()
+}
+
+/// Escapes the [`CODEPOINTS`] in `line`, and trims it.
+fn escape(line: &str) -> String {
+    line.trim()
+        .chars()
+        .map(|c| {
+            if CODEPOINTS.iter().any(|&(cp, _)| cp == c) {
+                c.escape_unicode().to_string()
+            } else {
+                c.to_string()
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn codepoints() {
+        let line = "/* \u{202E} } \u{2066} if (isAdmin) \u{2069} \u{2066} begin admins only */";
+        let found = find_codepoints(line);
+        let names = found.iter().map(|&(_, _, name)| name).collect::<Vec<_>>();
+        assert_eq!(
+            names,
+            [
+                "RIGHT-TO-LEFT OVERRIDE",
+                "LEFT-TO-RIGHT ISOLATE",
+                "POP DIRECTIONAL ISOLATE",
+                "LEFT-TO-RIGHT ISOLATE",
+            ]
+        );
+        assert_eq!(found[0].0, 3);
+        assert_eq!(
+            escape(line),
+            r"/* \u{202e} } \u{2066} if (isAdmin) \u{2069} \u{2066} begin admins only */"
+        );
+
+        assert_eq!(find_codepoints("string s = \"a\u{200B}b\";").len(), 1);
+        assert!(find_codepoints("string s = unicode\"héllo 😃\";").is_empty());
+    }
+}
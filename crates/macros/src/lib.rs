@@ -9,6 +9,7 @@
 use proc_macro::TokenStream;
 use syn::parse_macro_input;
 
+mod quote_sol;
 mod symbols;
 mod visitor;
 
@@ -27,8 +28,43 @@ pub fn symbols(input: TokenStream) -> TokenStream {
 /// Then `walk_` functions are generated for each `visit_` function with the same signature and
 /// block and the `visit_` functions are made to call the `walk_` functions by default.
 ///
-/// `walk_` functions should not be overridden.
+/// `walk_` functions should not be overridden. They are also generated as free functions taking
+/// the visitor as the first argument, like `walk_item(visitor, item)` and
+/// `walk_item_mut(visitor, item)`.
+///
+/// All the `visit_` and `walk_` functions return a `ControlFlow<Self::BreakValue>`, so a traversal
+/// can stop early by returning `ControlFlow::Break` and propagating it with `?`. Visitors that never
+/// break should use `Never` as the break value.
 #[proc_macro]
 pub fn declare_visitors(input: TokenStream) -> TokenStream {
     parse_macro_input!(input as visitor::Input).expand().into()
 }
+
+/// Parses a Solidity snippet into an AST node.
+///
+/// ```ignore
+/// let expr = quote_sol!(&sess, &arena, expr => #lhs + #(amount * 2) * 1 ether)?;
+/// ```
+///
+/// The first two arguments are the [`Session`] and the AST arena, and the kind of node to parse
+/// follows: `file`, `item`, `stmt`, `expr`, `ty`, or `yul` for a Yul statement. The snippet must
+/// consist of valid Rust tokens, so Rust comments are ignored and string literals with prefixes,
+/// like `hex"..."`, are not supported.
+///
+/// `#name` and `#(expr)` interpolate the [`Display`](std::fmt::Display) output of a Rust
+/// expression, such as an identifier, a string or another AST node, into the source. Interpolated
+/// values are inserted as-is, so they should be parenthesized in the snippet if precedence matters.
+///
+/// The snippet is converted to a format string at compile time, but it is parsed at runtime with
+/// `solar_parse::Parser::parse_snippet`, since the parser depends on this crate. `solar_parse`
+/// must be a dependency of the calling crate. Parse errors are emitted and returned as an
+/// `ErrorGuaranteed`.
+///
+/// [`Session`]: https://docs.rs/solar-interface/latest/solar_interface/struct.Session.html
+#[proc_macro]
+pub fn quote_sol(input: TokenStream) -> TokenStream {
+    parse_macro_input!(input as quote_sol::Input)
+        .expand()
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
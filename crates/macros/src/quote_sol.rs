@@ -0,0 +1,138 @@
+use proc_macro2::{Delimiter, Spacing, TokenStream, TokenTree};
+use quote::quote;
+use syn::{
+    parse::{Parse, ParseStream},
+    Expr, Ident, Token,
+};
+
+pub struct Input {
+    sess: Expr,
+    arena: Expr,
+    kind: Ident,
+    tokens: TokenStream,
+}
+
+impl Parse for Input {
+    fn parse(input: ParseStream<'_>) -> syn::Result<Self> {
+        let sess = input.parse()?;
+        input.parse::<Token![,]>()?;
+        let arena = input.parse()?;
+        input.parse::<Token![,]>()?;
+        let kind = input.parse()?;
+        input.parse::<Token![=>]>()?;
+        let tokens = input.parse()?;
+        Ok(Self { sess, arena, kind, tokens })
+    }
+}
+
+impl Input {
+    pub fn expand(self) -> syn::Result<TokenStream> {
+        let Self { sess, arena, kind, tokens } = self;
+        let parse = match kind.to_string().as_str() {
+            "file" => quote!(parser.parse_file()),
+            "item" => quote! {
+                match parser.parse_item()? {
+                    Some(item) => Ok(item),
+                    None => Err(parser.unexpected_error()),
+                }
+            },
+            "stmt" => quote!(parser.parse_stmt()),
+            "expr" => quote!(parser.parse_expr()),
+            "ty" => quote!(parser.parse_type()),
+            "yul" => quote!(parser.parse_yul_stmt()),
+            _ => {
+                let msg = "expected one of `file`, `item`, `stmt`, `expr`, `ty` or `yul`";
+                return Err(syn::Error::new(kind.span(), msg));
+            }
+        };
+        let mut source = Source::default();
+        source.push_tokens(tokens)?;
+        let Source { fmt, args } = source;
+        Ok(quote! {
+            ::solar_parse::Parser::parse_snippet(
+                #sess,
+                #arena,
+                ::std::format!(#fmt #(, #args)*),
+                |parser| #parse,
+            )
+        })
+    }
+}
+
+/// The Solidity source of a snippet, as a format string and its interpolated arguments.
+#[derive(Default)]
+struct Source {
+    fmt: String,
+    args: Vec<TokenStream>,
+}
+
+impl Source {
+    fn push_tokens(&mut self, tokens: TokenStream) -> syn::Result<()> {
+        let mut tokens = tokens.into_iter();
+        while let Some(tt) = tokens.next() {
+            match tt {
+                TokenTree::Punct(punct) if punct.as_char() == '#' => match tokens.next() {
+                    Some(TokenTree::Ident(ident)) => self.push_arg(quote!(#ident)),
+                    Some(TokenTree::Group(group))
+                        if group.delimiter() == Delimiter::Parenthesis =>
+                    {
+                        self.push_arg(group.stream())
+                    }
+                    tt => {
+                        let span = tt.map_or_else(|| punct.span(), |tt| tt.span());
+                        let msg = "expected an identifier or a parenthesized expression after `#`";
+                        return Err(syn::Error::new(span, msg));
+                    }
+                },
+                TokenTree::Punct(punct) => {
+                    self.fmt.push(punct.as_char());
+                    if punct.spacing() == Spacing::Alone {
+                        self.fmt.push(' ');
+                    }
+                }
+                TokenTree::Group(group) => {
+                    let (open, close) = match group.delimiter() {
+                        Delimiter::Parenthesis => ("(", ")"),
+                        Delimiter::Brace => ("{{", "}}"),
+                        Delimiter::Bracket => ("[", "]"),
+                        Delimiter::None => ("", ""),
+                    };
+                    self.fmt.push_str(open);
+                    self.fmt.push(' ');
+                    self.push_tokens(group.stream())?;
+                    self.fmt.push_str(close);
+                    self.fmt.push(' ');
+                }
+                TokenTree::Ident(ident) => {
+                    self.fmt.push_str(&ident.to_string());
+                    self.fmt.push(' ');
+                }
+                TokenTree::Literal(lit) => {
+                    self.fmt.push_str(&lit.to_string().replace('{', "{{").replace('}', "}}"));
+                    self.fmt.push(' ');
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn push_arg(&mut self, arg: TokenStream) {
+        self.fmt.push_str("{} ");
+        self.args.push(arg);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn source() {
+        let mut source = Source::default();
+        let tokens = r#"function #name(uint a) { a += #(x + 1) * 2; return "{}"; }"#;
+        source.push_tokens(tokens.parse().unwrap()).unwrap();
+        assert_eq!(source.fmt, "function {} ( uint a ) {{ a += {} * 2 ; return \"{{}}\" ; }} ");
+        let args = source.args.iter().map(ToString::to_string).collect::<Vec<_>>();
+        assert_eq!(args, ["name", "x + 1"]);
+    }
+}
@@ -1,5 +1,5 @@
 use proc_macro2::{Group, TokenStream, TokenTree};
-use quote::{quote, TokenStreamExt};
+use quote::{quote, ToTokens, TokenStreamExt};
 use syn::{
     braced, parse::Parse, Attribute, Block, FnArg, Generics, Ident, Pat, Stmt, Token, TraitItem,
     Visibility,
@@ -67,6 +67,9 @@ impl Input {
         add_walk_fns(&mut mut_trait_items);
         add_walk_fns(&mut nonmut_trait_items);
 
+        let nonmut_walk_fns = free_walk_fns(vis, name, generics, &nonmut_trait_items);
+        let mut_walk_fns = free_walk_fns(vis, mut_name, generics, &mut_trait_items);
+
         quote! {
             #(#attrs)*
             #vis #trait_token #name #generics {
@@ -77,6 +80,9 @@ impl Input {
             #vis #trait_token #mut_name #generics {
                 #(#mut_trait_items)*
             }
+
+            #nonmut_walk_fns
+            #mut_walk_fns
         }
     }
 }
@@ -206,3 +212,63 @@ fn add_walk_fns(items: &mut Vec<TraitItem>) {
         }
     }
 }
+
+// fn walk_...(&mut self, args...) -> R
+// -> fn walk_...<V: Trait + ?Sized>(visitor: &mut V, args...) -> R { visitor.walk_...(args...) }
+fn free_walk_fns(
+    vis: &Visibility,
+    trait_name: &Ident,
+    generics: &Generics,
+    items: &[TraitItem],
+) -> TokenStream {
+    let params = generics.params.iter();
+    let params = quote!(#(#params,)*);
+    let (_, ty_generics, _) = generics.split_for_impl();
+    let fns = items.iter().filter_map(|item| {
+        let TraitItem::Fn(f) = item else { return None };
+        let name = &f.sig.ident;
+        if !name.to_string().starts_with("walk_") {
+            return None;
+        }
+        let mut args = Vec::new();
+        let mut inputs = Vec::new();
+        for arg in &f.sig.inputs {
+            let FnArg::Typed(pat) = arg else { continue };
+            let Pat::Ident(ident) = &*pat.pat else { return None };
+            let id = &ident.ident;
+            let ty = replace_self(pat.ty.to_token_stream());
+            args.push(quote!(#id));
+            inputs.push(quote!(#id: #ty));
+        }
+        let output = replace_self(f.sig.output.to_token_stream());
+        let doc = format!("Calls [`{trait_name}::{name}`].");
+        Some(quote! {
+            #[doc = #doc]
+            #[inline]
+            #vis fn #name<#params V: #trait_name #ty_generics + ?Sized>(
+                visitor: &mut V,
+                #(#inputs),*
+            ) #output {
+                visitor.#name(#(#args),*)
+            }
+        })
+    });
+    quote!(#(#fns)*)
+}
+
+/// Replaces `Self` with `V`.
+fn replace_self(tts: TokenStream) -> TokenStream {
+    tts.into_iter()
+        .map(|tt| match tt {
+            TokenTree::Group(group) => {
+                let mut new = Group::new(group.delimiter(), replace_self(group.stream()));
+                new.set_span(group.span());
+                TokenTree::Group(new)
+            }
+            TokenTree::Ident(ident) if ident == "Self" => {
+                TokenTree::Ident(Ident::new("V", ident.span()))
+            }
+            tt => tt,
+        })
+        .collect()
+}
@@ -0,0 +1,230 @@
+//! Solidity source code formatting, using the [AST pretty-printer](solar_ast::pretty).
+
+use crate::{Lexer, Parser};
+use solar_ast::{
+    pretty::{self, Comment, Comments, FormatConfig},
+    trivia::{Trivia, TriviaKind},
+    Arena,
+};
+use solar_interface::{
+    source_map::{FileName, SourceFile},
+    Result, Session,
+};
+
+/// Collects the comments of a source file.
+pub fn gather_comments<'src>(sess: &Session, file: &'src SourceFile) -> Comments<'src> {
+    let (_, trivia) = Lexer::from_source_file(sess, file).into_tokens_with_trivia();
+    comments(file, &trivia)
+}
+
+fn comments<'src>(file: &'src SourceFile, trivia: &Trivia) -> Comments<'src> {
+    let comments = trivia
+        .pieces()
+        .iter()
+        .filter_map(|piece| match piece.kind {
+            TriviaKind::Comment { kind, is_doc } => {
+                Some(Comment { span: piece.span, kind, is_doc })
+            }
+            TriviaKind::Whitespace { .. } => None,
+        })
+        .collect();
+    Comments::new(&file.src, file.start_pos, comments)
+}
+
+/// Formats a source file, preserving its comments.
+///
+/// Returns an error if the file cannot be parsed. The formatted source is parsed again to make sure
+/// that it is still valid.
+pub fn format_file(sess: &Session, file: &SourceFile, config: &FormatConfig) -> Result<String> {
+    let errors = sess.dcx.err_count();
+    let arena = Arena::new();
+    let (mut parser, trivia) = Parser::from_source_file_with_trivia(sess, &arena, file);
+    let source_unit = parser.parse_file().map_err(|e| e.emit())?;
+    if sess.dcx.err_count() > errors {
+        return Err(sess.dcx.err(format!("could not format {}", file.name.display())).emit());
+    }
+    let comments = comments(file, &trivia);
+    let formatted = pretty::print_source_unit(&source_unit, Some(&comments), config);
+
+    let (parsed, diagnostics) = sess.dcx.capture(|| {
+        let name = FileName::Custom(format!("{} (formatted)", file.name.display()));
+        let Ok(file) = sess.source_map().new_source_file(name, || Ok(formatted.clone())) else {
+            return false;
+        };
+        let arena = Arena::new();
+        let mut parser = Parser::from_source_file(sess, &arena, &file);
+        parser.parse_file().map_err(|e| e.cancel()).is_ok()
+    });
+    if !parsed || diagnostics.iter().any(|d| d.is_error()) {
+        let msg =
+            format!("formatting {} produced invalid code; this is a bug", file.name.display());
+        return Err(sess.dcx.err(msg).emit());
+    }
+    Ok(formatted)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use solar_ast::{yul, ItemKind, StmtKind};
+
+    /// Calls `f` with a test session and a source file containing `src`.
+    fn with_file<R: Send>(src: &str, f: impl FnOnce(&Session, &SourceFile) -> R + Send) -> R {
+        let sess = Session::builder().with_test_emitter().build();
+        sess.enter(|| {
+            let name = FileName::Custom("test.sol".into());
+            let file = sess.source_map().new_source_file(name, || Ok(src.into())).unwrap();
+            f(&sess, &file)
+        })
+    }
+
+    fn format(src: &str, config: &FormatConfig) -> String {
+        with_file(src, |sess, file| format_file(sess, file, config).unwrap())
+    }
+
+    #[test]
+    fn format_source() {
+        let src = "\
+// SPDX-License-Identifier: MIT
+pragma   solidity ^0.8.0;
+import {A,B as C} from 'a.sol';
+
+
+/// A contract.
+contract   C is A , B(1) {
+    uint constant  X=1 ; // The value.
+    mapping(address=>uint) balances;
+    enum E {A, B}
+    function f(uint a,uint b) public view returns(uint){
+        if(a>b) return a; else { return b; }
+        /* The end. */
+    }
+    event Ev(address indexed a);
+}
+";
+        let expected = "\
+// SPDX-License-Identifier: MIT
+pragma solidity ^0.8.0;
+import {A, B as C} from 'a.sol';
+
+/// A contract.
+contract C is A, B(1) {
+    uint constant X = 1; // The value.
+    mapping(address => uint) balances;
+    enum E {
+        A,
+        B
+    }
+    function f(uint a, uint b) public view returns (uint) {
+        if (a > b) return a;
+        else {
+            return b;
+        }
+        /* The end. */
+    }
+    event Ev(address indexed a);
+}
+";
+        let config = FormatConfig::default();
+        assert_eq!(format(src, &config), expected);
+        assert_eq!(format(expected, &config), expected);
+    }
+
+    #[test]
+    fn display() {
+        let src = "contract C{uint x=1+2*3; function f()public{ x+=1; }}";
+        with_file(src, |sess, file| {
+            let arena = Arena::new();
+            let mut parser = Parser::from_source_file(sess, &arena, file);
+            let source_unit = parser.parse_file().map_err(|e| e.emit()).unwrap();
+            let expected = "\
+contract C {
+    uint x = 1 + 2 * 3;
+
+    function f() public {
+        x += 1;
+    }
+}";
+            assert_eq!(source_unit.to_string(), expected);
+
+            let ItemKind::Contract(contract) = &source_unit.items.raw[0].kind else { panic!() };
+            let ItemKind::Variable(var) = &contract.body[0].kind else { panic!() };
+            assert_eq!(var.to_string(), "uint x = 1 + 2 * 3");
+            assert_eq!(var.initializer.as_ref().unwrap().to_string(), "1 + 2 * 3");
+            assert_eq!(var.ty.to_string(), "uint");
+            assert_eq!(contract.body[1].to_string(), "function f() public {\n    x += 1;\n}");
+        });
+    }
+
+    #[test]
+    fn display_nodes() {
+        let src = "\
+pragma solidity ^0.8.0;
+import {A as B} from 'a.sol';
+contract C is D(1) {
+    struct S { uint a; }
+    enum E { X, Y }
+    error Err(uint);
+    function f() public m(2) {
+        try this.f() {} catch {}
+        assembly { switch x case 0 {} default {} function g(a) -> b {} }
+    }
+}";
+        with_file(src, |sess, file| {
+            let arena = Arena::new();
+            let mut parser = Parser::from_source_file(sess, &arena, file);
+            let source_unit = parser.parse_file().map_err(|e| e.emit()).unwrap();
+            let items = &source_unit.items.raw;
+            let ItemKind::Pragma(pragma) = &items[0].kind else { panic!() };
+            assert_eq!(pragma.to_string(), "pragma solidity ^0.8.0;");
+            let ItemKind::Import(import) = &items[1].kind else { panic!() };
+            assert_eq!(import.to_string(), "import {A as B} from \"a.sol\";");
+            assert_eq!(import.path.to_string(), "\"a.sol\"");
+
+            let ItemKind::Contract(contract) = &items[2].kind else { panic!() };
+            assert_eq!(contract.bases[0].to_string(), "D(1)");
+            let ItemKind::Struct(strukt) = &contract.body[0].kind else { panic!() };
+            assert_eq!(strukt.to_string(), "struct S {\n    uint a;\n}");
+            let ItemKind::Enum(enumm) = &contract.body[1].kind else { panic!() };
+            assert_eq!(enumm.to_string(), "enum E {\n    X,\n    Y\n}");
+            let ItemKind::Error(error) = &contract.body[2].kind else { panic!() };
+            assert_eq!(error.to_string(), "error Err(uint);");
+
+            let ItemKind::Function(function) = &contract.body[3].kind else { panic!() };
+            assert_eq!(function.header.modifiers[0].to_string(), "m(2)");
+            let body = function.body.as_ref().unwrap();
+            let StmtKind::Try(try_) = &body[0].kind else { panic!() };
+            assert_eq!(try_.to_string(), "try this.f() {} catch {}");
+            let StmtKind::Assembly(assembly) = &body[1].kind else { panic!() };
+            let yul::StmtKind::Switch(switch) = &assembly.block[0].kind else { panic!() };
+            assert_eq!(switch.to_string(), "switch x\ncase 0 {}\ndefault {}");
+            let yul::StmtKind::FunctionDef(yul_function) = &assembly.block[1].kind else {
+                panic!()
+            };
+            assert_eq!(yul_function.to_string(), "function g(a) -> b {}");
+        });
+    }
+
+    #[test]
+    fn format_options() {
+        let src = "contract C { function f(uint256 a, int b) { g({x: a}); } }";
+        let config = FormatConfig {
+            line_width: 30,
+            use_tabs: true,
+            bracket_spacing: true,
+            int_types: solar_interface::config::IntTypes::Long,
+            ..Default::default()
+        };
+        let expected = "\
+contract C {
+\tfunction f(
+\t\tuint256 a,
+\t\tint256 b
+\t) {
+\t\tg({ x: a });
+\t}
+}
+";
+        assert_eq!(format(src, &config), expected);
+    }
+}
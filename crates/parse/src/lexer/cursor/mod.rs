@@ -3,6 +3,7 @@
 //! Modified from Rust's [`rustc_lexer`](https://github.com/rust-lang/rust/blob/45749b21b7fd836f6c4f11dd40376f7c83e2791b/compiler/rustc_lexer/src/lib.rs).
 
 use solar_ast::Base;
+use solar_interface::{BytePos, Span};
 use std::str::Chars;
 
 pub mod token;
@@ -365,6 +366,12 @@ impl<'a> Cursor<'a> {
         self.eat_decimal_digits()
     }
 
+    /// Returns an iterator over the remaining tokens and their spans, with the first token starting
+    /// at `start_pos`.
+    pub fn spanned(self, start_pos: BytePos) -> SpannedCursor<'a> {
+        SpannedCursor { cursor: self, pos: start_pos }
+    }
+
     /// Returns the remaining input as a string slice.
     pub fn as_str(&self) -> &'a str {
         self.chars.as_str()
@@ -458,3 +465,32 @@ impl Iterator for Cursor<'_> {
 }
 
 impl std::iter::FusedIterator for Cursor<'_> {}
+
+/// Iterator over the [`RawTokenKind`]s of a [`Cursor`] and their spans.
+///
+/// Created with [`Cursor::spanned`].
+#[derive(Clone, Debug)]
+pub struct SpannedCursor<'a> {
+    cursor: Cursor<'a>,
+    pos: BytePos,
+}
+
+impl<'a> SpannedCursor<'a> {
+    /// Returns the remaining input as a string slice.
+    pub fn as_str(&self) -> &'a str {
+        self.cursor.as_str()
+    }
+}
+
+impl Iterator for SpannedCursor<'_> {
+    type Item = (RawTokenKind, Span);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let RawToken { kind, len } = self.cursor.next()?;
+        let lo = self.pos;
+        self.pos += len;
+        Some((kind, Span::new(lo, self.pos)))
+    }
+}
+
+impl std::iter::FusedIterator for SpannedCursor<'_> {}
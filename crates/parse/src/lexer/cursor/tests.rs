@@ -132,3 +132,33 @@ fn unicode_str() {
         "#]],
     );
 }
+
+#[test]
+fn spanned() {
+    let src = "uint x = 0x1;";
+    let tokens = crate::lexer::tokenize(src)
+        .map(|(kind, span)| (kind, &src[span.to_range()]))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        tokens,
+        [
+            (RawTokenKind::Ident, "uint"),
+            (RawTokenKind::Whitespace, " "),
+            (RawTokenKind::Ident, "x"),
+            (RawTokenKind::Whitespace, " "),
+            (RawTokenKind::Eq, "="),
+            (RawTokenKind::Whitespace, " "),
+            (
+                RawTokenKind::Literal {
+                    kind: RawLiteralKind::Int { base: Base::Hexadecimal, empty_int: false }
+                },
+                "0x1"
+            ),
+            (RawTokenKind::Semi, ";"),
+        ]
+    );
+
+    let mut cursor = Cursor::new(src).spanned(BytePos(100));
+    assert_eq!(cursor.next(), Some((RawTokenKind::Ident, Span::new(BytePos(100), BytePos(104)))));
+    assert_eq!(cursor.as_str(), " x = 0x1;");
+}
@@ -2,15 +2,20 @@
 
 use solar_ast::{
     token::{BinOpToken, CommentKind, Delimiter, Token, TokenKind, TokenLitKind},
+    trivia::{Trivia, TriviaKind, TriviaPiece},
     Base,
 };
 use solar_interface::{
-    diagnostics::DiagCtxt, source_map::SourceFile, sym, BytePos, Session, Span, Symbol,
+    diagnostics::{DiagCtxt, SuppressionCollector},
+    source_map::SourceFile,
+    sym, BytePos, Session, Span, Symbol,
 };
 
 mod cursor;
 use cursor::token::{RawLiteralKind, RawToken, RawTokenKind};
-pub use cursor::{is_id_continue, is_id_start, is_ident, is_whitespace, token, Cursor};
+pub use cursor::{
+    is_id_continue, is_id_start, is_ident, is_whitespace, token, Cursor, SpannedCursor,
+};
 
 pub mod unescape;
 
@@ -18,6 +23,18 @@ mod unicode_chars;
 
 mod utf8;
 
+/// Tokenizes the given source string into raw tokens and their spans.
+///
+/// Unlike [`Lexer`], this does not require a [`Session`]: tokens are not validated, interned, or
+/// glued together, no diagnostics are emitted, and whitespace and comments are included. This is
+/// meant for tools like syntax highlighters, which can get the text of each token by indexing the
+/// source with its span.
+///
+/// Spans start at `BytePos(0)`; use [`Cursor::spanned`] to start at another position.
+pub fn tokenize(src: &str) -> SpannedCursor<'_> {
+    Cursor::new(src).spanned(BytePos(0))
+}
+
 /// Solidity and Yul lexer.
 ///
 /// Converts a [`Cursor`]'s output from simple [`RawTokenKind`]s into rich [`TokenKind`]s, by
@@ -82,19 +99,22 @@ impl<'sess, 'src> Lexer<'sess, 'src> {
 
     /// Consumes the lexer and collects the remaining tokens into a vector.
     ///
-    /// Note that this skips comments, as [required by the parser](crate::Parser::new).
+    /// Note that this skips comments, as [required by the parser](crate::Parser::new). The lint
+    /// suppression comments among them are registered in the diagnostics context.
     ///
     /// Prefer using this method instead of manually collecting tokens using [`Iterator`].
     #[instrument(name = "lex", level = "debug", skip_all)]
     pub fn into_tokens(mut self) -> Vec<Token> {
         // `src.len() / 8` is an estimate of the number of tokens in the source.
         let mut tokens = Vec::with_capacity(self.src.len() / 8);
+        let mut suppressions = SuppressionCollector::new(self.src, self.start_pos);
         loop {
             let token = self.next_token();
             if token.is_eof() {
                 break;
             }
             if token.is_comment() {
+                add_suppression_comment(&mut suppressions, &token);
                 continue;
             }
             tokens.push(token);
@@ -105,9 +125,64 @@ impl<'sess, 'src> Lexer<'sess, 'src> {
             ratio = %format!("{:.2}", self.src.len() as f64 / tokens.len() as f64),
             "lexed"
         );
+        self.dcx().add_suppressions(suppressions.finish());
         tokens
     }
 
+    /// Consumes the lexer and collects the remaining tokens into a vector, like
+    /// [`into_tokens`](Self::into_tokens), and all the comments and whitespace into a [`Trivia`]
+    /// table.
+    ///
+    /// Use this when the source has to be regenerated from the AST without losing its comments.
+    /// Doc-comments are kept in the tokens as well, since they are part of the AST.
+    ///
+    /// Trivia is collected from the start of the source, so this must be called on a new lexer.
+    pub fn into_tokens_with_trivia(mut self) -> (Vec<Token>, Trivia) {
+        let mut tokens = Vec::with_capacity(self.src.len() / 8);
+        let mut pieces = Vec::new();
+        let mut suppressions = SuppressionCollector::new(self.src, self.start_pos);
+        let mut pos = self.start_pos;
+        loop {
+            let token = self.next_token();
+            let (lo, hi) = (token.span.lo(), token.span.hi());
+            if lo > pos {
+                let newlines = self.str_from_to(pos, lo).matches('\n').count() as u32;
+                let kind = TriviaKind::Whitespace { newlines };
+                pieces.push(TriviaPiece { span: self.new_span(pos, lo), kind });
+            }
+            pos = pos.max(hi);
+            if let TokenKind::Comment(is_doc, kind, _) = token.kind {
+                add_suppression_comment(&mut suppressions, &token);
+                let kind = TriviaKind::Comment { kind, is_doc };
+                pieces.push(TriviaPiece { span: token.span, kind });
+                if is_doc {
+                    tokens.push(token);
+                }
+                continue;
+            }
+            if token.is_eof() {
+                break;
+            }
+            tokens.push(token);
+        }
+        self.dcx().add_suppressions(suppressions.finish());
+        (tokens, Trivia::new(self.start_pos, pieces))
+    }
+
+    /// Consumes the lexer and returns an iterator over the remaining tokens, in which comments and
+    /// whitespace are yielded as [`TriviaPiece`]s instead of being skipped.
+    ///
+    /// This merges the tokens and the [`Trivia`] returned by
+    /// [`into_tokens_with_trivia`](Self::into_tokens_with_trivia). The spans of the yielded items
+    /// are contiguous, so concatenating their source text reproduces the source exactly. This is
+    /// meant for tools that process the source losslessly, like formatters and minifiers.
+    ///
+    /// Trivia is yielded from the start of the source, so this must be called on a new lexer.
+    pub fn into_lossless(self) -> Lossless {
+        let (tokens, trivia) = self.into_tokens_with_trivia();
+        Lossless { tokens: tokens.into_iter().peekable(), trivia, next_piece: 0 }
+    }
+
     /// Returns the next token, advancing the lexer.
     pub fn next_token(&mut self) -> Token {
         let mut next_token;
@@ -470,6 +545,64 @@ impl Iterator for Lexer<'_, '_> {
 
 impl std::iter::FusedIterator for Lexer<'_, '_> {}
 
+/// A token or a piece of trivia, yielded by [`Lossless`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum LosslessToken {
+    /// A token. Never a comment.
+    Token(Token),
+    /// Whitespace or a comment.
+    Trivia(TriviaPiece),
+}
+
+impl LosslessToken {
+    /// Returns the span of the token or trivia.
+    pub fn span(&self) -> Span {
+        match self {
+            Self::Token(token) => token.span,
+            Self::Trivia(piece) => piece.span,
+        }
+    }
+}
+
+/// Iterator over the tokens and trivia of a [`Lexer`].
+///
+/// Created with [`Lexer::into_lossless`].
+pub struct Lossless {
+    tokens: std::iter::Peekable<std::vec::IntoIter<Token>>,
+    trivia: Trivia,
+    /// The index of the next piece of trivia to yield.
+    next_piece: usize,
+}
+
+impl Lossless {
+    /// Returns the trivia of the whole source.
+    pub fn trivia(&self) -> &Trivia {
+        &self.trivia
+    }
+}
+
+impl Iterator for Lossless {
+    type Item = LosslessToken;
+
+    fn next(&mut self) -> Option<LosslessToken> {
+        // Doc-comments are both tokens and trivia.
+        while self.tokens.next_if(|token| matches!(token.kind, TokenKind::Comment(..))).is_some() {}
+        let piece = self.trivia.pieces().get(self.next_piece);
+        match (self.tokens.peek(), piece) {
+            (Some(token), Some(piece)) if token.span.lo() < piece.span.lo() => {
+                self.tokens.next().map(LosslessToken::Token)
+            }
+            (_, Some(&piece)) => {
+                self.next_piece += 1;
+                Some(LosslessToken::Trivia(piece))
+            }
+            (_, None) => self.tokens.next().map(LosslessToken::Token),
+        }
+    }
+}
+
+impl std::iter::FusedIterator for Lossless {}
+
 /// Pushes a character to a message string for error reporting
 fn escaped_char(c: char) -> String {
     match c {
@@ -481,6 +614,13 @@ fn escaped_char(c: char) -> String {
     }
 }
 
+/// Adds the given comment to the lint suppressions if it is not a doc-comment.
+fn add_suppression_comment(suppressions: &mut SuppressionCollector<'_>, comment: &Token) {
+    if let TokenKind::Comment(false, _, text) = comment.kind {
+        suppressions.add_comment(comment.span, text.as_str());
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -585,6 +725,88 @@ mod tests {
         });
     }
 
+    #[test]
+    fn trivia() {
+        use CommentKind::*;
+
+        let sess = Session::builder().with_test_emitter().build();
+        sess.enter(|| {
+            let src = "// a\n\n/// b\nx; // c\n/* d */ y\n";
+            let (tokens, trivia) = Lexer::new(&sess, src).into_tokens_with_trivia();
+            sess.dcx.has_errors().unwrap();
+            let kinds = tokens.iter().map(|t| t.kind.clone()).collect::<Vec<_>>();
+            assert_eq!(kinds, [Comment(true, Line, sym(" b")), id("x"), Semi, id("y")]);
+
+            let ws = |newlines| TriviaKind::Whitespace { newlines };
+            let comment = |kind, is_doc| TriviaKind::Comment { kind, is_doc };
+            let pieces = trivia
+                .pieces()
+                .iter()
+                .map(|p| (p.span.lo().to_usize()..p.span.hi().to_usize(), p.kind))
+                .collect::<Vec<_>>();
+            assert_eq!(
+                pieces,
+                [
+                    (0..4, comment(Line, false)),
+                    (4..6, ws(2)),
+                    (6..11, comment(Line, true)),
+                    (11..12, ws(1)),
+                    (14..15, ws(0)),
+                    (15..19, comment(Line, false)),
+                    (19..20, ws(1)),
+                    (20..27, comment(Block, false)),
+                    (27..28, ws(0)),
+                    (29..30, ws(1)),
+                ]
+            );
+            assert_eq!(trivia.comments().count(), 4);
+
+            let x = tokens[1].span;
+            assert_eq!(trivia.leading(x), &trivia.pieces()[..4]);
+            assert_eq!(trivia.trailing(tokens[2].span), &trivia.pieces()[4..7]);
+            let y = tokens[3].span;
+            assert_eq!(trivia.leading(y), &trivia.pieces()[7..9]);
+            assert_eq!(trivia.trailing(y), &trivia.pieces()[9..]);
+            assert_eq!(trivia.inner(x.to(y)).len(), 5);
+        });
+    }
+
+    #[test]
+    fn lossless() {
+        let sess = Session::builder().with_test_emitter().build();
+        sess.enter(|| {
+            let src = "\n/// a\nx  == /* b */y; // c\n";
+            let lossless = Lexer::new(&sess, src).into_lossless();
+            assert_eq!(lossless.trivia().comments().count(), 3);
+            let items = lossless.collect::<Vec<_>>();
+            sess.dcx.has_errors().unwrap();
+            let text = items.iter().map(|item| &src[item.span().to_range()]).collect::<Vec<_>>();
+            assert_eq!(
+                text,
+                ["\n", "/// a", "\n", "x", "  ", "==", " ", "/* b */", "y", ";", " ", "// c", "\n"]
+            );
+            assert_eq!(text.concat(), src);
+
+            let ws = |newlines| TriviaKind::Whitespace { newlines };
+            let kinds = items
+                .iter()
+                .map(|item| match item {
+                    LosslessToken::Token(token) => Ok(token.kind.clone()),
+                    LosslessToken::Trivia(piece) => Err(piece.kind),
+                })
+                .collect::<Vec<_>>();
+            assert_eq!(kinds[0], Err(ws(1)));
+            assert_eq!(
+                kinds[1],
+                Err(TriviaKind::Comment { kind: CommentKind::Line, is_doc: true })
+            );
+            assert_eq!(kinds[3], Ok(id("x")));
+            assert_eq!(kinds[4], Err(ws(0)));
+            assert_eq!(kinds[5], Ok(EqEq));
+            assert_eq!(kinds[12], Err(ws(1)));
+        });
+    }
+
     #[test]
     fn doc_comments() {
         use CommentKind::*;
@@ -10,6 +10,8 @@ extern crate tracing;
 
 use solar_interface::diagnostics::{DiagnosticBuilder, ErrorGuaranteed};
 
+pub mod format;
+
 pub mod lexer;
 pub use lexer::{unescape, Cursor, Lexer};
 
@@ -21,7 +21,11 @@ impl<'sess, 'ast> Parser<'sess, 'ast> {
             self.expect(&TokenKind::Colon)?;
             let else_ = self.parse_expr()?;
             let span = expr.span.to(self.prev_token.span);
-            Ok(self.alloc(Expr { span, kind: ExprKind::Ternary(expr, then, else_) }))
+            Ok(self.alloc(Expr {
+                id: self.next_node_id(),
+                span,
+                kind: ExprKind::Ternary(expr, then, else_),
+            }))
         } else {
             let kind = if let Some(binop_eq) = self.token.as_binop_eq() {
                 Some(binop_eq)
@@ -33,7 +37,11 @@ impl<'sess, 'ast> Parser<'sess, 'ast> {
             self.bump(); // binop token
             let rhs = self.parse_expr()?;
             let span = expr.span.to(self.prev_token.span);
-            Ok(self.alloc(Expr { span, kind: ExprKind::Assign(expr, kind, rhs) }))
+            Ok(self.alloc(Expr {
+                id: self.next_node_id(),
+                span,
+                kind: ExprKind::Assign(expr, kind, rhs),
+            }))
         }
     }
 
@@ -71,7 +79,7 @@ impl<'sess, 'ast> Parser<'sess, 'ast> {
                     let msg = format!("unknown binop token: {token:?}");
                     self.dcx().bug(msg).span(span).emit();
                 };
-                expr = self.alloc(Expr { span, kind });
+                expr = self.alloc(Expr { id: self.next_node_id(), span, kind });
             }
             precedence -= 1;
         }
@@ -93,7 +101,11 @@ impl<'sess, 'ast> Parser<'sess, 'ast> {
                 if let Some(unop) = this.token.as_unop(true) {
                     this.bump(); // unop
                     let span = lo.to(this.prev_token.span);
-                    this.alloc(Expr { span, kind: ExprKind::Unary(unop, expr) })
+                    this.alloc(Expr {
+                        id: this.next_node_id(),
+                        span,
+                        kind: ExprKind::Unary(unop, expr),
+                    })
                 } else {
                     expr
                 }
@@ -104,13 +116,17 @@ impl<'sess, 'ast> Parser<'sess, 'ast> {
         } else if self.eat_keyword(kw::Delete) {
             self.parse_unary_expr(None).map(|expr| {
                 let span = lo.to(self.prev_token.span);
-                self.alloc(Expr { span, kind: ExprKind::Delete(expr) })
+                self.alloc(Expr { id: self.next_node_id(), span, kind: ExprKind::Delete(expr) })
             })
         } else if let Some(unop) = self.token.as_unop(false) {
             self.bump(); // unop
             self.parse_unary_expr(None).map(|expr| {
                 let span = lo.to(self.prev_token.span);
-                self.alloc(Expr { span, kind: ExprKind::Unary(unop, expr) })
+                self.alloc(Expr {
+                    id: self.next_node_id(),
+                    span,
+                    kind: ExprKind::Unary(unop, expr),
+                })
             })
         } else {
             parse_lhs(self, None)
@@ -128,12 +144,12 @@ impl<'sess, 'ast> Parser<'sess, 'ast> {
         } else if self.eat_keyword(kw::New) {
             self.parse_type().map(|ty| {
                 let span = lo.to(self.prev_token.span);
-                self.alloc(Expr { span, kind: ExprKind::New(ty) })
+                self.alloc(Expr { id: self.next_node_id(), span, kind: ExprKind::New(ty) })
             })
         } else if self.eat_keyword(kw::Payable) {
             self.parse_call_args().map(|args| {
                 let span = lo.to(self.prev_token.span);
-                self.alloc(Expr { span, kind: ExprKind::Payable(args) })
+                self.alloc(Expr { id: self.next_node_id(), span, kind: ExprKind::Payable(args) })
             })
         } else {
             self.parse_primary_expr()
@@ -163,7 +179,7 @@ impl<'sess, 'ast> Parser<'sess, 'ast> {
                 break;
             };
             let span = lo.to(self.prev_token.span);
-            expr = self.alloc(Expr { span, kind });
+            expr = self.alloc(Expr { id: self.next_node_id(), span, kind });
         }
         Ok(expr)
     }
@@ -214,7 +230,7 @@ impl<'sess, 'ast> Parser<'sess, 'ast> {
             return self.unexpected();
         };
         let span = lo.to(self.prev_token.span);
-        Ok(self.alloc(Expr { span, kind }))
+        Ok(self.alloc(Expr { id: self.next_node_id(), span, kind }))
     }
 
     /// Parses a list of function call arguments.
@@ -2,7 +2,7 @@ use super::{ExpectedToken, SeqSep};
 use crate::{PResult, Parser};
 use itertools::Itertools;
 use solar_ast::{token::*, *};
-use solar_interface::{error_code, kw, sym, Ident, Span};
+use solar_interface::{diagnostics::Applicability, error_code, kw, sym, Ident, Span};
 use std::num::IntErrorKind;
 
 impl<'sess, 'ast> Parser<'sess, 'ast> {
@@ -13,6 +13,8 @@ impl<'sess, 'ast> Parser<'sess, 'ast> {
     }
 
     /// Parses a list of items until the given token is encountered.
+    ///
+    /// Items with syntax errors are skipped after emitting the error.
     fn parse_items(&mut self, end: &TokenKind) -> PResult<'sess, Box<'ast, [Item<'ast>]>> {
         let get_msg_note = |this: &mut Self| {
             let (prefix, list, link);
@@ -32,28 +34,74 @@ impl<'sess, 'ast> Parser<'sess, 'ast> {
         };
 
         let mut items = Vec::new();
-        while let Some(item) = self.parse_item()? {
-            if self.in_contract && !item.is_allowed_in_contract() {
-                let msg = format!("{}s are not allowed in contracts", item.description());
-                let (_, note) = get_msg_note(self);
-                self.dcx().err(msg).span(item.span).note(note).emit();
-            } else {
-                items.push(item);
+        loop {
+            let start = self.token.span;
+            match self.parse_item() {
+                Ok(Some(item)) => {
+                    if self.in_contract && !item.is_allowed_in_contract() {
+                        let msg = format!("{}s are not allowed in contracts", item.description());
+                        let (_, note) = get_msg_note(self);
+                        self.dcx().err(msg).span(item.span).note(note).emit();
+                    } else {
+                        items.push(item);
+                    }
+                }
+                Ok(None) => {
+                    if self.eat(end) {
+                        break;
+                    }
+                    let (msg, note) = get_msg_note(self);
+                    let err = self.dcx().err(msg).span(self.token.span).note(note);
+                    if self.token.is_eof() {
+                        return Err(err);
+                    }
+                    err.emit();
+                    self.recover_item();
+                }
+                Err(err) => {
+                    err.emit();
+                    let at_boundary = self.token.is_eof()
+                        || self.check_noexpect(&TokenKind::CloseDelim(Delimiter::Brace))
+                        || self.is_item_start();
+                    if self.token.span == start || !at_boundary {
+                        self.recover_item();
+                    }
+                }
             }
         }
-        if !self.eat(end) {
-            let (msg, note) = get_msg_note(self);
-            return Err(self.dcx().err(msg).span(self.token.span).note(note));
-        }
         Ok(self.alloc_vec(items))
     }
 
+    /// Skips at least one token, up to the start of the next item or the end of the enclosing
+    /// contract, to continue parsing after a syntax error.
+    fn recover_item(&mut self) {
+        self.last_unexpected_token_span = None;
+        let mut depth = 0usize;
+        while !self.token.is_eof() {
+            match self.token.kind {
+                TokenKind::OpenDelim(_) => depth += 1,
+                TokenKind::CloseDelim(_) => depth = depth.saturating_sub(1),
+                _ => {}
+            }
+            let is_end =
+                depth == 0 && matches!(self.token.kind, TokenKind::Semi | TokenKind::CloseDelim(_));
+            self.bump();
+            if is_end
+                || (depth == 0
+                    && (self.is_item_start()
+                        || self.check_noexpect(&TokenKind::CloseDelim(Delimiter::Brace))))
+            {
+                break;
+            }
+        }
+    }
+
     /// Parses an item.
     #[instrument(level = "debug", skip_all)]
     pub fn parse_item(&mut self) -> PResult<'sess, Option<Item<'ast>>> {
         let docs = self.parse_doc_comments()?;
         self.parse_spanned(Self::parse_item_kind)
-            .map(|(span, kind)| kind.map(|kind| Item { docs, span, kind }))
+            .map(|(span, kind)| kind.map(|kind| Item { id: self.next_node_id(), docs, span, kind }))
     }
 
     fn parse_item_kind(&mut self) -> PResult<'sess, Option<ItemKind<'ast>>> {
@@ -90,6 +138,21 @@ impl<'sess, 'ast> Parser<'sess, 'ast> {
         kind.map(Some)
     }
 
+    /// Returns `true` if the current token unambiguously starts an item, which makes it a point
+    /// where parsing can resume after a syntax error.
+    pub(super) fn is_item_start(&self) -> bool {
+        self.is_function_like()
+            || self.is_contract_like()
+            || self.token.is_keyword_any(&[
+                kw::Struct,
+                kw::Event,
+                kw::Enum,
+                kw::Pragma,
+                kw::Import,
+                kw::Using,
+            ])
+    }
+
     /// Returns `true` if the current token is the start of a function definition.
     fn is_function_like(&self) -> bool {
         (self.token.is_keyword(kw::Function)
@@ -181,7 +244,8 @@ impl<'sess, 'ast> Parser<'sess, 'ast> {
                 let mut warn = self.dcx().warn(msg).span(ident.span).code(error_code!(3445));
                 if self.in_contract {
                     let help = format!("remove the `function` keyword if you intend this to be a contract's {ident} function");
-                    warn = warn.span_help(kw_span, help);
+                    let span = kw_span.until(ident.span);
+                    warn = warn.span_suggestion(span, help, "", Applicability::MaybeIncorrect);
                 }
                 warn.emit();
             } else {
@@ -221,7 +285,11 @@ impl<'sess, 'ast> Parser<'sess, 'ast> {
                 } else {
                     header.visibility = Some(visibility);
                 }
-            } else if let Some(state_mutability) = self.parse_state_mutability() {
+            } else if let Some(state_mutability) = self.parse_state_mutability().or_else(|| {
+                // Legacy `constant` functions are `view` functions.
+                let legacy = flags == FunctionFlags::FUNCTION && self.sess.legacy_syntax;
+                (legacy && self.eat_keyword(kw::Constant)).then_some(StateMutability::View)
+            }) {
                 if !flags.contains(FunctionFlags::from_state_mutability(state_mutability)) {
                     let msg = state_mutability_error(state_mutability, flags.state_mutabilities());
                     self.dcx().err(msg).span(self.prev_token.span).emit();
@@ -322,8 +390,19 @@ impl<'sess, 'ast> Parser<'sess, 'ast> {
         let bases =
             if self.eat_keyword(kw::Is) { self.parse_inheritance()? } else { Default::default() };
         self.expect(&TokenKind::OpenDelim(Delimiter::Brace))?;
-        let body =
+        let mut body =
             self.in_contract(|this| this.parse_items(&TokenKind::CloseDelim(Delimiter::Brace)))?;
+        if self.sess.legacy_syntax && !kind.is_interface() {
+            // Legacy constructors are functions named after their contract.
+            for item in body.iter_mut() {
+                if let ItemKind::Function(f) = &mut item.kind {
+                    if f.kind.is_function() && f.header.name == Some(name) {
+                        f.kind = FunctionKind::Constructor;
+                        f.header.name = None;
+                    }
+                }
+            }
+        }
         Ok(ItemContract { kind, name, bases, body })
     }
 
@@ -719,6 +798,7 @@ impl<'sess, 'ast> Parser<'sess, 'ast> {
             self.dcx().err(msg).span(self.token.span).note(note).emit();
             let _ = self.parse_block()?;
             return Ok(VariableDefinition {
+                id: self.next_node_id(),
                 span: lo.to(self.prev_token.span),
                 ty,
                 visibility: None,
@@ -832,6 +912,7 @@ impl<'sess, 'ast> Parser<'sess, 'ast> {
         }
 
         Ok(VariableDefinition {
+            id: self.next_node_id(),
             span,
             ty,
             data_location,
@@ -1458,4 +1539,209 @@ mod tests {
             ("0.8.1", "0.8 || 0.9", true),
         ]);
     }
+
+    #[test]
+    fn semver_min_version() {
+        let sess = Session::builder().with_test_emitter().build();
+        sess.enter(|| -> Result {
+            for (i, &(req_s, min)) in [
+                ("0.8.1", Some("0.8.1")),
+                ("^0.8", Some("0.8.0")),
+                ("~0.7.6", Some("0.7.6")),
+                (">0.7.6", Some("0.7.7")),
+                (">0.7", Some("0.8.0")),
+                ("<0.8.0", Some("0.0.0")),
+                (">=0.6.2 <0.9.0", Some("0.6.2")),
+                ("0.4 - 0.5 || ^0.8.4", Some("0.4.0")),
+                (">=0.8.0 <0.5.0", None),
+                ("<0.5.0 >=0.6.0 || >=0.8.0", Some("0.8.0")),
+            ]
+            .iter()
+            .enumerate()
+            {
+                let name = i.to_string();
+                let arena = Arena::new();
+                let mut parser =
+                    Parser::from_source_code(&sess, &arena, FileName::Custom(name), req_s.into())?;
+                let req = parser.parse_semver_req().map_err(|e| e.emit()).unwrap();
+                let min_version = req.min_version().map(|v| v.to_string());
+                assert_eq!(min_version.as_deref(), min, "{req_s:?}");
+            }
+            Ok(())
+        })
+        .unwrap();
+    }
+
+    #[test]
+    fn node_ids() {
+        let sess = Session::builder().with_test_emitter().build();
+        sess.enter(|| -> Result {
+            let arena = Arena::new();
+            let src = "contract C { function f(uint a) { a + 1; } }".to_string();
+            let mut parser =
+                Parser::from_source_code(&sess, &arena, FileName::Custom("test".into()), src)?;
+            let source_unit = parser.parse_file().map_err(|e| e.emit())?;
+            let map = ParentMap::new(&source_unit);
+            // Item, item, variable, type, statement, expressions `a + 1`, `a` and `1`.
+            assert_eq!(map.len(), 8);
+
+            let contract = &source_unit.items.raw[0];
+            let ItemKind::Contract(c) = &contract.kind else { panic!() };
+            let function = &c.body[0];
+            let ItemKind::Function(f) = &function.kind else { panic!() };
+            let param = &f.header.parameters[0];
+            let stmt = &f.body.as_ref().unwrap()[0];
+            let StmtKind::Expr(expr) = &stmt.kind else { panic!() };
+            let ExprKind::Binary(lhs, _, _) = &expr.kind else { panic!() };
+
+            assert_eq!(map.parent(contract.id), None);
+            assert_eq!(map.parent(function.id), Some(contract.id));
+            assert_eq!(map.parent(param.id), Some(function.id));
+            assert_eq!(map.parent(param.ty.id), Some(param.id));
+            assert_eq!(map.parent(stmt.id), Some(function.id));
+            assert_eq!(map.parent(expr.id), Some(stmt.id));
+            let ancestors = map.ancestors(lhs.id).collect::<Vec<_>>();
+            assert_eq!(ancestors, [expr.id, stmt.id, function.id, contract.id]);
+            assert!(matches!(map.parent_node(lhs.id), Some(Node::Expr(e)) if e.id == expr.id));
+            assert_eq!(map.get(stmt.id).unwrap().span(), stmt.span);
+            Ok(())
+        })
+        .unwrap();
+    }
+
+    #[test]
+    fn par_visit() {
+        use solar_ast::visit::{ParVisit, Visit};
+        use solar_data_structures::Never;
+        use std::ops::ControlFlow;
+
+        #[derive(Default)]
+        struct Names(Vec<String>);
+
+        impl<'ast> Visit<'ast> for Names {
+            type BreakValue = Never;
+
+            fn visit_item(&mut self, item: &'ast Item<'ast>) -> ControlFlow<Self::BreakValue> {
+                if let Some(name) = item.name() {
+                    self.0.push(name.to_string());
+                }
+                self.walk_item(item)
+            }
+        }
+
+        impl ParVisit<'_> for Names {
+            fn fork(&self) -> Self {
+                Self::default()
+            }
+
+            fn merge(&mut self, other: Self) {
+                self.0.extend(other.0);
+            }
+        }
+
+        let sess = Session::builder().with_test_emitter().build();
+        sess.enter(|| -> Result {
+            let arena = Arena::new();
+            let src =
+                "contract A { function f() {} } struct S { uint x; } contract B { event E(); }";
+            let mut parser = Parser::from_source_code(
+                &sess,
+                &arena,
+                FileName::Custom("test".into()),
+                src.to_string(),
+            )?;
+            let source_unit = parser.parse_file().map_err(|e| e.emit())?;
+            let mut seq = Names::default();
+            let _ = seq.visit_source_unit(&source_unit);
+            let mut par = Names::default();
+            let _ = par.par_visit_source_unit(&source_unit);
+            assert_eq!(par.0, ["A", "f", "S", "B", "E"]);
+            assert_eq!(par.0, seq.0);
+            Ok(())
+        })
+        .unwrap();
+    }
+
+    #[test]
+    fn recover_from_errors() {
+        let sess = Session::builder().with_test_emitter().build();
+        sess.enter(|| -> Result {
+            let arena = Arena::new();
+            let src = "
+contract A {
+    function f() public {
+        uint x = 1
+        x += 2;
+        g(;
+        return;
+    }
+    uint y = ;
+    function h() public {}
+}
+struct S { uint a; }
+";
+            let mut parser = Parser::from_source_code(
+                &sess,
+                &arena,
+                FileName::Custom("test".into()),
+                src.to_string(),
+            )?;
+            let source_unit = parser.parse_file().map_err(|e| e.emit())?;
+            assert_eq!(sess.dcx.err_count(), 3);
+
+            let names = |items: &[Item<'_>]| {
+                items.iter().map(|item| item.name().unwrap().to_string()).collect::<Vec<_>>()
+            };
+            assert_eq!(names(&source_unit.items.raw), ["A", "S"]);
+            let ItemKind::Contract(contract) = &source_unit.items[0].kind else { panic!() };
+            assert_eq!(names(&contract.body), ["f", "h"]);
+            Ok(())
+        })
+        .unwrap();
+    }
+
+    #[test]
+    fn legacy_syntax() {
+        let src = "
+contract C {
+    function C() {}
+    function f() constant { var x = 1; var (a, , b) = (1, 2, 3); throw; }
+}
+";
+        for legacy_syntax in [false, true] {
+            let sess = Session::builder().with_test_emitter().legacy_syntax(legacy_syntax).build();
+            sess.enter(|| -> Result {
+                let arena = Arena::new();
+                let name = FileName::Custom("test".into());
+                let mut parser = Parser::from_source_code(&sess, &arena, name, src.to_string())?;
+                let source_unit = parser.parse_file().map_err(|e| e.emit());
+                if !legacy_syntax {
+                    assert!(sess.dcx.has_errors().is_err());
+                    return Ok(());
+                }
+                let source_unit = source_unit?;
+                sess.dcx.has_errors().unwrap();
+
+                let ItemKind::Contract(c) = &source_unit.items.raw[0].kind else { panic!() };
+                let ItemKind::Function(ctor) = &c.body[0].kind else { panic!() };
+                assert_eq!(ctor.kind, FunctionKind::Constructor);
+                assert_eq!(ctor.header.name, None);
+                let ItemKind::Function(f) = &c.body[1].kind else { panic!() };
+                assert_eq!(f.header.state_mutability, StateMutability::View);
+                let stmts = f.body.as_ref().unwrap();
+                let StmtKind::DeclSingle(x) = &stmts[0].kind else { panic!() };
+                assert!(
+                    matches!(&x.ty.kind, TypeKind::Custom(path) if path.first().name == kw::Var)
+                );
+                let StmtKind::DeclMulti(vars, _) = &stmts[1].kind else { panic!() };
+                assert_eq!(vars.len(), 3);
+                assert!(vars[1].is_none());
+                let StmtKind::Expr(throw) = &stmts[2].kind else { panic!() };
+                let ExprKind::Call(callee, _) = &throw.kind else { panic!() };
+                assert!(matches!(&callee.kind, ExprKind::Ident(ident) if ident.name == kw::Revert));
+                Ok(())
+            })
+            .unwrap();
+        }
+    }
 }
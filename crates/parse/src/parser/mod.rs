@@ -3,15 +3,16 @@ use smallvec::SmallVec;
 use solar_ast::{
     self as ast,
     token::{Delimiter, Token, TokenKind},
-    AstPath, Box, DocComment, DocComments, PathSlice,
+    trivia::Trivia,
+    AstPath, Box, DocComment, DocComments, NodeId, PathSlice,
 };
 use solar_data_structures::{fmt::or_list, BumpExt};
 use solar_interface::{
-    diagnostics::DiagCtxt,
+    diagnostics::{Applicability, DiagCtxt},
     source_map::{FileName, SourceFile},
     Ident, Result, Session, Span, Symbol,
 };
-use std::{fmt, path::Path};
+use std::{cell::Cell, fmt, path::Path};
 
 mod expr;
 mod item;
@@ -43,6 +44,9 @@ pub struct Parser<'sess, 'ast> {
     /// Whether the parser is currently parsing a contract block.
     in_contract: bool,
 
+    /// The ID of the next AST node.
+    next_node_id: Cell<NodeId>,
+
     /// The token stream.
     tokens: std::vec::IntoIter<Token>,
 }
@@ -137,6 +141,7 @@ impl<'sess, 'ast> Parser<'sess, 'ast> {
             last_unexpected_token_span: None,
             in_yul: false,
             in_contract: false,
+            next_node_id: Cell::new(NodeId::new(0)),
             tokens: tokens.into_iter(),
         };
         parser.bump();
@@ -183,17 +188,68 @@ impl<'sess, 'ast> Parser<'sess, 'ast> {
         Self::from_lexer(arena, Lexer::from_source_file(sess, file))
     }
 
+    /// Creates a new parser from a source file, also collecting its comments and whitespace.
+    ///
+    /// See [`Lexer::into_tokens_with_trivia`] for more details.
+    pub fn from_source_file_with_trivia(
+        sess: &'sess Session,
+        arena: &'ast ast::Arena,
+        file: &SourceFile,
+    ) -> (Self, Trivia) {
+        let (tokens, trivia) = Lexer::from_source_file(sess, file).into_tokens_with_trivia();
+        (Self::new(sess, arena, tokens), trivia)
+    }
+
     /// Creates a new parser from a lexer.
     pub fn from_lexer(arena: &'ast ast::Arena, lexer: Lexer<'sess, '_>) -> Self {
         Self::new(lexer.sess, arena, lexer.into_tokens())
     }
 
+    /// Parses a snippet of source code with `f`, making sure that all of it is consumed.
+    ///
+    /// This is used by the `solar_macros::quote_sol!` macro. Errors are emitted.
+    pub fn parse_snippet<T>(
+        sess: &'sess Session,
+        arena: &'ast ast::Arena,
+        src: String,
+        f: impl FnOnce(&mut Self) -> PResult<'sess, T>,
+    ) -> Result<T> {
+        let filename = FileName::Custom("quote_sol!".into());
+        let mut parser = Self::from_source_code(sess, arena, filename, src)?;
+        parser.parse_fragment(f).map_err(|e| e.emit())
+    }
+
+    /// Parses a fragment of source code with `f`, making sure that the rest of the input is
+    /// consumed.
+    ///
+    /// This allows parsing a single expression, statement, type, or Yul block, without wrapping it
+    /// in a contract:
+    ///
+    /// ```ignore
+    /// let expr = parser.parse_fragment(Parser::parse_expr)?;
+    /// ```
+    pub fn parse_fragment<T>(
+        &mut self,
+        f: impl FnOnce(&mut Self) -> PResult<'sess, T>,
+    ) -> PResult<'sess, T> {
+        let value = f(self)?;
+        self.expect(&TokenKind::Eof)?;
+        Ok(value)
+    }
+
     /// Returns the diagnostic context.
     #[inline]
     pub fn dcx(&self) -> &'sess DiagCtxt {
         &self.sess.dcx
     }
 
+    /// Returns a new AST node ID.
+    pub fn next_node_id(&self) -> NodeId {
+        let id = self.next_node_id.get();
+        self.next_node_id.set(NodeId::new(id.get() + 1));
+        id
+    }
+
     /// Allocates an object on the AST arena.
     pub fn alloc<T>(&self, value: T) -> Box<'ast, T> {
         self.arena.alloc(value)
@@ -958,7 +1014,12 @@ impl<'sess, 'ast> Parser<'sess, 'ast> {
                 self.bump();
                 recovered_ident = self.ident_or_err(false).ok();
             }
-            err = err.span_help(span, "remove this comma");
+            err = err.span_suggestion(
+                span,
+                "remove this comma",
+                "",
+                Applicability::MachineApplicable,
+            );
         }
 
         if recover {
@@ -975,3 +1036,41 @@ impl<'sess, 'ast> Parser<'sess, 'ast> {
         self.expected_ident_found(false).unwrap_err()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fragments() {
+        let sess = Session::builder().with_test_emitter().build();
+        sess.enter(|| -> Result {
+            let arena = ast::Arena::new();
+            let parser = |src: &str| {
+                let name = FileName::Custom(src.to_string());
+                Parser::from_source_code(&sess, &arena, name, src.to_string())
+            };
+
+            let expr = parser("a + b * c")?.parse_fragment(Parser::parse_expr).unwrap();
+            assert!(matches!(expr.kind, ast::ExprKind::Binary(..)), "{expr:?}");
+            let stmt = parser("if (a) { b; } else c;")?.parse_fragment(Parser::parse_stmt).unwrap();
+            assert!(matches!(stmt.kind, ast::StmtKind::If(..)), "{stmt:?}");
+            let ty = parser("mapping(address => uint256[])")?
+                .parse_fragment(Parser::parse_type)
+                .unwrap();
+            assert!(matches!(ty.kind, ast::TypeKind::Mapping(..)), "{ty:?}");
+            let block = parser("{ let x := add(1, 2) sstore(0, x) }")?
+                .parse_fragment(Parser::parse_yul_block)
+                .unwrap();
+            assert_eq!(block.len(), 2);
+            let stmt = parser("leave")?.parse_fragment(Parser::parse_yul_stmt).unwrap();
+            assert!(matches!(stmt.kind, ast::yul::StmtKind::Leave), "{stmt:?}");
+            sess.dcx.has_errors().unwrap();
+
+            parser("a + b c")?.parse_fragment(Parser::parse_expr).unwrap_err().emit();
+            assert_eq!(sess.dcx.err_count(), 1);
+            Ok(())
+        })
+        .unwrap();
+    }
+}
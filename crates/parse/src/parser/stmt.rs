@@ -10,7 +10,12 @@ impl<'sess, 'ast> Parser<'sess, 'ast> {
     #[instrument(level = "debug", skip_all)]
     pub fn parse_stmt(&mut self) -> PResult<'sess, Stmt<'ast>> {
         let docs = self.parse_doc_comments()?;
-        self.parse_spanned(Self::parse_stmt_kind).map(|(span, kind)| Stmt { docs, kind, span })
+        self.parse_spanned(Self::parse_stmt_kind).map(|(span, kind)| Stmt {
+            id: self.next_node_id(),
+            docs,
+            kind,
+            span,
+        })
     }
 
     /// Parses a statement into a new allocation.
@@ -46,8 +51,18 @@ impl<'sess, 'ast> Parser<'sess, 'ast> {
             let expr = if self.check(&TokenKind::Semi) { None } else { Some(self.parse_expr()?) };
             Ok(StmtKind::Return(expr))
         } else if self.eat_keyword(kw::Throw) {
-            let msg = "`throw` statements have been removed; use `revert`, `require`, or `assert` instead";
-            Err(self.dcx().err(msg).span(self.prev_token.span))
+            let span = self.prev_token.span;
+            if self.sess.legacy_syntax {
+                // `throw` is equivalent to `revert()`.
+                let revert = Ident::new(kw::Revert, span);
+                let callee =
+                    self.alloc(Expr { id: self.next_node_id(), ..Expr::from_ident(revert) });
+                let kind = ExprKind::Call(callee, CallArgs::empty());
+                Ok(StmtKind::Expr(self.alloc(Expr { id: self.next_node_id(), span, kind })))
+            } else {
+                let msg = "`throw` statements have been removed; use `revert`, `require`, or `assert` instead";
+                Err(self.dcx().err(msg).span(span))
+            }
         } else if self.eat_keyword(kw::Try) {
             semi = false;
             self.parse_stmt_try().map(|stmt| StmtKind::Try(self.alloc(stmt)))
@@ -73,8 +88,60 @@ impl<'sess, 'ast> Parser<'sess, 'ast> {
     }
 
     /// Parses a block of statements.
+    ///
+    /// Statements with syntax errors are skipped after emitting the error. The block ends early if
+    /// an item is found, since its closing brace is most likely missing.
     pub(super) fn parse_block(&mut self) -> PResult<'sess, Block<'ast>> {
-        self.parse_delim_seq(Delimiter::Brace, SeqSep::none(), true, Self::parse_stmt)
+        let lo = self.token.span;
+        self.expect(&TokenKind::OpenDelim(Delimiter::Brace))?;
+        let mut stmts = SmallVec::<[_; 8]>::new();
+        while !self.check(&TokenKind::CloseDelim(Delimiter::Brace)) {
+            if self.token.is_eof() || self.is_item_start_in_block() {
+                break;
+            }
+            match self.parse_stmt() {
+                Ok(stmt) => stmts.push(stmt),
+                Err(err) => {
+                    err.emit();
+                    self.recover_stmt();
+                }
+            }
+        }
+        self.expect(&TokenKind::CloseDelim(Delimiter::Brace))?;
+        Ok(Block { span: lo.to(self.prev_token.span), stmts: self.alloc_smallvec(stmts) })
+    }
+
+    /// Skips tokens up to the end of the current statement or of the enclosing block, to continue
+    /// parsing after a syntax error.
+    fn recover_stmt(&mut self) {
+        self.last_unexpected_token_span = None;
+        let mut depth = 0usize;
+        loop {
+            let is_end = match self.token.kind {
+                TokenKind::Eof => break,
+                TokenKind::CloseDelim(Delimiter::Brace) if depth == 0 => break,
+                _ if depth == 0 && self.is_item_start_in_block() => break,
+                TokenKind::Semi => depth == 0,
+                TokenKind::OpenDelim(_) => {
+                    depth += 1;
+                    false
+                }
+                TokenKind::CloseDelim(delim) => {
+                    depth = depth.saturating_sub(1);
+                    depth == 0 && delim == Delimiter::Brace
+                }
+                _ => false,
+            };
+            self.bump();
+            if is_end {
+                break;
+            }
+        }
+    }
+
+    /// Returns `true` if the current token starts an item that cannot be a statement.
+    fn is_item_start_in_block(&self) -> bool {
+        self.is_item_start() && !self.token.is_keyword_any(&[kw::Fallback, kw::Receive])
     }
 
     /// Parses an if statement.
@@ -132,16 +199,19 @@ impl<'sess, 'ast> Parser<'sess, 'ast> {
     /// Parses a try statement.
     fn parse_stmt_try(&mut self) -> PResult<'sess, StmtTry<'ast>> {
         let expr = self.parse_expr()?;
+        let lo = self.token.span;
         let returns = if self.eat_keyword(kw::Returns) {
             self.parse_parameter_list(false, VarFlags::FUNCTION)?
         } else {
             Default::default()
         };
         let block = self.parse_block()?;
+        let span = lo.to(self.prev_token.span);
 
         let mut catch = SmallVec::<[_; 4]>::new();
         self.expect_keyword(kw::Catch)?;
         loop {
+            let lo = self.prev_token.span;
             let name = self.parse_ident_opt()?;
             let args = if self.check(&TokenKind::OpenDelim(Delimiter::Parenthesis)) {
                 self.parse_parameter_list(false, VarFlags::FUNCTION)?
@@ -149,13 +219,14 @@ impl<'sess, 'ast> Parser<'sess, 'ast> {
                 Default::default()
             };
             let block = self.parse_block()?;
-            catch.push(CatchClause { name, args, block });
+            let span = lo.to(self.prev_token.span);
+            catch.push(CatchClause { span, name, args, block });
             if !self.eat_keyword(kw::Catch) {
                 break;
             }
         }
         let catch = self.alloc_smallvec(catch);
-        Ok(StmtTry { expr, returns, block, catch })
+        Ok(StmtTry { expr, span, returns, block, catch })
     }
 
     /// Parses an assembly block.
@@ -174,6 +245,7 @@ impl<'sess, 'ast> Parser<'sess, 'ast> {
     fn parse_simple_stmt(&mut self) -> PResult<'sess, Stmt<'ast>> {
         let docs = self.parse_doc_comments()?;
         self.parse_spanned(Self::parse_simple_stmt_kind).map(|(span, kind)| Stmt {
+            id: self.next_node_id(),
             docs,
             kind,
             span,
@@ -185,7 +257,9 @@ impl<'sess, 'ast> Parser<'sess, 'ast> {
     /// Also used in the for loop initializer. Does not parse the trailing semicolon.
     fn parse_simple_stmt_kind(&mut self) -> PResult<'sess, StmtKind<'ast>> {
         let lo = self.token.span;
-        if self.eat(&TokenKind::OpenDelim(Delimiter::Parenthesis)) {
+        if self.sess.legacy_syntax && self.eat_keyword(kw::Var) {
+            self.parse_legacy_var_decl()
+        } else if self.eat(&TokenKind::OpenDelim(Delimiter::Parenthesis)) {
             let mut empty_components = 0usize;
             while self.eat(&TokenKind::Comma) {
                 empty_components += 1;
@@ -217,6 +291,7 @@ impl<'sess, 'ast> Parser<'sess, 'ast> {
                         Self::parse_expr,
                     )?;
                     let partially_parsed = Expr {
+                        id: self.next_node_id(),
                         span: lo.to(self.prev_token.span),
                         kind: ExprKind::Tuple(self.alloc_smallvec(components)),
                     };
@@ -241,6 +316,50 @@ impl<'sess, 'ast> Parser<'sess, 'ast> {
         }
     }
 
+    /// Parses a legacy `var` declaration: `var x = expr` or `var (a, , b) = expr`.
+    ///
+    /// Expects the `var` keyword to have already been eaten. The declared variables have a custom
+    /// type named `var`, since their types are inferred from the initializer.
+    fn parse_legacy_var_decl(&mut self) -> PResult<'sess, StmtKind<'ast>> {
+        let var_span = self.prev_token.span;
+        let var = |this: &mut Self, span: Span, name: Ident, initializer| {
+            let ty = Type {
+                id: this.next_node_id(),
+                span: var_span,
+                kind: TypeKind::Custom(this.alloc_path(&[Ident::new(kw::Var, var_span)])),
+            };
+            VariableDefinition {
+                id: this.next_node_id(),
+                span,
+                ty,
+                visibility: None,
+                mutability: None,
+                data_location: None,
+                override_: None,
+                indexed: false,
+                name: Some(name),
+                initializer,
+            }
+        };
+        if self.check(&TokenKind::OpenDelim(Delimiter::Parenthesis)) {
+            let names = self.parse_optional_items_seq(Delimiter::Parenthesis, Self::parse_ident)?;
+            let variables = names
+                .iter()
+                .map(|&name| name.map(|name| var(self, var_span.to(name.span), name, None)))
+                .collect::<SmallVec<[_; 8]>>();
+            self.expect(&TokenKind::Eq)?;
+            let expr = self.parse_expr()?;
+            Ok(StmtKind::DeclMulti(self.alloc_smallvec(variables), expr))
+        } else {
+            let name = self.parse_ident()?;
+            let initializer =
+                if self.eat(&TokenKind::Eq) { Some(self.parse_expr()?) } else { None };
+            let span = var_span.to(self.prev_token.span);
+            let var = var(self, span, name, initializer);
+            Ok(StmtKind::DeclSingle(self.alloc(var)))
+        }
+    }
+
     /// Parses a `delim`-delimited, comma-separated list of maybe-optional items.
     /// E.g. `(a, b) => [Some, Some]`, `(, a,, b,) => [None, Some, None, Some, None]`.
     pub(super) fn parse_optional_items_seq<T>(
@@ -395,7 +514,7 @@ impl<'ast> IndexAccessedPath<'ast> {
 
         let mut ty = if let IapKind::MemberTy(span, kind) = first {
             debug_assert_eq!(self.n_idents, 1);
-            Type { span, kind: TypeKind::Elementary(kind) }
+            Type { id: parser.next_node_id(), span, kind: TypeKind::Elementary(kind) }
         } else {
             debug_assert!(self.n_idents >= 1);
             let first = std::iter::once(&first);
@@ -407,7 +526,7 @@ impl<'ast> IndexAccessedPath<'ast> {
                 })
                 .take(self.n_idents);
             let path = PathSlice::from_mut_slice(parser.arena.alloc_from_iter(path));
-            Type { span: path.span(), kind: TypeKind::Custom(path) }
+            Type { id: parser.next_node_id(), span: path.span(), kind: TypeKind::Custom(path) }
         };
 
         for index in path.skip(self.n_idents - 1) {
@@ -421,8 +540,8 @@ impl<'ast> IndexAccessedPath<'ast> {
                 }
             };
             let span = ty.span.to(span);
-            ty =
-                Type { span, kind: TypeKind::Array(parser.alloc(TypeArray { element: ty, size })) };
+            let kind = TypeKind::Array(parser.alloc(TypeArray { element: ty, size }));
+            ty = Type { id: parser.next_node_id(), span, kind };
         }
 
         Some(ty)
@@ -433,20 +552,23 @@ impl<'ast> IndexAccessedPath<'ast> {
         let mut path = self.path.into_iter();
 
         let mut expr = parser.alloc(match path.next()? {
-            IapKind::Member(ident) => Expr::from_ident(ident),
+            IapKind::Member(ident) => Expr { id: parser.next_node_id(), ..Expr::from_ident(ident) },
             IapKind::MemberTy(span, kind) => {
-                Expr { span, kind: ExprKind::Type(Type { span, kind: TypeKind::Elementary(kind) }) }
+                let ty = Type { id: parser.next_node_id(), span, kind: TypeKind::Elementary(kind) };
+                Expr { id: parser.next_node_id(), span, kind: ExprKind::Type(ty) }
             }
             IapKind::Index(..) => panic!("should not happen"),
         });
         for index in path {
             expr = parser.alloc(match index {
                 IapKind::Member(ident) => {
-                    Expr { span: expr.span.to(ident.span), kind: ExprKind::Member(expr, ident) }
+                    let span = expr.span.to(ident.span);
+                    Expr { id: parser.next_node_id(), span, kind: ExprKind::Member(expr, ident) }
                 }
                 IapKind::MemberTy(..) => panic!("should not happen"),
                 IapKind::Index(span, kind) => {
-                    Expr { span: expr.span.to(span), kind: ExprKind::Index(expr, kind) }
+                    let span = expr.span.to(span);
+                    Expr { id: parser.next_node_id(), span, kind: ExprKind::Index(expr, kind) }
                 }
             });
         }
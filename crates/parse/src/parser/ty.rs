@@ -8,9 +8,11 @@ impl<'sess, 'ast> Parser<'sess, 'ast> {
     /// Parses a type.
     #[instrument(level = "debug", skip_all)]
     pub fn parse_type(&mut self) -> PResult<'sess, Type<'ast>> {
-        let mut ty = self
-            .parse_spanned(Self::parse_basic_ty_kind)
-            .map(|(span, kind)| Type { span, kind })?;
+        let mut ty = self.parse_spanned(Self::parse_basic_ty_kind).map(|(span, kind)| Type {
+            id: self.next_node_id(),
+            span,
+            kind,
+        })?;
 
         // Parse suffixes.
         while self.eat(&TokenKind::OpenDelim(Delimiter::Bracket)) {
@@ -21,6 +23,7 @@ impl<'sess, 'ast> Parser<'sess, 'ast> {
             };
             self.expect(&TokenKind::CloseDelim(Delimiter::Bracket))?;
             ty = Type {
+                id: self.next_node_id(),
                 span: ty.span.to(self.prev_token.span),
                 kind: TypeKind::Array(self.alloc(TypeArray { element: ty, size })),
             };
@@ -34,20 +34,32 @@ impl<'sess, 'ast> Parser<'sess, 'ast> {
         let lo = self.token.span;
         self.expect_keyword(sym::object)?;
         let name = self.parse_str_lit()?;
+        if name.value.as_str().is_empty() {
+            self.dcx().err("object name cannot be empty").span(name.span).emit();
+        }
 
         self.expect(&TokenKind::OpenDelim(Delimiter::Brace))?;
         let code = self.parse_yul_code()?;
         let mut children = Vec::new();
         let mut data = Vec::new();
+        let mut names = Vec::<StrLit>::new();
         loop {
             let docs = self.parse_doc_comments()?;
-            if self.check_keyword(sym::object) {
-                children.push(self.parse_yul_object(docs)?);
+            let child_name = if self.check_keyword(sym::object) {
+                let child = self.parse_yul_object(docs)?;
+                let child_name = child.name.clone();
+                children.push(child);
+                child_name
             } else if self.check_keyword(sym::data) {
-                data.push(self.parse_yul_data()?);
+                let segment = self.parse_yul_data()?;
+                let child_name = segment.name.clone();
+                data.push(segment);
+                child_name
             } else {
                 break;
-            }
+            };
+            self.check_yul_object_name(&name, &names, &child_name);
+            names.push(child_name);
         }
         self.expect(&TokenKind::CloseDelim(Delimiter::Brace))?;
 
@@ -57,6 +69,24 @@ impl<'sess, 'ast> Parser<'sess, 'ast> {
         Ok(Object { docs, span, name, code, children, data })
     }
 
+    /// Checks that the name of a sub-object or data segment is unique within its containing object.
+    fn check_yul_object_name(&self, parent: &StrLit, siblings: &[StrLit], name: &StrLit) {
+        if name.value.as_str().is_empty() {
+            // Already reported for objects.
+            return;
+        }
+        if name.value == parent.value {
+            let msg = "object name cannot be the same as the name of the containing object";
+            self.dcx().err(msg).span(name.span).emit();
+        } else if let Some(prev) = siblings.iter().find(|prev| prev.value == name.value) {
+            let msg = format!(
+                "object name {:?} already exists inside the containing object",
+                name.value.as_str()
+            );
+            self.dcx().err(msg).span(name.span).span_note(prev.span, "previously used here").emit();
+        }
+    }
+
     /// Parses a Yul code block.
     fn parse_yul_code(&mut self) -> PResult<'sess, CodeBlock<'ast>> {
         let lo = self.token.span;
@@ -82,7 +112,7 @@ impl<'sess, 'ast> Parser<'sess, 'ast> {
 
     /// Parses a Yul statement.
     pub fn parse_yul_stmt(&mut self) -> PResult<'sess, Stmt<'ast>> {
-        self.in_yul(Self::parse_yul_stmt)
+        self.in_yul(Self::parse_yul_stmt_unchecked)
     }
 
     /// Parses a Yul statement, without setting `in_yul`.
@@ -290,3 +320,38 @@ impl<'sess, 'ast> Parser<'sess, 'ast> {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::Parser;
+    use solar_interface::{FileName, Result, Session};
+
+    #[test]
+    fn object_names() {
+        let sess = Session::builder().with_test_emitter().build();
+        sess.enter(|| -> Result {
+            let arena = solar_ast::Arena::new();
+            let mut parse = |src: &str| {
+                let name = FileName::Custom(src.to_string());
+                let mut parser =
+                    Parser::from_source_code(&sess, &arena, name, src.to_string()).unwrap();
+                let object = parser.parse_yul_file_object().map_err(|e| e.emit()).unwrap();
+                (object.children.len(), object.data.len())
+            };
+
+            let src =
+                r#"object "A" { code {} object "B" { code {} data "C" hex"00" } data "D" "x" }"#;
+            assert_eq!(parse(src), (1, 1));
+            sess.dcx.has_errors().unwrap();
+
+            parse(r#"object "" { code {} }"#);
+            assert_eq!(sess.dcx.err_count(), 1);
+            parse(r#"object "A" { code {} data "A" "x" }"#);
+            assert_eq!(sess.dcx.err_count(), 2);
+            parse(r#"object "A" { code {} object "B" { code {} } data "B" "x" }"#);
+            assert_eq!(sess.dcx.err_count(), 3);
+            Ok(())
+        })
+        .unwrap();
+    }
+}
@@ -20,7 +20,16 @@ impl super::LoweringContext<'_, '_, '_> {
                 self.linearize_contract(contract_id, &mut linearizer);
                 if linearizer.result.is_empty() {
                     let msg = "linearization of inheritance graph impossible";
-                    self.dcx().err(msg).span(self.hir.contract(contract_id).name.span).emit();
+                    let help =
+                        "list base contracts in order from \"most base-like\" to \"most derived\"";
+                    self.dcx()
+                        .err(msg)
+                        .span(self.hir.contract(contract_id).name.span)
+                        .help(help)
+                        .emit();
+                    // Don't report errors in derived contracts.
+                    self.hir.contracts[contract_id].linearized_bases =
+                        self.arena.alloc_slice_copy(&[contract_id]);
                     continue;
                 }
                 let linearized_bases = &*self.arena.alloc_slice_copy(&linearizer.result);
@@ -204,3 +213,36 @@ impl ContractLinearizer {
 // type List<T> = std::collections::LinkedList<T>;
 // #[cfg(not(feature = "nightly"))]
 type List<T> = std::collections::VecDeque<T>;
+
+#[cfg(test)]
+mod tests {
+    use crate::{hir, test_utils::with_gcx};
+
+    #[test]
+    fn diamond() {
+        let src = "\
+contract A {}
+contract B is A {}
+contract C is A {}
+contract D is B, C {}
+";
+        with_gcx(src, |gcx, _| {
+            let hir = &gcx.hir;
+            let id = |name: &str| {
+                hir.contract_ids().find(|&id| hir.contract(id).name.as_str() == name).unwrap()
+            };
+            let names = |ids: &[hir::ContractId]| {
+                ids.iter().map(|&id| hir.contract(id).name.to_string()).collect::<Vec<_>>()
+            };
+            let (a, d) = (id("A"), id("D"));
+            assert_eq!(names(hir.contract(d).linearized_bases), ["D", "C", "B", "A"]);
+            assert_eq!(names(hir.inherited_contracts(d)), ["C", "B", "A"]);
+            assert!(hir.inherited_contracts(a).is_empty());
+            let derived = hir.derived_contracts(a).collect::<Vec<_>>();
+            assert_eq!(names(&derived), ["A", "B", "C", "D"]);
+            assert_eq!(hir.derived_contracts(d).collect::<Vec<_>>(), [d]);
+            assert!(hir.is_derived_from(d, a));
+            assert!(!hir.is_derived_from(a, d));
+        });
+    }
+}
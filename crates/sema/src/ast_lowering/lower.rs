@@ -22,9 +22,8 @@ impl<'ast> super::LoweringContext<'_, 'ast, '_> {
                 self.current_source_id = id;
                 for item in ast.items.iter() {
                     match &item.kind {
-                        ast::ItemKind::Pragma(_)
-                        | ast::ItemKind::Import(_)
-                        | ast::ItemKind::Using(_) => {}
+                        ast::ItemKind::Pragma(_) | ast::ItemKind::Import(_) => {}
+                        ast::ItemKind::Using(using) => self.usings.push((id, None, using)),
                         ast::ItemKind::Contract(_)
                         | ast::ItemKind::Function(_)
                         | ast::ItemKind::Variable(_)
@@ -51,6 +50,7 @@ impl<'ast> super::LoweringContext<'_, 'ast, '_> {
             source: self.current_source_id,
             span: item.span,
             name: contract.name,
+            docs: &[],
             kind: contract.kind,
 
             // Set later.
@@ -71,7 +71,10 @@ impl<'ast> super::LoweringContext<'_, 'ast, '_> {
                 ast::ItemKind::Pragma(_)
                 | ast::ItemKind::Import(_)
                 | ast::ItemKind::Contract(_) => unreachable!("illegal item in contract body"),
-                ast::ItemKind::Using(_) => continue,
+                ast::ItemKind::Using(using) => {
+                    self.usings.push((self.current_source_id, Some(id), using));
+                    continue;
+                }
                 ast::ItemKind::Variable(_) => {
                     let hir::ItemId::Variable(id) = self.lower_item(item) else { unreachable!() };
                     items.push(hir::ItemId::Variable(id));
@@ -118,9 +121,36 @@ impl<'ast> super::LoweringContext<'_, 'ast, '_> {
             ast::ItemKind::Event(i) => hir::ItemId::Event(self.lower_event(item, i)),
         };
         self.hir_to_ast.insert(item_id, item);
+        if !item.docs.is_empty() {
+            self.lower_docs(item_id, &item.docs);
+        }
         item_id
     }
 
+    fn lower_docs(&mut self, id: hir::ItemId, docs: &[ast::DocComment]) {
+        let docs = ast::NatSpecItem::parse(docs);
+        for doc in &docs {
+            if let ast::NatSpecKind::Unknown(tag) = doc.kind {
+                let msg = format!("invalid NatSpec tag `{tag}`");
+                self.dcx().err(msg).span(doc.span).emit();
+            }
+        }
+        let docs = self.arena.alloc_slice_copy(&docs);
+        match id {
+            hir::ItemId::Contract(id) => self.hir.contracts[id].docs = docs,
+            hir::ItemId::Function(id) => self.hir.functions[id].docs = docs,
+            hir::ItemId::Variable(id) => {
+                self.hir.variables[id].docs = docs;
+                if let Some(getter) = self.hir.variables[id].getter {
+                    self.hir.functions[getter].docs = docs;
+                }
+            }
+            hir::ItemId::Event(id) => self.hir.events[id].docs = docs,
+            hir::ItemId::Error(id) => self.hir.errors[id].docs = docs,
+            hir::ItemId::Struct(_) | hir::ItemId::Enum(_) | hir::ItemId::Udvt(_) => {}
+        }
+    }
+
     fn lower_function(
         &mut self,
         item: &ast::Item<'_>,
@@ -143,6 +173,7 @@ impl<'ast> super::LoweringContext<'_, 'ast, '_> {
             contract: self.current_contract_id,
             span: item.span,
             name,
+            docs: &[],
             kind,
             gettee: None,
             modifiers: &[],
@@ -219,6 +250,7 @@ impl<'ast> super::LoweringContext<'_, 'ast, '_> {
             contract: self.current_contract_id,
             span: item.span,
             name,
+            docs: &[],
             parameters: &[],
         })
     }
@@ -231,6 +263,7 @@ impl<'ast> super::LoweringContext<'_, 'ast, '_> {
             contract: self.current_contract_id,
             span: item.span,
             name,
+            docs: &[],
             anonymous,
             parameters: &[],
         })
@@ -248,6 +281,7 @@ pub(super) fn lower_variable_partial(
 ) -> hir::VariableId {
     // handled later: ty, override_, initializer
     let ast::VariableDefinition {
+        id: _,
         span,
         ty: _,
         visibility,
@@ -266,6 +300,7 @@ pub(super) fn lower_variable_partial(
         kind,
         ty: hir::Type::DUMMY,
         name,
+        docs: &[],
         visibility,
         mutability,
         data_location,
@@ -291,6 +326,7 @@ fn generate_partial_getter(hir: &mut hir::Hir<'_>, id: hir::VariableId) -> hir::
         kind,
         ty: _,
         name,
+        docs,
         visibility,
         mutability: _,
         data_location,
@@ -310,6 +346,7 @@ fn generate_partial_getter(hir: &mut hir::Hir<'_>, id: hir::VariableId) -> hir::
         contract,
         span,
         name,
+        docs,
         kind: ast::FunctionKind::Function,
         visibility: ast::Visibility::Public,
         state_mutability: ast::StateMutability::View,
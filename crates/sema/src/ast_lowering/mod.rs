@@ -1,6 +1,6 @@
 use crate::{
     hir::{self, Hir},
-    ParsedSources,
+    lints, ParsedSources,
 };
 use solar_ast as ast;
 use solar_data_structures::{
@@ -8,7 +8,7 @@ use solar_data_structures::{
     map::FxHashMap,
     trustme,
 };
-use solar_interface::{diagnostics::DiagCtxt, Session};
+use solar_interface::{diagnostics::DiagCtxt, lint::LintLevel, Session, Span};
 
 mod lower;
 
@@ -55,6 +55,8 @@ struct LoweringContext<'sess, 'ast, 'hir> {
     hir: Hir<'hir>,
     /// Mapping from Hir ItemId to AST Item. Does not include function parameters or bodies.
     hir_to_ast: FxHashMap<hir::ItemId, &'ast ast::Item<'ast>>,
+    /// The `using` directives, which are not lowered to HIR, and the scope they are in.
+    usings: Vec<(hir::SourceId, Option<hir::ContractId>, &'ast ast::UsingDirective<'ast>)>,
 
     /// Current source being lowered.
     current_source_id: hir::SourceId,
@@ -62,6 +64,8 @@ struct LoweringContext<'sess, 'ast, 'hir> {
     current_contract_id: Option<hir::ContractId>,
 
     resolver: SymbolResolver<'sess>,
+    /// The declarations referred to by the resolved names. See [`SymbolResolver::references`].
+    references: Vec<(Span, Res)>,
 }
 
 impl<'sess, 'hir> LoweringContext<'sess, '_, 'hir> {
@@ -73,7 +77,9 @@ impl<'sess, 'hir> LoweringContext<'sess, '_, 'hir> {
             current_source_id: hir::SourceId::MAX,
             current_contract_id: None,
             hir_to_ast: FxHashMap::default(),
-            resolver: SymbolResolver::new(&sess.dcx),
+            usings: Vec::new(),
+            resolver: SymbolResolver::new(&sess.dcx, sess.legacy_syntax, record_references(sess)),
+            references: Vec::new(),
         }
     }
 
@@ -92,12 +98,24 @@ impl<'sess, 'hir> LoweringContext<'sess, '_, 'hir> {
     fn finish(self) -> (Hir<'hir>, SymbolResolver<'sess>) {
         // NOTE: Explicit scope to drop `self` before the span.
         {
-            let this = self;
+            let mut this = self;
+            this.resolver.set_references(this.references);
             (this.hir, this.resolver)
         }
     }
 }
 
+/// Returns whether to record the declarations that names refer to.
+///
+/// The unused variable and import lints are computed from them, so they are also recorded when
+/// these lints are enabled.
+fn record_references(sess: &Session) -> bool {
+    sess.record_references
+        || [&lints::UNUSED_VARIABLES, &lints::UNUSED_IMPORTS]
+            .into_iter()
+            .any(|lint| sess.lint_level(lint) != LintLevel::Allow)
+}
+
 #[inline]
 #[track_caller]
 fn get_two_mut_idx<I: Idx, T>(sl: &mut IndexVec<I, T>, idx_1: I, idx_2: I) -> (&mut T, &mut T) {
@@ -2,13 +2,13 @@ use crate::{builtins::Builtin, hir, ParsedSources};
 use solar_ast as ast;
 use solar_data_structures::{
     index::{Idx, IndexVec},
-    map::{FxIndexMap, IndexEntry},
+    map::{FxHashMap, FxIndexMap, IndexEntry},
     smallvec::SmallVec,
     BumpExt,
 };
 use solar_interface::{
-    diagnostics::{DiagCtxt, ErrorGuaranteed},
-    sym, Ident, Session, Span, Symbol,
+    diagnostics::{DiagCtxt, DiagnosticBuilder, ErrorGuaranteed},
+    error_code, kw, sym, Ident, Session, Span, Symbol,
 };
 use std::{fmt, sync::atomic::AtomicUsize};
 
@@ -37,6 +37,7 @@ impl super::LoweringContext<'_, '_, '_> {
 
     #[instrument(level = "debug", skip_all)]
     pub(super) fn perform_imports(&mut self, sources: &ParsedSources<'_>) {
+        let mut imports = Vec::new();
         for (source_id, source) in self.hir.sources_enumerated() {
             for &(item_id, import_id) in source.imports {
                 let import_item = &sources[source_id].ast.as_ref().unwrap().items[item_id];
@@ -54,22 +55,32 @@ impl super::LoweringContext<'_, '_, '_> {
                 match import.items {
                     ast::ImportItems::Plain(alias) | ast::ImportItems::Glob(alias) => {
                         if let Some(alias) = alias {
-                            let _ = source_scope.declare_res(
-                                self.sess,
-                                &self.hir,
-                                alias,
-                                Res::Namespace(import_id),
-                            );
+                            let res = Res::Namespace(import_id);
+                            let _ = source_scope.declare_res(self.sess, &self.hir, alias, res);
+                            imports.push(Import {
+                                source: source_id,
+                                span: import_item.span,
+                                name: Some(alias),
+                                res: vec![res],
+                            });
                         } else if let Some(import_scope) = import_scope {
                             // Import all declarations.
+                            let mut res = Vec::new();
                             for (&name, decls) in &import_scope.declarations {
                                 for decl in decls {
                                     // Re-span to the import statement.
                                     let mut decl = *decl;
                                     decl.span = import_item.span;
                                     let _ = source_scope.declare(self.sess, &self.hir, name, decl);
+                                    res.push(decl.res);
                                 }
                             }
+                            imports.push(Import {
+                                source: source_id,
+                                span: import_item.span,
+                                name: None,
+                                res,
+                            });
                         } else {
                             // `source_id == import_id` -> `import self::*;`: nothing to do.
                         }
@@ -77,12 +88,58 @@ impl super::LoweringContext<'_, '_, '_> {
                     ast::ImportItems::Aliases(ref aliases) => {
                         for &(import, alias) in aliases.iter() {
                             let name = alias.unwrap_or(import);
+                            let resolved = match import_scope {
+                                Some(import_scope) => import_scope.resolve(import),
+                                None => source_scope.resolve(import),
+                            };
+                            if let Some(resolved) = resolved {
+                                imports.push(Import {
+                                    source: source_id,
+                                    span: import_item.span,
+                                    name: Some(name),
+                                    res: resolved.iter().map(|decl| decl.res).collect(),
+                                });
+                            }
+                            if resolved.is_none() {
+                                let cycle = import_cycle(&self.hir, source_id, import_id);
+                                if !cycle.is_empty() {
+                                    let mut err = declaration_not_found(self.sess, source, import);
+                                    for (from, item_id, to) in cycle {
+                                        let items = &sources[from].ast.as_ref().unwrap().items;
+                                        let file_name = |id| {
+                                            let file = &self.hir.source(id).file;
+                                            self.sess
+                                                .source_map()
+                                                .filename_for_diagnostics(&file.name)
+                                        };
+                                        let msg = format!(
+                                            "{} imports {}",
+                                            file_name(from),
+                                            file_name(to)
+                                        );
+                                        err = err.span_note(items[item_id].span, msg);
+                                    }
+                                    let help = format!(
+                                        "the sources form an import cycle; import `{import}` from \
+                                         the source that declares it"
+                                    );
+                                    let guar = err.help(help).emit();
+                                    let _ = source_scope.declare_res(
+                                        self.sess,
+                                        &self.hir,
+                                        name,
+                                        Res::Err(guar),
+                                    );
+                                    continue;
+                                }
+                            }
                             if let Some(import_scope) = import_scope {
                                 Self::perform_alias_import(
                                     self.sess,
                                     &self.hir,
                                     source,
                                     source_scope,
+                                    &mut self.references,
                                     name,
                                     import,
                                     import_scope.resolve(import),
@@ -93,6 +150,7 @@ impl super::LoweringContext<'_, '_, '_> {
                                     &self.hir,
                                     source,
                                     source_scope,
+                                    &mut self.references,
                                     name,
                                     import,
                                     source_scope.resolve_cloned(import),
@@ -103,6 +161,7 @@ impl super::LoweringContext<'_, '_, '_> {
                 }
             }
         }
+        self.resolver.imports = imports;
     }
 
     /// Separate function to avoid cloning `resolved` when the import is not a self-import.
@@ -111,6 +170,7 @@ impl super::LoweringContext<'_, '_, '_> {
         hir: &hir::Hir<'_>,
         source: &hir::Source<'_>,
         source_scope: &mut Declarations,
+        references: &mut Vec<(Span, Res)>,
         name: Ident,
         import: Ident,
         resolved: Option<impl AsRef<[Declaration]>>,
@@ -118,6 +178,8 @@ impl super::LoweringContext<'_, '_, '_> {
         if let Some(resolved) = resolved {
             let resolved = resolved.as_ref();
             debug_assert!(!resolved.is_empty());
+            let resolved_items = resolved.iter().filter(|decl| !decl.res.is_err());
+            references.extend(resolved_items.map(|decl| (import.span, decl.res)));
             for decl in resolved {
                 // Re-span to the import name.
                 let mut decl = *decl;
@@ -125,11 +187,7 @@ impl super::LoweringContext<'_, '_, '_> {
                 let _ = source_scope.declare(sess, hir, name.name, decl);
             }
         } else {
-            let msg = format!(
-                "declaration `{import}` not found in {}",
-                sess.source_map().filename_for_diagnostics(&source.file.name)
-            );
-            let guar = sess.dcx.err(msg).span(import.span).emit();
+            let guar = declaration_not_found(sess, source, import).emit();
             let _ = source_scope.declare_res(sess, hir, name, Res::Err(guar));
         }
     }
@@ -179,10 +237,12 @@ impl super::LoweringContext<'_, '_, '_> {
             let mut bases = SmallVec::<[_; 8]>::new();
             for base in ast_contract.bases.iter() {
                 let name = &base.name;
-                let Ok(base_id) = self
-                    .resolver
-                    .resolve_path_as::<hir::ContractId>(base.name, &scopes, "contract")
-                else {
+                let Ok(base_id) = self.resolver.resolve_path_as::<hir::ContractId>(
+                    base.name,
+                    &scopes,
+                    "contract",
+                    &mut self.references,
+                ) else {
                     continue;
                 };
                 if base_id == contract_id {
@@ -254,6 +314,7 @@ impl<'hir> super::LoweringContext<'_, '_, 'hir> {
                     arena: self.arena,
                     hir: &mut self.hir,
                     resolver: &self.resolver,
+                    references: &mut self.references,
                     next_id,
                 }
             };
@@ -323,8 +384,12 @@ impl<'hir> super::LoweringContext<'_, '_, 'hir> {
                     } else {
                         "modifier"
                     };
-                    let Ok(id) = self.resolver.resolve_path_as(modifier.name, &scopes, expected)
-                    else {
+                    let Ok(id) = self.resolver.resolve_path_as(
+                        modifier.name,
+                        &scopes,
+                        expected,
+                        &mut self.references,
+                    ) else {
                         continue;
                     };
                     match id {
@@ -348,29 +413,10 @@ impl<'hir> super::LoweringContext<'_, '_, 'hir> {
                 self.arena.alloc_smallvec(modifiers)
             };
 
-            let func = self.hir.function(id);
-            self.hir.functions[id].overrides = {
-                let mut overrides = SmallVec::<[_; 8]>::new();
-                if let Some(ov) = &ast_func.header.override_ {
-                    for path in ov.paths.iter() {
-                        let Ok(id) = self.resolver.resolve_path_as(path, &scopes, "contract")
-                        else {
-                            continue;
-                        };
-                        // TODO: Move to override checker.
-                        let Some(c) = func.contract else {
-                            self.dcx().err("free functions cannot override").span(ov.span).emit();
-                            continue;
-                        };
-                        if !self.hir.contract(c).linearized_bases[1..].contains(&id) {
-                            self.dcx().err("override is not a base contract").span(ov.span).emit();
-                            continue;
-                        }
-                        overrides.push(id);
-                    }
-                }
-                self.arena.alloc_smallvec(overrides)
-            };
+            let contract = self.hir.function(id).contract;
+            let overrides =
+                self.resolve_overrides(ast_func.header.override_.as_ref(), contract, &scopes);
+            self.hir.functions[id].overrides = overrides;
 
             let mut cx = ResolveContext::new(self, scopes, next_id, Some(id));
             cx.hir.functions[id].parameters =
@@ -386,6 +432,38 @@ impl<'hir> super::LoweringContext<'_, '_, 'hir> {
         for id in self.hir.variable_ids().skip(normal_vars) {
             self.resolve_var(id, next_id);
         }
+
+        // `using` directives are not lowered yet, but record the names they refer to.
+        let usings = std::mem::take(&mut self.usings);
+        if self.resolver.record_references {
+            for (source, contract, using) in usings {
+                self.record_using_references(source, contract, using);
+            }
+        }
+    }
+
+    /// Records the declarations referred to by the paths in a `using` directive.
+    ///
+    /// Paths that cannot be resolved are ignored.
+    fn record_using_references(
+        &mut self,
+        source: hir::SourceId,
+        contract: Option<hir::ContractId>,
+        using: &ast::UsingDirective<'_>,
+    ) {
+        let scopes = SymbolResolverScopes::new_in(source, contract);
+        let mut record = |path: &ast::PathSlice| {
+            if let Ok(decls) = self.resolver.resolve_paths(path, &scopes) {
+                self.resolver.record_references(path, &scopes, decls, &mut self.references);
+            }
+        };
+        match &using.list {
+            ast::UsingList::Single(path) => record(path),
+            ast::UsingList::Multiple(paths) => paths.iter().for_each(|(path, _)| record(path)),
+        }
+        if let Some(ast::Type { kind: ast::TypeKind::Custom(path), .. }) = &using.ty {
+            record(path);
+        }
     }
 
     fn resolve_var(&mut self, id: hir::VariableId, next_id: &AtomicUsize) {
@@ -398,6 +476,13 @@ impl<'hir> super::LoweringContext<'_, '_, 'hir> {
         let ast::ItemKind::Variable(ast_var) = &ast_item.kind else { unreachable!() };
 
         let scopes = SymbolResolverScopes::new_in(var.source, var.contract);
+        let (contract, getter) = (var.contract, var.getter);
+        let overrides = self.resolve_overrides(ast_var.override_.as_ref(), contract, &scopes);
+        self.hir.variables[id].overrides = overrides;
+        if let Some(getter) = getter {
+            self.hir.functions[getter].overrides = overrides;
+        }
+
         let mut cx = ResolveContext::new(self, scopes, next_id, None);
         let init = ast_var.initializer.as_deref().map(|init| cx.lower_expr(init));
         let ty = cx.lower_type(&ast_var.ty);
@@ -405,6 +490,34 @@ impl<'hir> super::LoweringContext<'_, '_, 'hir> {
         self.hir.variables[id].ty = ty;
     }
 
+    /// Resolves the contracts in an `override(...)` specifier.
+    ///
+    /// Consistency with the overridden functions is checked later, in `typeck`.
+    fn resolve_overrides(
+        &mut self,
+        override_: Option<&ast::Override<'_>>,
+        contract: Option<hir::ContractId>,
+        scopes: &SymbolResolverScopes,
+    ) -> &'hir [hir::ContractId] {
+        let Some(ov) = override_ else { return &[] };
+        let mut overrides = SmallVec::<[_; 8]>::new();
+        for path in ov.paths.iter() {
+            let Ok(id) =
+                self.resolver.resolve_path_as(path, scopes, "contract", &mut self.references)
+            else {
+                continue;
+            };
+            // Free functions are reported in the override checker.
+            let Some(c) = contract else { continue };
+            if !self.hir.contract(c).linearized_bases[1..].contains(&id) {
+                self.dcx().err("override is not a base contract").span(ov.span).emit();
+                continue;
+            }
+            overrides.push(id);
+        }
+        self.arena.alloc_smallvec(overrides)
+    }
+
     /// Resolves a getter function.
     ///
     /// # Examples
@@ -550,6 +663,7 @@ impl<'hir> super::LoweringContext<'_, '_, 'hir> {
                         let decl_name = Ident::new(sym::__tmp_struct, ast_var.span);
                         let mut decl_var = self.mk_var_stmt(id, span, ret_ty.clone(), decl_name);
                         decl_var.data_location = Some(hir::DataLocation::Storage);
+                        decl_var.initializer = Some(expr);
                         let decl_id = self.hir.variables.push(decl_var);
                         let decl_stmt = mk_stmt(hir::StmtKind::DeclSingle(decl_id));
 
@@ -626,6 +740,7 @@ struct ResolveContext<'sess, 'hir, 'a> {
     arena: &'hir hir::Arena,
     hir: &'a mut hir::Hir<'hir>,
     resolver: &'a SymbolResolver<'sess>,
+    references: &'a mut Vec<(Span, Res)>,
     scopes: SymbolResolverScopes,
     function_id: Option<hir::FunctionId>,
     next_id: &'a AtomicUsize,
@@ -643,6 +758,7 @@ impl<'sess, 'hir, 'a> ResolveContext<'sess, 'hir, 'a> {
             arena: lcx.arena,
             hir: &mut lcx.hir,
             resolver: &lcx.resolver,
+            references: &mut lcx.references,
             scopes,
             function_id,
             next_id,
@@ -664,24 +780,21 @@ impl<'sess, 'hir, 'a> ResolveContext<'sess, 'hir, 'a> {
         }
     }
 
-    fn resolve_paths(
-        &'a self,
-        path: &ast::PathSlice,
-    ) -> Result<&'a [Declaration], ErrorGuaranteed> {
-        self.resolver.resolve_paths(path, &self.scopes).map_err(self.resolver.emit_resolver_error())
-    }
-
-    fn resolve_path(&self, path: &ast::PathSlice) -> Result<&'hir [Res], ErrorGuaranteed> {
-        self.resolve_paths(path)
-            .map(|decls| &*self.arena.alloc_slice_fill_iter(decls.iter().map(|decl| decl.res)))
+    fn resolve_path(&mut self, path: &ast::PathSlice) -> Result<&'hir [Res], ErrorGuaranteed> {
+        let decls = self
+            .resolver
+            .resolve_paths(path, &self.scopes)
+            .map_err(self.resolver.emit_resolver_error())?;
+        self.resolver.record_references(path, &self.scopes, decls, self.references);
+        Ok(self.arena.alloc_slice_fill_iter(decls.iter().map(|decl| decl.res)))
     }
 
     fn resolve_path_as<T: TryFrom<Res>>(
-        &self,
+        &mut self,
         path: &ast::PathSlice,
         description: &str,
     ) -> Result<T, ErrorGuaranteed> {
-        self.resolver.resolve_path_as(path, &self.scopes, description)
+        self.resolver.resolve_path_as(path, &self.scopes, description, self.references)
     }
 
     /// Lowers the given statements by first entering a new scope.
@@ -701,17 +814,24 @@ impl<'sess, 'hir, 'a> ResolveContext<'sess, 'hir, 'a> {
     fn lower_stmt_full(&mut self, stmt: &ast::Stmt<'_>) -> hir::Stmt<'hir> {
         let kind = match &stmt.kind {
             ast::StmtKind::DeclSingle(var) => {
+                self.check_legacy_var(&var.ty);
                 match self.lower_variable(var, hir::VarKind::Statement) {
                     (id, Ok(())) => hir::StmtKind::DeclSingle(id),
                     (_, Err(guar)) => hir::StmtKind::Err(guar),
                 }
             }
-            ast::StmtKind::DeclMulti(vars, expr) => hir::StmtKind::DeclMulti(
-                self.arena.alloc_slice_fill_iter(vars.iter().map(|var| {
-                    var.as_ref().map(|var| self.lower_variable(var, hir::VarKind::Statement).0)
-                })),
-                self.lower_expr(expr),
-            ),
+            ast::StmtKind::DeclMulti(vars, expr) => {
+                // All the variables of a legacy `var (a, , b)` declaration share the same type.
+                if let Some(var) = vars.iter().flatten().next() {
+                    self.check_legacy_var(&var.ty);
+                }
+                hir::StmtKind::DeclMulti(
+                    self.arena.alloc_slice_fill_iter(vars.iter().map(|var| {
+                        var.as_ref().map(|var| self.lower_variable(var, hir::VarKind::Statement).0)
+                    })),
+                    self.lower_expr(expr),
+                )
+            }
             ast::StmtKind::Assembly(_) => hir::StmtKind::Err(
                 // self.dcx().err("assembly is not yet implemented").span(stmt.span).emit(),
                 ErrorGuaranteed::new_unchecked(),
@@ -742,7 +862,7 @@ impl<'sess, 'hir, 'a> ResolveContext<'sess, 'hir, 'a> {
                 self.lower_stmt(then),
                 else_.as_deref().map(|stmt| self.lower_stmt(stmt)),
             ),
-            ast::StmtKind::Try(ast::StmtTry { expr, returns, block, catch }) => {
+            ast::StmtKind::Try(ast::StmtTry { expr, span: _, returns, block, catch }) => {
                 hir::StmtKind::Try(self.arena.alloc(hir::StmtTry {
                     expr: self.lower_expr_full(expr),
                     returns: self.lower_variables(returns, hir::VarKind::TryCatch),
@@ -751,7 +871,7 @@ impl<'sess, 'hir, 'a> ResolveContext<'sess, 'hir, 'a> {
                         hir::CatchClause {
                             name: catch.name,
                             args: self.lower_variables(catch.args, hir::VarKind::TryCatch),
-                            block: self.lower_block(catch.block),
+                            block: self.lower_block(&catch.block),
                         }
                     })),
                 }))
@@ -830,10 +950,8 @@ impl<'sess, 'hir, 'a> ResolveContext<'sess, 'hir, 'a> {
             // {
             //     <init>;
             //     loop {
-            //         if (<cond>) {
-            //             { <body> }
-            //             <next>;
-            //         } else break;
+            //         if (<cond>) { <body> } else break;
+            //         <next>;
             //     }
             // }
             ast::StmtKind::For { init, cond, next, body } => {
@@ -844,15 +962,6 @@ impl<'sess, 'hir, 'a> ResolveContext<'sess, 'hir, 'a> {
                         this.in_scope_if(next.is_some(), |this| this.lower_stmt_full(body));
                     let next = this.lower_expr_opt(next.as_deref());
 
-                    // <body> = { <body>; <next>; }
-                    if let Some(next) = next {
-                        let next = hir::Stmt { span: next.span, kind: hir::StmtKind::Expr(next) };
-                        body = hir::Stmt {
-                            span: body.span,
-                            kind: hir::StmtKind::Block(this.arena.alloc_array([body, next])),
-                        };
-                    }
-
                     // <body> = if (<cond>) { <body> } else break;
                     if let Some(cond) = cond {
                         let break_stmt =
@@ -863,8 +972,16 @@ impl<'sess, 'hir, 'a> ResolveContext<'sess, 'hir, 'a> {
                         };
                     }
 
-                    let mut kind =
-                        hir::StmtKind::Loop(self.arena.alloc_as_slice(body), hir::LoopSource::For);
+                    // `next` is the last statement of the loop so that `continue` jumps to it.
+                    let body = match next {
+                        Some(next) => {
+                            let next =
+                                hir::Stmt { span: next.span, kind: hir::StmtKind::Expr(next) };
+                            &*this.arena.alloc_array([body, next])
+                        }
+                        None => this.arena.alloc_as_slice(body),
+                    };
+                    let mut kind = hir::StmtKind::Loop(body, hir::LoopSource::For);
 
                     if let Some(init) = init {
                         let s = hir::Stmt { span, kind };
@@ -914,14 +1031,10 @@ impl<'sess, 'hir, 'a> ResolveContext<'sess, 'hir, 'a> {
                 hir::ExprKind::CallOptions(self.lower_expr(callee), self.lower_named_args(options))
             }
             ast::ExprKind::Delete(expr) => hir::ExprKind::Delete(self.lower_expr(expr)),
-            ast::ExprKind::Ident(name) => {
-                match self.resolve_paths(ast::PathSlice::from_ref(name)) {
-                    Ok(decls) => hir::ExprKind::Ident(
-                        self.arena.alloc_slice_fill_iter(decls.iter().map(|decl| decl.res)),
-                    ),
-                    Err(guar) => hir::ExprKind::Err(guar),
-                }
-            }
+            ast::ExprKind::Ident(name) => match self.resolve_path(ast::PathSlice::from_ref(name)) {
+                Ok(res) => hir::ExprKind::Ident(res),
+                Err(guar) => hir::ExprKind::Err(guar),
+            },
             ast::ExprKind::Index(expr, index) => match index {
                 ast::IndexKind::Index(index) => hir::ExprKind::Index(
                     self.lower_expr(expr),
@@ -933,8 +1046,12 @@ impl<'sess, 'hir, 'a> ResolveContext<'sess, 'hir, 'a> {
                     self.lower_expr_opt(end.as_deref()),
                 ),
             },
-            ast::ExprKind::Lit(lit, _) => {
-                hir::ExprKind::Lit(self.arena.literals.alloc(ast::Lit::clone(lit)))
+            ast::ExprKind::Lit(lit, sub_denomination) => {
+                let mut lit = ast::Lit::clone(lit);
+                if let Some(sub_denomination) = *sub_denomination {
+                    lit.kind.apply_sub_denomination(sub_denomination);
+                }
+                hir::ExprKind::Lit(self.arena.literals.alloc(lit))
             }
             ast::ExprKind::Member(expr, member) => {
                 hir::ExprKind::Member(self.lower_expr(expr), *member)
@@ -1005,6 +1122,7 @@ impl<'sess, 'hir, 'a> ResolveContext<'sess, 'hir, 'a> {
                     value_name: mapping.value_name,
                 }))
             }
+            ast::TypeKind::Custom(_) if self.is_legacy_var(ty) => hir::TypeKind::Infer,
             ast::TypeKind::Custom(path) => match self.resolve_path_as(path, "item") {
                 Ok(id) => hir::TypeKind::Custom(id),
                 Err(guar) => hir::TypeKind::Err(guar),
@@ -1013,6 +1131,29 @@ impl<'sess, 'hir, 'a> ResolveContext<'sess, 'hir, 'a> {
         hir::Type { kind, span: ty.span }
     }
 
+    /// Returns `true` if the given type is the type of a legacy `var` declaration.
+    fn is_legacy_var(&self, ty: &ast::Type<'_>) -> bool {
+        self.sess.legacy_syntax
+            && matches!(
+                &ty.kind,
+                ast::TypeKind::Custom(path)
+                    if path.get_ident().is_some_and(|ident| ident.name == kw::Var)
+            )
+    }
+
+    /// Warns if the given type is the type of a legacy `var` declaration.
+    fn check_legacy_var(&self, ty: &ast::Type<'_>) {
+        if self.is_legacy_var(ty) {
+            self.sess
+                .dcx
+                .warn("the types of `var` declarations are not inferred")
+                .span(ty.span)
+                .note("uses of the variable are not type checked")
+                .help("declare the type of the variable explicitly")
+                .emit();
+        }
+    }
+
     fn next_id<I: Idx>(&self) -> I {
         I::from_usize(self.next_id.fetch_add(1, std::sync::atomic::Ordering::Relaxed))
     }
@@ -1063,19 +1204,34 @@ pub(crate) struct SymbolResolver<'sess> {
     dcx: &'sess DiagCtxt,
     pub(crate) source_scopes: IndexVec<hir::SourceId, Declarations>,
     pub(crate) contract_scopes: IndexVec<hir::ContractId, Declarations>,
-    global_builtin_scope: Declarations,
+    /// The declarations referred to by the names in the sources, sorted by the span of the name.
+    ///
+    /// Names referring to overloaded functions have an entry for each overload.
+    pub(crate) references: Vec<(Span, Res)>,
+    /// The spans of the names referring to each declaration, sorted. The reverse of
+    /// [`references`](Self::references).
+    pub(crate) uses: FxHashMap<Res, Vec<Span>>,
+    /// The names declared by import directives, in source order.
+    pub(crate) imports: Vec<Import>,
+    pub(crate) global_builtin_scope: Declarations,
     builtin_members_scopes: Box<[Option<Declarations>; Builtin::COUNT]>,
+    /// Whether to record [`references`](Self::references).
+    pub(crate) record_references: bool,
 }
 
 impl<'sess> SymbolResolver<'sess> {
-    pub(crate) fn new(dcx: &'sess DiagCtxt) -> Self {
-        let (global_builtin_scope, builtin_members_scopes) = crate::builtins::scopes();
+    pub(crate) fn new(dcx: &'sess DiagCtxt, legacy_syntax: bool, record_references: bool) -> Self {
+        let (global_builtin_scope, builtin_members_scopes) = crate::builtins::scopes(legacy_syntax);
         Self {
             dcx,
             source_scopes: IndexVec::new(),
             contract_scopes: IndexVec::new(),
+            references: Vec::new(),
+            uses: FxHashMap::default(),
+            imports: Vec::new(),
             global_builtin_scope,
             builtin_members_scopes,
+            record_references,
         }
     }
 
@@ -1084,8 +1240,10 @@ impl<'sess> SymbolResolver<'sess> {
         path: &ast::PathSlice,
         scopes: &SymbolResolverScopes,
         description: &str,
+        references: &mut Vec<(Span, Res)>,
     ) -> Result<T, ErrorGuaranteed> {
         let decl = self.resolve_path(path, scopes).map_err(self.emit_resolver_error())?;
+        self.record_references(path, scopes, &[decl], references);
         if let Res::Err(guar) = decl.res {
             return Err(guar);
         }
@@ -1094,7 +1252,13 @@ impl<'sess> SymbolResolver<'sess> {
     }
 
     fn emit_resolver_error(&self) -> impl Fn(ResolverError) -> ErrorGuaranteed + '_ {
-        move |e| self.dcx.err(e.format()).span(e.span()).emit()
+        move |e| {
+            let mut err = self.dcx.err(e.format()).span(e.span());
+            if let ResolverErrorKind::Unresolved = e.kind {
+                err = err.code(error_code!(7576));
+            }
+            err.emit()
+        }
     }
 
     fn resolve_path(
@@ -1141,6 +1305,47 @@ impl<'sess> SymbolResolver<'sess> {
         Ok(decls)
     }
 
+    /// Sets the references recorded while lowering, and indexes them by declaration.
+    pub(super) fn set_references(&mut self, mut references: Vec<(Span, Res)>) {
+        references.sort_by_key(|&(span, _)| span.lo());
+        for &(span, res) in &references {
+            self.uses.entry(res).or_default().push(span);
+        }
+        self.references = references;
+    }
+
+    /// Records the declarations referred to by each segment of a resolved path, given the
+    /// declarations of the whole path.
+    ///
+    /// Builtins have no declaration, and are not recorded.
+    fn record_references(
+        &self,
+        path: &ast::PathSlice,
+        scopes: &SymbolResolverScopes,
+        decls: &[Declaration],
+        references: &mut Vec<(Span, Res)>,
+    ) {
+        if !self.record_references {
+            return;
+        }
+        let mut record = |segment: &Ident, decls: &[Declaration]| {
+            references.extend(
+                decls
+                    .iter()
+                    .filter(|decl| matches!(decl.res, Res::Item(_) | Res::Namespace(_)))
+                    .map(|decl| (segment.span, decl.res)),
+            );
+        };
+        let segments = path.segments();
+        for i in 0..segments.len() - 1 {
+            let prefix = ast::PathSlice::from_slice(&segments[..=i]);
+            if let Ok(decls) = self.resolve_paths(prefix, scopes) {
+                record(&segments[i], decls);
+            }
+        }
+        record(path.last(), decls);
+    }
+
     fn resolve_name_raw<'a>(
         &'a self,
         name: Ident,
@@ -1335,6 +1540,19 @@ impl Declarations {
     }
 }
 
+/// The names declared in a source by an import directive. See [`SymbolResolver::imports`].
+#[derive(Debug)]
+pub(crate) struct Import {
+    /// The source containing the import directive.
+    pub(crate) source: hir::SourceId,
+    /// The span of the import directive.
+    pub(crate) span: Span,
+    /// The declared name, or `None` if all the names of the imported source are declared.
+    pub(crate) name: Option<Ident>,
+    /// The declarations of the imported names.
+    pub(crate) res: Vec<Res>,
+}
+
 #[derive(Clone, Copy, Debug)]
 pub(crate) struct Declaration {
     pub(crate) res: Res,
@@ -1359,6 +1577,60 @@ impl PartialEq for Declaration {
 
 impl Eq for Declaration {}
 
+fn declaration_not_found<'a>(
+    sess: &'a Session,
+    source: &hir::Source<'_>,
+    import: Ident,
+) -> DiagnosticBuilder<'a, ErrorGuaranteed> {
+    let msg = format!(
+        "declaration `{import}` not found in {}",
+        sess.source_map().filename_for_diagnostics(&source.file.name)
+    );
+    sess.dcx.err(msg).code(error_code!(2904)).span(import.span)
+}
+
+/// Returns the import directives through which `to` imports `from`, transitively, starting with the
+/// one through which `from` imports `to`, as `(importer, import item, imported)`.
+///
+/// Returns an empty list if `to` does not import `from`, or if they are the same source.
+fn import_cycle(
+    hir: &hir::Hir<'_>,
+    from: hir::SourceId,
+    to: hir::SourceId,
+) -> Vec<(hir::SourceId, ast::ItemId, hir::SourceId)> {
+    if from == to {
+        return Vec::new();
+    }
+    let Some(&(first, _)) = hir.source(from).imports.iter().find(|&&(_, id)| id == to) else {
+        return Vec::new();
+    };
+
+    // Breadth-first search, to find the shortest cycle.
+    let mut parents = FxHashMap::default();
+    let mut queue = std::collections::VecDeque::from([to]);
+    while let Some(id) = queue.pop_front() {
+        if id == from {
+            let mut cycle = Vec::new();
+            let mut id = from;
+            while id != to {
+                let (parent, item_id) = parents[&id];
+                cycle.push((parent, item_id, id));
+                id = parent;
+            }
+            cycle.push((from, first, to));
+            cycle.reverse();
+            return cycle;
+        }
+        for &(item_id, import) in hir.source(id).imports {
+            if import != to && !parents.contains_key(&import) {
+                parents.insert(import, (id, item_id));
+                queue.push_back(import);
+            }
+        }
+    }
+    Vec::new()
+}
+
 pub(super) fn report_conflict(
     hir: &hir::Hir<'_>,
     sess: &Session,
@@ -1368,7 +1640,10 @@ pub(super) fn report_conflict(
 ) -> ErrorGuaranteed {
     debug_assert_ne!(decl.span, previous.span);
 
-    let mut err = sess.dcx.err(format!("identifier `{name}` already declared")).span(decl.span);
+    let msg = format!("identifier `{name}` already declared");
+    // Report all the redeclarations of the same declaration in a single error.
+    let group = (name, previous.span);
+    let mut err = sess.dcx.err(msg).code(error_code!(2333)).span(decl.span).group(group);
 
     // If `previous` is coming from an import, show both the import and the real span.
     if let Res::Item(item_id) = previous.res {
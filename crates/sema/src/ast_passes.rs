@@ -2,7 +2,7 @@
 
 use solar_ast::{self as ast, visit::Visit};
 use solar_data_structures::Never;
-use solar_interface::{diagnostics::DiagCtxt, sym, Session, Span};
+use solar_interface::{diagnostics::DiagCtxt, error_code, sym, Session, Span};
 use std::ops::ControlFlow;
 
 #[instrument(name = "ast_passes", level = "debug", skip_all)]
@@ -14,6 +14,7 @@ pub(crate) fn run(sess: &Session, ast: &ast::SourceUnit<'_>) {
 #[instrument(name = "validate", level = "debug", skip_all)]
 pub fn validate(sess: &Session, ast: &ast::SourceUnit<'_>) {
     let mut validator = AstValidator::new(sess);
+    validator.version_reqs = version_reqs(ast);
     validator.visit_source_unit(ast);
 }
 
@@ -25,6 +26,44 @@ struct AstValidator<'sess, 'ast> {
     function_kind: Option<ast::FunctionKind>,
     in_unchecked_block: bool,
     in_loop_depth: u64,
+    /// The requirements of the version pragmas of the source unit, with the spans of the pragmas.
+    version_reqs: Vec<(&'ast ast::SemverReq<'ast>, Span)>,
+}
+
+/// Returns the requirements of the Solidity version pragmas of `ast`.
+fn version_reqs<'ast>(ast: &'ast ast::SourceUnit<'ast>) -> Vec<(&'ast ast::SemverReq<'ast>, Span)> {
+    ast.items
+        .iter()
+        .filter_map(|item| match &item.kind {
+            ast::ItemKind::Pragma(ast::PragmaDirective {
+                tokens: ast::PragmaTokens::Version(name, req),
+            }) if name.name == sym::solidity => Some((req, item.span)),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Returns the lowest version greater than or equal to `bound` that is allowed by all of the
+/// requirements, if any.
+fn min_allowed_version(
+    reqs: &[(&ast::SemverReq<'_>, Span)],
+    bound: ast::SemverVersion,
+) -> Option<ast::SemverVersion> {
+    let mut version = bound;
+    // Raise the version until all of the requirements allow it.
+    loop {
+        let mut raised = false;
+        for (req, _) in reqs {
+            let min = req.min_version_from(&version)?;
+            if min > version {
+                version = min;
+                raised = true;
+            }
+        }
+        if !raised {
+            return Some(version);
+        }
+    }
 }
 
 impl<'sess> AstValidator<'sess, '_> {
@@ -36,6 +75,32 @@ impl<'sess> AstValidator<'sess, '_> {
             function_kind: None,
             in_unchecked_block: false,
             in_loop_depth: 0,
+            version_reqs: Vec::new(),
+        }
+    }
+
+    /// Checks that the versions allowed by the version pragmas support `feature`, which was
+    /// introduced in the given version.
+    ///
+    /// Emits an error if none of the allowed versions support it, and a warning if only some of
+    /// them do.
+    fn check_version(&self, feature: &str, (major, minor, patch): (u32, u32, u32), span: Span) {
+        let Some(&(_, pragma_span)) = self.version_reqs.first() else { return };
+        let lowest = ast::SemverVersion::new(0, 0, 0);
+        // Pragmas that cannot be satisfied are not checked here.
+        let Some(min_version) = min_allowed_version(&self.version_reqs, lowest) else { return };
+        let required = ast::SemverVersion::new(major, minor, patch);
+        if min_version >= required {
+            return;
+        }
+        let msg = format!("{feature} require Solidity >= {required}");
+        let help = format!("require a version >= {required} in the pragma");
+        if min_allowed_version(&self.version_reqs, required.clone()).is_none() {
+            let note = format!("the version pragma only allows Solidity < {required}");
+            self.dcx().err(msg).span(span).span_note(pragma_span, note).help(help).emit();
+        } else {
+            let note = format!("the version pragma also allows Solidity {min_version}");
+            self.dcx().warn(msg).span(span).span_note(pragma_span, note).help(help).emit();
         }
     }
 
@@ -55,6 +120,13 @@ impl<'ast> Visit<'ast> for AstValidator<'_, 'ast> {
 
     fn visit_item(&mut self, item: &'ast ast::Item<'ast>) -> ControlFlow<Self::BreakValue> {
         self.span = item.span;
+        match &item.kind {
+            ast::ItemKind::Error(_) => self.check_version("custom errors", (0, 8, 4), item.span),
+            ast::ItemKind::Udvt(_) => {
+                self.check_version("user-defined value types", (0, 8, 8), item.span)
+            }
+            _ => {}
+        }
         self.walk_item(item)
     }
 
@@ -95,13 +167,12 @@ impl<'ast> Visit<'ast> for AstValidator<'_, 'ast> {
                 }
             }
             ast::PragmaTokens::Custom(name, value) => {
-                let name = name.as_str();
-                let value = value.as_ref().map(ast::IdentOrStrLit::as_str);
-                match (name, value) {
-                    ("abicoder", Some("v1" | "v2")) => {}
-                    ("experimental", Some("ABIEncoderV2")) => {}
-                    ("experimental", Some("SMTChecker")) => {}
-                    ("experimental", Some("solidity")) => {
+                let value = value.as_ref().map(ast::IdentOrStrLit::symbol);
+                match (name.name, value) {
+                    (sym::abicoder, Some(sym::v1 | sym::v2)) => {}
+                    (sym::experimental, Some(sym::ABIEncoderV2)) => {}
+                    (sym::experimental, Some(sym::SMTChecker)) => {}
+                    (sym::experimental, Some(sym::solidity)) => {
                         let msg = "experimental solidity features are not supported";
                         self.dcx().err(msg).span(self.span).emit();
                     }
@@ -139,6 +210,7 @@ impl<'ast> Visit<'ast> for AstValidator<'_, 'ast> {
                 }
             }
             ast::StmtKind::UncheckedBlock(block) => {
+                self.check_version("`unchecked` blocks", (0, 8, 0), stmt.span);
                 if self.in_unchecked_block {
                     self.dcx().err("`unchecked` blocks cannot be nested").span(stmt.span).emit();
                 }
@@ -185,6 +257,7 @@ impl<'ast> Visit<'ast> for AstValidator<'_, 'ast> {
                     if func_name == contract.name {
                         self.dcx()
                             .err("functions are not allowed to have the same name as the contract")
+                            .code(error_code!(1686))
                             .note("if you intend this to be a constructor, use `constructor(...) { ... }` to define it")
                             .span(func_name.span)
                             .emit();
@@ -202,7 +275,15 @@ impl<'ast> Visit<'ast> for AstValidator<'_, 'ast> {
         &mut self,
         using: &'ast ast::UsingDirective<'ast>,
     ) -> ControlFlow<Self::BreakValue> {
-        let ast::UsingDirective { list: _, ty, global } = using;
+        let ast::UsingDirective { list, ty, global } = using;
+        if let ast::UsingList::Multiple(paths) = list {
+            if paths.iter().any(|(_, op)| op.is_some()) {
+                self.check_version("user-defined operators", (0, 8, 19), self.span);
+            }
+        }
+        if *global {
+            self.check_version("global `using for` directives", (0, 8, 13), self.span);
+        }
         let with_typ = ty.is_some();
         if self.contract.is_none() && !with_typ {
             self.dcx()
@@ -239,3 +320,41 @@ impl<'ast> Visit<'ast> for AstValidator<'_, 'ast> {
         ControlFlow::Continue(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use solar_interface::source_map::FileName;
+
+    #[test]
+    fn version_gates() {
+        // (source, errors, warnings)
+        let tests = [
+            ("pragma solidity ^0.7.0; function f() { unchecked {} }", 1, 0),
+            ("pragma solidity ^0.8.0; function f() { unchecked {} }", 0, 0),
+            ("pragma solidity >=0.7.6 <0.9.0; function f() { unchecked {} }", 0, 1),
+            ("pragma solidity <0.5.0 || >=0.8.0; function f() { unchecked {} }", 0, 1),
+            ("pragma solidity >=0.7; pragma solidity <0.8; function f() { unchecked {} }", 1, 0),
+            ("pragma solidity >=0.8.0 <0.5.0; function f() { unchecked {} }", 0, 0),
+            ("pragma solidity >0.7.6; pragma solidity >=0.8.4; error E(); type T is uint;", 0, 1),
+            ("pragma solidity ^0.8.13; type T is uint; using {f as +} for T global;", 0, 1),
+            ("pragma solidity ^0.8.19; type T is uint; using {f as +} for T global;", 0, 0),
+            ("type T is uint; using {f as +} for T global;", 0, 0),
+        ];
+        for (src, errors, warnings) in tests {
+            let sess = Session::builder().with_test_emitter().build();
+            sess.enter(|| -> solar_interface::Result {
+                let arena = ast::Arena::new();
+                let name = FileName::Custom("test".into());
+                let mut parser =
+                    solar_parse::Parser::from_source_code(&sess, &arena, name, src.into())?;
+                let ast = parser.parse_file().map_err(|e| e.emit())?;
+                validate(&sess, &ast);
+                assert_eq!(sess.dcx.err_count(), errors, "{src:?}");
+                assert_eq!(sess.dcx.warn_count(), warnings, "{src:?}");
+                Ok(())
+            })
+            .unwrap();
+        }
+    }
+}
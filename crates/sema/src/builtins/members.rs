@@ -53,7 +53,7 @@ pub(crate) fn members_of<'gcx>(gcx: Gcx<'gcx>, ty: Ty<'gcx>) -> MemberList<'gcx>
             .collect(),
         TyKind::Type(_ty) => type_type(gcx, ty),
         TyKind::Meta(_ty) => meta(gcx, ty),
-        TyKind::Err(_guar) => Default::default(),
+        TyKind::Infer | TyKind::Err(_guar) => Default::default(),
     })
 }
 
@@ -9,8 +9,16 @@ use solar_interface::{kw, sym, Span, Symbol};
 pub(crate) mod members;
 pub use members::{Member, MemberList};
 
-pub(crate) fn scopes() -> (Declarations, Box<[Option<Declarations>; Builtin::COUNT]>) {
-    let global = declarations(Builtin::global().iter().copied());
+pub(crate) fn scopes(
+    legacy_syntax: bool,
+) -> (Declarations, Box<[Option<Declarations>; Builtin::COUNT]>) {
+    let mut global = declarations(Builtin::global().iter().copied());
+    if legacy_syntax {
+        // `now` was removed in Solidity 0.7.0 in favor of `block.timestamp`.
+        let decl =
+            Declaration { res: hir::Res::Builtin(Builtin::BlockTimestamp), span: Span::DUMMY };
+        global.declarations.entry(sym::now).or_default().push(decl);
+    }
     let members_map = Box::new(std::array::from_fn(|i| {
         Some(declarations(Builtin::from_index(i).unwrap().members()?.iter().copied()))
     }));
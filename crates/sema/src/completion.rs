@@ -0,0 +1,422 @@
+//! Completion of identifiers, for editors.
+
+use crate::{
+    ast_lowering::resolve::Declarations,
+    builtins::{Builtin, Member},
+    hir,
+    ty::{Gcx, Ty, TyKind},
+    ParsingContext,
+};
+use solar_ast::{self as ast, ElementaryType};
+use solar_data_structures::map::FxHashSet;
+use solar_interface::{source_map::FileName, BytePos, Session, Span, Symbol};
+use solar_parse::{
+    lexer::{is_id_continue, is_id_start, is_whitespace},
+    Lexer, Parser,
+};
+
+/// The kind of a [`Completion`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum CompletionKind {
+    /// A variable, parameter, or a member or builtin that is not a function nor a module.
+    Variable,
+    /// A function, including builtin functions.
+    Function,
+    /// A modifier.
+    Modifier,
+    /// A contract, interface or library.
+    Contract,
+    /// A struct.
+    Struct,
+    /// An enum.
+    Enum,
+    /// An enum variant.
+    EnumVariant,
+    /// A user-defined value type.
+    UserDefinedValueType,
+    /// An event.
+    Event,
+    /// An error.
+    Error,
+    /// An imported source, or a builtin module like `msg`.
+    Module,
+}
+
+impl CompletionKind {
+    /// Returns the closest completion item kind of the Language Server Protocol.
+    pub fn lsp_kind(self) -> u8 {
+        match self {
+            Self::Variable => 6,
+            Self::Function | Self::Modifier => 3,
+            Self::Contract | Self::Error => 7,
+            Self::Struct | Self::UserDefinedValueType => 22,
+            Self::Enum => 13,
+            Self::EnumVariant => 20,
+            Self::Event => 23,
+            Self::Module => 9,
+        }
+    }
+}
+
+/// A completion candidate. See [`completions`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Completion {
+    /// The name to complete.
+    pub label: String,
+    /// The kind of the declaration.
+    pub kind: CompletionKind,
+}
+
+/// The identifier that replaces the name being completed, so that the source can be parsed.
+const PLACEHOLDER: &str = "__solar_completion";
+
+/// Returns the completions of the name at the given byte offset in `src`, sorted by label.
+///
+/// After a `.`, the completions are the members of the type of the receiver, which must be a chain
+/// of names like `a.b`; otherwise, they are the local variables declared before the offset in the
+/// enclosing function, the declarations of the enclosing contract, including inherited ones, and of
+/// the enclosing source, including imported ones, and the global builtins. Only the names that
+/// start with the part of the name before the offset are returned.
+///
+/// The source is incomplete while being edited, so the name is replaced with a placeholder, with a
+/// `;` after it or with the rest of its line blanked if necessary, until the source parses. If it
+/// still does not parse, its line is blanked out, which hides the declarations on it. The source is
+/// then analyzed with [`parse_and_lower_with`](crate::parse_and_lower_with), along with the files
+/// it imports, which are loaded with `pcx`. The diagnostics of the analysis are discarded.
+///
+/// `name` is the name of the source file, which must not be loaded in the source map yet.
+///
+/// Must be called within the session of `pcx`.
+pub fn completions(
+    mut pcx: ParsingContext<'_>,
+    name: FileName,
+    src: &str,
+    offset: usize,
+) -> Vec<Completion> {
+    let sess = pcx.sess;
+    let Some(before) = src.get(..offset) else { return Vec::new() };
+    let start = before.trim_end_matches(is_id_continue).len();
+    let prefix = &src[start..offset];
+    let receiver = match src[..start].trim_end_matches(is_whitespace).strip_suffix('.') {
+        Some(rest) => match receiver_path(rest) {
+            Some(path) => Some(path),
+            None => return Vec::new(),
+        },
+        None => None,
+    };
+
+    let patches = patches(src, start, offset);
+    let src = patches.iter().find(|patched| parses(sess, patched)).unwrap_or(&patches[3]);
+    let (completions, _) = sess.dcx.capture(|| {
+        let file = sess.source_map().new_source_file(name, || Ok(src.clone())).ok()?;
+        let pos = file.start_pos + start as u32;
+        pcx.add_file(file);
+        pcx.parse_and_lower_with(|gcx| complete(gcx, pos, receiver.as_deref(), prefix)).ok()?
+    });
+    completions.unwrap_or_default()
+}
+
+/// Returns the names of the chain of member accesses that ends at the end of `src`, like
+/// `["a", "b"]` for `a.b`.
+fn receiver_path(src: &str) -> Option<Vec<&str>> {
+    let mut path = Vec::new();
+    let mut src = src.trim_end_matches(is_whitespace);
+    loop {
+        let start = src.trim_end_matches(is_id_continue).len();
+        let name = &src[start..];
+        if !name.starts_with(is_id_start) {
+            return None;
+        }
+        path.push(name);
+        match src[..start].trim_end_matches(is_whitespace).strip_suffix('.') {
+            Some(rest) => src = rest.trim_end_matches(is_whitespace),
+            None => break,
+        }
+    }
+    path.reverse();
+    Some(path)
+}
+
+/// Returns the patched sources to try, in order, where the name between `start` and `offset` is
+/// replaced with [`PLACEHOLDER`].
+fn patches(src: &str, start: usize, offset: usize) -> [String; 4] {
+    let line_start = src[..start].rfind('\n').map_or(0, |i| i + 1);
+    let line_end = src[offset..].find('\n').map_or(src.len(), |i| offset + i);
+    let blank = |s: &str| " ".repeat(s.len());
+    let (before, after) = (&src[..start], &src[offset..]);
+    [
+        format!("{before}{PLACEHOLDER}{after}"),
+        format!("{before}{PLACEHOLDER};{after}"),
+        format!("{before}{PLACEHOLDER};{}{}", blank(&src[offset..line_end]), &src[line_end..]),
+        format!("{}{}{}", &src[..line_start], blank(&src[line_start..line_end]), &src[line_end..]),
+    ]
+}
+
+/// Returns `true` if the source parses without errors.
+fn parses(sess: &Session, src: &str) -> bool {
+    let arena = ast::Arena::new();
+    let (ok, diagnostics) = sess.dcx.capture(|| {
+        let mut parser = Parser::from_lexer(&arena, Lexer::new(sess, src));
+        parser.parse_file().map_err(|e| e.emit()).is_ok()
+    });
+    ok && !diagnostics.iter().any(|diagnostic| diagnostic.is_error())
+}
+
+/// Returns the completions at the given position, after the given receiver if any.
+fn complete(
+    gcx: Gcx<'_>,
+    pos: BytePos,
+    receiver: Option<&[&str]>,
+    prefix: &str,
+) -> Vec<Completion> {
+    let scope = Scope::at(gcx, pos);
+    let names = scope.names(gcx, pos);
+    let candidates: Vec<(Symbol, CompletionKind)> = match receiver {
+        None => names.iter().filter_map(|&(name, res)| Some((name, res_kind(gcx, res)?))).collect(),
+        Some(path) => {
+            let first = names.iter().find(|&&(name, _)| name.as_str() == path[0]);
+            let mut ty = first.and_then(|&(_, res)| scope.res_ty(gcx, res));
+            for &name in &path[1..] {
+                ty = ty.and_then(|ty| member_ty(gcx, &scope, ty, name));
+            }
+            match ty {
+                Some(ty) => members(gcx, ty),
+                None => Vec::new(),
+            }
+        }
+    };
+
+    let mut seen = FxHashSet::default();
+    let mut completions = candidates
+        .into_iter()
+        .filter(|&(name, _)| seen.insert(name))
+        .filter(|(name, _)| {
+            name.as_str().starts_with(prefix) && !name.as_str().starts_with(PLACEHOLDER)
+        })
+        .map(|(name, kind)| Completion { label: name.to_string(), kind })
+        .collect::<Vec<_>>();
+    completions.sort_by(|a, b| a.label.cmp(&b.label));
+    completions
+}
+
+/// The declarations enclosing a position.
+struct Scope {
+    source: Option<hir::SourceId>,
+    contract: Option<hir::ContractId>,
+    function: Option<hir::FunctionId>,
+}
+
+impl Scope {
+    fn at(gcx: Gcx<'_>, pos: BytePos) -> Self {
+        let hir = &gcx.hir;
+        let contains = |span: Span| span.lo() <= pos && pos <= span.hi();
+        Self {
+            source: hir.source_ids().find(|&id| hir.source(id).file.contains(pos)),
+            contract: hir.contract_ids().find(|&id| contains(hir.contract(id).span)),
+            function: hir.function_ids().find(|&id| {
+                let f = hir.function(id);
+                !f.is_getter() && contains(f.span)
+            }),
+        }
+    }
+
+    /// Returns the names visible at the given position, with the innermost declarations first.
+    fn names(&self, gcx: Gcx<'_>, pos: BytePos) -> Vec<(Symbol, hir::Res)> {
+        let resolver = &gcx.symbol_resolver;
+        let mut names = Vec::new();
+        if let Some(function) = self.function {
+            // Later declarations shadow earlier ones.
+            for id in gcx.hir.variable_ids().rev() {
+                let var = gcx.hir.variable(id);
+                match var.name {
+                    Some(name) if var.function == Some(function) && name.span.hi() <= pos => {
+                        names.push((name.name, hir::Res::Item(id.into())));
+                    }
+                    _ => {}
+                }
+            }
+        }
+        if let Some(contract) = self.contract {
+            names.extend(declarations(&resolver.contract_scopes[contract]));
+            names.extend([Builtin::This, Builtin::Super].map(|b| (b.name(), hir::Res::Builtin(b))));
+        }
+        if let Some(source) = self.source {
+            names.extend(declarations(&resolver.source_scopes[source]));
+        }
+        names.extend(Builtin::global().iter().map(|&b| (b.name(), hir::Res::Builtin(b))));
+        names
+    }
+
+    /// Returns the type of an expression consisting of a name referring to the given declaration.
+    fn res_ty<'gcx>(&self, gcx: Gcx<'gcx>, res: hir::Res) -> Option<Ty<'gcx>> {
+        match res {
+            hir::Res::Item(
+                id @ (hir::ItemId::Contract(_)
+                | hir::ItemId::Struct(_)
+                | hir::ItemId::Enum(_)
+                | hir::ItemId::Udvt(_)),
+            ) => Some(gcx.mk_ty(TyKind::Type(gcx.type_of_item(id)))),
+            hir::Res::Builtin(Builtin::This) => {
+                self.contract.map(|contract| gcx.type_of_item(contract.into()))
+            }
+            hir::Res::Builtin(Builtin::Super) | hir::Res::Err(_) => None,
+            _ => Some(gcx.type_of_res(res)),
+        }
+    }
+}
+
+/// Returns the names declared in the given scope.
+fn declarations(scope: &Declarations) -> impl Iterator<Item = (Symbol, hir::Res)> + '_ {
+    scope.declarations.iter().map(|(&name, decls)| (name, decls[0].res))
+}
+
+/// Returns the type of the member with the given name of the given type.
+fn member_ty<'gcx>(gcx: Gcx<'gcx>, scope: &Scope, ty: Ty<'gcx>, name: &str) -> Option<Ty<'gcx>> {
+    if let TyKind::Module(source) = ty.kind {
+        let (_, res) = declarations(&gcx.symbol_resolver.source_scopes[source])
+            .find(|&(member, _)| member.as_str() == name)?;
+        return scope.res_ty(gcx, res);
+    }
+    members_of(gcx, ty).iter().find(|member| member.name.as_str() == name).map(|member| member.ty)
+}
+
+/// Returns the names and kinds of the members of the given type.
+fn members(gcx: Gcx<'_>, ty: Ty<'_>) -> Vec<(Symbol, CompletionKind)> {
+    if let TyKind::Module(source) = ty.kind {
+        return declarations(&gcx.symbol_resolver.source_scopes[source])
+            .filter_map(|(name, res)| Some((name, res_kind(gcx, res)?)))
+            .collect();
+    }
+    let is_enum = matches!(ty.kind, TyKind::Type(inner) if matches!(inner.kind, TyKind::Enum(_)));
+    members_of(gcx, ty)
+        .iter()
+        .filter_map(|member| {
+            let kind = match member.res {
+                Some(res @ (hir::Res::Item(_) | hir::Res::Namespace(_))) => res_kind(gcx, res)?,
+                _ if is_enum => CompletionKind::EnumVariant,
+                _ => ty_kind(member.ty),
+            };
+            Some((member.name, kind))
+        })
+        .collect()
+}
+
+/// Returns the members of the given type, or none for the types that
+/// [`members_of`](Gcx::members_of) does not support, like functions and types that are only
+/// expected in references.
+fn members_of<'gcx>(gcx: Gcx<'gcx>, ty: Ty<'gcx>) -> &'gcx [Member<'gcx>] {
+    match ty.kind {
+        TyKind::FnPtr(_)
+        | TyKind::Struct(_)
+        | TyKind::Array(..)
+        | TyKind::DynArray(_)
+        | TyKind::Elementary(ElementaryType::Bytes) => &[],
+        _ => gcx.members_of(ty),
+    }
+}
+
+fn res_kind(gcx: Gcx<'_>, res: hir::Res) -> Option<CompletionKind> {
+    Some(match res {
+        hir::Res::Item(id) => match id {
+            hir::ItemId::Contract(_) => CompletionKind::Contract,
+            hir::ItemId::Function(id) => match gcx.hir.function(id).kind {
+                hir::FunctionKind::Modifier => CompletionKind::Modifier,
+                _ => CompletionKind::Function,
+            },
+            hir::ItemId::Variable(_) => CompletionKind::Variable,
+            hir::ItemId::Struct(_) => CompletionKind::Struct,
+            hir::ItemId::Enum(_) => CompletionKind::Enum,
+            hir::ItemId::Udvt(_) => CompletionKind::UserDefinedValueType,
+            hir::ItemId::Error(_) => CompletionKind::Error,
+            hir::ItemId::Event(_) => CompletionKind::Event,
+        },
+        hir::Res::Namespace(_) => CompletionKind::Module,
+        hir::Res::Builtin(builtin) => {
+            if builtin.members().is_some() {
+                CompletionKind::Module
+            } else if matches!(builtin, Builtin::This | Builtin::Super) {
+                CompletionKind::Variable
+            } else {
+                ty_kind(builtin.ty(gcx))
+            }
+        }
+        hir::Res::Err(_) => return None,
+    })
+}
+
+fn ty_kind(ty: Ty<'_>) -> CompletionKind {
+    match ty.kind {
+        TyKind::FnPtr(_) => CompletionKind::Function,
+        TyKind::Module(_) | TyKind::BuiltinModule(_) => CompletionKind::Module,
+        _ => CompletionKind::Variable,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{with_pcx, FILE_NAME};
+    use CompletionKind::*;
+
+    const SRC: &str = "\
+contract B { uint internal b; function g() internal {} }
+contract C is B {
+    struct S { uint a; uint bb; }
+    enum E { X, Y }
+    S s;
+    function f(uint z) public {
+        uint w;
+        $
+    }
+}
+";
+
+    /// Returns the completions after replacing `$` in `SRC` with `input`.
+    fn complete(input: &str) -> Vec<(String, CompletionKind)> {
+        with_pcx(&[], |pcx, _| {
+            let src = SRC.replace('$', input);
+            let offset = SRC.find('$').unwrap() + input.len();
+            let name = FileName::Custom(FILE_NAME.into());
+            let completions = completions(pcx, name, &src, offset);
+            completions.into_iter().map(|c| (c.label, c.kind)).collect()
+        })
+    }
+
+    fn labels(input: &str) -> Vec<String> {
+        complete(input).into_iter().map(|(label, _)| label).collect()
+    }
+
+    #[test]
+    fn scope() {
+        assert_eq!(labels("w"), ["w"]);
+        assert_eq!(labels("b"), ["b", "blobhash", "block", "blockhash"]);
+        let all = complete("");
+        for expected in [
+            ("B", Contract),
+            ("E", Enum),
+            ("S", Struct),
+            ("f", Function),
+            ("g", Function),
+            ("keccak256", Function),
+            ("msg", Module),
+            ("s", Variable),
+            ("this", Variable),
+            ("w", Variable),
+            ("z", Variable),
+        ] {
+            assert!(all.contains(&(expected.0.to_string(), expected.1)), "{expected:?}");
+        }
+    }
+
+    #[test]
+    fn members() {
+        assert_eq!(labels("s."), ["a", "bb"]);
+        assert_eq!(labels("s.b"), ["bb"]);
+        assert_eq!(labels("msg.s"), ["sender", "sig"]);
+        let variants = [("X".to_string(), EnumVariant), ("Y".to_string(), EnumVariant)];
+        assert_eq!(complete("E."), variants);
+        // The line does not parse, even with a placeholder.
+        assert_eq!(labels("uint q = (s."), ["a", "bb"]);
+        assert!(labels("f().").is_empty());
+    }
+}
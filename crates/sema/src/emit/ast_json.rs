@@ -0,0 +1,1308 @@
+//! solc-compatible AST JSON output.
+//!
+//! Node IDs are assigned in a single pre-order traversal over all the sources, sorted by file
+//! name, so that the same input always produces the same IDs regardless of the parsing order.
+//!
+//! Reference: <https://docs.soliditylang.org/en/latest/using-the-compiler.html#output-description>
+
+use crate::{ParsedSource, ParsedSources};
+use serde::Serialize;
+use serde_json::{Map, Value};
+use solar_ast::{self as ast, yul};
+use solar_interface::{source_map::SourceFile, Ident, Span};
+use std::collections::BTreeMap;
+
+/// The AST JSON of a single source, as found in the `sources` field of the combined JSON.
+#[derive(Serialize)]
+pub(crate) struct SourceJson {
+    pub(crate) id: usize,
+    #[serde(rename = "AST")]
+    pub(crate) ast: Value,
+}
+
+/// Sets fields on a JSON object node.
+macro_rules! fields {
+    ($node:ident { $($key:literal: $value:expr),* $(,)? }) => {
+        $( $node.insert($key.into(), Value::from($value)); )*
+    };
+}
+
+/// Serializes all the parsed sources into solc-compatible AST JSON, keyed by file name.
+pub(crate) fn sources_to_json(sources: &ParsedSources<'_>) -> BTreeMap<String, SourceJson> {
+    let mut sorted = sources.iter().filter(|s| s.ast.is_some()).collect::<Vec<_>>();
+    sorted.sort_by_cached_key(|s| s.file.name.display().to_string());
+
+    // Source unit IDs are reserved up front so that imports can refer to them.
+    let source_unit_ids = sorted
+        .iter()
+        .enumerate()
+        .map(|(i, s)| (s.file.stable_id, i as u64))
+        .collect::<BTreeMap<_, _>>();
+    let mut next_id = sorted.len() as u64;
+
+    let mut output = BTreeMap::new();
+    for (source_index, source) in sorted.iter().enumerate() {
+        let mut writer = AstJsonWriter {
+            sources,
+            source,
+            source_index,
+            source_unit_ids: &source_unit_ids,
+            next_id,
+            scopes: Vec::new(),
+        };
+        let ast = writer.source_unit(source_unit_ids[&source.file.stable_id]);
+        next_id = writer.next_id;
+        output.insert(source.file.name.display().to_string(), SourceJson { id: source_index, ast });
+    }
+    output
+}
+
+struct AstJsonWriter<'a, 'ast> {
+    sources: &'a ParsedSources<'ast>,
+    source: &'a ParsedSource<'ast>,
+    source_index: usize,
+    source_unit_ids: &'a BTreeMap<solar_interface::source_map::StableSourceFileId, u64>,
+    next_id: u64,
+    /// The stack of enclosing scope node IDs.
+    scopes: Vec<u64>,
+}
+
+impl AstJsonWriter<'_, '_> {
+    fn file(&self) -> &SourceFile {
+        &self.source.file
+    }
+
+    fn next_id(&mut self) -> u64 {
+        let id = self.next_id;
+        self.next_id += 1;
+        id
+    }
+
+    fn scope(&self) -> Value {
+        self.scopes.last().copied().into()
+    }
+
+    /// Formats a span as `start:length:source_index`.
+    fn src(&self, span: Span) -> String {
+        if span.is_dummy() {
+            return "-1:-1:-1".into();
+        }
+        let start = span.lo().0.saturating_sub(self.file().start_pos.0);
+        let len = span.hi().0 - span.lo().0;
+        format!("{start}:{len}:{}", self.source_index)
+    }
+
+    /// Creates a new node with a fresh ID.
+    fn node(&mut self, node_type: &'static str, span: Span) -> Map<String, Value> {
+        let id = self.next_id();
+        self.node_with_id(node_type, span, id)
+    }
+
+    fn node_with_id(&self, node_type: &'static str, span: Span, id: u64) -> Map<String, Value> {
+        let mut node = Map::new();
+        fields!(node { "id": id, "nodeType": node_type, "src": self.src(span) });
+        node
+    }
+
+    /// Creates a new Yul node. Yul nodes don't have IDs.
+    fn yul_node(&self, node_type: &'static str, span: Span) -> Map<String, Value> {
+        let mut node = Map::new();
+        fields!(node { "nodeType": node_type, "src": self.src(span) });
+        node
+    }
+
+    fn source_unit(&mut self, id: u64) -> Value {
+        let source = self.source;
+        let source_unit = source.ast.as_ref().unwrap();
+        let span = Span::new(source.file.start_pos, source.file.end_position());
+        let mut node = self.node_with_id("SourceUnit", span, id);
+        self.scopes.push(id);
+
+        let mut exported_symbols = Map::new();
+        let mut nodes = Vec::with_capacity(source_unit.items.len());
+        for (item_id, item) in source_unit.items.iter_enumerated() {
+            let item_json = self.item(item, Some(item_id));
+            if let (Some(name), Some(id)) = (item.name(), item_json.get("id")) {
+                exported_symbols.insert(name.to_string(), Value::Array(vec![id.clone()]));
+            }
+            nodes.push(item_json);
+        }
+
+        self.scopes.pop();
+        fields!(node {
+            "absolutePath": self.file().name.display().to_string(),
+            "exportedSymbols": exported_symbols,
+            "license": crate::license::source_license(&self.file().src),
+            "nodes": nodes,
+        });
+        node.into()
+    }
+
+    fn item(&mut self, item: &ast::Item<'_>, item_id: Option<ast::ItemId>) -> Value {
+        let documentation = self.documentation(&item.docs);
+        match &item.kind {
+            ast::ItemKind::Pragma(pragma) => {
+                let mut node = self.node("PragmaDirective", item.span);
+                let literals: Vec<Value> = match &pragma.tokens {
+                    ast::PragmaTokens::Version(name, req) => {
+                        vec![name.to_string().into(), req.to_string().into()]
+                    }
+                    ast::PragmaTokens::Custom(name, value) => std::iter::once(name)
+                        .chain(value.as_ref())
+                        .map(|x| x.as_str().into())
+                        .collect(),
+                    ast::PragmaTokens::Verbatim(tokens) => {
+                        tokens.iter().map(|t| t.kind.to_string().into()).collect()
+                    }
+                };
+                fields!(node { "literals": literals });
+                node.into()
+            }
+            ast::ItemKind::Import(import) => {
+                let mut node = self.node("ImportDirective", item.span);
+                let (sources, source) = (self.sources, self.source);
+                let imported = item_id.and_then(|item_id| {
+                    let (_, source_id) = source.imports.iter().find(|(id, _)| *id == item_id)?;
+                    Some(&sources[*source_id].file)
+                });
+                let (unit_alias, symbol_aliases) = match &import.items {
+                    ast::ImportItems::Plain(alias) | ast::ImportItems::Glob(alias) => {
+                        (alias.map(|a| a.to_string()).unwrap_or_default(), Vec::new())
+                    }
+                    ast::ImportItems::Aliases(aliases) => {
+                        let aliases = aliases
+                            .iter()
+                            .map(|(name, alias)| {
+                                let mut foreign = self.node("Identifier", name.span);
+                                fields!(foreign {
+                                    "name": name.to_string(),
+                                    "overloadedDeclarations": Vec::<Value>::new(),
+                                });
+                                let mut symbol_alias = Map::new();
+                                fields!(symbol_alias {
+                                    "foreign": foreign,
+                                    "local": alias.map(|a| a.to_string()),
+                                    "nameLocation": self.src(alias.unwrap_or(*name).span),
+                                });
+                                Value::from(symbol_alias)
+                            })
+                            .collect();
+                        (String::new(), aliases)
+                    }
+                };
+                fields!(node {
+                    "absolutePath": imported.map(|f| f.name.display().to_string()),
+                    "file": import.path.value.to_string(),
+                    "scope": self.scope(),
+                    "sourceUnit": imported.map(|f| self.source_unit_ids.get(&f.stable_id).copied()),
+                    "symbolAliases": symbol_aliases,
+                    "unitAlias": unit_alias,
+                });
+                node.into()
+            }
+            ast::ItemKind::Using(using) => self.using_directive(using, item.span),
+            ast::ItemKind::Contract(contract) => {
+                let mut node = self.node("ContractDefinition", item.span);
+                let id = node["id"].as_u64().unwrap();
+                let bases = contract
+                    .bases
+                    .iter()
+                    .map(|base| {
+                        let span = base.name.span();
+                        let mut node = self.node("InheritanceSpecifier", span);
+                        fields!(node {
+                            "baseName": self.identifier_path(&base.name),
+                            "arguments": if base.arguments.is_empty() {
+                                Value::Null
+                            } else {
+                                self.call_args(&base.arguments).0.into()
+                            },
+                        });
+                        Value::from(node)
+                    })
+                    .collect::<Vec<_>>();
+                fields!(node {
+                    "abstract": contract.kind.is_abstract_contract(),
+                    "baseContracts": bases,
+                    "contractDependencies": Vec::<Value>::new(),
+                    "contractKind": match contract.kind {
+                        ast::ContractKind::Contract | ast::ContractKind::AbstractContract => "contract",
+                        ast::ContractKind::Interface => "interface",
+                        ast::ContractKind::Library => "library",
+                    },
+                    "documentation": documentation,
+                    "name": contract.name.to_string(),
+                    "nameLocation": self.src(contract.name.span),
+                    "scope": self.scope(),
+                });
+                self.scopes.push(id);
+                let nodes =
+                    contract.body.iter().map(|item| self.item(item, None)).collect::<Vec<_>>();
+                self.scopes.pop();
+                fields!(node { "nodes": nodes });
+                node.into()
+            }
+            ast::ItemKind::Function(function) => {
+                let node_type = if function.kind.is_modifier() {
+                    "ModifierDefinition"
+                } else {
+                    "FunctionDefinition"
+                };
+                let mut node = self.node(node_type, item.span);
+                let id = node["id"].as_u64().unwrap();
+                let header = &function.header;
+                let name = header.name.map(|n| n.to_string()).unwrap_or_default();
+                let name_location = header.name.map(|n| self.src(n.span));
+                fields!(node {
+                    "documentation": documentation,
+                    "name": name,
+                    "nameLocation": name_location,
+                    "scope": self.scope(),
+                    "virtual": header.virtual_,
+                    "visibility": header.visibility.map_or("public", |v| v.to_str()),
+                });
+                if let Some(override_) = &header.override_ {
+                    fields!(node { "overrides": self.override_(override_) });
+                }
+                self.scopes.push(id);
+                fields!(node { "parameters": self.parameter_list(&header.parameters, item.span) });
+                if !function.kind.is_modifier() {
+                    let modifiers = header
+                        .modifiers
+                        .iter()
+                        .map(|modifier| {
+                            let span = modifier.name.span();
+                            let mut node = self.node("ModifierInvocation", span);
+                            fields!(node {
+                                "modifierName": self.identifier_path(&modifier.name),
+                                "arguments": if modifier.arguments.is_empty() {
+                                    Value::Null
+                                } else {
+                                    self.call_args(&modifier.arguments).0.into()
+                                },
+                            });
+                            Value::from(node)
+                        })
+                        .collect::<Vec<_>>();
+                    fields!(node {
+                        "implemented": function.body.is_some(),
+                        "kind": function.kind.to_str(),
+                        "modifiers": modifiers,
+                        "returnParameters": self.parameter_list(&header.returns, item.span),
+                        "stateMutability": header.state_mutability.to_str(),
+                    });
+                }
+                fields!(node {
+                    "body": function.body.as_ref().map(|body| self.block(body, body.span, false)),
+                });
+                self.scopes.pop();
+                node.into()
+            }
+            ast::ItemKind::Variable(var) => {
+                let mut node = self.variable_declaration(var, true);
+                fields!(node { "documentation": documentation });
+                node.into()
+            }
+            ast::ItemKind::Struct(strukt) => {
+                let mut node = self.node("StructDefinition", item.span);
+                let id = node["id"].as_u64().unwrap();
+                fields!(node {
+                    "canonicalName": self.canonical_name(strukt.name),
+                    "documentation": documentation,
+                    "name": strukt.name.to_string(),
+                    "nameLocation": self.src(strukt.name.span),
+                    "scope": self.scope(),
+                    "visibility": "public",
+                });
+                self.scopes.push(id);
+                let members = strukt
+                    .fields
+                    .iter()
+                    .map(|field| Value::from(self.variable_declaration(field, false)))
+                    .collect::<Vec<_>>();
+                self.scopes.pop();
+                fields!(node { "members": members });
+                node.into()
+            }
+            ast::ItemKind::Enum(enum_) => {
+                let mut node = self.node("EnumDefinition", item.span);
+                let members = enum_
+                    .variants
+                    .iter()
+                    .map(|variant| {
+                        let mut node = self.node("EnumValue", variant.span);
+                        fields!(node {
+                            "name": variant.to_string(),
+                            "nameLocation": self.src(variant.span),
+                        });
+                        Value::from(node)
+                    })
+                    .collect::<Vec<_>>();
+                fields!(node {
+                    "canonicalName": self.canonical_name(enum_.name),
+                    "documentation": documentation,
+                    "members": members,
+                    "name": enum_.name.to_string(),
+                    "nameLocation": self.src(enum_.name.span),
+                });
+                node.into()
+            }
+            ast::ItemKind::Udvt(udvt) => {
+                let mut node = self.node("UserDefinedValueTypeDefinition", item.span);
+                fields!(node {
+                    "canonicalName": self.canonical_name(udvt.name),
+                    "name": udvt.name.to_string(),
+                    "nameLocation": self.src(udvt.name.span),
+                    "underlyingType": self.type_name(&udvt.ty),
+                });
+                node.into()
+            }
+            ast::ItemKind::Error(error) => {
+                let mut node = self.node("ErrorDefinition", item.span);
+                let id = node["id"].as_u64().unwrap();
+                fields!(node {
+                    "documentation": documentation,
+                    "name": error.name.to_string(),
+                    "nameLocation": self.src(error.name.span),
+                });
+                self.scopes.push(id);
+                fields!(node { "parameters": self.parameter_list(&error.parameters, item.span) });
+                self.scopes.pop();
+                node.into()
+            }
+            ast::ItemKind::Event(event) => {
+                let mut node = self.node("EventDefinition", item.span);
+                let id = node["id"].as_u64().unwrap();
+                fields!(node {
+                    "anonymous": event.anonymous,
+                    "documentation": documentation,
+                    "name": event.name.to_string(),
+                    "nameLocation": self.src(event.name.span),
+                });
+                self.scopes.push(id);
+                fields!(node { "parameters": self.parameter_list(&event.parameters, item.span) });
+                self.scopes.pop();
+                node.into()
+            }
+        }
+    }
+
+    fn documentation(&mut self, docs: &ast::DocComments<'_>) -> Value {
+        let (Some(first), Some(last)) = (docs.first(), docs.last()) else { return Value::Null };
+        let mut node = self.node("StructuredDocumentation", first.span.to(last.span));
+        let text = docs.iter().map(|doc| doc.symbol.as_str()).collect::<Vec<_>>().join("\n");
+        fields!(node { "text": text });
+        node.into()
+    }
+
+    fn canonical_name(&self, name: Ident) -> String {
+        // Contract scopes are the only ones that can contain named items other than the source
+        // unit, so this is enough to reconstruct the canonical name.
+        let contract = self.source.ast.as_ref().unwrap().items.iter().find_map(|item| {
+            let ast::ItemKind::Contract(c) = &item.kind else { return None };
+            c.body.iter().any(|i| i.span.contains(name.span)).then_some(c.name)
+        });
+        match contract {
+            Some(contract) => format!("{contract}.{name}"),
+            None => name.to_string(),
+        }
+    }
+
+    fn using_directive(&mut self, using: &ast::UsingDirective<'_>, span: Span) -> Value {
+        let mut node = self.node("UsingForDirective", span);
+        match &using.list {
+            ast::UsingList::Single(path) => {
+                fields!(node { "libraryName": self.identifier_path(path) });
+            }
+            ast::UsingList::Multiple(paths) => {
+                let list = paths
+                    .iter()
+                    .map(|(path, op)| {
+                        let mut entry = Map::new();
+                        match op {
+                            Some(op) => {
+                                fields!(entry {
+                                    "definition": self.identifier_path(path),
+                                    "operator": user_definable_operator_str(*op),
+                                });
+                            }
+                            None => {
+                                fields!(entry { "function": self.identifier_path(path) });
+                            }
+                        }
+                        Value::from(entry)
+                    })
+                    .collect::<Vec<_>>();
+                fields!(node { "functionList": list });
+            }
+        }
+        fields!(node {
+            "global": using.global,
+            "typeName": using.ty.as_ref().map(|ty| self.type_name(ty)),
+        });
+        node.into()
+    }
+
+    fn override_(&mut self, override_: &ast::Override<'_>) -> Value {
+        let mut node = self.node("OverrideSpecifier", override_.span);
+        let overrides =
+            override_.paths.iter().map(|path| self.identifier_path(path)).collect::<Vec<_>>();
+        fields!(node { "overrides": overrides });
+        node.into()
+    }
+
+    fn parameter_list(&mut self, params: &[ast::VariableDefinition<'_>], fallback: Span) -> Value {
+        let span = match (params.first(), params.last()) {
+            (Some(first), Some(last)) => first.span.to(last.span),
+            _ => fallback.shrink_to_lo(),
+        };
+        let mut node = self.node("ParameterList", span);
+        let parameters = params
+            .iter()
+            .map(|param| Value::from(self.variable_declaration(param, false)))
+            .collect::<Vec<_>>();
+        fields!(node { "parameters": parameters });
+        node.into()
+    }
+
+    fn variable_declaration(
+        &mut self,
+        var: &ast::VariableDefinition<'_>,
+        state_variable: bool,
+    ) -> Map<String, Value> {
+        let mut node = self.node("VariableDeclaration", var.span);
+        let mutability = match var.mutability {
+            Some(m) => m.to_str(),
+            None => "mutable",
+        };
+        let default_visibility = "internal";
+        fields!(node {
+            "constant": var.mutability.is_some_and(|m| m.is_constant()),
+            "indexed": var.indexed,
+            "mutability": mutability,
+            "name": var.name.map(|n| n.to_string()).unwrap_or_default(),
+            "nameLocation": var.name.map(|n| self.src(n.span)),
+            "scope": self.scope(),
+            "stateVariable": state_variable,
+            "storageLocation": var.data_location.map_or("default", |l| l.to_str()),
+            "typeName": self.type_name(&var.ty),
+            "visibility": var.visibility.map_or(default_visibility, |v| v.to_str()),
+        });
+        if let Some(override_) = &var.override_ {
+            fields!(node { "overrides": self.override_(override_) });
+        }
+        fields!(node { "value": var.initializer.as_deref().map(|init| self.expr(init)) });
+        node
+    }
+
+    fn identifier_path(&mut self, path: &ast::PathSlice) -> Value {
+        let mut node = self.node("IdentifierPath", path.span());
+        let locations =
+            path.segments().iter().map(|segment| self.src(segment.span)).collect::<Vec<_>>();
+        fields!(node { "name": path.to_string(), "nameLocations": locations });
+        node.into()
+    }
+
+    fn type_name(&mut self, ty: &ast::Type<'_>) -> Value {
+        match &ty.kind {
+            ast::TypeKind::Elementary(elementary) => {
+                let mut node = self.node("ElementaryTypeName", ty.span);
+                fields!(node { "name": elementary.to_string() });
+                if let ast::ElementaryType::Address(payable) = elementary {
+                    let mutability = if *payable { "payable" } else { "nonpayable" };
+                    fields!(node { "stateMutability": mutability });
+                }
+                node.into()
+            }
+            ast::TypeKind::Array(array) => {
+                let mut node = self.node("ArrayTypeName", ty.span);
+                fields!(node {
+                    "baseType": self.type_name(&array.element),
+                    "length": array.size.as_deref().map(|size| self.expr(size)),
+                });
+                node.into()
+            }
+            ast::TypeKind::Function(function) => {
+                let mut node = self.node("FunctionTypeName", ty.span);
+                fields!(node {
+                    "parameterTypes": self.parameter_list(&function.parameters, ty.span),
+                    "returnParameterTypes": self.parameter_list(&function.returns, ty.span),
+                    "stateMutability": function.state_mutability.to_str(),
+                    "visibility": function.visibility.map_or("internal", |v| v.to_str()),
+                });
+                node.into()
+            }
+            ast::TypeKind::Mapping(mapping) => {
+                let mut node = self.node("Mapping", ty.span);
+                fields!(node {
+                    "keyName": mapping.key_name.map(|n| n.to_string()).unwrap_or_default(),
+                    "keyNameLocation": mapping.key_name.map(|n| self.src(n.span)),
+                    "keyType": self.type_name(&mapping.key),
+                    "valueName": mapping.value_name.map(|n| n.to_string()).unwrap_or_default(),
+                    "valueNameLocation": mapping.value_name.map(|n| self.src(n.span)),
+                    "valueType": self.type_name(&mapping.value),
+                });
+                node.into()
+            }
+            ast::TypeKind::Custom(path) => {
+                let mut node = self.node("UserDefinedTypeName", ty.span);
+                fields!(node { "pathNode": self.identifier_path(path) });
+                node.into()
+            }
+        }
+    }
+
+    fn block(&mut self, block: &[ast::Stmt<'_>], span: Span, unchecked: bool) -> Value {
+        let node_type = if unchecked { "UncheckedBlock" } else { "Block" };
+        let mut node = self.node(node_type, span);
+        let statements = block.iter().map(|stmt| self.stmt(stmt)).collect::<Vec<_>>();
+        fields!(node { "statements": statements });
+        node.into()
+    }
+
+    fn stmt(&mut self, stmt: &ast::Stmt<'_>) -> Value {
+        let span = stmt.span;
+        let documentation = match &stmt.docs[..] {
+            [] => None,
+            docs => docs.last().map(|doc| doc.symbol.to_string()),
+        };
+        let mut node = match &stmt.kind {
+            ast::StmtKind::Assembly(assembly) => {
+                let mut node = self.node("InlineAssembly", span);
+                let flags = assembly.flags.iter().map(|f| f.value.to_string()).collect::<Vec<_>>();
+                fields!(node {
+                    "AST": self.yul_block(&assembly.block, span),
+                    "externalReferences": Vec::<Value>::new(),
+                });
+                if !flags.is_empty() {
+                    fields!(node { "flags": flags });
+                }
+                node
+            }
+            ast::StmtKind::DeclSingle(var) => {
+                let mut node = self.node("VariableDeclarationStatement", span);
+                let id = self.next_id;
+                let mut decl = self.variable_declaration(var, false);
+                let initial_value = decl.remove("value").unwrap_or_default();
+                fields!(node {
+                    "assignments": vec![id],
+                    "declarations": vec![Value::from(decl)],
+                    "initialValue": initial_value,
+                });
+                node
+            }
+            ast::StmtKind::DeclMulti(vars, expr) => {
+                let mut node = self.node("VariableDeclarationStatement", span);
+                let mut assignments = Vec::with_capacity(vars.len());
+                let mut declarations = Vec::with_capacity(vars.len());
+                for var in vars.iter() {
+                    match var {
+                        Some(var) => {
+                            assignments.push(Value::from(self.next_id));
+                            declarations.push(Value::from(self.variable_declaration(var, false)));
+                        }
+                        None => {
+                            assignments.push(Value::Null);
+                            declarations.push(Value::Null);
+                        }
+                    }
+                }
+                fields!(node {
+                    "assignments": assignments,
+                    "declarations": declarations,
+                    "initialValue": self.expr(expr),
+                });
+                node
+            }
+            ast::StmtKind::Block(block) => {
+                return self.block(block, span, false);
+            }
+            ast::StmtKind::UncheckedBlock(block) => {
+                return self.block(block, span, true);
+            }
+            ast::StmtKind::Break => self.node("Break", span),
+            ast::StmtKind::Continue => self.node("Continue", span),
+            ast::StmtKind::Placeholder => self.node("PlaceholderStatement", span),
+            ast::StmtKind::DoWhile(body, cond) => {
+                let mut node = self.node("DoWhileStatement", span);
+                fields!(node { "body": self.stmt(body), "condition": self.expr(cond) });
+                node
+            }
+            ast::StmtKind::While(cond, body) => {
+                let mut node = self.node("WhileStatement", span);
+                fields!(node { "condition": self.expr(cond), "body": self.stmt(body) });
+                node
+            }
+            ast::StmtKind::Emit(path, args) => {
+                let mut node = self.node("EmitStatement", span);
+                fields!(node { "eventCall": self.path_call(path, args, span) });
+                node
+            }
+            ast::StmtKind::Revert(path, args) => {
+                let mut node = self.node("RevertStatement", span);
+                fields!(node { "errorCall": self.path_call(path, args, span) });
+                node
+            }
+            ast::StmtKind::Expr(expr) => {
+                let mut node = self.node("ExpressionStatement", span);
+                fields!(node { "expression": self.expr(expr) });
+                node
+            }
+            ast::StmtKind::For { init, cond, next, body } => {
+                let mut node = self.node("ForStatement", span);
+                fields!(node {
+                    "initializationExpression": init.as_deref().map(|init| self.stmt(init)),
+                    "condition": cond.as_deref().map(|cond| self.expr(cond)),
+                    "loopExpression": next.as_deref().map(|next| {
+                        let mut node = self.node("ExpressionStatement", next.span);
+                        fields!(node { "expression": self.expr(next) });
+                        Value::from(node)
+                    }),
+                    "body": self.stmt(body),
+                });
+                node
+            }
+            ast::StmtKind::If(cond, then, else_) => {
+                let mut node = self.node("IfStatement", span);
+                fields!(node {
+                    "condition": self.expr(cond),
+                    "trueBody": self.stmt(then),
+                    "falseBody": else_.as_deref().map(|else_| self.stmt(else_)),
+                });
+                node
+            }
+            ast::StmtKind::Return(expr) => {
+                let mut node = self.node("Return", span);
+                fields!(node { "expression": expr.as_deref().map(|expr| self.expr(expr)) });
+                node
+            }
+            ast::StmtKind::Try(try_) => {
+                let mut node = self.node("TryStatement", span);
+                let external_call = match &try_.expr.kind {
+                    ast::ExprKind::Call(callee, args) => {
+                        self.call(callee, args, try_.expr.span, true)
+                    }
+                    _ => self.expr(&try_.expr),
+                };
+                let mut clauses = Vec::with_capacity(1 + try_.catch.len());
+                {
+                    let mut clause = self.node("TryCatchClause", try_.span);
+                    fields!(clause {
+                        "errorName": "",
+                        "parameters": if try_.returns.is_empty() {
+                            Value::Null
+                        } else {
+                            self.parameter_list(&try_.returns, try_.span)
+                        },
+                        "block": self.block(&try_.block, try_.block.span, false),
+                    });
+                    clauses.push(Value::from(clause));
+                }
+                for catch in try_.catch.iter() {
+                    let mut clause = self.node("TryCatchClause", catch.span);
+                    fields!(clause {
+                        "errorName": catch.name.map(|n| n.to_string()).unwrap_or_default(),
+                        "parameters": if catch.args.is_empty() {
+                            Value::Null
+                        } else {
+                            self.parameter_list(&catch.args, catch.span)
+                        },
+                        "block": self.block(&catch.block, catch.block.span, false),
+                    });
+                    clauses.push(Value::from(clause));
+                }
+                fields!(node { "clauses": clauses, "externalCall": external_call });
+                node
+            }
+        };
+        if let Some(documentation) = documentation {
+            fields!(node { "documentation": documentation });
+        }
+        node.into()
+    }
+
+    /// `emit Event(args)` and `revert Error(args)`.
+    fn path_call(&mut self, path: &ast::PathSlice, args: &ast::CallArgs<'_>, span: Span) -> Value {
+        let mut node = self.node("FunctionCall", span);
+        let expression = self.path_expr(path);
+        let (arguments, names, name_locations) = self.call_args(args);
+        fields!(node {
+            "arguments": arguments,
+            "expression": expression,
+            "kind": "functionCall",
+            "names": names,
+            "nameLocations": name_locations,
+            "tryCall": false,
+        });
+        node.into()
+    }
+
+    /// Lowers a path into nested `MemberAccess` expressions.
+    fn path_expr(&mut self, path: &ast::PathSlice) -> Value {
+        match path.segments() {
+            [] => unreachable!(),
+            [ident] => self.identifier(*ident),
+            [rest @ .., last] => {
+                let mut node = self.node("MemberAccess", path.span());
+                let rest = ast::PathSlice::from_slice(rest);
+                fields!(node {
+                    "expression": self.path_expr(rest),
+                    "memberLocation": self.src(last.span),
+                    "memberName": last.to_string(),
+                });
+                node.into()
+            }
+        }
+    }
+
+    fn identifier(&mut self, ident: Ident) -> Value {
+        let mut node = self.node("Identifier", ident.span);
+        fields!(node {
+            "name": ident.to_string(),
+            "overloadedDeclarations": Vec::<Value>::new(),
+        });
+        node.into()
+    }
+
+    /// Returns `(arguments, names, nameLocations)`.
+    fn call_args(&mut self, args: &ast::CallArgs<'_>) -> (Vec<Value>, Vec<Value>, Vec<Value>) {
+        match args {
+            ast::CallArgs::Unnamed(exprs) => {
+                (exprs.iter().map(|expr| self.expr(expr)).collect(), Vec::new(), Vec::new())
+            }
+            ast::CallArgs::Named(named) => self.named_args(named),
+        }
+    }
+
+    /// Returns `(arguments, names, nameLocations)`.
+    fn named_args(&mut self, named: &[ast::NamedArg<'_>]) -> (Vec<Value>, Vec<Value>, Vec<Value>) {
+        let mut arguments = Vec::with_capacity(named.len());
+        let mut names = Vec::with_capacity(named.len());
+        let mut locations = Vec::with_capacity(named.len());
+        for arg in named {
+            names.push(arg.name.to_string().into());
+            locations.push(self.src(arg.name.span).into());
+            arguments.push(self.expr(&arg.value));
+        }
+        (arguments, names, locations)
+    }
+
+    fn expr(&mut self, expr: &ast::Expr<'_>) -> Value {
+        let span = expr.span;
+        match &expr.kind {
+            ast::ExprKind::Array(exprs) => {
+                let mut node = self.node("TupleExpression", span);
+                let components = exprs.iter().map(|expr| self.expr(expr)).collect::<Vec<_>>();
+                fields!(node { "components": components, "isInlineArray": true });
+                node.into()
+            }
+            ast::ExprKind::Tuple(exprs) => {
+                let mut node = self.node("TupleExpression", span);
+                let components = exprs
+                    .iter()
+                    .map(|expr| expr.as_deref().map(|expr| self.expr(expr)).into())
+                    .collect::<Vec<Value>>();
+                fields!(node { "components": components, "isInlineArray": false });
+                node.into()
+            }
+            ast::ExprKind::Assign(lhs, op, rhs) => {
+                let mut node = self.node("Assignment", span);
+                let operator = match op {
+                    Some(op) => format!("{}=", op.kind.to_str()),
+                    None => "=".into(),
+                };
+                fields!(node {
+                    "leftHandSide": self.expr(lhs),
+                    "operator": operator,
+                    "rightHandSide": self.expr(rhs),
+                });
+                node.into()
+            }
+            ast::ExprKind::Binary(lhs, op, rhs) => {
+                let mut node = self.node("BinaryOperation", span);
+                fields!(node {
+                    "leftExpression": self.expr(lhs),
+                    "operator": op.kind.to_str(),
+                    "rightExpression": self.expr(rhs),
+                });
+                node.into()
+            }
+            ast::ExprKind::Unary(op, operand) => {
+                let mut node = self.node("UnaryOperation", span);
+                fields!(node {
+                    "operator": op.kind.to_str(),
+                    "prefix": op.kind.is_prefix(),
+                    "subExpression": self.expr(operand),
+                });
+                node.into()
+            }
+            ast::ExprKind::Delete(operand) => {
+                let mut node = self.node("UnaryOperation", span);
+                fields!(node {
+                    "operator": "delete",
+                    "prefix": true,
+                    "subExpression": self.expr(operand),
+                });
+                node.into()
+            }
+            ast::ExprKind::Call(callee, args) => self.call(callee, args, span, false),
+            ast::ExprKind::CallOptions(callee, options) => {
+                let mut node = self.node("FunctionCallOptions", span);
+                let expression = self.expr(callee);
+                let (options, names, _) = self.named_args(options);
+                fields!(node { "expression": expression, "names": names, "options": options });
+                node.into()
+            }
+            ast::ExprKind::Ident(ident) => self.identifier(*ident),
+            ast::ExprKind::Index(base, kind) => match kind {
+                ast::IndexKind::Index(index) => {
+                    let mut node = self.node("IndexAccess", span);
+                    fields!(node {
+                        "baseExpression": self.expr(base),
+                        "indexExpression": index.as_deref().map(|index| self.expr(index)),
+                    });
+                    node.into()
+                }
+                ast::IndexKind::Range(start, end) => {
+                    let mut node = self.node("IndexRangeAccess", span);
+                    fields!(node {
+                        "baseExpression": self.expr(base),
+                        "startExpression": start.as_deref().map(|start| self.expr(start)),
+                        "endExpression": end.as_deref().map(|end| self.expr(end)),
+                    });
+                    node.into()
+                }
+            },
+            ast::ExprKind::Lit(lit, subdenomination) => {
+                let mut node = self.literal(lit);
+                fields!(node { "subdenomination": subdenomination.map(|s| s.to_str()) });
+                node.into()
+            }
+            ast::ExprKind::Member(base, member) => {
+                let mut node = self.node("MemberAccess", span);
+                fields!(node {
+                    "expression": self.expr(base),
+                    "memberLocation": self.src(member.span),
+                    "memberName": member.to_string(),
+                });
+                node.into()
+            }
+            ast::ExprKind::New(ty) => {
+                let mut node = self.node("NewExpression", span);
+                fields!(node { "typeName": self.type_name(ty) });
+                node.into()
+            }
+            ast::ExprKind::Payable(args) => {
+                let mut node = self.node("FunctionCall", span);
+                let mut callee = self.node("ElementaryTypeNameExpression", span);
+                let mut type_name = self.node("ElementaryTypeName", span);
+                fields!(type_name { "name": "address", "stateMutability": "payable" });
+                fields!(callee { "typeName": type_name });
+                let (arguments, names, name_locations) = self.call_args(args);
+                fields!(node {
+                    "arguments": arguments,
+                    "expression": callee,
+                    "kind": "typeConversion",
+                    "names": names,
+                    "nameLocations": name_locations,
+                    "tryCall": false,
+                });
+                node.into()
+            }
+            ast::ExprKind::Ternary(cond, true_, false_) => {
+                let mut node = self.node("Conditional", span);
+                fields!(node {
+                    "condition": self.expr(cond),
+                    "trueExpression": self.expr(true_),
+                    "falseExpression": self.expr(false_),
+                });
+                node.into()
+            }
+            ast::ExprKind::TypeCall(ty) => {
+                let mut node = self.node("FunctionCall", span);
+                let mut callee = self.node("Identifier", span);
+                fields!(callee {
+                    "name": "type",
+                    "overloadedDeclarations": Vec::<Value>::new(),
+                });
+                let mut argument = self.node("ElementaryTypeNameExpression", ty.span);
+                fields!(argument { "typeName": self.type_name(ty) });
+                fields!(node {
+                    "arguments": vec![Value::from(argument)],
+                    "expression": callee,
+                    "kind": "functionCall",
+                    "names": Vec::<Value>::new(),
+                    "nameLocations": Vec::<Value>::new(),
+                    "tryCall": false,
+                });
+                node.into()
+            }
+            ast::ExprKind::Type(ty) => {
+                let mut node = self.node("ElementaryTypeNameExpression", span);
+                fields!(node { "typeName": self.type_name(ty) });
+                node.into()
+            }
+        }
+    }
+
+    /// `callee(args)`, where `try_call` is set for the external call of a try statement.
+    fn call(
+        &mut self,
+        callee: &ast::Expr<'_>,
+        args: &ast::CallArgs<'_>,
+        span: Span,
+        try_call: bool,
+    ) -> Value {
+        let mut node = self.node("FunctionCall", span);
+        let expression = self.expr(callee);
+        let (arguments, names, name_locations) = self.call_args(args);
+        fields!(node {
+            "arguments": arguments,
+            "expression": expression,
+            "kind": "functionCall",
+            "names": names,
+            "nameLocations": name_locations,
+            "tryCall": try_call,
+        });
+        node.into()
+    }
+
+    fn literal(&mut self, lit: &ast::Lit) -> Map<String, Value> {
+        let mut node = self.node("Literal", lit.span);
+        let (kind, value, hex_value): (_, Value, _) = match &lit.kind {
+            ast::LitKind::Str(kind, bytes) => {
+                let kind = match kind {
+                    ast::StrKind::Str => "string",
+                    ast::StrKind::Unicode => "unicodeString",
+                    ast::StrKind::Hex => "hexString",
+                };
+                let value = std::str::from_utf8(bytes).ok().map(ToString::to_string);
+                (kind, value.into(), alloy_primitives::hex::encode(bytes))
+            }
+            ast::LitKind::Bool(_) => {
+                let value = lit.symbol.to_string();
+                let hex = alloy_primitives::hex::encode(value.as_bytes());
+                ("bool", value.into(), hex)
+            }
+            ast::LitKind::Number(_)
+            | ast::LitKind::Rational(_)
+            | ast::LitKind::Address(_)
+            | ast::LitKind::Err(_) => {
+                let value = lit.symbol.to_string();
+                let hex = alloy_primitives::hex::encode(value.as_bytes());
+                ("number", value.into(), hex)
+            }
+        };
+        fields!(node { "hexValue": hex_value, "kind": kind, "value": value });
+        node
+    }
+
+    fn yul_block(&mut self, block: &[yul::Stmt<'_>], span: Span) -> Value {
+        let mut node = self.yul_node("YulBlock", span);
+        let statements = block.iter().map(|stmt| self.yul_stmt(stmt)).collect::<Vec<_>>();
+        fields!(node { "statements": statements });
+        node.into()
+    }
+
+    fn yul_block_spanned(&mut self, block: &[yul::Stmt<'_>]) -> Value {
+        let span = match (block.first(), block.last()) {
+            (Some(first), Some(last)) => first.span.to(last.span),
+            _ => Span::DUMMY,
+        };
+        self.yul_block(block, span)
+    }
+
+    fn yul_stmt(&mut self, stmt: &yul::Stmt<'_>) -> Value {
+        let span = stmt.span;
+        match &stmt.kind {
+            yul::StmtKind::Block(block) => self.yul_block(block, span),
+            yul::StmtKind::AssignSingle(path, expr) => {
+                let mut node = self.yul_node("YulAssignment", span);
+                fields!(node {
+                    "value": self.yul_expr(expr),
+                    "variableNames": vec![self.yul_identifier_path(path)],
+                });
+                node.into()
+            }
+            yul::StmtKind::AssignMulti(paths, call) => {
+                let mut node = self.yul_node("YulAssignment", span);
+                let names =
+                    paths.iter().map(|path| self.yul_identifier_path(path)).collect::<Vec<_>>();
+                fields!(node { "value": self.yul_call(call, span), "variableNames": names });
+                node.into()
+            }
+            yul::StmtKind::Expr(call) => {
+                let mut node = self.yul_node("YulExpressionStatement", span);
+                fields!(node { "expression": self.yul_call(call, span) });
+                node.into()
+            }
+            yul::StmtKind::If(cond, body) => {
+                let mut node = self.yul_node("YulIf", span);
+                fields!(node { "body": self.yul_block_spanned(body), "condition": self.yul_expr(cond) });
+                node.into()
+            }
+            yul::StmtKind::For { init, cond, step, body } => {
+                let mut node = self.yul_node("YulForLoop", span);
+                fields!(node {
+                    "body": self.yul_block_spanned(body),
+                    "condition": self.yul_expr(cond),
+                    "post": self.yul_block_spanned(step),
+                    "pre": self.yul_block_spanned(init),
+                });
+                node.into()
+            }
+            yul::StmtKind::Switch(switch) => {
+                let mut node = self.yul_node("YulSwitch", span);
+                let mut cases = switch
+                    .branches
+                    .iter()
+                    .map(|case| {
+                        let mut node = self.yul_node("YulCase", case.constant.span);
+                        let mut value = self.yul_node("YulLiteral", case.constant.span);
+                        value.extend(self.yul_literal(case.constant));
+                        fields!(node { "body": self.yul_block_spanned(&case.body), "value": value });
+                        Value::from(node)
+                    })
+                    .collect::<Vec<_>>();
+                if let Some(default) = &switch.default_case {
+                    let mut node = self.yul_node("YulCase", span);
+                    fields!(node { "body": self.yul_block_spanned(default), "value": "default" });
+                    cases.push(node.into());
+                }
+                fields!(node { "cases": cases, "expression": self.yul_expr(&switch.selector) });
+                node.into()
+            }
+            yul::StmtKind::Leave => self.yul_node("YulLeave", span).into(),
+            yul::StmtKind::Break => self.yul_node("YulBreak", span).into(),
+            yul::StmtKind::Continue => self.yul_node("YulContinue", span).into(),
+            yul::StmtKind::FunctionDef(function) => {
+                let mut node = self.yul_node("YulFunctionDefinition", span);
+                let parameters =
+                    function.parameters.iter().map(|p| self.yul_typed_name(*p)).collect::<Vec<_>>();
+                let returns =
+                    function.returns.iter().map(|p| self.yul_typed_name(*p)).collect::<Vec<_>>();
+                fields!(node {
+                    "body": self.yul_block_spanned(&function.body),
+                    "name": function.name.to_string(),
+                    "parameters": parameters,
+                    "returnVariables": returns,
+                });
+                node.into()
+            }
+            yul::StmtKind::VarDecl(idents, expr) => {
+                let mut node = self.yul_node("YulVariableDeclaration", span);
+                let variables = idents.iter().map(|i| self.yul_typed_name(*i)).collect::<Vec<_>>();
+                fields!(node {
+                    "value": expr.as_ref().map(|expr| self.yul_expr(expr)),
+                    "variables": variables,
+                });
+                node.into()
+            }
+        }
+    }
+
+    fn yul_typed_name(&self, ident: Ident) -> Value {
+        let mut node = self.yul_node("YulTypedName", ident.span);
+        fields!(node { "name": ident.to_string(), "type": "" });
+        node.into()
+    }
+
+    fn yul_identifier_path(&self, path: &ast::PathSlice) -> Value {
+        let mut node = self.yul_node("YulIdentifier", path.span());
+        fields!(node { "name": path.to_string() });
+        node.into()
+    }
+
+    fn yul_call(&mut self, call: &yul::ExprCall<'_>, span: Span) -> Value {
+        let mut node = self.yul_node("YulFunctionCall", span);
+        let mut name = self.yul_node("YulIdentifier", call.name.span);
+        fields!(name { "name": call.name.to_string() });
+        let arguments = call.arguments.iter().map(|arg| self.yul_expr(arg)).collect::<Vec<_>>();
+        fields!(node { "arguments": arguments, "functionName": name });
+        node.into()
+    }
+
+    fn yul_expr(&mut self, expr: &yul::Expr<'_>) -> Value {
+        match &expr.kind {
+            yul::ExprKind::Path(path) => self.yul_identifier_path(path),
+            yul::ExprKind::Call(call) => self.yul_call(call, expr.span),
+            yul::ExprKind::Lit(lit) => {
+                let mut node = self.yul_node("YulLiteral", expr.span);
+                node.extend(self.yul_literal(lit));
+                node.into()
+            }
+        }
+    }
+
+    fn yul_literal(&self, lit: &ast::Lit) -> Map<String, Value> {
+        let mut node = Map::new();
+        match &lit.kind {
+            ast::LitKind::Str(_, bytes) => {
+                let value = String::from_utf8_lossy(bytes).into_owned();
+                fields!(node {
+                    "hexValue": alloy_primitives::hex::encode(bytes),
+                    "kind": "string",
+                    "value": value,
+                });
+            }
+            ast::LitKind::Bool(_) => {
+                fields!(node { "kind": "bool", "value": lit.symbol.to_string() });
+            }
+            _ => {
+                fields!(node { "kind": "number", "value": lit.symbol.to_string() });
+            }
+        }
+        fields!(node { "type": "" });
+        node
+    }
+}
+
+fn user_definable_operator_str(op: ast::UserDefinableOperator) -> &'static str {
+    match op.to_op() {
+        either::Either::Left(op) => op.to_str(),
+        either::Either::Right(op) => op.to_str(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{find, with_pcx, FILE_NAME};
+    use thread_local::ThreadLocal;
+
+    const SRC: &str = "\
+contract C {
+    function f() external returns (uint) {}
+    function g() public {
+        try this.f() returns (uint x) {
+            x;
+        } catch Error(string memory) {
+        } catch {
+        }
+        this.f();
+    }
+}
+";
+
+    #[test]
+    fn solc_json_round_trip() {
+        let src = "\
+contract B {}
+
+contract C is B {
+    uint256 public x = 1e3;
+    mapping(address => uint256) balances;
+
+    event E(uint256 indexed a);
+
+    function f(uint256 a) external returns (uint256 b) {
+        unchecked {
+            b = a * 2;
+        }
+        for (uint256 i; i < a; ++i) {
+            emit E(i);
+        }
+        try this.g() returns (uint256 y) {
+            b = y;
+        } catch Error(string memory) {
+            revert();
+        } catch {
+        }
+    }
+
+    function g() external view returns (uint256) {
+        return x;
+    }
+}
+";
+        with_pcx(&[(FILE_NAME, src)], |pcx, files| {
+            let arenas = ThreadLocal::new();
+            let sources = pcx.parse(&arenas);
+            let json = sources_to_json(&sources).remove(FILE_NAME).unwrap().ast;
+            let ast = sources.asts().next().unwrap();
+
+            let arena = ast::Arena::new();
+            let reader = ast::SolcJsonReader::new(&arena).with_base_pos(files[0].start_pos);
+            let converted = reader.source_unit(&json).unwrap();
+
+            let config = ast::pretty::FormatConfig::default();
+            assert_eq!(
+                ast::pretty::print_source_unit(&converted, None, &config),
+                ast::pretty::print_source_unit(ast, None, &config),
+            );
+            let spans =
+                |unit: &ast::SourceUnit<'_>| unit.items.iter().map(|i| i.span).collect::<Vec<_>>();
+            assert_eq!(spans(&converted), spans(ast));
+        });
+    }
+
+    fn ast_json() -> Value {
+        with_pcx(&[(FILE_NAME, SRC)], |pcx, _| {
+            let arenas = ThreadLocal::new();
+            let sources = pcx.parse(&arenas);
+            sources_to_json(&sources).remove(FILE_NAME).unwrap().ast
+        })
+    }
+
+    /// Collects all the nodes of the given type, in pre-order.
+    fn nodes<'a>(json: &'a Value, node_type: &str, out: &mut Vec<&'a Value>) {
+        match json {
+            Value::Object(map) => {
+                if map.get("nodeType").and_then(Value::as_str) == Some(node_type) {
+                    out.push(json);
+                }
+                map.values().for_each(|v| nodes(v, node_type, out));
+            }
+            Value::Array(values) => values.iter().for_each(|v| nodes(v, node_type, out)),
+            _ => {}
+        }
+    }
+
+    fn nodes_of<'a>(json: &'a Value, node_type: &str) -> Vec<&'a Value> {
+        let mut out = Vec::new();
+        nodes(json, node_type, &mut out);
+        out
+    }
+
+    /// Returns the `src` of the text from `start` to the end of `end`.
+    fn src(start: &str, end: &str) -> String {
+        let lo = find(SRC, start, 0);
+        let hi = lo + find(&SRC[lo..], end, 0) + end.len();
+        format!("{lo}:{}:0", hi - lo)
+    }
+
+    #[test]
+    fn try_call() {
+        let json = ast_json();
+        let try_ = nodes_of(&json, "TryStatement")[0];
+        assert_eq!(try_["externalCall"]["nodeType"], "FunctionCall");
+        assert_eq!(try_["externalCall"]["tryCall"], true);
+        assert_eq!(try_["externalCall"]["src"], src("this.f()", ")"));
+
+        let calls = nodes_of(&json, "FunctionCall");
+        assert_eq!(calls.len(), 2);
+        assert_eq!(calls[1]["tryCall"], false);
+    }
+
+    #[test]
+    fn try_clause_spans() {
+        let json = ast_json();
+        let clauses = &nodes_of(&json, "TryStatement")[0]["clauses"];
+        assert_eq!(clauses[0]["src"], src("returns (uint x)", "}"));
+        assert_eq!(clauses[0]["block"]["src"], src("{\n            x;", "}"));
+        assert_eq!(clauses[1]["errorName"], "Error");
+        assert_eq!(clauses[1]["src"], src("catch Error", "}"));
+        assert_eq!(clauses[1]["block"]["src"], src("{\n        } catch {", "}"));
+        assert_eq!(clauses[2]["src"], src("catch {", "}"));
+        assert_eq!(clauses[2]["block"]["src"], src("{\n        }\n        this", "}"));
+    }
+
+    #[test]
+    fn block_spans() {
+        let json = ast_json();
+        let functions = nodes_of(&json, "FunctionDefinition");
+        assert_eq!(functions[0]["body"]["src"], src("{}", "}"));
+        assert_eq!(functions[0]["body"]["statements"], Value::Array(Vec::new()));
+        assert_eq!(functions[1]["body"]["src"], src("{\n        try", "this.f();\n    }"));
+    }
+}
@@ -0,0 +1,236 @@
+//! On-disk cache of code generation outputs, enabled with the session's `cache_dir`.
+//!
+//! Outputs are cached per contract, keyed on a hash of the compiler version, the settings that
+//! affect code generation, the list of all the compiled sources, and the contents of the source
+//! file defining the contract and of all the files it imports, directly or transitively. Changing
+//! a file therefore invalidates the outputs of every contract that depends on it, while the
+//! outputs of the other contracts are reused instead of being generated again. Adding or removing
+//! a source invalidates every output, as source mappings refer to sources by their index in the
+//! source list.
+//!
+//! Only code generation is cached: the sources are still parsed and analyzed on every
+//! compilation. Outputs whose generation emitted diagnostics, such as the contract code size
+//! warning, are not cached, so that the diagnostics are emitted again on the next compilation.
+
+use crate::{hir, ty::Gcx, ContractBytecode, GasEstimates};
+use alloy_primitives::{hex, keccak256, B256};
+use serde::{Deserialize, Serialize};
+use solar_data_structures::map::FxHashSet;
+use std::{fs, io, path::PathBuf};
+
+/// The cached code generation outputs of a contract.
+///
+/// Outputs that were not requested when the contract was last compiled are `None`.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub(super) struct CachedOutputs {
+    /// The unlinked creation and runtime bytecode.
+    pub(super) bytecode: Option<ContractBytecode>,
+    pub(super) ir: Option<String>,
+    pub(super) asm: Option<String>,
+    pub(super) gas_estimates: Option<GasEstimates>,
+}
+
+/// The cache directory of a session.
+pub(super) struct Cache<'gcx> {
+    gcx: Gcx<'gcx>,
+    dir: PathBuf,
+}
+
+impl<'gcx> Cache<'gcx> {
+    /// Returns the cache of the session, or `None` if caching is disabled or the cache directory
+    /// cannot be created.
+    pub(super) fn new(gcx: Gcx<'gcx>) -> Option<Self> {
+        let dir = gcx.sess.cache_dir.clone()?;
+        if let Err(e) = fs::create_dir_all(&dir) {
+            let msg = format!("failed to create cache directory {}: {e}", dir.display());
+            gcx.dcx().warn(msg).emit();
+            return None;
+        }
+        Some(Self { gcx, dir })
+    }
+
+    /// Returns the cached outputs of the given contract.
+    ///
+    /// Missing and unreadable entries are treated alike, as the outputs are generated again.
+    pub(super) fn load(&self, id: hir::ContractId) -> Option<CachedOutputs> {
+        let data = fs::read(self.path(id)).ok()?;
+        serde_json::from_slice(&data).ok()
+    }
+
+    /// Stores the outputs of the given contract, warning if it fails.
+    pub(super) fn store(&self, id: hir::ContractId, outputs: &CachedOutputs) {
+        let path = self.path(id);
+        let r = serde_json::to_vec(outputs)
+            .map_err(io::Error::from)
+            .and_then(|data| fs::write(&path, data));
+        if let Err(e) = r {
+            let msg = format!("failed to write cache entry {}: {e}", path.display());
+            self.gcx.dcx().warn(msg).emit();
+        }
+    }
+
+    fn path(&self, id: hir::ContractId) -> PathBuf {
+        self.dir.join(format!("{}.json", hex::encode(self.key(id))))
+    }
+
+    /// Returns the cache key of the given contract.
+    fn key(&self, id: hir::ContractId) -> B256 {
+        let gcx = self.gcx;
+        let sess = gcx.sess;
+        // Libraries are linked after loading the bytecode, but they are part of the metadata, as
+        // are the remappings.
+        let mut data = format!(
+            "{}\n{}\n{:?}\n{:?}\n{:?}\n{}\n{}\n{:?}\n{:?}\n",
+            solar_interface::VERSION,
+            gcx.contract_fully_qualified_name(id),
+            sess.evm_version,
+            sess.bytecode_hash,
+            sess.optimizer,
+            sess.via_ir,
+            sess.eof,
+            sess.libraries,
+            gcx.remappings,
+        )
+        .into_bytes();
+        // Source mappings refer to sources by their index in this list.
+        for source in gcx.source_list() {
+            data.extend_from_slice(
+                gcx.hir.source(source).file.name.display().to_string().as_bytes(),
+            );
+            data.push(0);
+        }
+        data.push(b'\n');
+        for (name, hash) in dependencies(gcx, gcx.hir.contract(id).source) {
+            data.extend_from_slice(name.as_bytes());
+            data.push(0);
+            data.extend_from_slice(hash.as_slice());
+        }
+        keccak256(data)
+    }
+}
+
+/// Returns the names and content hashes of the given source and of all the sources it imports,
+/// directly or transitively, sorted by name.
+fn dependencies(gcx: Gcx<'_>, source: hir::SourceId) -> Vec<(String, B256)> {
+    let mut seen = FxHashSet::default();
+    let mut stack = vec![source];
+    let mut dependencies = Vec::new();
+    while let Some(id) = stack.pop() {
+        if !seen.insert(id) {
+            continue;
+        }
+        let source = gcx.hir.source(id);
+        dependencies.push((source.file.name.display().to_string(), keccak256(&*source.file.src)));
+        stack.extend(source.imports.iter().map(|&(_, import)| import));
+    }
+    dependencies.sort_unstable();
+    dependencies
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{link::Bytecode, Codegen, ContractBytecode, ParsingContext};
+    use solar_interface::{config::CompilerOutput, source_map::FileName, ColorChoice, Session};
+    use std::{
+        collections::BTreeMap,
+        path::Path,
+        sync::atomic::{AtomicUsize, Ordering},
+    };
+
+    /// The number of times the test backend generated bytecode.
+    ///
+    /// Every generation produces different bytecode, so that cache hits can be told apart.
+    static GENERATIONS: AtomicUsize = AtomicUsize::new(0);
+
+    const BACKEND: Codegen = Codegen {
+        bytecode: |gcx, id| {
+            let n = GENERATIONS.fetch_add(1, Ordering::Relaxed);
+            let c = gcx.hir.contract(id);
+            if c.name.as_str() == "Warn" {
+                gcx.dcx().warn("generated a warning").span(c.name.span).emit();
+            }
+            let index = gcx.source_list().iter().position(|&s| s == c.source).unwrap();
+            Some(ContractBytecode {
+                bytecode: Bytecode::new(n.to_be_bytes().to_vec(), []),
+                runtime_bytecode: Bytecode::default(),
+                source_map: format!("0:0:{index}:-"),
+                runtime_source_map: String::new(),
+            })
+        },
+        ir: |_, _| None,
+        asm: |_, _| None,
+        gas_estimates: |_, _| None,
+    };
+
+    /// Compiles the given sources, returning the bytecode and source mapping of each contract.
+    fn compile(cache_dir: &Path, sources: &[(&str, &str)]) -> BTreeMap<String, (String, String)> {
+        let out_dir = tempfile::tempdir().unwrap();
+        let sess = Session::builder()
+            .with_buffer_emitter(ColorChoice::Never)
+            .emit([CompilerOutput::Bin, CompilerOutput::Srcmap].into())
+            .out_dir(out_dir.path().to_path_buf())
+            .cache_dir(cache_dir.to_path_buf())
+            .build();
+        sess.enter(|| {
+            let mut pcx = ParsingContext::new(&sess);
+            pcx.codegen = Some(BACKEND);
+            for &(name, src) in sources {
+                let name = FileName::Custom(name.into());
+                pcx.add_file(sess.source_map().new_source_file(name, || Ok(src.into())).unwrap());
+            }
+            pcx.parse_and_resolve().unwrap();
+        });
+        let json = std::fs::read(out_dir.path().join("combined.json")).unwrap();
+        let json: serde_json::Value = serde_json::from_slice(&json).unwrap();
+        let contracts = json["contracts"].as_object().unwrap();
+        contracts
+            .iter()
+            .map(|(name, c)| {
+                let field = |key: &str| c[key].as_str().unwrap().to_string();
+                (name.clone(), (field("bin"), field("srcmap")))
+            })
+            .collect()
+    }
+
+    #[test]
+    fn hit_and_miss() {
+        let dir = tempfile::tempdir().unwrap();
+        let sources = [("a.sol", "contract A {}"), ("b.sol", "contract B {}")];
+        let first = compile(dir.path(), &sources);
+        assert_eq!(first.len(), 2);
+        assert_eq!(compile(dir.path(), &sources), first);
+
+        let sources = [("a.sol", "contract A { uint x; }"), ("b.sol", "contract B {}")];
+        let second = compile(dir.path(), &sources);
+        assert_ne!(second["a.sol:A"], first["a.sol:A"]);
+        assert_eq!(second["b.sol:B"], first["b.sol:B"]);
+    }
+
+    #[test]
+    fn invalidation() {
+        let dir = tempfile::tempdir().unwrap();
+        let a = "import \"b.sol\"; contract A {}";
+        let first = compile(dir.path(), &[("a.sol", a), ("b.sol", "contract B {}")]);
+        let second = compile(dir.path(), &[("a.sol", a), ("b.sol", "contract B { uint x; }")]);
+        assert_ne!(second["a.sol:A"], first["a.sol:A"]);
+        assert_ne!(second["b.sol:B"], first["b.sol:B"]);
+    }
+
+    #[test]
+    fn srcmap() {
+        let dir = tempfile::tempdir().unwrap();
+        let first = compile(dir.path(), &[("b.sol", "contract B {}")]);
+        assert_eq!(first["b.sol:B"].1, "0:0:0:-");
+        // Adding a source before `b.sol` in the source list changes its index.
+        let second = compile(dir.path(), &[("a.sol", "contract A {}"), ("b.sol", "contract B {}")]);
+        assert_eq!(second["b.sol:B"].1, "0:0:1:-");
+    }
+
+    #[test]
+    fn warnings_are_not_cached() {
+        let dir = tempfile::tempdir().unwrap();
+        let sources = [("a.sol", "contract Warn {}")];
+        let first = compile(dir.path(), &sources);
+        assert_ne!(compile(dir.path(), &sources), first);
+    }
+}
@@ -0,0 +1,283 @@
+use crate::{link::LinkReferences, ty::Gcx, Codegen, GasEstimates};
+use serde::Serialize;
+use solar_interface::config::CompilerOutput;
+use std::{
+    collections::BTreeMap,
+    io::{self, Write},
+    path::Path,
+};
+
+mod ast_json;
+pub(crate) use ast_json::{sources_to_json, SourceJson};
+
+mod cache;
+use cache::Cache;
+
+mod natspec;
+pub(crate) use natspec::{check_inheritdoc, devdoc, userdoc};
+
+mod storage_layout;
+
+#[derive(Default, Serialize)]
+struct CombinedJson {
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    contracts: BTreeMap<String, CombinedJsonContract>,
+    #[serde(rename = "sourceList", skip_serializing_if = "Vec::is_empty")]
+    source_list: Vec<String>,
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    sources: BTreeMap<String, SourceJson>,
+    #[serde(rename = "import-graph", skip_serializing_if = "Option::is_none")]
+    import_graph: Option<serde_json::Value>,
+    version: &'static str,
+}
+
+#[derive(Default, Serialize)]
+struct CombinedJsonContract {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    abi: Option<Abi>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    bin: Option<String>,
+    #[serde(rename = "bin-runtime", skip_serializing_if = "Option::is_none")]
+    bin_runtime: Option<String>,
+    #[serde(rename = "link-references", skip_serializing_if = "Option::is_none")]
+    link_references: Option<LinkReferences>,
+    #[serde(rename = "link-references-runtime", skip_serializing_if = "Option::is_none")]
+    link_references_runtime: Option<LinkReferences>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    srcmap: Option<String>,
+    #[serde(rename = "srcmap-runtime", skip_serializing_if = "Option::is_none")]
+    srcmap_runtime: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    ir: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    asm: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    hashes: Option<Hashes>,
+    #[serde(rename = "error-hashes", skip_serializing_if = "Option::is_none")]
+    error_hashes: Option<Hashes>,
+    #[serde(rename = "event-hashes", skip_serializing_if = "Option::is_none")]
+    event_hashes: Option<Hashes>,
+    #[serde(rename = "storage-layout", skip_serializing_if = "Option::is_none")]
+    storage_layout: Option<serde_json::Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    devdoc: Option<serde_json::Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    userdoc: Option<serde_json::Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    metadata: Option<String>,
+    #[serde(rename = "gas-estimates", skip_serializing_if = "Option::is_none")]
+    gas_estimates: Option<GasEstimates>,
+}
+
+type Abi = Vec<alloy_json_abi::AbiItem<'static>>;
+type Hashes = BTreeMap<String, String>;
+
+pub(crate) fn emit(gcx: Gcx<'_>, sources: BTreeMap<String, SourceJson>, codegen: Option<Codegen>) {
+    // Source indices in the AST and in source mappings refer to this list.
+    let wants_source_list = !sources.is_empty()
+        || gcx
+            .sess
+            .emit
+            .iter()
+            .any(|e| matches!(e, CompilerOutput::Srcmap | CompilerOutput::SrcmapRuntime));
+    let source_list = if wants_source_list {
+        let sources = gcx.source_list().into_iter();
+        sources.map(|id| gcx.hir.source(id).file.name.display().to_string()).collect()
+    } else {
+        Vec::new()
+    };
+    // The graph is also written in the DOT language next to the combined JSON.
+    let import_graph =
+        gcx.sess.emit.contains(&CompilerOutput::ImportGraph).then(|| gcx.hir.dependency_graph());
+    if let (Some(graph), Some(out_dir)) = (&import_graph, &gcx.sess.out_dir) {
+        let path = out_dir.join("import-graph.dot");
+        let _ = std::fs::create_dir_all(out_dir)
+            .and_then(|()| std::fs::write(&path, graph.to_dot()))
+            .map_err(|e| gcx.dcx().err(format!("failed to write {}: {e}", path.display())).emit());
+    }
+    let mut output = CombinedJson {
+        contracts: Default::default(),
+        source_list,
+        sources,
+        import_graph: import_graph.map(|graph| graph.to_json()),
+        version: solar_interface::VERSION,
+    };
+    let wants_codegen = gcx.sess.emit.iter().any(|e| {
+        matches!(
+            e,
+            CompilerOutput::Bin
+                | CompilerOutput::BinRuntime
+                | CompilerOutput::Srcmap
+                | CompilerOutput::SrcmapRuntime
+                | CompilerOutput::Ir
+                | CompilerOutput::Asm
+                | CompilerOutput::GasEstimates
+        )
+    });
+    if wants_codegen && codegen.is_none() {
+        let msg =
+            "bytecode, IR, assembly and gas estimate outputs require a code generation backend";
+        gcx.dcx().err(msg).emit();
+        return;
+    }
+    if gcx.sess.emit.iter().any(|e| e.is_per_contract()) {
+        let cache = if wants_codegen { Cache::new(gcx) } else { None };
+        for id in gcx.hir.contract_ids() {
+            let name = gcx.contract_fully_qualified_name(id).to_string();
+            let contract_output = output.contracts.entry(name).or_default();
+            let c = gcx.hir.contract(id);
+            // solc emits empty code for contracts that cannot be deployed.
+            let deployable = c.can_be_deployed() && !c.is_abstract();
+            let loaded = cache
+                .as_ref()
+                .filter(|_| deployable)
+                .and_then(|cache| cache.load(id))
+                .unwrap_or_default();
+            let mut cached = loaded.clone();
+            let diagnostics_before = diagnostic_count(gcx);
+            let mut bytecode_cache = None;
+            let mut bytecode = || {
+                bytecode_cache
+                    .get_or_insert_with(|| {
+                        if !deployable {
+                            return Some(Default::default());
+                        }
+                        let mut bytecode = get_or_generate(&mut cached.bytecode, || {
+                            (codegen.unwrap().bytecode)(gcx, id)
+                        })?;
+                        bytecode.bytecode.link(&gcx.sess.libraries);
+                        bytecode.runtime_bytecode.link(&gcx.sess.libraries);
+                        Some(bytecode)
+                    })
+                    .clone()
+            };
+            for &emit in &gcx.sess.emit {
+                match emit {
+                    // Unlinked library addresses are emitted as placeholders, along with their
+                    // link references.
+                    CompilerOutput::Bin => {
+                        let b = bytecode().map(|b| b.bytecode);
+                        contract_output.bin = b.as_ref().map(|b| b.to_hex());
+                        contract_output.link_references =
+                            b.filter(|b| !b.is_linked()).map(|b| b.link_references);
+                    }
+                    CompilerOutput::BinRuntime => {
+                        let b = bytecode().map(|b| b.runtime_bytecode);
+                        contract_output.bin_runtime = b.as_ref().map(|b| b.to_hex());
+                        contract_output.link_references_runtime =
+                            b.filter(|b| !b.is_linked()).map(|b| b.link_references);
+                    }
+                    CompilerOutput::Srcmap => {
+                        contract_output.srcmap = bytecode().map(|b| b.source_map);
+                    }
+                    CompilerOutput::SrcmapRuntime => {
+                        contract_output.srcmap_runtime = bytecode().map(|b| b.runtime_source_map);
+                    }
+                    CompilerOutput::Ir => {
+                        contract_output.ir = if deployable {
+                            get_or_generate(&mut cached.ir, || (codegen.unwrap().ir)(gcx, id))
+                        } else {
+                            Some(String::new())
+                        };
+                    }
+                    CompilerOutput::Asm => {
+                        contract_output.asm = if deployable {
+                            get_or_generate(&mut cached.asm, || (codegen.unwrap().asm)(gcx, id))
+                        } else {
+                            Some(String::new())
+                        };
+                    }
+                    CompilerOutput::Abi => contract_output.abi = Some(gcx.contract_abi(id)),
+                    CompilerOutput::Hashes => {
+                        let selectors = crate::selectors::contract_selectors(gcx, id);
+                        contract_output.hashes = Some(hashes(selectors.functions));
+                        if !selectors.errors.is_empty() {
+                            contract_output.error_hashes = Some(hashes(selectors.errors));
+                        }
+                        if !selectors.events.is_empty() {
+                            contract_output.event_hashes = Some(hashes(selectors.events));
+                        }
+                    }
+                    CompilerOutput::StorageLayout => {
+                        contract_output.storage_layout =
+                            Some(storage_layout::storage_layout(gcx, id));
+                    }
+                    CompilerOutput::Docs => {
+                        contract_output.devdoc = Some(natspec::devdoc(gcx, id));
+                        contract_output.userdoc = Some(natspec::userdoc(gcx, id));
+                    }
+                    CompilerOutput::Metadata => {
+                        contract_output.metadata =
+                            Some(crate::metadata::contract_metadata(gcx, id));
+                    }
+                    CompilerOutput::GasEstimates => {
+                        contract_output.gas_estimates = if deployable {
+                            get_or_generate(&mut cached.gas_estimates, || {
+                                (codegen.unwrap().gas_estimates)(gcx, id)
+                            })
+                        } else {
+                            Some(Default::default())
+                        };
+                    }
+                    // Emitted per source, not per contract.
+                    CompilerOutput::AstJson | CompilerOutput::ImportGraph => {}
+                }
+            }
+            if let Some(cache) = &cache {
+                // Diagnostics are not cached, so outputs that emitted some are generated again.
+                if cached != loaded && diagnostic_count(gcx) == diagnostics_before {
+                    cache.store(id, &cached);
+                }
+            }
+        }
+    }
+    let _ = (|| {
+        let out_path = gcx.sess.out_dir.as_deref().map(|dir| dir.join("combined.json"));
+        let mut writer = out_writer(out_path.as_deref())?;
+        to_json(&mut writer, &output, gcx.sess.pretty_json)?;
+        writer.flush()?;
+        Ok::<_, io::Error>(())
+    })()
+    .map_err(|e| gcx.dcx().err(format!("failed to write to output: {e}")).emit());
+}
+
+/// Returns the number of errors and warnings emitted so far.
+fn diagnostic_count(gcx: Gcx<'_>) -> usize {
+    gcx.dcx().err_count() + gcx.dcx().warn_count()
+}
+
+/// Returns the output in `slot`, generating it and storing it there if it is missing.
+fn get_or_generate<T: Clone>(
+    slot: &mut Option<T>,
+    generate: impl FnOnce() -> Option<T>,
+) -> Option<T> {
+    if slot.is_none() {
+        *slot = generate();
+    }
+    slot.clone()
+}
+
+fn hashes<T: AsRef<[u8]>>(selectors: BTreeMap<String, T>) -> Hashes {
+    selectors.into_iter().map(|(sig, sel)| (sig, alloy_primitives::hex::encode(sel))).collect()
+}
+
+fn out_writer(path: Option<&Path>) -> io::Result<impl io::Write> {
+    let out: Box<dyn io::Write> = if let Some(path) = path {
+        Box::new(std::fs::File::create(path)?)
+    } else {
+        Box::new(std::io::stdout())
+    };
+    Ok(io::BufWriter::new(out))
+}
+
+fn to_json<W: io::Write, T: Serialize>(
+    writer: W,
+    value: &T,
+    pretty: bool,
+) -> serde_json::Result<()> {
+    if pretty {
+        serde_json::to_writer_pretty(writer, value)
+    } else {
+        serde_json::to_writer(writer, value)
+    }
+}
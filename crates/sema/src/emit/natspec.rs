@@ -0,0 +1,304 @@
+//! solc-compatible NatSpec `userdoc` and `devdoc` output.
+//!
+//! Reference: <https://docs.soliditylang.org/en/latest/natspec-format.html#documentation-output>
+
+use crate::{hir, ty::Gcx};
+use serde_json::{Map, Value};
+use solar_ast::{NatSpecItem, NatSpecKind};
+use solar_interface::Span;
+
+/// Returns the user documentation of the given contract.
+pub(crate) fn userdoc(gcx: Gcx<'_>, id: hir::ContractId) -> Value {
+    let c = gcx.hir.contract(id);
+    let mut doc = Map::new();
+    doc.insert("kind".into(), "user".into());
+
+    let mut methods = Map::new();
+    let mut insert_method = |key: String, docs: &[NatSpecItem]| {
+        if let Some(notice) = join_tags(docs, NatSpecKind::Notice) {
+            methods.insert(key, object([("notice", notice.into())]));
+        }
+    };
+    if let Some(ctor) = c.ctor {
+        insert_method("constructor".into(), gcx.hir.function(ctor).docs);
+    }
+    for f in gcx.interface_functions(id) {
+        insert_method(gcx.item_signature(f.id.into()).to_string(), &function_docs(gcx, f.id));
+    }
+    doc.insert("methods".into(), methods.into());
+
+    let (events, errors) = events_and_errors(gcx, id, |docs| {
+        join_tags(docs, NatSpecKind::Notice).map(|notice| object([("notice", notice.into())]))
+    });
+    if !events.is_empty() {
+        doc.insert("events".into(), events.into());
+    }
+    if !errors.is_empty() {
+        doc.insert("errors".into(), errors.into());
+    }
+
+    if let Some(notice) = join_tags(c.docs, NatSpecKind::Notice) {
+        doc.insert("notice".into(), notice.into());
+    }
+    doc.insert("version".into(), 1.into());
+    doc.into()
+}
+
+/// Returns the developer documentation of the given contract.
+pub(crate) fn devdoc(gcx: Gcx<'_>, id: hir::ContractId) -> Value {
+    let c = gcx.hir.contract(id);
+    let mut doc = Map::new();
+    doc.insert("kind".into(), "dev".into());
+
+    if let Some(author) = join_tags(c.docs, NatSpecKind::Author) {
+        doc.insert("author".into(), author.into());
+    }
+    if let Some(details) = join_tags(c.docs, NatSpecKind::Dev) {
+        doc.insert("details".into(), details.into());
+    }
+    if let Some(title) = join_tags(c.docs, NatSpecKind::Title) {
+        doc.insert("title".into(), title.into());
+    }
+    insert_custom_tags(&mut doc, c.docs);
+
+    let mut methods = Map::new();
+    let mut insert_method = |key: String, f: hir::FunctionId, docs: &[NatSpecItem]| {
+        let method = dev_method(gcx, gcx.hir.function(f), docs);
+        if !method.is_empty() {
+            methods.insert(key, method.into());
+        }
+    };
+    if let Some(ctor) = c.ctor {
+        insert_method("constructor".into(), ctor, gcx.hir.function(ctor).docs);
+    }
+    for f in gcx.interface_functions(id) {
+        // Getters are documented in `stateVariables` instead.
+        if gcx.hir.function(f.id).is_getter() {
+            continue;
+        }
+        insert_method(gcx.item_signature(f.id.into()).to_string(), f.id, &function_docs(gcx, f.id));
+    }
+    doc.insert("methods".into(), methods.into());
+
+    let (events, errors) = events_and_errors(gcx, id, |docs| {
+        let mut item = Map::new();
+        if let Some(details) = join_tags(docs, NatSpecKind::Dev) {
+            item.insert("details".into(), details.into());
+        }
+        insert_params(&mut item, docs);
+        insert_custom_tags(&mut item, docs);
+        (!item.is_empty()).then(|| item.into())
+    });
+    if !events.is_empty() {
+        doc.insert("events".into(), events.into());
+    }
+    if !errors.is_empty() {
+        doc.insert("errors".into(), errors.into());
+    }
+
+    let mut state_variables = Map::new();
+    for var_id in c.variables() {
+        let var = gcx.hir.variable(var_id);
+        let Some(name) = var.name else { continue };
+        let mut item = Map::new();
+        if let Some(details) = join_tags(var.docs, NatSpecKind::Dev) {
+            item.insert("details".into(), details.into());
+        }
+        if let Some(ret) = join_tags(var.docs, NatSpecKind::Return) {
+            item.insert("return".into(), ret.into());
+        }
+        insert_custom_tags(&mut item, var.docs);
+        if !item.is_empty() {
+            state_variables.insert(name.to_string(), item.into());
+        }
+    }
+    if !state_variables.is_empty() {
+        doc.insert("stateVariables".into(), state_variables.into());
+    }
+
+    doc.insert("version".into(), 1.into());
+    doc.into()
+}
+
+fn dev_method(gcx: Gcx<'_>, f: &hir::Function<'_>, docs: &[NatSpecItem]) -> Map<String, Value> {
+    let mut method = Map::new();
+    if let Some(details) = join_tags(docs, NatSpecKind::Dev) {
+        method.insert("details".into(), details.into());
+    }
+    insert_params(&mut method, docs);
+
+    let mut returns = Map::new();
+    let return_docs = docs.iter().filter(|doc| doc.kind == NatSpecKind::Return);
+    for (i, doc) in return_docs.enumerate() {
+        let content = doc.content.as_str();
+        let name = f.returns.get(i).and_then(|&ret| gcx.hir.variable(ret).name);
+        let (key, value) = match name {
+            // Named return values are documented as `@return <name> <description>`.
+            Some(name) => {
+                let value = content
+                    .strip_prefix(name.as_str())
+                    .filter(|rest| rest.is_empty() || rest.starts_with(char::is_whitespace))
+                    .map_or(content, str::trim_start);
+                (name.to_string(), value)
+            }
+            None => (format!("_{i}"), content),
+        };
+        returns.insert(key, value.into());
+    }
+    if !returns.is_empty() {
+        method.insert("returns".into(), returns.into());
+    }
+
+    insert_custom_tags(&mut method, docs);
+    method
+}
+
+/// Collects the documentation of the events and errors of the given contract, including
+/// inherited ones.
+///
+/// Events are keyed by signature. Errors are keyed by signature too, but their values are arrays
+/// since multiple errors can share the same signature.
+fn events_and_errors(
+    gcx: Gcx<'_>,
+    id: hir::ContractId,
+    mut f: impl FnMut(&[NatSpecItem]) -> Option<Value>,
+) -> (Map<String, Value>, Map<String, Value>) {
+    let mut events = Map::new();
+    let mut errors = Map::new();
+    for item in gcx.hir.contract_item_ids(id) {
+        match item {
+            hir::ItemId::Event(event_id) => {
+                let key = gcx.item_signature(item).to_string();
+                if events.contains_key(&key) {
+                    continue;
+                }
+                if let Some(value) = f(gcx.hir.event(event_id).docs) {
+                    events.insert(key, value);
+                }
+            }
+            hir::ItemId::Error(error_id) => {
+                if let Some(value) = f(gcx.hir.error(error_id).docs) {
+                    let key = gcx.item_signature(item).to_string();
+                    let entry = errors.entry(key).or_insert_with(|| Value::Array(Vec::new()));
+                    entry.as_array_mut().unwrap().push(value);
+                }
+            }
+            _ => {}
+        }
+    }
+    (events, errors)
+}
+
+/// Checks that the `@inheritdoc` tag of the given function, if any, refers to a base contract
+/// with a matching function.
+pub(crate) fn check_inheritdoc(gcx: Gcx<'_>, id: hir::FunctionId) {
+    let f = gcx.hir.function(id);
+    if f.name.is_none()
+        || !f.docs.iter().any(|doc| matches!(doc.kind, NatSpecKind::Inheritdoc { .. }))
+    {
+        return;
+    }
+    if let Err((msg, span)) = resolve_function_docs(gcx, id) {
+        gcx.dcx().err(msg).span(span).emit();
+    }
+}
+
+/// Returns the documentation of the given function, resolving `@inheritdoc` and inheriting the
+/// documentation of the overridden base function if there is none.
+///
+/// Invalid `@inheritdoc` tags are ignored, since they are reported by [`check_inheritdoc`].
+fn function_docs(gcx: Gcx<'_>, id: hir::FunctionId) -> Vec<NatSpecItem> {
+    resolve_function_docs(gcx, id).unwrap_or_else(|_| gcx.hir.function(id).docs.to_vec())
+}
+
+/// Resolves the documentation of the given function like [`function_docs`], returning an error
+/// message and span if its `@inheritdoc` tag is invalid.
+fn resolve_function_docs(
+    gcx: Gcx<'_>,
+    id: hir::FunctionId,
+) -> Result<Vec<NatSpecItem>, (String, Span)> {
+    let f = gcx.hir.function(id);
+    let inheritdoc = f.docs.iter().find_map(|doc| match doc.kind {
+        NatSpecKind::Inheritdoc { contract } => Some((contract, doc.span)),
+        _ => None,
+    });
+    let Some(contract) = f.contract else { return Ok(f.docs.to_vec()) };
+    if inheritdoc.is_none() && (!f.docs.is_empty() || !f.override_) {
+        return Ok(f.docs.to_vec());
+    }
+
+    let parameters = gcx.item_parameter_types(id.into());
+    let bases =
+        gcx.hir.contract(contract).linearized_bases.iter().filter(|&&base| base != contract);
+    let mut base_contract_found = false;
+    for &base_id in bases {
+        let base = gcx.hir.contract(base_id);
+        if let Some((name, _)) = inheritdoc {
+            if base.name.name != name {
+                continue;
+            }
+            base_contract_found = true;
+        }
+        let base_f = base.functions().find(|&base_f| {
+            gcx.hir.function(base_f).name.map(|n| n.name) == f.name.map(|n| n.name)
+                && gcx.item_parameter_types(base_f.into()) == parameters
+        });
+        if let Some(base_f) = base_f {
+            // Tags specified locally take precedence over inherited ones.
+            let mut docs = function_docs(gcx, base_f);
+            docs.retain(|doc| !f.docs.iter().any(|local| local.kind == doc.kind));
+            docs.extend(
+                f.docs.iter().filter(|doc| !matches!(doc.kind, NatSpecKind::Inheritdoc { .. })),
+            );
+            return Ok(docs);
+        }
+        if inheritdoc.is_some() {
+            break;
+        }
+    }
+
+    match inheritdoc {
+        Some((name, span)) => {
+            let msg = if base_contract_found {
+                format!(
+                    "`@inheritdoc` references contract `{name}`, which has no matching function"
+                )
+            } else {
+                format!("`@inheritdoc` references `{name}`, which is not a base contract")
+            };
+            Err((msg, span))
+        }
+        None => Ok(f.docs.to_vec()),
+    }
+}
+
+/// Joins the contents of all the tags of the given kind with newlines.
+fn join_tags(docs: &[NatSpecItem], kind: NatSpecKind) -> Option<String> {
+    let mut contents = docs.iter().filter(|doc| doc.kind == kind).map(|doc| doc.content.as_str());
+    let first = contents.next()?;
+    Some(contents.fold(first.to_string(), |acc, s| acc + "\n" + s))
+}
+
+fn insert_params(map: &mut Map<String, Value>, docs: &[NatSpecItem]) {
+    let mut params = Map::new();
+    for doc in docs {
+        if let NatSpecKind::Param { name } = doc.kind {
+            params.insert(name.to_string(), doc.content.as_str().into());
+        }
+    }
+    if !params.is_empty() {
+        map.insert("params".into(), params.into());
+    }
+}
+
+fn insert_custom_tags(map: &mut Map<String, Value>, docs: &[NatSpecItem]) {
+    for doc in docs {
+        if let NatSpecKind::Custom { name } = doc.kind {
+            map.insert(format!("custom:{name}"), doc.content.as_str().into());
+        }
+    }
+}
+
+fn object<const N: usize>(fields: [(&str, Value); N]) -> Value {
+    fields.into_iter().map(|(k, v)| (k.to_string(), v)).collect::<Map<_, _>>().into()
+}
@@ -0,0 +1,160 @@
+//! solc-compatible `storageLayout` output.
+//!
+//! Reference: <https://docs.soliditylang.org/en/latest/internals/layout_in_storage.html#json-output>
+
+use crate::{
+    hir,
+    ty::{Gcx, StorageItem, Ty, TyKind, TySolcPrinter},
+};
+use alloy_primitives::U256;
+use serde_json::{Map, Value};
+use solar_ast::{DataLocation, ElementaryType};
+use std::fmt::Write;
+
+/// Returns the storage layout of the given contract.
+pub(super) fn storage_layout(gcx: Gcx<'_>, id: hir::ContractId) -> Value {
+    let layout = gcx.storage_layout(id);
+    let mut types = Map::new();
+    let storage =
+        layout.items.iter().map(|item| storage_item(gcx, item, &mut types)).collect::<Vec<_>>();
+    let mut output = Map::new();
+    output.insert("storage".into(), storage.into());
+    // solc emits `null` instead of an empty object.
+    output.insert("types".into(), if types.is_empty() { Value::Null } else { types.into() });
+    output.into()
+}
+
+fn storage_item<'gcx>(
+    gcx: Gcx<'gcx>,
+    item: &StorageItem<'gcx>,
+    types: &mut Map<String, Value>,
+) -> Value {
+    let var = gcx.hir.variable(item.id);
+    let mut node = Map::new();
+    if let Some(contract) = var.contract.filter(|_| var.is_state_variable()) {
+        node.insert(
+            "contract".into(),
+            gcx.contract_fully_qualified_name(contract).to_string().into(),
+        );
+    }
+    node.insert("label".into(), var.name.map(|n| n.to_string()).unwrap_or_default().into());
+    node.insert("offset".into(), item.offset.into());
+    node.insert("slot".into(), item.slot.to_string().into());
+    node.insert("type".into(), add_type(gcx, item.ty, types).into());
+    node.into()
+}
+
+/// Adds the given type and all the types it references to `types`, returning its identifier.
+fn add_type<'gcx>(gcx: Gcx<'gcx>, ty: Ty<'gcx>, types: &mut Map<String, Value>) -> String {
+    let id = type_identifier(gcx, ty);
+    if types.contains_key(&id) {
+        return id;
+    }
+    // Insert a placeholder first to stop recursion through recursive structs.
+    types.insert(id.clone(), Value::Null);
+
+    let ty = ty.peel_refs();
+    let mut node = Map::new();
+    let encoding = match ty.kind {
+        TyKind::Elementary(ElementaryType::String | ElementaryType::Bytes) => "bytes",
+        TyKind::DynArray(_) => "dynamic_array",
+        TyKind::Mapping(..) => "mapping",
+        _ => "inplace",
+    };
+    node.insert("encoding".into(), encoding.into());
+    match ty.kind {
+        TyKind::Mapping(key, value) => {
+            node.insert("key".into(), add_type(gcx, key, types).into());
+            node.insert("value".into(), add_type(gcx, value, types).into());
+        }
+        TyKind::DynArray(elem) | TyKind::Array(elem, _) => {
+            node.insert("base".into(), add_type(gcx, elem, types).into());
+        }
+        TyKind::Struct(struct_id) => {
+            let layout = gcx.struct_storage_layout(struct_id);
+            let members = layout.items.iter().map(|item| storage_item(gcx, item, types));
+            node.insert("members".into(), members.collect::<Vec<_>>().into());
+        }
+        _ => {}
+    }
+    let mut label = String::new();
+    TySolcPrinter::new(gcx, &mut label).print(ty).unwrap();
+    node.insert("label".into(), label.into());
+    let slots = gcx.type_storage_slots(ty);
+    let number_of_bytes = if slots == U256::from(1) {
+        U256::from(gcx.type_storage_bytes(ty))
+    } else {
+        slots.saturating_mul(U256::from(32))
+    };
+    node.insert("numberOfBytes".into(), number_of_bytes.to_string().into());
+
+    types.insert(id.clone(), node.into());
+    id
+}
+
+/// Returns the solc type identifier of the given type, e.g. `t_uint256` or
+/// `t_mapping(t_address,t_uint256)`.
+fn type_identifier(gcx: Gcx<'_>, ty: Ty<'_>) -> String {
+    let mut s = String::from("t_");
+    write_type_identifier(gcx, ty, &mut s);
+    s
+}
+
+fn write_type_identifier(gcx: Gcx<'_>, ty: Ty<'_>, s: &mut String) {
+    // Nested reference types, such as array elements, are implicitly in storage.
+    let (ty, loc) = match ty.kind {
+        TyKind::Ref(ty, loc) => (ty, Some(loc)),
+        _ => (ty, ty.is_reference_type().then_some(DataLocation::Storage)),
+    };
+    match ty.kind {
+        TyKind::Elementary(ElementaryType::Address(true)) => s.push_str("address_payable"),
+        TyKind::Elementary(ty) => ty.write_abi_str(s).unwrap(),
+        TyKind::Contract(id) => {
+            let c = gcx.hir.contract(id);
+            let kind = if c.kind.is_library() { "library" } else { "contract" };
+            write!(s, "{kind}({}){}", c.name, id.get()).unwrap();
+        }
+        TyKind::Struct(id) => write!(s, "struct({}){}", gcx.item_name(id), id.get()).unwrap(),
+        TyKind::Enum(id) => write!(s, "enum({}){}", gcx.item_name(id), id.get()).unwrap(),
+        TyKind::Udvt(_, id) => {
+            write!(s, "userDefinedValueType({}){}", gcx.item_name(id), id.get()).unwrap()
+        }
+        TyKind::DynArray(elem) => {
+            s.push_str("array(");
+            s.push_str(&type_identifier(gcx, elem));
+            s.push_str(")dyn");
+        }
+        TyKind::Array(elem, len) => {
+            s.push_str("array(");
+            s.push_str(&type_identifier(gcx, elem));
+            write!(s, "){len}").unwrap();
+        }
+        TyKind::Mapping(key, value) => {
+            write!(s, "mapping({},{})", type_identifier(gcx, key), type_identifier(gcx, value))
+                .unwrap();
+        }
+        TyKind::FnPtr(f) => {
+            let vis =
+                if f.visibility == hir::Visibility::External { "external" } else { "internal" };
+            write!(s, "function_{vis}_{}(", f.state_mutability).unwrap();
+            for (i, &param) in f.parameters.iter().enumerate() {
+                if i > 0 {
+                    s.push(',');
+                }
+                s.push_str(&type_identifier(gcx, param));
+            }
+            s.push_str(")returns(");
+            for (i, &ret) in f.returns.iter().enumerate() {
+                if i > 0 {
+                    s.push(',');
+                }
+                s.push_str(&type_identifier(gcx, ret));
+            }
+            s.push(')');
+        }
+        _ => write!(s, "{ty:?}").unwrap(),
+    }
+    if let Some(loc) = loc {
+        write!(s, "_{loc}").unwrap();
+    }
+}
@@ -1,19 +1,36 @@
-use crate::{hir, ty::Gcx};
+use crate::{
+    builtins::Builtin,
+    hir,
+    ty::{Gcx, Ty, TyKind},
+};
 use alloy_primitives::U256;
-use solar_ast::LitKind;
+use num_bigint::{BigInt, Sign};
+use num_integer::Integer;
+use num_rational::BigRational;
+use num_traits::{One, Signed, ToPrimitive, Zero};
+use solar_ast::{ElementaryType, LitKind};
 use solar_interface::{diagnostics::ErrorGuaranteed, Span};
 use std::fmt;
 
 const RECURSION_LIMIT: usize = 64;
 
-// TODO: `convertType` for truncating and extending correctly: https://github.com/ethereum/solidity/blob/de1a017ccb935d149ed6bcbdb730d89883f8ce02/libsolidity/analysis/ConstantEvaluator.cpp#L234
+/// The maximum number of bits of the numerator and denominator of an intermediate value.
+///
+/// Same as solc's limit for rational number literals.
+const PRECISION_LIMIT: u64 = 4096;
 
 /// Evaluates simple constants.
 ///
-/// This only supports basic arithmetic and logical operations, and does not support more complex
+/// Values are computed as arbitrary-precision rationals, like solc's rational number literals: for
+/// example, `(1 / 2) * 4` evaluates to `2`, and intermediate values can exceed 256 bits. The final
+/// value is then checked to be an integer that fits in the expected type. References to `constant`
+/// variables are checked against the declared type of the variable.
+///
+/// This only supports literals, arithmetic and logical operations, `constant` variables, integer
+/// and enum conversions, and `type(T).min` and `type(T).max`. It does not support more complex
 /// operations like function calls or memory allocation.
 ///
-/// This is only supposed to be used for array sizes and other simple constants.
+/// This is used for array sizes, `constant` variables and other simple constants.
 pub struct ConstantEvaluator<'gcx> {
     pub gcx: Gcx<'gcx>,
     depth: usize,
@@ -32,8 +49,44 @@ impl<'gcx> ConstantEvaluator<'gcx> {
         self.try_eval(expr).map_err(|err| self.emit_eval_error(expr, err))
     }
 
+    /// Evaluates the given expression as a value of type `ty`, emitting an error diagnostic if it
+    /// fails.
+    ///
+    /// See [`try_eval_as`](Self::try_eval_as).
+    pub fn eval_as(
+        &mut self,
+        expr: &hir::Expr<'_>,
+        ty: Ty<'gcx>,
+    ) -> Result<IntScalar, ErrorGuaranteed> {
+        self.try_eval_as(expr, ty).map_err(|err| self.emit_eval_error(expr, err))
+    }
+
     /// Evaluates the given expression, returning an error if it fails.
+    ///
+    /// The value must be an integer in the range of either `int256` or `uint256`. Negative values
+    /// are returned in two's complement.
     pub fn try_eval(&mut self, expr: &hir::Expr<'_>) -> EvalResult<'gcx> {
+        let value = self.try_eval_rational(expr)?;
+        IntScalar::from_rational(&value).map_err(|kind| kind.spanned(expr.span))
+    }
+
+    /// Evaluates the given expression as a value of type `ty`, returning an error if it fails.
+    ///
+    /// If `ty` is an integer type, the value must fit in it. Negative values are returned in two's
+    /// complement.
+    pub fn try_eval_as(&mut self, expr: &hir::Expr<'_>, ty: Ty<'gcx>) -> EvalResult<'gcx> {
+        let value = self.try_eval_rational(expr)?;
+        narrow(&value, ty)
+            .and_then(|()| IntScalar::from_rational(&value))
+            .map_err(|kind| kind.spanned(expr.span))
+    }
+
+    /// Evaluates the given expression to a rational number, returning an error if it fails.
+    pub fn try_eval_rational(&mut self, expr: &hir::Expr<'_>) -> Result<BigRational, EvalError> {
+        self.try_eval_value(expr).map(|v| v.value)
+    }
+
+    fn try_eval_value(&mut self, expr: &hir::Expr<'_>) -> Result<Value, EvalError> {
         self.depth += 1;
         if self.depth > RECURSION_LIMIT {
             return Err(EE::RecursionLimitReached.spanned(expr.span));
@@ -59,69 +112,315 @@ impl<'gcx> ConstantEvaluator<'gcx> {
         }
     }
 
-    fn eval_expr(&mut self, expr: &hir::Expr<'_>) -> EvalResult<'gcx> {
+    fn eval_expr(&mut self, expr: &hir::Expr<'_>) -> Result<Value, EvalError> {
         let expr = expr.peel_parens();
         match expr.kind {
-            // hir::ExprKind::Array(_) => {
-            //     unimplemented!()
-            // }
-            // hir::ExprKind::Assign(_, _, _) => unimplemented!(),
             hir::ExprKind::Binary(l, bin_op, r) => {
-                let l = self.try_eval(l)?;
-                let r = self.try_eval(r)?;
-                l.binop(&r, bin_op.kind).map_err(Into::into)
+                let l = self.try_eval_value(l)?;
+                let r = self.try_eval_value(r)?;
+                binop(&l, &r, bin_op.kind).map_err(Into::into)
+            }
+            hir::ExprKind::Call(callee, hir::CallArgs::Unnamed(&[ref arg])) => {
+                let hir::ExprKind::Type(hir::Type { kind: hir::TypeKind::Elementary(ty), .. }) =
+                    callee.kind
+                else {
+                    return Err(EE::UnsupportedExpr.into());
+                };
+                let value = match arg.peel_parens().kind {
+                    hir::ExprKind::Member(base, name) => match self.enum_variant(base, name) {
+                        Some(index) => index,
+                        None => self.try_eval_rational(arg)?,
+                    },
+                    _ => self.try_eval_rational(arg)?,
+                };
+                let value = convert(&value, ty)?;
+                Ok(Value { value, ty: Some(ty) })
             }
-            // hir::ExprKind::Call(_, _) => unimplemented!(),
-            // hir::ExprKind::CallOptions(_, _) => unimplemented!(),
-            // hir::ExprKind::Delete(_) => unimplemented!(),
-            hir::ExprKind::Ident(&[hir::Res::Item(hir::ItemId::Variable(v))]) => {
-                let v = self.gcx.hir.variable(v);
+            hir::ExprKind::Ident(&[hir::Res::Item(hir::ItemId::Variable(id))]) => {
+                let v = self.gcx.hir.variable(id);
                 if v.mutability != Some(hir::VarMut::Constant) {
                     return Err(EE::NonConstantVar.into());
                 }
-                self.try_eval(v.initializer.expect("constant variable has no initializer"))
+                let init = v.initializer.expect("constant variable has no initializer");
+                let value = self.try_eval_rational(init)?;
+                let ty = match self.gcx.type_of_item(id.into()).kind {
+                    TyKind::Elementary(ty) if int_bounds(ty).is_some() => Some(ty),
+                    _ => None,
+                };
+                if let Some(ty) = ty {
+                    check_fits(&value, ty).map_err(|kind| kind.spanned(init.span))?;
+                }
+                Ok(Value { value, ty })
+            }
+            hir::ExprKind::Lit(lit) => self.eval_lit(lit).map(Value::literal),
+            hir::ExprKind::Member(base, name) => {
+                let hir::ExprKind::TypeCall(ref ty) = base.kind else {
+                    return Err(EE::UnsupportedExpr.into());
+                };
+                let (min, max, ty) = match self.gcx.type_of_hir_ty(ty).kind {
+                    TyKind::Elementary(ty) => {
+                        let (min, max) =
+                            int_bounds(ty).ok_or_else(|| EvalError::from(EE::UnsupportedExpr))?;
+                        (min, max, Some(ty))
+                    }
+                    TyKind::Enum(id) => {
+                        let len = self.gcx.hir.enumm(id).variants.len();
+                        (BigInt::zero(), BigInt::from(len.saturating_sub(1)), None)
+                    }
+                    _ => return Err(EE::UnsupportedExpr.into()),
+                };
+                let value = if name.name == Builtin::TypeMin.name() {
+                    min
+                } else if name.name == Builtin::TypeMax.name() {
+                    max
+                } else {
+                    return Err(EE::UnsupportedExpr.into());
+                };
+                Ok(Value { value: BigRational::from_integer(value), ty })
             }
-            // hir::ExprKind::Index(_, _) => unimplemented!(),
-            // hir::ExprKind::Slice(_, _, _) => unimplemented!(),
-            hir::ExprKind::Lit(lit) => self.eval_lit(lit),
-            // hir::ExprKind::Member(_, _) => unimplemented!(),
-            // hir::ExprKind::New(_) => unimplemented!(),
-            // hir::ExprKind::Payable(_) => unimplemented!(),
             hir::ExprKind::Ternary(cond, t, f) => {
-                let cond = self.try_eval(cond)?;
-                Ok(if cond.to_bool() { self.try_eval(t)? } else { self.try_eval(f)? })
+                let cond = self.try_eval_rational(cond)?;
+                if !cond.is_zero() {
+                    self.try_eval_value(t)
+                } else {
+                    self.try_eval_value(f)
+                }
             }
-            // hir::ExprKind::Tuple(_) => unimplemented!(),
-            // hir::ExprKind::TypeCall(_) => unimplemented!(),
-            // hir::ExprKind::Type(_) => unimplemented!(),
             hir::ExprKind::Unary(un_op, v) => {
-                let v = self.try_eval(v)?;
-                v.unop(un_op.kind).map_err(Into::into)
+                let v = self.try_eval_value(v)?;
+                unop(&v, un_op.kind).map_err(Into::into)
             }
             hir::ExprKind::Err(guar) => Err(EE::AlreadyEmitted(guar).into()),
             _ => Err(EE::UnsupportedExpr.into()),
         }
     }
 
-    fn eval_lit(&mut self, lit: &hir::Lit) -> EvalResult<'gcx> {
-        match lit.kind {
-            // LitKind::Str(str_kind, arc) => todo!(),
-            LitKind::Number(ref big_int) => {
-                let (_, bytes) = big_int.to_bytes_be();
-                if bytes.len() > 32 {
-                    return Err(EE::IntTooBig.into());
-                }
-                Ok(IntScalar::from_be_bytes(&bytes))
+    fn eval_lit(&mut self, lit: &hir::Lit) -> Result<BigRational, EvalError> {
+        let value = match lit.kind {
+            LitKind::Number(ref int) => BigRational::from_integer(int.clone()),
+            LitKind::Rational(ref rational) => rational.clone(),
+            LitKind::Address(address) => {
+                BigRational::from_integer(BigInt::from_bytes_be(Sign::Plus, address.as_slice()))
+            }
+            LitKind::Bool(bool) => from_bool(bool),
+            LitKind::Err(guar) => return Err(EE::AlreadyEmitted(guar).into()),
+            LitKind::Str(..) => return Err(EE::UnsupportedLiteral.into()),
+        };
+        check_precision(value).map_err(Into::into)
+    }
+
+    /// Returns the index of the enum variant `base.name`, if `base` refers to an enum.
+    fn enum_variant(&self, base: &hir::Expr<'_>, name: hir::Ident) -> Option<BigRational> {
+        let hir::ExprKind::Ident(&[hir::Res::Item(hir::ItemId::Enum(id))]) = base.kind else {
+            return None;
+        };
+        let index = self.gcx.hir.enumm(id).variants.iter().position(|v| v.name == name.name)?;
+        Some(BigRational::from_integer(index.into()))
+    }
+}
+
+/// An intermediate value of the evaluation.
+struct Value {
+    /// The exact value.
+    value: BigRational,
+    /// The integer type of the value, or `None` if it is a literal.
+    ///
+    /// Operations on typed values have the semantics of fixed-size integers.
+    ty: Option<ElementaryType>,
+}
+
+impl Value {
+    fn literal(value: BigRational) -> Self {
+        Self { value, ty: None }
+    }
+
+    /// Creates a value of the given type. If `wrap` is `true`, values that do not fit in the type
+    /// are truncated like two's complement integers, otherwise they are an error.
+    fn typed(value: BigRational, ty: Option<ElementaryType>, wrap: bool) -> Result<Self, EE> {
+        let value = check_precision(value)?;
+        match ty {
+            Some(ty) if wrap => Ok(Self { value: convert(&value, ty)?, ty: Some(ty) }),
+            Some(ty) => check_fits(&value, ty).map(|()| Self { value, ty: Some(ty) }),
+            None => Ok(Self::literal(value)),
+        }
+    }
+}
+
+fn from_bool(value: bool) -> BigRational {
+    BigRational::from_integer(BigInt::from(value as u8))
+}
+
+/// Returns the integer value of `value`, or an error if it is not an integer.
+fn to_integer(value: &BigRational) -> Result<BigInt, EE> {
+    if !value.is_integer() {
+        return Err(EE::NotAnInteger);
+    }
+    Ok(value.to_integer())
+}
+
+/// Returns the value if it is within the precision limit.
+fn check_precision(value: BigRational) -> Result<BigRational, EE> {
+    if value.numer().bits() > PRECISION_LIMIT || value.denom().bits() > PRECISION_LIMIT {
+        return Err(EE::PrecisionLimitReached);
+    }
+    Ok(value)
+}
+
+/// Returns the minimum and maximum values of the given integer type.
+fn int_bounds(ty: ElementaryType) -> Option<(BigInt, BigInt)> {
+    let (signed, bits) = match ty {
+        ElementaryType::Int(size) => (true, size.bits()),
+        ElementaryType::UInt(size) => (false, size.bits()),
+        _ => return None,
+    };
+    let bits = bits as usize;
+    Some(if signed {
+        let half = BigInt::one() << (bits - 1);
+        (-half.clone(), half - 1)
+    } else {
+        (BigInt::zero(), (BigInt::one() << bits) - 1)
+    })
+}
+
+/// Checks that the value can be stored in a value of type `ty`.
+///
+/// Only integer types are checked.
+fn narrow(value: &BigRational, ty: Ty<'_>) -> Result<(), EE> {
+    match ty.kind {
+        TyKind::Elementary(ty) => check_fits(value, ty),
+        _ => Ok(()),
+    }
+}
+
+/// Checks that the value fits in the given integer type.
+fn check_fits(value: &BigRational, ty: ElementaryType) -> Result<(), EE> {
+    let Some((min, max)) = int_bounds(ty) else { return Ok(()) };
+    let value = to_integer(value)?;
+    if value < min {
+        return Err(EE::IntTooSmall);
+    }
+    if value > max {
+        return Err(EE::IntTooBig);
+    }
+    Ok(())
+}
+
+/// Converts the value to the given integer type, truncating it if it does not fit.
+fn convert(value: &BigRational, ty: ElementaryType) -> Result<BigRational, EE> {
+    let Some((min, max)) = int_bounds(ty) else { return Err(EE::UnsupportedExpr) };
+    let value = to_integer(value)?;
+    let modulus = &max - &min + 1;
+    let value = (value - &min).mod_floor(&modulus) + min;
+    Ok(BigRational::from_integer(value))
+}
+
+/// Applies the given unary operation to a value.
+fn unop(v: &Value, op: hir::UnOpKind) -> Result<Value, EE> {
+    match op {
+        hir::UnOpKind::PreInc
+        | hir::UnOpKind::PreDec
+        | hir::UnOpKind::PostInc
+        | hir::UnOpKind::PostDec => Err(EE::UnsupportedUnaryOp),
+        hir::UnOpKind::Not => Ok(Value::literal(from_bool(v.value.is_zero()))),
+        hir::UnOpKind::BitNot => {
+            Value::typed(BigRational::from_integer(!to_integer(&v.value)?), v.ty, true)
+        }
+        hir::UnOpKind::Neg => Value::typed(-&v.value, v.ty, false),
+    }
+}
+
+/// Applies the given binary operation to two values.
+fn binop(l: &Value, r: &Value, op: hir::BinOpKind) -> Result<Value, EE> {
+    let int = BigRational::from_integer;
+    // The type of shifts and exponentiations is the type of the left operand.
+    let (ty, l_ty) = (l.ty.or(r.ty), l.ty);
+    let (l, r) = (&l.value, &r.value);
+    let boolean = |value: bool| Ok(Value::literal(from_bool(value)));
+    match op {
+        hir::BinOpKind::Lt => boolean(l < r),
+        hir::BinOpKind::Le => boolean(l <= r),
+        hir::BinOpKind::Gt => boolean(l > r),
+        hir::BinOpKind::Ge => boolean(l >= r),
+        hir::BinOpKind::Eq => boolean(l == r),
+        hir::BinOpKind::Ne => boolean(l != r),
+        hir::BinOpKind::Or => boolean(!l.is_zero() || !r.is_zero()),
+        hir::BinOpKind::And => boolean(!l.is_zero() && !r.is_zero()),
+        hir::BinOpKind::BitOr => Value::typed(int(to_integer(l)? | to_integer(r)?), ty, true),
+        hir::BinOpKind::BitAnd => Value::typed(int(to_integer(l)? & to_integer(r)?), ty, true),
+        hir::BinOpKind::BitXor => Value::typed(int(to_integer(l)? ^ to_integer(r)?), ty, true),
+        hir::BinOpKind::Shl => {
+            let (l, shift) = (to_integer(l)?, shift_amount(r)?);
+            let value = if l.is_zero() {
+                l
+            } else if l_ty.is_some() {
+                // The result is truncated to the type, so the higher bits can be discarded.
+                l << shift.min(256)
+            } else if l.bits().saturating_add(shift) > PRECISION_LIMIT {
+                return Err(EE::PrecisionLimitReached);
+            } else {
+                l << shift
+            };
+            Value::typed(int(value), l_ty, true)
+        }
+        hir::BinOpKind::Shr | hir::BinOpKind::Sar => {
+            let (l, shift) = (to_integer(l)?, shift_amount(r)?);
+            // Rounds towards negative infinity, like an arithmetic shift.
+            let shift = shift.min(l.bits() + 1);
+            Value::typed(int(l.div_floor(&(BigInt::one() << shift))), l_ty, true)
+        }
+        hir::BinOpKind::Add => Value::typed(l + r, ty, false),
+        hir::BinOpKind::Sub => Value::typed(l - r, ty, false),
+        hir::BinOpKind::Mul => Value::typed(l * r, ty, false),
+        hir::BinOpKind::Div => {
+            if r.is_zero() {
+                return Err(EE::DivisionByZero);
             }
-            // LitKind::Rational(ratio) => todo!(),
-            LitKind::Address(address) => Ok(IntScalar::from_be_bytes(address.as_slice())),
-            LitKind::Bool(bool) => Ok(IntScalar::from_be_bytes(&[bool as u8])),
-            LitKind::Err(guar) => Err(EE::AlreadyEmitted(guar).into()),
-            _ => Err(EE::UnsupportedLiteral.into()),
+            // Integer division truncates towards zero.
+            let value = if ty.is_some() { (l / r).trunc() } else { l / r };
+            Value::typed(value, ty, false)
+        }
+        hir::BinOpKind::Rem => {
+            if r.is_zero() {
+                return Err(EE::DivisionByZero);
+            }
+            // The result has the sign of the dividend.
+            Value::typed(l - (l / r).trunc() * r, ty, false)
+        }
+        hir::BinOpKind::Pow => {
+            let exp = to_integer(r)?;
+            let value = if l.is_zero() {
+                if exp.is_negative() {
+                    return Err(EE::DivisionByZero);
+                }
+                from_bool(exp.is_zero())
+            } else if l.abs().is_one() {
+                if exp.is_even() {
+                    int(BigInt::one())
+                } else {
+                    l.clone()
+                }
+            } else {
+                let bits = l.numer().bits().max(l.denom().bits());
+                let exp = exp
+                    .to_i32()
+                    .filter(|exp| bits.saturating_mul(exp.unsigned_abs() as u64) <= PRECISION_LIMIT)
+                    .ok_or(EE::PrecisionLimitReached)?;
+                l.pow(exp)
+            };
+            Value::typed(value, l_ty, false)
         }
     }
 }
 
+/// Returns the amount of a shift operation.
+fn shift_amount(value: &BigRational) -> Result<u64, EE> {
+    let value = to_integer(value)?;
+    if value.is_negative() {
+        return Err(EE::NegativeShift);
+    }
+    Ok(value.to_u64().unwrap_or(u64::MAX))
+}
+
 pub struct IntScalar {
     pub data: U256,
 }
@@ -145,69 +444,41 @@ impl IntScalar {
         Self { data: U256::from_be_slice(bytes) }
     }
 
+    /// Creates a new integer value from a rational number.
+    ///
+    /// The value must be an integer in the range of either `int256` or `uint256`. Negative values
+    /// are stored in two's complement.
+    pub fn from_rational(value: &BigRational) -> Result<Self, EvalErrorKind> {
+        let value = to_integer(value)?;
+        let (sign, bytes) = value.to_bytes_be();
+        if bytes.len() > 32 {
+            return Err(if sign == Sign::Minus { EE::IntTooSmall } else { EE::IntTooBig });
+        }
+        let data = U256::from_be_slice(&bytes);
+        if sign != Sign::Minus {
+            return Ok(Self::new(data));
+        }
+        if data > U256::ONE << 255 {
+            return Err(EE::IntTooSmall);
+        }
+        Ok(Self::new(data.wrapping_neg()))
+    }
+
     /// Converts the integer value to a boolean.
     pub fn to_bool(&self) -> bool {
         !self.data.is_zero()
     }
-
-    /// Applies the given unary operation to this value.
-    pub fn unop(&self, op: hir::UnOpKind) -> Result<Self, EE> {
-        Ok(match op {
-            hir::UnOpKind::PreInc
-            | hir::UnOpKind::PreDec
-            | hir::UnOpKind::PostInc
-            | hir::UnOpKind::PostDec => return Err(EE::UnsupportedUnaryOp),
-            hir::UnOpKind::Not | hir::UnOpKind::BitNot => Self::new(!self.data),
-            hir::UnOpKind::Neg => Self::new(self.data.wrapping_neg()),
-        })
-    }
-
-    /// Applies the given binary operation to this value.
-    pub fn binop(&self, r: &Self, op: hir::BinOpKind) -> Result<Self, EE> {
-        let l = self;
-        Ok(match op {
-            hir::BinOpKind::Lt => Self::from_bool(l.data < r.data),
-            hir::BinOpKind::Le => Self::from_bool(l.data <= r.data),
-            hir::BinOpKind::Gt => Self::from_bool(l.data > r.data),
-            hir::BinOpKind::Ge => Self::from_bool(l.data >= r.data),
-            hir::BinOpKind::Eq => Self::from_bool(l.data == r.data),
-            hir::BinOpKind::Ne => Self::from_bool(l.data != r.data),
-            hir::BinOpKind::Or | hir::BinOpKind::BitOr => Self::new(l.data | r.data),
-            hir::BinOpKind::And | hir::BinOpKind::BitAnd => Self::new(l.data & r.data),
-            hir::BinOpKind::BitXor => Self::new(l.data ^ r.data),
-            hir::BinOpKind::Shr => {
-                Self::new(l.data.wrapping_shr(r.data.try_into().unwrap_or(usize::MAX)))
-            }
-            hir::BinOpKind::Shl => {
-                Self::new(l.data.wrapping_shl(r.data.try_into().unwrap_or(usize::MAX)))
-            }
-            hir::BinOpKind::Sar => {
-                Self::new(l.data.arithmetic_shr(r.data.try_into().unwrap_or(usize::MAX)))
-            }
-            hir::BinOpKind::Add => {
-                Self::new(l.data.checked_add(r.data).ok_or(EE::ArithmeticOverflow)?)
-            }
-            hir::BinOpKind::Sub => {
-                Self::new(l.data.checked_sub(r.data).ok_or(EE::ArithmeticOverflow)?)
-            }
-            hir::BinOpKind::Pow => {
-                Self::new(l.data.checked_pow(r.data).ok_or(EE::ArithmeticOverflow)?)
-            }
-            hir::BinOpKind::Mul => {
-                Self::new(l.data.checked_mul(r.data).ok_or(EE::ArithmeticOverflow)?)
-            }
-            hir::BinOpKind::Div => Self::new(l.data.checked_div(r.data).ok_or(EE::DivisionByZero)?),
-            hir::BinOpKind::Rem => Self::new(l.data.checked_rem(r.data).ok_or(EE::DivisionByZero)?),
-        })
-    }
 }
 
 #[derive(Debug)]
 pub enum EvalErrorKind {
     RecursionLimitReached,
-    ArithmeticOverflow,
+    PrecisionLimitReached,
     IntTooBig,
+    IntTooSmall,
+    NotAnInteger,
     DivisionByZero,
+    NegativeShift,
     UnsupportedLiteral,
     UnsupportedUnaryOp,
     UnsupportedExpr,
@@ -221,12 +492,27 @@ impl EvalErrorKind {
         EvalError { kind: self, span }
     }
 
+    /// Returns `true` if the evaluation failed because the expression is not supported by the
+    /// evaluator, rather than because of its value.
+    pub fn is_unsupported(&self) -> bool {
+        matches!(
+            self,
+            Self::UnsupportedLiteral
+                | Self::UnsupportedUnaryOp
+                | Self::UnsupportedExpr
+                | Self::NonConstantVar
+        )
+    }
+
     fn msg(&self) -> &'static str {
         match self {
             Self::RecursionLimitReached => "recursion limit reached",
-            Self::ArithmeticOverflow => "arithmetic overflow",
+            Self::PrecisionLimitReached => "precision limit of rational constants reached",
             Self::IntTooBig => "integer value is too big",
+            Self::IntTooSmall => "integer value is too small",
+            Self::NotAnInteger => "value is not an integer",
             Self::DivisionByZero => "division by zero",
+            Self::NegativeShift => "shift amount is negative",
             Self::UnsupportedLiteral => "unsupported literal",
             Self::UnsupportedUnaryOp => "unsupported unary operation",
             Self::UnsupportedExpr => "unsupported expression",
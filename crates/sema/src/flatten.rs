@@ -0,0 +1,244 @@
+//! Flattening of a source and its imports into a single source.
+
+use crate::{
+    hir,
+    license::{combine_licenses, find_licenses, SPDX_MARKER},
+    rename::{rename, RenameError},
+    ty::Gcx,
+};
+use solar_ast::{self as ast, ImportItems, ItemKind, PragmaTokens};
+use solar_data_structures::map::{FxHashMap, FxHashSet};
+use solar_interface::{source_map::SourceFile, sym, Span};
+use solar_parse::Parser;
+use std::{fmt, ops::Range};
+
+/// An error returned by [`flatten`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum FlattenError {
+    /// A source is imported with a unit alias, like `import "a.sol" as A;`, whose members cannot
+    /// be referred to once flattened. Contains the span of the import directive.
+    UnitAlias(Span),
+    /// A conflicting declaration could not be renamed. Contains its span.
+    Rename(Span, RenameError),
+    /// A source could not be parsed again.
+    Parse,
+}
+
+impl fmt::Display for FlattenError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::UnitAlias(_) => {
+                f.write_str("sources imported with a unit alias cannot be flattened")
+            }
+            Self::Rename(_, e) => write!(f, "failed to rename conflicting declaration: {e}"),
+            Self::Parse => f.write_str("failed to parse source"),
+        }
+    }
+}
+
+impl std::error::Error for FlattenError {}
+
+/// Returns the given source and the sources it imports, transitively, as a single source that can
+/// be compiled on its own.
+///
+/// Sources are written after the sources they import, each only once, without their import
+/// directives. Top-level declarations with the same name in different sources, other than
+/// functions and events, which can be overloaded, are renamed with a numeric suffix, along with the
+/// names referring to them. Names referring to declarations through import aliases are replaced
+/// with the name of the declaration.
+///
+/// The license identifiers of all the sources are combined into one that requires all of them at
+/// the top, followed by their combined `pragma solidity` requirements, and their other pragmas,
+/// once each.
+///
+/// Must be called within a session, as sources are parsed again to find their directives.
+pub fn flatten(gcx: Gcx<'_>, source: hir::SourceId) -> Result<String, FlattenError> {
+    let mut order = Vec::new();
+    import_order(gcx, source, &mut FxHashSet::default(), &mut order);
+
+    let edits = declaration_edits(gcx, &order)?;
+
+    let mut versions = Vec::<String>::new();
+    let mut pragmas = Vec::<String>::new();
+    let mut licenses = Vec::new();
+    let mut bodies = Vec::new();
+    for &id in &order {
+        let file = &gcx.hir.source(id).file;
+        let mut file_edits = Vec::new();
+
+        let arena = ast::Arena::new();
+        let mut parser = Parser::from_source_file(gcx.sess, &arena, file);
+        let ast = parser.parse_file().map_err(|e| {
+            e.emit();
+            FlattenError::Parse
+        })?;
+        for item in ast.items.iter() {
+            match &item.kind {
+                ItemKind::Import(import) => {
+                    if let ImportItems::Plain(Some(_)) | ImportItems::Glob(Some(_)) = import.items {
+                        return Err(FlattenError::UnitAlias(item.span));
+                    }
+                }
+                ItemKind::Pragma(pragma) => {
+                    let snippet = snippet(file, item.span);
+                    let is_version = matches!(
+                        &pragma.tokens,
+                        PragmaTokens::Version(name, _) if name.name == sym::solidity
+                    );
+                    let (list, text) = if is_version {
+                        let req = snippet.trim_end_matches(';').trim_start_matches("pragma");
+                        (
+                            &mut versions,
+                            req.trim_start().trim_start_matches("solidity").trim().into(),
+                        )
+                    } else {
+                        (&mut pragmas, snippet.to_string())
+                    };
+                    if !list.contains(&text) {
+                        list.push(text);
+                    }
+                }
+                _ => continue,
+            }
+            file_edits.push((range(file, item.span), String::new()));
+        }
+
+        // Only one license identifier is allowed per source, so they are combined at the top.
+        let comments = find_licenses(&file.src);
+        licenses.extend(comments.first().map(|comment| comment.expr));
+        for comment in comments {
+            let mut range = comment.range;
+            if comment.is_line && file.src[range.end..].starts_with('\n') {
+                range.end += 1;
+            }
+            file_edits.push((range, String::new()));
+        }
+
+        file_edits.extend(
+            edits
+                .iter()
+                .filter(|(span, _)| file.contains(span.lo()))
+                .map(|(span, text)| (range(file, *span), text.clone())),
+        );
+        bodies.push((file.name.display().to_string(), apply_edits(&file.src, file_edits)));
+    }
+
+    let mut out = String::new();
+    if let Some(license) = combine_licenses(licenses) {
+        out.push_str(&format!("// {SPDX_MARKER} {license}\n"));
+    }
+    if !versions.is_empty() {
+        out.push_str(&format!("pragma solidity {};\n", versions.join(" ")));
+    }
+    for pragma in &pragmas {
+        out.push_str(pragma);
+        out.push('\n');
+    }
+    for (name, body) in bodies {
+        if !out.is_empty() {
+            out.push('\n');
+        }
+        out.push_str(&format!("// {name}\n"));
+        let body = body.trim();
+        if !body.is_empty() {
+            out.push_str(body);
+            out.push('\n');
+        }
+    }
+    Ok(out)
+}
+
+/// Pushes the sources imported by `id`, transitively, and then `id` to `order`.
+fn import_order(
+    gcx: Gcx<'_>,
+    id: hir::SourceId,
+    seen: &mut FxHashSet<hir::SourceId>,
+    order: &mut Vec<hir::SourceId>,
+) {
+    if !seen.insert(id) {
+        return;
+    }
+    for &(_, import) in gcx.hir.source(id).imports {
+        import_order(gcx, import, seen, order);
+    }
+    order.push(id);
+}
+
+/// Returns the edits that rename conflicting top-level declarations, and replace the names
+/// referring to declarations through import aliases.
+fn declaration_edits(
+    gcx: Gcx<'_>,
+    order: &[hir::SourceId],
+) -> Result<Vec<(Span, String)>, FlattenError> {
+    let items = order.iter().flat_map(|&id| gcx.hir.source(id).items.iter().copied());
+    let mut names = FxHashSet::default();
+    let mut new_names = FxHashMap::default();
+    let mut edits = Vec::new();
+    for id in items.clone() {
+        let item = gcx.hir.item(id);
+        let Some(name) = item.name() else { continue };
+        let name = name.as_str().to_string();
+        if names.insert(name.clone())
+            || matches!(item, hir::Item::Function(_) | hir::Item::Event(_))
+        {
+            continue;
+        }
+        let new_name = (1..).map(|i| format!("{name}_{i}")).find(|n| !names.contains(n)).unwrap();
+        let renames =
+            rename(gcx, id, &new_name).map_err(|e| FlattenError::Rename(item.span(), e))?;
+        edits.extend(renames.into_iter().map(|edit| (edit.span, edit.new_text)));
+        names.insert(new_name.clone());
+        new_names.insert(id, new_name);
+    }
+
+    let source_map = gcx.sess.source_map();
+    for id in items {
+        let Some(name) = gcx.hir.item(id).name() else { continue };
+        let new_name = new_names.get(&id).map_or(name.as_str(), String::as_str);
+        for span in gcx.references(hir::Res::Item(id)) {
+            if source_map.span_to_snippet(span).is_ok_and(|snippet| snippet != name.as_str()) {
+                edits.push((span, new_name.to_string()));
+            }
+        }
+    }
+    Ok(edits)
+}
+
+fn range(file: &SourceFile, span: Span) -> Range<usize> {
+    let start = (span.lo().0 - file.start_pos.0) as usize;
+    start..start + (span.hi().0 - span.lo().0) as usize
+}
+
+fn snippet(file: &SourceFile, span: Span) -> &str {
+    &file.src[range(file, span)]
+}
+
+/// Applies the edits to `src`. Edits within the range of a previous edit are ignored.
+fn apply_edits(src: &str, mut edits: Vec<(Range<usize>, String)>) -> String {
+    edits.sort_by_key(|(range, _)| (range.start, std::cmp::Reverse(range.end)));
+    let mut out = String::with_capacity(src.len());
+    let mut pos = 0;
+    for (range, text) in edits {
+        if range.start < pos {
+            continue;
+        }
+        out.push_str(&src[pos..range.start]);
+        out.push_str(&text);
+        pos = range.end;
+    }
+    out.push_str(&src[pos..]);
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn edits() {
+        let edits = vec![(4..5, "Y".to_string()), (0..3, String::new()), (0..6, "ab".to_string())];
+        assert_eq!(apply_edits("abc X; def", edits), "ab def");
+        let edits = vec![(6..7, "Y".to_string()), (0..4, String::new())];
+        assert_eq!(apply_edits("abc;\n X;", edits), "\n Y;");
+    }
+}
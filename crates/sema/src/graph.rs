@@ -0,0 +1,251 @@
+//! Source unit dependency graph.
+
+use crate::{hir, ParsedSources};
+use serde::Serialize;
+use solar_data_structures::index::Idx;
+use solar_interface::source_map::SourceFile;
+use std::{collections::BTreeMap, fmt::Write};
+
+/// The graph of the imports between source units.
+///
+/// Nodes are the sources, sorted by name, and there is an edge from each source to each of the
+/// sources it imports. Returned by [`ParsedSources::dependency_graph`] and
+/// [`hir::Hir::dependency_graph`].
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct DependencyGraph {
+    nodes: Vec<String>,
+    edges: Vec<Vec<usize>>,
+}
+
+impl DependencyGraph {
+    /// Creates the graph of the given sources and the indices of the sources they import.
+    fn new<'a>(sources: impl IntoIterator<Item = (&'a SourceFile, Vec<usize>)>) -> Self {
+        let (names, imports): (Vec<_>, Vec<_>) = sources
+            .into_iter()
+            .map(|(file, imports)| (file.name.display().to_string(), imports))
+            .unzip();
+        let mut order = (0..names.len()).collect::<Vec<_>>();
+        order.sort_by(|&a, &b| names[a].cmp(&names[b]));
+        let mut new_index = vec![0; names.len()];
+        for (new, &old) in order.iter().enumerate() {
+            new_index[old] = new;
+        }
+        let nodes = order.iter().map(|&old| names[old].clone()).collect();
+        let edges = order
+            .iter()
+            .map(|&old| {
+                let mut edges = imports[old].iter().map(|&i| new_index[i]).collect::<Vec<_>>();
+                edges.sort_unstable();
+                edges.dedup();
+                edges
+            })
+            .collect();
+        Self { nodes, edges }
+    }
+
+    /// Returns the names of the sources, sorted.
+    pub fn nodes(&self) -> &[String] {
+        &self.nodes
+    }
+
+    /// Returns the indices of the sources imported by the source at index `node`, sorted.
+    pub fn imports(&self, node: usize) -> &[usize] {
+        &self.edges[node]
+    }
+
+    /// Returns an iterator over all the edges, as `(importer, imported)` indices.
+    pub fn edges(&self) -> impl Iterator<Item = (usize, usize)> + '_ {
+        self.edges.iter().enumerate().flat_map(|(from, to)| to.iter().map(move |&to| (from, to)))
+    }
+
+    /// Returns the import cycles of the graph, as the sorted indices of the sources of each cycle.
+    ///
+    /// Each cycle is a strongly connected component with more than one source, or a source that
+    /// imports itself. Cyclic imports are allowed in Solidity.
+    pub fn cycles(&self) -> Vec<Vec<usize>> {
+        let mut tarjan = Tarjan {
+            graph: self,
+            index: 0,
+            indices: vec![None; self.nodes.len()],
+            low_links: vec![0; self.nodes.len()],
+            stack: Vec::new(),
+            on_stack: vec![false; self.nodes.len()],
+            components: Vec::new(),
+        };
+        for node in 0..self.nodes.len() {
+            if tarjan.indices[node].is_none() {
+                tarjan.visit(node);
+            }
+        }
+        let mut cycles = tarjan
+            .components
+            .into_iter()
+            .filter(|c| c.len() > 1 || self.edges[c[0]].contains(&c[0]))
+            .map(|mut c| {
+                c.sort_unstable();
+                c
+            })
+            .collect::<Vec<_>>();
+        cycles.sort_unstable();
+        cycles
+    }
+
+    /// Returns the graph as JSON: the imports of each source, and the import cycles.
+    pub fn to_json(&self) -> serde_json::Value {
+        #[derive(Serialize)]
+        struct Json<'a> {
+            sources: BTreeMap<&'a str, Vec<&'a str>>,
+            cycles: Vec<Vec<&'a str>>,
+        }
+
+        let name = |i: usize| self.nodes[i].as_str();
+        let sources = (0..self.nodes.len())
+            .map(|i| (name(i), self.edges[i].iter().map(|&i| name(i)).collect()))
+            .collect();
+        let cycles = self.cycles().into_iter().map(|c| c.into_iter().map(name).collect()).collect();
+        serde_json::to_value(Json { sources, cycles }).unwrap()
+    }
+
+    /// Returns the graph in the [DOT](https://graphviz.org/doc/info/lang.html) language.
+    pub fn to_dot(&self) -> String {
+        let quote = |i: usize| format!("{:?}", self.nodes[i]);
+        let mut dot = String::from("digraph imports {\n");
+        for node in 0..self.nodes.len() {
+            if self.edges[node].is_empty() {
+                writeln!(dot, "    {};", quote(node)).unwrap();
+            }
+            for &import in &self.edges[node] {
+                writeln!(dot, "    {} -> {};", quote(node), quote(import)).unwrap();
+            }
+        }
+        dot.push_str("}\n");
+        dot
+    }
+}
+
+/// Tarjan's strongly connected components algorithm.
+struct Tarjan<'a> {
+    graph: &'a DependencyGraph,
+    index: usize,
+    indices: Vec<Option<usize>>,
+    low_links: Vec<usize>,
+    stack: Vec<usize>,
+    on_stack: Vec<bool>,
+    components: Vec<Vec<usize>>,
+}
+
+impl Tarjan<'_> {
+    fn visit(&mut self, node: usize) {
+        self.indices[node] = Some(self.index);
+        self.low_links[node] = self.index;
+        self.index += 1;
+        self.stack.push(node);
+        self.on_stack[node] = true;
+
+        for &next in &self.graph.edges[node] {
+            match self.indices[next] {
+                None => {
+                    self.visit(next);
+                    self.low_links[node] = self.low_links[node].min(self.low_links[next]);
+                }
+                Some(index) if self.on_stack[next] => {
+                    self.low_links[node] = self.low_links[node].min(index);
+                }
+                Some(_) => {}
+            }
+        }
+
+        if Some(self.low_links[node]) == self.indices[node] {
+            let mut component = Vec::new();
+            loop {
+                let n = self.stack.pop().unwrap();
+                self.on_stack[n] = false;
+                component.push(n);
+                if n == node {
+                    break;
+                }
+            }
+            self.components.push(component);
+        }
+    }
+}
+
+impl ParsedSources<'_> {
+    /// Returns the dependency graph of the sources.
+    pub fn dependency_graph(&self) -> DependencyGraph {
+        DependencyGraph::new(self.sources.iter().map(|source| {
+            (&*source.file, source.imports.iter().map(|&(_, id)| id.index()).collect())
+        }))
+    }
+}
+
+impl hir::Hir<'_> {
+    /// Returns the dependency graph of the sources.
+    pub fn dependency_graph(&self) -> DependencyGraph {
+        DependencyGraph::new(self.sources().map(|source| {
+            (&*source.file, source.imports.iter().map(|&(_, id)| id.index()).collect())
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use solar_interface::source_map::SourceMap;
+    use std::path::PathBuf;
+
+    fn graph(sources: &[(&str, &[usize])]) -> DependencyGraph {
+        let sm = SourceMap::empty();
+        let files = sources
+            .iter()
+            .map(|&(name, _)| sm.new_dummy_source_file(PathBuf::from(name), String::new()).unwrap())
+            .collect::<Vec<_>>();
+        DependencyGraph::new(
+            files.iter().zip(sources).map(|(file, &(_, imports))| (&**file, imports.to_vec())),
+        )
+    }
+
+    #[test]
+    fn dependency_graph() {
+        let g = graph(&[
+            ("d.sol", &[1, 1]),
+            ("b.sol", &[2]),
+            ("c.sol", &[1]),
+            ("a.sol", &[0, 4]),
+            ("e.sol", &[4]),
+            ("f.sol", &[]),
+        ]);
+        assert_eq!(g.nodes(), ["a.sol", "b.sol", "c.sol", "d.sol", "e.sol", "f.sol"]);
+        assert_eq!(g.imports(3), [1]);
+        assert_eq!(g.edges().collect::<Vec<_>>(), [(0, 3), (0, 4), (1, 2), (2, 1), (3, 1), (4, 4)]);
+        assert_eq!(g.cycles(), [vec![1, 2], vec![4]]);
+
+        assert_eq!(
+            g.to_json(),
+            serde_json::json!({
+                "sources": {
+                    "a.sol": ["d.sol", "e.sol"],
+                    "b.sol": ["c.sol"],
+                    "c.sol": ["b.sol"],
+                    "d.sol": ["b.sol"],
+                    "e.sol": ["e.sol"],
+                    "f.sol": [],
+                },
+                "cycles": [["b.sol", "c.sol"], ["e.sol"]],
+            })
+        );
+        assert_eq!(
+            g.to_dot(),
+            r#"digraph imports {
+    "a.sol" -> "d.sol";
+    "a.sol" -> "e.sol";
+    "b.sol" -> "c.sol";
+    "c.sol" -> "b.sol";
+    "d.sol" -> "b.sol";
+    "e.sol" -> "e.sol";
+    "f.sol";
+}
+"#
+        );
+    }
+}
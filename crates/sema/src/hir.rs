@@ -1,4 +1,18 @@
 //! High-level intermediate representation (HIR).
+//!
+//! The HIR is produced by lowering the parsed AST of all sources (see `ast_lowering`), and is what
+//! type checking, the lints and code generation operate on instead of the surface syntax. Compared
+//! to the AST:
+//! - all items are stored in flat arrays in [`Hir`], and refer to each other through IDs like
+//!   [`ContractId`] and [`FunctionId`];
+//! - paths in expressions and types are resolved to a [`Res`];
+//! - public state variables have an explicit getter [`Function`] (see [`Variable::getter`]);
+//! - modifier invocations and base constructor calls are resolved to the [`ItemId`]s in
+//!   [`Function::modifiers`]. Their bodies are not inlined into the function here.
+//!
+//! The HIR stays in this crate rather than in a separate one because it refers to the
+//! [`Builtin`]s defined here. It is available through
+//! [`GlobalCtxt::hir`](crate::ty::GlobalCtxt::hir).
 
 use crate::builtins::Builtin;
 use derive_more::derive::From;
@@ -224,6 +238,26 @@ impl<'hir> Hir<'hir> {
     pub fn contract_items(&self, id: ContractId) -> impl Iterator<Item = Item<'_, 'hir>> + Clone {
         self.contract_item_ids(id).map(move |id| self.item(id))
     }
+
+    /// Returns the contracts that the given contract inherits from, in linearization order,
+    /// excluding the contract itself.
+    pub fn inherited_contracts(&self, id: ContractId) -> &'hir [ContractId] {
+        self.contract(id).linearized_bases.get(1..).unwrap_or_default()
+    }
+
+    /// Returns an iterator over the contracts that inherit from the given contract, including
+    /// itself.
+    pub fn derived_contracts(
+        &self,
+        id: ContractId,
+    ) -> impl Iterator<Item = ContractId> + Clone + use<'_, 'hir> {
+        self.contract_ids().filter(move |&c| self.contract(c).linearized_bases.contains(&id))
+    }
+
+    /// Returns `true` if `derived` is `base` or inherits from it.
+    pub fn is_derived_from(&self, derived: ContractId, base: ContractId) -> bool {
+        self.contract(derived).linearized_bases.contains(&base)
+    }
 }
 
 newtype_index! {
@@ -363,6 +397,19 @@ impl<'hir> Item<'_, 'hir> {
         })
     }
 
+    /// Returns the NatSpec documentation of the item.
+    #[inline]
+    pub fn docs(self) -> &'hir [ast::NatSpecItem] {
+        match self {
+            Item::Contract(c) => c.docs,
+            Item::Function(f) => f.docs,
+            Item::Error(e) => e.docs,
+            Item::Event(e) => e.docs,
+            Item::Variable(v) => v.docs,
+            Item::Struct(_) | Item::Enum(_) | Item::Udvt(_) => &[],
+        }
+    }
+
     /// Returns `true` if the item is visible in derived contracts.
     #[inline]
     pub fn is_visible_in_derived_contracts(self) -> bool {
@@ -487,11 +534,17 @@ pub struct Contract<'hir> {
     pub span: Span,
     /// The contract name.
     pub name: Ident,
+    /// The contract's NatSpec documentation.
+    pub docs: &'hir [ast::NatSpecItem],
     /// The contract kind.
     pub kind: ContractKind,
     /// The contract bases.
     pub bases: &'hir [ContractId],
-    /// The linearized contract bases.
+    /// The linearized contract bases, computed with the C3 linearization algorithm.
+    ///
+    /// The list starts with the contract itself and goes from the most derived to the most
+    /// base-like contract. This is the order in which names are looked up and functions are
+    /// overridden. See also [`Hir::inherited_contracts`] and [`Hir::derived_contracts`].
     pub linearized_bases: &'hir [ContractId],
     /// The constructor function.
     pub ctor: Option<FunctionId>,
@@ -548,6 +601,8 @@ pub struct Function<'hir> {
     /// The function name.
     /// Only `None` if this is a constructor, fallback, or receive function.
     pub name: Option<Ident>,
+    /// The function's NatSpec documentation.
+    pub docs: &'hir [ast::NatSpecItem],
     /// The function kind.
     pub kind: FunctionKind,
     /// The visibility of the function.
@@ -653,6 +708,8 @@ pub struct Event<'hir> {
     pub span: Span,
     /// The event name.
     pub name: Ident,
+    /// The event's NatSpec documentation.
+    pub docs: &'hir [ast::NatSpecItem],
     /// Whether this event is anonymous.
     pub anonymous: bool,
     pub parameters: &'hir [VariableId],
@@ -677,6 +734,8 @@ pub struct Error<'hir> {
     pub span: Span,
     /// The error name.
     pub name: Ident,
+    /// The error's NatSpec documentation.
+    pub docs: &'hir [ast::NatSpecItem],
     pub parameters: &'hir [VariableId],
 }
 
@@ -697,6 +756,8 @@ pub struct Variable<'hir> {
     pub ty: Type<'hir>,
     /// The variable's name.
     pub name: Option<Ident>,
+    /// The variable's NatSpec documentation.
+    pub docs: &'hir [ast::NatSpecItem],
     /// The visibility of the variable.
     pub visibility: Option<Visibility>,
     pub mutability: Option<VarMut>,
@@ -720,6 +781,7 @@ impl<'hir> Variable<'hir> {
             kind,
             ty,
             name,
+            docs: &[],
             visibility: None,
             mutability: None,
             data_location: None,
@@ -918,6 +980,10 @@ pub enum StmtKind<'hir> {
     Continue,
 
     /// A loop statement. This is desugared from all `for`, `while`, and `do while` statements.
+    ///
+    /// If the block contains more than one statement, the last one is the continuation of the
+    /// loop (the `for` loop increment or the `do while` condition), which `continue` statements
+    /// jump to. Otherwise, `continue` statements jump to the start of the loop.
     Loop(Block<'hir>, LoopSource),
 
     /// An `if` statement with an optional `else` block: `if (expr) { ... } else { ... }`.
@@ -1227,6 +1293,7 @@ impl<'hir> Type<'hir> {
                 ty.value.visit(hir, f)
             }
             TypeKind::Custom(_) => ControlFlow::Continue(()),
+            TypeKind::Infer => ControlFlow::Continue(()),
             TypeKind::Err(_) => ControlFlow::Continue(()),
         }
     }
@@ -1248,6 +1315,11 @@ pub enum TypeKind<'hir> {
     /// A custom type name.
     Custom(ItemId),
 
+    /// The type of a legacy `var` declaration, which is not inferred.
+    ///
+    /// Only lowered with legacy syntax, which disables code generation.
+    Infer,
+
     Err(ErrorGuaranteed),
 }
 
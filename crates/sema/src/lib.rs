@@ -11,7 +11,11 @@ extern crate tracing;
 
 use rayon::prelude::*;
 use solar_data_structures::{trustme, OnDrop};
-use solar_interface::{config::CompilerStage, Result, Session};
+use solar_interface::{
+    config::{CompilerOutput, CompilerStage, Unpretty},
+    Result, Session,
+};
+use std::{collections::BTreeMap, io::Write};
 use thread_local::ThreadLocal;
 use ty::Gcx;
 
@@ -28,8 +32,19 @@ mod parse;
 pub use parse::{ParsedSource, ParsedSources, ParsingContext};
 
 pub mod builtins;
+pub mod completion;
 pub mod eval;
+pub mod flatten;
+pub mod graph;
 pub mod hir;
+pub mod license;
+pub mod link;
+pub mod lints;
+pub mod metadata;
+pub mod outline;
+pub mod rename;
+pub mod selectors;
+pub mod semantic_tokens;
 pub mod ty;
 
 mod typeck;
@@ -38,21 +53,122 @@ mod emit;
 
 pub mod stats;
 
+#[cfg(test)]
+mod test_utils;
+
+/// The creation and runtime bytecode of a contract.
+///
+/// Return type of [`Codegen::bytecode`].
+#[derive(Clone, Debug, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct ContractBytecode {
+    /// The creation bytecode, which deploys the runtime bytecode.
+    pub bytecode: link::Bytecode,
+    /// The runtime bytecode.
+    pub runtime_bytecode: link::Bytecode,
+    /// The compressed source mapping of the creation bytecode.
+    pub source_map: String,
+    /// The compressed source mapping of the runtime bytecode.
+    pub runtime_source_map: String,
+}
+
+/// Gas estimates of a contract, in the format of the `gasEstimates` output of solc.
+///
+/// Estimates are either a number or `infinite`. Return type of [`Codegen::gas_estimates`].
+#[derive(Clone, Debug, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct GasEstimates {
+    /// The cost of deploying the contract.
+    pub creation: CreationGasEstimates,
+    /// The cost of each external function, by signature.
+    pub external: BTreeMap<String, String>,
+    /// The cost of each internal function, by signature.
+    pub internal: BTreeMap<String, String>,
+}
+
+/// The cost of deploying a contract. See [`GasEstimates`].
+#[derive(Clone, Debug, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CreationGasEstimates {
+    /// The cost of storing the runtime code.
+    pub code_deposit_cost: String,
+    /// The cost of executing the creation code.
+    pub execution_cost: String,
+    /// The sum of the two above.
+    pub total_cost: String,
+}
+
+/// A code generation backend, used for the `bin`, `bin-runtime`, `srcmap`, `srcmap-runtime`, `ir`,
+/// `asm` and `gas-estimates` outputs.
+///
+/// The functions return `None` if the output could not be generated, in which case an error must
+/// have been emitted. See [`ParsingContext::codegen`].
+#[derive(Clone, Copy, Debug)]
+pub struct Codegen {
+    /// Generates the creation and runtime bytecode of a contract.
+    pub bytecode: for<'gcx> fn(Gcx<'gcx>, hir::ContractId) -> Option<ContractBytecode>,
+    /// Generates the Yul IR of a contract.
+    pub ir: for<'gcx> fn(Gcx<'gcx>, hir::ContractId) -> Option<String>,
+    /// Generates the human-readable EVM assembly of a contract.
+    pub asm: for<'gcx> fn(Gcx<'gcx>, hir::ContractId) -> Option<String>,
+    /// Estimates the gas costs of a contract.
+    pub gas_estimates: for<'gcx> fn(Gcx<'gcx>, hir::ContractId) -> Option<GasEstimates>,
+}
+
+/// Lint passes, run on the AST of each source and on the HIR once it has been type checked.
+///
+/// Lints are emitted with [`Session::lint`]. See [`ParsingContext::linter`].
+#[derive(Clone, Copy, Debug)]
+pub struct Linter {
+    /// Checks the AST of a source.
+    pub check_ast: for<'sess, 'ast> fn(&'sess Session, &ast::SourceUnit<'ast>),
+    /// Checks the HIR.
+    pub check_hir: for<'gcx> fn(Gcx<'gcx>),
+}
+
 /// Parses and semantically analyzes all the loaded sources, recursing into imports.
 pub fn parse_and_resolve(pcx: ParsingContext<'_>) -> Result<()> {
+    parse_and_resolve_with(pcx, |_| ()).map(drop)
+}
+
+/// Parses and semantically analyzes all the loaded sources, recursing into imports, then calls
+/// `f` with the global context.
+///
+/// This can be used to query the results of the analysis, such as
+/// [`contract_abi`](Gcx::contract_abi).
+///
+/// Returns `Ok(None)` if compilation was stopped before analysis, e.g. because of
+/// `--stop-after=parsing`.
+pub fn parse_and_resolve_with<R>(
+    pcx: ParsingContext<'_>,
+    f: impl FnOnce(Gcx<'_>) -> R,
+) -> Result<Option<R>> {
     let sess = pcx.sess;
+    let codegen = pcx.codegen;
+    let linter = pcx.linter;
 
     if pcx.sources.is_empty() {
         let msg = "no files found";
         let note = "if you wish to use the standard input, please specify `-` explicitly";
         return Err(sess.dcx.err(msg).note(note).emit());
     }
+    // The types of legacy `var` declarations are not inferred.
+    if sess.legacy_syntax {
+        if let Some(emit) = sess.emit.iter().find(|emit| emit.requires_codegen()) {
+            let msg = format!("cannot emit `{emit}` with legacy syntax enabled");
+            return Err(sess.dcx.err(msg).emit());
+        }
+    }
 
     let ast_arenas = OnDrop::new(ThreadLocal::<ast::Arena>::new(), |mut arenas| {
         debug!(asts_allocated = arenas.iter_mut().map(|a| a.allocated_bytes()).sum::<usize>());
         debug_span!("dropping_ast_arenas").in_scope(|| drop(arenas));
     });
-    let mut sources = pcx.parse(&ast_arenas);
+    let mut remappings = pcx
+        .file_resolver
+        .import_maps()
+        .map(|(map, path)| format!("{}={}", map.display(), path.display()))
+        .collect::<Vec<_>>();
+    remappings.sort_unstable();
+    let mut sources = sess.time("parse", || pcx.parse(&ast_arenas));
 
     if let Some(dump) = &sess.dump {
         if dump.kind.is_ast() {
@@ -60,6 +176,10 @@ pub fn parse_and_resolve(pcx: ParsingContext<'_>) -> Result<()> {
         }
     }
 
+    if let Some(unpretty) = sess.unpretty {
+        print_unpretty(sess, unpretty, &sources);
+    }
+
     if sess.ast_stats {
         for source in sources.asts() {
             stats::print_ast_stats(source, "AST STATS", "ast-stats");
@@ -67,16 +187,26 @@ pub fn parse_and_resolve(pcx: ParsingContext<'_>) -> Result<()> {
     }
 
     if sess.language.is_yul() || sess.stop_after(CompilerStage::Parsed) {
-        return Ok(());
+        return Ok(None);
     }
 
+    // The ASTs are dropped after lowering, so serialize them now.
+    let ast_json = if sess.emit.contains(&CompilerOutput::AstJson) {
+        debug_span!("ast_json").in_scope(|| emit::sources_to_json(&sources))
+    } else {
+        Default::default()
+    };
+
     sources.topo_sort();
 
     let hir_arena = OnDrop::new(ThreadLocal::<hir::Arena>::new(), |hir_arena| {
-        debug!(hir_allocated = hir_arena.get_or_default().allocated_bytes());
+        let hir_allocated = hir_arena.get_or_default().allocated_bytes();
+        debug!(hir_allocated);
+        sess.record_hir_arena_bytes(hir_allocated);
         debug_span!("dropping_hir_arena").in_scope(|| drop(hir_arena));
     });
-    let (hir, symbol_resolver) = lower(sess, &sources, hir_arena.get_or_default())?;
+    let (hir, symbol_resolver) =
+        sess.time("resolve", || lower(sess, &sources, hir_arena.get_or_default(), linter))?;
 
     // Drop the ASTs and AST arenas in a separate thread.
     sess.spawn({
@@ -90,14 +220,53 @@ pub fn parse_and_resolve(pcx: ParsingContext<'_>) -> Result<()> {
         }
     });
 
-    let global_context =
-        OnDrop::new(ty::GlobalCtxt::new(sess, &hir_arena, hir, symbol_resolver), |gcx| {
+    let global_context = OnDrop::new(
+        ty::GlobalCtxt::new(sess, &hir_arena, hir, symbol_resolver, remappings),
+        |gcx| {
             debug_span!("drop_gcx").in_scope(|| drop(gcx));
-        });
+        },
+    );
     let gcx = ty::Gcx::new(unsafe { trustme::decouple_lt(&global_context) });
-    analysis(gcx)?;
+    analysis(gcx, ast_json, codegen, linter)?;
 
-    Ok(())
+    Ok(Some(f(gcx)))
+}
+
+/// Parses all the loaded sources, recursing into imports, and lowers them into the HIR, then calls
+/// `f` with the global context, even if errors were emitted.
+///
+/// Unlike [`parse_and_resolve_with`], sources that fail to parse are skipped, names that cannot be
+/// resolved are lowered as errors, and types are neither lowered upfront nor checked. This is meant
+/// for tools that work on incomplete code, like completions, on a best-effort basis.
+///
+/// Returns `Ok(None)` if the language is Yul.
+pub fn parse_and_lower_with<R>(
+    pcx: ParsingContext<'_>,
+    f: impl FnOnce(Gcx<'_>) -> R,
+) -> Result<Option<R>> {
+    let sess = pcx.sess;
+
+    if pcx.sources.is_empty() {
+        return Err(sess.dcx.err("no files found").emit());
+    }
+
+    let ast_arenas = ThreadLocal::<ast::Arena>::new();
+    let mut sources = pcx.parse(&ast_arenas);
+    if sess.language.is_yul() {
+        return Ok(None);
+    }
+    sources.topo_sort();
+
+    debug_span!("all_ast_passes").in_scope(|| {
+        par_for_each_ordered(sess, sources.par_asts(), |ast| ast_passes::run(sess, ast));
+    });
+    let hir_arena = ThreadLocal::<hir::Arena>::new();
+    let (hir, symbol_resolver) = ast_lowering::lower(sess, &sources, hir_arena.get_or_default());
+    drop(sources);
+
+    let global_context = ty::GlobalCtxt::new(sess, &hir_arena, hir, symbol_resolver, Vec::new());
+    let gcx = ty::Gcx::new(unsafe { trustme::decouple_lt(&global_context) });
+    Ok(Some(f(gcx)))
 }
 
 /// Lowers the parsed ASTs into the HIR.
@@ -105,10 +274,14 @@ fn lower<'sess, 'hir>(
     sess: &'sess Session,
     sources: &ParsedSources<'_>,
     arena: &'hir hir::Arena,
+    linter: Option<Linter>,
 ) -> Result<(hir::Hir<'hir>, ast_lowering::SymbolResolver<'sess>)> {
     debug_span!("all_ast_passes").in_scope(|| {
-        sources.par_asts().for_each(|ast| {
+        par_for_each_ordered(sess, sources.par_asts(), |ast| {
             ast_passes::run(sess, ast);
+            if let Some(linter) = linter {
+                (linter.check_ast)(sess, ast);
+            }
         });
     });
 
@@ -118,35 +291,60 @@ fn lower<'sess, 'hir>(
 }
 
 #[instrument(level = "debug", skip_all)]
-fn analysis(gcx: Gcx<'_>) -> Result<()> {
+fn analysis(
+    gcx: Gcx<'_>,
+    ast_json: BTreeMap<String, emit::SourceJson>,
+    codegen: Option<Codegen>,
+    linter: Option<Linter>,
+) -> Result<()> {
     if let Some(dump) = &gcx.sess.dump {
         if dump.kind.is_hir() {
             dump_hir(gcx, dump.paths.as_deref())?;
         }
     }
 
-    // Lower HIR types.
-    gcx.hir.par_item_ids().for_each(|id| {
-        let _ = gcx.type_of_item(id);
-        match id {
-            hir::ItemId::Struct(id) => _ = gcx.struct_field_types(id),
-            hir::ItemId::Contract(id) => _ = gcx.interface_functions(id),
-            _ => {}
-        }
-    });
-    gcx.sess.dcx.has_errors()?;
+    gcx.sess.time("typeck", || {
+        // Lower HIR types.
+        par_for_each_ordered(gcx.sess, gcx.hir.par_item_ids(), |id| {
+            let _ = gcx.type_of_item(id);
+            match id {
+                hir::ItemId::Struct(id) => _ = gcx.struct_field_types(id),
+                hir::ItemId::Contract(id) => _ = gcx.interface_functions(id),
+                _ => {}
+            }
+        });
+        gcx.sess.dcx.has_errors()?;
+
+        typeck::check(gcx);
+        gcx.sess.dcx.has_errors()
+    })?;
 
-    typeck::check(gcx);
-    gcx.sess.dcx.has_errors()?;
+    if let Some(linter) = linter {
+        gcx.sess.time("lint", || debug_span!("lint_hir").in_scope(|| (linter.check_hir)(gcx)));
+        gcx.sess.dcx.has_errors()?;
+    }
 
     if !gcx.sess.emit.is_empty() {
-        emit::emit(gcx);
+        gcx.sess.time("emit", || emit::emit(gcx, ast_json, codegen));
         gcx.sess.dcx.has_errors()?;
     }
 
     Ok(())
 }
 
+/// Calls `f` on each item, in parallel if enabled, then emits the diagnostics in the order of the
+/// items, so that they do not depend on how the work was scheduled.
+pub(crate) fn par_for_each_ordered<T: Send>(
+    sess: &Session,
+    items: impl ParallelIterator<Item = T>,
+    f: impl Fn(T) + Sync + Send,
+) {
+    let diagnostics = items.map(|item| sess.dcx.capture(|| f(item)).1).collect::<Vec<_>>();
+    for diagnostics in diagnostics {
+        sess.dcx.emit_captured(diagnostics);
+    }
+}
+
 fn dump_ast(sess: &Session, sources: &ParsedSources<'_>, paths: Option<&[String]>) -> Result<()> {
     if let Some(paths) = paths {
         for path in paths {
@@ -172,6 +370,44 @@ fn dump_ast(sess: &Session, sources: &ParsedSources<'_>, paths: Option<&[String]
     Ok(())
 }
 
+/// Prints the `-Zunpretty` representation of the parsed sources to stdout.
+fn print_unpretty(sess: &Session, unpretty: Unpretty, sources: &ParsedSources<'_>) {
+    let mut out = std::io::stdout().lock();
+    for source in sources.iter() {
+        match unpretty {
+            Unpretty::Ast => {
+                let Some(ast) = &source.ast else { continue };
+                let _ = writeln!(out, "// {}", source.file.name.display());
+                let _ = writeln!(out, "{ast:#?}");
+            }
+            Unpretty::Tokens => {
+                let _ = writeln!(out, "// {}", source.file.name.display());
+                // Lexer errors were already emitted when parsing.
+                let mut lexer = solar_parse::Lexer::from_source_file(sess, &source.file);
+                let (tokens, _) = sess.dcx.capture(|| {
+                    std::iter::from_fn(|| Some(lexer.next_token()))
+                        .take_while(|token| !token.is_eof())
+                        .collect::<Vec<_>>()
+                });
+                let sm = sess.source_map();
+                for token in tokens {
+                    let lo = sm.lookup_char_pos(token.span.lo());
+                    let hi = sm.lookup_char_pos(token.span.hi());
+                    let _ = writeln!(
+                        out,
+                        "{}:{}-{}:{} {:?}",
+                        lo.line,
+                        lo.col.0 + 1,
+                        hi.line,
+                        hi.col.0 + 1,
+                        token.kind
+                    );
+                }
+            }
+        }
+    }
+}
+
 fn dump_hir(gcx: Gcx<'_>, paths: Option<&[String]>) -> Result<()> {
     println!("{:#?}", gcx.hir);
     if let Some(paths) = paths {
@@ -186,6 +422,68 @@ fn match_file_name(name: &solar_interface::source_map::FileName, path: &str) ->
             path_buf.as_os_str() == path || path_buf.file_stem() == Some(path.as_ref())
         }
         solar_interface::source_map::FileName::Stdin => path == "stdin" || path == "<stdin>",
-        solar_interface::source_map::FileName::Custom(name) => path == name,
+        solar_interface::source_map::FileName::Custom(name)
+        | solar_interface::source_map::FileName::Generated(name) => path == name,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use solar_interface::{source_map::FileName, ColorChoice};
+    use std::num::NonZeroUsize;
+
+    #[test]
+    fn deterministic_diagnostics() {
+        let sources = [
+            ("a.sol", "contract A { function f() public { uint x; y = 1; } }"),
+            ("b.sol", "contract B { function }"),
+            ("c.sol", "contract C { uint8 constant X = 256; function g() public {} }"),
+            ("d.sol", "contract D is Missing { function h(uint a) public returns (uint) {} }"),
+            ("e.sol", "contract A {}"),
+        ];
+        let compile = |jobs: usize| {
+            let sess = Session::builder()
+                .with_buffer_emitter(ColorChoice::Never)
+                .jobs(NonZeroUsize::new(jobs).unwrap())
+                .build();
+            sess.enter(|| {
+                let mut pcx = ParsingContext::new(&sess);
+                for (name, src) in sources {
+                    let name = FileName::Custom(name.into());
+                    pcx.add_file(
+                        sess.source_map().new_source_file(name, || Ok(src.into())).unwrap(),
+                    );
+                }
+                let _ = pcx.parse_and_resolve();
+            });
+            sess.dcx.emitted_diagnostics().unwrap().to_string()
+        };
+
+        let expected = compile(1);
+        assert!(expected.contains("error"), "{expected}");
+        for _ in 0..4 {
+            assert_eq!(compile(8), expected);
+        }
+    }
+
+    #[test]
+    fn legacy_syntax_codegen() {
+        use solar_interface::config::CompilerOutput;
+
+        let sess = Session::builder()
+            .with_buffer_emitter(ColorChoice::Never)
+            .legacy_syntax(true)
+            .emit([CompilerOutput::Abi, CompilerOutput::Bin].into())
+            .build();
+        sess.enter(|| {
+            let mut pcx = ParsingContext::new(&sess);
+            let src = "contract C { function f() public { var x = 1; } }";
+            let name = FileName::Custom("legacy.sol".into());
+            pcx.add_file(sess.source_map().new_source_file(name, || Ok(src.into())).unwrap());
+            assert!(pcx.parse_and_resolve().is_err());
+        });
+        let emitted = sess.dcx.emitted_diagnostics().unwrap().to_string();
+        assert!(emitted.contains("with legacy syntax enabled"), "{emitted}");
     }
 }
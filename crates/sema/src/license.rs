@@ -0,0 +1,506 @@
+//! SPDX license identifiers.
+//!
+//! Reference: <https://spdx.github.io/spdx-spec/v2.3/SPDX-license-expressions/>
+
+use crate::{hir, lints, ty::Gcx};
+use solar_interface::{error_code, BytePos, Span};
+use solar_parse::{lexer::token::RawTokenKind, Cursor};
+use std::{fmt, iter::Peekable, ops::Range, str::SplitWhitespace};
+
+/// The marker of license identifier comments.
+pub const SPDX_MARKER: &str = "SPDX-License-Identifier:";
+
+/// A license identifier comment, like `// SPDX-License-Identifier: MIT`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LicenseComment<'a> {
+    /// The license expression, trimmed.
+    pub expr: &'a str,
+    /// The byte range of the comment in the source.
+    pub range: Range<usize>,
+    /// Whether this is a line comment.
+    pub is_line: bool,
+}
+
+/// Returns the license identifier comments of a source, in order.
+///
+/// The license expression extends from the marker to the end of the line, or of the comment.
+pub fn find_licenses(src: &str) -> Vec<LicenseComment<'_>> {
+    let mut licenses = Vec::new();
+    let mut pos = 0;
+    for token in Cursor::new(src) {
+        let range = pos..pos + token.len as usize;
+        pos = range.end;
+        let is_line = match token.kind {
+            RawTokenKind::LineComment { .. } => true,
+            RawTokenKind::BlockComment { .. } => false,
+            _ => continue,
+        };
+        let comment = &src[range.clone()];
+        let Some(start) = comment.find(SPDX_MARKER) else { continue };
+        let expr = &comment[start + SPDX_MARKER.len()..];
+        let expr = expr.split(['\n', '\r']).next().unwrap();
+        let expr = if is_line { expr } else { expr.trim_end().trim_end_matches("*/") };
+        licenses.push(LicenseComment { expr: expr.trim(), range, is_line });
+    }
+    licenses
+}
+
+/// Returns the license expression of a source: the expression of its first license identifier
+/// comment.
+pub fn source_license(src: &str) -> Option<&str> {
+    find_licenses(src).first().map(|license| license.expr)
+}
+
+/// Combines license expressions into one that requires all of them, in order, without duplicates.
+///
+/// Compound expressions are parenthesized. Returns `None` if there are no expressions.
+pub fn combine_licenses<'a>(exprs: impl IntoIterator<Item = &'a str>) -> Option<String> {
+    let mut unique = Vec::<&str>::new();
+    for expr in exprs {
+        if !unique.contains(&expr) {
+            unique.push(expr);
+        }
+    }
+    if let [expr] = unique[..] {
+        return Some(expr.to_string());
+    }
+    let parts = unique
+        .iter()
+        .map(|expr| {
+            if expr.contains(char::is_whitespace) && !is_parenthesized(expr) {
+                format!("({expr})")
+            } else {
+                expr.to_string()
+            }
+        })
+        .collect::<Vec<_>>();
+    (!parts.is_empty()).then(|| parts.join(" AND "))
+}
+
+/// Returns `true` if the whole expression is within a single pair of parentheses.
+fn is_parenthesized(expr: &str) -> bool {
+    let Some(inner) = expr.strip_prefix('(') else { return false };
+    let mut depth = 1;
+    for (i, c) in inner.char_indices() {
+        match c {
+            '(' => depth += 1,
+            ')' => depth -= 1,
+            _ => {}
+        }
+        if depth == 0 {
+            return i == inner.len() - 1;
+        }
+    }
+    false
+}
+
+/// An error in a license expression.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum LicenseError {
+    /// The expression is empty or malformed.
+    Syntax,
+    /// A license identifier is not on the SPDX license list.
+    UnknownLicense(String),
+    /// A license exception identifier is not on the SPDX license exception list.
+    UnknownException(String),
+}
+
+impl fmt::Display for LicenseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Syntax => f.write_str("invalid SPDX license expression"),
+            Self::UnknownLicense(id) => write!(f, "unknown SPDX license identifier `{id}`"),
+            Self::UnknownException(id) => {
+                write!(f, "unknown SPDX license exception identifier `{id}`")
+            }
+        }
+    }
+}
+
+impl std::error::Error for LicenseError {}
+
+/// Validates a license expression against the SPDX license list.
+///
+/// `UNLICENSED`, which is not an SPDX identifier, is also accepted for code that is not open
+/// source, as are `LicenseRef-` and `DocumentRef-` references to licenses that are not listed.
+pub fn validate_license(expr: &str) -> Result<(), LicenseError> {
+    let spaced = expr.replace('(', " ( ").replace(')', " ) ");
+    let mut parser = ExprParser { tokens: spaced.split_whitespace().peekable() };
+    parser.or()?;
+    match parser.tokens.next() {
+        Some(_) => Err(LicenseError::Syntax),
+        None => Ok(()),
+    }
+}
+
+/// A recursive descent parser of license expressions.
+///
+/// `WITH` binds tighter than `AND`, which binds tighter than `OR`.
+struct ExprParser<'a> {
+    tokens: Peekable<SplitWhitespace<'a>>,
+}
+
+impl ExprParser<'_> {
+    fn or(&mut self) -> Result<(), LicenseError> {
+        self.and()?;
+        while self.tokens.next_if_eq(&"OR").is_some() {
+            self.and()?;
+        }
+        Ok(())
+    }
+
+    fn and(&mut self) -> Result<(), LicenseError> {
+        self.with()?;
+        while self.tokens.next_if_eq(&"AND").is_some() {
+            self.with()?;
+        }
+        Ok(())
+    }
+
+    fn with(&mut self) -> Result<(), LicenseError> {
+        let is_simple = self.tokens.peek() != Some(&"(");
+        self.atom()?;
+        if is_simple && self.tokens.next_if_eq(&"WITH").is_some() {
+            let id = self.tokens.next().ok_or(LicenseError::Syntax)?;
+            if !is_id(id) {
+                return Err(LicenseError::Syntax);
+            }
+            if !EXCEPTIONS.iter().any(|e| e.eq_ignore_ascii_case(id)) {
+                return Err(LicenseError::UnknownException(id.to_string()));
+            }
+        }
+        Ok(())
+    }
+
+    fn atom(&mut self) -> Result<(), LicenseError> {
+        match self.tokens.next() {
+            Some("(") => {
+                self.or()?;
+                self.tokens.next_if_eq(&")").map(drop).ok_or(LicenseError::Syntax)
+            }
+            Some(id) => {
+                let id = id.strip_suffix('+').unwrap_or(id);
+                if is_license_ref(id) {
+                    return Ok(());
+                }
+                if !is_id(id) || matches!(id, "AND" | "OR" | "WITH") {
+                    return Err(LicenseError::Syntax);
+                }
+                if id == "UNLICENSED" || LICENSES.iter().any(|l| l.eq_ignore_ascii_case(id)) {
+                    Ok(())
+                } else {
+                    Err(LicenseError::UnknownLicense(id.to_string()))
+                }
+            }
+            None => Err(LicenseError::Syntax),
+        }
+    }
+}
+
+fn is_id(s: &str) -> bool {
+    !s.is_empty() && s.bytes().all(|b| b.is_ascii_alphanumeric() || b == b'-' || b == b'.')
+}
+
+fn is_license_ref(id: &str) -> bool {
+    let id = match id.strip_prefix("DocumentRef-") {
+        Some(rest) => match rest.split_once(':') {
+            Some((doc, id)) if is_id(doc) => id,
+            _ => return false,
+        },
+        None => id,
+    };
+    id.strip_prefix("LicenseRef-").is_some_and(is_id)
+}
+
+/// Warns about sources without a license identifier, with more than one, or with an invalid one.
+pub(crate) fn check_source(gcx: Gcx<'_>, id: hir::SourceId) {
+    let file = &gcx.hir.source(id).file;
+    let span = |range: Range<usize>| {
+        let pos = |offset| file.start_pos + BytePos::from_usize(offset);
+        Span::new(pos(range.start), pos(range.end))
+    };
+    let licenses = find_licenses(&file.src);
+    let Some(first) = licenses.first() else {
+        let start = Span::new(file.start_pos, file.start_pos);
+        gcx.sess
+            .lint(&lints::MISSING_LICENSE, "SPDX license identifier not provided in source file")
+            .code(error_code!(1878))
+            .span(start)
+            .help(format!("add a comment containing `{SPDX_MARKER} <SPDX-License>`"))
+            .help(format!("use `{SPDX_MARKER} UNLICENSED` for code that is not open source"))
+            .emit();
+        return;
+    };
+    if let Err(e) = validate_license(first.expr) {
+        gcx.sess
+            .lint(&lints::INVALID_LICENSE, e.to_string())
+            .span(span(first.range.clone()))
+            .emit();
+    }
+    for license in &licenses[1..] {
+        gcx.sess
+            .lint(&lints::INVALID_LICENSE, "multiple SPDX license identifiers found in source file")
+            .code(error_code!(3716))
+            .span(span(license.range.clone()))
+            .help("use `AND` or `OR` to combine multiple licenses in a single identifier")
+            .emit();
+    }
+}
+
+/// The identifiers of the SPDX license list.
+static LICENSES: &[&str] = &[
+    "0BSD",
+    "AAL",
+    "AFL-1.1",
+    "AFL-1.2",
+    "AFL-2.0",
+    "AFL-2.1",
+    "AFL-3.0",
+    "AGPL-1.0",
+    "AGPL-1.0-only",
+    "AGPL-1.0-or-later",
+    "AGPL-3.0",
+    "AGPL-3.0-only",
+    "AGPL-3.0-or-later",
+    "Apache-1.0",
+    "Apache-1.1",
+    "Apache-2.0",
+    "APL-1.0",
+    "APSL-1.0",
+    "APSL-1.1",
+    "APSL-1.2",
+    "APSL-2.0",
+    "Artistic-1.0",
+    "Artistic-1.0-cl8",
+    "Artistic-1.0-Perl",
+    "Artistic-2.0",
+    "BlueOak-1.0.0",
+    "BSD-1-Clause",
+    "BSD-2-Clause",
+    "BSD-2-Clause-Patent",
+    "BSD-3-Clause",
+    "BSD-3-Clause-Clear",
+    "BSD-3-Clause-LBNL",
+    "BSD-4-Clause",
+    "BSL-1.0",
+    "BUSL-1.1",
+    "CAL-1.0",
+    "CATOSL-1.1",
+    "CC-BY-1.0",
+    "CC-BY-2.0",
+    "CC-BY-2.5",
+    "CC-BY-3.0",
+    "CC-BY-4.0",
+    "CC-BY-NC-4.0",
+    "CC-BY-NC-ND-4.0",
+    "CC-BY-NC-SA-4.0",
+    "CC-BY-ND-4.0",
+    "CC-BY-SA-3.0",
+    "CC-BY-SA-4.0",
+    "CC0-1.0",
+    "CDDL-1.0",
+    "CDDL-1.1",
+    "CECILL-2.1",
+    "CERN-OHL-P-2.0",
+    "CERN-OHL-S-2.0",
+    "CERN-OHL-W-2.0",
+    "CNRI-Python",
+    "CPAL-1.0",
+    "CPL-1.0",
+    "CUA-OPL-1.0",
+    "ECL-1.0",
+    "ECL-2.0",
+    "EFL-1.0",
+    "EFL-2.0",
+    "Entessa",
+    "EPL-1.0",
+    "EPL-2.0",
+    "EUDatagrid",
+    "EUPL-1.1",
+    "EUPL-1.2",
+    "Fair",
+    "Frameworx-1.0",
+    "FSFAP",
+    "GFDL-1.3-only",
+    "GFDL-1.3-or-later",
+    "GPL-1.0-only",
+    "GPL-1.0-or-later",
+    "GPL-2.0",
+    "GPL-2.0-only",
+    "GPL-2.0-or-later",
+    "GPL-3.0",
+    "GPL-3.0-only",
+    "GPL-3.0-or-later",
+    "HPND",
+    "Intel",
+    "IPA",
+    "IPL-1.0",
+    "ISC",
+    "Jam",
+    "LGPL-2.0-only",
+    "LGPL-2.0-or-later",
+    "LGPL-2.1",
+    "LGPL-2.1-only",
+    "LGPL-2.1-or-later",
+    "LGPL-3.0",
+    "LGPL-3.0-only",
+    "LGPL-3.0-or-later",
+    "LiLiQ-P-1.1",
+    "LiLiQ-R-1.1",
+    "LiLiQ-Rplus-1.1",
+    "LPL-1.0",
+    "LPL-1.02",
+    "LPPL-1.3c",
+    "MirOS",
+    "MIT",
+    "MIT-0",
+    "MIT-Modern-Variant",
+    "Motosoto",
+    "MPL-1.0",
+    "MPL-1.1",
+    "MPL-2.0",
+    "MPL-2.0-no-copyleft-exception",
+    "MS-PL",
+    "MS-RL",
+    "MulanPSL-2.0",
+    "Multics",
+    "NASA-1.3",
+    "Naumen",
+    "NCSA",
+    "NGPL",
+    "Nokia",
+    "NPOSL-3.0",
+    "NTP",
+    "OCLC-2.0",
+    "ODbL-1.0",
+    "OFL-1.1",
+    "OGTSL",
+    "OLDAP-2.8",
+    "OSET-PL-2.1",
+    "OSL-1.0",
+    "OSL-2.0",
+    "OSL-2.1",
+    "OSL-3.0",
+    "PHP-3.0",
+    "PHP-3.01",
+    "PostgreSQL",
+    "PSF-2.0",
+    "Python-2.0",
+    "QPL-1.0",
+    "RPL-1.1",
+    "RPL-1.5",
+    "RPSL-1.0",
+    "RSCPL",
+    "SimPL-2.0",
+    "SISSL",
+    "Sleepycat",
+    "SPL-1.0",
+    "UCL-1.0",
+    "Unicode-DFS-2016",
+    "Unlicense",
+    "UPL-1.0",
+    "VSL-1.0",
+    "W3C",
+    "Watcom-1.0",
+    "WTFPL",
+    "Xnet",
+    "Zlib",
+    "ZPL-2.0",
+    "ZPL-2.1",
+];
+
+/// The identifiers of the SPDX license exception list.
+static EXCEPTIONS: &[&str] = &[
+    "389-exception",
+    "Autoconf-exception-2.0",
+    "Autoconf-exception-3.0",
+    "Bison-exception-2.2",
+    "Bootloader-exception",
+    "Classpath-exception-2.0",
+    "CLISP-exception-2.0",
+    "DigiRule-FOSS-exception",
+    "eCos-exception-2.0",
+    "Fawkes-Runtime-exception",
+    "FLTK-exception",
+    "Font-exception-2.0",
+    "freertos-exception-2.0",
+    "GCC-exception-2.0",
+    "GCC-exception-3.1",
+    "gnu-javamail-exception",
+    "GPL-3.0-linking-exception",
+    "GPL-3.0-linking-source-exception",
+    "GPL-CC-1.0",
+    "i2p-gpl-java-exception",
+    "LGPL-3.0-linking-exception",
+    "Libtool-exception",
+    "Linux-syscall-note",
+    "LLVM-exception",
+    "LZMA-exception",
+    "mif-exception",
+    "OCaml-LGPL-linking-exception",
+    "OCCT-exception-1.0",
+    "OpenJDK-assembly-exception-1.0",
+    "openvpn-openssl-exception",
+    "PS-or-PDF-font-exception-20170817",
+    "Qt-GPL-exception-1.0",
+    "Qt-LGPL-exception-1.1",
+    "Qwt-exception-1.0",
+    "Swift-exception",
+    "u-boot-exception-2.0",
+    "Universal-FOSS-exception-1.0",
+    "WxWindows-exception-3.1",
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn find() {
+        let src = "// SPDX-License-Identifier: MIT\n/* SPDX-License-Identifier: GPL-3.0 */\n\
+                   // a\n/**\n * SPDX-License-Identifier: Apache-2.0 OR MIT\n */";
+        let licenses = find_licenses(src);
+        let exprs = licenses.iter().map(|l| l.expr).collect::<Vec<_>>();
+        assert_eq!(exprs, ["MIT", "GPL-3.0", "Apache-2.0 OR MIT"]);
+        assert_eq!(licenses[0].range, 0..31);
+        assert!(licenses[0].is_line && !licenses[1].is_line);
+        assert_eq!(source_license("contract C {}"), None);
+    }
+
+    #[test]
+    fn validate() {
+        for expr in [
+            "MIT",
+            "mit",
+            "UNLICENSED",
+            "GPL-2.0+",
+            "MIT OR Apache-2.0",
+            "(MIT AND BSD-3-Clause) OR GPL-3.0-only WITH Classpath-exception-2.0",
+            "LicenseRef-Proprietary",
+            "DocumentRef-spdx:LicenseRef-Custom",
+        ] {
+            assert_eq!(validate_license(expr), Ok(()), "{expr}");
+        }
+        for expr in
+            ["", "MIT OR", "(MIT", "MIT)", "MIT Apache-2.0", "AND", "(MIT) WITH LLVM-exception"]
+        {
+            assert_eq!(validate_license(expr), Err(LicenseError::Syntax), "{expr}");
+        }
+        assert_eq!(validate_license("MIT OR Foo"), Err(LicenseError::UnknownLicense("Foo".into())));
+        assert_eq!(
+            validate_license("MIT WITH Foo"),
+            Err(LicenseError::UnknownException("Foo".into()))
+        );
+    }
+
+    #[test]
+    fn combine() {
+        assert_eq!(combine_licenses([]), None);
+        assert_eq!(combine_licenses(["MIT", "MIT"]).as_deref(), Some("MIT"));
+        assert_eq!(
+            combine_licenses(["MIT", "Apache-2.0 OR MIT", "(GPL-3.0 OR MIT)", "MIT"]).as_deref(),
+            Some("MIT AND (Apache-2.0 OR MIT) AND (GPL-3.0 OR MIT)")
+        );
+        assert!(!is_parenthesized("(MIT) OR (GPL-3.0)"));
+    }
+}
@@ -0,0 +1,150 @@
+//! Library linking.
+//!
+//! Calls of public and external library functions are compiled to `DELEGATECALL`s to the address
+//! of the deployed library, which is not known at compile time. The address is left as a
+//! placeholder in the bytecode, whose location is recorded in the link references, and is filled
+//! in when linking, either during compilation with the session's `libraries` or afterwards with
+//! [`link_hex`].
+//!
+//! Reference: <https://docs.soliditylang.org/en/latest/using-the-compiler.html#library-linking>
+
+use alloy_primitives::{hex, keccak256};
+use serde::{Deserialize, Serialize};
+use solar_interface::config::Library;
+use std::collections::BTreeMap;
+
+/// The byte range of a library address placeholder in the bytecode.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LinkReference {
+    /// The byte offset of the placeholder.
+    pub start: usize,
+    /// The length of the placeholder, always 20.
+    pub length: usize,
+}
+
+/// The unlinked library references of bytecode, by source file path and library name.
+///
+/// Serialized as the `linkReferences` JSON object of `solc`.
+pub type LinkReferences = BTreeMap<String, BTreeMap<String, Vec<LinkReference>>>;
+
+/// Bytecode, possibly with unlinked library references.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Bytecode {
+    /// The code, in which the unlinked library addresses are zero.
+    pub code: Vec<u8>,
+    /// The unlinked library references.
+    pub link_references: LinkReferences,
+}
+
+impl Bytecode {
+    /// Creates new bytecode from the code and the byte offsets of the library addresses in it,
+    /// along with the fully qualified names of the libraries, as `<path>:<name>`.
+    pub fn new(code: Vec<u8>, references: impl IntoIterator<Item = (usize, String)>) -> Self {
+        let mut link_references = LinkReferences::new();
+        for (start, library) in references {
+            let (path, name) = library.rsplit_once(':').unwrap_or(("", &library));
+            link_references
+                .entry(path.to_string())
+                .or_default()
+                .entry(name.to_string())
+                .or_default()
+                .push(LinkReference { start, length: 20 });
+        }
+        Self { code, link_references }
+    }
+
+    /// Returns `true` if all library references are linked.
+    pub fn is_linked(&self) -> bool {
+        self.link_references.is_empty()
+    }
+
+    /// Fills in the addresses of the given libraries, removing their link references.
+    ///
+    /// Libraries without a path match the libraries with the same name in any file.
+    pub fn link(&mut self, libraries: &[Library]) {
+        let code = &mut self.code;
+        self.link_references.retain(|path, references| {
+            references.retain(|name, references| {
+                let Some(library) = libraries.iter().find(|lib| lib.matches(path, name)) else {
+                    return true;
+                };
+                for reference in references {
+                    code[reference.start..reference.start + 20].copy_from_slice(&library.address);
+                }
+                false
+            });
+            !references.is_empty()
+        });
+    }
+
+    /// Returns the hex-encoded code, with the unlinked library addresses replaced by
+    /// [placeholders](placeholder).
+    pub fn to_hex(&self) -> String {
+        let mut hex = hex::encode(&self.code);
+        for (path, references) in &self.link_references {
+            for (name, references) in references {
+                let placeholder = placeholder(&format!("{path}:{name}"));
+                for reference in references {
+                    let start = reference.start * 2;
+                    hex.replace_range(start..start + 40, &placeholder);
+                }
+            }
+        }
+        hex
+    }
+}
+
+/// Returns the 40-character placeholder of the address of the library with the given fully
+/// qualified name in hex-encoded bytecode: `__$<34 hex characters of its keccak256 hash>$__`.
+pub fn placeholder(library: &str) -> String {
+    format!("__${}$__", &hex::encode(keccak256(library))[..34])
+}
+
+/// Links hex-encoded bytecode, replacing the placeholders of the given libraries with their
+/// addresses.
+///
+/// Since placeholders are derived from the fully qualified name of the library, libraries
+/// without a path are not linked.
+pub fn link_hex(hex: &str, libraries: &[Library]) -> String {
+    let mut hex = hex.to_string();
+    for library in libraries.iter().filter(|lib| lib.name.contains(':')) {
+        hex = hex.replace(&placeholder(&library.name), &hex::encode(library.address));
+    }
+    hex
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn link() {
+        let code = vec![0x73; 43];
+        let refs = [(1, "a.sol:L".to_string()), (22, "b.sol:M".to_string())];
+        let mut bytecode = Bytecode::new(code, refs);
+        assert!(!bytecode.is_linked());
+        assert_eq!(
+            bytecode.link_references["a.sol"]["L"],
+            [LinkReference { start: 1, length: 20 }]
+        );
+
+        let unlinked = bytecode.to_hex();
+        assert_eq!(unlinked.len(), 86);
+        assert_eq!(&unlinked[2..42], placeholder("a.sol:L"));
+        assert_eq!(&unlinked[44..84], placeholder("b.sol:M"));
+        assert_eq!(placeholder("a.sol:L").len(), 40);
+
+        let l = Library { name: "L".into(), address: [0x11; 20] };
+        let m = Library { name: "b.sol:M".into(), address: [0x22; 20] };
+        assert_eq!(link_hex(&unlinked, &[l.clone()]), unlinked);
+        let linked_m = link_hex(&unlinked, &[m.clone()]);
+        assert_eq!(&linked_m[44..84], "22".repeat(20));
+
+        bytecode.link(&[m]);
+        assert_eq!(bytecode.to_hex(), linked_m);
+        bytecode.link(&[l]);
+        assert!(bytecode.is_linked());
+        assert_eq!(&bytecode.code[1..21], [0x11; 20]);
+        assert_eq!(&bytecode.code[22..42], [0x22; 20]);
+    }
+}
@@ -0,0 +1,45 @@
+//! The lints emitted during semantic analysis.
+
+use solar_interface::{declare_lint, lint::Lint};
+
+declare_lint! {
+    /// Unused local variables, function parameters and try/catch parameters.
+    pub UNUSED_VARIABLES, "unused-variables", Warn, "detects unused variables and parameters"
+}
+
+declare_lint! {
+    /// Imported names that are never referred to.
+    pub UNUSED_IMPORTS, "unused-imports", Warn, "detects unused imports"
+}
+
+declare_lint! {
+    /// Parameters and local variables with the same name as a member, a declaration of the source
+    /// or a builtin.
+    pub SHADOWING, "shadowing", Warn, "detects declarations that shadow other declarations"
+}
+
+declare_lint! {
+    /// Statements that can never be executed.
+    pub UNREACHABLE_CODE, "unreachable-code", Warn, "detects unreachable code"
+}
+
+declare_lint! {
+    /// Sources without an SPDX license identifier comment.
+    pub MISSING_LICENSE, "missing-license", Allow, "detects sources without a license identifier"
+}
+
+declare_lint! {
+    /// SPDX license identifiers that are not valid license expressions, or that are not the only
+    /// one of their source.
+    pub INVALID_LICENSE, "invalid-license", Warn, "detects invalid license identifiers"
+}
+
+/// All the lints emitted during semantic analysis.
+pub static LINTS: &[&Lint] = &[
+    &UNUSED_VARIABLES,
+    &UNUSED_IMPORTS,
+    &SHADOWING,
+    &UNREACHABLE_CODE,
+    &MISSING_LICENSE,
+    &INVALID_LICENSE,
+];
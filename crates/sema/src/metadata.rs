@@ -0,0 +1,354 @@
+//! Contract metadata JSON and the CBOR-encoded metadata trailer appended to the bytecode.
+//!
+//! Reference: <https://docs.soliditylang.org/en/latest/metadata.html>
+
+use crate::{hir, ty::Gcx};
+use alloy_primitives::{hex, keccak256, B256};
+use serde_json::{Map, Value};
+use sha2::{Digest, Sha256};
+use solar_interface::config::BytecodeHash;
+
+/// Returns the metadata JSON of the given contract.
+///
+/// The result is serialized compactly with sorted keys, as its hash is embedded in the bytecode.
+pub fn contract_metadata(gcx: Gcx<'_>, id: hir::ContractId) -> String {
+    let c = gcx.hir.contract(id);
+    let source = gcx.hir.source(c.source);
+
+    let mut output = Map::new();
+    let abi = serde_json::to_value(gcx.contract_abi(id)).unwrap();
+    output.insert("abi".into(), abi);
+    output.insert("devdoc".into(), crate::emit::devdoc(gcx, id));
+    output.insert("userdoc".into(), crate::emit::userdoc(gcx, id));
+
+    let mut compilation_target = Map::new();
+    compilation_target.insert(source.file.name.display().to_string(), c.name.to_string().into());
+    let mut settings = Map::new();
+    settings.insert("compilationTarget".into(), compilation_target.into());
+    settings.insert("evmVersion".into(), gcx.sess.evm_version.to_str().into());
+    let libraries = gcx
+        .sess
+        .libraries
+        .iter()
+        .map(|lib| (lib.name.clone(), Value::from(format!("0x{}", hex::encode(lib.address)))));
+    settings.insert("libraries".into(), libraries.collect::<Map<_, _>>().into());
+    let mut metadata_settings = Map::new();
+    metadata_settings.insert("bytecodeHash".into(), gcx.sess.bytecode_hash.to_str().into());
+    settings.insert("metadata".into(), metadata_settings.into());
+    let mut optimizer = Map::new();
+    optimizer.insert("enabled".into(), gcx.sess.optimizer.enabled.into());
+    optimizer.insert("runs".into(), gcx.sess.optimizer.runs.into());
+    settings.insert("optimizer".into(), optimizer.into());
+    settings.insert("remappings".into(), gcx.remappings.clone().into());
+    if gcx.sess.codegen_via_ir() {
+        settings.insert("viaIR".into(), true.into());
+    }
+
+    let mut sources = Map::new();
+    for source_id in source_dependencies(gcx, c.source) {
+        let file = &gcx.hir.source(source_id).file;
+        let content = file.src.as_bytes();
+        let mut source_json = Map::new();
+        source_json.insert("keccak256".into(), keccak256(content).to_string().into());
+        if let Some(license) = crate::license::source_license(&file.src) {
+            source_json.insert("license".into(), license.into());
+        }
+        let urls = vec![
+            format!("bzz-raw://{}", hex::encode(bzzr1_hash(content))),
+            format!("dweb:/ipfs/{}", base58_encode(&ipfs_hash(content))),
+        ];
+        source_json.insert("urls".into(), urls.into());
+        sources.insert(file.name.display().to_string(), source_json.into());
+    }
+
+    let mut metadata = Map::new();
+    let mut compiler = Map::new();
+    compiler.insert("version".into(), solar_interface::VERSION.into());
+    metadata.insert("compiler".into(), compiler.into());
+    metadata.insert("language".into(), "Solidity".into());
+    metadata.insert("output".into(), output.into());
+    metadata.insert("settings".into(), settings.into());
+    metadata.insert("sources".into(), sources.into());
+    metadata.insert("version".into(), 1.into());
+    Value::from(metadata).to_string()
+}
+
+/// Returns the given source and all the sources it imports, recursively, sorted by ID.
+fn source_dependencies(gcx: Gcx<'_>, id: hir::SourceId) -> Vec<hir::SourceId> {
+    let mut seen = vec![false; gcx.hir.sources().len()];
+    let mut stack = vec![id];
+    while let Some(id) = stack.pop() {
+        if std::mem::replace(&mut seen[id.index()], true) {
+            continue;
+        }
+        stack.extend(gcx.hir.source(id).imports.iter().map(|&(_, import)| import));
+    }
+    gcx.hir.source_ids().filter(|id| seen[id.index()]).collect()
+}
+
+/// Returns the CBOR-encoded metadata trailer for the given metadata JSON, including the trailing
+/// 2-byte big-endian length.
+///
+/// The trailer is a CBOR map containing the hash of the metadata according to `hash`, if any,
+/// and the compiler version under the `solar` key.
+pub fn cbor_metadata(metadata: &str, hash: BytecodeHash) -> Vec<u8> {
+    let mut entries: Vec<(&str, Vec<u8>)> = Vec::with_capacity(2);
+    match hash {
+        BytecodeHash::Ipfs => entries.push(("ipfs", ipfs_hash(metadata.as_bytes()))),
+        BytecodeHash::Bzzr1 => entries.push(("bzzr1", bzzr1_hash(metadata.as_bytes()).to_vec())),
+        BytecodeHash::None => {}
+    }
+    entries.push(("solar", compiler_version_bytes().to_vec()));
+
+    let mut cbor = Vec::with_capacity(64);
+    cbor_header(&mut cbor, 5, entries.len());
+    for (key, value) in &entries {
+        cbor_header(&mut cbor, 3, key.len());
+        cbor.extend_from_slice(key.as_bytes());
+        cbor_header(&mut cbor, 2, value.len());
+        cbor.extend_from_slice(value);
+    }
+    let len = u16::try_from(cbor.len()).expect("CBOR metadata too long");
+    cbor.extend_from_slice(&len.to_be_bytes());
+    cbor
+}
+
+/// Writes a CBOR item header with the given major type and length.
+fn cbor_header(out: &mut Vec<u8>, major: u8, len: usize) {
+    let major = major << 5;
+    match len {
+        0..24 => out.push(major | len as u8),
+        24..0x100 => out.extend_from_slice(&[major | 24, len as u8]),
+        0x100..0x10000 => {
+            out.push(major | 25);
+            out.extend_from_slice(&(len as u16).to_be_bytes());
+        }
+        _ => {
+            out.push(major | 26);
+            out.extend_from_slice(&(len as u32).to_be_bytes());
+        }
+    }
+}
+
+/// Returns the `[major, minor, patch]` version of the compiler.
+fn compiler_version_bytes() -> [u8; 3] {
+    let mut parts = solar_interface::VERSION.split(['.', '-', '+']).map(|s| s.parse().unwrap_or(0));
+    [(); 3].map(|()| parts.next().unwrap_or(0))
+}
+
+/// Computes the IPFS multihash (CIDv0) of the given data, as a file with the default chunking
+/// and a balanced DAG layout.
+///
+/// The result is the 34-byte `sha2-256` multihash, which is displayed in base58 as `Qm...`.
+pub fn ipfs_hash(data: &[u8]) -> Vec<u8> {
+    const CHUNK_SIZE: usize = 256 * 1024;
+    const MAX_LINKS: usize = 174;
+
+    let mut nodes = if data.is_empty() {
+        vec![IpfsNode::leaf(data)]
+    } else {
+        data.chunks(CHUNK_SIZE).map(IpfsNode::leaf).collect::<Vec<_>>()
+    };
+    while nodes.len() > 1 {
+        nodes = nodes.chunks(MAX_LINKS).map(IpfsNode::parent).collect();
+    }
+    nodes.pop().unwrap().hash.to_vec()
+}
+
+struct IpfsNode {
+    /// The `sha2-256` multihash of the block.
+    hash: [u8; 34],
+    /// The size of the file data this node represents.
+    file_size: usize,
+    /// The cumulative size of the encoded blocks of this node and all its children.
+    dag_size: usize,
+}
+
+impl IpfsNode {
+    fn leaf(data: &[u8]) -> Self {
+        // UnixFS `Data { Type: File, Data: data, filesize: len }`.
+        let mut unixfs = vec![0x08, 0x02];
+        if !data.is_empty() {
+            protobuf_bytes(&mut unixfs, 2, data);
+        }
+        protobuf_varint_field(&mut unixfs, 3, data.len());
+        // `PBNode { Data: unixfs }`.
+        let mut block = Vec::with_capacity(unixfs.len() + 8);
+        protobuf_bytes(&mut block, 1, &unixfs);
+        Self { hash: multihash(&block), file_size: data.len(), dag_size: block.len() }
+    }
+
+    fn parent(children: &[Self]) -> Self {
+        let file_size = children.iter().map(|c| c.file_size).sum();
+        // UnixFS `Data { Type: File, filesize, blocksizes }`.
+        let mut unixfs = vec![0x08, 0x02];
+        protobuf_varint_field(&mut unixfs, 3, file_size);
+        for child in children {
+            protobuf_varint_field(&mut unixfs, 4, child.file_size);
+        }
+        // `PBNode { Links, Data: unixfs }`. Links are encoded first.
+        let mut block = Vec::new();
+        for child in children {
+            let mut link = Vec::with_capacity(48);
+            protobuf_bytes(&mut link, 1, &child.hash);
+            protobuf_bytes(&mut link, 2, &[]);
+            protobuf_varint_field(&mut link, 3, child.dag_size);
+            protobuf_bytes(&mut block, 2, &link);
+        }
+        protobuf_bytes(&mut block, 1, &unixfs);
+        let dag_size = block.len() + children.iter().map(|c| c.dag_size).sum::<usize>();
+        Self { hash: multihash(&block), file_size, dag_size }
+    }
+}
+
+fn multihash(block: &[u8]) -> [u8; 34] {
+    let mut hash = [0; 34];
+    hash[0] = 0x12;
+    hash[1] = 0x20;
+    hash[2..].copy_from_slice(&Sha256::digest(block));
+    hash
+}
+
+fn protobuf_bytes(out: &mut Vec<u8>, field: u8, bytes: &[u8]) {
+    out.push(field << 3 | 2);
+    protobuf_varint(out, bytes.len());
+    out.extend_from_slice(bytes);
+}
+
+fn protobuf_varint_field(out: &mut Vec<u8>, field: u8, value: usize) {
+    out.push(field << 3);
+    protobuf_varint(out, value);
+}
+
+fn protobuf_varint(out: &mut Vec<u8>, mut value: usize) {
+    while value >= 0x80 {
+        out.push(value as u8 | 0x80);
+        value >>= 7;
+    }
+    out.push(value as u8);
+}
+
+/// Computes the Swarm `bzzr1` hash of the given data.
+///
+/// This is the binary Merkle tree chunk hash, using 4096-byte chunks.
+pub fn bzzr1_hash(data: &[u8]) -> B256 {
+    swarm_chunk_hash(data, false)
+}
+
+fn swarm_chunk_hash(data: &[u8], force_higher_level: bool) -> B256 {
+    const CHUNK_SIZE: usize = 0x1000;
+    const BRANCHES: usize = CHUNK_SIZE / 32;
+
+    let mut to_hash;
+    if data.len() < CHUNK_SIZE || (data.len() == CHUNK_SIZE && !force_higher_level) {
+        to_hash = data.to_vec();
+    } else {
+        let mut max_represented_size = CHUNK_SIZE;
+        while max_represented_size * BRANCHES < data.len() {
+            max_represented_size *= BRANCHES;
+        }
+        // If the remaining size is exactly one chunk but `max_represented_size` is larger, we still
+        // have to do one level of chunk hashes.
+        let force_higher = max_represented_size > CHUNK_SIZE;
+        to_hash = Vec::with_capacity(CHUNK_SIZE);
+        for chunk in data.chunks(max_represented_size) {
+            to_hash.extend_from_slice(swarm_chunk_hash(chunk, force_higher).as_slice());
+        }
+    }
+    to_hash.resize(CHUNK_SIZE, 0);
+
+    let mut preimage = Vec::with_capacity(8 + 32);
+    preimage.extend_from_slice(&(data.len() as u64).to_le_bytes());
+    preimage.extend_from_slice(swarm_bmt_hash(&to_hash).as_slice());
+    keccak256(preimage)
+}
+
+fn swarm_bmt_hash(data: &[u8]) -> B256 {
+    if data.len() <= 64 {
+        return keccak256(data);
+    }
+    let (left, right) = data.split_at(data.len() / 2);
+    let mut concat = [0; 64];
+    concat[..32].copy_from_slice(swarm_bmt_hash(left).as_slice());
+    concat[32..].copy_from_slice(swarm_bmt_hash(right).as_slice());
+    keccak256(concat)
+}
+
+/// Encodes the given bytes in base58, using the Bitcoin alphabet.
+pub fn base58_encode(data: &[u8]) -> String {
+    const ALPHABET: &[u8; 58] = b"123456789ABCDEFGHJKLMNPQRSTUVWXYZabcdefghijkmnopqrstuvwxyz";
+
+    let zeros = data.iter().take_while(|&&b| b == 0).count();
+    // Little-endian base58 digits.
+    let mut digits = Vec::<u8>::with_capacity(data.len() * 138 / 100 + 1);
+    for &byte in &data[zeros..] {
+        let mut carry = byte as usize;
+        for digit in &mut digits {
+            carry += (*digit as usize) << 8;
+            *digit = (carry % 58) as u8;
+            carry /= 58;
+        }
+        while carry > 0 {
+            digits.push((carry % 58) as u8);
+            carry /= 58;
+        }
+    }
+    let mut s = String::with_capacity(zeros + digits.len());
+    s.extend(std::iter::repeat_n('1', zeros));
+    s.extend(digits.iter().rev().map(|&d| ALPHABET[d as usize] as char));
+    s
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn remappings() {
+        let metadata = crate::test_utils::with_pcx(&[("a.sol", "contract A {}")], |mut pcx, _| {
+            pcx.file_resolver.add_import_map("lib/".into(), "node_modules/lib/".into());
+            pcx.file_resolver.add_import_map("@a/".into(), "deps/a/".into());
+            pcx.parse_and_resolve_with(|gcx| {
+                contract_metadata(gcx, gcx.hir.contract_ids().next().unwrap())
+            })
+        });
+        let metadata: Value = serde_json::from_str(&metadata.unwrap().unwrap()).unwrap();
+        assert_eq!(
+            metadata["settings"]["remappings"],
+            serde_json::json!(["@a/=deps/a/", "lib/=node_modules/lib/"])
+        );
+    }
+
+    #[test]
+    fn ipfs() {
+        let hash = |data: &str| base58_encode(&ipfs_hash(data.as_bytes()));
+        assert_eq!(hash(""), "QmbFMke1KXqnYyBBWxB74N4c5SBnJMVAiMNRcGu6x1AwQH");
+        assert_eq!(hash("hello"), "QmWfVY9y3xjsixTgbd9AorQxH7VtMpzfx2HaWtsoUYecaX");
+    }
+
+    #[test]
+    fn base58() {
+        assert_eq!(base58_encode(&[]), "");
+        assert_eq!(base58_encode(&[0, 0]), "11");
+        assert_eq!(base58_encode(b"hello world"), "StV1DL6CwTryKyV");
+    }
+
+    #[test]
+    fn cbor() {
+        let cbor = cbor_metadata("{}", BytecodeHash::None);
+        let [major, minor, patch] = compiler_version_bytes();
+        assert_eq!(
+            cbor,
+            [0xa1, 0x65, b's', b'o', b'l', b'a', b'r', 0x43, major, minor, patch, 0, 11]
+        );
+
+        let cbor = cbor_metadata("{}", BytecodeHash::Ipfs);
+        assert_eq!(&cbor[..8], [0xa2, 0x64, b'i', b'p', b'f', b's', 0x58, 0x22]);
+        assert_eq!(&cbor[8..42], ipfs_hash(b"{}"));
+        assert_eq!(cbor.len(), 42 + 10 + 2);
+        assert_eq!(&cbor[cbor.len() - 2..], [0, 52]);
+
+        let cbor = cbor_metadata("{}", BytecodeHash::Bzzr1);
+        assert_eq!(&cbor[..9], [0xa2, 0x65, b'b', b'z', b'z', b'r', b'1', 0x58, 0x20]);
+    }
+}
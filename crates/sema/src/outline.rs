@@ -0,0 +1,254 @@
+//! Document outlines: the hierarchy of the declarations of a source unit, for editor outlines and
+//! documentation tools.
+
+use serde::Serialize;
+use solar_ast as ast;
+use solar_interface::{source_map::SourceFile, BytePos, Span};
+
+/// The kind of an [`OutlineItem`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum OutlineKind {
+    /// A contract or abstract contract.
+    Contract,
+    /// An interface.
+    Interface,
+    /// A library.
+    Library,
+    /// A function, fallback or receive function.
+    Function,
+    /// A modifier.
+    Modifier,
+    /// A constructor.
+    Constructor,
+    /// An event.
+    Event,
+    /// An error.
+    Error,
+    /// A state or file-level variable.
+    Variable,
+    /// A struct.
+    Struct,
+    /// A struct field.
+    Field,
+    /// An enum.
+    Enum,
+    /// An enum variant.
+    EnumVariant,
+    /// A user-defined value type.
+    UserDefinedValueType,
+}
+
+impl OutlineKind {
+    /// Returns the closest symbol kind of the Language Server Protocol.
+    pub fn lsp_kind(self) -> u8 {
+        match self {
+            Self::Contract => 5,
+            Self::Interface => 11,
+            Self::Library => 2,
+            Self::Function | Self::Modifier => 12,
+            Self::Constructor => 9,
+            Self::Event => 24,
+            Self::Error => 19,
+            Self::Variable => 13,
+            Self::Struct => 23,
+            Self::Field => 8,
+            Self::Enum => 10,
+            Self::EnumVariant => 22,
+            Self::UserDefinedValueType => 26,
+        }
+    }
+}
+
+/// A declaration in a document outline. See [`outline`].
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OutlineItem {
+    /// The name of the declaration, or the kind of function for unnamed functions.
+    pub name: String,
+    /// The kind of the declaration.
+    pub kind: OutlineKind,
+    /// The span of the whole declaration.
+    #[serde(skip)]
+    pub span: Span,
+    /// The span of the name of the declaration, or of the whole declaration if it is unnamed.
+    #[serde(skip)]
+    pub name_span: Span,
+    /// The range of [`span`](Self::span).
+    pub range: Range,
+    /// The range of [`name_span`](Self::name_span).
+    pub selection_range: Range,
+    /// The declarations nested in this one.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub children: Vec<OutlineItem>,
+}
+
+/// A range of source text, between two [`Position`]s.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+pub struct Range {
+    /// The start position.
+    pub start: Position,
+    /// The end position, exclusive.
+    pub end: Position,
+}
+
+/// A position in a source file.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+pub struct Position {
+    /// The 0-based line.
+    pub line: usize,
+    /// The 0-based column, in UTF-16 code units.
+    pub character: usize,
+}
+
+/// Returns the outline of the given source file and its AST: its contracts and file-level
+/// declarations, with the declarations of contracts, the fields of structs and the variants of
+/// enums nested in them.
+///
+/// Pragmas, imports and `using` directives are not included.
+///
+/// Must be called within a session, as identifiers are interned.
+pub fn outline(file: &SourceFile, ast: &ast::SourceUnit<'_>) -> Vec<OutlineItem> {
+    ast.items.iter().filter_map(|item| outline_item(file, item)).collect()
+}
+
+fn outline_item(file: &SourceFile, item: &ast::Item<'_>) -> Option<OutlineItem> {
+    let (kind, children) = match &item.kind {
+        ast::ItemKind::Pragma(_) | ast::ItemKind::Import(_) | ast::ItemKind::Using(_) => {
+            return None;
+        }
+        ast::ItemKind::Contract(contract) => {
+            let kind = match contract.kind {
+                ast::ContractKind::Contract | ast::ContractKind::AbstractContract => {
+                    OutlineKind::Contract
+                }
+                ast::ContractKind::Interface => OutlineKind::Interface,
+                ast::ContractKind::Library => OutlineKind::Library,
+            };
+            (kind, contract.body.iter().filter_map(|item| outline_item(file, item)).collect())
+        }
+        ast::ItemKind::Function(function) => {
+            let kind = match function.kind {
+                ast::FunctionKind::Constructor => OutlineKind::Constructor,
+                ast::FunctionKind::Modifier => OutlineKind::Modifier,
+                ast::FunctionKind::Function
+                | ast::FunctionKind::Fallback
+                | ast::FunctionKind::Receive => OutlineKind::Function,
+            };
+            (kind, Vec::new())
+        }
+        ast::ItemKind::Variable(_) => (OutlineKind::Variable, Vec::new()),
+        ast::ItemKind::Struct(strukt) => {
+            let fields = strukt.fields.iter().filter_map(|field| {
+                let name = field.name?;
+                let kind = OutlineKind::Field;
+                Some(OutlineItem::new(file, name.to_string(), kind, field.span, name.span, vec![]))
+            });
+            (OutlineKind::Struct, fields.collect())
+        }
+        ast::ItemKind::Enum(enum_) => {
+            let variants = enum_.variants.iter().map(|v| {
+                let kind = OutlineKind::EnumVariant;
+                OutlineItem::new(file, v.to_string(), kind, v.span, v.span, vec![])
+            });
+            (OutlineKind::Enum, variants.collect())
+        }
+        ast::ItemKind::Udvt(_) => (OutlineKind::UserDefinedValueType, Vec::new()),
+        ast::ItemKind::Error(_) => (OutlineKind::Error, Vec::new()),
+        ast::ItemKind::Event(_) => (OutlineKind::Event, Vec::new()),
+    };
+    let (name, name_span) = match (item.name(), &item.kind) {
+        (Some(name), _) => (name.to_string(), name.span),
+        (None, ast::ItemKind::Function(function)) => (function.kind.to_str().into(), item.span),
+        (None, _) => return None,
+    };
+    Some(OutlineItem::new(file, name, kind, item.span, name_span, children))
+}
+
+impl OutlineItem {
+    fn new(
+        file: &SourceFile,
+        name: String,
+        kind: OutlineKind,
+        span: Span,
+        name_span: Span,
+        children: Vec<Self>,
+    ) -> Self {
+        let range = |span: Span| Range {
+            start: Position::new(file, span.lo()),
+            end: Position::new(file, span.hi()),
+        };
+        Self {
+            name,
+            kind,
+            span,
+            name_span,
+            range: range(span),
+            selection_range: range(name_span),
+            children,
+        }
+    }
+}
+
+impl Position {
+    fn new(file: &SourceFile, pos: BytePos) -> Self {
+        let (line, character) = file.utf16_position(pos);
+        Self { line, character }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::with_ast;
+
+    #[test]
+    fn outline() {
+        let src = "\
+pragma solidity ^0.8.0;
+interface I { event E(uint); }
+contract C is I {
+    struct S { uint a; }
+    uint x;
+    constructor() {}
+    function f() public {}
+}
+";
+        with_ast(src, |file, ast| {
+            let outline = super::outline(file, ast);
+
+            let names = |items: &[OutlineItem]| {
+                items.iter().map(|item| (item.name.clone(), item.kind)).collect::<Vec<_>>()
+            };
+            let expected = [("I", OutlineKind::Interface), ("C", OutlineKind::Contract)];
+            assert_eq!(names(&outline), expected.map(|(name, kind)| (name.to_string(), kind)));
+            assert_eq!(names(&outline[0].children), [("E".to_string(), OutlineKind::Event)]);
+            let expected = [
+                ("S", OutlineKind::Struct),
+                ("x", OutlineKind::Variable),
+                ("constructor", OutlineKind::Constructor),
+                ("f", OutlineKind::Function),
+            ];
+            let c = &outline[1];
+            assert_eq!(names(&c.children), expected.map(|(name, kind)| (name.to_string(), kind)));
+            assert_eq!(names(&c.children[0].children), [("a".to_string(), OutlineKind::Field)]);
+
+            let json = serde_json::to_value(&c.children[3]).unwrap();
+            assert_eq!(
+                json,
+                serde_json::json!({
+                    "name": "f",
+                    "kind": "function",
+                    "range": {
+                        "start": { "line": 6, "character": 4 },
+                        "end": { "line": 6, "character": 26 },
+                    },
+                    "selectionRange": {
+                        "start": { "line": 6, "character": 13 },
+                        "end": { "line": 6, "character": 14 },
+                    },
+                })
+            );
+        });
+    }
+}
@@ -11,7 +11,12 @@ use solar_interface::{
     Result, Session,
 };
 use solar_parse::{unescape, Lexer, Parser};
-use std::{borrow::Cow, fmt, path::Path, sync::Arc};
+use std::{
+    borrow::Cow,
+    fmt,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
 use thread_local::ThreadLocal;
 
 pub struct ParsingContext<'sess> {
@@ -19,6 +24,11 @@ pub struct ParsingContext<'sess> {
     pub sess: &'sess Session,
     /// The file resolver.
     pub file_resolver: FileResolver<'sess>,
+    /// The code generation backend used for the `bin`, `bin-runtime`, `srcmap`, `srcmap-runtime`,
+    /// `ir`, `asm` and `gas-estimates` outputs, if any.
+    pub codegen: Option<crate::Codegen>,
+    /// The lint passes to run, if any.
+    pub linter: Option<crate::Linter>,
     /// The loaded sources. Consumed once `parse` is called.
     /// The `'static` lifetime is a lie, as nothing borrowed is ever stored in this field.
     pub(crate) sources: ParsedSources<'static>,
@@ -30,6 +40,8 @@ impl<'sess> ParsingContext<'sess> {
         Self {
             sess,
             file_resolver: FileResolver::new(sess.source_map()),
+            codegen: None,
+            linter: None,
             sources: ParsedSources::new(),
         }
     }
@@ -49,6 +61,21 @@ impl<'sess> ParsingContext<'sess> {
         Ok(())
     }
 
+    /// Loads `stdin` into the context as the file at `path`.
+    ///
+    /// The path is displayed in diagnostics and relative imports are resolved from it. It does not
+    /// need to exist.
+    #[instrument(level = "debug", skip_all)]
+    pub fn load_stdin_as(&mut self, path: &Path) -> Result<()> {
+        let path = self.input_path(path);
+        let file = self
+            .file_resolver
+            .load_stdin_as(&path)
+            .map_err(|e| self.dcx().err(e.to_string()).emit())?;
+        self.add_file(file);
+        Ok(())
+    }
+
     /// Loads files into the context.
     #[instrument(level = "debug", skip_all)]
     pub fn load_files(&mut self, paths: impl IntoIterator<Item = impl AsRef<Path>>) -> Result<()> {
@@ -61,8 +88,19 @@ impl<'sess> ParsingContext<'sess> {
     /// Loads a file into the context.
     #[instrument(level = "debug", skip_all)]
     pub fn load_file(&mut self, path: &Path) -> Result<()> {
-        // Paths must be canonicalized before passing to the resolver.
-        let path = match solar_interface::canonicalize(path) {
+        let path = self.input_path(path);
+        let file = self
+            .file_resolver
+            .resolve_file(&path, None)
+            .map_err(|e| self.dcx().err(e.to_string()).emit())?;
+        self.add_file(file);
+        Ok(())
+    }
+
+    /// Canonicalizes the path of an input file. Paths must be canonicalized before passing to the
+    /// resolver.
+    fn input_path(&self, path: &Path) -> PathBuf {
+        match self.sess.source_map().file_loader().canonicalize_path(path) {
             Ok(path) => {
                 // Base paths from arguments to the current directory for shorter diagnostics
                 // output.
@@ -72,13 +110,7 @@ impl<'sess> ParsingContext<'sess> {
                 }
             }
             Err(_) => path.to_path_buf(),
-        };
-        let file = self
-            .file_resolver
-            .resolve_file(&path, None)
-            .map_err(|e| self.dcx().err(e.to_string()).emit())?;
-        self.add_file(file);
-        Ok(())
+        }
     }
 
     /// Adds a preloaded file to the resolver.
@@ -90,6 +122,22 @@ impl<'sess> ParsingContext<'sess> {
         crate::parse_and_resolve(self)
     }
 
+    /// See [`crate::parse_and_resolve_with`].
+    pub fn parse_and_resolve_with<R>(
+        self,
+        f: impl FnOnce(crate::ty::Gcx<'_>) -> R,
+    ) -> Result<Option<R>> {
+        crate::parse_and_resolve_with(self, f)
+    }
+
+    /// See [`crate::parse_and_lower_with`].
+    pub fn parse_and_lower_with<R>(
+        self,
+        f: impl FnOnce(crate::ty::Gcx<'_>) -> R,
+    ) -> Result<Option<R>> {
+        crate::parse_and_lower_with(self, f)
+    }
+
     /// Parses all the loaded sources, recursing into imports.
     ///
     /// Sources are not guaranteed to be in any particular order, as they may be parsed in parallel.
@@ -150,19 +198,24 @@ impl<'sess> ParsingContext<'sess> {
             }
             trace!(start, "parsing {} files", to_parse.len());
             start += to_parse.len();
-            let imports = to_parse
+            // Diagnostics are captured and emitted in source order, as if parsed sequentially.
+            let results = to_parse
                 .par_iter_mut()
-                .enumerate()
-                .flat_map_iter(|(i, source)| {
-                    debug_assert!(source.ast.is_none(), "source already parsed");
-                    source.ast = self.parse_one(&source.file, arenas.get_or_default());
-                    resolve_imports!(self, &source.file, source.ast.as_ref())
-                        .map(move |import| (i, import))
+                .map(|source| {
+                    self.dcx().capture(|| {
+                        debug_assert!(source.ast.is_none(), "source already parsed");
+                        source.ast = self.parse_one(&source.file, arenas.get_or_default());
+                        resolve_imports!(self, &source.file, source.ast.as_ref())
+                            .collect::<Vec<_>>()
+                    })
                 })
-                .collect_vec_list();
+                .collect::<Vec<_>>();
             let n_sources = sources.len();
-            for (i, (import_item_id, import)) in imports.into_iter().flatten() {
-                sources.add_import(SourceId::from_usize(base + i), import_item_id, import);
+            for (i, (imports, diagnostics)) in results.into_iter().enumerate() {
+                self.dcx().emit_captured(diagnostics);
+                for (import_item_id, import) in imports {
+                    sources.add_import(SourceId::from_usize(base + i), import_item_id, import);
+                }
             }
             let new_files = sources.len() - n_sources;
             if new_files > 0 {
@@ -178,15 +231,19 @@ impl<'sess> ParsingContext<'sess> {
         file: &SourceFile,
         arena: &'ast ast::Arena,
     ) -> Option<ast::SourceUnit<'ast>> {
-        let lexer = Lexer::from_source_file(self.sess, file);
-        let mut parser = Parser::from_lexer(arena, lexer);
-        let r = if self.sess.language.is_yul() {
-            let _file = parser.parse_yul_file_object().map_err(|e| e.emit());
-            None
-        } else {
-            parser.parse_file().map_err(|e| e.emit()).ok()
-        };
+        let allocated = arena.allocated_bytes();
+        let r = self.sess.time_file("parse", &file.name, || {
+            let lexer = Lexer::from_source_file(self.sess, file);
+            let mut parser = Parser::from_lexer(arena, lexer);
+            if self.sess.language.is_yul() {
+                let _file = parser.parse_yul_file_object().map_err(|e| e.emit());
+                None
+            } else {
+                parser.parse_file().map_err(|e| e.emit()).ok()
+            }
+        });
         trace!(allocated = arena.allocated_bytes(), used = arena.used_bytes(), "AST arena stats");
+        self.sess.record_ast_arena_bytes(arena.allocated_bytes() - allocated);
         r
     }
 }
@@ -205,7 +262,7 @@ macro_rules! resolve_imports {
             FileName::Real(path) => Some(path.to_path_buf()),
             // Use current directory for stdin.
             FileName::Stdin => Some(Path::new("").to_path_buf()),
-            FileName::Custom(_) => None,
+            FileName::Custom(_) | FileName::Generated(_) => None,
         };
         let items = ast.map(|ast| &ast.items[..]).unwrap_or_default();
         items
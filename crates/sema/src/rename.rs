@@ -0,0 +1,271 @@
+//! Renaming of declarations, for refactoring tools.
+
+use crate::{ast_lowering::resolve::Declarations, hir, ty::Gcx};
+use solar_ast::NatSpecKind;
+use solar_interface::{Span, Symbol};
+use solar_parse::{lexer::token::RawTokenKind, Cursor};
+use std::fmt;
+
+/// A replacement of the source text in a span.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TextEdit {
+    /// The span of the text to replace.
+    pub span: Span,
+    /// The new text.
+    pub new_text: String,
+}
+
+/// An error returned by [`rename`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RenameError {
+    /// The new name is not an identifier, or is a keyword.
+    InvalidName,
+    /// The item cannot be renamed. Contains a description of the kind of item.
+    Unsupported(&'static str),
+    /// The new name is already declared in a scope of the declaration or of one of the names
+    /// referring to it. Contains the span of the existing declaration.
+    Conflict(Span),
+}
+
+impl fmt::Display for RenameError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidName => f.write_str("the new name is not a valid identifier"),
+            Self::Unsupported(kind) => write!(f, "{kind} cannot be renamed"),
+            Self::Conflict(_) => f.write_str("the new name is already declared"),
+        }
+    }
+}
+
+impl std::error::Error for RenameError {}
+
+/// Returns the edits that rename the given item and the names referring to it, in all the
+/// sources, sorted by position.
+///
+/// The names of imported items are renamed, like `X` in `import {X as Y} from "a.sol"`, but not
+/// their aliases nor the names referring to them. Renaming a contract also renames the
+/// `@inheritdoc` tags referring to it.
+///
+/// Fails if the new name is already declared in the scope of the item, in the scope of any name
+/// referring to it, or in a contract inheriting it, as the renamed item would then conflict with or
+/// be shadowed by the existing declaration.
+///
+/// Members of values are not resolved, so struct fields, getters and overloaded or overridden
+/// functions and events, which may be referred to through such members or with ambiguous names,
+/// cannot be renamed. Other members, like `f` in `this.f()`, are not renamed.
+///
+/// Must be called within a session, as names are interned.
+pub fn rename(gcx: Gcx<'_>, id: hir::ItemId, new_name: &str) -> Result<Vec<TextEdit>, RenameError> {
+    let item = gcx.hir.item(id);
+    let Some(name) = item.name() else { return Err(RenameError::Unsupported("unnamed items")) };
+    match item {
+        hir::Item::Function(f) if f.is_getter() => return Err(RenameError::Unsupported("getters")),
+        hir::Item::Variable(v) if v.kind == hir::VarKind::Struct => {
+            return Err(RenameError::Unsupported("struct fields"));
+        }
+        _ => {}
+    }
+    if is_overloaded(gcx, item, name.name) {
+        return Err(RenameError::Unsupported("overloaded or overridden functions and events"));
+    }
+    if !is_identifier(new_name) {
+        return Err(RenameError::InvalidName);
+    }
+
+    // Names referring to the item through an alias have a different name.
+    let source_map = gcx.sess.source_map();
+    let mut spans = vec![name.span];
+    spans.extend(gcx.references(hir::Res::Item(id)).into_iter().filter(|&span| {
+        source_map.span_to_snippet(span).is_ok_and(|snippet| snippet == name.as_str())
+    }));
+
+    let new = Symbol::intern(new_name);
+    if new != name.name {
+        check_conflicts(gcx, item, new, &spans)?;
+    }
+
+    if let hir::ItemId::Contract(contract) = id {
+        spans.extend(inheritdoc_targets(gcx, contract, name.name));
+    }
+    spans.sort_by_key(|span| span.lo());
+    spans.dedup();
+    Ok(spans.into_iter().map(|span| TextEdit { span, new_text: new_name.to_string() }).collect())
+}
+
+/// Returns `true` if the name is a single identifier that is not a keyword.
+fn is_identifier(name: &str) -> bool {
+    let mut tokens = Cursor::new(name);
+    let is_ident = matches!(
+        tokens.next(),
+        Some(token) if token.kind == RawTokenKind::Ident && token.len as usize == name.len()
+    );
+    is_ident && !Symbol::intern(name).is_reserved(false)
+}
+
+/// Returns `true` if the function or event shares its name with other functions or events in the
+/// scope of its contract or source, or of the contracts inheriting it.
+fn is_overloaded(gcx: Gcx<'_>, item: hir::Item<'_, '_>, name: Symbol) -> bool {
+    if !matches!(item, hir::Item::Function(_) | hir::Item::Event(_)) {
+        return false;
+    }
+    let overloads = |scope: &Declarations| {
+        let decls = scope.declarations.get(&name).map_or(&[][..], |decls| &decls[..]);
+        let is_overload =
+            |res| matches!(res, hir::Res::Item(hir::ItemId::Function(_) | hir::ItemId::Event(_)));
+        decls.iter().filter(|decl| is_overload(decl.res)).count()
+    };
+    let resolver = &gcx.symbol_resolver;
+    match item.contract() {
+        Some(contract) => gcx
+            .hir
+            .derived_contracts(contract)
+            .any(|derived| overloads(&resolver.contract_scopes[derived]) > 1),
+        None => resolver.source_scopes.iter().any(|scope| overloads(scope) > 1),
+    }
+}
+
+/// Checks that the new name is not declared in any scope that the renamed item is visible in.
+fn check_conflicts(
+    gcx: Gcx<'_>,
+    item: hir::Item<'_, '_>,
+    new: Symbol,
+    spans: &[Span],
+) -> Result<(), RenameError> {
+    let resolver = &gcx.symbol_resolver;
+    let check = |scope: &Declarations| match scope.declarations.get(&new) {
+        Some(decls) => Err(RenameError::Conflict(decls[0].span)),
+        None => Ok(()),
+    };
+
+    // The members of a contract are inherited.
+    if let Some(contract) = item.contract() {
+        for derived in gcx.hir.derived_contracts(contract) {
+            check(&resolver.contract_scopes[derived])?;
+        }
+    }
+
+    for &span in spans {
+        let contains = |item_span: Span| item_span.contains(span);
+        let function =
+            gcx.hir.function_ids().find(|&function| contains(gcx.hir.function(function).span));
+        if let Some(function) = function {
+            let local = gcx.hir.variables().find(|var| {
+                var.function == Some(function) && var.name.is_some_and(|name| name.name == new)
+            });
+            if let Some(local) = local {
+                return Err(RenameError::Conflict(local.name.unwrap().span));
+            }
+        }
+        let contract = gcx.hir.contract_ids().find(|&c| contains(gcx.hir.contract(c).span));
+        if let Some(contract) = contract {
+            check(&resolver.contract_scopes[contract])?;
+        }
+        let source = gcx.hir.source_ids().find(|&s| gcx.hir.source(s).file.contains(span.lo()));
+        if let Some(source) = source {
+            check(&resolver.source_scopes[source])?;
+        }
+    }
+    Ok(())
+}
+
+/// Returns the spans of the contract names in the `@inheritdoc` tags referring to the given
+/// contract.
+fn inheritdoc_targets(gcx: Gcx<'_>, contract: hir::ContractId, name: Symbol) -> Vec<Span> {
+    let functions = gcx.hir.functions().map(|f| (f.contract, f.docs));
+    let variables = gcx.hir.variables().map(|v| (v.contract, v.docs));
+    let mut spans = Vec::new();
+    for (derived, docs) in functions.chain(variables) {
+        let Some(derived) = derived else { continue };
+        let bases = gcx.hir.contract(derived).linearized_bases;
+        if !bases.iter().skip(1).any(|&base| base == contract) {
+            continue;
+        }
+        for doc in docs {
+            if doc.kind != (NatSpecKind::Inheritdoc { contract: name }) {
+                continue;
+            }
+            let Ok(text) = gcx.sess.source_map().span_to_snippet(doc.span) else { continue };
+            for (start, _) in text.match_indices("@inheritdoc") {
+                let rest = &text[start + "@inheritdoc".len()..];
+                let target = rest.trim_start();
+                let end = target.find(|c: char| c.is_whitespace()).unwrap_or(target.len());
+                if &target[..end] != name.as_str() {
+                    continue;
+                }
+                let lo = doc.span.lo() + (text.len() - target.len()) as u32;
+                spans.push(Span::new(lo, lo + end as u32));
+            }
+        }
+    }
+    spans
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{find, with_gcx_files};
+
+    const A: &str = "\
+contract A {
+    function f() public virtual {}
+}
+";
+
+    const B: &str = "\
+import {A} from \"a.sol\";
+import {A as X} from \"a.sol\";
+contract B is A {
+    /// @inheritdoc A
+    function f() public override {}
+    function g() public { X x; A y; }
+}
+";
+
+    /// Renames the item whose name is at the `n`th occurrence of `old` in `B`, and returns the
+    /// edited sources.
+    fn rename_in_b(old: &str, n: usize, new: &str) -> Result<[String; 2], RenameError> {
+        with_gcx_files(&[("a.sol", A), ("b.sol", B)], |gcx, files| {
+            let pos = files[1].start_pos + find(B, old, n) as u32;
+            let hir::Res::Item(id) = gcx.res_at(pos).unwrap() else { panic!() };
+            let edits = rename(gcx, id, new)?;
+            Ok([&files[0], &files[1]].map(|file| {
+                let mut src = file.src.to_string();
+                for edit in edits.iter().rev().filter(|edit| file.contains(edit.span.lo())) {
+                    let lo = file.relative_position(edit.span.lo()).to_usize();
+                    let hi = file.relative_position(edit.span.hi()).to_usize();
+                    src.replace_range(lo..hi, &edit.new_text);
+                }
+                src
+            }))
+        })
+    }
+
+    #[test]
+    fn rename_contract() {
+        let [a, b] = rename_in_b("A", 0, "Z").unwrap();
+        assert_eq!(a, A.replace("A", "Z"));
+        assert_eq!(
+            b,
+            "\
+import {Z} from \"a.sol\";
+import {Z as X} from \"a.sol\";
+contract B is Z {
+    /// @inheritdoc Z
+    function f() public override {}
+    function g() public { X x; Z y; }
+}
+"
+        );
+    }
+
+    #[test]
+    fn rename_errors() {
+        assert_eq!(rename_in_b("y", 0, "1y"), Err(RenameError::InvalidName));
+        assert_eq!(rename_in_b("y", 0, "uint8"), Err(RenameError::InvalidName));
+        assert!(matches!(rename_in_b("y", 0, "x"), Err(RenameError::Conflict(_))));
+        assert!(matches!(rename_in_b("A", 2, "B"), Err(RenameError::Conflict(_))));
+        assert!(matches!(rename_in_b("g", 0, "f"), Err(RenameError::Conflict(_))));
+        assert!(matches!(rename_in_b("f", 0, "h"), Err(RenameError::Unsupported(_))));
+        assert_eq!(rename_in_b("y", 0, "z").unwrap()[1], B.replace("A y", "A z"));
+    }
+}
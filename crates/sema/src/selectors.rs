@@ -0,0 +1,66 @@
+//! Function selectors, error selectors, and event topics.
+//!
+//! Reference: <https://docs.soliditylang.org/en/latest/abi-spec.html#function-selector>
+
+use crate::{hir, ty::Gcx};
+use alloy_primitives::{keccak256, Selector, B256};
+use std::collections::BTreeMap;
+
+/// The canonical signatures and selectors of the external interface of a contract.
+///
+/// Return type of [`contract_selectors`].
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ContractSelectors {
+    /// Function signatures mapped to their 4-byte selectors.
+    pub functions: BTreeMap<String, Selector>,
+    /// Error signatures mapped to their 4-byte selectors.
+    pub errors: BTreeMap<String, Selector>,
+    /// Event signatures mapped to their 32-byte topics.
+    ///
+    /// Anonymous events are not included, as they don't have a topic.
+    pub events: BTreeMap<String, B256>,
+}
+
+/// Returns the signatures and selectors of the external functions, errors, and events of the
+/// given contract, including inherited ones.
+pub fn contract_selectors(gcx: Gcx<'_>, id: hir::ContractId) -> ContractSelectors {
+    let mut selectors = ContractSelectors::default();
+    for f in gcx.interface_functions(id) {
+        selectors.functions.insert(gcx.item_signature(f.id.into()).to_string(), f.selector);
+    }
+    for item in gcx.hir.contract_item_ids(id) {
+        match item {
+            hir::ItemId::Error(error_id) => {
+                let signature = gcx.item_signature(item).to_string();
+                selectors.errors.insert(signature, gcx.function_selector(error_id));
+            }
+            hir::ItemId::Event(event_id) if !gcx.hir.event(event_id).anonymous => {
+                let signature = gcx.item_signature(item).to_string();
+                selectors.events.insert(signature, gcx.event_selector(event_id));
+            }
+            _ => {}
+        }
+    }
+    selectors
+}
+
+/// Computes the 4-byte selector of the given canonical function or error signature.
+///
+/// The signature is not validated.
+///
+/// # Examples
+///
+/// ```
+/// let selector = solar_sema::selectors::selector("transfer(address,uint256)");
+/// assert_eq!(selector.0, [0xa9, 0x05, 0x9c, 0xbb]);
+/// ```
+pub fn selector(signature: &str) -> Selector {
+    keccak256(signature)[..4].try_into().unwrap()
+}
+
+/// Computes the 32-byte topic of the given canonical event signature.
+///
+/// The signature is not validated.
+pub fn event_topic(signature: &str) -> B256 {
+    keccak256(signature)
+}
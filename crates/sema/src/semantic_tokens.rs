@@ -0,0 +1,262 @@
+//! Semantic classification of source code, for syntax highlighting in editors.
+
+use solar_ast::{self as ast, visit::Visit};
+use solar_data_structures::{map::FxHashMap, Never};
+use solar_interface::{
+    source_map::{RelativeBytePos, SourceFile},
+    Ident, Span, Symbol,
+};
+use solar_parse::{lexer::token::RawTokenKind, Cursor};
+use std::ops::ControlFlow;
+
+/// The kind of a [`SemanticToken`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum SemanticTokenKind {
+    /// A keyword, such as `contract` or `returns`.
+    Keyword,
+    /// An elementary type name, or the name of a contract, struct, enum or user-defined value type.
+    Type,
+    /// The name of a state variable.
+    StateVariable,
+    /// The name of a function or modifier.
+    Function,
+    /// The name of an event.
+    Event,
+}
+
+impl SemanticTokenKind {
+    /// All the kinds, in declaration order.
+    pub const ALL: [Self; 5] =
+        [Self::Keyword, Self::Type, Self::StateVariable, Self::Function, Self::Event];
+
+    /// Returns the name of the closest standard token type of the Language Server Protocol.
+    pub fn lsp_name(self) -> &'static str {
+        match self {
+            Self::Keyword => "keyword",
+            Self::Type => "type",
+            Self::StateVariable => "property",
+            Self::Function => "function",
+            Self::Event => "event",
+        }
+    }
+}
+
+/// A classified token. See [`semantic_tokens`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SemanticToken {
+    /// The span of the token.
+    pub span: Span,
+    /// The 0-based line of the token.
+    pub line: usize,
+    /// The 0-based column of the start of the token, in UTF-16 code units.
+    pub start: usize,
+    /// The length of the token, in UTF-16 code units.
+    pub len: usize,
+    /// The kind of the token.
+    pub kind: SemanticTokenKind,
+}
+
+/// Returns the semantic tokens of the given source file and its AST, sorted by position.
+///
+/// Keywords and elementary type names are found by lexing the source. Other identifiers are
+/// classified using the AST, without resolving names: declarations by their kind, and references
+/// by the kind of the declaration with the same name in the source unit. Identifiers that do not
+/// name such a declaration, like local variables and imported items, are not classified.
+///
+/// Must be called within a session, as identifiers are interned.
+pub fn semantic_tokens(file: &SourceFile, ast: &ast::SourceUnit<'_>) -> Vec<SemanticToken> {
+    let mut tokens = Vec::new();
+
+    let mut declarations = Declarations { names: FxHashMap::default(), tokens: &mut tokens };
+    let _ = declarations.visit_source_unit(ast);
+    let names = declarations.names;
+    let _ = References { names: &names, tokens: &mut tokens }.visit_source_unit(ast);
+
+    let mut pos = 0;
+    for token in Cursor::new(&file.src) {
+        let len = token.len as usize;
+        if token.kind == RawTokenKind::Ident {
+            let name = Symbol::intern(&file.src[pos..pos + len]);
+            let kind = if name.is_elementary_type() {
+                Some(SemanticTokenKind::Type)
+            } else if name.is_used_keyword() {
+                Some(SemanticTokenKind::Keyword)
+            } else {
+                None
+            };
+            if let Some(kind) = kind {
+                let lo = file.absolute_position(RelativeBytePos::from_usize(pos));
+                let hi = file.absolute_position(RelativeBytePos::from_usize(pos + len));
+                tokens.push((Span::new(lo, hi), kind));
+            }
+        }
+        pos += len;
+    }
+
+    // Declarations come first, so they take precedence over references to the same name.
+    tokens.sort_by_key(|&(span, _)| span.lo());
+    tokens.dedup_by_key(|&mut (span, _)| span);
+    tokens
+        .into_iter()
+        .filter(|&(span, _)| file.contains(span.lo()) && file.contains(span.hi()))
+        .map(|(span, kind)| {
+            let (line, start) = file.utf16_position(span.lo());
+            let len = file
+                .utf16_len(file.relative_position(span.lo()), file.relative_position(span.hi()));
+            SemanticToken { span, line, start, len, kind }
+        })
+        .collect()
+}
+
+/// Classifies the names of declarations, and records their kind by name.
+struct Declarations<'a> {
+    names: FxHashMap<Symbol, SemanticTokenKind>,
+    tokens: &'a mut Vec<(Span, SemanticTokenKind)>,
+}
+
+impl Declarations<'_> {
+    fn declare(&mut self, name: Ident, kind: SemanticTokenKind) {
+        self.names.entry(name.name).or_insert(kind);
+        self.tokens.push((name.span, kind));
+    }
+}
+
+impl<'ast> Visit<'ast> for Declarations<'_> {
+    type BreakValue = Never;
+
+    fn visit_item_contract(
+        &mut self,
+        contract: &'ast ast::ItemContract<'ast>,
+    ) -> ControlFlow<Self::BreakValue> {
+        self.declare(contract.name, SemanticTokenKind::Type);
+        for item in contract.body.iter() {
+            if let ast::ItemKind::Variable(var) = &item.kind {
+                if let Some(name) = var.name {
+                    self.declare(name, SemanticTokenKind::StateVariable);
+                }
+            } else {
+                self.visit_item(item)?;
+            }
+        }
+        ControlFlow::Continue(())
+    }
+
+    fn visit_item_function(
+        &mut self,
+        function: &'ast ast::ItemFunction<'ast>,
+    ) -> ControlFlow<Self::BreakValue> {
+        if let Some(name) = function.header.name {
+            self.declare(name, SemanticTokenKind::Function);
+        }
+        ControlFlow::Continue(())
+    }
+
+    fn visit_item_struct(
+        &mut self,
+        strukt: &'ast ast::ItemStruct<'ast>,
+    ) -> ControlFlow<Self::BreakValue> {
+        self.declare(strukt.name, SemanticTokenKind::Type);
+        ControlFlow::Continue(())
+    }
+
+    fn visit_item_enum(
+        &mut self,
+        enum_: &'ast ast::ItemEnum<'ast>,
+    ) -> ControlFlow<Self::BreakValue> {
+        self.declare(enum_.name, SemanticTokenKind::Type);
+        ControlFlow::Continue(())
+    }
+
+    fn visit_item_udvt(
+        &mut self,
+        udvt: &'ast ast::ItemUdvt<'ast>,
+    ) -> ControlFlow<Self::BreakValue> {
+        self.declare(udvt.name, SemanticTokenKind::Type);
+        ControlFlow::Continue(())
+    }
+
+    fn visit_item_event(
+        &mut self,
+        event: &'ast ast::ItemEvent<'ast>,
+    ) -> ControlFlow<Self::BreakValue> {
+        self.declare(event.name, SemanticTokenKind::Event);
+        ControlFlow::Continue(())
+    }
+}
+
+/// Classifies identifiers by the kind of the declaration with the same name.
+struct References<'a> {
+    names: &'a FxHashMap<Symbol, SemanticTokenKind>,
+    tokens: &'a mut Vec<(Span, SemanticTokenKind)>,
+}
+
+impl<'ast> Visit<'ast> for References<'_> {
+    type BreakValue = Never;
+
+    fn visit_variable_definition(
+        &mut self,
+        var: &'ast ast::VariableDefinition<'ast>,
+    ) -> ControlFlow<Self::BreakValue> {
+        // The names of local variables and parameters may shadow other declarations.
+        self.visit_ty(&var.ty)?;
+        if let Some(initializer) = &var.initializer {
+            self.visit_expr(initializer)?;
+        }
+        ControlFlow::Continue(())
+    }
+
+    fn visit_ident(&mut self, ident: &'ast Ident) -> ControlFlow<Self::BreakValue> {
+        if let Some(&kind) = self.names.get(&ident.name) {
+            self.tokens.push((ident.span, kind));
+        }
+        ControlFlow::Continue(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::with_ast;
+    use SemanticTokenKind::*;
+
+    #[test]
+    fn classify() {
+        let src = "\
+contract C {
+    uint x;
+    event E(uint);
+    function f(uint y) public { emit E(x + y); f(y); }
+}
+";
+        with_ast(src, |file, ast| {
+            let tokens = semantic_tokens(file, ast)
+                .into_iter()
+                .map(|token| {
+                    let lo = file.relative_position(token.span.lo()).to_usize();
+                    let hi = file.relative_position(token.span.hi()).to_usize();
+                    (token.line, token.start, src[lo..hi].to_string(), token.kind)
+                })
+                .collect::<Vec<_>>();
+            let expected = [
+                (0, 0, "contract", Keyword),
+                (0, 9, "C", Type),
+                (1, 4, "uint", Type),
+                (1, 9, "x", StateVariable),
+                (2, 4, "event", Keyword),
+                (2, 10, "E", Event),
+                (2, 12, "uint", Type),
+                (3, 4, "function", Keyword),
+                (3, 13, "f", Function),
+                (3, 15, "uint", Type),
+                (3, 23, "public", Keyword),
+                (3, 32, "emit", Keyword),
+                (3, 37, "E", Event),
+                (3, 39, "x", StateVariable),
+                (3, 47, "f", Function),
+            ];
+            let expected =
+                expected.map(|(line, start, text, kind)| (line, start, text.to_string(), kind));
+            assert_eq!(tokens, expected);
+        });
+    }
+}
@@ -0,0 +1,90 @@
+//! Helpers shared by unit tests.
+
+use crate::{ty::Gcx, ParsingContext};
+use solar_ast as ast;
+use solar_interface::{
+    source_map::{FileName, SourceFile},
+    BytePos, Session,
+};
+use solar_parse::Parser;
+use std::sync::Arc;
+
+/// The name of the file created by the `with_*` helpers that take a single source.
+pub(crate) const FILE_NAME: &str = "test.sol";
+
+/// Creates a session with a test emitter, adds the given `(name, source)` pairs to a parsing
+/// context, then calls `f` with it and the source files, in the same order.
+pub(crate) fn with_pcx<R: Send>(
+    sources: &[(&str, &str)],
+    f: impl FnOnce(ParsingContext<'_>, &[Arc<SourceFile>]) -> R + Send,
+) -> R {
+    let sess = Session::builder().with_test_emitter().record_references(true).build();
+    sess.enter(|| {
+        let mut pcx = ParsingContext::new(&sess);
+        let files = sources
+            .iter()
+            .map(|&(name, src)| {
+                let name = FileName::Real(name.into());
+                sess.source_map().new_source_file(name, || Ok(src.into())).unwrap()
+            })
+            .collect::<Vec<_>>();
+        for file in &files {
+            pcx.add_file(file.clone());
+        }
+        f(pcx, &files)
+    })
+}
+
+/// Parses and resolves the given `(name, source)` pairs, then calls `f` with the global context and
+/// the source files, in the same order.
+///
+/// Panics if the analysis fails.
+pub(crate) fn with_gcx_files<R: Send>(
+    sources: &[(&str, &str)],
+    f: impl FnOnce(Gcx<'_>, &[Arc<SourceFile>]) -> R + Send,
+) -> R {
+    with_pcx(sources, |pcx, files| pcx.parse_and_resolve_with(|gcx| f(gcx, files)))
+        .unwrap()
+        .unwrap()
+}
+
+/// Parses and resolves `src`, then calls `f` with the global context and a function that returns
+/// the position of the `n`th occurrence of a string in `src`.
+///
+/// Panics if the analysis fails.
+pub(crate) fn with_gcx<R: Send>(
+    src: &str,
+    f: impl FnOnce(Gcx<'_>, &dyn Fn(&str, usize) -> BytePos) -> R + Send,
+) -> R {
+    with_gcx_files(&[(FILE_NAME, src)], |gcx, files| {
+        let pos = |s: &str, n: usize| files[0].start_pos + find(src, s, n) as u32;
+        f(gcx, &pos)
+    })
+}
+
+/// Parses `src` on its own, without its imports, then calls `f` with its source file and AST.
+///
+/// Panics if parsing fails.
+pub(crate) fn with_ast<R: Send>(
+    src: &str,
+    f: impl FnOnce(&SourceFile, &ast::SourceUnit<'_>) -> R + Send,
+) -> R {
+    let sess = Session::builder().with_test_emitter().build();
+    sess.enter(|| {
+        let name = FileName::Real(FILE_NAME.into());
+        let file = sess.source_map().new_source_file(name, || Ok(src.into())).unwrap();
+        let arena = ast::Arena::new();
+        let mut parser = Parser::from_source_file(&sess, &arena, &file);
+        let ast = parser.parse_file().map_err(|e| e.emit()).unwrap();
+        f(&file, &ast)
+    })
+}
+
+/// Returns the byte offset of the `n`th occurrence of `s` in `src`.
+#[track_caller]
+pub(crate) fn find(src: &str, s: &str, n: usize) -> usize {
+    match src.match_indices(s).nth(n) {
+        Some((i, _)) => i,
+        None => panic!("{s:?} does not occur {} times", n + 1),
+    }
+}
@@ -2,6 +2,7 @@ use super::{Gcx, Ty, TyKind};
 use crate::hir;
 use alloy_json_abi as json;
 use solar_ast::ElementaryType;
+use solar_data_structures::map::FxHashSet;
 use std::{fmt, ops::ControlFlow};
 
 impl<'gcx> Gcx<'gcx> {
@@ -43,10 +44,17 @@ impl<'gcx> Gcx<'gcx> {
         }
         // TODO: Does not include referenced items.
         // See solc `interfaceEvents` and `interfaceErrors`.
+        // Events and errors with the same signature can be inherited multiple times.
+        let mut events_seen = FxHashSet::default();
+        let mut errors_seen = FxHashSet::default();
         for item in self.hir.contract_item_ids(id) {
             match item {
-                hir::ItemId::Event(id) => items.push(self.event_abi(id).into()),
-                hir::ItemId::Error(id) => items.push(self.error_abi(id).into()),
+                hir::ItemId::Event(id) if events_seen.insert(self.item_signature(item)) => {
+                    items.push(self.event_abi(id).into())
+                }
+                hir::ItemId::Error(id) if errors_seen.insert(self.item_signature(item)) => {
+                    items.push(self.error_abi(id).into())
+                }
                 _ => {}
             }
         }
@@ -61,6 +69,13 @@ impl<'gcx> Gcx<'gcx> {
         items
     }
 
+    /// Returns the ABI of the given contract as a [`JsonAbi`](json::JsonAbi).
+    ///
+    /// See [`contract_abi`](Self::contract_abi) for more details.
+    pub fn contract_json_abi(self, id: hir::ContractId) -> json::JsonAbi {
+        self.contract_abi(id).into_iter().collect()
+    }
+
     fn function_abi(self, id: hir::FunctionId) -> json::Function {
         let f = self.hir.function(id);
         json::Function {
@@ -224,6 +239,7 @@ impl<'gcx, W: fmt::Write> TyAbiPrinter<'gcx, W> {
             | TyKind::BuiltinModule(_)
             | TyKind::Type(_)
             | TyKind::Meta(_)
+            | TyKind::Infer
             | TyKind::Err(_) => panic!("printing unsupported type as ABI: {ty:?}"),
         }
     }
@@ -246,26 +262,26 @@ impl<'gcx, W: fmt::Write> TyAbiPrinter<'gcx, W> {
 /// This is mainly used in the `internalType` field of the ABI.
 ///
 /// Example: https://github.com/ethereum/solidity/blob/9d7cc42bc1c12bb43e9dccf8c6c36833fdfcbbca/libsolidity/ast/Types.cpp#L2352-L2358
-struct TySolcPrinter<'gcx, W> {
+pub(crate) struct TySolcPrinter<'gcx, W> {
     gcx: Gcx<'gcx>,
     buf: W,
     data_locations: bool,
 }
 
 impl<'gcx, W: fmt::Write> TySolcPrinter<'gcx, W> {
-    fn new(gcx: Gcx<'gcx>, buf: W) -> Self {
+    pub(crate) fn new(gcx: Gcx<'gcx>, buf: W) -> Self {
         Self { gcx, buf, data_locations: false }
     }
 
     /// Whether to print data locations for reference types.
     ///
     /// Default: `false`.
-    fn data_locations(mut self, yes: bool) -> Self {
+    pub(crate) fn data_locations(mut self, yes: bool) -> Self {
         self.data_locations = yes;
         self
     }
 
-    fn print(&mut self, ty: Ty<'gcx>) -> fmt::Result {
+    pub(crate) fn print(&mut self, ty: Ty<'gcx>) -> fmt::Result {
         match ty.kind {
             TyKind::Elementary(ty) => {
                 ty.write_abi_str(&mut self.buf)?;
@@ -300,11 +316,18 @@ impl<'gcx, W: fmt::Write> TySolcPrinter<'gcx, W> {
                 self.print(ty)?;
                 write!(self.buf, "[{len}]")
             }
+            TyKind::Mapping(key, value) => {
+                self.buf.write_str("mapping(")?;
+                self.print(key)?;
+                self.buf.write_str(" => ")?;
+                self.print(value)?;
+                self.buf.write_str(")")
+            }
+            TyKind::Infer => self.buf.write_str("var"),
 
             TyKind::StringLiteral(..)
             | TyKind::IntLiteral(_)
             | TyKind::Tuple(_)
-            | TyKind::Mapping(..)
             | TyKind::Error(..)
             | TyKind::Event(..)
             | TyKind::Module(_)
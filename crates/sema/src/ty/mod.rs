@@ -14,7 +14,7 @@ use solar_data_structures::{
 };
 use solar_interface::{
     diagnostics::{DiagCtxt, ErrorGuaranteed},
-    Ident, Session, Span,
+    error_code, Ident, Session, Span,
 };
 use std::{
     fmt,
@@ -24,6 +24,7 @@ use std::{
 use thread_local::ThreadLocal;
 
 mod abi;
+pub(crate) use abi::TySolcPrinter;
 pub use abi::{TyAbiPrinter, TyAbiPrinterMode};
 
 mod common;
@@ -32,6 +33,12 @@ pub use common::{CommonTypes, EachDataLoc};
 mod interner;
 use interner::Interner;
 
+mod references;
+pub use references::{Hover, Scope};
+
+mod storage;
+pub use storage::{StorageItem, StorageLayout};
+
 #[allow(clippy::module_inception)]
 mod ty;
 pub use ty::{Ty, TyData, TyFlags, TyFnPtr, TyKind};
@@ -147,6 +154,8 @@ pub struct GlobalCtxt<'gcx> {
     pub types: CommonTypes<'gcx>,
     pub hir: Hir<'gcx>,
     pub(crate) symbol_resolver: SymbolResolver<'gcx>,
+    /// The import remappings used to resolve the sources, as `prefix=target`, sorted.
+    pub(crate) remappings: Vec<String>,
 
     interner: Interner<'gcx>,
     cache: Cache<'gcx>,
@@ -158,6 +167,7 @@ impl<'gcx> GlobalCtxt<'gcx> {
         arena: &'gcx ThreadLocal<hir::Arena>,
         hir: Hir<'gcx>,
         symbol_resolver: SymbolResolver<'gcx>,
+        remappings: Vec<String>,
     ) -> Self {
         let interner = Interner::new(arena);
         Self {
@@ -165,6 +175,7 @@ impl<'gcx> GlobalCtxt<'gcx> {
             types: CommonTypes::new(&interner),
             hir,
             symbol_resolver,
+            remappings,
             interner,
             cache: Cache::default(),
         }
@@ -283,6 +294,16 @@ impl<'gcx> Gcx<'gcx> {
         })
     }
 
+    /// Returns the sources sorted by file name.
+    ///
+    /// The index of a source in this list is its index in source locations and source mappings,
+    /// as in the `sourceList` of the combined JSON.
+    pub fn source_list(self) -> Vec<hir::SourceId> {
+        let mut sources = self.hir.source_ids().collect::<Vec<_>>();
+        sources.sort_by_cached_key(|&id| self.hir.source(id).file.name.display().to_string());
+        sources
+    }
+
     /// Returns the fully qualified name of the contract.
     pub fn contract_fully_qualified_name(
         self,
@@ -397,7 +418,9 @@ impl<'gcx> Gcx<'gcx> {
             hir::TypeKind::Array(array) => {
                 let ty = self.type_of_hir_ty(&array.element);
                 match array.size {
-                    Some(size) => match crate::eval::ConstantEvaluator::new(self).eval(size) {
+                    Some(size) => match crate::eval::ConstantEvaluator::new(self)
+                        .eval_as(size, self.types.uint(256))
+                    {
                         Ok(int) => {
                             if int.data.is_zero() {
                                 let msg = "array length must be greater than zero";
@@ -424,6 +447,7 @@ impl<'gcx> Gcx<'gcx> {
                 TyKind::Mapping(key, value)
             }
             hir::TypeKind::Custom(item) => return self.type_of_item_simple(item, ty.span),
+            hir::TypeKind::Infer => TyKind::Infer,
             hir::TypeKind::Err(guar) => TyKind::Err(guar),
         };
         self.mk_ty(kind)
@@ -684,11 +708,27 @@ pub fn struct_recursiveness(gcx: _, id: hir::StructId) -> Recursiveness {
         CycleDetectorResult::Break(Either::Left(guar)) => Recursiveness::Infinite(guar),
         CycleDetectorResult::Break(Either::Right(())) => Recursiveness::Recursive,
         CycleDetectorResult::Cycle(id) => Recursiveness::Infinite(
-            gcx.dcx().err("recursive struct definition").span(gcx.item_span(id)).emit()
+            gcx.dcx()
+                .err("recursive struct definition")
+                .code(error_code!(2046))
+                .span(gcx.item_span(id))
+                .emit()
         ),
     }
 }
 
+/// Returns the storage layout of the given contract, including inherited state variables.
+///
+/// Constants, immutables, and transient state variables are not included.
+pub fn storage_layout(gcx: _, id: hir::ContractId) -> StorageLayout<'gcx> {
+    storage::storage_layout(gcx, id)
+}
+
+/// Returns the storage layout of the fields of the given struct, relative to its first slot.
+pub fn struct_storage_layout(gcx: _, id: hir::StructId) -> StorageLayout<'gcx> {
+    storage::struct_storage_layout(gcx, id)
+}
+
 /// Returns the members of the given type.
 pub fn members_of(gcx: _, ty: Ty<'gcx>) -> members::MemberList<'gcx> {
     members::members_of(gcx, ty)
@@ -0,0 +1,358 @@
+use super::{Gcx, TySolcPrinter};
+use crate::{ast_lowering::resolve::Declarations, hir};
+use solar_ast::NatSpecItem;
+use solar_interface::{BytePos, Span, Symbol};
+
+/// Information about the declaration that a name refers to. See [`Gcx::hover`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Hover<'gcx> {
+    /// The span of the name.
+    pub span: Span,
+    /// The declaration.
+    pub item: hir::ItemId,
+    /// A Solidity-like declaration of the item, like `function C.f(uint x) returns (bool)`.
+    pub signature: String,
+    /// The type of the item, including the data location, if it is a variable.
+    pub ty: Option<String>,
+    /// The NatSpec documentation of the declaration.
+    pub docs: &'gcx [NatSpecItem],
+}
+
+/// A scope in which names are declared, used to resolve names.
+///
+/// Scopes form a tree: the scope of a contract is nested in the scope of the source it is defined
+/// in, which is nested in the global scope. The local variables of functions are not part of any
+/// scope; the names referring to them can be resolved with [`Gcx::res_at`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Scope {
+    /// The global scope, containing the global builtins like `msg` and `keccak256`.
+    Global,
+    /// The top-level scope of a source, containing its items and imported names.
+    Source(hir::SourceId),
+    /// The scope of a contract, containing its own and inherited items, `this` and `super`.
+    Contract(hir::ContractId),
+}
+
+impl Scope {
+    /// Returns the scope that this scope is nested in, or `None` for the global scope.
+    pub fn parent(self, gcx: Gcx<'_>) -> Option<Self> {
+        match self {
+            Self::Global => None,
+            Self::Source(_) => Some(Self::Global),
+            Self::Contract(id) => Some(Self::Source(gcx.hir.contract(id).source)),
+        }
+    }
+}
+
+impl<'gcx> Gcx<'gcx> {
+    /// Returns the innermost scope containing the given position.
+    pub fn scope_at(self, pos: BytePos) -> Scope {
+        let hir = &self.hir;
+        let contains = |span: Span| span.lo() <= pos && pos <= span.hi();
+        if let Some(id) = hir.contract_ids().find(|&id| contains(hir.contract(id).span)) {
+            return Scope::Contract(id);
+        }
+        match hir.source_ids().find(|&id| hir.source(id).file.contains(pos)) {
+            Some(id) => Scope::Source(id),
+            None => Scope::Global,
+        }
+    }
+
+    /// Returns the names declared in the given scope, in declaration order.
+    ///
+    /// Overloaded names have an entry for each overload.
+    pub fn scope_names(self, scope: Scope) -> Vec<(Symbol, hir::Res)> {
+        self.scope_declarations(scope)
+            .declarations
+            .iter()
+            .flat_map(|(&name, decls)| decls.iter().map(move |decl| (name, decl.res)))
+            .collect()
+    }
+
+    /// Resolves a name in the given scope and the scopes it is nested in, like an identifier
+    /// appearing in that scope.
+    ///
+    /// Returns all the overloads of the name in the innermost scope declaring it, or an empty
+    /// vector if the name is not declared.
+    pub fn resolve_name(self, scope: Scope, name: Symbol) -> Vec<hir::Res> {
+        std::iter::successors(Some(scope), |scope| scope.parent(self))
+            .find_map(|scope| self.scope_declarations(scope).declarations.get(&name))
+            .map(|decls| decls.iter().map(|decl| decl.res).collect())
+            .unwrap_or_default()
+    }
+
+    fn scope_declarations(self, scope: Scope) -> &'gcx Declarations {
+        let resolver = &self.0.symbol_resolver;
+        match scope {
+            Scope::Global => &resolver.global_builtin_scope,
+            Scope::Source(id) => &resolver.source_scopes[id],
+            Scope::Contract(id) => &resolver.contract_scopes[id],
+        }
+    }
+
+    /// Returns the resolved names in all the sources, with the declarations they refer to, sorted
+    /// by span.
+    ///
+    /// Names referring to overloaded functions have an entry for each overload. See
+    /// [`res_at`](Self::res_at) for which names are resolved.
+    pub fn resolutions(self) -> &'gcx [(Span, hir::Res)] {
+        &self.0.symbol_resolver.references
+    }
+
+    /// Returns the sources directly imported by the given source, in import order.
+    pub fn imports(self, source: hir::SourceId) -> Vec<hir::SourceId> {
+        let mut imports = Vec::new();
+        for &(_, import) in self.hir.source(source).imports {
+            if !imports.contains(&import) {
+                imports.push(import);
+            }
+        }
+        imports
+    }
+
+    /// Returns the sources that directly import the given source.
+    pub fn importers(self, source: hir::SourceId) -> Vec<hir::SourceId> {
+        self.hir
+            .source_ids()
+            .filter(|&id| self.hir.source(id).imports.iter().any(|&(_, import)| import == source))
+            .collect()
+    }
+
+    /// Returns the declaration that the name at the given position refers to.
+    ///
+    /// The names of declarations refer to the declarations themselves. Names that refer to
+    /// overloaded functions return the first overload, as calls are not resolved to a specific
+    /// overload. Members of values, like `x` in `s.x`, are not resolved.
+    ///
+    /// Names are only resolved if [`Session::record_references`] is set, or if the unused
+    /// variable or import lints are enabled.
+    ///
+    /// [`Session::record_references`]: solar_interface::Session::record_references
+    pub fn res_at(self, pos: BytePos) -> Option<hir::Res> {
+        self.name_at(pos).map(|(_, res)| res)
+    }
+
+    /// Returns the span of the name at the given position, and the declaration it refers to. See
+    /// [`res_at`](Self::res_at).
+    pub(super) fn name_at(self, pos: BytePos) -> Option<(Span, hir::Res)> {
+        let references = &self.symbol_resolver.references;
+        let end = references.partition_point(|&(span, _)| span.lo() <= pos);
+        if let Some(&(span, _)) = end.checked_sub(1).map(|i| &references[i]) {
+            if pos <= span.hi() {
+                let start = references[..end].partition_point(|&(s, _)| s.lo() < span.lo());
+                return Some(references[start]);
+            }
+        }
+
+        self.hir
+            .item_ids()
+            .filter(|&id| !matches!(self.hir.item(id), hir::Item::Function(f) if f.is_getter()))
+            .find_map(|id| {
+                let name = self.item_name_opt(id)?;
+                let contains = name.span.lo() <= pos && pos <= name.span.hi();
+                contains.then_some((name.span, hir::Res::Item(id)))
+            })
+    }
+
+    /// Returns the span of the name of the declaration that the name at the given position refers
+    /// to, for "go to definition".
+    ///
+    /// Import namespaces, like `X` in `import "a.sol" as X`, go to the start of the imported
+    /// source. See [`res_at`](Self::res_at) for which names are resolved.
+    pub fn def_at(self, pos: BytePos) -> Option<Span> {
+        match self.res_at(pos)? {
+            hir::Res::Item(id) => {
+                Some(self.item_name_opt(id).map_or(self.item_span(id), |n| n.span))
+            }
+            hir::Res::Namespace(id) => {
+                let start = self.hir.source(id).file.start_pos;
+                Some(Span::new(start, start))
+            }
+            hir::Res::Builtin(_) | hir::Res::Err(_) => None,
+        }
+    }
+
+    /// Returns the spans of the names referring to the given declaration in all the sources,
+    /// sorted, for "find all references".
+    ///
+    /// The name of the declaration itself is not included. Names referring to overloaded
+    /// functions are references to all the overloads. See [`res_at`](Self::res_at) for which
+    /// names are resolved.
+    pub fn references(self, res: hir::Res) -> Vec<Span> {
+        self.symbol_resolver.uses.get(&res).cloned().unwrap_or_default()
+    }
+
+    /// Returns the signature, type and documentation of the declaration that the name at the
+    /// given position refers to, for hovers.
+    ///
+    /// Only items are described, not builtins nor imported sources. See [`res_at`](Self::res_at)
+    /// for which names are resolved.
+    pub fn hover(self, pos: BytePos) -> Option<Hover<'gcx>> {
+        let (span, hir::Res::Item(id)) = self.name_at(pos)? else { return None };
+        let ty = matches!(id, hir::ItemId::Variable(_)).then(|| {
+            let mut s = String::new();
+            let mut printer = TySolcPrinter::new(self, &mut s).data_locations(true);
+            printer.print(self.type_of_item(id)).unwrap();
+            s
+        });
+        let signature = self.item_declaration(id);
+        Some(Hover { span, item: id, signature, ty, docs: self.hir.item(id).docs() })
+    }
+
+    /// Returns a Solidity-like declaration of the given item.
+    fn item_declaration(self, id: hir::ItemId) -> String {
+        let snippet = |span: Span| self.sess.source_map().span_to_snippet(span).unwrap_or_default();
+        let variables = |ids: &[hir::VariableId]| {
+            ids.iter().map(|&id| snippet(self.hir.variable(id).span)).collect::<Vec<_>>().join(", ")
+        };
+        let item = self.hir.item(id);
+        match item {
+            hir::Item::Variable(var) => snippet(var.span).trim_end_matches(';').to_string(),
+            hir::Item::Function(_) | hir::Item::Error(_) | hir::Item::Event(_) => {
+                let mut s = format!("{} {}", item.description(), self.item_canonical_name(id));
+                s.push_str(&format!("({})", variables(item.parameters().unwrap_or_default())));
+                if let hir::Item::Function(f) = item {
+                    if !f.returns.is_empty() {
+                        s.push_str(&format!(" returns ({})", variables(f.returns)));
+                    }
+                }
+                s
+            }
+            _ => format!("{} {}", item.description(), self.item_canonical_name(id)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Scope;
+    use crate::{
+        hir,
+        lints::{UNUSED_IMPORTS, UNUSED_VARIABLES},
+        test_utils::{with_gcx, FILE_NAME},
+        ParsingContext,
+    };
+    use solar_ast::NatSpecKind;
+    use solar_interface::{
+        lint::{Lint, LintLevel},
+        source_map::FileName,
+        Session, Symbol,
+    };
+
+    const SRC: &str = "\
+contract B { event E(uint); }
+contract C is B {
+    struct S { uint a; }
+    uint x;
+    /// Does f.
+    function f(uint z) public returns (S memory s) { emit E(x + z); f(z); s.a = 1; }
+}
+";
+
+    #[test]
+    fn def_at() {
+        with_gcx(SRC, |gcx, pos| {
+            let def = |s: &str, n: usize| gcx.def_at(pos(s, n)).map(|span| (span.lo(), span.hi()));
+            let name = |s: &str, n: usize| Some((pos(s, n), pos(s, n) + s.len() as u32));
+            // References.
+            assert_eq!(def("B", 1), name("B", 0));
+            assert_eq!(def("S", 1), name("S", 0));
+            assert_eq!(def("E", 1), name("E", 0));
+            assert_eq!(def("x", 1), name("x", 0));
+            assert_eq!(def("z", 1), name("z", 0));
+            let f = pos("f(", 0);
+            assert_eq!(def("f(z)", 0), Some((f, f + 1)));
+            // Declarations.
+            assert_eq!(def("C", 0), name("C", 0));
+            assert_eq!(def("x", 0), name("x", 0));
+            // Members, keywords and elementary types.
+            assert_eq!(def("a = 1", 0), None);
+            assert_eq!(def("contract", 0), None);
+            assert_eq!(def("uint", 0), None);
+        });
+    }
+
+    #[test]
+    fn references() {
+        with_gcx(SRC, |gcx, pos| {
+            let refs = |s: &str, n: usize| {
+                let res = gcx.res_at(pos(s, n)).unwrap();
+                gcx.references(res).iter().map(|span| span.lo()).collect::<Vec<_>>()
+            };
+            assert_eq!(refs("B", 0), [pos("B", 1)]);
+            assert_eq!(refs("S", 1), [pos("S", 1)]);
+            assert_eq!(refs("z", 0), [pos("z", 1), pos("z", 2)]);
+            assert_eq!(refs("f(", 0), [pos("f(z)", 0)]);
+            assert!(refs("C", 0).is_empty());
+        });
+    }
+
+    #[test]
+    fn hover() {
+        with_gcx(SRC, |gcx, pos| {
+            let hover = gcx.hover(pos("x", 1)).unwrap();
+            assert_eq!(hover.span.lo(), pos("x", 1));
+            assert_eq!(hover.signature, "uint x");
+            assert_eq!(hover.ty.as_deref(), Some("uint256"));
+
+            let hover = gcx.hover(pos("s.a", 0)).unwrap();
+            assert_eq!(hover.ty.as_deref(), Some("struct C.S memory"));
+
+            let hover = gcx.hover(pos("f(z)", 0)).unwrap();
+            assert_eq!(hover.signature, "function C.f(uint z) returns (S memory s)");
+            assert_eq!(hover.ty, None);
+            assert_eq!(hover.docs.len(), 1);
+            assert_eq!(hover.docs[0].kind, NatSpecKind::Notice);
+            assert_eq!(hover.docs[0].content.as_str(), "Does f.");
+
+            assert_eq!(gcx.hover(pos("uint", 0)), None);
+        });
+    }
+
+    #[test]
+    fn scopes() {
+        with_gcx(SRC, |gcx, pos| {
+            let scope = gcx.scope_at(pos("uint x", 0));
+            let Scope::Contract(c) = scope else { panic!("{scope:?}") };
+            assert_eq!(gcx.hir.contract(c).name.as_str(), "C");
+            let source = gcx.scope_at(pos("contract C", 0));
+            assert!(matches!(source, Scope::Source(_)));
+            assert_eq!(scope.parent(gcx), Some(source));
+            assert_eq!(source.parent(gcx), Some(Scope::Global));
+            assert_eq!(Scope::Global.parent(gcx), None);
+
+            let names = gcx.scope_names(scope);
+            let names = names.iter().map(|(name, _)| name.as_str()).collect::<Vec<_>>();
+            assert_eq!(names, ["this", "super", "S", "x", "f", "E"]);
+
+            let res = |scope: Scope, name: &str| gcx.resolve_name(scope, Symbol::intern(name));
+            assert_eq!(res(scope, "x"), [gcx.res_at(pos("x", 0)).unwrap()]);
+            assert_eq!(res(scope, "B"), [gcx.res_at(pos("B", 0)).unwrap()]);
+            assert!(matches!(res(scope, "msg")[..], [hir::Res::Builtin(_)]));
+            assert!(res(source, "x").is_empty());
+
+            let resolutions = gcx.resolutions();
+            assert!(resolutions.windows(2).all(|w| w[0].0.lo() <= w[1].0.lo()));
+            assert!(resolutions.iter().any(|&(span, _)| span.lo() == pos("x", 1)));
+
+            let Scope::Source(id) = source else { unreachable!() };
+            assert!(gcx.imports(id).is_empty());
+            assert!(gcx.importers(id).is_empty());
+        });
+    }
+
+    #[test]
+    fn not_recorded_by_default() {
+        let allow = |lint: &Lint| (lint.name.to_string(), LintLevel::Allow);
+        let sess = Session::builder()
+            .with_test_emitter()
+            .lint_levels([allow(&UNUSED_VARIABLES), allow(&UNUSED_IMPORTS)].into())
+            .build();
+        sess.enter(|| {
+            let mut pcx = ParsingContext::new(&sess);
+            let name = FileName::Real(FILE_NAME.into());
+            pcx.add_file(sess.source_map().new_source_file(name, || Ok(SRC.into())).unwrap());
+            pcx.parse_and_resolve_with(|gcx| assert!(gcx.resolutions().is_empty())).unwrap();
+        });
+    }
+}
@@ -0,0 +1,164 @@
+use super::{Gcx, Ty, TyKind};
+use crate::hir;
+use alloy_primitives::U256;
+use solar_ast::{DataLocation, ElementaryType};
+
+/// The storage layout of a contract or struct.
+///
+/// Return type of [`Gcx::storage_layout`] and [`Gcx::struct_storage_layout`].
+#[derive(Clone, Copy, Debug)]
+pub struct StorageLayout<'gcx> {
+    /// The storage items, in declaration order.
+    pub items: &'gcx [StorageItem<'gcx>],
+    /// The total number of slots used.
+    pub slots: U256,
+}
+
+/// The storage location of a state variable or struct field.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct StorageItem<'gcx> {
+    /// The variable ID.
+    pub id: hir::VariableId,
+    /// The variable type.
+    pub ty: Ty<'gcx>,
+    /// The slot the variable starts at.
+    pub slot: U256,
+    /// The byte offset of the variable in its slot. Always `0` for types that don't fit in a
+    /// single slot.
+    pub offset: u8,
+}
+
+/// Packs items into storage slots.
+///
+/// Reference: <https://docs.soliditylang.org/en/latest/internals/layout_in_storage.html>
+struct SlotPacker<'gcx> {
+    gcx: Gcx<'gcx>,
+    items: Vec<StorageItem<'gcx>>,
+    slot: U256,
+    offset: u8,
+}
+
+impl<'gcx> SlotPacker<'gcx> {
+    fn new(gcx: Gcx<'gcx>) -> Self {
+        Self { gcx, items: Vec::new(), slot: U256::ZERO, offset: 0 }
+    }
+
+    fn push(&mut self, id: hir::VariableId, ty: Ty<'gcx>) {
+        let bytes = self.gcx.type_storage_bytes(ty);
+        let slots = self.gcx.type_storage_slots(ty);
+        let item = if slots == U256::from(1) && bytes <= 32 {
+            // Value types are packed into the current slot if they fit.
+            if self.offset + bytes > 32 {
+                self.next_slot(U256::from(1), id);
+            }
+            let item = StorageItem { id, ty, slot: self.slot, offset: self.offset };
+            self.offset += bytes;
+            item
+        } else {
+            // Everything else starts a new slot, and the next item starts a new slot too.
+            if self.offset > 0 {
+                self.next_slot(U256::from(1), id);
+            }
+            let item = StorageItem { id, ty, slot: self.slot, offset: 0 };
+            self.next_slot(slots, id);
+            item
+        };
+        self.items.push(item);
+    }
+
+    fn next_slot(&mut self, slots: U256, id: hir::VariableId) {
+        self.offset = 0;
+        self.slot = self.slot.checked_add(slots).unwrap_or_else(|| {
+            let span = self.gcx.hir.variable(id).span;
+            self.gcx.dcx().err("storage size overflows 2**256 slots").span(span).emit();
+            U256::MAX
+        });
+    }
+
+    fn finish(self) -> StorageLayout<'gcx> {
+        let slots =
+            if self.offset > 0 { self.slot.saturating_add(U256::from(1)) } else { self.slot };
+        StorageLayout { items: self.gcx.bump().alloc_slice_copy(&self.items), slots }
+    }
+}
+
+impl<'gcx> Gcx<'gcx> {
+    /// Returns the number of bytes a value of the given type occupies in storage.
+    ///
+    /// Types that are not packed with their neighbours, such as arrays and structs, always
+    /// return 32. See [`type_storage_slots`](Self::type_storage_slots) for the number of slots.
+    pub fn type_storage_bytes(self, ty: Ty<'gcx>) -> u8 {
+        match ty.kind {
+            TyKind::Elementary(ty) => match ty {
+                ElementaryType::Address(_) => 20,
+                ElementaryType::Bool => 1,
+                ElementaryType::Int(size)
+                | ElementaryType::UInt(size)
+                | ElementaryType::FixedBytes(size)
+                | ElementaryType::Fixed(size, _)
+                | ElementaryType::UFixed(size, _) => size.bytes(),
+                ElementaryType::String | ElementaryType::Bytes => 32,
+            },
+            TyKind::Contract(_) => 20,
+            TyKind::Enum(_) => 1,
+            TyKind::Udvt(ty, _) | TyKind::Ref(ty, _) => self.type_storage_bytes(ty),
+            TyKind::FnPtr(f) => {
+                if f.visibility == hir::Visibility::External {
+                    24
+                } else {
+                    8
+                }
+            }
+            _ => 32,
+        }
+    }
+
+    /// Returns the number of storage slots a value of the given type occupies.
+    pub fn type_storage_slots(self, ty: Ty<'gcx>) -> U256 {
+        match ty.kind {
+            TyKind::Ref(ty, _) => self.type_storage_slots(ty),
+            TyKind::Array(elem, len) => {
+                let elem_bytes = self.type_storage_bytes(elem);
+                if elem_bytes <= 16 {
+                    let per_slot = U256::from(32 / elem_bytes);
+                    len.div_ceil(per_slot)
+                } else {
+                    len.saturating_mul(self.type_storage_slots(elem))
+                }
+            }
+            TyKind::Struct(id) => {
+                if matches!(self.struct_recursiveness(id), super::Recursiveness::Infinite(_)) {
+                    return U256::from(1);
+                }
+                self.struct_storage_layout(id).slots.max(U256::from(1))
+            }
+            _ => U256::from(1),
+        }
+    }
+}
+
+pub(super) fn storage_layout(gcx: Gcx<'_>, id: hir::ContractId) -> StorageLayout<'_> {
+    let mut packer = SlotPacker::new(gcx);
+    // Base contracts' variables come first.
+    for &base in gcx.hir.contract(id).linearized_bases.iter().rev() {
+        for var_id in gcx.hir.contract(base).variables() {
+            let var = gcx.hir.variable(var_id);
+            if var.is_constant()
+                || var.is_immutable()
+                || var.data_location == Some(DataLocation::Transient)
+            {
+                continue;
+            }
+            packer.push(var_id, gcx.type_of_item(var_id.into()));
+        }
+    }
+    packer.finish()
+}
+
+pub(super) fn struct_storage_layout(gcx: Gcx<'_>, id: hir::StructId) -> StorageLayout<'_> {
+    let mut packer = SlotPacker::new(gcx);
+    for (ty, var_id) in gcx.item_fields(id) {
+        packer.push(var_id, ty);
+    }
+    packer.finish()
+}
@@ -218,6 +218,7 @@ impl<'gcx> Ty<'gcx> {
             | TyKind::Module(_)
             | TyKind::BuiltinModule(_)
             | TyKind::Struct(_)
+            | TyKind::Infer
             | TyKind::Err(_) => ControlFlow::Continue(()),
 
             TyKind::Ref(ty, _)
@@ -325,6 +326,11 @@ pub enum TyKind<'gcx> {
     /// The meta type: `type(<inner_type>)`.
     Meta(Ty<'gcx>),
 
+    /// The type of a legacy `var` declaration, which is not inferred.
+    ///
+    /// Like [`Err`](Self::Err), this is not type checked, but no error has been emitted for it.
+    Infer,
+
     /// An invalid type. Silences further errors.
     Err(ErrorGuaranteed),
 }
@@ -373,7 +379,8 @@ impl TyFlags {
             | TyKind::FnPtr(_)
             | TyKind::Enum(_)
             | TyKind::Module(_)
-            | TyKind::BuiltinModule(_) => {}
+            | TyKind::BuiltinModule(_)
+            | TyKind::Infer => {}
 
             TyKind::Ref(ty, _)
             | TyKind::DynArray(ty)
@@ -0,0 +1,699 @@
+//! Type checking of function bodies.
+//!
+//! Computes the types of expressions and checks implicit conversions, operator types, function
+//! call arguments and return statements.
+//!
+//! The checks are conservative: the types of some expressions, like calls to overloaded functions
+//! and members of contracts, are not computed yet, and expressions involving them are not checked.
+//! Conversions between types that are not modeled precisely, like fixed-point numbers and function
+//! pointers, are always allowed.
+
+use crate::{
+    builtins::Builtin,
+    hir::{self, Res},
+    ty::{Gcx, Ty, TyKind, TySolcPrinter},
+};
+use solar_ast::{BinOpKind, DataLocation, ElementaryType, LitKind, TypeSize, UnOpKind};
+use solar_interface::{diagnostics::DiagnosticId, error_code, Span};
+
+/// Type checks the body of the given function, if any.
+#[instrument(level = "trace", skip(gcx))]
+pub(super) fn check_function(gcx: Gcx<'_>, id: hir::FunctionId) {
+    let function = gcx.hir.function(id);
+    let Some(body) = function.body else { return };
+    let returns = function.returns.iter().map(|&ret| gcx.type_of_item(ret.into())).collect();
+    let checker = TypeChecker { gcx, contract: function.contract, returns };
+    checker.check_block(body);
+}
+
+struct TypeChecker<'gcx> {
+    gcx: Gcx<'gcx>,
+    /// The contract of the function being checked, if any.
+    contract: Option<hir::ContractId>,
+    /// The return types of the function being checked.
+    returns: Vec<Ty<'gcx>>,
+}
+
+impl<'gcx> TypeChecker<'gcx> {
+    fn check_block(&self, block: hir::Block<'_>) {
+        for stmt in block {
+            self.check_stmt(stmt);
+        }
+    }
+
+    fn check_stmt(&self, stmt: &hir::Stmt<'_>) {
+        match stmt.kind {
+            hir::StmtKind::DeclSingle(id) => {
+                if let Some(init) = self.gcx.hir.variable(id).initializer {
+                    let ty = self.gcx.type_of_item(id.into());
+                    self.check_expr_coerces_to(init, ty, error_code!(9574));
+                }
+            }
+            hir::StmtKind::DeclMulti(_, expr) | hir::StmtKind::Expr(expr) => {
+                self.check_expr(expr);
+            }
+            hir::StmtKind::Block(block)
+            | hir::StmtKind::UncheckedBlock(block)
+            | hir::StmtKind::Loop(block, _) => self.check_block(block),
+            hir::StmtKind::Emit(res, ref args) | hir::StmtKind::Revert(res, ref args) => {
+                let callee = match *res {
+                    [Res::Item(id @ (hir::ItemId::Event(_) | hir::ItemId::Error(_)))] => Some(id),
+                    _ => None,
+                };
+                self.check_call_args(callee, args, stmt.span);
+            }
+            hir::StmtKind::Return(expr) => self.check_return(expr, stmt.span),
+            hir::StmtKind::If(cond, then, else_) => {
+                self.check_expr_coerces_to(cond, self.gcx.types.bool, error_code!(7407));
+                self.check_stmt(then);
+                if let Some(else_) = else_ {
+                    self.check_stmt(else_);
+                }
+            }
+            hir::StmtKind::Try(try_) => {
+                self.check_expr(&try_.expr);
+                self.check_block(try_.block);
+                for catch in try_.catch {
+                    self.check_block(catch.block);
+                }
+            }
+            hir::StmtKind::Break
+            | hir::StmtKind::Continue
+            | hir::StmtKind::Placeholder
+            | hir::StmtKind::Err(_) => {}
+        }
+    }
+
+    fn check_return(&self, expr: Option<&hir::Expr<'_>>, span: Span) {
+        let Some(expr) = expr else { return };
+        let ty = self.check_expr(expr);
+        let count = ty.map(|ty| match ty.kind {
+            TyKind::Tuple(tys) => tys.len(),
+            _ => 1,
+        });
+        if self.returns.is_empty() || count.is_some_and(|count| count != self.returns.len()) {
+            let msg =
+                "different number of arguments in return statement than in returns declaration";
+            self.gcx.dcx().err(msg).code(error_code!(8863)).span(span).emit();
+            return;
+        }
+        let expected = match self.returns[..] {
+            [ret] => ret,
+            ref rets => self.gcx.mk_ty(TyKind::Tuple(self.gcx.mk_tys(rets))),
+        };
+        if let Some(ty) = ty {
+            self.check_coercion(expr.span, ty, expected, error_code!(6359));
+        }
+    }
+
+    /// Checks the arguments of a call to the given function, event or error.
+    fn check_call_args(&self, callee: Option<hir::ItemId>, args: &hir::CallArgs<'_>, span: Span) {
+        let Some(id) = callee else {
+            for arg in args.exprs() {
+                self.check_expr(arg);
+            }
+            return;
+        };
+        let params = self.gcx.item_parameters(id);
+        let tys = self.gcx.item_parameter_types(id);
+        if args.len() != params.len() {
+            for arg in args.exprs() {
+                self.check_expr(arg);
+            }
+            let msg = format!(
+                "wrong argument count for {} call: {} arguments given but expected {}",
+                self.gcx.hir.item(id).description(),
+                args.len(),
+                params.len(),
+            );
+            self.gcx.dcx().err(msg).code(error_code!(6160)).span(span).emit();
+            return;
+        }
+        match *args {
+            hir::CallArgs::Unnamed(exprs) => {
+                for (arg, &ty) in exprs.iter().zip(tys) {
+                    self.check_expr_coerces_to(arg, ty, error_code!(7407));
+                }
+            }
+            hir::CallArgs::Named(args) => {
+                for arg in args {
+                    let param = params.iter().position(|&param| {
+                        self.gcx.hir.variable(param).name.is_some_and(|n| n.name == arg.name.name)
+                    });
+                    match param {
+                        Some(i) => {
+                            self.check_expr_coerces_to(&arg.value, tys[i], error_code!(7407))
+                        }
+                        None => {
+                            self.check_expr(&arg.value);
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    fn check_expr_coerces_to(&self, expr: &hir::Expr<'_>, expected: Ty<'gcx>, code: DiagnosticId) {
+        if let Some(ty) = self.check_expr(expr) {
+            self.check_coercion(expr.span, ty, expected, code);
+        }
+    }
+
+    /// Checks that `ty` is implicitly convertible to `expected`, emitting an error with the given
+    /// code otherwise.
+    fn check_coercion(&self, span: Span, ty: Ty<'gcx>, expected: Ty<'gcx>, code: DiagnosticId) {
+        if !self.is_implicitly_convertible(ty, expected) {
+            let msg = format!(
+                "type `{}` is not implicitly convertible to expected type `{}`",
+                self.ty_name(ty),
+                self.ty_name(expected),
+            );
+            self.gcx.dcx().err(msg).code(code).span(span).emit();
+        }
+    }
+
+    /// Checks an expression and its subexpressions, and returns its type if it is known.
+    fn check_expr(&self, expr: &hir::Expr<'_>) -> Option<Ty<'gcx>> {
+        let gcx = self.gcx;
+        let types = &gcx.types;
+        match expr.kind {
+            hir::ExprKind::Assign(lhs, op, rhs) => {
+                let lhs_ty = self.check_expr(lhs);
+                match (op, lhs_ty) {
+                    (Some(op), _) => {
+                        if let (Some(l), Some(r)) = (lhs_ty, self.check_expr(rhs)) {
+                            self.check_binary(expr.span, l, op, r, error_code!(7366));
+                        }
+                    }
+                    (None, Some(lhs_ty)) => {
+                        self.check_expr_coerces_to(rhs, lhs_ty, error_code!(7407))
+                    }
+                    (None, None) => {
+                        self.check_expr(rhs);
+                    }
+                }
+                lhs_ty
+            }
+            hir::ExprKind::Binary(l, op, r) => {
+                let (l, r) = (self.check_expr(l), self.check_expr(r));
+                self.check_binary(expr.span, l?, op, r?, error_code!(2271))
+            }
+            hir::ExprKind::Call(callee, ref args) => self.check_call(callee, args, expr.span),
+            hir::ExprKind::Ident(res) => match *res {
+                [Res::Item(id)] => self.item_ty(id),
+                [Res::Builtin(Builtin::This)] => {
+                    self.contract.map(|id| gcx.type_of_item(id.into()))
+                }
+                [Res::Builtin(builtin)] => builtin_ty(gcx, builtin),
+                _ => None,
+            },
+            hir::ExprKind::Index(base, index) => {
+                let base_ty = self.check_expr(base);
+                let index_ty = index.and_then(|index| Some((index.span, self.check_expr(index)?)));
+                let (key, value) = match peel_value_ref(base_ty?).peel_refs().kind {
+                    TyKind::Mapping(key, value) => (key, value),
+                    TyKind::Array(elem, _) | TyKind::DynArray(elem) => (types.uint(256), elem),
+                    TyKind::Elementary(ElementaryType::Bytes | ElementaryType::FixedBytes(_)) => {
+                        (types.uint(256), types.fixed_bytes(1))
+                    }
+                    _ => return None,
+                };
+                if let Some((span, index_ty)) = index_ty.filter(|_| key.is_value_type()) {
+                    self.check_coercion(span, index_ty, key, error_code!(7407));
+                }
+                value.is_value_type().then_some(value)
+            }
+            hir::ExprKind::Lit(lit) => match lit.kind {
+                LitKind::Str(_, ref value) => Some(gcx.mk_ty_string_literal(value)),
+                LitKind::Number(ref n) => {
+                    let bytes = n.bits().div_ceil(8).max(1);
+                    let size = TypeSize::new(bytes.try_into().ok()?)?;
+                    Some(gcx.mk_ty_int_literal(size))
+                }
+                LitKind::Address(_) => Some(types.address),
+                LitKind::Bool(_) => Some(types.bool),
+                LitKind::Rational(_) | LitKind::Err(_) => None,
+            },
+            hir::ExprKind::Member(base, name) => {
+                match base.kind {
+                    // Members of builtin modules, like `msg.sender`.
+                    hir::ExprKind::Ident(&[Res::Builtin(builtin)]) => {
+                        let member = builtin.members()?.iter().find(|b| b.name() == name.name)?;
+                        return builtin_ty(gcx, *member);
+                    }
+                    // Enum variants.
+                    hir::ExprKind::Ident(&[Res::Item(hir::ItemId::Enum(id))]) => {
+                        return Some(gcx.type_of_item(id.into()));
+                    }
+                    _ => {}
+                }
+                let ty = match peel_value_ref(self.check_expr(base)?).peel_refs().kind {
+                    TyKind::Struct(id) => {
+                        let field = gcx.hir.strukt(id).fields.iter().find(|&&field| {
+                            gcx.hir.variable(field).name.is_some_and(|n| n.name == name.name)
+                        })?;
+                        gcx.type_of_item((*field).into())
+                    }
+                    TyKind::Array(..)
+                    | TyKind::DynArray(_)
+                    | TyKind::Elementary(ElementaryType::Bytes)
+                        if name.name == Builtin::ArrayLength.name() =>
+                    {
+                        types.uint(256)
+                    }
+                    TyKind::Elementary(ElementaryType::FixedBytes(_))
+                        if name.name == Builtin::FixedBytesLength.name() =>
+                    {
+                        types.uint(8)
+                    }
+                    TyKind::Elementary(ElementaryType::Address(_))
+                        if name.name == Builtin::AddressBalance.name() =>
+                    {
+                        types.uint(256)
+                    }
+                    _ => return None,
+                };
+                ty.is_value_type().then_some(ty)
+            }
+            hir::ExprKind::Payable(expr) => {
+                self.check_expr(expr);
+                Some(types.address_payable)
+            }
+            hir::ExprKind::Ternary(cond, t, f) => {
+                self.check_expr_coerces_to(cond, types.bool, error_code!(7407));
+                let (t, f) = (self.check_expr(t), self.check_expr(f));
+                let (t, f) = (t?, f?);
+                if t.has_error().is_err() || f.has_error().is_err() {
+                    return None;
+                }
+                if is_literal(t) || is_literal(f) {
+                    return None;
+                }
+                let common = self.common_ty(t, f);
+                if common.is_none() {
+                    let msg = format!(
+                        "true expression's type `{}` does not match false expression's type `{}`",
+                        self.ty_name(t),
+                        self.ty_name(f),
+                    );
+                    gcx.dcx().err(msg).code(error_code!(1080)).span(expr.span).emit();
+                }
+                common
+            }
+            hir::ExprKind::Tuple(&[Some(expr)]) => self.check_expr(expr),
+            hir::ExprKind::Tuple(exprs) => {
+                let tys = exprs
+                    .iter()
+                    .map(|expr| expr.and_then(|expr| self.check_expr(expr)))
+                    .collect::<Vec<_>>();
+                let tys = tys.into_iter().collect::<Option<Vec<_>>>()?;
+                Some(gcx.mk_ty(TyKind::Tuple(gcx.mk_tys(&tys))))
+            }
+            hir::ExprKind::Type(ref ty) => Some(gcx.mk_ty(TyKind::Type(gcx.type_of_hir_ty(ty)))),
+            hir::ExprKind::Unary(op, inner) => {
+                let ty = self.check_expr(inner)?;
+                self.check_unary(expr.span, op, ty)
+            }
+            hir::ExprKind::Array(exprs) => {
+                for expr in exprs {
+                    self.check_expr(expr);
+                }
+                None
+            }
+            hir::ExprKind::CallOptions(callee, options) => {
+                self.check_expr(callee);
+                for option in options {
+                    self.check_expr(&option.value);
+                }
+                None
+            }
+            hir::ExprKind::Delete(expr) => {
+                self.check_expr(expr);
+                None
+            }
+            hir::ExprKind::Slice(base, start, end) => {
+                self.check_expr(base);
+                for expr in [start, end].into_iter().flatten() {
+                    self.check_expr(expr);
+                }
+                None
+            }
+            hir::ExprKind::New(_) | hir::ExprKind::TypeCall(_) | hir::ExprKind::Err(_) => None,
+        }
+    }
+
+    /// Returns the type of an expression consisting of a name referring to the given item.
+    fn item_ty(&self, id: hir::ItemId) -> Option<Ty<'gcx>> {
+        let ty = self.gcx.type_of_item(id);
+        match id {
+            hir::ItemId::Variable(_) | hir::ItemId::Function(_) => Some(ty),
+            hir::ItemId::Contract(_)
+            | hir::ItemId::Struct(_)
+            | hir::ItemId::Enum(_)
+            | hir::ItemId::Udvt(_) => Some(ty.make_type_type(self.gcx)),
+            hir::ItemId::Event(_) | hir::ItemId::Error(_) => None,
+        }
+    }
+
+    fn check_call(
+        &self,
+        callee: &hir::Expr<'_>,
+        args: &hir::CallArgs<'_>,
+        span: Span,
+    ) -> Option<Ty<'gcx>> {
+        let gcx = self.gcx;
+        if let hir::ExprKind::Ident(&[Res::Item(id @ hir::ItemId::Function(_))]) = callee.kind {
+            self.check_call_args(Some(id), args, span);
+            let returns = gcx.type_of_item(id).returns()?;
+            return Some(match returns {
+                [ret] => *ret,
+                rets => gcx.mk_ty(TyKind::Tuple(rets)),
+            });
+        }
+
+        let callee_ty = self.check_expr(callee);
+        let arg_tys = args.exprs().map(|arg| self.check_expr(arg)).collect::<Vec<_>>();
+        match callee_ty?.kind {
+            // Type conversions and struct constructors.
+            TyKind::Type(ty) if ty.is_reference_type() => {
+                let loc = match arg_tys[..] {
+                    [Some(arg)] if !matches!(ty.kind, TyKind::Struct(_)) => match arg.kind {
+                        TyKind::Ref(_, loc) => loc,
+                        _ => DataLocation::Memory,
+                    },
+                    _ => DataLocation::Memory,
+                };
+                Some(ty.with_loc(gcx, loc))
+            }
+            TyKind::Type(ty) => Some(ty),
+            TyKind::FnPtr(f) => match f.returns {
+                [] => None,
+                [ret] => Some(*ret),
+                rets => Some(gcx.mk_ty(TyKind::Tuple(rets))),
+            },
+            _ => None,
+        }
+    }
+
+    fn check_unary(&self, span: Span, op: hir::UnOp, ty: Ty<'gcx>) -> Option<Ty<'gcx>> {
+        use ElementaryType::*;
+
+        if ty.has_error().is_err() {
+            return None;
+        }
+        let ty = peel_value_ref(ty);
+        let valid = match (op.kind, &ty.kind) {
+            (_, TyKind::Udvt(..) | TyKind::Elementary(Fixed(..) | UFixed(..))) => return None,
+            (UnOpKind::Not, TyKind::Elementary(Bool)) => true,
+            (UnOpKind::Not, _) => false,
+            (UnOpKind::Neg | UnOpKind::BitNot, TyKind::IntLiteral(_)) => return Some(ty),
+            (_, TyKind::IntLiteral(_)) => return None,
+            (UnOpKind::Neg, TyKind::Elementary(Int(_))) => true,
+            (UnOpKind::BitNot, TyKind::Elementary(Int(_) | UInt(_) | FixedBytes(_))) => true,
+            (
+                UnOpKind::PreInc | UnOpKind::PreDec | UnOpKind::PostInc | UnOpKind::PostDec,
+                TyKind::Elementary(Int(_) | UInt(_)),
+            ) => true,
+            (_, kind) if is_checkable(kind) => false,
+            _ => return None,
+        };
+        if !valid {
+            let msg =
+                format!("unary operator `{op}` cannot be applied to type `{}`", self.ty_name(ty));
+            self.gcx.dcx().err(msg).code(error_code!(4907)).span(span).emit();
+            return None;
+        }
+        Some(if op.kind == UnOpKind::Not { self.gcx.types.bool } else { ty })
+    }
+
+    /// Checks the operand types of a binary operation, and returns the type of the result.
+    ///
+    /// `code` is the error code to emit, which differs for compound assignments.
+    fn check_binary(
+        &self,
+        span: Span,
+        l: Ty<'gcx>,
+        op: hir::BinOp,
+        r: Ty<'gcx>,
+        code: DiagnosticId,
+    ) -> Option<Ty<'gcx>> {
+        use BinOpKind::*;
+
+        if l.has_error().is_err() || r.has_error().is_err() {
+            return None;
+        }
+        let (l, r) = (peel_value_ref(l), peel_value_ref(r));
+        if !is_checkable(&l.kind) || !is_checkable(&r.kind) {
+            return None;
+        }
+        // Operators can be defined for user-defined value types with `using for`.
+        if [l, r].iter().any(|ty| {
+            matches!(
+                ty.kind,
+                TyKind::Udvt(..)
+                    | TyKind::Elementary(ElementaryType::Fixed(..) | ElementaryType::UFixed(..))
+            )
+        }) {
+            return None;
+        }
+        // Operations on literals are evaluated at compile time.
+        if is_literal(l) && is_literal(r) {
+            return None;
+        }
+
+        let bool = self.gcx.types.bool;
+        let common = self.common_ty(l, r);
+        let result = match op.kind {
+            And | Or => (l == bool && r == bool).then_some(bool),
+            Eq | Ne => common.filter(|ty| is_comparable(*ty)).map(|_| bool),
+            Lt | Le | Gt | Ge => common.filter(|ty| is_ordered(*ty)).map(|_| bool),
+            Add | Sub | Mul | Div | Rem => common.filter(|ty| is_int(*ty)),
+            BitAnd | BitOr | BitXor => common.filter(|ty| is_int(*ty) || is_fixed_bytes(*ty)),
+            // The type of the result depends on the value of the literal.
+            Shl | Shr | Sar | Pow if is_literal(l) => return None,
+            Shl | Shr | Sar => {
+                let valid_l = is_int(l) || is_fixed_bytes(l);
+                (valid_l && (is_uint(r) || is_literal(r))).then_some(l)
+            }
+            Pow => (is_int(l) && (is_uint(r) || is_literal(r))).then_some(l),
+        };
+        if result.is_none() {
+            let msg = format!(
+                "operator `{op}` not compatible with types `{}` and `{}`",
+                self.ty_name(l),
+                self.ty_name(r),
+            );
+            self.gcx.dcx().err(msg).code(code).span(span).emit();
+        }
+        result
+    }
+
+    /// Returns the type that both types can be implicitly converted to, if any.
+    fn common_ty(&self, a: Ty<'gcx>, b: Ty<'gcx>) -> Option<Ty<'gcx>> {
+        if self.is_implicitly_convertible(a, b) {
+            Some(b)
+        } else if self.is_implicitly_convertible(b, a) {
+            Some(a)
+        } else {
+            None
+        }
+    }
+
+    /// Returns `true` if `from` can be implicitly converted to `to`.
+    ///
+    /// Returns `true` for conversions that are not modeled yet.
+    fn is_implicitly_convertible(&self, from: Ty<'gcx>, to: Ty<'gcx>) -> bool {
+        use ElementaryType::*;
+
+        if from == to || from.has_error().is_err() || to.has_error().is_err() {
+            return true;
+        }
+        let (from, to) = (peel_value_ref(from), peel_value_ref(to));
+        if !is_checkable(&from.kind) || !is_checkable(&to.kind) {
+            return true;
+        }
+        match (&from.kind, &to.kind) {
+            (TyKind::Elementary(a), TyKind::Elementary(b)) => match (*a, *b) {
+                (Fixed(..) | UFixed(..), _) | (_, Fixed(..) | UFixed(..)) => true,
+                (UInt(a), UInt(b)) | (Int(a), Int(b)) | (FixedBytes(a), FixedBytes(b)) => {
+                    a.bytes() <= b.bytes()
+                }
+                (UInt(a), Int(b)) => a.bytes() < b.bytes(),
+                (Address(a), Address(b)) => a || !b,
+                _ => a == b,
+            },
+
+            // The sign of the literal is not known here.
+            (TyKind::IntLiteral(size), TyKind::Elementary(UInt(b) | Int(b))) => {
+                size.bytes_raw() <= b.bytes()
+            }
+            (TyKind::IntLiteral(_), TyKind::Elementary(Fixed(..) | UFixed(..) | FixedBytes(_))) => {
+                true
+            }
+            (TyKind::IntLiteral(_), _) => false,
+
+            (TyKind::StringLiteral(_, size), TyKind::Elementary(FixedBytes(b))) => {
+                size.bytes_raw() <= b.bytes()
+            }
+            (TyKind::StringLiteral(utf8, _), TyKind::Elementary(String)) => *utf8,
+            (TyKind::StringLiteral(_, _), TyKind::Elementary(Bytes)) => true,
+            (TyKind::StringLiteral(utf8, _), TyKind::Ref(inner, _)) => match inner.kind {
+                TyKind::Elementary(String) => *utf8,
+                TyKind::Elementary(Bytes) => true,
+                _ => false,
+            },
+            (TyKind::StringLiteral(..), _) => false,
+
+            (TyKind::Ref(a, from_loc), TyKind::Ref(b, to_loc)) => {
+                if *to_loc == DataLocation::Calldata && *from_loc != DataLocation::Calldata {
+                    return false;
+                }
+                same_reference_type(*a, *b)
+            }
+            (TyKind::Ref(a, _), _) if to.is_reference_type() => same_reference_type(*a, to),
+            (_, TyKind::Ref(b, _)) if from.is_reference_type() => same_reference_type(from, *b),
+            (TyKind::Ref(..), _) | (_, TyKind::Ref(..)) => false,
+
+            (TyKind::Contract(a), TyKind::Contract(b)) => self.gcx.hir.is_derived_from(*a, *b),
+
+            (TyKind::Tuple(a), TyKind::Tuple(b)) => {
+                a.len() == b.len()
+                    && a.iter().zip(b).all(|(&a, &b)| self.is_implicitly_convertible(a, b))
+            }
+
+            (TyKind::Contract(_) | TyKind::Enum(_) | TyKind::Udvt(..) | TyKind::Tuple(_), _)
+            | (_, TyKind::Contract(_) | TyKind::Enum(_) | TyKind::Udvt(..) | TyKind::Tuple(_)) => {
+                false
+            }
+
+            _ => true,
+        }
+    }
+
+    /// Returns a solc-like name of the type, for diagnostics.
+    fn ty_name(&self, ty: Ty<'gcx>) -> String {
+        match ty.kind {
+            TyKind::IntLiteral(_) => "int_const".to_string(),
+            TyKind::StringLiteral(..) => "literal_string".to_string(),
+            TyKind::Tuple(tys) => {
+                let tys = tys.iter().map(|&ty| self.ty_name(ty)).collect::<Vec<_>>();
+                format!("tuple({})", tys.join(","))
+            }
+            TyKind::Type(ty) | TyKind::Meta(ty) => format!("type({})", self.ty_name(ty)),
+            TyKind::Error(..)
+            | TyKind::Event(..)
+            | TyKind::Module(_)
+            | TyKind::BuiltinModule(_)
+            | TyKind::Err(_) => format!("{:?}", ty.kind),
+            _ => {
+                let mut s = String::new();
+                TySolcPrinter::new(self.gcx, &mut s).data_locations(true).print(ty).unwrap();
+                s
+            }
+        }
+    }
+}
+
+/// Returns the type of the given builtin, if it is a value.
+fn builtin_ty(gcx: Gcx<'_>, builtin: Builtin) -> Option<Ty<'_>> {
+    match builtin {
+        Builtin::This
+        | Builtin::Super
+        | Builtin::TypeMin
+        | Builtin::TypeMax
+        | Builtin::UdvtWrap
+        | Builtin::UdvtUnwrap => None,
+        _ => Some(builtin.ty(gcx)),
+    }
+}
+
+/// Removes the data location of references to value types.
+fn peel_value_ref(ty: Ty<'_>) -> Ty<'_> {
+    match ty.kind {
+        TyKind::Ref(inner, _) if inner.is_value_type() => inner,
+        _ => ty,
+    }
+}
+
+/// Returns `true` if the two reference types are the same, ignoring data locations.
+fn same_reference_type(a: Ty<'_>, b: Ty<'_>) -> bool {
+    let (a, b) = (a.peel_refs(), b.peel_refs());
+    if a == b {
+        return true;
+    }
+    match (&a.kind, &b.kind) {
+        (TyKind::Elementary(a), TyKind::Elementary(b)) => a == b,
+        (TyKind::Struct(a), TyKind::Struct(b)) => a == b,
+        (TyKind::DynArray(a), TyKind::DynArray(b)) => {
+            !(a.is_value_type() && b.is_value_type()) || a == b
+        }
+        (TyKind::Array(a, a_len), TyKind::Array(b, b_len)) => {
+            a_len == b_len && (!(a.is_value_type() && b.is_value_type()) || a == b)
+        }
+        (TyKind::Mapping(..), TyKind::Mapping(..)) => true,
+        (a, b) => !(is_reference_kind(a) && is_reference_kind(b)),
+    }
+}
+
+fn is_reference_kind(kind: &TyKind<'_>) -> bool {
+    matches!(
+        kind,
+        TyKind::Elementary(ElementaryType::String | ElementaryType::Bytes)
+            | TyKind::Struct(_)
+            | TyKind::DynArray(_)
+            | TyKind::Array(..)
+            | TyKind::Mapping(..)
+    )
+}
+
+/// Returns `true` if implicit conversions and operators are checked for types of this kind.
+fn is_checkable(kind: &TyKind<'_>) -> bool {
+    matches!(
+        kind,
+        TyKind::Elementary(_)
+            | TyKind::StringLiteral(..)
+            | TyKind::IntLiteral(_)
+            | TyKind::Ref(..)
+            | TyKind::DynArray(_)
+            | TyKind::Array(..)
+            | TyKind::Tuple(_)
+            | TyKind::Contract(_)
+            | TyKind::Struct(_)
+            | TyKind::Enum(_)
+            | TyKind::Udvt(..)
+    )
+}
+
+fn is_literal(ty: Ty<'_>) -> bool {
+    matches!(ty.kind, TyKind::IntLiteral(_) | TyKind::StringLiteral(..))
+}
+
+fn is_int(ty: Ty<'_>) -> bool {
+    matches!(ty.kind, TyKind::Elementary(ElementaryType::Int(_) | ElementaryType::UInt(_)))
+}
+
+fn is_uint(ty: Ty<'_>) -> bool {
+    matches!(ty.kind, TyKind::Elementary(ElementaryType::UInt(_)))
+}
+
+fn is_fixed_bytes(ty: Ty<'_>) -> bool {
+    matches!(ty.kind, TyKind::Elementary(ElementaryType::FixedBytes(_)))
+}
+
+/// Returns `true` if values of the type can be compared with `==` and `!=`.
+fn is_comparable(ty: Ty<'_>) -> bool {
+    matches!(ty.kind, TyKind::Elementary(_) | TyKind::Contract(_) | TyKind::Enum(_))
+        && ty.is_value_type()
+}
+
+/// Returns `true` if values of the type can be compared with `<`, `<=`, `>` and `>=`.
+fn is_ordered(ty: Ty<'_>) -> bool {
+    use ElementaryType::*;
+
+    matches!(
+        ty.kind,
+        TyKind::Elementary(Int(_) | UInt(_) | FixedBytes(_) | Address(_))
+            | TyKind::Contract(_)
+            | TyKind::Enum(_)
+    )
+}
@@ -1,21 +1,56 @@
 use crate::{
     ast_lowering::resolve::{Declaration, Declarations},
+    eval::{ConstantEvaluator, EvalErrorKind},
     hir::{self, Res},
     ty::{Gcx, Ty},
 };
-use rayon::prelude::*;
-use solar_data_structures::{map::FxHashSet, parallel};
+use solar_data_structures::map::FxHashSet;
 
+mod body;
+mod overrides;
+mod shadowing;
+mod unreachable;
+mod unused;
+
+#[instrument(name = "typeck", level = "debug", skip_all)]
 pub(crate) fn check(gcx: Gcx<'_>) {
-    parallel!(
-        gcx.sess,
-        gcx.hir.par_contract_ids().for_each(|id| {
-            check_duplicate_definitions(gcx, &gcx.symbol_resolver.contract_scopes[id]);
-        }),
-        gcx.hir.par_source_ids().for_each(|id| {
-            check_duplicate_definitions(gcx, &gcx.symbol_resolver.source_scopes[id]);
-        }),
-    );
+    crate::par_for_each_ordered(gcx.sess, gcx.hir.par_contract_ids(), |id| {
+        check_duplicate_definitions(gcx, &gcx.symbol_resolver.contract_scopes[id]);
+        overrides::check_contract(gcx, id);
+    });
+    crate::par_for_each_ordered(gcx.sess, gcx.hir.par_source_ids(), |id| {
+        check_duplicate_definitions(gcx, &gcx.symbol_resolver.source_scopes[id]);
+        unused::check_imports(gcx, id);
+        crate::license::check_source(gcx, id);
+    });
+    crate::par_for_each_ordered(gcx.sess, gcx.hir.par_variable_ids(), |id| {
+        check_constant(gcx, id);
+        overrides::check_variable(gcx, id);
+        shadowing::check_variable(gcx, id);
+    });
+    crate::par_for_each_ordered(gcx.sess, gcx.hir.par_function_ids(), |id| {
+        overrides::check_function(gcx, id);
+        body::check_function(gcx, id);
+        unused::check_function(gcx, id);
+        unreachable::check_function(gcx, id);
+        crate::emit::check_inheritdoc(gcx, id);
+    });
+}
+
+/// Checks that the value of a `constant` variable fits in its type, if it can be evaluated.
+fn check_constant(gcx: Gcx<'_>, id: hir::VariableId) {
+    let var = gcx.hir.variable(id);
+    if !var.is_constant() {
+        return;
+    }
+    let Some(init) = var.initializer else { return };
+    let mut evaluator = ConstantEvaluator::new(gcx);
+    if let Err(err) = evaluator.try_eval_as(init, gcx.type_of_item(id.into())) {
+        // Only report errors in the value itself, since most constants cannot be evaluated.
+        if !err.kind.is_unsupported() && !matches!(err.kind, EvalErrorKind::RecursionLimitReached) {
+            evaluator.emit_eval_error(init, err);
+        }
+    }
 }
 
 /// Checks for definitions that have the same name and parameter types in the given scope.
@@ -0,0 +1,329 @@
+//! Checking of `virtual` and `override` specifiers.
+//!
+//! Modified from [`solc`].
+//!
+//! [`solc`]: https://github.com/ethereum/solidity/blob/2694190d1dbbc90b001aa76f8d7bd0794923c343/libsolidity/analysis/OverrideChecker.cpp
+
+use super::same_external_params;
+use crate::{
+    hir::{self, ContractId, FunctionId, VariableId},
+    ty::Gcx,
+};
+use solar_ast::{FunctionKind, StateMutability, Visibility};
+use solar_interface::{error_code, Span};
+
+/// Checks the `virtual` and `override` specifiers of a single function that do not depend on
+/// inheritance.
+pub(super) fn check_function(gcx: Gcx<'_>, id: FunctionId) {
+    let func = gcx.hir.function(id);
+    if func.is_getter() {
+        return;
+    }
+    let span = name_span(gcx, id);
+    let Some(contract) = func.contract else {
+        if func.marked_virtual {
+            gcx.dcx().err("free functions cannot be virtual").span(span).emit();
+        }
+        if func.override_ {
+            gcx.dcx().err("free functions cannot override").span(span).emit();
+        }
+        return;
+    };
+    if !func.marked_virtual {
+        return;
+    }
+    if gcx.hir.contract(contract).kind.is_library() {
+        let what = if func.kind.is_modifier() { "modifiers" } else { "functions" };
+        gcx.dcx().err(format!("library {what} cannot be virtual")).span(span).emit();
+    } else if func.kind.is_function() && func.visibility == Visibility::Private {
+        gcx.dcx().err("`virtual` and `private` cannot be used together").span(span).emit();
+    }
+}
+
+/// Checks the `override` specifier of a variable that does not have a getter.
+pub(super) fn check_variable(gcx: Gcx<'_>, id: VariableId) {
+    let var = gcx.hir.variable(id);
+    if var.override_ && var.getter.is_none() {
+        let msg = "override can only be used with public state variables";
+        gcx.dcx().err(msg).span(name_span(gcx, id)).emit();
+    }
+}
+
+/// Checks that the functions, modifiers and public state variables of a contract correctly
+/// override the ones inherited from its base contracts.
+pub(super) fn check_contract(gcx: Gcx<'_>, id: ContractId) {
+    let own = overridable_functions(gcx, id).collect::<Vec<_>>();
+    for &func in &own {
+        let bases = overridden_functions(gcx, id, func);
+        check_override(gcx, func, &bases);
+    }
+    check_ambiguous_overrides(gcx, id, &own);
+}
+
+/// Checks a function against the base functions it directly overrides.
+fn check_override(gcx: Gcx<'_>, id: FunctionId, bases: &[FunctionId]) {
+    let func = gcx.hir.function(id);
+    let what = describe(func);
+    let span = name_span(gcx, id);
+
+    if bases.is_empty() {
+        if func.override_ {
+            let msg = format!("{what} has override specified but does not override anything");
+            gcx.dcx().err(msg).span(span).emit();
+        }
+        return;
+    }
+
+    let implements_interface = bases.len() == 1
+        && gcx.hir.contract(gcx.hir.function(bases[0]).contract.unwrap()).kind.is_interface();
+    if !func.override_ && !implements_interface {
+        let msg = format!("overriding {what} is missing `override` specifier");
+        gcx.dcx().err(msg).span(span).emit();
+    }
+
+    let mut base_contracts = Vec::<ContractId>::with_capacity(bases.len());
+    for &base_id in bases {
+        let base = gcx.hir.function(base_id);
+        let base_contract = base.contract.unwrap();
+        if !base_contracts.contains(&base_contract) {
+            base_contracts.push(base_contract);
+        }
+        let base_span = name_span(gcx, base_id);
+
+        if base.is_getter() {
+            gcx.dcx()
+                .err("cannot override public state variable")
+                .span(span)
+                .span_note(base_span, "overridden public state variable is here")
+                .emit();
+            continue;
+        }
+        if !base.virtual_ {
+            let base_what = if base.kind.is_modifier() { "modifier" } else { "function" };
+            gcx.dcx()
+                .err(format!("trying to override non-virtual {base_what}"))
+                .code(error_code!(4334))
+                .span(span)
+                .span_note(base_span, format!("overridden {base_what} is here"))
+                .emit();
+        }
+        if func.kind.is_modifier() {
+            continue;
+        }
+
+        if func.is_getter() {
+            if base.visibility != Visibility::External {
+                let msg = "public state variables can only override functions with external \
+                           visibility";
+                gcx.dcx()
+                    .err(msg)
+                    .span(span)
+                    .span_note(base_span, "overridden function is here")
+                    .emit();
+            }
+        } else if func.visibility != base.visibility
+            && !(base.visibility == Visibility::External && func.visibility == Visibility::Public)
+        {
+            gcx.dcx()
+                .err("overriding function visibility differs")
+                .code(error_code!(9098))
+                .span(span)
+                .span_note(base_span, "overridden function is here")
+                .emit();
+        }
+
+        if !is_allowed_mutability_change(base.state_mutability, func.state_mutability) {
+            let msg = format!(
+                "overriding {what} changes state mutability from `{}` to `{}`",
+                base.state_mutability, func.state_mutability,
+            );
+            gcx.dcx()
+                .err(msg)
+                .code(error_code!(6959))
+                .span(span)
+                .span_note(base_span, "overridden function is here")
+                .emit();
+        }
+
+        let mut ty = gcx.type_of_item(id.into());
+        let mut base_ty = gcx.type_of_item(base_id.into());
+        if base.visibility == Visibility::External {
+            ty = ty.as_externally_callable_function(gcx);
+            base_ty = base_ty.as_externally_callable_function(gcx);
+        }
+        if ty.returns() != base_ty.returns() {
+            gcx.dcx()
+                .err(format!("overriding {what} return types differ"))
+                .code(error_code!(4822))
+                .span(span)
+                .span_note(base_span, "overridden function is here")
+                .emit();
+        }
+    }
+
+    base_contracts.sort_unstable();
+
+    // The override list is required when overriding functions from multiple bases, and must
+    // otherwise only name contracts that define one of the overridden functions.
+    if base_contracts.len() > 1 {
+        let missing = base_contracts
+            .iter()
+            .filter(|c| !func.overrides.contains(c))
+            .map(|&c| format!("`{}`", gcx.item_name(c)))
+            .collect::<Vec<_>>();
+        if !missing.is_empty() {
+            let contracts = if missing.len() == 1 { "contract" } else { "contracts" };
+            let msg =
+                format!("{what} needs to specify overridden {contracts} {}", join_and(&missing));
+            gcx.dcx().err(msg).span(span).emit();
+        }
+    }
+    for &c in func.overrides {
+        if !base_contracts.contains(&c) {
+            let msg =
+                format!("invalid contract specified in override list: `{}`", gcx.item_name(c));
+            gcx.dcx().err(msg).span(span).emit();
+        }
+    }
+}
+
+/// Checks that functions inherited from multiple unrelated bases are overridden in the contract.
+fn check_ambiguous_overrides(gcx: Gcx<'_>, id: ContractId, own: &[FunctionId]) {
+    let mut inherited = gcx
+        .hir
+        .inherited_contracts(id)
+        .iter()
+        .flat_map(|&base| inheritable_functions(gcx, base))
+        .filter(|&f| !own.iter().any(|&g| is_same_callable(gcx, f, g)))
+        .collect::<Vec<_>>();
+    while let Some(f) = inherited.pop() {
+        let mut group = vec![f];
+        inherited.retain(|&g| {
+            let same = is_same_callable(gcx, f, g);
+            if same {
+                group.push(g);
+            }
+            !same
+        });
+        let mut group = most_derived(gcx, group);
+        if group.len() <= 1 {
+            continue;
+        }
+
+        let func = gcx.hir.function(f);
+        let what = if func.kind.is_modifier() { "modifier" } else { "function" };
+        let name = match func.name {
+            Some(name) => name.to_string(),
+            None => func.kind.to_str().to_string(),
+        };
+        let msg = format!("derived contract must override {what} `{name}`");
+        let mut err = gcx.dcx().err(msg).span(gcx.item_name(id).span).help(
+            "two or more base contracts define a function with the same name and parameter types",
+        );
+        group.sort_unstable();
+        for f in group {
+            let contract = gcx.hir.function(f).contract.unwrap();
+            let note = format!("definition in `{}`", gcx.item_name(contract));
+            err = err.span_note(name_span(gcx, f), note);
+        }
+        err.emit();
+    }
+}
+
+/// Returns the functions, modifiers and getters defined in the given contract that can override
+/// or be overridden.
+fn overridable_functions(
+    gcx: Gcx<'_>,
+    id: ContractId,
+) -> impl Iterator<Item = FunctionId> + Clone + use<'_> {
+    // Getters, `fallback` and `receive` are in the contract items. Note that `Contract::fallback`
+    // and `Contract::receive` may be inherited.
+    let c = gcx.hir.contract(id);
+    c.functions().filter(move |&f| !gcx.hir.function(f).kind.is_constructor())
+}
+
+/// Returns the functions of [`overridable_functions`] that are visible in derived contracts.
+fn inheritable_functions(
+    gcx: Gcx<'_>,
+    id: ContractId,
+) -> impl Iterator<Item = FunctionId> + Clone + use<'_> {
+    overridable_functions(gcx, id)
+        .filter(move |&f| gcx.hir.function(f).visibility != Visibility::Private)
+}
+
+/// Returns the base functions that are directly overridden by the given function.
+fn overridden_functions(gcx: Gcx<'_>, contract: ContractId, id: FunctionId) -> Vec<FunctionId> {
+    let bases = gcx
+        .hir
+        .inherited_contracts(contract)
+        .iter()
+        .flat_map(|&base| inheritable_functions(gcx, base))
+        .filter(|&base| is_same_callable(gcx, id, base))
+        .collect();
+    most_derived(gcx, bases)
+}
+
+/// Removes the functions that are defined in a base contract of another function's contract.
+fn most_derived(gcx: Gcx<'_>, mut functions: Vec<FunctionId>) -> Vec<FunctionId> {
+    let contract = |f: FunctionId| gcx.hir.function(f).contract.unwrap();
+    let all = functions.clone();
+    functions.retain(|&f| {
+        !all.iter().any(|&g| {
+            contract(g) != contract(f) && gcx.hir.is_derived_from(contract(g), contract(f))
+        })
+    });
+    functions
+}
+
+/// Returns `true` if the two functions have the same kind and signature.
+fn is_same_callable(gcx: Gcx<'_>, a: FunctionId, b: FunctionId) -> bool {
+    let (fa, fb) = (gcx.hir.function(a), gcx.hir.function(b));
+    if fa.kind != fb.kind {
+        return false;
+    }
+    match fa.kind {
+        FunctionKind::Function => {
+            fa.name.map(|n| n.name) == fb.name.map(|n| n.name)
+                && same_external_params(gcx, gcx.type_of_item(a.into()), gcx.type_of_item(b.into()))
+        }
+        FunctionKind::Modifier => fa.name.map(|n| n.name) == fb.name.map(|n| n.name),
+        _ => true,
+    }
+}
+
+/// Returns `true` if a function with mutability `base` can be overridden by one with `derived`.
+fn is_allowed_mutability_change(base: StateMutability, derived: StateMutability) -> bool {
+    base == derived
+        || matches!(
+            (base, derived),
+            (StateMutability::NonPayable, StateMutability::View | StateMutability::Pure)
+                | (StateMutability::View, StateMutability::Pure)
+        )
+}
+
+fn describe(func: &hir::Function<'_>) -> &'static str {
+    if func.is_getter() {
+        "public state variable"
+    } else if func.kind.is_modifier() {
+        "modifier"
+    } else {
+        "function"
+    }
+}
+
+/// Returns the span of the name of the given item, or the whole item if it has no name.
+fn name_span(gcx: Gcx<'_>, id: impl Into<hir::ItemId>) -> Span {
+    let id = id.into();
+    match gcx.item_name_opt(id) {
+        Some(name) => name.span,
+        None => gcx.item_span(id),
+    }
+}
+
+fn join_and(items: &[String]) -> String {
+    match items {
+        [] => String::new(),
+        [item] => item.clone(),
+        [init @ .., last] => format!("{} and {last}", init.join(", ")),
+    }
+}
@@ -0,0 +1,69 @@
+//! Warnings for function parameters and local variables that shadow other declarations.
+
+use crate::{
+    ast_lowering::resolve::Declaration,
+    hir::{self, Res},
+    lints,
+    ty::Gcx,
+};
+use solar_interface::{error_code, Ident};
+
+/// Warns if the given variable is a parameter or local variable that shadows a declaration of the
+/// enclosing contract, including inherited members, of the source, or a builtin.
+pub(super) fn check_variable(gcx: Gcx<'_>, id: hir::VariableId) {
+    let var = gcx.hir.variable(id);
+    let Some(function) = var.function else { return };
+    if !matches!(
+        var.kind,
+        hir::VarKind::FunctionParam
+            | hir::VarKind::FunctionReturn
+            | hir::VarKind::Statement
+            | hir::VarKind::TryCatch
+    ) || gcx.hir.function(function).is_getter()
+    {
+        return;
+    }
+    let Some(name) = var.name else { return };
+
+    let Some(shadowed) = shadowed_declaration(gcx, var.contract, var.source, name) else { return };
+    match shadowed.res {
+        Res::Builtin(_) => {
+            gcx.sess
+                .lint(&lints::SHADOWING, "this declaration shadows a builtin symbol")
+                .code(error_code!(2319))
+                .span(name.span)
+                .emit();
+        }
+        _ => {
+            gcx.sess
+                .lint(&lints::SHADOWING, "this declaration shadows an existing declaration")
+                .code(error_code!(2519))
+                .span(name.span)
+                .span_note(shadowed.span, "the shadowed declaration is here")
+                .emit();
+        }
+    }
+}
+
+/// Returns the declaration that `name` refers to outside of function bodies.
+fn shadowed_declaration(
+    gcx: Gcx<'_>,
+    contract: Option<hir::ContractId>,
+    source: hir::SourceId,
+    name: Ident,
+) -> Option<Declaration> {
+    let resolver = &gcx.symbol_resolver;
+    let scopes = contract
+        .map(|id| &resolver.contract_scopes[id])
+        .into_iter()
+        .chain(std::iter::once(&resolver.source_scopes[source]))
+        .chain(std::iter::once(&resolver.global_builtin_scope));
+    // Getters are declared with the same name as their state variable.
+    let is_getter = |decl: &Declaration| match decl.res {
+        Res::Item(hir::ItemId::Function(f)) => gcx.hir.function(f).is_getter(),
+        _ => false,
+    };
+    scopes
+        .filter_map(|scope| scope.resolve(name))
+        .find_map(|decls| decls.iter().find(|decl| !is_getter(*decl)).or(decls.first()).copied())
+}
@@ -0,0 +1,154 @@
+//! Warnings for statements that can never be executed.
+
+use crate::{
+    builtins::Builtin,
+    hir::{self, Res},
+    lints,
+    ty::Gcx,
+};
+use solar_ast::LitKind;
+use solar_interface::{error_code, Span};
+
+/// Warns about the unreachable statements in the body of the given function.
+pub(super) fn check_function(gcx: Gcx<'_>, id: hir::FunctionId) {
+    let Some(body) = gcx.hir.function(id).body else { return };
+    ReachabilityChecker { gcx, loops: Vec::new() }.check_block(body);
+}
+
+struct ReachabilityChecker<'gcx> {
+    gcx: Gcx<'gcx>,
+    /// The enclosing loops, innermost last.
+    loops: Vec<LoopState>,
+}
+
+struct LoopState {
+    span: Span,
+    /// Whether the loop contains a reachable `break`.
+    breaks: bool,
+    /// Whether the loop contains a reachable `continue`.
+    continues: bool,
+}
+
+impl ReachabilityChecker<'_> {
+    /// Checks a block, returning `true` if control flow can reach its end.
+    fn check_block(&mut self, block: hir::Block<'_>) -> bool {
+        for (i, stmt) in block.iter().enumerate() {
+            if !self.check_stmt(stmt) {
+                self.report(&block[i + 1..]);
+                return false;
+            }
+        }
+        true
+    }
+
+    /// Checks a statement, returning `true` if control flow can continue after it.
+    fn check_stmt(&mut self, stmt: &hir::Stmt<'_>) -> bool {
+        match stmt.kind {
+            hir::StmtKind::Return(_) | hir::StmtKind::Revert(..) => false,
+            hir::StmtKind::Break => {
+                if let Some(loop_) = self.loops.last_mut() {
+                    loop_.breaks = true;
+                }
+                false
+            }
+            hir::StmtKind::Continue => {
+                if let Some(loop_) = self.loops.last_mut() {
+                    loop_.continues = true;
+                }
+                false
+            }
+            hir::StmtKind::Expr(expr) => !is_diverging_call(expr),
+            hir::StmtKind::Block(block) | hir::StmtKind::UncheckedBlock(block) => {
+                self.check_block(block)
+            }
+            hir::StmtKind::If(cond, then, else_) => match bool_value(cond) {
+                Some(true) => {
+                    let reachable = self.check_stmt(then);
+                    if let Some(else_) = else_ {
+                        self.report(std::slice::from_ref(else_));
+                    }
+                    reachable
+                }
+                Some(false) => {
+                    self.report(std::slice::from_ref(then));
+                    else_.is_none_or(|else_| self.check_stmt(else_))
+                }
+                None => {
+                    let then_reachable = self.check_stmt(then);
+                    let else_reachable = else_.is_none_or(|else_| self.check_stmt(else_));
+                    then_reachable || else_reachable
+                }
+            },
+            // Loops are only exited with `break`, which is also how their condition is lowered.
+            hir::StmtKind::Loop(block, source) => {
+                self.loops.push(LoopState { span: stmt.span, breaks: false, continues: false });
+                match (source, block) {
+                    // The condition of `do while` loops and the next expression of `for` loops are
+                    // lowered at the end of the block, and are also reached with `continue`.
+                    (hir::LoopSource::DoWhile | hir::LoopSource::For, [body, latch]) => {
+                        let end_reachable = self.check_stmt(body);
+                        if end_reachable || self.loops.last().unwrap().continues {
+                            self.check_stmt(latch);
+                        } else {
+                            self.report(std::slice::from_ref(latch));
+                        }
+                    }
+                    _ => {
+                        self.check_block(block);
+                    }
+                }
+                self.loops.pop().unwrap().breaks
+            }
+            hir::StmtKind::Try(try_) => {
+                let mut reachable = self.check_block(try_.block);
+                for catch in try_.catch {
+                    reachable |= self.check_block(catch.block);
+                }
+                reachable
+            }
+            hir::StmtKind::DeclSingle(_)
+            | hir::StmtKind::DeclMulti(..)
+            | hir::StmtKind::Emit(..)
+            | hir::StmtKind::Placeholder
+            | hir::StmtKind::Err(_) => true,
+        }
+    }
+
+    /// Reports the given statements as unreachable, with a single warning.
+    fn report(&self, stmts: &[hir::Stmt<'_>]) {
+        // Ignore the statements introduced by desugaring the enclosing loop, which have its span.
+        let loop_span = self.loops.last().map(|loop_| loop_.span);
+        let mut spans = stmts.iter().map(|stmt| stmt.span).filter(|&span| Some(span) != loop_span);
+        let Some(first) = spans.next() else { return };
+        let span = first.to(spans.last().unwrap_or(first));
+        let msg = "unreachable code";
+        self.gcx.sess.lint(&lints::UNREACHABLE_CODE, msg).code(error_code!(5740)).span(span).emit();
+    }
+}
+
+/// Returns `true` if the expression is a call that always reverts, like `revert()` or
+/// `require(false)`.
+fn is_diverging_call(expr: &hir::Expr<'_>) -> bool {
+    let hir::ExprKind::Call(callee, ref args) = expr.kind else { return false };
+    let hir::ExprKind::Ident(res) = callee.kind else { return false };
+    !res.is_empty()
+        && res.iter().all(|res| match res {
+            Res::Builtin(Builtin::Revert | Builtin::RevertMsg) => true,
+            Res::Builtin(Builtin::Require | Builtin::RequireMsg | Builtin::Assert) => {
+                args.exprs().next().and_then(bool_value) == Some(false)
+            }
+            _ => false,
+        })
+}
+
+/// Returns the value of a boolean literal, possibly parenthesized.
+fn bool_value(expr: &hir::Expr<'_>) -> Option<bool> {
+    match expr.kind {
+        hir::ExprKind::Lit(lit) => match lit.kind {
+            LitKind::Bool(value) => Some(value),
+            _ => None,
+        },
+        hir::ExprKind::Tuple(&[Some(expr)]) => bool_value(expr),
+        _ => None,
+    }
+}
@@ -0,0 +1,143 @@
+//! Warnings for unused local variables, function parameters and imports.
+//!
+//! Names starting with an underscore are never reported, and neither are the parameters of functions
+//! that are not implemented or have an empty body.
+
+use crate::{
+    hir::{self, Res},
+    lints,
+    ty::Gcx,
+};
+use solar_data_structures::map::FxHashSet;
+use solar_interface::{diagnostics::Applicability, error_code, Ident, Span};
+
+/// Warns about the unused parameters and local variables of the given function.
+pub(super) fn check_function(gcx: Gcx<'_>, id: hir::FunctionId) {
+    let func = gcx.hir.function(id);
+    // Parameters of functions without an implementation, or with an empty one, are only declared
+    // for the signature.
+    let Some(body) = func.body.filter(|body| !body.is_empty()) else { return };
+
+    // Modifier invocations and base constructor calls are not lowered yet, so their arguments are
+    // not resolved.
+    let mut vars = if func.modifiers.is_empty() { func.parameters.to_vec() } else { Vec::new() };
+    // Neither are assembly blocks, so the variables they use are unknown.
+    if !collect_block_vars(body, &mut vars) {
+        return;
+    }
+
+    for var_id in vars {
+        let var = gcx.hir.variable(var_id);
+        let Some(name) = var.name else { continue };
+        if name.as_str().starts_with('_') || is_used(gcx, Res::Item(var_id.into())) {
+            continue;
+        }
+        let (msg, code) = match var.kind {
+            hir::VarKind::FunctionParam => ("unused function parameter", error_code!(5667)),
+            hir::VarKind::TryCatch => ("unused try/catch parameter", error_code!(5667)),
+            _ => ("unused local variable", error_code!(2072)),
+        };
+        gcx.sess
+            .lint(&lints::UNUSED_VARIABLES, msg)
+            .code(code)
+            .span(name.span)
+            .span_suggestion(
+                name.span,
+                "if this is intentional, prefix it with an underscore",
+                format!("_{name}"),
+                Applicability::MaybeIncorrect,
+            )
+            .emit();
+    }
+}
+
+/// Warns about the imports of the given source whose names are not used.
+///
+/// Names are considered used if they are referred to in the source or in any source that imports
+/// it, directly or indirectly, since imported names are also exported.
+pub(super) fn check_imports(gcx: Gcx<'_>, id: hir::SourceId) {
+    let imports = gcx.symbol_resolver.imports.iter().filter(|import| import.source == id);
+    let mut imports = imports.peekable();
+    if imports.peek().is_none() {
+        return;
+    }
+
+    let mut visible_in = FxHashSet::default();
+    let mut stack = vec![id];
+    while let Some(source) = stack.pop() {
+        if visible_in.insert(source) {
+            stack.extend(gcx.importers(source));
+        }
+    }
+    let is_visible = |span: Span| {
+        gcx.hir
+            .source_ids()
+            .find(|&source| gcx.hir.source(source).file.contains(span.lo()))
+            .is_some_and(|source| visible_in.contains(&source))
+    };
+    // The same declaration can be imported under multiple names.
+    let refers_to = |span: Span, name: Ident| {
+        gcx.sess.source_map().span_to_snippet(span).is_ok_and(|s| s == name.as_str())
+    };
+
+    for import in imports {
+        let used = import.res.iter().any(|&res| {
+            gcx.references(res).into_iter().any(|span| {
+                !import.span.contains(span)
+                    && import.name.is_none_or(|name| refers_to(span, name))
+                    && is_visible(span)
+            })
+        });
+        if !used {
+            let span = import.name.map_or(import.span, |name| name.span);
+            gcx.sess.lint(&lints::UNUSED_IMPORTS, "unused import").span(span).emit();
+        }
+    }
+}
+
+fn is_used(gcx: Gcx<'_>, res: Res) -> bool {
+    !gcx.references(res).is_empty()
+}
+
+/// Collects the variables declared in the given block into `vars`.
+///
+/// Returns `false` if the block contains an assembly block.
+fn collect_block_vars(block: hir::Block<'_>, vars: &mut Vec<hir::VariableId>) -> bool {
+    block.iter().all(|stmt| collect_stmt_vars(stmt, vars))
+}
+
+fn collect_stmt_vars(stmt: &hir::Stmt<'_>, vars: &mut Vec<hir::VariableId>) -> bool {
+    match stmt.kind {
+        hir::StmtKind::DeclSingle(id) => vars.push(id),
+        hir::StmtKind::DeclMulti(ids, _) => vars.extend(ids.iter().flatten()),
+        hir::StmtKind::Block(block)
+        | hir::StmtKind::UncheckedBlock(block)
+        | hir::StmtKind::Loop(block, _) => return collect_block_vars(block, vars),
+        hir::StmtKind::If(_, then, else_) => {
+            return collect_stmt_vars(then, vars)
+                && else_.is_none_or(|else_| collect_stmt_vars(else_, vars));
+        }
+        hir::StmtKind::Try(try_) => {
+            vars.extend(try_.returns);
+            if !collect_block_vars(try_.block, vars) {
+                return false;
+            }
+            for catch in try_.catch {
+                vars.extend(catch.args);
+                if !collect_block_vars(catch.block, vars) {
+                    return false;
+                }
+            }
+        }
+        // Programs with errors are not type checked, so this is an assembly block.
+        hir::StmtKind::Err(_) => return false,
+        hir::StmtKind::Emit(..)
+        | hir::StmtKind::Revert(..)
+        | hir::StmtKind::Return(_)
+        | hir::StmtKind::Break
+        | hir::StmtKind::Continue
+        | hir::StmtKind::Expr(_)
+        | hir::StmtKind::Placeholder => {}
+    }
+    true
+}
@@ -10,12 +10,16 @@
 #[doc(inline)]
 pub use solar_ast as ast;
 #[doc(inline)]
+pub use solar_codegen as codegen;
+#[doc(inline)]
 pub use solar_config as config;
 #[doc(inline)]
 pub use solar_data_structures as data_structures;
 #[doc(inline)]
 pub use solar_interface as interface;
 #[doc(inline)]
+pub use solar_lint as lint;
+#[doc(inline)]
 pub use solar_macros as macros;
 #[doc(inline)]
 pub use solar_parse as parse;
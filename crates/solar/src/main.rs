@@ -12,11 +12,11 @@ static ALLOC: utils::Allocator = utils::new_allocator();
 fn main() -> ExitCode {
     sigsegv_handler::install();
     panic_hook::install();
-    let _guard = utils::init_logger();
     let args = match parse_args(std::env::args_os()) {
         Ok(args) => args,
         Err(e) => e.exit(),
     };
+    let _guard = utils::init_logger_with(&args);
     match run_compiler_args(args) {
         Ok(()) => ExitCode::SUCCESS,
         Err(_) => ExitCode::FAILURE,
@@ -0,0 +1,34 @@
+use solar::{
+    interface::{diagnostics::EmittedDiagnostics, Session},
+    sema::ParsingContext,
+};
+use std::path::Path;
+
+#[test]
+fn main() -> Result<(), EmittedDiagnostics> {
+    let path = Path::new("src/Counter.sol");
+
+    let sess = Session::builder().with_buffer_emitter(solar::interface::ColorChoice::Auto).build();
+
+    let _ = sess.enter(|| -> solar::interface::Result<()> {
+        // Load the file into a new parsing context.
+        let mut pcx = ParsingContext::new(&sess);
+        pcx.load_file(path)?;
+
+        // Parse and analyze the sources, then query the ABI of every contract.
+        let abis = pcx.parse_and_resolve_with(|gcx| {
+            gcx.hir
+                .contract_ids()
+                .map(|id| {
+                    (gcx.contract_fully_qualified_name(id).to_string(), gcx.contract_json_abi(id))
+                })
+                .collect::<Vec<_>>()
+        })?;
+        for (name, abi) in abis.unwrap_or_default() {
+            println!("{name}: {}", serde_json::to_string_pretty(&abi).unwrap());
+        }
+        Ok(())
+    });
+
+    sess.emitted_errors().unwrap()
+}
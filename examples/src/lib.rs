@@ -3,4 +3,6 @@
 #![allow(unreachable_pub)]
 #![cfg(test)]
 
+mod abi;
 mod parser;
+mod virtual_files;
@@ -0,0 +1,39 @@
+use solar::{
+    interface::{
+        diagnostics::EmittedDiagnostics,
+        source_map::{InMemoryFileLoader, SourceMap},
+        Session,
+    },
+    sema::ParsingContext,
+};
+use std::{path::Path, sync::Arc};
+
+#[test]
+fn main() -> Result<(), EmittedDiagnostics> {
+    // Register the sources in memory. Relative imports resolve like they would on disk.
+    let files = InMemoryFileLoader::new();
+    files.insert(
+        "src/Token.sol",
+        "import \"./lib/Math.sol\";\ncontract Token { uint x = Math.one(); }",
+    );
+    files.insert(
+        "src/lib/Math.sol",
+        "library Math { function one() internal pure returns (uint) { return 1; } }",
+    );
+
+    // Load the sources from memory instead of the file system.
+    let mut source_map = SourceMap::empty();
+    source_map.set_file_loader(files);
+    let sess = Session::builder()
+        .source_map(Arc::new(source_map))
+        .with_buffer_emitter(solar::interface::ColorChoice::Auto)
+        .build();
+
+    let _ = sess.enter(|| -> solar::interface::Result<()> {
+        let mut pcx = ParsingContext::new(&sess);
+        pcx.load_file(Path::new("src/Token.sol"))?;
+        pcx.parse_and_resolve()
+    });
+
+    sess.emitted_errors().unwrap()
+}
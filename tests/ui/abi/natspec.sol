@@ -0,0 +1,32 @@
+//@ignore-host: windows
+//@compile-flags: --emit=docs --pretty-json
+
+/// @title A title
+/// @author Someone
+/// @notice Contract notice
+/// @dev Contract details
+/// @custom:security none
+contract C {
+    /// @notice The value
+    /// @dev Stored value
+    uint256 public value;
+
+    /// @notice Emitted on set
+    /// @param x The new value
+    event Set(uint256 x);
+
+    /// @notice Setting failed
+    error Failed(uint256 x);
+
+    /**
+     * @notice Sets the value
+     * @dev Details
+     * @param x The new value
+     * @return ok Whether it succeeded
+     */
+    function set(uint256 x) public virtual returns (bool ok) {}
+}
+
+contract D is C {
+    function set(uint256 x) public override returns (bool ok) {}
+}
@@ -0,0 +1,21 @@
+//@ignore-host: windows
+//@compile-flags: --emit=storage-layout --pretty-json
+
+contract A {
+    uint128 a;
+    bool b;
+    address c;
+}
+
+contract B is A {
+    struct S {
+        uint8 x;
+        uint256 y;
+    }
+
+    uint256 constant K = 1;
+    mapping(address => uint256) m;
+    S s;
+    uint8[3] arr;
+    string str;
+}
@@ -0,0 +1,14 @@
+//@compile-flags: --emit=bin
+//@normalize-stderr-test: "\d+ bytes" -> "N bytes"
+
+contract C { //~ WARN: contract code size
+    uint256 n;
+
+    modifier m1() { _; _; _; _; _; _; _; _; }
+    modifier m2() { _; _; _; _; _; _; _; _; }
+    modifier m3() { _; _; _; _; _; _; _; _; }
+    modifier m4() { _; _; _; _; _; _; _; _; }
+
+    // The body is inlined 8^4 times.
+    function f() external m1 m2 m3 m4 { n = 0; }
+}
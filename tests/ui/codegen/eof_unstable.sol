@@ -0,0 +1,3 @@
+//@compile-flags: --emit=bin --evm-version=osaka
+
+contract C {} //~ ERROR: generating EOF code for the `osaka` EVM version is unstable
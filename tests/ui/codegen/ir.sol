@@ -0,0 +1,6 @@
+//@ignore-host: windows
+//@compile-flags: --emit=ir --pretty-json
+
+contract C {
+    function f() external {}
+}
@@ -0,0 +1,11 @@
+//@compile-flags: --emit=bin
+
+contract C {
+    function f(uint256 x) external pure returns (uint256) {
+        return g(g(g(g(g(g(g(g(x)))))))); //~ ERROR: stack too deep
+    }
+
+    function g(uint256 y) internal pure returns (uint256) {
+        return y;
+    }
+}
@@ -0,0 +1,9 @@
+//@compile-flags: --emit=bin
+
+contract C {
+    string reason;
+
+    function f(uint256 x) external view {
+        require(x != 0, reason); //~ ERROR: non-literal revert reasons is not yet supported
+    }
+}
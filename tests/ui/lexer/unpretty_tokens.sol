@@ -0,0 +1,2 @@
+//@compile-flags: -Zunpretty=tokens --stop-after=parsing
+contract C { uint x = 0x1; }
@@ -0,0 +1,9 @@
+//@compile-flags: --deny-warnings -A 5740
+
+contract C {
+    function f() public pure {
+        uint256 x; //~ ERROR: unused local variable
+        return;
+        f();
+    }
+}
@@ -0,0 +1,9 @@
+//@compile-flags: -A unreachable-code -D unused_variables
+
+contract C {
+    function f() public pure {
+        uint256 x; //~ ERROR: unused local variable
+        return;
+        f();
+    }
+}
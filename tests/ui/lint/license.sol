@@ -0,0 +1,7 @@
+// SPDX-License-Identifier: MIT OR Foo-1.0
+//~^ WARN: unknown SPDX license identifier `Foo-1.0`
+
+/* SPDX-License-Identifier: GPL-3.0 */
+//~^ WARN: multiple SPDX license identifiers found in source file
+
+contract C {}
@@ -0,0 +1,3 @@
+//@compile-flags: -Wmissing-license
+
+contract C {}
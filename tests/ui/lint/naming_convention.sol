@@ -0,0 +1,27 @@
+//@compile-flags: -W naming-convention
+
+contract my_contract { //~ WARN: contract name `my_contract` should be in CapWords
+    struct point { //~ WARN: struct name `point` should be in CapWords
+        uint256 x;
+    }
+    enum Color {
+        Red
+    }
+    type amount is uint256; //~ WARN: type name `amount` should be in CapWords
+    event transfer(); //~ WARN: event name `transfer` should be in CapWords
+    error Bad_Thing(); //~ WARN: error name `Bad_Thing` should be in CapWords
+
+    function DoThing() public {} //~ WARN: function name `DoThing` should be in mixedCase
+    function do_thing() public {} //~ WARN: function name `do_thing` should be in mixedCase
+    function doThing() public {}
+    function _doThing() internal {}
+
+    modifier OnlyOwner() { //~ WARN: modifier name `OnlyOwner` should be in mixedCase
+        _;
+    }
+
+    constructor() {}
+    receive() external payable {}
+}
+
+interface IERC20 {}
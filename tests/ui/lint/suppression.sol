@@ -0,0 +1,28 @@
+//@compile-flags: --verbose
+
+contract C {
+    function f() public pure {
+        // solar-disable-next-line unused-variables
+        uint256 x;
+        uint256 y; //~ WARN: unused local variable
+        // solar-disable-next-line shadowing
+        uint256 z; //~ WARN: unused local variable
+    }
+
+    // solar-disable unused_variables, shadowing
+    function g(uint256 a) public pure {
+        uint256 msg;
+    }
+    // solar-enable shadowing
+
+    function h() public pure {
+        uint256 msg; //~ WARN: this declaration shadows a builtin symbol
+    }
+    // solar-enable
+
+    function i() public pure {
+        return;
+        // solar-disable-next-line
+        i();
+    }
+}
@@ -0,0 +1,19 @@
+//@compile-flags: --legacy-syntax
+
+contract Legacy {
+    uint public created;
+
+    function Legacy() public {
+        created = now;
+    }
+
+    function check(uint a) public constant returns (uint) {
+        var b = a + 1; //~ WARN: the types of `var` declarations are not inferred
+        var (c, , d) = (b, a, created); //~ WARN: the types of `var` declarations are not inferred
+        if (c == 0) {
+            throw;
+        }
+        for (var i = 0; i < d; i++) {} //~ WARN: the types of `var` declarations are not inferred
+        return c;
+    }
+}
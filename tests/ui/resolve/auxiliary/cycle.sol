@@ -0,0 +1,3 @@
+import "../import_cycle.sol" as Main;
+
+contract D {}
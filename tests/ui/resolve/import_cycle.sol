@@ -0,0 +1,4 @@
+import "./auxiliary/cycle.sol";
+import {Missing} from "./auxiliary/cycle.sol"; //~ ERROR: not found in
+
+contract C {}
@@ -0,0 +1,8 @@
+//@ignore-host: windows
+//@compile-flags: --emit=import-graph --pretty-json -A unused-imports
+
+import "./auxiliary/udvt.sol";
+import "./import_graph.sol";
+import "./auxiliary/udvt.sol" as udvt;
+
+contract C {}
@@ -0,0 +1,10 @@
+contract A {}
+contract B is A {}
+contract C is A, B {}
+
+contract D is B, A {} //~ ERROR: linearization of inheritance graph impossible
+contract E is D {}
+
+interface I {}
+interface J is I {}
+contract F is J, I {} //~ ERROR: linearization of inheritance graph impossible
@@ -0,0 +1,8 @@
+contract C {
+    uint x;
+    uint x;
+    bool x;
+
+    uint y;
+    uint y;
+}
@@ -0,0 +1,16 @@
+contract C {
+    event E(uint a);
+    error Err();
+
+    function g(uint a, bool b) internal pure returns (uint) {
+        return b ? a : 0;
+    }
+
+    function f(uint x) public {
+        g(x); //~ ERROR: wrong argument count for function call: 1 arguments given but expected 2
+        g(true, true); //~ ERROR: type `bool` is not implicitly convertible to expected type `uint256`
+        g({a: x, b: x}); //~ ERROR: type `uint256` is not implicitly convertible to expected type `bool`
+        emit E(x, x); //~ ERROR: wrong argument count for event call: 2 arguments given but expected 1
+        revert Err(x); //~ ERROR: wrong argument count for error call: 1 arguments given but expected 0
+    }
+}
@@ -0,0 +1,7 @@
+contract C {
+    function f(uint x, int8 y, bool b) public pure returns (uint) {
+        uint a = x ? 1 : 2; //~ ERROR: type `uint256` is not implicitly convertible to expected type `bool`
+        b ? x : y; //~ ERROR: true expression's type `uint256` does not match false expression's type `int8`
+        return b ? a : 0;
+    }
+}
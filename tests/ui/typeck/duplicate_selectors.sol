@@ -6,3 +6,12 @@ contract D is C {
     //~^ ERROR: function signature hash collision
     function BlazingIt4490597615() public {}
 }
+
+contract E { //~ ERROR: function signature hash collision
+    uint public mintEfficientN2M_001Z5BWH;
+    function BlazingIt4490597615() public {}
+}
+
+contract H { function mintEfficientN2M_001Z5BWH() public {} }
+contract I { function BlazingIt4490597615() public {} }
+contract J is H, I {} //~ ERROR: function signature hash collision
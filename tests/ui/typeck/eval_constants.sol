@@ -0,0 +1,28 @@
+enum E {
+    A,
+    B,
+    C
+}
+
+uint constant HALF_TIMES_FOUR = (1 / 2) * 4;
+uint constant MAX = 2**256 - 1;
+uint constant ALL_ONES = ~uint256(0);
+uint constant TRUNCATED = uint8(MAX);
+uint constant VARIANT = uint(E.C);
+int8 constant MIN = type(int8).min;
+uint constant INT_DIV = HALF_TIMES_FOUR * 3 / 4;
+
+uint8 constant TOO_BIG = 256; //~ ERROR: evaluation of constant value failed
+int8 constant TOO_SMALL = -129; //~ ERROR: evaluation of constant value failed
+uint constant NEGATIVE = 1 - 2; //~ ERROR: evaluation of constant value failed
+uint constant FRACTION = 5 / 2; //~ ERROR: evaluation of constant value failed
+uint constant DIV_ZERO = 1 / (2 - 2); //~ ERROR: evaluation of constant value failed
+int8 constant OVERFLOW = MIN - 1; //~ ERROR: evaluation of constant value failed
+
+contract C {
+    uint[HALF_TIMES_FOUR] a;
+    uint[TRUNCATED] b;
+    uint[VARIANT + type(uint8).max] c;
+    uint[ALL_ONES == MAX ? 1 : 0] d;
+    uint[INT_DIV] e;
+}
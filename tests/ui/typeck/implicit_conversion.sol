@@ -0,0 +1,14 @@
+contract C {
+    mapping(address => uint) m;
+
+    function f(uint x, bool b) public view returns (uint) {
+        if (x) {} //~ ERROR: type `uint256` is not implicitly convertible to expected type `bool`
+        uint8 small = 256; //~ ERROR: type `int_const` is not implicitly convertible to expected type `uint8`
+        b = x; //~ ERROR: type `uint256` is not implicitly convertible to expected type `bool`
+        return m[b] + small; //~ ERROR: type `bool` is not implicitly convertible to expected type `address`
+    }
+
+    function g(uint x) public pure returns (bool) {
+        return x; //~ ERROR: type `uint256` is not implicitly convertible to expected type `bool`
+    }
+}
@@ -0,0 +1,21 @@
+contract A {
+    function f() public virtual {}
+}
+
+contract B is A {
+    /// @inheritdoc C
+    //~^ ERROR: which is not a base contract
+    function f() public virtual override {}
+
+    /// @inheritdoc A
+    //~^ ERROR: which has no matching function
+    function g() public virtual {}
+}
+
+// Each invalid tag is only reported once, even though the functions are inherited here.
+contract D is B {}
+
+contract E is B {
+    /// @inheritdoc B
+    function f() public override {}
+}
@@ -0,0 +1,11 @@
+contract C {
+    function f(uint x, int8 y, bool b, string memory s) public pure {
+        bool eq = s == s; //~ ERROR: operator `==` not compatible with types `string memory` and `string memory`
+        uint z = x + y; //~ ERROR: operator `+` not compatible with types `uint256` and `int8`
+        b += true; //~ ERROR: operator `+` not compatible with types `bool` and `bool`
+        bool n = !x; //~ ERROR: unary operator `!` cannot be applied to type `uint256`
+        eq;
+        z;
+        n;
+    }
+}
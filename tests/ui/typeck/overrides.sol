@@ -0,0 +1,42 @@
+interface I { function f() external; function g() external view returns (uint); }
+abstract contract A is I { function f() external virtual override; }
+contract B is A { function f() public override {} uint public override g; }
+contract C1 { function f() public virtual {} modifier m() virtual { _; } }
+contract C2 { function f() public virtual {} }
+contract C3 is C1, C2 { function f() public view override(C1, C2) {} modifier m() override { _; } }
+contract C4 is I { function f() external {} function g() external pure returns (uint) {} }
+
+contract NV { function f() public {} }
+contract NVD is NV { function f() public override {} } //~ ERROR: trying to override non-virtual function
+
+contract NVM { modifier m() { _; } }
+contract NVMD is NVM { modifier m() override { _; } } //~ ERROR: trying to override non-virtual modifier
+
+contract V1 { function f() public virtual {} }
+contract MissingOverride is V1 { function f() public {} } //~ ERROR: overriding function is missing `override` specifier
+contract NothingToOverride { function f() public override {} } //~ ERROR: function has override specified but does not override anything
+
+contract V2 { function f() public virtual {} }
+contract Visibility is V2 { function f() external override {} } //~ ERROR: overriding function visibility differs
+
+contract V3 { function f() public view virtual {} }
+contract Mutability is V3 { function f() public override {} } //~ ERROR: overriding function changes state mutability from `view` to `nonpayable`
+
+contract V4 { function f() public virtual returns (uint) {} }
+contract Returns is V4 { function f() public override returns (int) {} } //~ ERROR: overriding function return types differ
+
+contract V5 { function f() public virtual returns (uint) {} }
+contract PublicVar is V5 { uint public override f; } //~ ERROR: public state variables can only override functions with external visibility
+
+contract V6 { function f() public virtual {} }
+contract V7 { function f() public virtual {} }
+contract Ambiguous is V6, V7 {} //~ ERROR: derived contract must override function `f`
+contract MissingList is V6, V7 { function f() public override {} } //~ ERROR: function needs to specify overridden contracts `V6` and `V7`
+contract Empty {}
+contract WrongList is Empty, V6 { function f() public override(Empty, V6) {} } //~ ERROR: invalid contract specified in override list: `Empty`
+
+contract PrivateVar { uint internal override x; } //~ ERROR: override can only be used with public state variables
+contract Private { function f() private virtual {} } //~ ERROR: `virtual` and `private` cannot be used together
+library L { function f() internal virtual {} } //~ ERROR: library functions cannot be virtual
+function freeVirtual() virtual {} //~ ERROR: free functions cannot be virtual
+function freeOverride() override {} //~ ERROR: free functions cannot override
@@ -0,0 +1,13 @@
+contract C {
+    function f(uint x) public pure returns (uint, bool) {
+        return x; //~ ERROR: different number of arguments in return statement than in returns declaration
+    }
+
+    function g(uint x) public pure {
+        return x; //~ ERROR: different number of arguments in return statement than in returns declaration
+    }
+
+    function h(uint x) public pure returns (uint, bool) {
+        return (x, true);
+    }
+}
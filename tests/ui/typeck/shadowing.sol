@@ -0,0 +1,21 @@
+function helper() pure {}
+function free(uint helper) pure { helper; } //~ WARN: this declaration shadows an existing declaration
+
+contract B { uint internal inherited; }
+contract C is B { function f(uint inherited) public pure { inherited; } } //~ WARN: this declaration shadows an existing declaration
+
+contract D {
+    uint256 public total;
+    function f() public pure returns (uint256 total) { //~ WARN: this declaration shadows an existing declaration
+        total = 1;
+    }
+
+    uint256 state;
+    function g() public pure { uint256 state = 1; state; } //~ WARN: this declaration shadows an existing declaration
+
+    function h(uint256 block) public pure { //~ WARN: this declaration shadows a builtin symbol
+        uint256 msg = 1; //~ WARN: this declaration shadows a builtin symbol
+        block;
+        msg;
+    }
+}
@@ -0,0 +1,44 @@
+contract C {
+    error E();
+
+    function f() public pure returns (uint) {
+        return 1;
+        f(); //~ WARN: unreachable code
+    }
+
+    function g(bool b) public pure {
+        if (b) {
+            revert E();
+        } else {
+            revert("no");
+        }
+        g(b); //~ WARN: unreachable code
+    }
+
+    function h() public pure {
+        require(false, "never");
+        h(); //~ WARN: unreachable code
+    }
+
+    function i() public pure {
+        if (false) i(); //~ WARN: unreachable code
+        while (true) {}
+        i(); //~ WARN: unreachable code
+    }
+
+    function j(uint n) public pure returns (uint) {
+        for (uint k = 0; k < n; k++) {
+            if (k == 1) continue;
+            return k;
+        }
+        do {
+            if (n == 0) continue;
+            break;
+        } while (n > 1);
+        while (n > 0) {
+            break;
+            n--; //~ WARN: unreachable code
+        }
+        return n;
+    }
+}
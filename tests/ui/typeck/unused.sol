@@ -0,0 +1,49 @@
+import {MyUdvt} from "../resolve/auxiliary/udvt.sol"; //~ WARN: unused import
+import {MyUdvt as Used} from "../resolve/auxiliary/udvt.sol";
+import "../resolve/auxiliary/udvt.sol" as Namespace; //~ WARN: unused import
+import "../resolve/auxiliary/udvt.sol" as UsedNamespace;
+
+contract C {
+    Used u;
+    UsedNamespace.MyUdvt v;
+
+    function params(uint a, uint b, uint _c, uint) public pure returns (uint r, uint s) { //~ WARN: unused function parameter
+        r = a;
+    }
+
+    function locals() public pure returns (uint) {
+        uint x = 1; //~ WARN: unused local variable
+        uint y = 2;
+        uint _z = 3;
+        (uint w, ) = (4, 5); //~ WARN: unused local variable
+        for (uint i = 0; i < 10; i++) {
+            uint j; //~ WARN: unused local variable
+        }
+        return y;
+    }
+
+    function tryCatch() public {
+        try this.locals() returns (uint res) { //~ WARN: unused try/catch parameter
+        } catch Error(string memory reason) { //~ WARN: unused try/catch parameter
+        } catch (bytes memory) {}
+    }
+
+    modifier m(uint a) { //~ WARN: unused function parameter
+        _;
+    }
+
+    // Modifier arguments are not checked yet.
+    function withModifier(uint a) public m(a) {}
+
+    function withAssembly(uint a) public {
+        assembly {
+            pop(a)
+        }
+    }
+}
+
+abstract contract A {
+    // Parameters of functions without a body or with an empty body are not reported.
+    function unimplemented(uint a) public virtual;
+    function empty(uint a) public virtual {}
+}
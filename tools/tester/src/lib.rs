@@ -134,6 +134,8 @@ fn config(cmd: &'static Path, args: &ui_test::Args, mode: Mode) -> ui_test::Conf
     let stdout_filters: &[(&str, &str)] = &[
         //
         (&env!("CARGO_PKG_VERSION").replace(".", r"\."), "VERSION"),
+        // Erase the metadata in the IR, which contains the version.
+        (r#"hex\\"[0-9a-f]+\\""#, r#"hex\"METADATA\""#),
     ];
     for &(pattern, replacement) in stdout_filters {
         config.stdout_filter(pattern, replacement);